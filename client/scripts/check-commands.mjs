@@ -21,6 +21,26 @@ const expectedCommands = [
     id: "phpLsp.showServerVersion",
     title: "Show Language Server Version",
   },
+  {
+    id: "phpLsp.showIndexStats",
+    title: "Show Index Statistics",
+  },
+  {
+    id: "phpLsp.reindexWorkspace",
+    title: "Reindex Workspace",
+  },
+  {
+    id: "phpLsp.dumpIndex",
+    title: "Dump Index to File",
+  },
+  {
+    id: "phpLsp.findUnusedSymbols",
+    title: "Find Unused Symbols",
+  },
+  {
+    id: "phpLsp.newClass",
+    title: "Insert Class Scaffold",
+  },
 ];
 
 const contributedCommands = new Map(
@@ -16,6 +16,7 @@ import {
   MarkdownString,
   OutputChannel,
   ThemeColor,
+  Uri,
 } from "vscode";
 import {
   CloseAction,
@@ -30,6 +31,7 @@ import {
 let client: LanguageClient | undefined;
 let statusController: PhpLspStatusController | undefined;
 let indexingStatusSubscription: Disposable | undefined;
+let serverStatusSubscription: Disposable | undefined;
 let outputChannel: OutputChannel | undefined;
 let lastBinaryResolutionError: string | undefined;
 let lastStartError: string | undefined;
@@ -45,6 +47,7 @@ type IndexingPhase =
   | "loadingStubs"
   | "stubsLoaded"
   | "indexing"
+  | "analyzing"
   | "ready"
   | "error";
 
@@ -61,6 +64,13 @@ interface IndexingStatus {
   lastUpdatedAt?: number;
 }
 
+/** Coarse `$/php-lsp/status` notification; indexing details still arrive via `phpLsp/indexingStatus`. */
+interface ServerStatus {
+  state: "idle" | "indexing" | "analyzing" | "error" | string;
+  message?: string;
+  percentage?: number;
+}
+
 interface ExtensionSnapshot {
   extensionVersion: string;
   serverName: string;
@@ -78,14 +88,36 @@ interface ExtensionSnapshot {
   diagnosticsMode: string;
   composerEnabled: boolean;
   indexVendor: boolean;
+  vendorIndexing: string;
   phpstanEnabled: boolean;
-  psalmEnabled: boolean;
+  psalmEnabled: boolean | "auto";
+  phpcsEnabled: boolean;
   formattingProvider: string;
   logLevel: string;
   includePaths: string[];
   excludePaths: string[];
 }
 
+/** Result of the `php-lsp/capabilities` request; absent on older servers. */
+interface ServerCapabilityReport {
+  schemaVersion: number;
+  features: {
+    diagnostics: string;
+    diagnosticsBaseline: boolean;
+    formatting: string;
+    phpstan: boolean;
+    psalm: { enabled: "on" | "off" | "auto"; active: boolean };
+    phpcs?: boolean;
+    analyzerCodeActions: boolean;
+    composer: boolean;
+    vendorIndex: boolean;
+    vendorIndexing?: "off" | "lazy" | "full";
+  };
+  frameworkPlugins: string[];
+  stubs: { path: string | null; extensions: string[]; version: string | null; loadedFiles: number };
+  index: { backend: string; files: number };
+}
+
 interface StatusQuickPickItem extends QuickPickItem {
   action?: "version" | "restart" | "clearCache" | "output" | "settings";
 }
@@ -129,9 +161,19 @@ class PhpLspStatusController implements Disposable {
     this.render();
   }
 
+  /** Surface external analyzer runs; indexing and errors come from the detailed indexing status. */
+  updateServerStatus(status: ServerStatus): void {
+    if (status.state === "analyzing") {
+      this.update({ phase: "analyzing", message: status.message });
+    } else if (status.state === "idle" && this.status.phase === "analyzing") {
+      this.update({ phase: "ready", message: status.message });
+    }
+  }
+
   async showPopup(): Promise<void> {
     const snapshot = this.snapshotProvider();
     const status = this.status;
+    const report = await fetchServerCapabilities();
     const items: StatusQuickPickItem[] = [
       {
         label: `${phaseIcon(status.phase)} ${phaseTitle(status.phase)}`,
@@ -151,23 +193,32 @@ class PhpLspStatusController implements Disposable {
       {
         label: "$(database) Stubs",
         description: formatCount(status.stubFiles),
-        detail: snapshot.stubsPath ?? "Bundled stubs directory was not found",
+        detail: stubsDetail(snapshot, report),
       },
       {
         label: "$(settings-gear) Diagnostics",
-        description: snapshot.diagnosticsMode,
-        detail: `PHP ${snapshot.phpVersion}; Composer: ${onOff(snapshot.composerEnabled)}; Vendor lazy index: ${onOff(snapshot.indexVendor)}`,
+        description: report?.features.diagnostics ?? snapshot.diagnosticsMode,
+        detail: `PHP ${snapshot.phpVersion}; Composer: ${onOff(snapshot.composerEnabled)}; Vendor index: ${report?.features.vendorIndexing ?? snapshot.vendorIndexing}`,
       },
       {
         label: "$(beaker) External analyzers",
-        description: analyzerSummary(snapshot),
-        detail: `PHPStan: ${onOff(snapshot.phpstanEnabled)}; Psalm: ${onOff(snapshot.psalmEnabled)}`,
+        description: report ? reportedAnalyzerSummary(report) : analyzerSummary(snapshot),
+        detail: `PHPStan: ${onOff(snapshot.phpstanEnabled)}; Psalm: ${onOff(snapshot.psalmEnabled)}; phpcs: ${onOff(snapshot.phpcsEnabled)}`,
       },
       {
         label: "$(tools) Formatter",
-        description: snapshot.formattingProvider,
-        detail: formatterDetail(snapshot.formattingProvider),
+        description: report?.features.formatting ?? snapshot.formattingProvider,
+        detail: formatterDetail(report?.features.formatting ?? snapshot.formattingProvider),
       },
+      ...(report
+        ? [
+          {
+            label: "$(extensions) Framework plugins",
+            description: `${report.frameworkPlugins.length}`,
+            detail: report.frameworkPlugins.join("; ") || "No framework plugins loaded",
+          },
+        ]
+        : []),
       {
         label: "$(output) Log level",
         description: snapshot.logLevel,
@@ -255,6 +306,9 @@ function statusText(status: IndexingStatus): string {
   if (status.phase === "discovering" || status.phase === "loadingStubs") {
     return "$(sync~spin) PHP LSP";
   }
+  if (status.phase === "analyzing") {
+    return "$(loading~spin) PHP LSP";
+  }
   if (status.phase === "error") {
     return "$(error) PHP LSP";
   }
@@ -285,6 +339,9 @@ function phaseIcon(phase: string): string {
   if (phase === "indexing" || phase === "discovering" || phase === "loadingStubs") {
     return "$(sync~spin)";
   }
+  if (phase === "analyzing") {
+    return "$(loading~spin)";
+  }
   if (phase === "error") {
     return "$(error)";
   }
@@ -303,6 +360,8 @@ function phaseTitle(phase: string): string {
       return "Stubs loaded";
     case "indexing":
       return "Indexing";
+    case "analyzing":
+      return "Running analyzers";
     case "ready":
       return "Ready";
     case "error":
@@ -357,18 +416,51 @@ function folderCountLabel(count: number): string {
   return `${count} folders`;
 }
 
-function onOff(enabled: boolean): string {
-  return enabled ? "on" : "off";
+function onOff(enabled: boolean | "auto"): string {
+  return enabled === "auto" ? "auto" : enabled ? "on" : "off";
 }
 
 function analyzerSummary(snapshot: ExtensionSnapshot): string {
   const enabled = [
     snapshot.phpstanEnabled ? "PHPStan" : undefined,
-    snapshot.psalmEnabled ? "Psalm" : undefined,
+    snapshot.psalmEnabled === "auto" ? "Psalm (auto)" : snapshot.psalmEnabled ? "Psalm" : undefined,
+    snapshot.phpcsEnabled ? "phpcs" : undefined,
   ].filter(Boolean);
   return enabled.length > 0 ? enabled.join(", ") : "off";
 }
 
+function reportedAnalyzerSummary(report: ServerCapabilityReport): string {
+  const psalm = report.features.psalm;
+  const enabled = [
+    report.features.phpstan ? "PHPStan" : undefined,
+    psalm.active ? "Psalm" : psalm.enabled === "auto" ? "Psalm (auto, no psalm.xml)" : undefined,
+    report.features.phpcs ? "phpcs" : undefined,
+  ].filter(Boolean);
+  return enabled.length > 0 ? enabled.join(", ") : "off";
+}
+
+function stubsDetail(snapshot: ExtensionSnapshot, report: ServerCapabilityReport | undefined): string {
+  if (!report) {
+    return snapshot.stubsPath ?? "Bundled stubs directory was not found";
+  }
+  if (!report.stubs.path) {
+    return "No usable stubs directory was found";
+  }
+  return `${report.stubs.path} (${report.stubs.extensions.length} extensions; version ${report.stubs.version ?? "unknown"})`;
+}
+
+async function fetchServerCapabilities(): Promise<ServerCapabilityReport | undefined> {
+  if (!client?.isRunning()) {
+    return undefined;
+  }
+  try {
+    return await client.sendRequest<ServerCapabilityReport>("php-lsp/capabilities");
+  } catch (error: unknown) {
+    lifecycleLog(`Capability report unavailable: ${errorMessage(error)}`);
+    return undefined;
+  }
+}
+
 function formatterDetail(provider: string): string {
   if (provider === "none") {
     return "Document formatting is disabled";
@@ -376,6 +468,9 @@ function formatterDetail(provider: string): string {
   if (provider === "auto") {
     return "Auto-detects Laravel Pint, php-cs-fixer, or phpcbf from Composer metadata";
   }
+  if (provider === "built-in") {
+    return "Built-in indentation formatter";
+  }
   return "External formatter is configured";
 }
 
@@ -403,8 +498,13 @@ function getExtensionSnapshot(context: ExtensionContext): ExtensionSnapshot {
     diagnosticsMode: config.get<string>("diagnostics.mode", "basic-semantic"),
     composerEnabled: config.get<boolean>("composer.enabled", true),
     indexVendor: config.get<boolean>("indexVendor", true),
+    vendorIndexing: config.get<string>(
+      "vendorIndexing",
+      config.get<boolean>("indexVendor", true) ? "lazy" : "off",
+    ),
     phpstanEnabled: config.get<boolean>("phpstan.enabled", false),
-    psalmEnabled: config.get<boolean>("psalm.enabled", false),
+    psalmEnabled: config.get<boolean | "auto">("psalm.enabled", false),
+    phpcsEnabled: config.get<boolean>("phpcs.enabled", false),
     formattingProvider: config.get<string>("formatting.provider", "auto"),
     logLevel: config.get<string>("logLevel", "info"),
     includePaths: config.get<string[]>("includePaths", []),
@@ -474,6 +574,145 @@ async function showServerVersion(snapshot: ExtensionSnapshot): Promise<void> {
   }
 }
 
+/**
+ * Serves read-only `phar://` documents (classes inside PHAR archives such as
+ * phpunit.phar) so go-to-definition can open them.
+ */
+async function providePharContent(uri: Uri): Promise<string> {
+  if (!client?.isRunning()) {
+    return "";
+  }
+  const result = await client.sendRequest<{ text: string } | null>("php-lsp/pharContent", {
+    uri: uri.toString(),
+  });
+  return result?.text ?? "";
+}
+
+async function showIndexStats(): Promise<void> {
+  if (!client?.isRunning()) {
+    void window.showWarningMessage("PHP Language Server is not running.");
+    return;
+  }
+  let stats: unknown;
+  try {
+    stats = await client.sendRequest("php-lsp/indexStats");
+  } catch (error: unknown) {
+    void window.showErrorMessage(`Index statistics unavailable: ${errorMessage(error)}`);
+    return;
+  }
+
+  const report = JSON.stringify(stats, null, 2);
+  const channel = getOutputChannel();
+  channel.appendLine(`[${new Date().toISOString()}] Index statistics:`);
+  channel.appendLine(report);
+  channel.show(true);
+  const selected = await window.showInformationMessage(
+    "PHP index statistics were written to the output channel.",
+    "Copy JSON",
+  );
+  if (selected === "Copy JSON") {
+    await env.clipboard.writeText(report);
+  }
+}
+
+async function reindexWorkspace(): Promise<void> {
+  if (!client?.isRunning()) {
+    void window.showWarningMessage("PHP Language Server is not running.");
+    return;
+  }
+  try {
+    await client.sendRequest("workspace/executeCommand", { command: "php-lsp.reindex", arguments: [] });
+    lifecycleLog("Requested a full workspace reindex");
+  } catch (error: unknown) {
+    void window.showErrorMessage(`Workspace reindex failed: ${errorMessage(error)}`);
+  }
+}
+
+async function dumpIndex(): Promise<void> {
+  if (!client?.isRunning()) {
+    void window.showWarningMessage("PHP Language Server is not running.");
+    return;
+  }
+  const target = await window.showSaveDialog({
+    title: "Dump PHP Index",
+    saveLabel: "Dump Index",
+    filters: { JSON: ["json"], "SQLite script": ["sql"] },
+  });
+  if (!target) {
+    return;
+  }
+  try {
+    const result = await client.sendRequest<{ path: string; files: number; symbols: number } | null>(
+      "workspace/executeCommand",
+      { command: "php-lsp.dumpIndex", arguments: [{ path: target.fsPath, references: true }] },
+    );
+    lifecycleLog(`Dumped index to ${result?.path ?? target.fsPath}`);
+    void window.showInformationMessage(
+      `PHP index dumped: ${result?.files ?? 0} files, ${result?.symbols ?? 0} symbols.`,
+    );
+  } catch (error: unknown) {
+    void window.showErrorMessage(`Index dump failed: ${errorMessage(error)}`);
+  }
+}
+
+interface UnusedSymbolsReport {
+  symbols: number;
+  files: {
+    uri: string;
+    diagnostics: { range: { start: { line: number; character: number } }; message: string }[];
+  }[];
+}
+
+async function findUnusedSymbols(): Promise<void> {
+  if (!client?.isRunning()) {
+    void window.showWarningMessage("PHP Language Server is not running.");
+    return;
+  }
+  let report: UnusedSymbolsReport | null;
+  try {
+    report = await client.sendRequest<UnusedSymbolsReport | null>("workspace/executeCommand", {
+      command: "php-lsp.findUnusedSymbols",
+      arguments: [],
+    });
+  } catch (error: unknown) {
+    void window.showErrorMessage(`Unused symbol search failed: ${errorMessage(error)}`);
+    return;
+  }
+
+  const channel = getOutputChannel();
+  channel.appendLine(`[${new Date().toISOString()}] Unused public symbols: ${report?.symbols ?? 0}`);
+  for (const file of report?.files ?? []) {
+    for (const diagnostic of file.diagnostics) {
+      const { line, character } = diagnostic.range.start;
+      channel.appendLine(`${file.uri}:${line + 1}:${character + 1} ${diagnostic.message}`);
+    }
+  }
+  channel.show(true);
+}
+
+async function newClass(): Promise<void> {
+  if (!client?.isRunning()) {
+    void window.showWarningMessage("PHP Language Server is not running.");
+    return;
+  }
+  const document = window.activeTextEditor?.document;
+  if (!document || document.languageId !== "php") {
+    void window.showWarningMessage("Open an empty PHP file to insert a class scaffold.");
+    return;
+  }
+  try {
+    const result = await client.sendRequest<{ applied: boolean } | null>("workspace/executeCommand", {
+      command: "php-lsp.newClass",
+      arguments: [document.uri.toString()],
+    });
+    if (!result?.applied) {
+      void window.showInformationMessage("Class scaffolds are only inserted into empty PHP files.");
+    }
+  } catch (error: unknown) {
+    void window.showErrorMessage(`Class scaffold failed: ${errorMessage(error)}`);
+  }
+}
+
 function getOutputChannel(): OutputChannel {
   outputChannel ??= window.createOutputChannel("PHP Language Server");
   return outputChannel;
@@ -860,11 +1099,25 @@ function buildInitializationOptions(config: ReturnType<typeof workspace.getConfi
     "diagnosticsPartialAnalysisDiagnostic",
     true,
   );
+  setIfConfigured(options, config, "diagnostics.debounceMs", "diagnosticsDebounceMs", 180);
+  setIfConfigured(
+    options,
+    config,
+    "diagnostics.semanticOnSave",
+    "diagnosticsSemanticOnSave",
+    false,
+  );
+  setIfConfigured(options, config, "diagnostics.baseline", "diagnosticsBaseline", "");
   setIfConfigured(options, config, "composer.enabled", "composerEnabled", true);
   setIfConfigured(options, config, "indexVendor", "indexVendor", true);
+  setIfConfigured(options, config, "vendorIndexing", "vendorIndexing", "lazy");
   setIfConfigured(options, config, "includePaths", "includePaths", []);
   setIfConfigured(options, config, "excludePaths", "excludePaths", []);
+  setIfConfigured(options, config, "respectGitignore", "respectGitignore", false);
+  setIfConfigured(options, config, "fileExtensions", "fileExtensions", ["php"]);
   setIfConfigured(options, config, "stubs.extensions", "stubExtensions", []);
+  setIfConfigured(options, config, "stubs.enable", "stubExtensionsEnable", []);
+  setIfConfigured(options, config, "stubs.disable", "stubExtensionsDisable", []);
   setIfConfigured(options, config, "logLevel", "logLevel", "info");
   setIfConfigured(options, config, "allowProjectCommands", "allowProjectCommands", false);
   setIfConfigured(options, config, "formatting.provider", "formattingProvider", "auto");
@@ -879,7 +1132,7 @@ function buildInitializationOptions(config: ReturnType<typeof workspace.getConfi
     "vendor/bin/phpstan analyse --error-format=json --no-progress --no-interaction {file}",
   );
   setIfConfigured(options, config, "phpstan.timeoutMs", "phpstanTimeoutMs", 30000);
-  setIfConfigured(options, config, "psalm.enabled", "psalmEnabled", false);
+  setIfConfigured<boolean | "auto">(options, config, "psalm.enabled", "psalmEnabled", false);
   setIfConfigured(
     options,
     config,
@@ -888,6 +1141,13 @@ function buildInitializationOptions(config: ReturnType<typeof workspace.getConfi
     "vendor/bin/psalm --output-format=json --no-progress {file}",
   );
   setIfConfigured(options, config, "psalm.timeoutMs", "psalmTimeoutMs", 30000);
+  setIfConfigured(options, config, "phpcs.enabled", "phpcsEnabled", false);
+  setIfConfigured(options, config, "phpcs.command", "phpcsCommand", "vendor/bin/phpcs --report=json -q {file}");
+  setIfConfigured(options, config, "phpcs.fixCommand", "phpcsFixCommand", "vendor/bin/phpcbf -q {file}");
+  setIfConfigured(options, config, "phpcs.timeoutMs", "phpcsTimeoutMs", 30000);
+  setIfConfigured(options, config, "runtimeStubs.enabled", "runtimeStubsEnabled", false);
+  setIfConfigured(options, config, "runtimeStubs.phpExecutable", "runtimeStubsPhpExecutable", "php");
+  setIfConfigured(options, config, "runtimeStubs.timeoutMs", "runtimeStubsTimeoutMs", 10000);
   setIfConfigured(
     options,
     config,
@@ -895,6 +1155,11 @@ function buildInitializationOptions(config: ReturnType<typeof workspace.getConfi
     "analyzerCodeActionsEnabled",
     false,
   );
+  setIfConfigured(options, config, "completion.snippets", "completionSnippets", true);
+  setIfConfigured(options, config, "completion.callSnippets", "completionCallSnippets", false);
+  setIfConfigured(options, config, "completion.maxItems", "completionMaxItems", 100);
+  setIfConfigured(options, config, "newFiles.scaffold", "newFilesScaffold", true);
+  setIfConfigured(options, config, "newFiles.strictTypes", "newFilesStrictTypes", true);
 
   if (stubsPath) {
     options.bundledStubsPath = stubsPath;
@@ -903,6 +1168,18 @@ function buildInitializationOptions(config: ReturnType<typeof workspace.getConfi
   return options;
 }
 
+/** Watcher glob for the configured PHP source extensions. */
+function phpSourceGlob(config: ReturnType<typeof workspace.getConfiguration>): string {
+  const extensions = config
+    .get<string[]>("fileExtensions", ["php"])
+    .map((extension) => extension.trim().replace(/^\./, ""))
+    .filter((extension) => extension.length > 0);
+  if (extensions.length === 0) {
+    return "**/*.php";
+  }
+  return extensions.length === 1 ? `**/*.${extensions[0]}` : `**/*.{${extensions.join(",")}}`;
+}
+
 function createLanguageClient(context: ExtensionContext, binary: ServerBinaryResolution): LanguageClient {
   const config = workspace.getConfiguration("phpLsp");
   const stubsPath = getStubsPath(context);
@@ -933,6 +1210,8 @@ function createLanguageClient(context: ExtensionContext, binary: ServerBinaryRes
     documentSelector: [
       { scheme: "file", language: "php" },
       { scheme: "untitled", language: "php" },
+      { scheme: "phar", language: "php" },
+      { scheme: "vscode-vfs", language: "php" },
       { scheme: "file", language: "blade" },
       { scheme: "untitled", language: "blade" },
       { scheme: "file", language: "twig" },
@@ -940,7 +1219,7 @@ function createLanguageClient(context: ExtensionContext, binary: ServerBinaryRes
     ],
     synchronize: {
       fileEvents: [
-        workspace.createFileSystemWatcher("**/*.php"),
+        workspace.createFileSystemWatcher(phpSourceGlob(config)),
         workspace.createFileSystemWatcher("**/*.twig"),
         workspace.createFileSystemWatcher("**/composer.json"),
         workspace.createFileSystemWatcher("**/composer.lock"),
@@ -977,6 +1256,8 @@ async function notifyServerConfigurationChanged(context: ExtensionContext): Prom
 async function stopLanguageClient(reason: string): Promise<void> {
   indexingStatusSubscription?.dispose();
   indexingStatusSubscription = undefined;
+  serverStatusSubscription?.dispose();
+  serverStatusSubscription = undefined;
 
   const currentClient = client;
   client = undefined;
@@ -1025,6 +1306,10 @@ async function startLanguageClient(context: ExtensionContext, reason: string): P
       "phpLsp/indexingStatus",
       (status: IndexingStatus) => statusController?.update(status),
     );
+    serverStatusSubscription = nextClient.onNotification(
+      "$/php-lsp/status",
+      (status: ServerStatus) => statusController?.updateServerStatus(status),
+    );
 
     await nextClient.start();
     lastStartError = undefined;
@@ -1036,6 +1321,8 @@ async function startLanguageClient(context: ExtensionContext, reason: string): P
     client = undefined;
     indexingStatusSubscription?.dispose();
     indexingStatusSubscription = undefined;
+    serverStatusSubscription?.dispose();
+    serverStatusSubscription = undefined;
     statusController?.update({
       phase: "error",
       message,
@@ -1160,6 +1447,35 @@ export function activate(context: ExtensionContext): void {
     async () => showServerVersion(getExtensionSnapshot(context)),
   );
 
+  const showIndexStatsCommand = commands.registerCommand(
+    "phpLsp.showIndexStats",
+    async () => showIndexStats(),
+  );
+
+  const reindexWorkspaceCommand = commands.registerCommand(
+    "phpLsp.reindexWorkspace",
+    async () => reindexWorkspace(),
+  );
+
+  const dumpIndexCommand = commands.registerCommand(
+    "phpLsp.dumpIndex",
+    async () => dumpIndex(),
+  );
+
+  const findUnusedSymbolsCommand = commands.registerCommand(
+    "phpLsp.findUnusedSymbols",
+    async () => findUnusedSymbols(),
+  );
+
+  const newClassCommand = commands.registerCommand(
+    "phpLsp.newClass",
+    async () => newClass(),
+  );
+
+  const pharContentProvider = workspace.registerTextDocumentContentProvider("phar", {
+    provideTextDocumentContent: async (uri) => providePharContent(uri),
+  });
+
   const enableConfigSubscription = workspace.onDidChangeConfiguration(async (event) => {
     if (!event.affectsConfiguration("phpLsp")) {
       return;
@@ -1193,6 +1509,12 @@ export function activate(context: ExtensionContext): void {
     clearCacheCommand,
     showStatusCommand,
     showServerVersionCommand,
+    showIndexStatsCommand,
+    reindexWorkspaceCommand,
+    dumpIndexCommand,
+    findUnusedSymbolsCommand,
+    newClassCommand,
+    pharContentProvider,
     enableConfigSubscription,
   );
 
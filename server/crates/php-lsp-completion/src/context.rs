@@ -3,6 +3,8 @@
 //! Determines what kind of completion is appropriate based on
 //! the cursor position in the CST and surrounding text.
 
+use crate::strings::{scan_strings, StringPosition};
+use php_lsp_parser::resolve::local_variable_names_at_position;
 use php_lsp_types::FileSymbols;
 use tree_sitter::{Node, Point, Tree};
 
@@ -12,6 +14,51 @@ pub enum MemberAccessMode {
     Write,
 }
 
+/// Syntactic position of a free-context word, used to pick which keywords
+/// and symbol kinds are offered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeywordPosition {
+    /// Start of a statement at top level or inside a body.
+    #[default]
+    Statement,
+    /// Inside an expression: conditions, arguments, right-hand sides.
+    Expression,
+    /// Right after `new`.
+    Instantiation,
+    /// In a class-like header, before the body opens.
+    ClassHeader,
+    /// A class name is required: after `extends`, `implements`,
+    /// `instanceof`, or inside `catch (`.
+    ClassReference,
+    /// Member declaration position in a class-like body.
+    ClassBody {
+        /// The body belongs to an enum, so `case` is valid.
+        in_enum: bool,
+        /// A modifier precedes the word, so property types are valid too.
+        after_modifier: bool,
+    },
+    /// Parameter, property, or return type position.
+    Type {
+        /// Return type position, where `void` and `never` are valid.
+        return_type: bool,
+    },
+}
+
+/// What is being completed inside a `/** */` doc comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhpDocPosition {
+    /// Tag name right after `@`.
+    Tag,
+    /// Type after a typed tag such as `@param`, `@return` or `@var`.
+    Type,
+    /// Variable after `@param <type>`.
+    ParamName {
+        /// Parameters of the documented function without a `@param` yet,
+        /// without the leading `$`.
+        parameters: Vec<String>,
+    },
+}
+
 /// The context in which completion was triggered.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CompletionContext {
@@ -51,6 +98,9 @@ pub enum CompletionContext {
     Variable {
         /// Partial variable name typed so far (without $).
         prefix: String,
+        /// Variables visible at the cursor in the current function scope,
+        /// with `$`, `$this` first where it is bound.
+        in_scope: Vec<String>,
     },
 
     /// After `\` or in namespace context: namespace/class completion.
@@ -63,6 +113,22 @@ pub enum CompletionContext {
     Free {
         /// The partial word typed.
         prefix: String,
+        /// Where the word sits syntactically.
+        position: KeywordPosition,
+    },
+
+    /// Attribute name inside `#[...]`: only attribute classes apply.
+    Attribute {
+        /// The partial, possibly qualified, class name typed.
+        prefix: String,
+    },
+
+    /// Inside a `/** */` doc comment.
+    PhpDoc {
+        /// The partial tag name, type name or variable name (without `@`/`$`).
+        prefix: String,
+        /// What the prefix completes to.
+        position: PhpDocPosition,
     },
 
     /// Inside a use statement.
@@ -86,6 +152,22 @@ pub fn detect_context_at_byte_col(
     line: u32,
     byte_col: u32,
     file_symbols: &FileSymbols,
+) -> CompletionContext {
+    match detect_context_kind(tree, source, line, byte_col, file_symbols) {
+        CompletionContext::Variable { prefix, .. } => CompletionContext::Variable {
+            in_scope: local_variable_names_at_position(tree, source, line, byte_col),
+            prefix,
+        },
+        other => other,
+    }
+}
+
+fn detect_context_kind(
+    tree: &Tree,
+    source: &str,
+    line: u32,
+    byte_col: u32,
+    file_symbols: &FileSymbols,
 ) -> CompletionContext {
     let (line_start, line_end) = match line_byte_bounds_without_newline(source, line) {
         Some(bounds) => bounds,
@@ -109,8 +191,45 @@ pub fn detect_context_at_byte_col(
     let text_before = &line_text[..cursor_col];
     let text_after = &line_text[cursor_col..];
 
+    let scan = scan_strings(&source[..cursor_offset]);
+
+    // Template text between `?>` and `<?php` is not PHP code.
+    if scan.in_inline_html() {
+        return CompletionContext::None;
+    }
+
+    // Inside a doc comment only tags, types and parameter names complete.
+    if let Some(comment_start) = scan.doc_comment_start(source) {
+        return check_phpdoc_context(&root, source, comment_start, cursor_offset)
+            .unwrap_or(CompletionContext::None);
+    }
+
+    // Inside string literals only array keys and interpolation complete.
+    match scan.position() {
+        StringPosition::Code => {}
+        StringPosition::Literal => {
+            return check_array_key_access(text_before).unwrap_or(CompletionContext::None);
+        }
+        StringPosition::Interpolating => {
+            return check_array_key_access(text_before)
+                .or_else(|| check_simple_interpolation(text_before))
+                .unwrap_or(CompletionContext::None);
+        }
+        StringPosition::DollarBrace { start } => {
+            let name = &source[start..cursor_offset];
+            return if name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                CompletionContext::Variable {
+                    prefix: name.to_string(),
+                    in_scope: Vec::new(),
+                }
+            } else {
+                CompletionContext::None
+            };
+        }
+    }
+
     // Check for `->` member access
-    if let Some(ctx) = check_member_access(text_before, text_after, &node, source) {
+    if let Some(ctx) = check_member_access(text_before, text_after, &node, source, line_start) {
         return ctx;
     }
 
@@ -129,6 +248,11 @@ pub fn detect_context_at_byte_col(
         return ctx;
     }
 
+    // Check for an attribute name inside `#[...]`
+    if let Some(ctx) = check_attribute_context(&node, &source[..cursor_offset]) {
+        return ctx;
+    }
+
     // Check for `use` statement context
     if let Some(ctx) = check_use_context(&node, text_before, source) {
         return ctx;
@@ -141,19 +265,199 @@ pub fn detect_context_at_byte_col(
 
     // Default: free context with the current word as prefix
     let prefix = extract_word_before_cursor(text_before);
+    let text_before_word = &text_before[..text_before.len() - prefix.len()];
+    let source_before_word = &source[..cursor_offset - prefix.len()];
+    let position = keyword_position(&node, text_before_word, source_before_word);
 
     // Don't complete on empty prefix unless triggered by a character
     if prefix.is_empty() {
         // Check if we're in a type hint position
-        if is_type_hint_position(&node, source, cursor_offset) {
+        if matches!(position, KeywordPosition::Type { .. })
+            || is_type_hint_position(&node, source, cursor_offset)
+        {
+            let position = match position {
+                KeywordPosition::Type { .. } => position,
+                _ => KeywordPosition::Type { return_type: false },
+            };
             return CompletionContext::Free {
                 prefix: String::new(),
+                position,
             };
         }
         return CompletionContext::None;
     }
 
-    CompletionContext::Free { prefix }
+    CompletionContext::Free { prefix, position }
+}
+
+const CLASS_MEMBER_MODIFIERS: &[&str] = &[
+    "public",
+    "protected",
+    "private",
+    "static",
+    "readonly",
+    "abstract",
+    "final",
+    "var",
+];
+
+/// Classify the position of the word that starts after `source_before_word`.
+///
+/// The CST decides class bodies, parameter lists and type nodes; the text
+/// right before the word decides between statement and expression starts,
+/// since incomplete code often parses as an `ERROR` node.
+fn keyword_position(
+    node: &Node,
+    line_before_word: &str,
+    source_before_word: &str,
+) -> KeywordPosition {
+    let before = source_before_word.trim_end();
+    let last_word = last_word(before);
+
+    if last_word.eq_ignore_ascii_case("new") {
+        return KeywordPosition::Instantiation;
+    }
+    if ["extends", "implements", "instanceof", "insteadof"]
+        .iter()
+        .any(|keyword| last_word.eq_ignore_ascii_case(keyword))
+        || catch_type_position(line_before_word)
+    {
+        return KeywordPosition::ClassReference;
+    }
+    if let Some(position) = class_header_position(line_before_word) {
+        return position;
+    }
+    if function_return_type_position(line_before_word) {
+        return KeywordPosition::Type { return_type: true };
+    }
+
+    let mut current = Some(*node);
+    while let Some(n) = current {
+        match n.kind() {
+            "named_type" | "optional_type" | "union_type" | "intersection_type"
+            | "primitive_type" => {
+                let return_type = n.parent().is_some_and(|parent| {
+                    matches!(
+                        parent.kind(),
+                        "function_definition"
+                            | "method_declaration"
+                            | "anonymous_function"
+                            | "arrow_function"
+                    )
+                });
+                if n.parent()
+                    .is_some_and(|parent| parent.kind() == "property_declaration")
+                {
+                    break;
+                }
+                return KeywordPosition::Type { return_type };
+            }
+            "formal_parameters" => {
+                return if before.ends_with(['(', ',']) || is_member_modifier(last_word) {
+                    KeywordPosition::Type { return_type: false }
+                } else {
+                    KeywordPosition::Expression
+                };
+            }
+            "declaration_list" | "enum_declaration_list" => {
+                if before.ends_with('=') {
+                    return KeywordPosition::Expression;
+                }
+                return KeywordPosition::ClassBody {
+                    in_enum: n.kind() == "enum_declaration_list",
+                    after_modifier: is_member_modifier(last_word),
+                };
+            }
+            "compound_statement"
+            | "colon_block"
+            | "program"
+            | "arguments"
+            | "array_creation_expression" => {
+                break;
+            }
+            _ => {}
+        }
+        current = n.parent();
+    }
+
+    if starts_statement(before) {
+        KeywordPosition::Statement
+    } else {
+        KeywordPosition::Expression
+    }
+}
+
+fn is_member_modifier(word: &str) -> bool {
+    CLASS_MEMBER_MODIFIERS
+        .iter()
+        .any(|modifier| word.eq_ignore_ascii_case(modifier))
+}
+
+fn last_word(text: &str) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+        .map(|(idx, c)| idx + c.len_utf8())
+        .unwrap_or(0);
+    &text[start..]
+}
+
+/// Whether a word after `before` begins a new statement.
+fn starts_statement(before: &str) -> bool {
+    if before.is_empty() || before.ends_with("<?php") || before.ends_with("<?") {
+        return true;
+    }
+    if before.ends_with([';', '{', '}']) {
+        return true;
+    }
+    // `case 1:` / `default:` / alternative syntax `if (...):`, but not a
+    // ternary `? a :` or a scope resolution `::`.
+    if before.ends_with(':') && !before.ends_with("::") {
+        let line = before.rsplit('\n').next().unwrap_or(before).trim_start();
+        return line.starts_with("case ")
+            || line.starts_with("default")
+            || line.ends_with("):")
+            || line == "else:";
+    }
+    false
+}
+
+/// `class Foo ` / `final class Foo extends Bar ` before the body opens.
+fn class_header_position(line_before_word: &str) -> Option<KeywordPosition> {
+    if line_before_word.contains(['{', '(', ';', '=']) {
+        return None;
+    }
+    let mut words = line_before_word.split_whitespace();
+    let declares = words.any(|word| matches!(word, "class" | "interface" | "enum" | "trait"));
+    // The header needs at least a name after the declaration keyword.
+    (declares && words.next().is_some()).then_some(KeywordPosition::ClassHeader)
+}
+
+fn catch_type_position(line_before_word: &str) -> bool {
+    let Some(catch_start) = line_before_word.rfind("catch") else {
+        return false;
+    };
+    let rest = line_before_word[catch_start + "catch".len()..].trim_start();
+    rest.strip_prefix('(').is_some_and(|types| {
+        !types.contains(['$', ')']) && (types.trim().is_empty() || types.trim_end().ends_with('|'))
+    })
+}
+
+/// `function name(...): ` or `fn (...): ?` on the current line.
+fn function_return_type_position(line_before_word: &str) -> bool {
+    let trimmed = line_before_word
+        .trim_end()
+        .trim_end_matches(['?', '|', '&'])
+        .trim_end();
+    let Some(header) = trimmed.strip_suffix(':') else {
+        return false;
+    };
+    let header = header.trim_end();
+    header.ends_with(')')
+        && header
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .any(|word| word == "function" || word == "fn")
 }
 
 fn line_byte_bounds_without_newline(source: &str, line: u32) -> Option<(usize, usize)> {
@@ -204,8 +508,20 @@ fn check_member_access(
     text_after: &str,
     node: &Node,
     source: &str,
+    line_start: usize,
 ) -> Option<CompletionContext> {
-    let trimmed = text_before.trim_end();
+    let mut trimmed = text_before.trim_end();
+
+    // A line starting with `->` continues the chain from the lines above.
+    let joined;
+    let continuation = trimmed.trim_start();
+    if continuation.starts_with("->") || continuation.starts_with("?->") {
+        let receiver = chain_receiver_before_line(source, line_start);
+        if !receiver.is_empty() {
+            joined = format!("{receiver}{continuation}");
+            trimmed = &joined;
+        }
+    }
 
     // Check if text ends with `->`  or `->partial`
     if let Some(arrow_pos) = trimmed.rfind("->") {
@@ -234,6 +550,50 @@ fn check_member_access(
     None
 }
 
+/// Join the lines of a member chain that ends right before `line_start`:
+///
+/// ```php
+/// $query
+///     ->where('active', 1)
+///     ->
+/// ```
+///
+/// yields `$query->where('active', 1)` for the last line. Blank and comment
+/// lines inside the chain are skipped.
+fn chain_receiver_before_line(source: &str, line_start: usize) -> String {
+    let mut lines = Vec::new();
+    let mut end = line_start;
+    while end > 0 {
+        let text = &source[..end];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        let start = text.rfind('\n').map_or(0, |idx| idx + 1);
+        end = start;
+
+        let line = text[start..].trim();
+        if line.is_empty()
+            || line.starts_with("//")
+            || line.starts_with('#')
+            || line.starts_with('*')
+        {
+            continue;
+        }
+        if line.starts_with("/*")
+            || line.ends_with(';')
+            || line.ends_with('{')
+            || line.ends_with('}')
+        {
+            break;
+        }
+        lines.push(line);
+        if !line.starts_with("->") && !line.starts_with("?->") {
+            lines.reverse();
+            return lines.concat();
+        }
+    }
+    String::new()
+}
+
 fn receiver_text_before_member_arrow(text: &str) -> &str {
     let before_arrow = text.trim_end();
     before_arrow
@@ -244,10 +604,7 @@ fn receiver_text_before_member_arrow(text: &str) -> &str {
 
 fn member_access_mode_after_cursor(text_after: &str) -> MemberAccessMode {
     let mut rest = text_after;
-    loop {
-        let Some(ch) = rest.chars().next() else {
-            break;
-        };
+    while let Some(ch) = rest.chars().next() {
         if ch.is_alphanumeric() || ch == '_' {
             rest = &rest[ch.len_utf8()..];
         } else {
@@ -369,6 +726,40 @@ fn check_static_access(
     None
 }
 
+/// Simple interpolation in the literal part of a string: `"$name` or
+/// `"$user->name` (one property level, as PHP itself interpolates).
+fn check_simple_interpolation(text_before: &str) -> Option<CompletionContext> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let word_start = text_before
+        .rfind(|c: char| !is_word(c))
+        .map_or(0, |pos| pos + 1);
+    let word = &text_before[word_start..];
+    let before_word = &text_before[..word_start];
+
+    if before_word.ends_with('$') && !before_word.ends_with("\\$") {
+        return Some(CompletionContext::Variable {
+            prefix: word.to_string(),
+            in_scope: Vec::new(),
+        });
+    }
+    let receiver = before_word
+        .strip_suffix("?->")
+        .or_else(|| before_word.strip_suffix("->"))?;
+    Some(CompletionContext::MemberAccess {
+        object_expr: trailing_interpolated_variable(receiver)?.to_string(),
+        member_prefix: word.to_string(),
+        class_fqn: None,
+        access_mode: MemberAccessMode::Read,
+    })
+}
+
+/// The unescaped `$name` that ends `text`, if any.
+fn trailing_interpolated_variable(text: &str) -> Option<&str> {
+    let name_start = text.rfind(|c: char| !c.is_alphanumeric() && c != '_')? + 1;
+    let before_dollar = text[..name_start].strip_suffix('$')?;
+    (name_start < text.len() && !before_dollar.ends_with('\\')).then(|| &text[name_start - 1..])
+}
+
 /// Check for `$` variable access.
 fn check_variable_access(text_before: &str) -> Option<CompletionContext> {
     let trimmed = text_before.trim_end();
@@ -392,6 +783,7 @@ fn check_variable_access(text_before: &str) -> Option<CompletionContext> {
             {
                 return Some(CompletionContext::Variable {
                     prefix: after_dollar.to_string(),
+                    in_scope: Vec::new(),
                 });
             }
         }
@@ -424,6 +816,262 @@ fn check_namespace_access(text_before: &str) -> Option<CompletionContext> {
     None
 }
 
+/// PHPDoc tags whose first argument is a type, without `phpstan-`/`psalm-`.
+const PHPDOC_TYPED_TAGS: &[&str] = &[
+    "param",
+    "return",
+    "var",
+    "throws",
+    "property",
+    "property-read",
+    "property-write",
+    "method",
+    "extends",
+    "implements",
+    "use",
+    "mixin",
+];
+
+/// PHPDoc tags declaring a template, whose bound follows `of`.
+const PHPDOC_TEMPLATE_TAGS: &[&str] = &["template", "template-covariant", "template-contravariant"];
+
+/// Classify a cursor inside the doc comment opening at `comment_start`: a tag
+/// name after `@`, a type after a typed tag, or the variable of a `@param`.
+/// Description text yields `None`.
+fn check_phpdoc_context(
+    root: &Node,
+    source: &str,
+    comment_start: usize,
+    cursor_offset: usize,
+) -> Option<CompletionContext> {
+    let line_start = source[..cursor_offset]
+        .rfind('\n')
+        .map_or(0, |pos| pos + 1)
+        .max(comment_start + 3);
+    let text_before = &source[line_start..cursor_offset];
+
+    let word = trailing_word(text_before, |c| c.is_alphanumeric() || c == '_' || c == '-');
+    let before_word = &text_before[..text_before.len() - word.len()];
+    if let Some(before_at) = before_word.strip_suffix('@') {
+        return before_at
+            .chars()
+            .last()
+            .is_none_or(|c| c.is_whitespace() || c == '*' || c == '{')
+            .then(|| CompletionContext::PhpDoc {
+                prefix: word.to_string(),
+                position: PhpDocPosition::Tag,
+            });
+    }
+
+    let content = text_before
+        .trim_start()
+        .trim_start_matches('*')
+        .trim_start();
+    let tag_line = content.strip_prefix('@')?;
+    let tag_end = tag_line.find(char::is_whitespace)?;
+    let tag = &tag_line[..tag_end];
+    let tag = tag
+        .strip_prefix("phpstan-")
+        .or_else(|| tag.strip_prefix("psalm-"))
+        .unwrap_or(tag);
+    let arguments = tag_line[tag_end..].trim_start();
+
+    let type_text = if PHPDOC_TEMPLATE_TAGS.contains(&tag) {
+        let (_, bound) = arguments.split_once(char::is_whitespace)?;
+        bound
+            .trim_start()
+            .strip_prefix("of")?
+            .strip_prefix(char::is_whitespace)?
+            .trim_start()
+    } else if PHPDOC_TYPED_TAGS.contains(&tag) {
+        arguments
+    } else {
+        return None;
+    };
+
+    let param_name = if tag == "param" && arguments.starts_with(['$', '&', '.']) {
+        Some(arguments)
+    } else {
+        match split_phpdoc_type(type_text) {
+            None => {
+                let prefix = trailing_word(type_text, |c| {
+                    c.is_alphanumeric() || c == '_' || c == '\\' || c == '-'
+                });
+                return Some(CompletionContext::PhpDoc {
+                    prefix: prefix.to_string(),
+                    position: PhpDocPosition::Type,
+                });
+            }
+            Some(rest) if tag == "param" => Some(rest.trim_start()),
+            Some(_) => None,
+        }
+    }?;
+
+    let name = param_name.trim_start_matches(['&', '.']);
+    let name = match name.strip_prefix('$') {
+        Some(name) => name,
+        None if name.is_empty() => name,
+        None => return None,
+    };
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(CompletionContext::PhpDoc {
+        prefix: name.to_string(),
+        position: PhpDocPosition::ParamName {
+            parameters: phpdoc_undocumented_parameters(root, source, comment_start, line_start),
+        },
+    })
+}
+
+/// The longest suffix of `text` made of characters accepted by `is_word`.
+fn trailing_word(text: &str, is_word: impl Fn(char) -> bool) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map_or(text.len(), |(idx, _)| idx);
+    &text[start..]
+}
+
+/// Split off the text after a complete PHPDoc type: the type ends at the
+/// first whitespace outside brackets that does not follow a `|`, `&`, `,` or
+/// `:`. Returns `None` while the type is still being typed.
+fn split_phpdoc_type(text: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut last = None;
+    for (idx, ch) in text.char_indices() {
+        match ch {
+            '<' | '(' | '{' | '[' => depth += 1,
+            '>' | ')' | '}' | ']' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace()
+                && depth == 0
+                && !matches!(last, None | Some('|' | '&' | ',' | ':')) =>
+            {
+                return Some(&text[idx..]);
+            }
+            _ => {}
+        }
+        if !ch.is_whitespace() {
+            last = Some(ch);
+        }
+    }
+    None
+}
+
+/// Parameters of the function or method documented by the comment opening
+/// at `comment_start` that no `@param` outside the cursor line mentions yet.
+fn phpdoc_undocumented_parameters(
+    root: &Node,
+    source: &str,
+    comment_start: usize,
+    cursor_line_start: usize,
+) -> Vec<String> {
+    let Some(mut comment) = root.descendant_for_byte_range(comment_start, comment_start) else {
+        return Vec::new();
+    };
+    while comment.kind() != "comment" || comment.start_byte() != comment_start {
+        match comment.parent() {
+            Some(parent) => comment = parent,
+            None => return Vec::new(),
+        }
+    }
+    let mut documented = comment.next_named_sibling();
+    while let Some(node) = documented.filter(|node| node.kind() == "comment") {
+        documented = node.next_named_sibling();
+    }
+    let Some(parameters) = documented
+        .filter(|node| matches!(node.kind(), "function_definition" | "method_declaration"))
+        .and_then(|node| node.child_by_field_name("parameters"))
+    else {
+        return Vec::new();
+    };
+
+    let comment_text = &source[comment.byte_range()];
+    let mut offset = comment_start;
+    let mut already_documented = Vec::new();
+    for line in comment_text.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+        let tag = line
+            .trim_start()
+            .trim_start_matches("/**")
+            .trim_start_matches('*');
+        let is_param_tag = tag
+            .trim_start()
+            .strip_prefix('@')
+            .and_then(|tag| tag.split_whitespace().next())
+            .is_some_and(|tag| tag == "param" || tag.ends_with("-param"));
+        if line_offset == cursor_line_start || !is_param_tag {
+            continue;
+        }
+        if let Some((_, rest)) = line.split_once('$') {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            already_documented.push(rest[..end].to_string());
+        }
+    }
+
+    let mut cursor = parameters.walk();
+    parameters
+        .named_children(&mut cursor)
+        .filter_map(|parameter| parameter.child_by_field_name("name"))
+        .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+        .map(|name| name.trim_start_matches('$').to_string())
+        .filter(|name| !already_documented.contains(name))
+        .collect()
+}
+
+/// Check if the cursor is on an attribute name: right after `#[` or after a
+/// top-level `,` inside an attribute group, possibly spanning several lines.
+fn check_attribute_context(node: &Node, source_before: &str) -> Option<CompletionContext> {
+    if matches!(
+        node.kind(),
+        "comment" | "string" | "string_content" | "encapsed_string" | "heredoc" | "nowdoc"
+    ) {
+        return None;
+    }
+
+    let prefix_start = source_before
+        .rfind(|c: char| !c.is_alphanumeric() && c != '_' && c != '\\')
+        .map(|pos| pos + 1)
+        .unwrap_or(0);
+    let prefix = &source_before[prefix_start..];
+    let before = source_before[..prefix_start].trim_end();
+    if !(before.ends_with("#[") || before.ends_with(',')) {
+        return None;
+    }
+
+    let mut depth = 0usize;
+    for (idx, ch) in before.char_indices().rev() {
+        match ch {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '{' => {
+                if depth == 0 {
+                    return None;
+                }
+                depth -= 1;
+            }
+            '[' => {
+                if depth > 0 {
+                    depth -= 1;
+                } else if before[..idx].ends_with('#') {
+                    return Some(CompletionContext::Attribute {
+                        prefix: prefix.to_string(),
+                    });
+                } else {
+                    return None;
+                }
+            }
+            ';' if depth == 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Check if cursor is inside a use statement.
 fn check_use_context(node: &Node, text_before: &str, source: &str) -> Option<CompletionContext> {
     let mut current = Some(*node);
@@ -524,7 +1172,13 @@ fn extract_object_expr(text: &str) -> String {
 fn find_object_in_cst(node: &Node, source: &str) -> Option<String> {
     let mut current = Some(*node);
     while let Some(n) = current {
-        if n.kind() == "member_access_expression" || n.kind() == "member_call_expression" {
+        if matches!(
+            n.kind(),
+            "member_access_expression"
+                | "member_call_expression"
+                | "nullsafe_member_access_expression"
+                | "nullsafe_member_call_expression"
+        ) {
             if let Some(obj) = n.child_by_field_name("object") {
                 return Some(source[obj.byte_range()].to_string());
             }
@@ -603,6 +1257,86 @@ mod tests {
         detect_at_byte_col(&code, line, byte_col)
     }
 
+    #[test]
+    fn test_string_interpolation_contexts() {
+        let member = |object_expr: &str, member_prefix: &str| CompletionContext::MemberAccess {
+            object_expr: object_expr.to_string(),
+            member_prefix: member_prefix.to_string(),
+            class_fqn: None,
+            access_mode: MemberAccessMode::Read,
+        };
+        let variable = |prefix: &str| CompletionContext::Variable {
+            prefix: prefix.to_string(),
+            in_scope: Vec::new(),
+        };
+
+        assert_eq!(
+            detect_at_marker("<?php\necho \"Hello {$user->/*caret*/\n"),
+            member("$user", "")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\necho \"Hi {$user->profile->na/*caret*/}\";\n"),
+            member("$user->profile", "na")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\necho \"Hi $user->na/*caret*/ there\";\n"),
+            member("$user", "na")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\necho <<<EOT\n  Hi {$user->na/*caret*/\n  EOT;\n"),
+            member("$user", "na")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\necho \"Hi $us/*caret*/ there\";\n"),
+            variable("us")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\necho \"Hi ${us/*caret*/}\";\n"),
+            variable("us")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\necho <<<EOT\nHi $/*caret*/\nEOT;\n"),
+            variable("")
+        );
+
+        for code in [
+            "<?php\necho 'Hi $us/*caret*/';\n",
+            "<?php\necho \"Hi Us/*caret*/\";\n",
+            "<?php\necho \"Hi \\$us/*caret*/\";\n",
+            "<?php\necho \"Hi $user->profile->na/*caret*/\";\n",
+            "<?php\necho <<<'EOT'\nHi {$user->/*caret*/\nEOT;\n",
+        ] {
+            assert_eq!(detect_at_marker(code), CompletionContext::None, "{code:?}");
+        }
+    }
+
+    #[test]
+    fn test_template_regions_complete_only_inside_php_blocks() {
+        let header = "<?php $user = new User(); ?>\n<a href=\"http://x/#y\">It's ";
+        assert_eq!(
+            detect_at_marker(&format!("{header}<?php echo $user->na/*caret*/ ?></a>\n")),
+            CompletionContext::MemberAccess {
+                object_expr: "$user".to_string(),
+                member_prefix: "na".to_string(),
+                class_fqn: None,
+                access_mode: MemberAccessMode::Read,
+            }
+        );
+        assert!(matches!(
+            detect_at_marker(&format!("{header}<?= $us/*caret*/ ?></a>\n")),
+            CompletionContext::Variable { prefix, .. } if prefix == "us"
+        ));
+
+        for code in [
+            "<?php $user = 1; ?>\n<p>$us/*caret*/</p>\n",
+            "<?php $user = 1; ?>\n<p>$user->/*caret*/</p>\n",
+            "<?php $user = 1; ?>\n<p>User::/*caret*/</p>\n<?php $user; ?>\n",
+            "<p>Us/*caret*/</p>\n",
+        ] {
+            assert_eq!(detect_at_marker(code), CompletionContext::None, "{code:?}");
+        }
+    }
+
     #[test]
     fn test_member_access_context() {
         let code = "<?php\n$obj->meth";
@@ -706,6 +1440,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_member_access_context_joins_multiline_fluent_chain() {
+        let code = "<?php\n$users = $query\n    ->where('active', 1)\n\n    // newest first\n    ?->orderBy('id')\n    ->lim";
+        let ctx = detect_at_byte_col(code, 6, 9);
+        match ctx {
+            CompletionContext::MemberAccess {
+                object_expr,
+                member_prefix,
+                ..
+            } => {
+                assert_eq!(object_expr, "$query->where('active', 1)?->orderBy('id')");
+                assert_eq!(member_prefix, "lim");
+            }
+            other => panic!("Expected MemberAccess, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_member_access_context_keeps_array_access_object() {
         let code = "<?php\n$users[0]->";
@@ -949,7 +1700,7 @@ mod tests {
         for code in ["<?php\n$привет", "<?php\n$中文", "<?php\n$བོད"] {
             let ctx = detect_at_byte_col(code, 1, 2);
             match ctx {
-                CompletionContext::Variable { prefix } => {
+                CompletionContext::Variable { prefix, .. } => {
                     assert_eq!(prefix, "");
                 }
                 other => panic!("Expected Variable for {code:?}, got {:?}", other),
@@ -1035,7 +1786,7 @@ mod tests {
         let code = "<?php\n$use";
         let ctx = detect_at_byte_col(code, 1, 4);
         match ctx {
-            CompletionContext::Variable { prefix } => {
+            CompletionContext::Variable { prefix, .. } => {
                 assert_eq!(prefix, "use");
             }
             other => panic!("Expected Variable, got {:?}", other),
@@ -1059,8 +1810,9 @@ mod tests {
         let code = "<?php\narray_m";
         let ctx = detect_at_byte_col(code, 1, 7);
         match ctx {
-            CompletionContext::Free { prefix } => {
+            CompletionContext::Free { prefix, position } => {
                 assert_eq!(prefix, "array_m");
+                assert_eq!(position, KeywordPosition::Statement);
             }
             other => panic!("Expected Free, got {:?}", other),
         }
@@ -1071,10 +1823,179 @@ mod tests {
         let code = "<?php\nfoo('ཇི་ཨེམ་ཏི', Timez/*caret*/);";
         let ctx = detect_at_marker(code);
         match ctx {
-            CompletionContext::Free { prefix } => {
+            CompletionContext::Free { prefix, position } => {
                 assert_eq!(prefix, "Timez");
+                assert_eq!(position, KeywordPosition::Expression);
             }
             other => panic!("Expected Free, got {:?}", other),
         }
     }
+
+    fn free_position_at_marker(code: &str) -> KeywordPosition {
+        match detect_at_marker(code) {
+            CompletionContext::Free { position, .. } => position,
+            other => panic!("Expected Free, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_attribute_context_after_group_start_and_comma() {
+        let attribute = |prefix: &str| CompletionContext::Attribute {
+            prefix: prefix.to_string(),
+        };
+
+        assert_eq!(
+            detect_at_marker("<?php\n#[Ro/*caret*/\nclass Controller {}\n"),
+            attribute("Ro")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\nclass C {\n    #[/*caret*/]\n    public function f() {}\n}\n"),
+            attribute("")
+        );
+        assert_eq!(
+            detect_at_marker(
+                "<?php\n#[Route('/a', methods: ['GET']),\n  ORM\\En/*caret*/]\nclass C {}\n"
+            ),
+            attribute("ORM\\En")
+        );
+        assert_eq!(
+            detect_at_marker("<?php\nfunction f(#[Sens/*caret*/] $secret) {}\n"),
+            attribute("Sens")
+        );
+        assert!(!matches!(
+            detect_at_marker("<?php\n#[Route('/a', Na/*caret*/)]\nclass C {}\n"),
+            CompletionContext::Attribute { .. }
+        ));
+        assert!(!matches!(
+            detect_at_marker("<?php\n$list = [1, Fo/*caret*/];\n"),
+            CompletionContext::Attribute { .. }
+        ));
+    }
+
+    #[test]
+    fn test_phpdoc_tag_type_and_param_contexts() {
+        let phpdoc = |prefix: &str, position: PhpDocPosition| CompletionContext::PhpDoc {
+            prefix: prefix.to_string(),
+            position,
+        };
+        let function = |doc: &str| {
+            format!("<?php\n/**\n{doc}\n */\nfunction run(int $id, string ...$names) {{}}\n")
+        };
+
+        assert_eq!(
+            detect_at_marker(&function(" * @par/*caret*/")),
+            phpdoc("par", PhpDocPosition::Tag)
+        );
+        assert_eq!(
+            detect_at_marker("<?php\n/** @/*caret*/ */\n$a = 1;\n"),
+            phpdoc("", PhpDocPosition::Tag)
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * @param Us/*caret*/")),
+            phpdoc("Us", PhpDocPosition::Type)
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * @return array<int, App\\Mo/*caret*/")),
+            phpdoc("App\\Mo", PhpDocPosition::Type)
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * @phpstan-param int|/*caret*/")),
+            phpdoc("", PhpDocPosition::Type)
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * @template T of Mo/*caret*/")),
+            phpdoc("Mo", PhpDocPosition::Type)
+        );
+        assert_eq!(
+            detect_at_marker(&function(
+                " * @param int $id\n * @param string[] $/*caret*/"
+            )),
+            phpdoc(
+                "",
+                PhpDocPosition::ParamName {
+                    parameters: vec!["names".to_string()],
+                }
+            )
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * @param $i/*caret*/")),
+            phpdoc(
+                "i",
+                PhpDocPosition::ParamName {
+                    parameters: vec!["id".to_string(), "names".to_string()],
+                }
+            )
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * Runs the th/*caret*/")),
+            CompletionContext::None
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * @return int the us/*caret*/")),
+            CompletionContext::None
+        );
+        assert_eq!(
+            detect_at_marker(&function(" * mail me@exa/*caret*/")),
+            CompletionContext::None
+        );
+    }
+
+    #[test]
+    fn test_free_context_keyword_positions() {
+        assert_eq!(
+            free_position_at_marker(
+                "<?php\nfunction run(): void\n{\n    $x = 1;\n    fore/*caret*/\n}"
+            ),
+            KeywordPosition::Statement
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\n$value = ma/*caret*/"),
+            KeywordPosition::Expression
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nclass A\n{\n    pub/*caret*/\n}"),
+            KeywordPosition::ClassBody {
+                in_enum: false,
+                after_modifier: false
+            }
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nclass A\n{\n    private rea/*caret*/\n}"),
+            KeywordPosition::ClassBody {
+                in_enum: false,
+                after_modifier: true
+            }
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nenum Suit\n{\n    ca/*caret*/\n}"),
+            KeywordPosition::ClassBody {
+                in_enum: true,
+                after_modifier: false
+            }
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nfunction run(str/*caret*/ $name) {}"),
+            KeywordPosition::Type { return_type: false }
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nfunction run(): vo/*caret*/"),
+            KeywordPosition::Type { return_type: true }
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nclass A ext/*caret*/"),
+            KeywordPosition::ClassHeader
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\nclass A extends Ba/*caret*/"),
+            KeywordPosition::ClassReference
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\n$x = new Fo/*caret*/"),
+            KeywordPosition::Instantiation
+        );
+        assert_eq!(
+            free_position_at_marker("<?php\ntry {\n} catch (Run/*caret*/"),
+            KeywordPosition::ClassReference
+        );
+    }
 }
@@ -5,3 +5,6 @@
 
 pub mod context;
 pub mod provider;
+pub mod ranking;
+pub mod strings;
+pub mod superglobals;
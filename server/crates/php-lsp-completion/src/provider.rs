@@ -3,8 +3,12 @@
 //! Given a completion context and the workspace index, provides relevant
 //! completion items.
 
-use crate::context::{CompletionContext, MemberAccessMode};
-use lsp_types::{CompletionItem, CompletionItemKind, InsertTextFormat};
+use crate::context::{CompletionContext, KeywordPosition, MemberAccessMode, PhpDocPosition};
+use crate::ranking::{
+    match_quality, preselect_best_item, relevance_sort_rank, symbol_origin, MatchQuality,
+};
+use crate::superglobals::SUPERGLOBALS;
+use lsp_types::{Command, CompletionItem, CompletionItemKind, InsertTextFormat};
 use php_lsp_index::workspace::WorkspaceIndex;
 use php_lsp_parser::phpdoc::parse_phpdoc;
 use php_lsp_types::{
@@ -14,13 +18,10 @@ use php_lsp_types::{
 use serde_json::json;
 use std::collections::HashSet;
 
-/// PHP keywords for free context.
-const PHP_KEYWORDS: &[&str] = &[
+/// Keywords that can begin a statement.
+const STATEMENT_KEYWORDS: &[&str] = &[
     "abstract",
-    "array",
-    "as",
     "break",
-    "callable",
     "case",
     "catch",
     "class",
@@ -34,7 +35,6 @@ const PHP_KEYWORDS: &[&str] = &[
     "else",
     "elseif",
     "enum",
-    "extends",
     "final",
     "finally",
     "fn",
@@ -43,19 +43,14 @@ const PHP_KEYWORDS: &[&str] = &[
     "function",
     "global",
     "if",
-    "implements",
     "include",
     "include_once",
-    "instanceof",
     "interface",
     "list",
     "match",
     "namespace",
     "new",
     "print",
-    "private",
-    "protected",
-    "public",
     "readonly",
     "require",
     "require_once",
@@ -65,37 +60,213 @@ const PHP_KEYWORDS: &[&str] = &[
     "throw",
     "trait",
     "try",
+    "unset",
     "use",
-    "var",
     "while",
     "yield",
 ];
 
+/// Keywords valid inside an expression.
+const EXPRESSION_KEYWORDS: &[&str] = &[
+    "array",
+    "clone",
+    "empty",
+    "false",
+    "fn",
+    "function",
+    "include",
+    "include_once",
+    "instanceof",
+    "isset",
+    "list",
+    "match",
+    "new",
+    "null",
+    "parent",
+    "print",
+    "require",
+    "require_once",
+    "self",
+    "static",
+    "throw",
+    "true",
+    "yield",
+];
+
+/// Keywords valid right after `new`.
+const INSTANTIATION_KEYWORDS: &[&str] = &["class", "parent", "self", "static"];
+
+/// Keywords valid in a class-like header.
+const CLASS_HEADER_KEYWORDS: &[&str] = &["extends", "implements"];
+
+/// Keywords that start a member declaration in a class-like body.
+const CLASS_BODY_KEYWORDS: &[&str] = &[
+    "abstract",
+    "const",
+    "final",
+    "function",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "static",
+    "use",
+    "var",
+];
+
+/// Type keywords for parameter, property and return types.
+const TYPE_KEYWORDS: &[&str] = &[
+    "array", "bool", "callable", "false", "float", "int", "iterable", "mixed", "null", "object",
+    "parent", "self", "static", "string", "true",
+];
+
+/// Type keywords only valid as return types.
+const RETURN_ONLY_TYPE_KEYWORDS: &[&str] = &["never", "void"];
+
+/// PHPDoc-only type keywords, offered with `TYPE_KEYWORDS` in doc comments.
+const PHPDOC_TYPE_KEYWORDS: &[&str] = &[
+    "$this",
+    "array-key",
+    "class-string",
+    "list",
+    "never",
+    "non-empty-array",
+    "non-empty-list",
+    "non-empty-string",
+    "numeric",
+    "numeric-string",
+    "positive-int",
+    "negative-int",
+    "resource",
+    "scalar",
+];
+
+/// PHPDoc tags with their argument synopsis.
+const PHPDOC_TAGS: &[(&str, &str)] = &[
+    ("param", "@param Type $name description"),
+    ("return", "@return Type description"),
+    ("var", "@var Type $name"),
+    ("throws", "@throws ExceptionClass description"),
+    ("template", "@template T of Bound"),
+    ("template-covariant", "@template-covariant T of Bound"),
+    ("extends", "@extends Parent<T>"),
+    ("implements", "@implements Interface<T>"),
+    ("use", "@use Trait<T>"),
+    ("mixin", "@mixin ClassName"),
+    ("property", "@property Type $name"),
+    ("property-read", "@property-read Type $name"),
+    ("property-write", "@property-write Type $name"),
+    ("method", "@method ReturnType name(Type $param)"),
+    ("deprecated", "@deprecated description"),
+    ("see", "@see ClassName::member()"),
+    ("since", "@since version"),
+    ("internal", "@internal"),
+    ("inheritDoc", "@inheritDoc"),
+    ("phpstan-param", "@phpstan-param Type $name"),
+    ("phpstan-return", "@phpstan-return Type"),
+    ("phpstan-var", "@phpstan-var Type"),
+    ("phpstan-type", "@phpstan-type Alias Type"),
+    (
+        "phpstan-import-type",
+        "@phpstan-import-type Alias from ClassName",
+    ),
+    ("psalm-param", "@psalm-param Type $name"),
+    ("psalm-return", "@psalm-return Type"),
+    ("psalm-type", "@psalm-type Alias Type"),
+];
+
+/// Keywords offered at `position`, without duplicates.
+fn keywords_for_position(position: KeywordPosition) -> Vec<&'static str> {
+    let lists: Vec<&[&'static str]> = match position {
+        KeywordPosition::Statement => vec![STATEMENT_KEYWORDS],
+        KeywordPosition::Expression => vec![EXPRESSION_KEYWORDS],
+        KeywordPosition::Instantiation => vec![INSTANTIATION_KEYWORDS],
+        KeywordPosition::ClassHeader => vec![CLASS_HEADER_KEYWORDS],
+        KeywordPosition::ClassReference => vec![],
+        KeywordPosition::ClassBody {
+            in_enum,
+            after_modifier,
+        } => {
+            let mut lists = vec![CLASS_BODY_KEYWORDS];
+            if in_enum {
+                lists.push(&["case"]);
+            }
+            if after_modifier {
+                lists.push(TYPE_KEYWORDS);
+            }
+            lists
+        }
+        KeywordPosition::Type { return_type } => {
+            if return_type {
+                vec![TYPE_KEYWORDS, RETURN_ONLY_TYPE_KEYWORDS]
+            } else {
+                vec![TYPE_KEYWORDS]
+            }
+        }
+    };
+
+    let mut seen = HashSet::new();
+    lists
+        .into_iter()
+        .flatten()
+        .copied()
+        .filter(|keyword| seen.insert(*keyword))
+        .collect()
+}
+
+/// Snippet completion settings supplied by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetOptions {
+    /// Offer snippet-kind completions. Keywords stay plain when disabled.
+    pub enabled: bool,
+    /// Namespace declaration to prepend to class-like snippets, inferred from
+    /// PSR-4 autoload for files without a `namespace` statement yet.
+    pub class_namespace: Option<String>,
+    /// Default class name for class-like snippets, from the file name.
+    pub class_name: Option<String>,
+    /// Insert call parentheses after function and method names, with a
+    /// placeholder and signature help when parameters are expected.
+    pub call_parentheses: bool,
+}
+
+impl Default for SnippetOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            class_namespace: None,
+            class_name: None,
+            call_parentheses: false,
+        }
+    }
+}
+
 struct SnippetTemplate {
     label: &'static str,
     insert_text: &'static str,
     detail: &'static str,
 }
 
+/// Snippets that replace the keyword of the same name at statement and
+/// class-body positions. `{{name}}` is the class-like name placeholder.
 const PHP_SNIPPETS: &[SnippetTemplate] = &[
     SnippetTemplate {
         label: "class",
-        insert_text: "class ${1:Name}\n{\n    $0\n}",
+        insert_text: "class {{name}}\n{\n    $0\n}",
         detail: "class declaration",
     },
     SnippetTemplate {
         label: "interface",
-        insert_text: "interface ${1:Name}\n{\n    $0\n}",
+        insert_text: "interface {{name}}\n{\n    $0\n}",
         detail: "interface declaration",
     },
     SnippetTemplate {
         label: "trait",
-        insert_text: "trait ${1:Name}\n{\n    $0\n}",
+        insert_text: "trait {{name}}\n{\n    $0\n}",
         detail: "trait declaration",
     },
     SnippetTemplate {
         label: "enum",
-        insert_text: "enum ${1:Name}\n{\n    $0\n}",
+        insert_text: "enum {{name}}\n{\n    $0\n}",
         detail: "enum declaration",
     },
     SnippetTemplate {
@@ -120,6 +291,100 @@ const PHP_SNIPPETS: &[SnippetTemplate] = &[
     },
 ];
 
+/// Member skeletons offered at class-body positions.
+const CLASS_BODY_SNIPPETS: &[SnippetTemplate] = &[
+    SnippetTemplate {
+        label: "pubf",
+        insert_text: "public function ${1:name}(${2}): ${3:void}\n{\n    $0\n}",
+        detail: "public function",
+    },
+    SnippetTemplate {
+        label: "prof",
+        insert_text: "protected function ${1:name}(${2}): ${3:void}\n{\n    $0\n}",
+        detail: "protected function",
+    },
+    SnippetTemplate {
+        label: "prif",
+        insert_text: "private function ${1:name}(${2}): ${3:void}\n{\n    $0\n}",
+        detail: "private function",
+    },
+    SnippetTemplate {
+        label: "pubsf",
+        insert_text: "public static function ${1:name}(${2}): ${3:void}\n{\n    $0\n}",
+        detail: "public static function",
+    },
+    SnippetTemplate {
+        label: "ctor",
+        insert_text: "public function __construct(${1})\n{\n    $0\n}",
+        detail: "constructor",
+    },
+    SnippetTemplate {
+        label: "getter",
+        insert_text: "public function get${1:Name}(): ${2:mixed}\n{\n    return \\$this->${3:name};\n}",
+        detail: "getter method",
+    },
+    SnippetTemplate {
+        label: "setter",
+        insert_text: "public function set${1:Name}(${2:mixed} \\$${3:value}): void\n{\n    \\$this->${4:name} = \\$${3:value};\n}",
+        detail: "setter method",
+    },
+];
+
+/// Escape literal text for use inside an LSP snippet.
+fn escape_snippet_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '$' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Expand the class-like name placeholder and, for top-level declarations,
+/// prepend the PSR-4 namespace.
+fn render_snippet(
+    template: &SnippetTemplate,
+    position: KeywordPosition,
+    options: &SnippetOptions,
+) -> String {
+    if !template.insert_text.contains("{{name}}") {
+        return template.insert_text.to_string();
+    }
+    let name = options
+        .class_name
+        .as_deref()
+        .map(escape_snippet_text)
+        .unwrap_or_else(|| "Name".to_string());
+    let body = template
+        .insert_text
+        .replace("{{name}}", &format!("${{1:{}}}", name));
+    match options.class_namespace.as_deref() {
+        Some(namespace) if position == KeywordPosition::Statement && !namespace.is_empty() => {
+            format!("namespace {};\n\n{}", escape_snippet_text(namespace), body)
+        }
+        _ => body,
+    }
+}
+
+fn snippet_completion_item(
+    template: &SnippetTemplate,
+    position: KeywordPosition,
+    options: &SnippetOptions,
+) -> CompletionItem {
+    CompletionItem {
+        label: template.label.to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some(template.detail.to_string()),
+        insert_text: Some(render_snippet(template, position, options)),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        sort_text: Some(format!("0000_{}", template.label)),
+        filter_text: Some(template.label.to_string()),
+        ..Default::default()
+    }
+}
+
 /// Provide completion items based on context.
 pub fn provide_completions(
     context: &CompletionContext,
@@ -132,6 +397,7 @@ pub fn provide_completions(
         index,
         file_symbols,
         current_class_fqn.as_deref(),
+        &SnippetOptions::default(),
     )
 }
 
@@ -141,6 +407,7 @@ pub fn provide_completions_at_range(
     index: &WorkspaceIndex,
     file_symbols: &FileSymbols,
     cursor_range: (u32, u32, u32, u32),
+    snippets: &SnippetOptions,
 ) -> Vec<CompletionItem> {
     let current_class_fqn = find_current_class_fqn_at_range(file_symbols, cursor_range);
     provide_completions_with_current_class(
@@ -148,6 +415,7 @@ pub fn provide_completions_at_range(
         index,
         file_symbols,
         current_class_fqn.as_deref(),
+        snippets,
     )
 }
 
@@ -156,8 +424,9 @@ fn provide_completions_with_current_class(
     index: &WorkspaceIndex,
     file_symbols: &FileSymbols,
     current_class_fqn: Option<&str>,
+    snippets: &SnippetOptions,
 ) -> Vec<CompletionItem> {
-    match context {
+    let mut items = match context {
         CompletionContext::MemberAccess {
             object_expr,
             class_fqn,
@@ -170,6 +439,7 @@ fn provide_completions_with_current_class(
             index,
             current_class_fqn,
             *access_mode,
+            snippets.call_parentheses,
         ),
         CompletionContext::StaticAccess {
             class_fqn,
@@ -181,18 +451,31 @@ fn provide_completions_with_current_class(
             member_prefix,
             index,
             current_class_fqn,
+            snippets.call_parentheses,
         ),
         CompletionContext::ArrayKey { .. } => vec![],
-        CompletionContext::Variable { prefix } => {
-            provide_variable_completions(prefix, file_symbols)
+        CompletionContext::Variable { prefix, in_scope } => {
+            provide_variable_completions(prefix, in_scope)
+        }
+        CompletionContext::Namespace { prefix } => {
+            provide_namespace_completions(prefix, index, file_symbols)
         }
-        CompletionContext::Namespace { prefix } => provide_namespace_completions(prefix, index),
         CompletionContext::UseStatement { prefix } => {
-            provide_use_statement_completions(prefix, index)
+            provide_use_statement_completions(prefix, index, file_symbols)
+        }
+        CompletionContext::Attribute { prefix } => {
+            provide_attribute_completions(prefix, index, file_symbols)
+        }
+        CompletionContext::PhpDoc { prefix, position } => {
+            provide_phpdoc_completions(prefix, position, index, file_symbols)
+        }
+        CompletionContext::Free { prefix, position } => {
+            provide_free_completions(prefix, *position, index, file_symbols, snippets)
         }
-        CompletionContext::Free { prefix } => provide_free_completions(prefix, index),
         CompletionContext::None => vec![],
-    }
+    };
+    preselect_best_item(&mut items);
+    items
 }
 
 /// Provide member access completions (`->`).
@@ -203,6 +486,7 @@ fn provide_member_completions(
     index: &WorkspaceIndex,
     current_class_fqn: Option<&str>,
     access_mode: MemberAccessMode,
+    call_parentheses: bool,
 ) -> Vec<CompletionItem> {
     let mut items = Vec::new();
 
@@ -234,11 +518,11 @@ fn provide_member_completions(
                 }
             }
 
-            items.push(symbol_to_completion_item(
-                &member,
-                false,
-                Some(member_prefix),
-            ));
+            let mut item = symbol_to_completion_item(&member, false, Some(member_prefix));
+            if call_parentheses && member.kind == PhpSymbolKind::Method {
+                apply_call_snippet(&mut item, symbol_has_parameters(&member));
+            }
+            items.push(item);
         }
         let mut seen_labels: HashSet<String> =
             items.iter().map(|item| item.label.clone()).collect();
@@ -249,6 +533,7 @@ fn provide_member_completions(
             &mut items,
             &mut seen_labels,
             access_mode,
+            call_parentheses,
         );
     }
 
@@ -263,6 +548,7 @@ fn add_phpdoc_virtual_member_completions(
     items: &mut Vec<CompletionItem>,
     seen_labels: &mut HashSet<String>,
     access_mode: MemberAccessMode,
+    call_parentheses: bool,
 ) {
     for owner in index.get_type_hierarchy_symbols(class_fqn) {
         let Some(ref doc_comment) = owner.doc_comment else {
@@ -274,12 +560,16 @@ fn add_phpdoc_virtual_member_completions(
             if method.is_static || !seen_labels.insert(method.name.clone()) {
                 continue;
             }
-            items.push(phpdoc_method_completion_item(
+            let mut item = phpdoc_method_completion_item(
                 &owner.fqn,
                 method,
                 member_prefix,
                 symbol_sort_rank(PhpSymbolKind::Method),
-            ));
+            );
+            if call_parentheses {
+                apply_call_snippet(&mut item, !method.params.is_empty());
+            }
+            items.push(item);
         }
 
         for property in &phpdoc.properties {
@@ -304,6 +594,7 @@ fn add_phpdoc_static_virtual_method_completions(
     index: &WorkspaceIndex,
     items: &mut Vec<CompletionItem>,
     seen_labels: &mut HashSet<String>,
+    call_parentheses: bool,
 ) {
     for owner in index.get_type_hierarchy_symbols(class_fqn) {
         let Some(ref doc_comment) = owner.doc_comment else {
@@ -315,12 +606,16 @@ fn add_phpdoc_static_virtual_method_completions(
             if !method.is_static || !seen_labels.insert(method.name.clone()) {
                 continue;
             }
-            items.push(phpdoc_method_completion_item(
+            let mut item = phpdoc_method_completion_item(
                 &owner.fqn,
                 method,
                 member_prefix,
                 static_symbol_sort_rank(PhpSymbolKind::Method),
-            ));
+            );
+            if call_parentheses {
+                apply_call_snippet(&mut item, !method.params.is_empty());
+            }
+            items.push(item);
         }
     }
 }
@@ -444,6 +739,7 @@ fn provide_static_completions(
     member_prefix: &str,
     index: &WorkspaceIndex,
     current_class_fqn: Option<&str>,
+    call_parentheses: bool,
 ) -> Vec<CompletionItem> {
     let mut items = Vec::new();
 
@@ -474,11 +770,11 @@ fn provide_static_completions(
         ) {
             continue;
         }
-        items.push(symbol_to_completion_item(
-            &member,
-            true,
-            Some(member_prefix),
-        ));
+        let mut item = symbol_to_completion_item(&member, true, Some(member_prefix));
+        if call_parentheses && member.kind == PhpSymbolKind::Method {
+            apply_call_snippet(&mut item, symbol_has_parameters(&member));
+        }
+        items.push(item);
     }
     let mut seen_labels: HashSet<String> = items.iter().map(|item| item.label.clone()).collect();
     add_phpdoc_static_virtual_method_completions(
@@ -487,6 +783,7 @@ fn provide_static_completions(
         index,
         &mut items,
         &mut seen_labels,
+        call_parentheses,
     );
 
     sort_completion_items(&mut items);
@@ -515,46 +812,40 @@ fn class_pseudo_constant_completion_item(class_fqn: &str, member_prefix: &str) -
     item
 }
 
-/// Provide variable completions.
-fn provide_variable_completions(prefix: &str, file_symbols: &FileSymbols) -> Vec<CompletionItem> {
-    let mut items = Vec::new();
-    let mut seen = std::collections::HashSet::new();
-
-    // Collect variables from file symbols
-    // In PHP, common variables are $this, parameters, local vars
+/// Provide variable completions from the variables in scope at the cursor,
+/// followed by superglobals.
+fn provide_variable_completions(prefix: &str, in_scope: &[String]) -> Vec<CompletionItem> {
     let prefix_lower = prefix.to_lowercase();
-
-    // Add $this
-    if "this".starts_with(&prefix_lower) {
-        items.push(CompletionItem {
-            label: "$this".to_string(),
-            kind: Some(CompletionItemKind::VARIABLE),
-            sort_text: Some("0100_$this".to_string()),
-            filter_text: Some("$this this".to_string()),
-            ..Default::default()
-        });
-        seen.insert("$this".to_string());
-    }
-
-    // Extract parameters from method/function symbols
-    for sym in &file_symbols.symbols {
-        if let Some(ref sig) = sym.signature {
-            for param in &sig.params {
-                let var_name = format!("${}", param.name);
-                if !seen.contains(&var_name) && param.name.to_lowercase().starts_with(&prefix_lower)
-                {
-                    let detail = param.type_info.as_ref().map(|t| t.to_string());
-                    items.push(CompletionItem {
-                        label: var_name.clone(),
-                        kind: Some(CompletionItemKind::VARIABLE),
-                        detail,
-                        sort_text: Some(format!("0101_{}", param.name.to_ascii_lowercase())),
-                        filter_text: Some(format!("{} {}", var_name, param.name)),
-                        ..Default::default()
-                    });
-                    seen.insert(var_name);
-                }
-            }
+    let mut items: Vec<CompletionItem> = in_scope
+        .iter()
+        .filter_map(|var_name| {
+            let name = var_name.trim_start_matches('$');
+            name.to_lowercase()
+                .starts_with(&prefix_lower)
+                .then(|| CompletionItem {
+                    label: var_name.clone(),
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    sort_text: Some(if name == "this" {
+                        "0100_$this".to_string()
+                    } else {
+                        format!("0101_{}", name.to_ascii_lowercase())
+                    }),
+                    filter_text: Some(format!("{} {}", var_name, name)),
+                    ..Default::default()
+                })
+        })
+        .collect();
+    for var_name in SUPERGLOBALS {
+        let name = var_name.trim_start_matches('$');
+        if name.to_lowercase().starts_with(&prefix_lower) {
+            items.push(CompletionItem {
+                label: var_name.to_string(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: Some("superglobal".to_string()),
+                sort_text: Some(format!("0103_{}", name.to_ascii_lowercase())),
+                filter_text: Some(format!("{} {}", var_name, name)),
+                ..Default::default()
+            });
         }
     }
 
@@ -563,17 +854,26 @@ fn provide_variable_completions(prefix: &str, file_symbols: &FileSymbols) -> Vec
 }
 
 /// Provide namespace/class completions.
-fn provide_namespace_completions(prefix: &str, index: &WorkspaceIndex) -> Vec<CompletionItem> {
-    provide_namespace_completions_with_options(prefix, index, false)
+fn provide_namespace_completions(
+    prefix: &str,
+    index: &WorkspaceIndex,
+    file_symbols: &FileSymbols,
+) -> Vec<CompletionItem> {
+    provide_namespace_completions_with_options(prefix, index, file_symbols, false)
 }
 
-fn provide_use_statement_completions(prefix: &str, index: &WorkspaceIndex) -> Vec<CompletionItem> {
-    provide_namespace_completions_with_options(prefix, index, true)
+fn provide_use_statement_completions(
+    prefix: &str,
+    index: &WorkspaceIndex,
+    file_symbols: &FileSymbols,
+) -> Vec<CompletionItem> {
+    provide_namespace_completions_with_options(prefix, index, file_symbols, true)
 }
 
 fn provide_namespace_completions_with_options(
     prefix: &str,
     index: &WorkspaceIndex,
+    file_symbols: &FileSymbols,
     insert_fqn: bool,
 ) -> Vec<CompletionItem> {
     let mut items = Vec::new();
@@ -584,27 +884,69 @@ fn provide_namespace_completions_with_options(
             let mut item = CompletionItem {
                 label: sym.name.clone(),
                 kind: Some(symbol_kind_to_completion_kind(sym.kind)),
-                detail: Some(sym.fqn.clone()),
+                detail: Some(sym.fqn.to_string()),
                 sort_text: Some(format!(
-                    "0300_{}_{}_{}",
+                    "0300_{}_{}_{}_{}",
                     match_rank,
+                    symbol_origin(sym, file_symbols).sort_rank(),
                     sym.name.to_ascii_lowercase(),
                     sym.fqn.to_ascii_lowercase()
                 )),
                 filter_text: Some(format!("{} {}", sym.name, sym.fqn)),
-                data: Some(serde_json::Value::String(sym.fqn.clone())),
+                data: Some(serde_json::Value::String(sym.fqn.to_string())),
                 ..Default::default()
             };
             if insert_fqn {
-                item.insert_text = Some(sym.fqn.clone());
+                item.insert_text = Some(sym.fqn.to_string());
             }
             items.push(item);
         }
     }
 
-    // Limit results
     sort_completion_items(&mut items);
-    items.truncate(100);
+    items
+}
+
+/// Provide attribute class completions inside `#[...]`: concrete classes
+/// that are themselves marked `#[Attribute]`.
+fn provide_attribute_completions(
+    prefix: &str,
+    index: &WorkspaceIndex,
+    file_symbols: &FileSymbols,
+) -> Vec<CompletionItem> {
+    let mut items = Vec::new();
+
+    for entry in index.types.iter() {
+        let sym = entry.value();
+        if sym.kind != PhpSymbolKind::Class
+            || sym.modifiers.is_abstract
+            || !sym
+                .attributes
+                .iter()
+                .any(|attribute| attribute.has_name("Attribute"))
+        {
+            continue;
+        }
+        if let Some(match_rank) = namespace_completion_match_rank(&sym.name, &sym.fqn, prefix) {
+            items.push(CompletionItem {
+                label: sym.name.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(sym.fqn.to_string()),
+                sort_text: Some(format!(
+                    "0300_{}_{}_{}_{}",
+                    match_rank,
+                    symbol_origin(sym, file_symbols).sort_rank(),
+                    sym.name.to_ascii_lowercase(),
+                    sym.fqn.to_ascii_lowercase()
+                )),
+                filter_text: Some(format!("{} {}", sym.name, sym.fqn)),
+                data: Some(serde_json::Value::String(sym.fqn.to_string())),
+                ..Default::default()
+            });
+        }
+    }
+
+    sort_completion_items(&mut items);
     items
 }
 
@@ -626,6 +968,8 @@ fn namespace_completion_match_rank(name: &str, fqn: &str, prefix: &str) -> Optio
         Some("0200")
     } else if name_lower.starts_with(&prefix_lower) || fqn_lower.starts_with(&prefix_lower) {
         Some("0300")
+    } else if match_quality(name, prefix) == Some(MatchQuality::CamelCase) {
+        Some("0500")
     } else if name_lower.contains(&prefix_lower) || fqn_lower.contains(&prefix_lower) {
         Some("0900")
     } else {
@@ -633,59 +977,196 @@ fn namespace_completion_match_rank(name: &str, fqn: &str, prefix: &str) -> Optio
     }
 }
 
+/// Provide completions inside a `/** */` doc comment.
+fn provide_phpdoc_completions(
+    prefix: &str,
+    position: &PhpDocPosition,
+    index: &WorkspaceIndex,
+    file_symbols: &FileSymbols,
+) -> Vec<CompletionItem> {
+    let prefix_lower = prefix.to_lowercase();
+    let mut items = Vec::new();
+
+    match position {
+        PhpDocPosition::Tag => {
+            for (tag, synopsis) in PHPDOC_TAGS {
+                if tag.to_lowercase().starts_with(&prefix_lower) {
+                    items.push(CompletionItem {
+                        label: format!("@{tag}"),
+                        kind: Some(CompletionItemKind::KEYWORD),
+                        detail: Some(synopsis.to_string()),
+                        sort_text: Some(format!("0001_{tag}")),
+                        filter_text: Some(tag.to_string()),
+                        insert_text: Some(tag.to_string()),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        PhpDocPosition::Type => {
+            let keywords = [
+                TYPE_KEYWORDS,
+                RETURN_ONLY_TYPE_KEYWORDS,
+                PHPDOC_TYPE_KEYWORDS,
+            ];
+            for keyword in keywords.into_iter().flatten() {
+                if keyword.starts_with(&prefix_lower) {
+                    items.push(keyword_completion_item(
+                        keyword,
+                        KeywordPosition::Type { return_type: true },
+                        None,
+                    ));
+                }
+            }
+            let name_prefix = prefix.rsplit('\\').next().unwrap_or(prefix);
+            for entry in index.types.iter() {
+                if let Some(item) =
+                    ranked_symbol_completion_item(entry.value(), name_prefix, file_symbols)
+                {
+                    items.push(item);
+                }
+            }
+        }
+        PhpDocPosition::ParamName { parameters } => {
+            for name in parameters {
+                if name.to_lowercase().starts_with(&prefix_lower) {
+                    items.push(CompletionItem {
+                        label: format!("${name}"),
+                        kind: Some(CompletionItemKind::VARIABLE),
+                        sort_text: Some(format!("0101_{}", name.to_ascii_lowercase())),
+                        filter_text: Some(format!("${name} {name}")),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    sort_completion_items(&mut items);
+    items
+}
+
+/// Completion item for an indexed class-like, function or constant that
+/// matches `prefix`, ranked by match quality and then by where it is
+/// declared relative to the current file.
+fn ranked_symbol_completion_item(
+    sym: &SymbolInfo,
+    prefix: &str,
+    file_symbols: &FileSymbols,
+) -> Option<CompletionItem> {
+    let quality = match_quality(&sym.name, prefix)?;
+    Some(CompletionItem {
+        label: sym.name.clone(),
+        kind: Some(symbol_kind_to_completion_kind(sym.kind)),
+        detail: Some(sym.fqn.to_string()),
+        sort_text: Some(format!(
+            "0300_{}_{}",
+            relevance_sort_rank(quality, symbol_origin(sym, file_symbols)),
+            sym.name.to_ascii_lowercase()
+        )),
+        filter_text: Some(format!("{} {}", sym.name, sym.fqn)),
+        commit_characters: (sym.kind == PhpSymbolKind::Function).then(|| vec!["(".to_string()]),
+        data: Some(serde_json::Value::String(sym.fqn.to_string())),
+        ..Default::default()
+    })
+}
+
 /// Provide free context completions (classes, functions, keywords).
-fn provide_free_completions(prefix: &str, index: &WorkspaceIndex) -> Vec<CompletionItem> {
+fn provide_free_completions(
+    prefix: &str,
+    position: KeywordPosition,
+    index: &WorkspaceIndex,
+    file_symbols: &FileSymbols,
+    snippets: &SnippetOptions,
+) -> Vec<CompletionItem> {
     let mut items = Vec::new();
     let prefix_lower = prefix.to_lowercase();
-
-    // Add matching keywords
-    for keyword in PHP_KEYWORDS {
+    let call_parentheses = snippets.call_parentheses;
+
+    // Add keywords valid at this position. Declaration snippets only make
+    // sense where a declaration can start.
+    let in_class_body = matches!(
+        position,
+        KeywordPosition::ClassBody {
+            after_modifier: false,
+            ..
+        }
+    );
+    let snippets = (snippets.enabled && (position == KeywordPosition::Statement || in_class_body))
+        .then_some(snippets);
+    for keyword in keywords_for_position(position) {
         if keyword.starts_with(&prefix_lower) {
-            items.push(keyword_completion_item(keyword));
+            items.push(keyword_completion_item(keyword, position, snippets));
         }
     }
-
-    // Add matching types
-    let results = index.search(prefix);
-    for sym in results {
-        items.push(CompletionItem {
-            label: sym.name.clone(),
-            kind: Some(symbol_kind_to_completion_kind(sym.kind)),
-            detail: Some(sym.fqn.clone()),
-            sort_text: Some(format!(
-                "0300_{}_{}",
-                completion_prefix_rank(&sym.name, Some(prefix)),
-                sym.name.to_ascii_lowercase()
-            )),
-            filter_text: Some(format!("{} {}", sym.name, sym.fqn)),
-            data: Some(serde_json::Value::String(sym.fqn.clone())),
-            ..Default::default()
-        });
+    if let Some(snippets) = snippets.filter(|_| in_class_body) {
+        for template in CLASS_BODY_SNIPPETS {
+            if template.label.starts_with(&prefix_lower) {
+                items.push(snippet_completion_item(template, position, snippets));
+            }
+        }
     }
 
-    // Add matching functions
-    for entry in index.functions.iter() {
+    let offer_types = !matches!(
+        position,
+        KeywordPosition::ClassHeader
+            | KeywordPosition::ClassBody {
+                after_modifier: false,
+                ..
+            }
+    );
+    let offer_functions = matches!(
+        position,
+        KeywordPosition::Statement | KeywordPosition::Expression
+    );
+
+    // Add matching types, then functions and constants where an expression
+    // can start.
+    let types = index.types.iter().filter(|_| offer_types);
+    let functions = index.functions.iter().filter(|_| offer_functions);
+    let constants = index.constants.iter().filter(|_| offer_functions);
+    for entry in types.chain(functions).chain(constants) {
         let sym = entry.value();
-        if sym.name.to_lowercase().starts_with(&prefix_lower) {
-            items.push(CompletionItem {
-                label: sym.name.clone(),
-                kind: Some(CompletionItemKind::FUNCTION),
-                detail: Some(sym.fqn.clone()),
-                sort_text: Some(format!("0200_{}", sym.name.to_ascii_lowercase())),
-                filter_text: Some(format!("{} {}", sym.name, sym.fqn)),
-                commit_characters: Some(vec!["(".to_string()]),
-                data: Some(serde_json::Value::String(sym.fqn.clone())),
-                ..Default::default()
-            });
+        if let Some(mut item) = ranked_symbol_completion_item(sym, prefix, file_symbols) {
+            if call_parentheses && sym.kind == PhpSymbolKind::Function {
+                apply_call_snippet(&mut item, symbol_has_parameters(sym));
+            }
+            items.push(item);
         }
     }
 
-    // Limit
     sort_completion_items(&mut items);
-    items.truncate(100);
     items
 }
 
+/// Turn a function or method item into a call: `name($1)$0` that opens
+/// signature help, or `name()` when nothing is expected.
+fn apply_call_snippet(item: &mut CompletionItem, has_parameters: bool) {
+    let name = item
+        .insert_text
+        .take()
+        .unwrap_or_else(|| item.label.clone());
+    if has_parameters {
+        item.insert_text = Some(format!("{name}($1)$0"));
+        item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+        item.command = Some(Command {
+            title: "Trigger parameter hints".to_string(),
+            command: "editor.action.triggerParameterHints".to_string(),
+            arguments: None,
+        });
+    } else {
+        item.insert_text = Some(format!("{name}()"));
+    }
+    // Typing `(` would otherwise commit the item and add a second one.
+    item.commit_characters = None;
+}
+
+fn symbol_has_parameters(sym: &SymbolInfo) -> bool {
+    sym.signature
+        .as_ref()
+        .is_some_and(|signature| !signature.params.is_empty())
+}
+
 /// Convert a SymbolInfo to a CompletionItem.
 fn symbol_to_completion_item(
     sym: &SymbolInfo,
@@ -714,7 +1195,7 @@ fn symbol_to_completion_item(
         detail,
         tags: if tags.is_empty() { None } else { Some(tags) },
         // Store FQN in data for resolve
-        data: Some(serde_json::Value::String(sym.fqn.clone())),
+        data: Some(serde_json::Value::String(sym.fqn.to_string())),
         ..Default::default()
     };
     item.sort_text = Some(format!(
@@ -808,30 +1289,28 @@ fn completion_prefix_rank(label: &str, member_prefix: Option<&str>) -> &'static
         return "1000";
     };
 
-    let normalized_label = label.trim_start_matches('$').to_ascii_lowercase();
-    let normalized_prefix = prefix.trim_start_matches('$').to_ascii_lowercase();
-
-    if normalized_label.starts_with(&normalized_prefix) {
-        "0000"
-    } else if normalized_label.contains(&normalized_prefix) {
-        "0100"
-    } else {
-        "1000"
+    match match_quality(label, prefix) {
+        Some(MatchQuality::Exact) => "0000",
+        Some(MatchQuality::Prefix) => "0010",
+        Some(MatchQuality::CamelCase) => "0050",
+        Some(MatchQuality::Substring) => "0100",
+        None => "1000",
     }
 }
 
-fn keyword_completion_item(keyword: &str) -> CompletionItem {
-    if let Some(snippet) = PHP_SNIPPETS.iter().find(|snippet| snippet.label == keyword) {
-        CompletionItem {
-            label: snippet.label.to_string(),
-            kind: Some(CompletionItemKind::SNIPPET),
-            detail: Some(snippet.detail.to_string()),
-            insert_text: Some(snippet.insert_text.to_string()),
-            insert_text_format: Some(InsertTextFormat::SNIPPET),
-            sort_text: Some(format!("0000_{}", snippet.label)),
-            filter_text: Some(snippet.label.to_string()),
-            ..Default::default()
-        }
+fn keyword_completion_item(
+    keyword: &str,
+    position: KeywordPosition,
+    snippets: Option<&SnippetOptions>,
+) -> CompletionItem {
+    let snippet = snippets.and_then(|options| {
+        PHP_SNIPPETS
+            .iter()
+            .find(|snippet| snippet.label == keyword)
+            .map(|snippet| (snippet, options))
+    });
+    if let Some((snippet, options)) = snippet {
+        snippet_completion_item(snippet, position, options)
     } else {
         CompletionItem {
             label: keyword.to_string(),
@@ -943,7 +1422,7 @@ fn find_current_class_fqn(file_symbols: &FileSymbols) -> Option<String> {
             | PhpSymbolKind::Interface
             | PhpSymbolKind::Trait
             | PhpSymbolKind::Enum => {
-                return Some(sym.fqn.clone());
+                return Some(sym.fqn.to_string());
             }
             _ => {}
         }
@@ -955,7 +1434,7 @@ fn find_current_class_fqn_at_range(
     file_symbols: &FileSymbols,
     range: (u32, u32, u32, u32),
 ) -> Option<String> {
-    current_class_symbol_at_range(file_symbols, range).map(|sym| sym.fqn.clone())
+    current_class_symbol_at_range(file_symbols, range).map(|sym| sym.fqn.to_string())
 }
 
 fn current_class_symbol_at_range(
@@ -1003,9 +1482,9 @@ mod tests {
     ) -> SymbolInfo {
         SymbolInfo {
             name: name.to_string(),
-            fqn: fqn.to_string(),
+            fqn: fqn.into(),
             kind,
-            uri: "file:///test.php".to_string(),
+            uri: "file:///test.php".into(),
             range: (0, 0, 0, 0),
             selection_range: (0, 0, 0, name.len() as u32),
             visibility,
@@ -1019,16 +1498,19 @@ mod tests {
                 Some(Signature {
                     params: vec![],
                     return_type: None,
+                    throws: Vec::new(),
                 })
             } else {
                 None
             },
-            parent_fqn: parent_fqn.map(str::to_string),
+            value: None,
+            parent_fqn: parent_fqn.map(Into::into),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         }
     }
 
@@ -1043,6 +1525,7 @@ mod tests {
         let file_symbols = FileSymbols::default();
         let ctx = CompletionContext::Free {
             prefix: "cla".to_string(),
+            position: KeywordPosition::Statement,
         };
         let items = provide_completions(&ctx, &index, &file_symbols);
         let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
@@ -1065,6 +1548,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_keyword_completion_follows_position() {
+        let index = WorkspaceIndex::new();
+        let file_symbols = FileSymbols::default();
+        let labels = |prefix: &str, position: KeywordPosition| -> Vec<String> {
+            let ctx = CompletionContext::Free {
+                prefix: prefix.to_string(),
+                position,
+            };
+            provide_completions(&ctx, &index, &file_symbols)
+                .into_iter()
+                .map(|item| item.label)
+                .collect()
+        };
+
+        let body = labels(
+            "p",
+            KeywordPosition::ClassBody {
+                in_enum: false,
+                after_modifier: false,
+            },
+        );
+        assert!(body.contains(&"public".to_string()));
+        assert!(body.contains(&"private".to_string()));
+        assert!(!body.contains(&"print".to_string()));
+
+        let typed = labels("", KeywordPosition::Type { return_type: false });
+        assert!(typed.contains(&"string".to_string()));
+        assert!(!typed.contains(&"void".to_string()));
+        assert!(
+            labels("v", KeywordPosition::Type { return_type: true }).contains(&"void".to_string())
+        );
+
+        let statement = labels("fore", KeywordPosition::Statement);
+        assert_eq!(statement, vec!["foreach".to_string()]);
+        assert!(!labels("rea", KeywordPosition::Expression).contains(&"readonly".to_string()));
+
+        let ctx = CompletionContext::Free {
+            prefix: "func".to_string(),
+            position: KeywordPosition::Expression,
+        };
+        let closure = provide_completions(&ctx, &index, &file_symbols);
+        assert_eq!(closure[0].label, "function");
+        assert_eq!(closure[0].kind, Some(CompletionItemKind::KEYWORD));
+        assert!(closure[0].insert_text.is_none());
+    }
+
+    #[test]
+    fn test_snippet_completions_follow_options_and_position() {
+        let index = WorkspaceIndex::new();
+        let file_symbols = FileSymbols::default();
+        let complete = |prefix: &str, position: KeywordPosition, snippets: &SnippetOptions| {
+            let ctx = CompletionContext::Free {
+                prefix: prefix.to_string(),
+                position,
+            };
+            provide_completions_at_range(&ctx, &index, &file_symbols, (0, 0, 0, 0), snippets)
+        };
+        let options = SnippetOptions {
+            enabled: true,
+            class_namespace: Some("App\\Service".to_string()),
+            class_name: Some("UserService".to_string()),
+            ..SnippetOptions::default()
+        };
+
+        let class_items = complete("class", KeywordPosition::Statement, &options);
+        let class_item = class_items
+            .iter()
+            .find(|item| item.label == "class")
+            .expect("class snippet");
+        assert_eq!(class_item.kind, Some(CompletionItemKind::SNIPPET));
+        assert_eq!(
+            class_item.insert_text.as_deref(),
+            Some("namespace App\\\\Service;\n\nclass ${1:UserService}\n{\n    $0\n}")
+        );
+
+        let body = KeywordPosition::ClassBody {
+            in_enum: false,
+            after_modifier: false,
+        };
+        let pubf = complete("pub", body, &options);
+        let pubf = pubf
+            .iter()
+            .find(|item| item.label == "pubf")
+            .expect("pubf snippet");
+        assert_eq!(pubf.insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert!(pubf
+            .insert_text
+            .as_deref()
+            .is_some_and(|text| text.starts_with("public function ${1:name}")));
+        let labels: Vec<String> = complete("", body, &options)
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert!(labels.contains(&"getter".to_string()));
+        assert!(labels.contains(&"setter".to_string()));
+        assert!(!complete("pub", KeywordPosition::Statement, &options)
+            .iter()
+            .any(|item| item.label == "pubf"));
+
+        let disabled = SnippetOptions {
+            enabled: false,
+            ..SnippetOptions::default()
+        };
+        let plain = complete("class", KeywordPosition::Statement, &disabled);
+        assert!(plain
+            .iter()
+            .all(|item| item.kind != Some(CompletionItemKind::SNIPPET)));
+        assert!(complete("pub", body, &disabled)
+            .iter()
+            .all(|item| item.label != "pubf"));
+    }
+
     #[test]
     fn test_class_completion() {
         let index = WorkspaceIndex::new();
@@ -1073,9 +1669,9 @@ mod tests {
             use_statements: vec![],
             symbols: vec![SymbolInfo {
                 name: "UserService".to_string(),
-                fqn: "App\\UserService".to_string(),
+                fqn: "App\\UserService".into(),
                 kind: PhpSymbolKind::Class,
-                uri: "file:///test.php".to_string(),
+                uri: "file:///test.php".into(),
                 range: (0, 0, 10, 0),
                 selection_range: (0, 6, 0, 17),
                 visibility: Visibility::Public,
@@ -1083,12 +1679,14 @@ mod tests {
                 attributes: vec![],
                 doc_comment: None,
                 signature: None,
+                value: None,
                 parent_fqn: None,
                 extends: vec![],
                 implements: vec![],
                 traits: vec![],
                 templates: vec![],
                 template_bindings: vec![],
+                trait_adaptations: vec![],
             }],
             ..Default::default()
         };
@@ -1096,6 +1694,7 @@ mod tests {
 
         let ctx = CompletionContext::Free {
             prefix: "User".to_string(),
+            position: KeywordPosition::Expression,
         };
         let items = provide_completions(&ctx, &index, &file_symbols);
         assert!(
@@ -1114,7 +1713,7 @@ mod tests {
             Visibility::Public,
             false,
         );
-        class.uri = "file:///vendor/ClassName.php".to_string();
+        class.uri = "file:///vendor/ClassName.php".into();
         let symbols = FileSymbols {
             symbols: vec![class],
             ..Default::default()
@@ -1174,6 +1773,122 @@ mod tests {
         assert_eq!(labels, vec!["ZedService", "AlphaNoise"]);
     }
 
+    #[test]
+    fn test_attribute_completion_only_offers_attribute_classes() {
+        let attribute_class = |name: &str, fqn: &str| {
+            let mut symbol = make_symbol(
+                name,
+                fqn,
+                PhpSymbolKind::Class,
+                None,
+                Visibility::Public,
+                false,
+            );
+            symbol.attributes.push(SymbolAttribute {
+                text: "#[\\Attribute]".to_string(),
+                range: (0, 0, 0, 12),
+                names: vec!["Attribute".to_string()],
+            });
+            symbol
+        };
+        let mut abstract_attribute = attribute_class("RouteBase", "App\\Routing\\RouteBase");
+        abstract_attribute.modifiers.is_abstract = true;
+        let file_symbols = FileSymbols {
+            namespace: Some("App".to_string()),
+            use_statements: vec![],
+            symbols: vec![
+                attribute_class("Route", "App\\Routing\\Route"),
+                abstract_attribute,
+                make_symbol(
+                    "RouteCollection",
+                    "App\\Routing\\RouteCollection",
+                    PhpSymbolKind::Class,
+                    None,
+                    Visibility::Public,
+                    false,
+                ),
+            ],
+            ..Default::default()
+        };
+        let index = WorkspaceIndex::new();
+        index.update_file("file:///test.php", file_symbols.clone());
+
+        let ctx = CompletionContext::Attribute {
+            prefix: "Rou".to_string(),
+        };
+        let items = provide_completions(&ctx, &index, &file_symbols);
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["Route"]);
+        assert_eq!(items[0].detail.as_deref(), Some("App\\Routing\\Route"));
+    }
+
+    #[test]
+    fn test_phpdoc_completions_for_tags_types_and_params() {
+        let file_symbols = FileSymbols {
+            namespace: Some("App".to_string()),
+            use_statements: vec![],
+            symbols: vec![
+                make_symbol(
+                    "UserRepository",
+                    "App\\UserRepository",
+                    PhpSymbolKind::Class,
+                    None,
+                    Visibility::Public,
+                    false,
+                ),
+                make_symbol(
+                    "user_name",
+                    "App\\user_name",
+                    PhpSymbolKind::Function,
+                    None,
+                    Visibility::Public,
+                    false,
+                ),
+            ],
+            ..Default::default()
+        };
+        let index = WorkspaceIndex::new();
+        index.update_file("file:///test.php", file_symbols.clone());
+        let labels = |prefix: &str, position: PhpDocPosition| {
+            let ctx = CompletionContext::PhpDoc {
+                prefix: prefix.to_string(),
+                position,
+            };
+            provide_completions(&ctx, &index, &file_symbols)
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<_>>()
+        };
+
+        let tags = labels("pro", PhpDocPosition::Tag);
+        assert_eq!(tags, vec!["@property", "@property-read", "@property-write"]);
+        let items = provide_completions(
+            &CompletionContext::PhpDoc {
+                prefix: "ret".to_string(),
+                position: PhpDocPosition::Tag,
+            },
+            &index,
+            &file_symbols,
+        );
+        assert_eq!(items[0].insert_text.as_deref(), Some("return"));
+
+        let types = labels("u", PhpDocPosition::Type);
+        assert!(types.contains(&"UserRepository".to_string()), "{types:?}");
+        assert!(!types.contains(&"user_name".to_string()), "{types:?}");
+        let types = labels("non-empty", PhpDocPosition::Type);
+        assert!(types.contains(&"non-empty-string".to_string()), "{types:?}");
+        let types = labels("App\\User", PhpDocPosition::Type);
+        assert_eq!(types, vec!["UserRepository"]);
+
+        let params = labels(
+            "n",
+            PhpDocPosition::ParamName {
+                parameters: vec!["id".to_string(), "name".to_string()],
+            },
+        );
+        assert_eq!(params, vec!["$name"]);
+    }
+
     #[test]
     fn test_namespace_completion_keeps_prefix_matches_before_truncating_contains_noise() {
         let mut symbols = Vec::new();
@@ -1251,6 +1966,7 @@ mod tests {
 
         let ctx = CompletionContext::Free {
             prefix: "Ty".to_string(),
+            position: KeywordPosition::Expression,
         };
         let items = provide_completions(&ctx, &index, &file_symbols);
 
@@ -1271,9 +1987,9 @@ mod tests {
             use_statements: vec![],
             symbols: vec![SymbolInfo {
                 name: "test".to_string(),
-                fqn: "test".to_string(),
+                fqn: "test".into(),
                 kind: PhpSymbolKind::Function,
-                uri: "file:///test.php".to_string(),
+                uri: "file:///test.php".into(),
                 range: (0, 0, 5, 0),
                 selection_range: (0, 9, 0, 13),
                 visibility: Visibility::Public,
@@ -1282,21 +1998,25 @@ mod tests {
                 doc_comment: None,
                 signature: Some(Signature {
                     params: vec![ParamInfo {
-                        name: "username".to_string(),
+                        name: "userId".to_string(),
                         type_info: Some(TypeInfo::Simple("string".to_string())),
                         default_value: None,
                         is_variadic: false,
                         is_by_ref: false,
                         is_promoted: false,
+                        attributes: vec![],
                     }],
                     return_type: None,
+                    throws: Vec::new(),
                 }),
+                value: None,
                 parent_fqn: None,
                 extends: vec![],
                 implements: vec![],
                 traits: vec![],
                 templates: vec![],
                 template_bindings: vec![],
+                trait_adaptations: vec![],
             }],
             ..Default::default()
         };
@@ -1304,12 +2024,31 @@ mod tests {
 
         let ctx = CompletionContext::Variable {
             prefix: "user".to_string(),
+            in_scope: vec!["$this".to_string(), "$username".to_string()],
         };
         let items = provide_completions(&ctx, &index, &file_symbols);
-        assert!(
-            items.iter().any(|i| i.label == "$username"),
-            "Should find $username"
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["$username"],
+            "parameters of other functions are out of scope"
         );
+
+        let ctx = CompletionContext::Variable {
+            prefix: String::new(),
+            in_scope: vec!["$username".to_string(), "$this".to_string()],
+        };
+        let items = provide_completions(&ctx, &index, &file_symbols);
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(&labels[..3], ["$this", "$username", "$_COOKIE"]);
+
+        let ctx = CompletionContext::Variable {
+            prefix: "_se".to_string(),
+            in_scope: vec![],
+        };
+        let items = provide_completions(&ctx, &index, &file_symbols);
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["$_SERVER", "$_SESSION"]);
     }
 
     #[test]
@@ -1320,9 +2059,9 @@ mod tests {
             symbols: vec![
                 SymbolInfo {
                     name: "Baz".to_string(),
-                    fqn: "App\\Test\\Baz".to_string(),
+                    fqn: "App\\Test\\Baz".into(),
                     kind: PhpSymbolKind::Class,
-                    uri: "file:///test.php".to_string(),
+                    uri: "file:///test.php".into(),
                     range: (0, 0, 10, 0),
                     selection_range: (0, 6, 0, 9),
                     visibility: Visibility::Public,
@@ -1330,18 +2069,20 @@ mod tests {
                     attributes: vec![],
                     doc_comment: None,
                     signature: None,
+                    value: None,
                     parent_fqn: None,
                     extends: vec![],
                     implements: vec![],
                     traits: vec![],
                     templates: vec![],
                     template_bindings: vec![],
+                    trait_adaptations: vec![],
                 },
                 SymbolInfo {
                     name: "test".to_string(),
-                    fqn: "App\\Test\\Baz::test".to_string(),
+                    fqn: "App\\Test\\Baz::test".into(),
                     kind: PhpSymbolKind::Method,
-                    uri: "file:///test.php".to_string(),
+                    uri: "file:///test.php".into(),
                     range: (2, 4, 2, 20),
                     selection_range: (2, 13, 2, 17),
                     visibility: Visibility::Public,
@@ -1351,13 +2092,16 @@ mod tests {
                     signature: Some(Signature {
                         params: vec![],
                         return_type: None,
+                        throws: Vec::new(),
                     }),
-                    parent_fqn: Some("App\\Test\\Baz".to_string()),
+                    value: None,
+                    parent_fqn: Some("App\\Test\\Baz".into()),
                     extends: vec![],
                     implements: vec![],
                     traits: vec![],
                     templates: vec![],
                     template_bindings: vec![],
+                    trait_adaptations: vec![],
                 },
             ],
             ..Default::default()
@@ -1967,7 +2711,13 @@ mod tests {
             class_fqn: Some("App\\Child".to_string()),
             access_mode: MemberAccessMode::Read,
         };
-        let items = provide_completions_at_range(&ctx, &index, &file_symbols, (10, 12, 10, 12));
+        let items = provide_completions_at_range(
+            &ctx,
+            &index,
+            &file_symbols,
+            (10, 12, 10, 12),
+            &SnippetOptions::default(),
+        );
         let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
 
         assert!(
@@ -2027,7 +2777,13 @@ mod tests {
             class_fqn: Some("App\\Feature".to_string()),
             access_mode: MemberAccessMode::Read,
         };
-        let items = provide_completions_at_range(&ctx, &index, &file_symbols, (8, 12, 8, 12));
+        let items = provide_completions_at_range(
+            &ctx,
+            &index,
+            &file_symbols,
+            (8, 12, 8, 12),
+            &SnippetOptions::default(),
+        );
         let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
 
         assert!(
@@ -2093,7 +2849,13 @@ mod tests {
             class_fqn: Some(anonymous_fqn.to_string()),
             access_mode: MemberAccessMode::Read,
         };
-        let items = provide_completions_at_range(&ctx, &index, &file_symbols, (12, 16, 12, 16));
+        let items = provide_completions_at_range(
+            &ctx,
+            &index,
+            &file_symbols,
+            (12, 16, 12, 16),
+            &SnippetOptions::default(),
+        );
         let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
 
         assert!(
@@ -2170,7 +2932,13 @@ mod tests {
                 member_prefix: String::new(),
                 class_fqn: "App\\Child".to_string(),
             };
-            let items = provide_completions_at_range(&ctx, &index, &file_symbols, (10, 12, 10, 12));
+            let items = provide_completions_at_range(
+                &ctx,
+                &index,
+                &file_symbols,
+                (10, 12, 10, 12),
+                &SnippetOptions::default(),
+            );
             let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
 
             assert!(
@@ -2188,7 +2956,13 @@ mod tests {
             member_prefix: String::new(),
             class_fqn: "App\\Base".to_string(),
         };
-        let items = provide_completions_at_range(&ctx, &index, &file_symbols, (10, 12, 10, 12));
+        let items = provide_completions_at_range(
+            &ctx,
+            &index,
+            &file_symbols,
+            (10, 12, 10, 12),
+            &SnippetOptions::default(),
+        );
         let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
 
         assert!(
@@ -2200,4 +2974,101 @@ mod tests {
             "parent:: should not expose private parent members"
         );
     }
+
+    #[test]
+    fn test_free_completion_ranks_by_match_quality_then_origin() {
+        let class_in = |name: &str, fqn: &str, uri: &str| {
+            let mut class = make_symbol(
+                name,
+                fqn,
+                PhpSymbolKind::Class,
+                None,
+                Visibility::Public,
+                false,
+            );
+            class.uri = uri.into();
+            class
+        };
+        let index = WorkspaceIndex::new();
+        for class in [
+            class_in("UserStub", "UserStub", "phpstub://standard/user.php"),
+            class_in(
+                "UserVendor",
+                "Acme\\UserVendor",
+                "file:///project/vendor/acme/UserVendor.php",
+            ),
+            class_in(
+                "UserMapper",
+                "App\\Domain\\UserMapper",
+                "file:///project/src/Domain.php",
+            ),
+            class_in(
+                "UserResource",
+                "App\\Http\\UserResource",
+                "file:///project/src/Resource.php",
+            ),
+            class_in(
+                "UrlRouter",
+                "App\\UrlRouter",
+                "file:///project/src/UrlRouter.php",
+            ),
+            class_in("Future", "App\\Future", "file:///project/src/Future.php"),
+        ] {
+            let uri = class.uri.clone();
+            index.update_file(
+                &uri,
+                FileSymbols {
+                    symbols: vec![class],
+                    ..Default::default()
+                },
+            );
+        }
+        let current = class_in(
+            "UserRepository",
+            "App\\Http\\UserRepository",
+            "file:///project/src/Http.php",
+        );
+        let file_symbols = FileSymbols {
+            namespace: Some("App\\Http".to_string()),
+            symbols: vec![current],
+            ..Default::default()
+        };
+        index.update_file("file:///project/src/Http.php", file_symbols.clone());
+
+        let labels = |prefix: &str| -> Vec<String> {
+            let ctx = CompletionContext::Free {
+                prefix: prefix.to_string(),
+                position: KeywordPosition::Expression,
+            };
+            provide_completions(&ctx, &index, &file_symbols)
+                .into_iter()
+                .filter(|item| item.kind == Some(CompletionItemKind::CLASS))
+                .map(|item| item.label)
+                .collect()
+        };
+
+        assert_eq!(
+            labels("User"),
+            vec![
+                "UserRepository",
+                "UserResource",
+                "UserMapper",
+                "UserVendor",
+                "UserStub"
+            ]
+        );
+        assert_eq!(
+            labels("ur"),
+            vec!["UrlRouter", "UserRepository", "UserResource", "Future"]
+        );
+
+        let ctx = CompletionContext::Free {
+            prefix: "usre".to_string(),
+            position: KeywordPosition::Expression,
+        };
+        let items = provide_completions(&ctx, &index, &file_symbols);
+        assert_eq!(items[0].label, "UserRepository");
+        assert_eq!(items[0].preselect, Some(true));
+        assert!(items[1..].iter().all(|item| item.preselect.is_none()));
+    }
 }
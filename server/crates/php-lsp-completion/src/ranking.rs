@@ -0,0 +1,73 @@
+//! Relevance scoring for completion items.
+//!
+//! Candidates are ordered first by how well their name matches the typed
+//! prefix (exact, prefix, camelCase humps, substring) and then by where the
+//! symbol is declared, so symbols of the current file and namespace come
+//! before other project symbols, vendor packages and PHP stubs.
+
+use lsp_types::CompletionItem;
+pub use php_lsp_index::symbol_query::{match_quality, MatchQuality};
+use php_lsp_types::{FileSymbols, SymbolInfo};
+
+/// Where a symbol is declared relative to the file being edited. Closer
+/// origins order first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SymbolOrigin {
+    CurrentFile,
+    CurrentNamespace,
+    Project,
+    Vendor,
+    Stub,
+}
+
+impl SymbolOrigin {
+    pub fn sort_rank(self) -> u8 {
+        match self {
+            SymbolOrigin::CurrentFile => 0,
+            SymbolOrigin::CurrentNamespace => 1,
+            SymbolOrigin::Project => 2,
+            SymbolOrigin::Vendor => 3,
+            SymbolOrigin::Stub => 4,
+        }
+    }
+}
+
+/// Classify where `sym` is declared relative to the edited file.
+pub fn symbol_origin(sym: &SymbolInfo, file_symbols: &FileSymbols) -> SymbolOrigin {
+    if file_symbols
+        .symbols
+        .iter()
+        .any(|declared| declared.kind == sym.kind && declared.fqn == sym.fqn)
+    {
+        SymbolOrigin::CurrentFile
+    } else if sym.uri.starts_with("phpstub://") {
+        SymbolOrigin::Stub
+    } else if sym.uri.contains("/vendor/") {
+        SymbolOrigin::Vendor
+    } else if symbol_namespace(&sym.fqn)
+        .eq_ignore_ascii_case(file_symbols.namespace.as_deref().unwrap_or_default())
+    {
+        SymbolOrigin::CurrentNamespace
+    } else {
+        SymbolOrigin::Project
+    }
+}
+
+fn symbol_namespace(fqn: &str) -> &str {
+    fqn.trim_start_matches('\\')
+        .rsplit_once('\\')
+        .map_or("", |(namespace, _)| namespace)
+}
+
+/// Sort rank for an indexed symbol, combining match quality and origin.
+pub fn relevance_sort_rank(quality: MatchQuality, origin: SymbolOrigin) -> String {
+    format!("{:02}{:02}", quality.sort_rank(), origin.sort_rank())
+}
+
+/// Mark the first of the sorted `items` as preselected so clients select
+/// the most relevant candidate rather than the first one alphabetically.
+pub fn preselect_best_item(items: &mut [CompletionItem]) {
+    if let Some(item) = items.first_mut() {
+        item.preselect = Some(true);
+    }
+}
@@ -0,0 +1,420 @@
+//! Lexical string state at the cursor.
+//!
+//! Completion runs on half-typed code where an unterminated string swallows
+//! the rest of the file in the CST, so the state is recovered by lexing the
+//! source before the cursor: PHP tags, comments, quoted strings, heredoc and
+//! nowdoc bodies, and `{$...}` / `${...}` interpolation.
+
+/// Where the cursor sits relative to string literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringPosition {
+    /// PHP code, including code inside `{$...}` interpolation.
+    Code,
+    /// Single-quoted string or nowdoc body: nothing is interpolated.
+    Literal,
+    /// Literal part of a double-quoted string or heredoc body, where simple
+    /// `$var` and `$var->prop` interpolation applies.
+    Interpolating,
+    /// Inside `${...}`, where the content names a variable.
+    DollarBrace {
+        /// Byte offset right after `${`.
+        start: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Frame {
+    Single,
+    Double,
+    Heredoc(String),
+    Nowdoc(String),
+    Interpolation {
+        depth: usize,
+        dollar_brace: bool,
+        start: usize,
+    },
+}
+
+/// Lexer state at the end of a source prefix.
+#[derive(Debug, Clone, Default)]
+pub struct StringScan {
+    frames: Vec<Frame>,
+    in_comment: bool,
+    block_comment_start: Option<usize>,
+    in_inline_html: bool,
+}
+
+impl StringScan {
+    /// Classify the cursor position.
+    pub fn position(&self) -> StringPosition {
+        match self.frames.last() {
+            None => StringPosition::Code,
+            Some(Frame::Single | Frame::Nowdoc(_)) => StringPosition::Literal,
+            Some(Frame::Double | Frame::Heredoc(_)) => StringPosition::Interpolating,
+            Some(Frame::Interpolation {
+                dollar_brace: true,
+                start,
+                ..
+            }) => StringPosition::DollarBrace { start: *start },
+            Some(Frame::Interpolation { .. }) => StringPosition::Code,
+        }
+    }
+
+    /// Whether the cursor is inside any string, interpolation included.
+    pub fn in_string(&self) -> bool {
+        !self.frames.is_empty() && !self.in_comment && !self.in_inline_html
+    }
+
+    /// Whether the cursor is in inline HTML outside `<?php ... ?>` blocks.
+    pub fn in_inline_html(&self) -> bool {
+        self.in_inline_html
+    }
+
+    /// Byte offset of the `/**` that opens the doc comment around the cursor.
+    pub fn doc_comment_start(&self, source: &str) -> Option<usize> {
+        self.block_comment_start
+            .filter(|&start| source[start..].starts_with("/**"))
+    }
+
+    /// Text that closes every open interpolation and string, innermost first.
+    fn closers(&self) -> String {
+        let mut out = String::new();
+        for frame in self.frames.iter().rev() {
+            match frame {
+                Frame::Single => out.push('\''),
+                Frame::Double => out.push('"'),
+                Frame::Heredoc(label) | Frame::Nowdoc(label) => {
+                    out.push('\n');
+                    out.push_str(label);
+                }
+                Frame::Interpolation { depth, .. } => {
+                    out.extend(std::iter::repeat_n('}', depth + 1));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Lex `source_before` (the text up to the cursor) and report the string
+/// state at its end.
+pub fn scan_strings(source_before: &str) -> StringScan {
+    let bytes = source_before.as_bytes();
+    let mut scan = StringScan {
+        in_inline_html: true,
+        ..StringScan::default()
+    };
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        if scan.in_inline_html {
+            match find_open_tag(source_before, i) {
+                Some(end) => {
+                    scan.in_inline_html = false;
+                    i = end;
+                    continue;
+                }
+                None => return scan,
+            }
+        }
+
+        match scan.frames.last_mut() {
+            Some(Frame::Single) => {
+                match bytes[i] {
+                    b'\\' => i += 1,
+                    b'\'' => {
+                        scan.frames.pop();
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            Some(Frame::Double) => match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => {
+                    scan.frames.pop();
+                    i += 1;
+                }
+                _ => i = enter_interpolation(&mut scan.frames, bytes, i),
+            },
+            Some(Frame::Heredoc(label)) => {
+                if let Some(end) = closing_label_end(source_before, i, label) {
+                    scan.frames.pop();
+                    i = end;
+                } else if bytes[i] == b'\\' {
+                    i += 2;
+                } else {
+                    i = enter_interpolation(&mut scan.frames, bytes, i);
+                }
+            }
+            Some(Frame::Nowdoc(label)) => {
+                if let Some(end) = closing_label_end(source_before, i, label) {
+                    scan.frames.pop();
+                    i = end;
+                } else {
+                    i += 1;
+                }
+            }
+            Some(Frame::Interpolation { .. }) | None => {
+                if let Some(Frame::Interpolation { depth, .. }) = scan.frames.last_mut() {
+                    match bytes[i] {
+                        b'{' => *depth += 1,
+                        b'}' if *depth == 0 => {
+                            scan.frames.pop();
+                            i += 1;
+                            continue;
+                        }
+                        b'}' => *depth -= 1,
+                        _ => {}
+                    }
+                }
+                match scan_code_token(source_before, i, &mut scan) {
+                    Some(next) => i = next,
+                    None => return scan,
+                }
+            }
+        }
+    }
+
+    scan
+}
+
+/// Close every string open at `cursor_offset` so the remainder of the file
+/// parses as code again. Returns `None` when the cursor is not in a string.
+///
+/// A placeholder identifier keeps `{$obj->` well formed, and the rest of the
+/// cursor line is dropped since it belongs to the unterminated literal.
+pub fn close_strings_at(source: &str, cursor_offset: usize) -> Option<String> {
+    let cursor_offset = cursor_offset.min(source.len());
+    let before = &source[..cursor_offset];
+    let scan = scan_strings(before);
+    if !scan.in_string() {
+        return None;
+    }
+
+    let line_end = source[cursor_offset..]
+        .find('\n')
+        .map_or(source.len(), |offset| cursor_offset + offset);
+    let mut repaired = String::with_capacity(source.len() + 16);
+    repaired.push_str(before);
+    repaired.push('x');
+    repaired.push_str(&scan.closers());
+    repaired.push(';');
+    repaired.push_str(&source[line_end..]);
+    Some(repaired)
+}
+
+/// Advance past one code token starting at `i`, entering strings and
+/// skipping comments. Returns `None` when the prefix ends inside a comment.
+fn scan_code_token(source: &str, i: usize, scan: &mut StringScan) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let rest = &source[i..];
+    match bytes[i] {
+        b'\'' => {
+            scan.frames.push(Frame::Single);
+            Some(i + 1)
+        }
+        b'"' => {
+            scan.frames.push(Frame::Double);
+            Some(i + 1)
+        }
+        b'#' if !rest.starts_with("#[") => skip_line_comment(source, i, scan),
+        b'/' if rest.starts_with("//") => skip_line_comment(source, i, scan),
+        b'/' if rest.starts_with("/*") => match rest[2..].find("*/") {
+            Some(end) => Some(i + 2 + end + 2),
+            None => {
+                scan.in_comment = true;
+                scan.block_comment_start = Some(i);
+                None
+            }
+        },
+        b'?' if rest.starts_with("?>") && scan.frames.is_empty() => {
+            scan.in_inline_html = true;
+            Some(i + 2)
+        }
+        b'<' if rest.starts_with("<<<") => match heredoc_start(rest) {
+            Some((frame, len)) => {
+                scan.frames.push(frame);
+                Some(i + len)
+            }
+            None => Some(i + 3),
+        },
+        _ => Some(i + rest.chars().next().map_or(1, char::len_utf8)),
+    }
+}
+
+fn skip_line_comment(source: &str, i: usize, scan: &mut StringScan) -> Option<usize> {
+    let rest = &source[i..];
+    let newline = rest.find('\n');
+    let close_tag = rest.find("?>").filter(|_| scan.frames.is_empty());
+    match (newline, close_tag) {
+        (Some(newline), Some(tag)) if tag < newline => Some(i + tag),
+        (Some(newline), _) => Some(i + newline + 1),
+        (None, Some(tag)) => Some(i + tag),
+        (None, None) => {
+            scan.in_comment = true;
+            None
+        }
+    }
+}
+
+/// Push an interpolation frame for `{$` or `${` at `i`, or step one character.
+fn enter_interpolation(frames: &mut Vec<Frame>, bytes: &[u8], i: usize) -> usize {
+    match (bytes[i], bytes.get(i + 1)) {
+        (b'{', Some(b'$')) => {
+            frames.push(Frame::Interpolation {
+                depth: 0,
+                dollar_brace: false,
+                start: i + 1,
+            });
+            i + 1
+        }
+        (b'$', Some(b'{')) => {
+            frames.push(Frame::Interpolation {
+                depth: 0,
+                dollar_brace: true,
+                start: i + 2,
+            });
+            i + 2
+        }
+        _ => i + 1,
+    }
+}
+
+/// Parse `<<<LABEL`, `<<<"LABEL"` or `<<<'LABEL'` up to the end of its line.
+fn heredoc_start(rest: &str) -> Option<(Frame, usize)> {
+    let header_end = rest.find('\n')?;
+    let header = rest[3..header_end].trim_matches([' ', '\t', '\r']);
+    let (label, nowdoc) = if let Some(label) = header
+        .strip_prefix('\'')
+        .and_then(|label| label.strip_suffix('\''))
+    {
+        (label, true)
+    } else {
+        let label = header
+            .strip_prefix('"')
+            .and_then(|label| label.strip_suffix('"'))
+            .unwrap_or(header);
+        (label, false)
+    };
+    if label.is_empty() || !label.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let frame = if nowdoc {
+        Frame::Nowdoc(label.to_string())
+    } else {
+        Frame::Heredoc(label.to_string())
+    };
+    Some((frame, header_end + 1))
+}
+
+/// If a heredoc/nowdoc closing label starts the line at `i`, return the
+/// offset just past it.
+fn closing_label_end(source: &str, i: usize, label: &str) -> Option<usize> {
+    if i > 0 && source.as_bytes()[i - 1] != b'\n' {
+        return None;
+    }
+    let line = &source[i..];
+    let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+    let after = line[indent..].strip_prefix(label)?;
+    if after
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    Some(i + indent + label.len())
+}
+
+fn find_open_tag(source: &str, from: usize) -> Option<usize> {
+    let offset = source[from..].find("<?")?;
+    let start = from + offset + 2;
+    let rest = &source[start..];
+    if rest.len() >= 3 && rest[..3].eq_ignore_ascii_case("php") {
+        Some(start + 3)
+    } else if rest.starts_with('=') {
+        Some(start + 1)
+    } else {
+        Some(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_strings_and_interpolation() {
+        let position = |source: &str| scan_strings(source).position();
+
+        assert_eq!(position("<?php\n$a = 'x';\n$b"), StringPosition::Code);
+        assert_eq!(position("<?php\n$a = 'it\\'s $na"), StringPosition::Literal);
+        assert_eq!(
+            position("<?php\n$a = \"Hi $na"),
+            StringPosition::Interpolating
+        );
+        assert_eq!(
+            position("<?php\n$a = \"Hi {$user->na"),
+            StringPosition::Code
+        );
+        assert_eq!(
+            position("<?php\n$a = \"Hi {$user->name} and \\\"$"),
+            StringPosition::Interpolating
+        );
+        assert_eq!(
+            position("<?php\n$a = \"Hi ${na"),
+            StringPosition::DollarBrace { start: 17 }
+        );
+        assert_eq!(
+            position("<?php\n$a = <<<EOT\n  Hi {$user->na"),
+            StringPosition::Code
+        );
+        assert_eq!(
+            position("<?php\n$a = <<<EOT\n  Hi\n  EOT;\n$b"),
+            StringPosition::Code
+        );
+        assert_eq!(
+            position("<?php\n$a = <<<'EOT'\nHi {$user->na"),
+            StringPosition::Literal
+        );
+        assert_eq!(
+            position("<?php\n// \"quoted\nfoo('"),
+            StringPosition::Literal
+        );
+        assert_eq!(position("<p>\"</p><?php $a"), StringPosition::Code);
+    }
+
+    #[test]
+    fn closes_unterminated_strings_at_cursor() {
+        let source = "<?php\nfunction f($user) {\n    echo \"Hi {$user->\n}\n";
+        let cursor = source.find("->").unwrap() + 2;
+        assert_eq!(
+            close_strings_at(source, cursor).as_deref(),
+            Some("<?php\nfunction f($user) {\n    echo \"Hi {$user->x}\";\n}\n")
+        );
+
+        let heredoc = "<?php\necho <<<EOT\nHi $na\nEOT;\n";
+        let cursor = heredoc.find("$na").unwrap() + 3;
+        assert_eq!(
+            close_strings_at(heredoc, cursor).as_deref(),
+            Some("<?php\necho <<<EOT\nHi $nax\nEOT;\nEOT;\n")
+        );
+
+        assert_eq!(close_strings_at("<?php\n$a = 1;", 12), None);
+    }
+
+    #[test]
+    fn finds_enclosing_doc_comment() {
+        let source = "<?php\n/** @return int */\nfunction f() {}\n/**\n * @par";
+        assert_eq!(
+            scan_strings(source).doc_comment_start(source),
+            source.rfind("/**")
+        );
+
+        let plain = "<?php\n/* @par";
+        assert_eq!(scan_strings(plain).doc_comment_start(plain), None);
+        let closed = "<?php\n/** @return int */ $a";
+        assert_eq!(scan_strings(closed).doc_comment_start(closed), None);
+    }
+}
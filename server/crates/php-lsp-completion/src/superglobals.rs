@@ -0,0 +1,76 @@
+//! PHP superglobals and their well-known keys.
+
+use php_lsp_types::{ArrayShapeItem, TypeInfo};
+
+/// Superglobal variables, available in every scope.
+pub const SUPERGLOBALS: &[&str] = &[
+    "$GLOBALS",
+    "$_COOKIE",
+    "$_ENV",
+    "$_FILES",
+    "$_GET",
+    "$_POST",
+    "$_REQUEST",
+    "$_SERVER",
+    "$_SESSION",
+];
+
+/// Well-known `$_SERVER` entries and their value types.
+const SERVER_KEYS: &[(&str, &str)] = &[
+    ("CONTENT_LENGTH", "string"),
+    ("CONTENT_TYPE", "string"),
+    ("DOCUMENT_ROOT", "string"),
+    ("GATEWAY_INTERFACE", "string"),
+    ("HTTPS", "string"),
+    ("HTTP_ACCEPT", "string"),
+    ("HTTP_ACCEPT_ENCODING", "string"),
+    ("HTTP_ACCEPT_LANGUAGE", "string"),
+    ("HTTP_AUTHORIZATION", "string"),
+    ("HTTP_CONNECTION", "string"),
+    ("HTTP_COOKIE", "string"),
+    ("HTTP_HOST", "string"),
+    ("HTTP_REFERER", "string"),
+    ("HTTP_USER_AGENT", "string"),
+    ("HTTP_X_FORWARDED_FOR", "string"),
+    ("HTTP_X_REQUESTED_WITH", "string"),
+    ("PATH_INFO", "string"),
+    ("PHP_AUTH_PW", "string"),
+    ("PHP_AUTH_USER", "string"),
+    ("PHP_SELF", "string"),
+    ("QUERY_STRING", "string"),
+    ("REMOTE_ADDR", "string"),
+    ("REMOTE_HOST", "string"),
+    ("REMOTE_PORT", "string"),
+    ("REQUEST_METHOD", "string"),
+    ("REQUEST_SCHEME", "string"),
+    ("REQUEST_TIME", "int"),
+    ("REQUEST_TIME_FLOAT", "float"),
+    ("REQUEST_URI", "string"),
+    ("SCRIPT_FILENAME", "string"),
+    ("SCRIPT_NAME", "string"),
+    ("SERVER_ADDR", "string"),
+    ("SERVER_NAME", "string"),
+    ("SERVER_PORT", "string"),
+    ("SERVER_PROTOCOL", "string"),
+    ("SERVER_SOFTWARE", "string"),
+    ("argc", "int"),
+    ("argv", "array"),
+];
+
+/// Array shape of a superglobal with well-known keys, for array-key
+/// completion on expressions such as `$_SERVER['`.
+pub fn superglobal_array_shape(array_expr: &str) -> Option<TypeInfo> {
+    if array_expr.trim() != "$_SERVER" {
+        return None;
+    }
+    Some(TypeInfo::ArrayShape(
+        SERVER_KEYS
+            .iter()
+            .map(|(key, value)| ArrayShapeItem {
+                key: Some(key.to_string()),
+                optional: true,
+                value: TypeInfo::Simple(value.to_string()),
+            })
+            .collect(),
+    ))
+}
@@ -24,7 +24,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// bytes. The cache schema fixture test below guards the representative binary
 /// shape so CI fails until this version and its fingerprint are updated
 /// together.
-pub const CACHE_SCHEMA_VERSION: u32 = 20;
+pub const CACHE_SCHEMA_VERSION: u32 = 27;
 pub const CACHE_FILE_NAME: &str = "index.bin";
 const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
 const FNV_PRIME: u64 = 0x100000001b3;
@@ -637,14 +637,15 @@ mod tests {
     use super::*;
     use php_lsp_types::{
         ArrayShapeItem, ParamInfo, PhpDocTypeAlias, PhpDocTypeAliasImport, Signature,
-        SymbolModifiers, SymbolReferenceReceiver, TemplateBinding, TemplateBindingKind,
-        TemplateParam, TemplateVariance, TypeInfo, UseKind, UseStatement, Visibility,
+        SymbolAttribute, SymbolModifiers, SymbolReferenceReceiver, SymbolReferenceUsage,
+        TemplateBinding, TemplateBindingKind, TemplateParam, TemplateVariance, TraitAdaptation,
+        TraitAdaptationKind, TypeInfo, UseKind, UseStatement, Visibility,
     };
     use std::io::Write;
 
-    const CACHE_SCHEMA_FIXTURE_VERSION: u32 = 20;
-    const CACHE_SCHEMA_FIXTURE_SERIALIZED_LEN: usize = 3263;
-    const CACHE_SCHEMA_FIXTURE_HASH: u64 = 0x5b93_7117_674b_b711;
+    const CACHE_SCHEMA_FIXTURE_VERSION: u32 = 27;
+    const CACHE_SCHEMA_FIXTURE_SERIALIZED_LEN: usize = 4188;
+    const CACHE_SCHEMA_FIXTURE_HASH: u64 = 0xf1b2_d348_d49d_d325;
 
     fn unique_temp_dir(name: &str) -> PathBuf {
         let dir = std::env::temp_dir().join(format!(
@@ -660,9 +661,9 @@ mod tests {
     fn make_symbol(uri: &str) -> SymbolInfo {
         SymbolInfo {
             name: "Foo".to_string(),
-            fqn: "App\\Foo".to_string(),
+            fqn: "App\\Foo".into(),
             kind: PhpSymbolKind::Class,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: (0, 0, 1, 0),
             selection_range: (0, 6, 0, 9),
             visibility: Visibility::Public,
@@ -670,21 +671,23 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         }
     }
 
     fn cache_schema_symbol(uri: &str, name: &str, kind: PhpSymbolKind) -> SymbolInfo {
         SymbolInfo {
             name: name.to_string(),
-            fqn: format!("App\\{name}"),
+            fqn: format!("App\\{name}").into(),
             kind,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: (1, 2, 3, 4),
             selection_range: (1, 12, 1, 12 + name.len() as u32),
             visibility: Visibility::Protected,
@@ -696,7 +699,11 @@ mod tests {
                 is_deprecated: true,
                 is_builtin: false,
             },
-            attributes: vec![],
+            attributes: vec![SymbolAttribute {
+                text: "#[\\Attribute]".to_string(),
+                range: (0, 0, 0, 12),
+                names: vec!["Attribute".to_string()],
+            }],
             doc_comment: Some("/** @template T of object */".to_string()),
             signature: Some(Signature {
                 params: vec![ParamInfo {
@@ -713,12 +720,15 @@ mod tests {
                     is_variadic: false,
                     is_by_ref: true,
                     is_promoted: false,
+                    attributes: vec![],
                 }],
                 return_type: Some(TypeInfo::ClassString(Some(Box::new(TypeInfo::Simple(
                     "App\\Foo".to_string(),
                 ))))),
+                throws: vec!["App\\FooException".to_string()],
             }),
-            parent_fqn: Some("App\\Base".to_string()),
+            value: Some("'1.0.0'".to_string()),
+            parent_fqn: Some("App\\Base".into()),
             extends: vec!["App\\Base".to_string()],
             implements: vec!["App\\Contract".to_string()],
             traits: vec!["App\\SharedTrait".to_string()],
@@ -732,6 +742,14 @@ mod tests {
                 target: "App\\Base".to_string(),
                 args: vec![TypeInfo::Static_],
             }],
+            trait_adaptations: vec![TraitAdaptation {
+                trait_fqn: Some("App\\Greets".to_string()),
+                method: "hello".to_string(),
+                kind: TraitAdaptationKind::Alias {
+                    alias: Some("greet".to_string()),
+                    visibility: Some(Visibility::Protected),
+                },
+            }],
         }
     }
 
@@ -813,6 +831,8 @@ mod tests {
                         is_declaration: true,
                         starts_with_dollar: false,
                         receiver: SymbolReferenceReceiver::None,
+                        is_string_literal: false,
+                        usage: SymbolReferenceUsage::Other,
                     },
                     SymbolReference {
                         target_fqn: "App\\Foo::bar".to_string(),
@@ -823,6 +843,8 @@ mod tests {
                         receiver: SymbolReferenceReceiver::ResolvedType {
                             type_fqn: "App\\Foo".to_string(),
                         },
+                        is_string_literal: false,
+                        usage: SymbolReferenceUsage::Other,
                     },
                     SymbolReference {
                         target_fqn: "App\\Foo::$name".to_string(),
@@ -833,6 +855,8 @@ mod tests {
                         receiver: SymbolReferenceReceiver::StaticClass {
                             class_fqn: "App\\Foo".to_string(),
                         },
+                        is_string_literal: false,
+                        usage: SymbolReferenceUsage::Other,
                     },
                     SymbolReference {
                         target_fqn: "App\\Foo::missing".to_string(),
@@ -841,6 +865,8 @@ mod tests {
                         is_declaration: false,
                         starts_with_dollar: false,
                         receiver: SymbolReferenceReceiver::Unresolved,
+                        is_string_literal: false,
+                        usage: SymbolReferenceUsage::Other,
                     },
                 ],
             }],
@@ -1026,6 +1052,8 @@ mod tests {
             is_declaration: false,
             starts_with_dollar: false,
             receiver: Default::default(),
+            is_string_literal: false,
+            usage: SymbolReferenceUsage::Other,
         }];
 
         let index = WorkspaceIndex::new();
@@ -1192,7 +1220,7 @@ mod tests {
         let second_index = WorkspaceIndex::new();
         let mut bar_symbol = make_symbol(&uri);
         bar_symbol.name = "Bar".to_string();
-        bar_symbol.fqn = "App\\Bar".to_string();
+        bar_symbol.fqn = "App\\Bar".into();
         second_index.update_file(
             &uri,
             FileSymbols {
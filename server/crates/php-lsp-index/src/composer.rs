@@ -49,6 +49,49 @@ impl NamespaceMap {
         results
     }
 
+    /// Infer the fully qualified class name for a PHP file from PSR-4
+    /// mappings. The longest matching directory wins.
+    ///
+    /// E.g., with mapping `App\\` → `src/`, the path `src/Service/Foo.php`
+    /// yields `App\\Service\\Foo`.
+    pub fn class_name_for_path(&self, path: &Path) -> Option<String> {
+        let stem = path.file_stem()?.to_str()?;
+        if path.extension().and_then(|ext| ext.to_str()) != Some("php") {
+            return None;
+        }
+
+        let mut best: Option<(usize, String)> = None;
+        for (prefix, dirs) in &self.psr4 {
+            for dir in dirs {
+                let Ok(relative) = path.strip_prefix(dir) else {
+                    continue;
+                };
+                let depth = dir.components().count();
+                if best
+                    .as_ref()
+                    .is_some_and(|(best_depth, _)| *best_depth >= depth)
+                {
+                    continue;
+                }
+                let segments: Option<Vec<&str>> = relative
+                    .parent()
+                    .into_iter()
+                    .flat_map(Path::components)
+                    .map(|component| component.as_os_str().to_str())
+                    .collect();
+                let mut fqn = prefix.trim_end_matches('\\').to_string();
+                for segment in segments?.into_iter().chain([stem]) {
+                    if !fqn.is_empty() {
+                        fqn.push('\\');
+                    }
+                    fqn.push_str(segment);
+                }
+                best = Some((depth, fqn));
+            }
+        }
+        best.map(|(_, fqn)| fqn)
+    }
+
     /// Get all directories that should be scanned for PHP files.
     pub fn source_directories(&self) -> Vec<&Path> {
         let mut dirs: Vec<&Path> = Vec::new();
@@ -254,6 +297,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_class_name_for_path_prefers_longest_psr4_directory() {
+        let json = r#"{
+            "autoload": {
+                "psr-4": {
+                    "App\\": "src/",
+                    "App\\Domain\\": "modules/domain/"
+                }
+            },
+            "autoload-dev": {
+                "psr-4": {
+                    "": "legacy/"
+                }
+            }
+        }"#;
+        let map = parse_composer_json_str(json, Path::new("/project")).unwrap();
+        assert_eq!(
+            map.class_name_for_path(Path::new("/project/src/Service/UserService.php"))
+                .as_deref(),
+            Some("App\\Service\\UserService")
+        );
+        assert_eq!(
+            map.class_name_for_path(Path::new("/project/modules/domain/User.php"))
+                .as_deref(),
+            Some("App\\Domain\\User")
+        );
+        assert_eq!(
+            map.class_name_for_path(Path::new("/project/legacy/Helper.php"))
+                .as_deref(),
+            Some("Helper")
+        );
+        assert_eq!(
+            map.class_name_for_path(Path::new("/project/tests/FooTest.php")),
+            None
+        );
+    }
+
     #[test]
     fn test_resolve_class_psr0_keeps_namespace_underscores() {
         let json = r#"{
@@ -0,0 +1,71 @@
+//! String interning for symbol URIs and FQNs.
+//!
+//! Every symbol of a file repeats the file URI, and every class member repeats
+//! its class FQN as `parent_fqn`. The index funnels those strings through one
+//! pool so each distinct value is allocated once.
+
+use dashmap::DashSet;
+use php_lsp_types::{SharedStr, SymbolInfo};
+use std::collections::HashSet;
+
+/// Concurrent pool of shared strings.
+#[derive(Default)]
+pub struct StringInterner {
+    strings: DashSet<SharedStr>,
+}
+
+impl StringInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the pooled copy of `value`, adding it when missing.
+    pub fn intern(&self, value: &SharedStr) -> SharedStr {
+        if let Some(existing) = self.strings.get(value.as_str()) {
+            return existing.key().clone();
+        }
+        // Another thread may insert the same value between the lookup and the
+        // insert; whichever copy lands in the pool first wins.
+        if self.strings.insert(value.clone()) {
+            return value.clone();
+        }
+        self.strings
+            .get(value.as_str())
+            .map(|existing| existing.key().clone())
+            .unwrap_or_else(|| value.clone())
+    }
+
+    /// Point the URI and FQN fields of `symbol` at pooled strings.
+    pub fn intern_symbol(&self, symbol: &mut SymbolInfo) {
+        symbol.uri = self.intern(&symbol.uri);
+        symbol.fqn = self.intern(&symbol.fqn);
+        if let Some(parent_fqn) = &symbol.parent_fqn {
+            symbol.parent_fqn = Some(self.intern(parent_fqn));
+        }
+    }
+
+    /// Drop pooled strings that are no longer referenced outside the pool.
+    ///
+    /// `released` holds one copy of each candidate; a pooled string is
+    /// unused when only the pool and that copy remain.
+    pub fn release(&self, released: HashSet<SharedStr>) {
+        for value in released {
+            self.strings
+                .remove_if(value.as_str(), |pooled| pooled.strong_count() <= 2);
+        }
+    }
+
+    /// Number of distinct pooled strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Total bytes of pooled string data.
+    pub fn byte_len(&self) -> usize {
+        self.strings.iter().map(|value| value.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
@@ -5,5 +5,7 @@
 
 pub mod cache;
 pub mod composer;
+pub mod intern;
 pub mod stubs;
+pub mod symbol_query;
 pub mod workspace;
@@ -0,0 +1,588 @@
+//! `workspace/symbol` query parsing.
+//!
+//! A query is free text, optionally narrowed by:
+//!
+//! - a kind filter: `method:get`, `method get` or `#method get` (`#method`
+//!   alone lists every method);
+//! - a member scope: `UserService::get`, `App\Service\UserService::$name` or
+//!   `::get` for any member;
+//! - a namespace scope: `App\Service\` lists the symbols declared in that
+//!   namespace and below.
+//!
+//! [`SymbolQuery::score`] ranks matching names by tier (exact, prefix,
+//! camelCase humps, substring, then fuzzy abbreviation) and prefers workspace
+//! symbols over vendor ones within a tier, like an IDE "Go to Class" popup.
+//! Completion reuses [`match_quality`] for the same tiers.
+
+use php_lsp_types::{PhpSymbolKind, SymbolInfo};
+
+/// Width of one match tier in [`SymbolQuery::score`]. Origin and closeness
+/// bonuses stay below it, so a better tier always ranks first.
+const SCORE_TIER: i64 = 20_000;
+const WORKSPACE_ORIGIN_BONUS: i64 = 10_000;
+const VENDOR_ORIGIN_BONUS: i64 = 5_000;
+const MAX_CLOSENESS: i64 = 4_000;
+
+/// How well a candidate name matches the typed prefix. Better matches order
+/// first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchQuality {
+    /// The name equals the prefix, ignoring case.
+    Exact,
+    /// The name starts with the prefix, ignoring case.
+    Prefix,
+    /// The prefix matches the starts of the name's camelCase or snake_case
+    /// humps, e.g. `gUN` for `getUserName` or `AC` for `ArrayCollection`.
+    /// Symbol search also lets each hump skip letters (`USvc` for
+    /// `UserService`).
+    CamelCase,
+    /// The name contains the prefix somewhere else.
+    Substring,
+}
+
+impl MatchQuality {
+    pub fn sort_rank(self) -> u8 {
+        match self {
+            MatchQuality::Exact => 0,
+            MatchQuality::Prefix => 1,
+            MatchQuality::CamelCase => 2,
+            MatchQuality::Substring => 3,
+        }
+    }
+}
+
+/// Symbol kind groups accepted by `kind:` / `#kind` prefixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKindFilter {
+    Type,
+    Class,
+    Interface,
+    Trait,
+    Enum,
+    Function,
+    Method,
+    Property,
+    Constant,
+}
+
+impl SymbolKindFilter {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "type" | "types" => Some(Self::Type),
+            "class" | "classes" => Some(Self::Class),
+            "interface" | "interfaces" => Some(Self::Interface),
+            "trait" | "traits" => Some(Self::Trait),
+            "enum" | "enums" => Some(Self::Enum),
+            "function" | "functions" | "fn" => Some(Self::Function),
+            "method" | "methods" => Some(Self::Method),
+            "property" | "properties" | "prop" | "props" => Some(Self::Property),
+            "const" | "constant" | "constants" => Some(Self::Constant),
+            _ => None,
+        }
+    }
+
+    pub fn matches(self, kind: PhpSymbolKind) -> bool {
+        match self {
+            Self::Type => matches!(
+                kind,
+                PhpSymbolKind::Class
+                    | PhpSymbolKind::Interface
+                    | PhpSymbolKind::Trait
+                    | PhpSymbolKind::Enum
+            ),
+            Self::Class => kind == PhpSymbolKind::Class,
+            Self::Interface => kind == PhpSymbolKind::Interface,
+            Self::Trait => kind == PhpSymbolKind::Trait,
+            Self::Enum => kind == PhpSymbolKind::Enum,
+            Self::Function => kind == PhpSymbolKind::Function,
+            Self::Method => kind == PhpSymbolKind::Method,
+            Self::Property => kind == PhpSymbolKind::Property,
+            Self::Constant => matches!(
+                kind,
+                PhpSymbolKind::ClassConstant
+                    | PhpSymbolKind::GlobalConstant
+                    | PhpSymbolKind::EnumCase
+            ),
+        }
+    }
+
+    /// Whether the filter can match class members.
+    fn includes_members(self) -> bool {
+        matches!(self, Self::Method | Self::Property | Self::Constant)
+    }
+}
+
+/// Where matching symbols must be declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolQueryScope {
+    /// No restriction.
+    Any,
+    /// Members of types whose short name starts with, or whose FQN ends with,
+    /// the given container. An empty container matches every member.
+    Members(String),
+    /// Symbols whose FQN starts with `namespace\`.
+    Namespace(String),
+}
+
+/// Parsed `workspace/symbol` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolQuery {
+    pub kind: Option<SymbolKindFilter>,
+    pub scope: SymbolQueryScope,
+    /// Remaining text to match against symbol names.
+    pub text: String,
+}
+
+impl SymbolQuery {
+    pub fn parse(raw: &str) -> Self {
+        let (kind, text) = split_kind_filter(raw.trim());
+        let text = text.trim();
+
+        if let Some((container, member)) = text.rsplit_once("::") {
+            return Self {
+                kind,
+                scope: SymbolQueryScope::Members(container.trim_start_matches('\\').to_string()),
+                text: member.trim_start_matches('$').to_string(),
+            };
+        }
+        if text.len() > 1 && text.ends_with('\\') {
+            return Self {
+                kind,
+                scope: SymbolQueryScope::Namespace(text.trim_matches('\\').to_string()),
+                text: String::new(),
+            };
+        }
+
+        Self {
+            kind,
+            scope: SymbolQueryScope::Any,
+            text: text.to_string(),
+        }
+    }
+
+    /// Whether the query has nothing to narrow results by.
+    pub fn is_empty(&self) -> bool {
+        self.kind.is_none() && self.scope == SymbolQueryScope::Any && self.text.is_empty()
+    }
+
+    /// Whether class members can match, so callers scanning only top-level
+    /// symbols know to look further.
+    pub fn includes_members(&self) -> bool {
+        matches!(self.scope, SymbolQueryScope::Members(_))
+            || self.kind.is_some_and(SymbolKindFilter::includes_members)
+    }
+
+    /// Check the kind filter and scope, ignoring `text`.
+    pub fn matches_scope(&self, symbol: &SymbolInfo) -> bool {
+        if !self.kind.is_none_or(|kind| kind.matches(symbol.kind)) {
+            return false;
+        }
+
+        match &self.scope {
+            SymbolQueryScope::Any => true,
+            SymbolQueryScope::Members(container) => symbol
+                .parent_fqn
+                .as_deref()
+                .is_some_and(|parent| container_matches(parent, container)),
+            SymbolQueryScope::Namespace(namespace) => {
+                // Listing a namespace shows its declarations; members only
+                // when a kind filter asks for them.
+                (symbol.parent_fqn.is_none() || self.kind.is_some())
+                    && starts_with_ignore_ascii_case(&symbol.fqn, namespace)
+                    && symbol.fqn.as_bytes().get(namespace.len()) == Some(&b'\\')
+            }
+        }
+    }
+
+    /// Relevance of `symbol`, or `None` when it is out of scope or its name
+    /// does not match `text`. Qualified text (containing `\`) is matched
+    /// against the FQN instead of the short name. Higher is better.
+    pub fn score(&self, symbol: &SymbolInfo) -> Option<i64> {
+        if !self.matches_scope(symbol) {
+            return None;
+        }
+        let text_score = if self.text.contains('\\') {
+            text_match_score(
+                symbol.fqn.trim_start_matches('\\'),
+                self.text.trim_start_matches('\\'),
+            )
+        } else {
+            text_match_score(&symbol.name, &self.text)
+        }?;
+        Some(text_score + origin_bonus(&symbol.uri))
+    }
+}
+
+/// Match `name` against the typed `prefix`. An empty prefix matches every
+/// name as a prefix match.
+pub fn match_quality(name: &str, prefix: &str) -> Option<MatchQuality> {
+    classify_match(name, prefix, false)
+}
+
+fn classify_match(name: &str, prefix: &str, skip_within_humps: bool) -> Option<MatchQuality> {
+    let name = name.trim_start_matches('$');
+    let prefix = prefix.trim_start_matches('$');
+    let name_lower = name.to_lowercase();
+    let prefix_lower = prefix.to_lowercase();
+
+    if name_lower == prefix_lower {
+        Some(MatchQuality::Exact)
+    } else if name_lower.starts_with(&prefix_lower) {
+        Some(MatchQuality::Prefix)
+    } else if camel_case_matches(name, prefix, skip_within_humps) {
+        Some(MatchQuality::CamelCase)
+    } else if name_lower.contains(&prefix_lower) {
+        Some(MatchQuality::Substring)
+    } else {
+        None
+    }
+}
+
+/// Whether every prefix character continues the current hump of `name` or
+/// starts a later one. The first character must start the name. With
+/// `skip_within_humps`, a character may match any later letter of the
+/// current hump instead of only the next one.
+fn camel_case_matches(name: &str, prefix: &str, skip_within_humps: bool) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let prefix: Vec<char> = prefix.chars().map(|c| c.to_ascii_lowercase()).collect();
+    if prefix.len() < 2 || name.is_empty() || name[0].to_ascii_lowercase() != prefix[0] {
+        return false;
+    }
+    let hump_starts: Vec<bool> = (0..name.len())
+        .map(|i| {
+            let c = name[i];
+            if i == 0 || c == '_' {
+                return i == 0;
+            }
+            let previous = name[i - 1];
+            previous == '_'
+                || (c.is_uppercase() && !previous.is_uppercase())
+                || (c.is_uppercase() && name.get(i + 1).is_some_and(|next| next.is_lowercase()))
+                || (c.is_ascii_digit() && !previous.is_ascii_digit())
+        })
+        .collect();
+
+    fn matches_from(
+        name: &[char],
+        hump_starts: &[bool],
+        prefix: &[char],
+        at: usize,
+        skip_within_humps: bool,
+    ) -> bool {
+        let Some(&wanted) = prefix.first() else {
+            return true;
+        };
+        (at..name.len()).any(|next| {
+            // Continue the current hump, or jump to the start of a later one.
+            let reachable = hump_starts[next]
+                || if skip_within_humps {
+                    !hump_starts[at..=next].contains(&true)
+                } else {
+                    next == at
+                };
+            reachable
+                && name[next].to_ascii_lowercase() == wanted
+                && matches_from(name, hump_starts, &prefix[1..], next + 1, skip_within_humps)
+        })
+    }
+
+    matches_from(&name, &hump_starts, &prefix[1..], 1, skip_within_humps)
+}
+
+/// Tiered score of `text` against `query`: exact > prefix > camelCase >
+/// substring > fuzzy abbreviation, with shorter names and earlier substrings
+/// first inside a tier.
+pub fn text_match_score(text: &str, query: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text_lower = text.to_ascii_lowercase();
+    let query_lower = query.to_ascii_lowercase();
+    let length_penalty = text.len().saturating_sub(query.len()) as i64 * 10;
+
+    let (tier, closeness) = match classify_match(text, query, true) {
+        Some(MatchQuality::Exact) => (5, MAX_CLOSENESS),
+        Some(MatchQuality::Prefix) => (4, MAX_CLOSENESS - length_penalty),
+        Some(MatchQuality::CamelCase) => (3, MAX_CLOSENESS - length_penalty),
+        Some(MatchQuality::Substring) => {
+            let index = text_lower.find(&query_lower).unwrap_or_default() as i64;
+            (2, MAX_CLOSENESS - index * 10)
+        }
+        None => (1, fuzzy_abbreviation_score(&text_lower, &query_lower)?),
+    };
+    Some(tier * SCORE_TIER + closeness.clamp(0, MAX_CLOSENESS))
+}
+
+/// Match `query` characters in order anywhere in `text`, rewarding matches
+/// at word boundaries and penalizing gaps. Both must already be lowercase.
+fn fuzzy_abbreviation_score(text: &str, query: &str) -> Option<i64> {
+    let mut score = MAX_CLOSENESS;
+    let mut last_match_index: Option<usize> = None;
+    let mut search_from = 0usize;
+
+    for query_char in query.chars() {
+        let relative_index = text[search_from..].find(query_char)?;
+        let absolute_index = search_from + relative_index;
+        if let Some(last_match_index) = last_match_index {
+            let gap = absolute_index.saturating_sub(last_match_index + 1);
+            score -= gap as i64 * 8;
+        } else {
+            score -= absolute_index as i64 * 4;
+        }
+        if absolute_index == 0
+            || text[..absolute_index]
+                .chars()
+                .last()
+                .is_some_and(|ch| ch == '\\' || ch == '_' || ch == '-' || ch.is_whitespace())
+        {
+            score += 80;
+        }
+        last_match_index = Some(absolute_index);
+        search_from = absolute_index + query_char.len_utf8();
+    }
+
+    Some(score - text.len() as i64)
+}
+
+fn origin_bonus(uri: &str) -> i64 {
+    if uri.starts_with("phpstub://") {
+        0
+    } else if uri.contains("/vendor/") {
+        VENDOR_ORIGIN_BONUS
+    } else {
+        WORKSPACE_ORIGIN_BONUS
+    }
+}
+
+fn split_kind_filter(query: &str) -> (Option<SymbolKindFilter>, &str) {
+    let (hashed, body) = match query.strip_prefix('#') {
+        Some(rest) => (true, rest),
+        None => (false, query),
+    };
+
+    if let Some((prefix, rest)) = body.split_once(':') {
+        // `Foo::bar` is a member scope, not a `Foo:` kind prefix.
+        if !rest.starts_with(':') {
+            if let Some(kind) = SymbolKindFilter::parse(prefix) {
+                return (Some(kind), rest);
+            }
+        }
+    }
+    if let Some((prefix, rest)) = body.split_once(char::is_whitespace) {
+        if let Some(kind) = SymbolKindFilter::parse(prefix) {
+            return (Some(kind), rest);
+        }
+    }
+    if hashed {
+        if let Some(kind) = SymbolKindFilter::parse(body) {
+            return (Some(kind), "");
+        }
+    }
+
+    (None, query)
+}
+
+fn container_matches(parent_fqn: &str, container: &str) -> bool {
+    if container.is_empty() {
+        return true;
+    }
+    if container.contains('\\') {
+        let suffix_start = parent_fqn.len().saturating_sub(container.len());
+        return parent_fqn.eq_ignore_ascii_case(container)
+            || (suffix_start > 0
+                && parent_fqn.as_bytes()[suffix_start - 1] == b'\\'
+                && parent_fqn.as_bytes()[suffix_start..]
+                    .eq_ignore_ascii_case(container.as_bytes()));
+    }
+
+    let short_name = parent_fqn.rsplit('\\').next().unwrap_or(parent_fqn);
+    starts_with_ignore_ascii_case(short_name, container)
+}
+
+fn starts_with_ignore_ascii_case(text: &str, prefix: &str) -> bool {
+    text.len() >= prefix.len()
+        && text.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use php_lsp_types::{SymbolModifiers, Visibility};
+
+    fn symbol(name: &str, fqn: &str, kind: PhpSymbolKind, parent_fqn: Option<&str>) -> SymbolInfo {
+        SymbolInfo {
+            name: name.to_string(),
+            fqn: fqn.into(),
+            kind,
+            uri: "file:///test.php".into(),
+            range: (0, 0, 1, 0),
+            selection_range: (0, 0, 0, 0),
+            visibility: Visibility::Public,
+            modifiers: SymbolModifiers::default(),
+            attributes: vec![],
+            doc_comment: None,
+            signature: None,
+            value: None,
+            parent_fqn: parent_fqn.map(Into::into),
+            extends: vec![],
+            implements: vec![],
+            traits: vec![],
+            templates: vec![],
+            template_bindings: vec![],
+            trait_adaptations: vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_kind_filters() {
+        for raw in ["method:get", "method get", "#method get", "#method:get"] {
+            let query = SymbolQuery::parse(raw);
+            assert_eq!(query.kind, Some(SymbolKindFilter::Method), "{raw}");
+            assert_eq!(query.text, "get", "{raw}");
+        }
+
+        let query = SymbolQuery::parse("#method");
+        assert_eq!(query.kind, Some(SymbolKindFilter::Method));
+        assert!(query.text.is_empty());
+        assert!(!query.is_empty());
+
+        let query = SymbolQuery::parse("method");
+        assert_eq!(query.kind, None);
+        assert_eq!(query.text, "method");
+    }
+
+    #[test]
+    fn test_parse_member_and_namespace_scopes() {
+        let query = SymbolQuery::parse("UserService::get");
+        assert_eq!(
+            query.scope,
+            SymbolQueryScope::Members("UserService".to_string())
+        );
+        assert_eq!(query.text, "get");
+
+        let query = SymbolQuery::parse("#property \\App\\User::$na");
+        assert_eq!(query.kind, Some(SymbolKindFilter::Property));
+        assert_eq!(
+            query.scope,
+            SymbolQueryScope::Members("App\\User".to_string())
+        );
+        assert_eq!(query.text, "na");
+
+        let query = SymbolQuery::parse("App\\Service\\");
+        assert_eq!(
+            query.scope,
+            SymbolQueryScope::Namespace("App\\Service".to_string())
+        );
+        assert!(query.text.is_empty());
+
+        let query = SymbolQuery::parse("App\\Service\\User");
+        assert_eq!(query.scope, SymbolQueryScope::Any);
+        assert_eq!(query.text, "App\\Service\\User");
+    }
+
+    #[test]
+    fn test_matches_scope() {
+        let class = symbol(
+            "UserService",
+            "App\\Service\\UserService",
+            PhpSymbolKind::Class,
+            None,
+        );
+        let method = symbol(
+            "getUser",
+            "App\\Service\\UserService::getUser",
+            PhpSymbolKind::Method,
+            Some("App\\Service\\UserService"),
+        );
+        let other = symbol(
+            "Helper",
+            "App\\ServiceLocator\\Helper",
+            PhpSymbolKind::Class,
+            None,
+        );
+
+        let members = SymbolQuery::parse("userserv::get");
+        assert!(members.score(&method).is_some());
+        assert!(members.score(&class).is_none());
+        assert!(SymbolQuery::parse("Service\\UserService::").matches_scope(&method));
+        assert!(!SymbolQuery::parse("Other\\UserService::get").matches_scope(&method));
+
+        let namespace = SymbolQuery::parse("App\\Service\\");
+        assert!(namespace.matches_scope(&class));
+        assert!(!namespace.matches_scope(&method));
+        assert!(!namespace.matches_scope(&other));
+        assert!(SymbolQuery::parse("#method App\\Service\\").matches_scope(&method));
+    }
+
+    #[test]
+    fn classifies_prefix_matches() {
+        assert_eq!(match_quality("count", "Count"), Some(MatchQuality::Exact));
+        assert_eq!(match_quality("$user", "$us"), Some(MatchQuality::Prefix));
+        assert_eq!(
+            match_quality("getUserName", "gUN"),
+            Some(MatchQuality::CamelCase)
+        );
+        assert_eq!(
+            match_quality("getUserName", "getun"),
+            Some(MatchQuality::CamelCase)
+        );
+        assert_eq!(
+            match_quality("HTTPClient", "htcl"),
+            Some(MatchQuality::CamelCase)
+        );
+        assert_eq!(
+            match_quality("array_key_exists", "ake"),
+            Some(MatchQuality::CamelCase)
+        );
+        assert_eq!(
+            match_quality("getUserName", "name"),
+            Some(MatchQuality::Substring)
+        );
+        assert_eq!(match_quality("UserService", "USvc"), None);
+        assert_eq!(
+            classify_match("UserService", "USvc", true),
+            Some(MatchQuality::CamelCase)
+        );
+        assert_eq!(match_quality("getUserName", "gsn"), None);
+        assert_eq!(
+            match_quality("getUserName", "rna"),
+            Some(MatchQuality::Substring)
+        );
+    }
+
+    #[test]
+    fn test_score_orders_tiers_then_origin() {
+        let mut user_service = symbol(
+            "UserService",
+            "App\\UserService",
+            PhpSymbolKind::Class,
+            None,
+        );
+        user_service.uri = "file:///project/src/UserService.php".into();
+        let mut vendor_user_service = user_service.clone();
+        vendor_user_service.fqn = "Vendor\\UserService".into();
+        vendor_user_service.uri = "file:///project/vendor/acme/src/UserService.php".into();
+        let mut user_service_factory = user_service.clone();
+        user_service_factory.name = "UserServiceFactory".to_string();
+        let mut superuser = user_service.clone();
+        superuser.name = "SuperUserServiceHelper".to_string();
+
+        let query = SymbolQuery::parse("USvc");
+        let camel = query.score(&user_service).expect("camel-hump match");
+        let query = SymbolQuery::parse("UserService");
+        let exact = query.score(&user_service).unwrap();
+        let vendor_exact = query.score(&vendor_user_service).unwrap();
+        let prefix = query.score(&user_service_factory).unwrap();
+        let substring = query.score(&superuser).unwrap();
+
+        assert!(exact > vendor_exact, "workspace before vendor");
+        assert!(vendor_exact > prefix, "exact before prefix");
+        assert!(prefix > camel, "prefix before camel-hump");
+        assert!(camel > substring, "camel-hump before substring");
+        assert!(
+            SymbolQuery::parse("ersvc").score(&user_service).unwrap() < substring,
+            "fuzzy abbreviations rank last"
+        );
+        assert!(SymbolQuery::parse("xyz").score(&user_service).is_none());
+    }
+}
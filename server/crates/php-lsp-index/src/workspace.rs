@@ -1,9 +1,11 @@
 //! Global workspace symbol index.
 
+use crate::intern::StringInterner;
+use crate::symbol_query::SymbolQuery;
 use dashmap::DashMap;
 use php_lsp_types::{
-    ArrayShapeItem, FileSymbols, PhpSymbolKind, Signature, SymbolInfo, SymbolReference,
-    TemplateBindingKind, TypeInfo,
+    ArrayShapeItem, FileSymbols, PhpSymbolKind, SharedStr, Signature, SymbolInfo, SymbolReference,
+    TemplateBindingKind, TraitAdaptationKind, TypeInfo, Visibility,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -32,19 +34,31 @@ struct TypeAliasVisit {
 /// Global index of all symbols in the workspace.
 pub struct WorkspaceIndex {
     /// FQN → SymbolInfo for types (classes, interfaces, traits, enums)
-    pub types: DashMap<String, Arc<SymbolInfo>>,
+    pub types: DashMap<SharedStr, Arc<SymbolInfo>>,
 
     /// FQN → SymbolInfo for functions
-    pub functions: DashMap<String, Arc<SymbolInfo>>,
+    pub functions: DashMap<SharedStr, Arc<SymbolInfo>>,
 
     /// FQN → SymbolInfo for constants
-    pub constants: DashMap<String, Arc<SymbolInfo>>,
+    pub constants: DashMap<SharedStr, Arc<SymbolInfo>>,
 
     /// File URI → extracted symbols for that file
     pub file_symbols: DashMap<String, FileSymbols>,
 
     /// File URI → precomputed non-local symbol references for that file
     pub file_references: DashMap<String, Vec<SymbolReference>>,
+
+    /// Lowercase short name of a reference target → files referencing it
+    reference_name_files: DashMap<String, HashSet<String>>,
+
+    /// Lowercase short name → FQNs of indexed types with that name
+    type_short_names: DashMap<String, HashSet<SharedStr>>,
+
+    /// Lowercase short name → FQNs of indexed functions with that name
+    function_short_names: DashMap<String, HashSet<SharedStr>>,
+
+    /// Shared pool for symbol URIs and FQNs
+    strings: StringInterner,
 }
 
 impl WorkspaceIndex {
@@ -56,6 +70,10 @@ impl WorkspaceIndex {
             constants: DashMap::new(),
             file_symbols: DashMap::new(),
             file_references: DashMap::new(),
+            reference_name_files: DashMap::new(),
+            type_short_names: DashMap::new(),
+            function_short_names: DashMap::new(),
+            strings: StringInterner::new(),
         }
     }
 
@@ -68,12 +86,16 @@ impl WorkspaceIndex {
     pub fn update_file_with_references(
         &self,
         uri: &str,
-        file_symbols: FileSymbols,
+        mut file_symbols: FileSymbols,
         file_references: Vec<SymbolReference>,
     ) {
         // Remove old symbols for this file
         self.remove_file(uri);
 
+        for sym in &mut file_symbols.symbols {
+            self.strings.intern_symbol(sym);
+        }
+
         // Add new symbols to global indices
         for sym in &file_symbols.symbols {
             let sym_arc = Arc::new(sym.clone());
@@ -83,9 +105,11 @@ impl WorkspaceIndex {
                 | PhpSymbolKind::Trait
                 | PhpSymbolKind::Enum => {
                     self.types.insert(sym.fqn.clone(), sym_arc);
+                    remember_short_name(&self.type_short_names, &sym.fqn);
                 }
                 PhpSymbolKind::Function => {
                     self.functions.insert(sym.fqn.clone(), sym_arc);
+                    remember_short_name(&self.function_short_names, &sym.fqn);
                 }
                 PhpSymbolKind::GlobalConstant => {
                     self.constants.insert(sym.fqn.clone(), sym_arc);
@@ -98,13 +122,54 @@ impl WorkspaceIndex {
 
         // Store file symbols
         self.file_symbols.insert(uri.to_string(), file_symbols);
+        for name in reference_names(&file_references) {
+            self.reference_name_files
+                .entry(name)
+                .or_default()
+                .insert(uri.to_string());
+        }
         self.file_references
             .insert(uri.to_string(), file_references);
     }
 
+    /// Files whose precomputed references include a target with the given
+    /// short name (class/function/constant name or member name).
+    ///
+    /// This is a candidate filter only: callers still match each reference
+    /// against the exact target.
+    pub fn files_referencing_name(&self, target_fqn: &str) -> Vec<String> {
+        let mut files: Vec<String> = self
+            .reference_name_files
+            .get(&reference_name_key(target_fqn))
+            .map(|entry| entry.value().iter().cloned().collect())
+            .unwrap_or_default();
+        files.sort();
+        files
+    }
+
+    /// Indexed types whose short name is `short_name` (case-insensitive),
+    /// without scanning every type.
+    pub fn types_named(&self, short_name: &str) -> Vec<Arc<SymbolInfo>> {
+        symbols_named(&self.type_short_names, &self.types, short_name)
+    }
+
+    /// Indexed functions whose short name is `short_name` (case-insensitive),
+    /// without scanning every function.
+    pub fn functions_named(&self, short_name: &str) -> Vec<Arc<SymbolInfo>> {
+        symbols_named(&self.function_short_names, &self.functions, short_name)
+    }
+
     /// Remove all symbols from a file.
     pub fn remove_file(&self, uri: &str) {
-        self.file_references.remove(uri);
+        if let Some((_, old_references)) = self.file_references.remove(uri) {
+            for name in reference_names(&old_references) {
+                if let Some(mut files) = self.reference_name_files.get_mut(&name) {
+                    files.remove(uri);
+                }
+                self.reference_name_files
+                    .remove_if(&name, |_, files| files.is_empty());
+            }
+        }
         if let Some((_, old_symbols)) = self.file_symbols.remove(uri) {
             for sym in &old_symbols.symbols {
                 match sym.kind {
@@ -113,9 +178,11 @@ impl WorkspaceIndex {
                     | PhpSymbolKind::Trait
                     | PhpSymbolKind::Enum => {
                         self.remove_top_level_symbol(uri, sym, &self.types);
+                        forget_short_name(&self.type_short_names, &self.types, &sym.fqn);
                     }
                     PhpSymbolKind::Function => {
                         self.remove_top_level_symbol(uri, sym, &self.functions);
+                        forget_short_name(&self.function_short_names, &self.functions, &sym.fqn);
                     }
                     PhpSymbolKind::GlobalConstant => {
                         self.remove_top_level_symbol(uri, sym, &self.constants);
@@ -123,23 +190,41 @@ impl WorkspaceIndex {
                     _ => {}
                 }
             }
+            self.strings.release(
+                old_symbols
+                    .symbols
+                    .into_iter()
+                    .flat_map(|sym| [Some(sym.uri), Some(sym.fqn), sym.parent_fqn])
+                    .flatten()
+                    .collect(),
+            );
         }
     }
 
+    /// Number of distinct URIs and FQNs shared by indexed symbols.
+    pub fn interned_string_count(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Total bytes held by the shared URI and FQN pool.
+    pub fn interned_string_bytes(&self) -> usize {
+        self.strings.byte_len()
+    }
+
     fn remove_top_level_symbol(
         &self,
         removed_uri: &str,
         removed_symbol: &SymbolInfo,
-        symbols: &DashMap<String, Arc<SymbolInfo>>,
+        symbols: &DashMap<SharedStr, Arc<SymbolInfo>>,
     ) {
         let should_remove = symbols
-            .get(&removed_symbol.fqn)
+            .get(removed_symbol.fqn.as_str())
             .is_some_and(|entry| entry.uri == removed_uri);
         if !should_remove {
             return;
         }
 
-        symbols.remove(&removed_symbol.fqn);
+        symbols.remove(removed_symbol.fqn.as_str());
 
         if let Some(replacement) = self.find_top_level_symbol_replacement(removed_symbol) {
             symbols.insert(removed_symbol.fqn.clone(), replacement);
@@ -189,15 +274,19 @@ impl WorkspaceIndex {
     /// prefix in the symbol but not in the query.
     /// Walks the class hierarchy (extends/implements) when the member is not
     /// found directly on the given class.
+    ///
+    /// A generic owner such as `App\Collection<int, App\User>::first` binds
+    /// the class's `@template` parameters in the returned member signature.
     pub fn resolve_member(&self, fqn: &str) -> Option<Arc<SymbolInfo>> {
         let (class_fqn, member_name) = fqn.rsplit_once("::")?;
+        let (class_fqn, substitutions) = self.generic_owner_substitutions(class_fqn);
         self.resolve_member_in_hierarchy(
-            class_fqn,
+            &class_fqn,
             member_name,
-            fqn,
+            &format!("{class_fqn}::{member_name}"),
             None,
             &mut HashSet::new(),
-            &TemplateSubstitutions::new(),
+            &substitutions,
         )
     }
 
@@ -208,16 +297,46 @@ impl WorkspaceIndex {
         expected_kinds: &[PhpSymbolKind],
     ) -> Option<Arc<SymbolInfo>> {
         let (class_fqn, member_name) = fqn.rsplit_once("::")?;
+        let (class_fqn, substitutions) = self.generic_owner_substitutions(class_fqn);
         self.resolve_member_in_hierarchy(
-            class_fqn,
+            &class_fqn,
             member_name,
-            fqn,
+            &format!("{class_fqn}::{member_name}"),
             Some(expected_kinds),
             &mut HashSet::new(),
-            &TemplateSubstitutions::new(),
+            &substitutions,
         )
     }
 
+    /// Split a generic owner (`App\Collection<int, App\User>`) into its class
+    /// FQN and the bindings for that class's own template parameters.
+    fn generic_owner_substitutions(&self, owner: &str) -> (String, TemplateSubstitutions) {
+        let owner = owner.trim();
+        if !owner.contains('<') {
+            return (owner.to_string(), TemplateSubstitutions::new());
+        }
+        let Some(TypeInfo::Generic { base, args }) =
+            php_lsp_parser::phpdoc::parse_phpdoc(&format!("/** @var {owner} */")).var_type
+        else {
+            return (owner.to_string(), TemplateSubstitutions::new());
+        };
+        let class_fqn = base.trim_start_matches('\\').to_string();
+        let substitutions = self
+            .types
+            .get(class_fqn.as_str())
+            .map(|entry| {
+                entry
+                    .value()
+                    .templates
+                    .iter()
+                    .zip(args)
+                    .map(|(template, arg)| (template.name.clone(), arg))
+                    .collect()
+            })
+            .unwrap_or_default();
+        (class_fqn, substitutions)
+    }
+
     /// Internal helper: resolve member walking the inheritance chain.
     /// `visited` prevents infinite loops when there are circular references.
     fn resolve_member_in_hierarchy(
@@ -251,6 +370,16 @@ impl WorkspaceIndex {
 
         // Walk the class hierarchy: look up extends and implements
         if let Some(class_sym) = self.types.get(class_fqn).map(|r| r.value().clone()) {
+            // `use A { hello as helloA; }` aliases resolve to the trait method.
+            if let Some(sym) = self.resolve_trait_method_alias(
+                &class_sym,
+                member_name,
+                expected_kinds,
+                visited,
+                substitutions,
+            ) {
+                return Some(sym);
+            }
             // Try traits first: their members are mixed into the class/trait body.
             for trait_fqn in &class_sym.traits {
                 let edge_substitutions =
@@ -263,7 +392,12 @@ impl WorkspaceIndex {
                     visited,
                     &edge_substitutions,
                 ) {
-                    return Some(sym);
+                    if sym.kind == PhpSymbolKind::Method
+                        && trait_method_is_excluded(&class_sym, trait_fqn, &sym.name)
+                    {
+                        continue;
+                    }
+                    return Some(adapt_trait_method_visibility(&class_sym, trait_fqn, sym));
                 }
             }
             // Try PHPDoc mixins as member providers.
@@ -321,28 +455,126 @@ impl WorkspaceIndex {
         None
     }
 
-    /// Search symbols by name (simple substring match for now).
-    pub fn search(&self, query: &str) -> Vec<Arc<SymbolInfo>> {
-        let query_lower = query.to_lowercase();
-        let mut results = Vec::new();
+    /// Resolve `member_name` as an `as` alias declared in `class_sym`'s trait
+    /// `use` blocks, returning the trait method under the alias name.
+    fn resolve_trait_method_alias(
+        &self,
+        class_sym: &SymbolInfo,
+        member_name: &str,
+        expected_kinds: Option<&[PhpSymbolKind]>,
+        visited: &HashSet<String>,
+        substitutions: &TemplateSubstitutions,
+    ) -> Option<Arc<SymbolInfo>> {
+        if !member_kind_matches(PhpSymbolKind::Method, expected_kinds) {
+            return None;
+        }
+        class_sym.trait_adaptations.iter().find_map(|adaptation| {
+            let TraitAdaptationKind::Alias {
+                alias: Some(alias),
+                visibility,
+            } = &adaptation.kind
+            else {
+                return None;
+            };
+            if !alias.eq_ignore_ascii_case(member_name) {
+                return None;
+            }
+            class_sym
+                .traits
+                .iter()
+                .filter(|trait_fqn| {
+                    adaptation
+                        .trait_fqn
+                        .as_deref()
+                        .is_none_or(|named| same_fqn(named, trait_fqn))
+                })
+                .find_map(|trait_fqn| {
+                    let edge_substitutions =
+                        self.template_substitutions_for_edge(class_sym, trait_fqn, substitutions);
+                    self.resolve_member_in_hierarchy(
+                        trait_fqn,
+                        &adaptation.method,
+                        &format!("{trait_fqn}::{}", adaptation.method),
+                        Some(&[PhpSymbolKind::Method]),
+                        &mut visited.clone(),
+                        &edge_substitutions,
+                    )
+                })
+                .map(|method| aliased_trait_method(method, Some(alias), *visibility))
+        })
+    }
 
-        for entry in self.types.iter() {
-            if entry.value().name.to_lowercase().contains(&query_lower) {
-                results.push(entry.value().clone());
+    /// Apply `class_sym`'s `insteadof`/`as` rules to the members collected
+    /// through its `use trait_fqn;` edge.
+    fn adapt_trait_members(
+        &self,
+        class_sym: &SymbolInfo,
+        trait_fqn: &str,
+        trait_members: Vec<Arc<SymbolInfo>>,
+    ) -> Vec<Arc<SymbolInfo>> {
+        let mut adapted = Vec::with_capacity(trait_members.len());
+        for member in trait_members {
+            if member.kind != PhpSymbolKind::Method {
+                adapted.push(member);
+                continue;
+            }
+            for adaptation in &class_sym.trait_adaptations {
+                if let TraitAdaptationKind::Alias {
+                    alias: Some(alias),
+                    visibility,
+                } = &adaptation.kind
+                {
+                    if trait_adaptation_applies(adaptation, trait_fqn, &member.name) {
+                        adapted.push(aliased_trait_method(
+                            member.clone(),
+                            Some(alias),
+                            *visibility,
+                        ));
+                    }
+                }
+            }
+            if !trait_method_is_excluded(class_sym, trait_fqn, &member.name) {
+                adapted.push(adapt_trait_method_visibility(class_sym, trait_fqn, member));
             }
         }
-        for entry in self.functions.iter() {
-            if entry.value().name.to_lowercase().contains(&query_lower) {
-                results.push(entry.value().clone());
+        adapted
+    }
+
+    /// Search symbols with a [`SymbolQuery`], best matches first. Names match
+    /// exactly, by prefix, by camelCase humps (`USvc` for `UserService`), as a
+    /// substring or as a fuzzy abbreviation; workspace symbols outrank vendor
+    /// ones within each tier. `#kind`, `Class::member` and trailing-backslash
+    /// namespace queries narrow the results.
+    pub fn search(&self, query: &str) -> Vec<Arc<SymbolInfo>> {
+        let query = SymbolQuery::parse(query);
+        let mut results = Vec::new();
+
+        for map in [&self.types, &self.functions, &self.constants] {
+            for entry in map.iter() {
+                if let Some(score) = query.score(entry.value()) {
+                    results.push((score, entry.value().clone()));
+                }
             }
         }
-        for entry in self.constants.iter() {
-            if entry.value().name.to_lowercase().contains(&query_lower) {
-                results.push(entry.value().clone());
+        if query.includes_members() {
+            for entry in self.file_symbols.iter() {
+                for sym in &entry.value().symbols {
+                    if sym.parent_fqn.is_none() {
+                        continue;
+                    }
+                    if let Some(score) = query.score(sym) {
+                        results.push((score, Arc::new(sym.clone())));
+                    }
+                }
             }
         }
 
-        results
+        results.sort_by(|(left_score, left), (right_score, right)| {
+            right_score
+                .cmp(left_score)
+                .then_with(|| left.fqn.cmp(&right.fqn))
+        });
+        results.into_iter().map(|(_, sym)| sym).collect()
     }
 
     /// Get members (methods, properties, constants) of a type by its FQN.
@@ -403,7 +635,23 @@ impl WorkspaceIndex {
             for trait_fqn in &class_sym.traits {
                 let edge_substitutions =
                     self.template_substitutions_for_edge(&class_sym, trait_fqn, substitutions);
-                self.collect_members_recursive(trait_fqn, members, visited, &edge_substitutions);
+                if class_sym.trait_adaptations.is_empty() {
+                    self.collect_members_recursive(
+                        trait_fqn,
+                        members,
+                        visited,
+                        &edge_substitutions,
+                    );
+                    continue;
+                }
+                let mut trait_members = Vec::new();
+                self.collect_members_recursive(
+                    trait_fqn,
+                    &mut trait_members,
+                    visited,
+                    &edge_substitutions,
+                );
+                members.extend(self.adapt_trait_members(&class_sym, trait_fqn, trait_members));
             }
             for mixin_fqn in class_sym
                 .template_bindings
@@ -488,6 +736,13 @@ impl WorkspaceIndex {
                 .signature
                 .as_ref()
                 .map(|signature| substitute_signature(signature, &scoped_substitutions));
+            // PHPDoc types win over less specific native ones downstream, so
+            // bind templates in the doc comment too.
+            materialized.doc_comment = materialized.doc_comment.as_deref().map(|doc| {
+                php_lsp_parser::phpdoc::map_phpdoc_tag_types(doc, |type_info| {
+                    substitute_type_info(type_info, &scoped_substitutions)
+                })
+            });
             changed = true;
         }
 
@@ -519,6 +774,7 @@ impl WorkspaceIndex {
                 .return_type
                 .as_ref()
                 .map(|type_info| self.expand_type_aliases(type_info, scope, &mut Vec::new())),
+            throws: signature.throws.clone(),
         }
     }
 
@@ -681,7 +937,7 @@ impl WorkspaceIndex {
             .map(|entry| entry.value().clone())?;
         let file_symbols = self
             .file_symbols
-            .get(&class_symbol.uri)
+            .get(class_symbol.uri.as_str())
             .map(|entry| entry.value().clone());
         let phpdoc = class_symbol
             .doc_comment
@@ -806,13 +1062,131 @@ impl WorkspaceIndex {
     }
 }
 
+/// Lowercase short name used to bucket references: the member name for
+/// `Class::member`, otherwise the last namespace segment.
+pub fn reference_name_key(target_fqn: &str) -> String {
+    let name = target_fqn
+        .rsplit_once("::")
+        .map_or(target_fqn, |(_, member)| member);
+    let name = name.rsplit('\\').next().unwrap_or(name);
+    name.trim_start_matches('$').to_ascii_lowercase()
+}
+
+fn remember_short_name(short_names: &DashMap<String, HashSet<SharedStr>>, fqn: &SharedStr) {
+    short_names
+        .entry(reference_name_key(fqn))
+        .or_default()
+        .insert(fqn.clone());
+}
+
+/// Drop `fqn` from the short-name lookup once no file declares it anymore.
+fn forget_short_name(
+    short_names: &DashMap<String, HashSet<SharedStr>>,
+    symbols: &DashMap<SharedStr, Arc<SymbolInfo>>,
+    fqn: &str,
+) {
+    if symbols.contains_key(fqn) {
+        return;
+    }
+    let key = reference_name_key(fqn);
+    if let Some(mut fqns) = short_names.get_mut(&key) {
+        fqns.remove(fqn);
+    }
+    short_names.remove_if(&key, |_, fqns| fqns.is_empty());
+}
+
+fn symbols_named(
+    short_names: &DashMap<String, HashSet<SharedStr>>,
+    symbols: &DashMap<SharedStr, Arc<SymbolInfo>>,
+    short_name: &str,
+) -> Vec<Arc<SymbolInfo>> {
+    let Some(fqns) = short_names.get(&reference_name_key(short_name)) else {
+        return Vec::new();
+    };
+    fqns.iter()
+        .filter_map(|fqn| symbols.get(fqn.as_str()).map(|entry| entry.value().clone()))
+        .collect()
+}
+
+fn reference_names(references: &[SymbolReference]) -> HashSet<String> {
+    references
+        .iter()
+        .map(|reference| reference_name_key(&reference.target_fqn))
+        .collect()
+}
+
+fn trait_adaptation_applies(
+    adaptation: &php_lsp_types::TraitAdaptation,
+    trait_fqn: &str,
+    method_name: &str,
+) -> bool {
+    adaptation.method.eq_ignore_ascii_case(method_name)
+        && adaptation
+            .trait_fqn
+            .as_deref()
+            .is_none_or(|named| same_fqn(named, trait_fqn))
+}
+
+/// Whether an `X::method insteadof trait_fqn` rule drops the method taken
+/// through `trait_fqn`.
+fn trait_method_is_excluded(class_sym: &SymbolInfo, trait_fqn: &str, method_name: &str) -> bool {
+    class_sym.trait_adaptations.iter().any(|adaptation| {
+        matches!(&adaptation.kind, TraitAdaptationKind::InsteadOf { excluded }
+            if adaptation.method.eq_ignore_ascii_case(method_name)
+                && excluded.iter().any(|excluded| same_fqn(excluded, trait_fqn)))
+    })
+}
+
+/// Apply a `method as protected;` visibility change to a trait method.
+fn adapt_trait_method_visibility(
+    class_sym: &SymbolInfo,
+    trait_fqn: &str,
+    method: Arc<SymbolInfo>,
+) -> Arc<SymbolInfo> {
+    if method.kind != PhpSymbolKind::Method {
+        return method;
+    }
+    let visibility =
+        class_sym
+            .trait_adaptations
+            .iter()
+            .find_map(|adaptation| match &adaptation.kind {
+                TraitAdaptationKind::Alias {
+                    alias: None,
+                    visibility,
+                } if trait_adaptation_applies(adaptation, trait_fqn, &method.name) => *visibility,
+                _ => None,
+            });
+    aliased_trait_method(method, None, visibility)
+}
+
+/// A trait method renamed and/or with changed visibility. The FQN, location
+/// and signature stay those of the trait declaration.
+fn aliased_trait_method(
+    method: Arc<SymbolInfo>,
+    alias: Option<&str>,
+    visibility: Option<Visibility>,
+) -> Arc<SymbolInfo> {
+    if alias.is_none() && visibility.is_none() {
+        return method;
+    }
+    let mut adapted = (*method).clone();
+    if let Some(alias) = alias {
+        adapted.name = alias.to_string();
+    }
+    if let Some(visibility) = visibility {
+        adapted.visibility = visibility;
+    }
+    Arc::new(adapted)
+}
+
 fn same_fqn(left: &str, right: &str) -> bool {
     left.trim_start_matches('\\') == right.trim_start_matches('\\')
 }
 
 fn alias_scope_for_symbol(symbol: &SymbolInfo) -> TypeAliasScope {
     if let Some(parent_fqn) = symbol.parent_fqn.as_ref() {
-        TypeAliasScope::Class(parent_fqn.clone())
+        TypeAliasScope::Class(parent_fqn.to_string())
     } else if matches!(
         symbol.kind,
         PhpSymbolKind::Class
@@ -820,9 +1194,9 @@ fn alias_scope_for_symbol(symbol: &SymbolInfo) -> TypeAliasScope {
             | PhpSymbolKind::Trait
             | PhpSymbolKind::Enum
     ) {
-        TypeAliasScope::Class(symbol.fqn.clone())
+        TypeAliasScope::Class(symbol.fqn.to_string())
     } else {
-        TypeAliasScope::File(symbol.uri.clone())
+        TypeAliasScope::File(symbol.uri.to_string())
     }
 }
 
@@ -1083,6 +1457,7 @@ fn substitute_signature(signature: &Signature, substitutions: &TemplateSubstitut
             .return_type
             .as_ref()
             .map(|type_info| substitute_type_info(type_info, substitutions)),
+        throws: signature.throws.clone(),
     }
 }
 
@@ -1189,9 +1564,9 @@ mod tests {
     fn make_class(name: &str, fqn: &str, uri: &str) -> SymbolInfo {
         SymbolInfo {
             name: name.to_string(),
-            fqn: fqn.to_string(),
+            fqn: fqn.into(),
             kind: PhpSymbolKind::Class,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: (0, 0, 10, 0),
             selection_range: (0, 6, 0, 6 + name.len() as u32),
             visibility: Visibility::Public,
@@ -1199,21 +1574,23 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         }
     }
 
     fn make_function(name: &str, fqn: &str, uri: &str) -> SymbolInfo {
         SymbolInfo {
             name: name.to_string(),
-            fqn: fqn.to_string(),
+            fqn: fqn.into(),
             kind: PhpSymbolKind::Function,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: (0, 0, 5, 0),
             selection_range: (0, 9, 0, 9 + name.len() as u32),
             visibility: Visibility::Public,
@@ -1221,21 +1598,23 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         }
     }
 
     fn make_method(name: &str, parent_fqn: &str, uri: &str) -> SymbolInfo {
         SymbolInfo {
             name: name.to_string(),
-            fqn: format!("{parent_fqn}::{name}"),
+            fqn: format!("{parent_fqn}::{name}").into(),
             kind: PhpSymbolKind::Method,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: (1, 4, 3, 5),
             selection_range: (1, 20, 1, 20 + name.len() as u32),
             visibility: Visibility::Public,
@@ -1243,12 +1622,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
-            parent_fqn: Some(parent_fqn.to_string()),
+            value: None,
+            parent_fqn: Some(parent_fqn.into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         }
     }
 
@@ -1288,6 +1669,79 @@ mod tests {
         assert!(index.resolve_fqn("App\\Foo").is_none());
     }
 
+    #[test]
+    fn test_types_named_tracks_short_names_across_files() {
+        let index = WorkspaceIndex::new();
+        for (uri, fqn) in [
+            ("file:///a.php", "App\\Mailer"),
+            ("file:///b.php", "Legacy\\Mailer"),
+            ("file:///c.php", "Legacy\\Mailer"),
+        ] {
+            index.update_file(
+                uri,
+                FileSymbols {
+                    symbols: vec![make_class("Mailer", fqn, uri)],
+                    ..Default::default()
+                },
+            );
+        }
+        index.update_file(
+            "file:///d.php",
+            FileSymbols {
+                symbols: vec![make_function("mailer", "App\\mailer", "file:///d.php")],
+                ..Default::default()
+            },
+        );
+        let fqns = |symbols: Vec<Arc<SymbolInfo>>| {
+            let mut fqns: Vec<String> = symbols.iter().map(|sym| sym.fqn.to_string()).collect();
+            fqns.sort();
+            fqns
+        };
+
+        assert_eq!(
+            fqns(index.types_named("mailer")),
+            vec!["App\\Mailer", "Legacy\\Mailer"]
+        );
+        assert_eq!(fqns(index.functions_named("Mailer")), vec!["App\\mailer"]);
+
+        index.remove_file("file:///b.php");
+        assert_eq!(
+            fqns(index.types_named("Mailer")),
+            vec!["App\\Mailer", "Legacy\\Mailer"]
+        );
+        index.remove_file("file:///c.php");
+        index.remove_file("file:///d.php");
+        assert_eq!(fqns(index.types_named("Mailer")), vec!["App\\Mailer"]);
+        assert!(index.functions_named("mailer").is_empty());
+    }
+
+    #[test]
+    fn test_update_interns_symbol_uris_and_fqns() {
+        let index = WorkspaceIndex::new();
+        let file_symbols = FileSymbols {
+            namespace: Some("App".to_string()),
+            symbols: vec![
+                make_class("Foo", "App\\Foo", "file:///foo.php"),
+                make_method("bar", "App\\Foo", "file:///foo.php"),
+                make_method("baz", "App\\Foo", "file:///foo.php"),
+            ],
+            ..Default::default()
+        };
+        index.update_file("file:///foo.php", file_symbols);
+
+        let class = index.resolve_fqn("App\\Foo").unwrap();
+        let stored = index.file_symbols.get("file:///foo.php").unwrap();
+        for member in &stored.symbols[1..] {
+            assert!(member.uri.ptr_eq(&class.uri));
+            assert!(member.parent_fqn.as_ref().unwrap().ptr_eq(&class.fqn));
+        }
+        drop((stored, class));
+        assert_eq!(index.interned_string_count(), 4);
+
+        index.remove_file("file:///foo.php");
+        assert_eq!(index.interned_string_count(), 0);
+    }
+
     #[test]
     fn test_remove_file_preserves_duplicate_fqn_from_other_file() {
         let index = WorkspaceIndex::new();
@@ -1315,6 +1769,49 @@ mod tests {
         assert_eq!(found.uri, "file:///b.php");
     }
 
+    #[test]
+    fn test_files_referencing_name_tracks_updates_and_removals() {
+        let reference = |target_fqn: &str, target_kind: PhpSymbolKind| SymbolReference {
+            target_fqn: target_fqn.to_string(),
+            target_kind,
+            range: (1, 0, 1, 3),
+            is_declaration: false,
+            starts_with_dollar: false,
+            receiver: SymbolReferenceReceiver::None,
+            is_string_literal: false,
+            usage: SymbolReferenceUsage::Other,
+        };
+        let index = WorkspaceIndex::new();
+        index.update_file_with_references(
+            "file:///b.php",
+            FileSymbols::default(),
+            vec![
+                reference("App\\Foo", PhpSymbolKind::Class),
+                reference("App\\Foo::$items", PhpSymbolKind::Property),
+            ],
+        );
+        index.update_file_with_references(
+            "file:///a.php",
+            FileSymbols::default(),
+            vec![reference("App\\Other\\foo", PhpSymbolKind::Function)],
+        );
+
+        assert_eq!(
+            index.files_referencing_name("\\App\\Foo"),
+            vec!["file:///a.php".to_string(), "file:///b.php".to_string()]
+        );
+        assert_eq!(
+            index.files_referencing_name("App\\Bar::items"),
+            vec!["file:///b.php".to_string()]
+        );
+
+        index.update_file_with_references("file:///b.php", FileSymbols::default(), vec![]);
+        assert!(index.files_referencing_name("App\\Foo::$items").is_empty());
+
+        index.remove_file("file:///a.php");
+        assert!(index.files_referencing_name("App\\Foo").is_empty());
+    }
+
     #[test]
     fn test_search() {
         let index = WorkspaceIndex::new();
@@ -1365,9 +1862,9 @@ mod tests {
         let class_sym = make_class("Foo", "App\\Foo", "file:///test.php");
         let method_sym = SymbolInfo {
             name: "increment".to_string(),
-            fqn: "App\\Foo::increment".to_string(),
+            fqn: "App\\Foo::increment".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///test.php".to_string(),
+            uri: "file:///test.php".into(),
             range: (10, 0, 15, 0),
             selection_range: (10, 20, 10, 29),
             visibility: Visibility::Public,
@@ -1375,12 +1872,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
-            parent_fqn: Some("App\\Foo".to_string()),
+            value: None,
+            parent_fqn: Some("App\\Foo".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let file_symbols = FileSymbols {
             namespace: Some("App".to_string()),
@@ -1414,13 +1913,13 @@ mod tests {
         let method_sym = make_method("propFind", "App\\Foo", uri);
         let mut conflicting_property_sym = make_method("propfind", "App\\Foo", uri);
         conflicting_property_sym.kind = PhpSymbolKind::Property;
-        conflicting_property_sym.fqn = "App\\Foo::$propfind".to_string();
+        conflicting_property_sym.fqn = "App\\Foo::$propfind".into();
         let mut property_sym = make_method("PortingNumber", "App\\Foo", uri);
         property_sym.kind = PhpSymbolKind::Property;
-        property_sym.fqn = "App\\Foo::$PortingNumber".to_string();
+        property_sym.fqn = "App\\Foo::$PortingNumber".into();
         let mut constant_sym = make_method("STATE_READY", "App\\Foo", uri);
         constant_sym.kind = PhpSymbolKind::ClassConstant;
-        constant_sym.fqn = "App\\Foo::STATE_READY".to_string();
+        constant_sym.fqn = "App\\Foo::STATE_READY".into();
 
         index.update_file(
             uri,
@@ -1478,9 +1977,9 @@ mod tests {
         // Parent class with a method
         let parent_class = SymbolInfo {
             name: "SoapHandler".to_string(),
-            fqn: "App\\SoapHandler".to_string(),
+            fqn: "App\\SoapHandler".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///parent.php".to_string(),
+            uri: "file:///parent.php".into(),
             range: (0, 0, 20, 0),
             selection_range: (0, 6, 0, 17),
             visibility: Visibility::Public,
@@ -1488,18 +1987,20 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let parent_method = SymbolInfo {
             name: "okResponse".to_string(),
-            fqn: "App\\SoapHandler::okResponse".to_string(),
+            fqn: "App\\SoapHandler::okResponse".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///parent.php".to_string(),
+            uri: "file:///parent.php".into(),
             range: (5, 4, 8, 5),
             selection_range: (5, 20, 5, 30),
             visibility: Visibility::Protected,
@@ -1507,12 +2008,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
-            parent_fqn: Some("App\\SoapHandler".to_string()),
+            value: None,
+            parent_fqn: Some("App\\SoapHandler".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let parent_file = FileSymbols {
             namespace: Some("App".to_string()),
@@ -1525,9 +2028,9 @@ mod tests {
         // Child class that extends the parent
         let child_class = SymbolInfo {
             name: "TestHandler".to_string(),
-            fqn: "App\\TestHandler".to_string(),
+            fqn: "App\\TestHandler".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///child.php".to_string(),
+            uri: "file:///child.php".into(),
             range: (0, 0, 5, 0),
             selection_range: (0, 6, 0, 17),
             visibility: Visibility::Public,
@@ -1535,12 +2038,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec!["App\\SoapHandler".to_string()],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let child_file = FileSymbols {
             namespace: Some("App".to_string()),
@@ -1613,9 +2118,9 @@ mod tests {
 
         let trait_sym = SymbolInfo {
             name: "Assertions".to_string(),
-            fqn: "App\\Assertions".to_string(),
+            fqn: "App\\Assertions".into(),
             kind: PhpSymbolKind::Trait,
-            uri: "file:///trait.php".to_string(),
+            uri: "file:///trait.php".into(),
             range: (0, 0, 10, 0),
             selection_range: (0, 6, 0, 16),
             visibility: Visibility::Public,
@@ -1623,18 +2128,20 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let trait_method = SymbolInfo {
             name: "assertOk".to_string(),
-            fqn: "App\\Assertions::assertOk".to_string(),
+            fqn: "App\\Assertions::assertOk".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///trait.php".to_string(),
+            uri: "file:///trait.php".into(),
             range: (2, 4, 4, 5),
             selection_range: (2, 20, 2, 28),
             visibility: Visibility::Public,
@@ -1642,12 +2149,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
-            parent_fqn: Some("App\\Assertions".to_string()),
+            value: None,
+            parent_fqn: Some("App\\Assertions".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///trait.php",
@@ -1661,9 +2170,9 @@ mod tests {
 
         let class_sym = SymbolInfo {
             name: "TestCase".to_string(),
-            fqn: "App\\TestCase".to_string(),
+            fqn: "App\\TestCase".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///class.php".to_string(),
+            uri: "file:///class.php".into(),
             range: (0, 0, 5, 0),
             selection_range: (0, 6, 0, 14),
             visibility: Visibility::Public,
@@ -1671,12 +2180,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec!["App\\Assertions".to_string()],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///class.php",
@@ -1700,9 +2211,9 @@ mod tests {
         // Two classes that extend each other (pathological case)
         let class_a = SymbolInfo {
             name: "A".to_string(),
-            fqn: "A".to_string(),
+            fqn: "A".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///a.php".to_string(),
+            uri: "file:///a.php".into(),
             range: (0, 0, 5, 0),
             selection_range: (0, 6, 0, 7),
             visibility: Visibility::Public,
@@ -1710,18 +2221,20 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec!["B".to_string()],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let class_b = SymbolInfo {
             name: "B".to_string(),
-            fqn: "B".to_string(),
+            fqn: "B".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///b.php".to_string(),
+            uri: "file:///b.php".into(),
             range: (0, 0, 5, 0),
             selection_range: (0, 6, 0, 7),
             visibility: Visibility::Public,
@@ -1729,12 +2242,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec!["A".to_string()],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let file_a = FileSymbols {
             namespace: None,
@@ -1755,6 +2270,99 @@ mod tests {
         assert!(index.resolve_fqn("A::nonexistent").is_none());
     }
 
+    #[test]
+    fn test_trait_insteadof_and_as_adapt_members() {
+        let index = WorkspaceIndex::new();
+        let uri = "file:///traits.php";
+
+        let mut trait_a = make_class("A", "App\\A", uri);
+        trait_a.kind = PhpSymbolKind::Trait;
+        let mut trait_b = make_class("B", "App\\B", uri);
+        trait_b.kind = PhpSymbolKind::Trait;
+        let mut class = make_class("Greeter", "App\\Greeter", uri);
+        class.traits = vec!["App\\A".to_string(), "App\\B".to_string()];
+        class.trait_adaptations = vec![
+            TraitAdaptation {
+                trait_fqn: Some("App\\A".to_string()),
+                method: "hello".to_string(),
+                kind: TraitAdaptationKind::InsteadOf {
+                    excluded: vec!["App\\B".to_string()],
+                },
+            },
+            TraitAdaptation {
+                trait_fqn: Some("App\\B".to_string()),
+                method: "hello".to_string(),
+                kind: TraitAdaptationKind::Alias {
+                    alias: Some("helloB".to_string()),
+                    visibility: Some(Visibility::Protected),
+                },
+            },
+            TraitAdaptation {
+                trait_fqn: None,
+                method: "world".to_string(),
+                kind: TraitAdaptationKind::Alias {
+                    alias: None,
+                    visibility: Some(Visibility::Private),
+                },
+            },
+        ];
+        index.update_file(
+            uri,
+            FileSymbols {
+                symbols: vec![
+                    trait_a,
+                    make_method("hello", "App\\A", uri),
+                    trait_b,
+                    make_method("hello", "App\\B", uri),
+                    make_method("world", "App\\B", uri),
+                    class,
+                ],
+                ..Default::default()
+            },
+        );
+
+        let members: Vec<(String, String, Visibility)> = index
+            .get_members("App\\Greeter")
+            .iter()
+            .map(|member| {
+                (
+                    member.name.clone(),
+                    member.fqn.to_string(),
+                    member.visibility,
+                )
+            })
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                (
+                    "hello".to_string(),
+                    "App\\A::hello".to_string(),
+                    Visibility::Public
+                ),
+                (
+                    "helloB".to_string(),
+                    "App\\B::hello".to_string(),
+                    Visibility::Protected
+                ),
+                (
+                    "world".to_string(),
+                    "App\\B::world".to_string(),
+                    Visibility::Private
+                ),
+            ]
+        );
+
+        let hello = index.resolve_member("App\\Greeter::hello").unwrap();
+        assert_eq!(hello.fqn.as_str(), "App\\A::hello");
+        let alias = index.resolve_member("App\\Greeter::helloB").unwrap();
+        assert_eq!(alias.name, "helloB");
+        assert_eq!(alias.fqn.as_str(), "App\\B::hello");
+        assert_eq!(alias.visibility, Visibility::Protected);
+        let world = index.resolve_member("App\\Greeter::world").unwrap();
+        assert_eq!(world.visibility, Visibility::Private);
+    }
+
     #[test]
     fn test_hierarchy_visited_sets_handle_trait_mixin_and_parent_cycles() {
         let index = WorkspaceIndex::new();
@@ -1850,9 +2458,9 @@ mod tests {
         // Step 1: Index child class (extends a parent not yet indexed)
         let child_class = SymbolInfo {
             name: "MyTest".to_string(),
-            fqn: "App\\MyTest".to_string(),
+            fqn: "App\\MyTest".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///tests/MyTest.php".to_string(),
+            uri: "file:///tests/MyTest.php".into(),
             range: (0, 0, 10, 0),
             selection_range: (0, 6, 0, 12),
             visibility: Visibility::Public,
@@ -1860,12 +2468,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec!["Vendor\\TestCase".to_string()],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let child_file = FileSymbols {
             namespace: Some("App".to_string()),
@@ -1884,9 +2494,9 @@ mod tests {
         // Step 2: Index parent class (vendor lazy-load simulation)
         let parent_class = SymbolInfo {
             name: "TestCase".to_string(),
-            fqn: "Vendor\\TestCase".to_string(),
+            fqn: "Vendor\\TestCase".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///vendor/TestCase.php".to_string(),
+            uri: "file:///vendor/TestCase.php".into(),
             range: (0, 0, 20, 0),
             selection_range: (0, 6, 0, 14),
             visibility: Visibility::Public,
@@ -1894,18 +2504,20 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec!["Vendor\\BaseAssert".to_string()],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let parent_method = SymbolInfo {
             name: "doSetUp".to_string(),
-            fqn: "Vendor\\TestCase::doSetUp".to_string(),
+            fqn: "Vendor\\TestCase::doSetUp".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///vendor/TestCase.php".to_string(),
+            uri: "file:///vendor/TestCase.php".into(),
             range: (5, 4, 8, 5),
             selection_range: (5, 20, 5, 27),
             visibility: Visibility::Protected,
@@ -1913,12 +2525,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
-            parent_fqn: Some("Vendor\\TestCase".to_string()),
+            value: None,
+            parent_fqn: Some("Vendor\\TestCase".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let parent_file = FileSymbols {
             namespace: Some("Vendor".to_string()),
@@ -1939,9 +2553,9 @@ mod tests {
         // Step 3: Index grandparent class (deeper vendor lazy-load)
         let gp_class = SymbolInfo {
             name: "BaseAssert".to_string(),
-            fqn: "Vendor\\BaseAssert".to_string(),
+            fqn: "Vendor\\BaseAssert".into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///vendor/BaseAssert.php".to_string(),
+            uri: "file:///vendor/BaseAssert.php".into(),
             range: (0, 0, 30, 0),
             selection_range: (0, 6, 0, 16),
             visibility: Visibility::Public,
@@ -1949,18 +2563,20 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let gp_method = SymbolInfo {
             name: "createStub".to_string(),
-            fqn: "Vendor\\BaseAssert::createStub".to_string(),
+            fqn: "Vendor\\BaseAssert::createStub".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///vendor/BaseAssert.php".to_string(),
+            uri: "file:///vendor/BaseAssert.php".into(),
             range: (10, 4, 13, 5),
             selection_range: (10, 20, 10, 30),
             visibility: Visibility::Public,
@@ -1968,12 +2584,14 @@ mod tests {
             attributes: vec![],
             doc_comment: None,
             signature: None,
-            parent_fqn: Some("Vendor\\BaseAssert".to_string()),
+            value: None,
+            parent_fqn: Some("Vendor\\BaseAssert".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         let gp_file = FileSymbols {
             namespace: Some("Vendor".to_string()),
@@ -2005,9 +2623,9 @@ mod tests {
         }];
         let repository_method = SymbolInfo {
             name: "find".to_string(),
-            fqn: "App\\Repository::find".to_string(),
+            fqn: "App\\Repository::find".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///repo.php".to_string(),
+            uri: "file:///repo.php".into(),
             range: (3, 4, 3, 40),
             selection_range: (3, 20, 3, 24),
             visibility: Visibility::Public,
@@ -2017,13 +2635,16 @@ mod tests {
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(TypeInfo::Simple("TEntity".to_string())),
+                throws: Vec::new(),
             }),
-            parent_fqn: Some("App\\Repository".to_string()),
+            value: None,
+            parent_fqn: Some("App\\Repository".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///repo.php",
@@ -2080,9 +2701,9 @@ mod tests {
         }];
         let first_method = SymbolInfo {
             name: "first".to_string(),
-            fqn: "App\\Collection::first".to_string(),
+            fqn: "App\\Collection::first".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///collection.php".to_string(),
+            uri: "file:///collection.php".into(),
             range: (3, 4, 3, 40),
             selection_range: (3, 20, 3, 25),
             visibility: Visibility::Public,
@@ -2092,13 +2713,16 @@ mod tests {
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(TypeInfo::Simple("TItem".to_string())),
+                throws: Vec::new(),
             }),
-            parent_fqn: Some("App\\Collection".to_string()),
+            value: None,
+            parent_fqn: Some("App\\Collection".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///collection.php",
@@ -2151,9 +2775,9 @@ mod tests {
             Some("/**\n * @phpstan-type UserShape array{id: int, name?: string}\n */".to_string());
         let method = SymbolInfo {
             name: "getShape".to_string(),
-            fqn: "App\\UserService::getShape".to_string(),
+            fqn: "App\\UserService::getShape".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///service.php".to_string(),
+            uri: "file:///service.php".into(),
             range: (5, 4, 7, 5),
             selection_range: (5, 20, 5, 28),
             visibility: Visibility::Public,
@@ -2163,13 +2787,16 @@ mod tests {
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(TypeInfo::Simple("UserShape".to_string())),
+                throws: Vec::new(),
             }),
-            parent_fqn: Some("App\\UserService".to_string()),
+            value: None,
+            parent_fqn: Some("App\\UserService".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///service.php",
@@ -2220,9 +2847,9 @@ mod tests {
         );
         let method = SymbolInfo {
             name: "getShape".to_string(),
-            fqn: "App\\UserService::getShape".to_string(),
+            fqn: "App\\UserService::getShape".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///service.php".to_string(),
+            uri: "file:///service.php".into(),
             range: (5, 4, 7, 5),
             selection_range: (5, 20, 5, 28),
             visibility: Visibility::Public,
@@ -2232,13 +2859,16 @@ mod tests {
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(TypeInfo::Simple("LocalShape".to_string())),
+                throws: Vec::new(),
             }),
-            parent_fqn: Some("App\\UserService".to_string()),
+            value: None,
+            parent_fqn: Some("App\\UserService".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///service.php",
@@ -2268,9 +2898,9 @@ mod tests {
 
         let function = SymbolInfo {
             name: "getShape".to_string(),
-            fqn: "App\\getShape".to_string(),
+            fqn: "App\\getShape".into(),
             kind: PhpSymbolKind::Function,
-            uri: "file:///functions.php".to_string(),
+            uri: "file:///functions.php".into(),
             range: (6, 0, 8, 1),
             selection_range: (6, 9, 6, 17),
             visibility: Visibility::Public,
@@ -2280,13 +2910,16 @@ mod tests {
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(TypeInfo::Simple("UserShape".to_string())),
+                throws: Vec::new(),
             }),
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///functions.php",
@@ -2327,9 +2960,9 @@ mod tests {
             Some("/**\n * @phpstan-type A B\n * @phpstan-type B A\n */".to_string());
         let method = SymbolInfo {
             name: "loop".to_string(),
-            fqn: "App\\LoopService::loop".to_string(),
+            fqn: "App\\LoopService::loop".into(),
             kind: PhpSymbolKind::Method,
-            uri: "file:///loop.php".to_string(),
+            uri: "file:///loop.php".into(),
             range: (5, 4, 7, 5),
             selection_range: (5, 20, 5, 24),
             visibility: Visibility::Public,
@@ -2339,13 +2972,16 @@ mod tests {
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(TypeInfo::Simple("A".to_string())),
+                throws: Vec::new(),
             }),
-            parent_fqn: Some("App\\LoopService".to_string()),
+            value: None,
+            parent_fqn: Some("App\\LoopService".into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         };
         index.update_file(
             "file:///loop.php",
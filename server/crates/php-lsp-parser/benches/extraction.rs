@@ -0,0 +1,175 @@
+//! Timings for the CST passes that run per indexed or diagnosed file.
+//!
+//! `cargo bench -p php-lsp-parser --bench extraction` parses a generated
+//! file of about 4,500 lines and prints the median time of each pass. Pass a
+//! number to change how many times each pass runs (default 50).
+
+use php_lsp_parser::parser::FileParser;
+use php_lsp_parser::position::PositionEncoding;
+use php_lsp_parser::references::{collect_symbol_references_in_file, find_references_in_file};
+use php_lsp_parser::semantic::{collect_aliased_class_fqns, extract_semantic_diagnostics};
+use php_lsp_parser::symbols::extract_file_symbols;
+use php_lsp_types::PhpSymbolKind;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const CLASSES: usize = 74;
+
+fn generated_source() -> String {
+    let mut source = String::from(
+        "<?php\nnamespace App\\Bench;\n\nuse App\\Models as M;\nuse App\\Contracts\\Repository;\n\nconst LIMIT = 10;\n\n",
+    );
+    for i in 0..CLASSES {
+        source.push_str(&format!(
+            r#"/**
+ * Service {i}.
+ */
+#[Attribute]
+final class Service{i} extends Base implements Repository, \Countable
+{{
+    use Loggable;
+
+    public const NAME = 'service{i}';
+    private ?Service{i} $next = null;
+    protected array $items = [];
+
+    public function __construct(private readonly M\User $user, ?Repository $repository = null)
+    {{
+        $this->items = [];
+        $this->next = new Service{i}(new M\User(), $repository);
+    }}
+
+    public function count(): int
+    {{
+        return \count($this->items) + LIMIT;
+    }}
+
+    public function handle(M\Order $order, int $limit = 10): ?M\Invoice
+    {{
+        if ($order instanceof M\Refund) {{
+            return null;
+        }}
+        try {{
+            $total = 0;
+            foreach ($order->lines() as $line) {{
+                $total += $line->price * $line->quantity;
+                helper_{i}($line, self::NAME);
+            }}
+            $invoice = M\Invoice::fromTotal($total, static::NAME);
+            $invoice?->send($this->user->email);
+            $class = 'App\Models\Invoice';
+            $callback = fn (M\Invoice $i): bool => $i->paid && $total > LIMIT;
+            return array_filter([$invoice], $callback)[0] ?? null;
+        }} catch (\RuntimeException | M\PaymentFailed $e) {{
+            $this->log("failed {{$e->getMessage()}}");
+            throw new \LogicException($e->getMessage(), 0, $e);
+        }}
+    }}
+
+    public static function make(): static
+    {{
+        return new static(M\User::current());
+    }}
+
+    private function log(string $message): void
+    {{
+        printf('%s: %s', self::NAME, $message);
+    }}
+}}
+
+function helper_{i}(object $line, string $name): string
+{{
+    return sprintf('%s-%s', $name, $line::class);
+}}
+
+"#
+        ));
+    }
+    source
+}
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort_unstable();
+    samples[samples.len() / 2]
+}
+
+fn bench(name: &str, iterations: usize, mut pass: impl FnMut()) {
+    pass();
+    let samples = (0..iterations)
+        .map(|_| {
+            let started = Instant::now();
+            pass();
+            started.elapsed()
+        })
+        .collect();
+    println!("{name:<40} {:>10.3?}", median(samples));
+}
+
+fn main() {
+    let iterations = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(50);
+    let source = generated_source();
+    let mut parser = FileParser::new();
+    parser.parse_full(&source);
+    let tree = parser.tree().expect("generated source parses");
+    let uri = "file:///bench/Services.php";
+    let symbols = extract_file_symbols(tree, &source, uri);
+    println!(
+        "{} lines, {} symbols, median of {iterations} runs",
+        source.lines().count(),
+        symbols.symbols.len()
+    );
+
+    bench("extract_file_symbols", iterations, || {
+        black_box(extract_file_symbols(tree, &source, uri));
+    });
+    bench("collect_symbol_references_in_file", iterations, || {
+        black_box(collect_symbol_references_in_file(
+            tree,
+            &source,
+            &symbols,
+            PositionEncoding::Utf16,
+        ));
+    });
+    for (name, fqn, kind) in [
+        (
+            "find_references_in_file (class)",
+            "App\\Models\\Invoice",
+            PhpSymbolKind::Class,
+        ),
+        (
+            "find_references_in_file (function)",
+            "App\\Bench\\helper_7",
+            PhpSymbolKind::Function,
+        ),
+        (
+            "find_references_in_file (method)",
+            "App\\Bench\\Service7::handle",
+            PhpSymbolKind::Method,
+        ),
+        (
+            "find_references_in_file (constant)",
+            "App\\Bench\\LIMIT",
+            PhpSymbolKind::GlobalConstant,
+        ),
+    ] {
+        bench(name, iterations, || {
+            black_box(find_references_in_file(
+                tree, &source, &symbols, fqn, kind, true,
+            ));
+        });
+    }
+    bench("extract_semantic_diagnostics", iterations, || {
+        black_box(extract_semantic_diagnostics(
+            tree,
+            &source,
+            &symbols,
+            |_| None,
+        ));
+    });
+    bench("collect_aliased_class_fqns", iterations, || {
+        black_box(collect_aliased_class_fqns(tree, &source, &symbols));
+    });
+}
@@ -35,6 +35,48 @@ fn is_identifier_char(ch: char) -> bool {
     ch.is_ascii_alphanumeric() || ch == '_'
 }
 
+/// Text inside a single-line string literal without interpolation, with
+/// escaped backslashes collapsed (`"App\\Foo"` yields `App\Foo`).
+pub(crate) fn plain_string_literal_content(node: Node, source: &str) -> Option<String> {
+    if !matches!(node.kind(), "string" | "encapsed_string")
+        || node.start_position().row != node.end_position().row
+    {
+        return None;
+    }
+    let mut cursor = node.walk();
+    if node
+        .named_children(&mut cursor)
+        .any(|child| !matches!(child.kind(), "string_content" | "escape_sequence"))
+    {
+        return None;
+    }
+    let content = source[node.byte_range()]
+        .strip_prefix(['\'', '"'])?
+        .strip_suffix(['\'', '"'])?;
+    Some(content.replace("\\\\", "\\"))
+}
+
+/// `Name` or `Namespace\Name` made only of PHP identifier segments.
+pub(crate) fn is_qualified_identifier(name: &str) -> bool {
+    name.split('\\').all(|segment| {
+        segment
+            .chars()
+            .next()
+            .is_some_and(|ch| ch == '_' || ch.is_ascii_alphabetic() || !ch.is_ascii())
+            && segment
+                .chars()
+                .all(|ch| ch == '_' || ch.is_ascii_alphanumeric() || !ch.is_ascii())
+    })
+}
+
+/// `A::hello` naming a trait method in an `insteadof`/`as` rule rather than
+/// a class constant.
+pub(crate) fn is_trait_adaptation_method(class_constant_access: Node) -> bool {
+    class_constant_access
+        .parent()
+        .is_some_and(|parent| matches!(parent.kind(), "use_instead_of_clause" | "use_as_clause"))
+}
+
 pub(crate) fn ancestor_field_contains(node: Node, ancestor_kind: &str, fields: &[&str]) -> bool {
     let mut current = node.parent();
     while let Some(parent) = current {
@@ -1,46 +1,124 @@
 //! Extract diagnostics (syntax errors) from tree-sitter CST.
 
-use crate::utf16::Utf16LineIndex;
+use crate::position::{LspLineIndex, PositionEncoding};
 use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
-use tree_sitter::Node;
+use tree_sitter::{Node, Point};
 
 /// Extract syntax error diagnostics from a tree-sitter tree.
-pub fn extract_syntax_errors(tree: &tree_sitter::Tree, source: &str) -> Vec<Diagnostic> {
+pub fn extract_syntax_errors(
+    tree: &tree_sitter::Tree,
+    source: &str,
+    encoding: PositionEncoding,
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
-    let utf16_index = Utf16LineIndex::new(source);
-    collect_errors(tree.root_node(), &utf16_index, &mut diagnostics);
+    let line_index = LspLineIndex::new(source, encoding);
+    collect_errors(tree.root_node(), &line_index, &mut diagnostics);
     diagnostics
 }
 
-fn collect_errors(node: Node, utf16_index: &Utf16LineIndex, diagnostics: &mut Vec<Diagnostic>) {
-    if let Some(message) = tree_sitter_error_message(node) {
-        let start = node.start_position();
-        let end = node.end_position();
-        diagnostics.push(Diagnostic {
-            range: Range {
-                start: Position::new(
-                    start.row as u32,
-                    utf16_index.byte_col_to_utf16(start.row as u32, start.column as u32),
-                ),
-                end: Position::new(
-                    end.row as u32,
-                    utf16_index.byte_col_to_utf16(end.row as u32, end.column as u32),
-                ),
-            },
-            severity: Some(DiagnosticSeverity::ERROR),
-            source: Some("php-lsp".to_string()),
+fn collect_errors(node: Node, line_index: &LspLineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    if node.is_error() && spans_php_regions(node) {
+        collect_region_errors(node, line_index, diagnostics);
+    } else if let Some(message) = tree_sitter_error_message(node) {
+        push_error(
+            node.start_position(),
+            node.end_position(),
             message,
-            ..Default::default()
-        });
+            line_index,
+            diagnostics,
+        );
     }
 
     // Recurse into children
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_errors(child, utf16_index, diagnostics);
+        collect_errors(child, line_index, diagnostics);
     }
 }
 
+fn push_error(
+    start: Point,
+    end: Point,
+    message: String,
+    line_index: &LspLineIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    diagnostics.push(Diagnostic {
+        range: Range {
+            start: Position::new(
+                start.row as u32,
+                line_index.byte_col_to_lsp(start.row as u32, start.column as u32),
+            ),
+            end: Position::new(
+                end.row as u32,
+                line_index.byte_col_to_lsp(end.row as u32, end.column as u32),
+            ),
+        },
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("php-lsp".to_string()),
+        message,
+        ..Default::default()
+    });
+}
+
+/// Whether an ERROR node swallowed `?> ... <?php` boundaries.
+///
+/// Tree-sitter recovers from an error in one block of a PHP/HTML template by
+/// wrapping the following blocks, often the whole file, in the same ERROR.
+fn spans_php_regions(node: Node) -> bool {
+    let mut cursor = node.walk();
+    let spans = node
+        .children(&mut cursor)
+        .any(|child| child.kind() == "text_interpolation");
+    spans
+}
+
+/// Report an ERROR spanning several PHP blocks once per block that holds
+/// tokens the parser could not place, instead of over the whole span.
+fn collect_region_errors(node: Node, line_index: &LspLineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    let mut region: Option<(Point, Point)> = None;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "text_interpolation" {
+            if let Some((start, end)) = region.take() {
+                push_error(
+                    start,
+                    end,
+                    "Syntax error".to_string(),
+                    line_index,
+                    diagnostics,
+                );
+            }
+            continue;
+        }
+        if is_recovered_child(child) {
+            continue;
+        }
+        let start = region.map_or(child.start_position(), |(start, _)| start);
+        region = Some((start, child.end_position()));
+    }
+    if let Some((start, end)) = region {
+        push_error(
+            start,
+            end,
+            "Syntax error".to_string(),
+            line_index,
+            diagnostics,
+        );
+    }
+}
+
+/// Children of an ERROR that parsed as complete constructs. Nested ERROR
+/// nodes are skipped too since they report themselves.
+fn is_recovered_child(node: Node) -> bool {
+    let kind = node.kind();
+    node.is_error()
+        || kind.ends_with("_statement")
+        || kind.ends_with("_declaration")
+        || kind.ends_with("_definition")
+        || matches!(kind, "comment" | "php_tag" | "text")
+}
+
 fn tree_sitter_error_message(node: Node) -> Option<String> {
     if node.is_error() {
         Some("Syntax error".to_string())
@@ -62,7 +140,7 @@ mod tests {
         parser.parse_full("<?php\nclass Foo {\n    public function bar(): void {}\n}\n");
 
         let tree = parser.tree().unwrap();
-        let diags = extract_syntax_errors(tree, &parser.source());
+        let diags = extract_syntax_errors(tree, &parser.source(), PositionEncoding::Utf16);
         assert!(diags.is_empty());
     }
 
@@ -72,7 +150,7 @@ mod tests {
         parser.parse_full("<?php\nfunction foo( {\n}\n");
 
         let tree = parser.tree().unwrap();
-        let diags = extract_syntax_errors(tree, &parser.source());
+        let diags = extract_syntax_errors(tree, &parser.source(), PositionEncoding::Utf16);
         assert!(!diags.is_empty());
         assert_eq!(diags[0].severity, Some(DiagnosticSeverity::ERROR));
         assert_eq!(diags[0].source.as_deref(), Some("php-lsp"));
@@ -84,7 +162,7 @@ mod tests {
         parser.parse_full("<?php\nfunction demo(object $item): void {\n    $item->\n}\n");
 
         let tree = parser.tree().unwrap();
-        let diags = extract_syntax_errors(tree, &parser.source());
+        let diags = extract_syntax_errors(tree, &parser.source(), PositionEncoding::Utf16);
         assert!(
             diags
                 .iter()
@@ -99,7 +177,7 @@ mod tests {
         parser.parse_full("<?php\nclass { }\nfunction ( {}\n");
 
         let tree = parser.tree().unwrap();
-        let diags = extract_syntax_errors(tree, &parser.source());
+        let diags = extract_syntax_errors(tree, &parser.source(), PositionEncoding::Utf16);
         assert!(
             diags.len() >= 2,
             "Expected multiple errors, got {}",
@@ -107,13 +185,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_template_error_is_reported_in_its_own_php_block() {
+        let source = "<?php $a = 1 ?>
+<p>text</p>
+<?php function broken( { ?>
+<p>middle</p>
+<?php class Ok { public function m(): void {} } ?>
+<p>end <?= $a ?></p>
+<?php echo new Ok(); ?>
+";
+        let mut parser = FileParser::new();
+        parser.parse_full(source);
+
+        let tree = parser.tree().unwrap();
+        assert!(tree.root_node().is_error());
+        let diags = extract_syntax_errors(tree, &parser.source(), PositionEncoding::Utf16);
+        let syntax_errors = diags
+            .iter()
+            .filter(|diag| diag.message == "Syntax error")
+            .collect::<Vec<_>>();
+
+        assert_eq!(syntax_errors.len(), 1, "{diags:?}");
+        assert_eq!(syntax_errors[0].range.start, Position::new(2, 6));
+        assert_eq!(syntax_errors[0].range.end.line, 2);
+    }
+
     #[test]
     fn test_error_ranges_use_utf16_after_emoji_comment() {
         let mut parser = FileParser::new();
         parser.parse_full("<?php\n// 😀😀😀\nfunction foo( {\n}\n");
 
         let tree = parser.tree().unwrap();
-        let diags = extract_syntax_errors(tree, &parser.source());
+        let diags = extract_syntax_errors(tree, &parser.source(), PositionEncoding::Utf16);
         let diag = diags.first().expect("expected syntax diagnostic");
 
         assert_eq!(diag.range.start.line, 2);
@@ -0,0 +1,359 @@
+//! CST-driven indentation levels for the built-in formatter.
+//!
+//! Levels come from bracket nesting (`{`, `(`, `[`, `#[`), `case`/`default`
+//! bodies, and alternative-syntax `colon_block`s. A line that continues the
+//! previous statement (method chains, operators, `implements` lists) is
+//! indented one extra level. String, heredoc, comment and inline HTML
+//! content is never touched.
+
+use tree_sitter::{Node, Tree};
+
+/// Desired indentation for one source line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineIndent {
+    /// Leave the line unchanged (string/heredoc/inline HTML content, PHP tags).
+    Preserve,
+    /// Whitespace-only line outside string content. `level` is where code
+    /// typed on this line would go, used for on-type formatting after Enter.
+    Blank { level: u32 },
+    /// Indent by `level` units followed by `align` spaces (docblock `*` lines).
+    Indent { level: u32, align: u32 },
+}
+
+/// Compute the desired indentation of every line in `source`.
+///
+/// Returns `None` when the tree contains syntax errors, since bracket
+/// nesting cannot be trusted then.
+pub fn line_indents(tree: &Tree, source: &str) -> Option<Vec<LineIndent>> {
+    let root = tree.root_node();
+    if root.has_error() {
+        return None;
+    }
+
+    let mut scan = TokenScan::default();
+    scan.collect(root);
+
+    let line_starts = line_start_offsets(source);
+    let mut indents = Vec::with_capacity(line_starts.len());
+    let mut stack: Vec<OpenBracket> = Vec::new();
+    let mut token_index = 0usize;
+    let mut last_token_kind: Option<&'static str> = None;
+    let mut previous_token: Option<Token> = None;
+
+    for (line, &line_start) in line_starts.iter().enumerate() {
+        let line_end = line_starts.get(line + 1).copied().unwrap_or(source.len());
+        let first_byte = source.as_bytes()[line_start..line_end]
+            .iter()
+            .position(|byte| !matches!(byte, b' ' | b'\t'))
+            .map(|offset| line_start + offset)
+            .filter(|&offset| !matches!(source.as_bytes()[offset], b'\n' | b'\r'));
+
+        let line_tokens_start = token_index;
+        while token_index < scan.tokens.len() && scan.tokens[token_index].start < line_end {
+            token_index += 1;
+        }
+        let line_tokens = &scan.tokens[line_tokens_start..token_index];
+
+        let indent = match first_byte {
+            None if scan.opaque_containing(line_start).is_some() => LineIndent::Preserve,
+            None => LineIndent::Blank {
+                level: blank_line_level(root, &stack, previous_token, last_token_kind),
+            },
+            Some(first_byte) => match scan.opaque_containing(first_byte) {
+                Some(opaque) if opaque.start < first_byte || opaque.kind == "text" => {
+                    if opaque.kind == "comment" && source.as_bytes()[first_byte] == b'*' {
+                        let level = opaque_start_level(&indents, &line_starts, opaque.start);
+                        level.map_or(LineIndent::Preserve, |level| LineIndent::Indent {
+                            level,
+                            align: 1,
+                        })
+                    } else {
+                        LineIndent::Preserve
+                    }
+                }
+                _ => {
+                    let first_token = line_tokens
+                        .first()
+                        .filter(|token| token.start == first_byte);
+                    line_indent(root, first_byte, first_token, &stack, last_token_kind)
+                }
+            },
+        };
+        indents.push(indent);
+
+        for token in line_tokens {
+            if is_opener(token.kind) {
+                let level = match indent {
+                    LineIndent::Indent { level, .. } => level,
+                    _ => stack.last().map_or(0, |open| open.level + 1),
+                };
+                stack.push(OpenBracket {
+                    start: token.start,
+                    level,
+                });
+            } else if is_closer(token.kind) {
+                stack.pop();
+            }
+            last_token_kind = Some(token.kind);
+            previous_token = Some(*token);
+        }
+    }
+
+    Some(indents)
+}
+
+fn line_indent(
+    root: Node,
+    first_byte: usize,
+    first_token: Option<&Token>,
+    stack: &[OpenBracket],
+    last_token_kind: Option<&'static str>,
+) -> LineIndent {
+    let Some(token) = first_token else {
+        // Line starts with a comment or string literal: indent like code.
+        let base = stack.last().map_or(0, |open| open.level + 1);
+        let continuation = last_token_kind.is_some_and(continues_statement);
+        return LineIndent::Indent {
+            level: base + u32::from(continuation),
+            align: 0,
+        };
+    };
+
+    if matches!(token.kind, "php_tag" | "?>") {
+        return LineIndent::Preserve;
+    }
+    if is_closer(token.kind) {
+        return LineIndent::Indent {
+            level: stack.last().map_or(0, |open| open.level),
+            align: 0,
+        };
+    }
+
+    let innermost_open = stack.last().map_or(0, |open| open.start);
+    let base = stack.last().map_or(0, |open| open.level + 1);
+    let continuation = token.kind != "{" && last_token_kind.is_some_and(continues_statement);
+    let blocks = virtual_block_depth(root, first_byte, first_byte, innermost_open);
+
+    LineIndent::Indent {
+        level: base + blocks + u32::from(continuation),
+        align: 0,
+    }
+}
+
+/// Level for a whitespace-only line, continuing from the last token before it.
+fn blank_line_level(
+    root: Node,
+    stack: &[OpenBracket],
+    previous_token: Option<Token>,
+    last_token_kind: Option<&'static str>,
+) -> u32 {
+    let base = stack.last().map_or(0, |open| open.level + 1);
+    let innermost_open = stack.last().map_or(0, |open| open.start);
+    let blocks = previous_token
+        .filter(|token| token.start > innermost_open)
+        .map_or(0, |token| {
+            virtual_block_depth(root, token.start, token.end, innermost_open)
+        });
+    let continuation = last_token_kind.is_some_and(continues_statement);
+    base + blocks + u32::from(continuation)
+}
+
+/// Number of `case`/`default` bodies and alternative-syntax blocks that
+/// enclose the `start..end` token inside the innermost open bracket and began
+/// before the token ends.
+fn virtual_block_depth(root: Node, start: usize, end: usize, innermost_open: usize) -> u32 {
+    let Some(mut node) = root.descendant_for_byte_range(start, end) else {
+        return 0;
+    };
+    let mut depth = 0;
+    while let Some(parent) = node.parent() {
+        if parent.start_byte() <= innermost_open {
+            break;
+        }
+        if matches!(
+            parent.kind(),
+            "case_statement" | "default_statement" | "colon_block"
+        ) && parent.start_byte() < end
+        {
+            depth += 1;
+        }
+        node = parent;
+    }
+    depth
+}
+
+fn opaque_start_level(indents: &[LineIndent], line_starts: &[usize], start: usize) -> Option<u32> {
+    let line = line_starts.partition_point(|&line_start| line_start <= start) - 1;
+    match indents.get(line)? {
+        LineIndent::Indent { level, .. } => Some(*level),
+        _ => None,
+    }
+}
+
+fn is_opener(kind: &str) -> bool {
+    matches!(kind, "{" | "(" | "[" | "#[")
+}
+
+fn is_closer(kind: &str) -> bool {
+    matches!(kind, "}" | ")" | "]")
+}
+
+/// Whether a line ending in `kind` leaves its statement unfinished.
+fn continues_statement(kind: &str) -> bool {
+    !matches!(
+        kind,
+        ";" | "{" | "}" | ":" | "," | "(" | "[" | "#[" | "]" | "php_tag" | "?>"
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OpenBracket {
+    start: usize,
+    level: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    start: usize,
+    end: usize,
+    kind: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OpaqueRange {
+    start: usize,
+    end: usize,
+    kind: &'static str,
+}
+
+#[derive(Default)]
+struct TokenScan {
+    tokens: Vec<Token>,
+    opaque: Vec<OpaqueRange>,
+}
+
+impl TokenScan {
+    fn collect(&mut self, node: Node) {
+        if is_opaque(node.kind()) {
+            self.opaque.push(OpaqueRange {
+                start: node.start_byte(),
+                end: node.end_byte(),
+                kind: node.kind(),
+            });
+            return;
+        }
+        if node.child_count() == 0 {
+            if node.end_byte() > node.start_byte() {
+                self.tokens.push(Token {
+                    start: node.start_byte(),
+                    end: node.end_byte(),
+                    kind: node.kind(),
+                });
+            }
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.collect(child);
+        }
+    }
+
+    fn opaque_containing(&self, offset: usize) -> Option<OpaqueRange> {
+        let index = self.opaque.partition_point(|range| range.start <= offset);
+        let range = *self.opaque.get(index.checked_sub(1)?)?;
+        (offset < range.end).then_some(range)
+    }
+}
+
+fn is_opaque(kind: &str) -> bool {
+    matches!(
+        kind,
+        "comment"
+            | "string"
+            | "encapsed_string"
+            | "heredoc"
+            | "nowdoc"
+            | "shell_command_expression"
+            | "text"
+    )
+}
+
+fn line_start_offsets(source: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(
+        source
+            .bytes()
+            .enumerate()
+            .filter(|(_, byte)| *byte == b'\n')
+            .map(|(index, _)| index + 1),
+    );
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::FileParser;
+
+    fn reindent(source: &str) -> String {
+        let mut parser = FileParser::new();
+        parser.parse_full(source);
+        let indents = line_indents(parser.tree().unwrap(), source).expect("valid tree");
+        source
+            .split('\n')
+            .zip(indents)
+            .map(|(line, indent)| match indent {
+                LineIndent::Preserve => line.to_string(),
+                LineIndent::Blank { .. } => String::new(),
+                LineIndent::Indent { level, align } => format!(
+                    "{}{}{}",
+                    "    ".repeat(level as usize),
+                    " ".repeat(align as usize),
+                    line.trim_start_matches([' ', '\t'])
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn indents_blocks_calls_and_closures() {
+        let source = "<?php\nclass Demo\n{\n/**\n* Run.\n*/\npublic function run(): void\n{\nitems(function ($x) {\nreturn $x;\n});\n$value = $this\n->first()\n->second();\n$list = [\n1,\n2,\n];\n}\n}\n";
+        let expected = "<?php\nclass Demo\n{\n    /**\n     * Run.\n     */\n    public function run(): void\n    {\n        items(function ($x) {\n            return $x;\n        });\n        $value = $this\n            ->first()\n            ->second();\n        $list = [\n            1,\n            2,\n        ];\n    }\n}\n";
+        assert_eq!(reindent(source), expected);
+    }
+
+    #[test]
+    fn indents_switch_cases_and_alternative_syntax() {
+        let source = "<?php\nswitch ($x) {\ncase 1:\nfoo();\nbreak;\ndefault:\nbar();\n}\nif ($y):\nbaz();\nendif;\n";
+        let expected = "<?php\nswitch ($x) {\n    case 1:\n        foo();\n        break;\n    default:\n        bar();\n}\nif ($y):\n    baz();\nendif;\n";
+        assert_eq!(reindent(source), expected);
+    }
+
+    #[test]
+    fn preserves_string_heredoc_and_inline_html_content() {
+        let source = "<div>\n  <?php\nfunction f() {\n$s = \"a\n   b\";\n$h = <<<EOT\n  body\n  EOT;\n}\n?>\n  <p></p>\n";
+        let expected = "<div>\n  <?php\nfunction f() {\n    $s = \"a\n   b\";\n    $h = <<<EOT\n  body\n  EOT;\n}\n?>\n  <p></p>\n";
+        assert_eq!(reindent(source), expected);
+    }
+
+    #[test]
+    fn blank_lines_report_the_level_for_new_code() {
+        let source = "<?php\nswitch ($x) {\ncase 1:\n\n}\nif ($y) {\n\n}\n$a = $b\n\n;\n";
+        let mut parser = FileParser::new();
+        parser.parse_full(source);
+        let indents = line_indents(parser.tree().unwrap(), source).unwrap();
+        assert_eq!(indents[3], LineIndent::Blank { level: 2 });
+        assert_eq!(indents[6], LineIndent::Blank { level: 1 });
+        assert_eq!(indents[9], LineIndent::Blank { level: 1 });
+        assert_eq!(indents[11], LineIndent::Blank { level: 0 });
+    }
+
+    #[test]
+    fn refuses_trees_with_syntax_errors() {
+        let source = "<?php\nfunction f( {\n";
+        let mut parser = FileParser::new();
+        parser.parse_full(source);
+        assert!(line_indents(parser.tree().unwrap(), source).is_none());
+    }
+}
@@ -5,13 +5,16 @@
 
 mod cst;
 pub mod diagnostics;
+pub mod indent;
 pub mod parser;
 pub mod phpdoc;
+pub mod position;
+mod queries;
 pub mod references;
 pub mod resolve;
 pub mod return_type;
 pub mod semantic;
 pub mod semantic_tokens;
 pub mod signature_help;
+pub mod source;
 pub mod symbols;
-pub mod utf16;
@@ -1,42 +1,78 @@
 //! FileParser: tree-sitter + ropey::Rope for incremental PHP parsing.
 
+use crate::position::PositionEncoding;
+use crate::symbols::{
+    extract_file_symbol_chunks, extract_file_symbols, patch_file_symbols, SymbolChunk,
+};
+use php_lsp_types::FileSymbols;
 use ropey::Rope;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::sync::OnceLock;
 use tree_sitter::{InputEdit, Parser, Point, Tree};
 
+/// Parsers kept per thread for reuse once their `FileParser` is dropped.
+const PARSER_POOL_CAPACITY: usize = 4;
+
+thread_local! {
+    /// Idle tree-sitter parsers with the PHP grammar already loaded, so
+    /// short-lived `FileParser`s for closed files skip grammar setup.
+    static PARSER_POOL: RefCell<Vec<Parser>> = const { RefCell::new(Vec::new()) };
+}
+
 /// Manages parsing state for a single PHP file.
 pub struct FileParser {
-    parser: Parser,
+    /// Always `Some` until the parser goes back to the pool on drop.
+    parser: Option<Parser>,
     tree: Option<Tree>,
     rope: Rope,
+    /// Symbols extracted from the current tree. Filled on first use, so all
+    /// requests against one document version share a single extraction, and
+    /// patched by every edit.
+    symbols: OnceLock<CachedSymbols>,
+}
+
+/// Symbols extracted for a URI, with the per-statement chunks an edit uses
+/// to re-extract only the statements it touched.
+struct CachedSymbols {
+    uri: String,
+    symbols: FileSymbols,
+    chunks: Option<Vec<SymbolChunk>>,
 }
 
 impl FileParser {
-    /// Create a new FileParser with tree-sitter-php language.
+    /// Create a new FileParser with tree-sitter-php language, reusing an
+    /// idle parser of this thread when there is one.
     pub fn new() -> Self {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_php::LANGUAGE_PHP.into())
-            .expect("Failed to set tree-sitter PHP language");
+        let parser = PARSER_POOL.with_borrow_mut(Vec::pop).unwrap_or_else(|| {
+            let mut parser = Parser::new();
+            parser
+                .set_language(&tree_sitter_php::LANGUAGE_PHP.into())
+                .expect("Failed to set tree-sitter PHP language");
+            parser
+        });
 
         FileParser {
-            parser,
+            parser: Some(parser),
             tree: None,
             rope: Rope::new(),
+            symbols: OnceLock::new(),
         }
     }
 
     /// Full parse of a source string (used on didOpen).
     pub fn parse_full(&mut self, source: &str) {
         self.rope = Rope::from_str(source);
+        self.symbols = OnceLock::new();
         let source_bytes = source.as_bytes();
-        self.tree = self.parser.parse(source_bytes, None);
+        self.tree = self.parser_mut().parse(source_bytes, None);
     }
 
     /// Apply an incremental edit from LSP didChange and reparse.
     ///
     /// LSP positions use (line, character) where `character` is measured in
-    /// **UTF-16 code units**.  Tree-sitter and ropey work in bytes, so we must
-    /// convert before applying the edit.
+    /// code units of the negotiated `encoding`.  Tree-sitter and ropey work in
+    /// bytes, so we must convert before applying the edit.
     pub fn apply_edit(
         &mut self,
         start_line: u32,
@@ -44,15 +80,16 @@ impl FileParser {
         end_line: u32,
         end_char: u32,
         new_text: &str,
+        encoding: PositionEncoding,
     ) {
         let start_line = start_line as usize;
         let start_char = start_char as usize;
         let end_line = end_line as usize;
         let end_char = end_char as usize;
 
-        // Calculate byte offsets from rope (UTF-16 → byte).
-        let start_byte = self.utf16_position_to_byte(start_line, start_char);
-        let old_end_byte = self.utf16_position_to_byte(end_line, end_char);
+        // Calculate byte offsets from rope (LSP column → byte).
+        let start_byte = self.lsp_position_to_byte(start_line, start_char, encoding);
+        let old_end_byte = self.lsp_position_to_byte(end_line, end_char, encoding);
 
         // Tree-sitter Points need byte columns (byte offset from line start).
         let start_byte_col = start_byte - self.line_start_byte(start_line);
@@ -66,6 +103,7 @@ impl FileParser {
         let end_char_idx = self.rope.byte_to_char(old_end_byte);
         self.rope.remove(start_char_idx..end_char_idx);
         self.rope.insert(start_char_idx, new_text);
+        let previous_symbols = self.symbols.take();
 
         // Calculate new end position
         let new_end_byte = start_byte + new_text.len();
@@ -76,20 +114,54 @@ impl FileParser {
         let new_end_point = Point::new(new_end_line, new_end_byte_col);
 
         // Apply edit to tree-sitter tree for incremental reparsing
-        if let Some(tree) = &mut self.tree {
-            tree.edit(&InputEdit {
-                start_byte,
-                old_end_byte,
-                new_end_byte,
-                start_position: start_point,
-                old_end_position: old_end_point,
-                new_end_position: new_end_point,
-            });
+        let edit = InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte,
+            start_position: start_point,
+            old_end_position: old_end_point,
+            new_end_position: new_end_point,
+        };
+        let mut old_tree = self.tree.take();
+        if let Some(tree) = &mut old_tree {
+            tree.edit(&edit);
         }
 
         // Reparse incrementally
         let source = self.rope.to_string();
-        self.tree = self.parser.parse(source.as_bytes(), self.tree.as_ref());
+        self.tree = self
+            .parser_mut()
+            .parse(source.as_bytes(), old_tree.as_ref());
+
+        // Patch the symbols of the previous version instead of dropping them,
+        // re-extracting only the statements the edit changed.
+        if let (Some(old_tree), Some(tree), Some(previous)) =
+            (&old_tree, &self.tree, previous_symbols)
+        {
+            let Some(chunks) = previous.chunks else {
+                return;
+            };
+            let changed_ranges: Vec<_> = old_tree.changed_ranges(tree).collect();
+            if let Some((symbols, chunks)) = patch_file_symbols(
+                previous.symbols,
+                chunks,
+                tree,
+                &source,
+                &previous.uri,
+                &edit,
+                &changed_ranges,
+            ) {
+                let _ = self.symbols.set(CachedSymbols {
+                    uri: previous.uri,
+                    symbols,
+                    chunks,
+                });
+            }
+        }
+    }
+
+    fn parser_mut(&mut self) -> &mut Parser {
+        self.parser.as_mut().expect("parser is only taken on drop")
     }
 
     /// Get the current tree-sitter Tree (if parsed successfully).
@@ -97,6 +169,28 @@ impl FileParser {
         self.tree.as_ref()
     }
 
+    /// Symbols of the current tree, extracted at most once per document
+    /// version. A `uri` other than the cached one is extracted uncached.
+    pub fn file_symbols(&self, uri: &str) -> Option<Cow<'_, FileSymbols>> {
+        let tree = self.tree.as_ref()?;
+        if let Some(cached) = self.symbols.get() {
+            return Some(if cached.uri == uri {
+                Cow::Borrowed(&cached.symbols)
+            } else {
+                Cow::Owned(extract_file_symbols(tree, &self.source(), uri))
+            });
+        }
+        let cached = self.symbols.get_or_init(|| {
+            let (symbols, chunks) = extract_file_symbol_chunks(tree, &self.source(), uri);
+            CachedSymbols {
+                uri: uri.to_string(),
+                symbols,
+                chunks,
+            }
+        });
+        Some(Cow::Borrowed(&cached.symbols))
+    }
+
     /// Get the current source as a String.
     pub fn source(&self) -> String {
         self.rope.to_string()
@@ -107,27 +201,33 @@ impl FileParser {
         &self.rope
     }
 
-    /// Convert (line, utf16_character) to byte offset in the rope.
+    /// Convert (line, LSP character) to byte offset in the rope.
     ///
-    /// LSP `Position.character` is measured in UTF-16 code units.
-    /// For ASCII this is the same as byte offset, but for multi-byte characters
-    /// (Cyrillic, CJK, emoji, etc.) it differs.
-    fn utf16_position_to_byte(&self, line: usize, utf16_char: usize) -> usize {
+    /// LSP `Position.character` is measured in code units of the negotiated
+    /// position encoding (UTF-16 by default). For ASCII this is the same as
+    /// byte offset, but for multi-byte characters (Cyrillic, CJK, emoji, etc.)
+    /// it differs.
+    fn lsp_position_to_byte(
+        &self,
+        line: usize,
+        lsp_char: usize,
+        encoding: PositionEncoding,
+    ) -> usize {
         if line >= self.rope.len_lines() {
             return self.rope.len_bytes();
         }
         let line_start = self.rope.line_to_byte(line);
         let line_slice = self.rope.line(line);
 
-        let mut utf16_offset = 0;
+        let mut lsp_offset = 0;
         let mut byte_offset = 0;
 
         for ch in line_slice.chars() {
-            if utf16_offset >= utf16_char {
+            if lsp_offset >= lsp_char {
                 break;
             }
             byte_offset += ch.len_utf8();
-            utf16_offset += ch.len_utf16();
+            lsp_offset += encoding.char_len(ch);
         }
 
         line_start + byte_offset
@@ -141,10 +241,11 @@ impl FileParser {
         self.rope.line_to_byte(line)
     }
 
-    /// Convert (line, byte_column) to (line, utf16_character) for LSP.
+    /// Convert (line, byte_column) to (line, LSP character).
     ///
-    /// Tree-sitter positions use byte columns; LSP uses UTF-16 code unit columns.
-    pub fn byte_col_to_utf16(&self, line: u32, byte_col: u32) -> u32 {
+    /// Tree-sitter positions use byte columns; LSP uses columns in the
+    /// negotiated position encoding.
+    pub fn byte_col_to_lsp(&self, line: u32, byte_col: u32, encoding: PositionEncoding) -> u32 {
         let line = line as usize;
         if line >= self.rope.len_lines() {
             return byte_col;
@@ -152,7 +253,7 @@ impl FileParser {
         let line_slice = self.rope.line(line);
         let byte_col = byte_col as usize;
 
-        let mut utf16_offset: usize = 0;
+        let mut lsp_offset: usize = 0;
         let mut byte_offset: usize = 0;
 
         for ch in line_slice.chars() {
@@ -160,10 +261,26 @@ impl FileParser {
                 break;
             }
             byte_offset += ch.len_utf8();
-            utf16_offset += ch.len_utf16();
+            lsp_offset += encoding.char_len(ch);
         }
 
-        utf16_offset as u32
+        lsp_offset as u32
+    }
+}
+
+impl Drop for FileParser {
+    fn drop(&mut self) {
+        let Some(mut parser) = self.parser.take() else {
+            return;
+        };
+        parser.reset();
+        // The pool is gone while the thread shuts down; drop the parser then.
+        let _ = PARSER_POOL.try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < PARSER_POOL_CAPACITY {
+                pool.push(parser);
+            }
+        });
     }
 }
 
@@ -223,13 +340,123 @@ mod tests {
         assert!(root.has_error());
     }
 
+    #[test]
+    fn test_file_symbols_are_cached_until_the_next_edit() {
+        let uri = "file:///test.php";
+        let mut parser = FileParser::new();
+        parser.parse_full("<?php\nclass Foo {}\n");
+
+        let first = parser.file_symbols(uri).unwrap();
+        let second = parser.file_symbols(uri).unwrap();
+        assert!(matches!(first, Cow::Borrowed(_)));
+        assert!(std::ptr::eq(first.as_ref(), second.as_ref()));
+        assert!(matches!(
+            parser.file_symbols("file:///other.php").unwrap(),
+            Cow::Owned(_)
+        ));
+
+        parser.apply_edit(1, 6, 1, 9, "Bar", PositionEncoding::Utf16);
+        let edited = parser.file_symbols(uri).unwrap();
+        assert_eq!(edited.symbols[0].name, "Bar");
+
+        parser.parse_full("<?php\nfunction baz() {}\n");
+        assert_eq!(parser.file_symbols(uri).unwrap().symbols[0].name, "baz");
+    }
+
+    #[test]
+    fn test_patched_file_symbols_match_a_full_extraction() {
+        let uri = "file:///test.php";
+        let mut parser = FileParser::new();
+        parser.parse_full(
+            "<?php\nnamespace App;\n\nuse App\\Contracts\\Shape;\n\n/** First. */\nclass First {\n    public function run(#[\\SensitiveParameter] string $secret): void {\n        $a = 1;\n    }\n}\n\n#[\\Attribute]\nclass Second implements Shape {\n    const LIMIT = 10;\n}\n\nfunction helper(): int { return 1; }\n",
+        );
+        let helper_fqn = |parser: &FileParser| {
+            let symbols = parser.file_symbols(uri).unwrap();
+            let helper = symbols.symbols.iter().find(|s| s.name == "helper");
+            helper.unwrap().fqn.clone()
+        };
+        let before = helper_fqn(&parser);
+        let (line, character) = utf16_position_after(&parser.source(), "$a = 1;");
+        parser.apply_edit(
+            line,
+            character,
+            line,
+            character,
+            "\n        $z = 0;",
+            PositionEncoding::Utf16,
+        );
+        // `helper` was moved over from the previous version, not re-extracted.
+        assert!(helper_fqn(&parser).ptr_eq(&before));
+
+        let edits = [
+            ("$a = 1;", "$a = 1;\n        $b = 2;\n        $c = 3;"),
+            ("/** First. */", "/** Renamed docs. */"),
+            ("class Second", "class Other"),
+            ("const LIMIT = 10;\n", ""),
+            (
+                "function helper(): int { return 1; }",
+                "function helper(): int {",
+            ),
+            (
+                "function helper(): int {",
+                "function helper(): int { return 2; }",
+            ),
+            ("namespace App;", "namespace App\\Http;"),
+            ("\n        $b = 2;", ""),
+            ("use App\\Contracts\\Shape;\n", ""),
+        ];
+        for (needle, replacement) in edits {
+            let source = parser.source();
+            let (start_line, start_char) = utf16_position_at(&source, needle);
+            let (end_line, end_char) = utf16_position_after(&source, needle);
+            parser.apply_edit(
+                start_line,
+                start_char,
+                end_line,
+                end_char,
+                replacement,
+                PositionEncoding::Utf16,
+            );
+
+            let tree = parser.tree().unwrap();
+            let expected = extract_file_symbols(tree, &parser.source(), uri);
+            assert_eq!(
+                format!("{:?}", parser.file_symbols(uri).unwrap()),
+                format!("{expected:?}"),
+                "after replacing `{needle}`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dropped_parsers_are_reused_on_the_same_thread() {
+        std::thread::spawn(|| {
+            let first = FileParser::new();
+            drop(first);
+            assert_eq!(PARSER_POOL.with_borrow(Vec::len), 1);
+
+            let mut reused = FileParser::new();
+            assert_eq!(PARSER_POOL.with_borrow(Vec::len), 0);
+            reused.parse_full("<?php\nclass Foo {}\n");
+            assert!(!reused.tree().unwrap().root_node().has_error());
+
+            let parsers: Vec<_> = (0..PARSER_POOL_CAPACITY + 2)
+                .map(|_| FileParser::new())
+                .collect();
+            drop(parsers);
+            assert_eq!(PARSER_POOL.with_borrow(Vec::len), PARSER_POOL_CAPACITY);
+        })
+        .join()
+        .unwrap();
+    }
+
     #[test]
     fn test_incremental_edit() {
         let mut parser = FileParser::new();
         parser.parse_full("<?php\nclass Foo {}\n");
 
         // Change "Foo" to "Bar" (line 1, chars 6-9)
-        parser.apply_edit(1, 6, 1, 9, "Bar");
+        parser.apply_edit(1, 6, 1, 9, "Bar", PositionEncoding::Utf16);
 
         let source = parser.source();
         assert!(source.contains("class Bar {}"));
@@ -243,7 +470,7 @@ mod tests {
         let mut parser = FileParser::new();
         parser.parse_full("<?php\n$emoji = \"😀\"; $name = 1;\n");
 
-        parser.apply_edit(1, 15, 1, 20, "$value");
+        parser.apply_edit(1, 15, 1, 20, "$value", PositionEncoding::Utf16);
 
         let source = parser.source();
         assert!(source.contains("$emoji = \"😀\"; $value = 1;"));
@@ -260,7 +487,14 @@ mod tests {
 
         let start = utf16_position_at(source, "$name");
         let end = utf16_position_after(source, "$name");
-        parser.apply_edit(start.0, start.1, end.0, end.1, "$value");
+        parser.apply_edit(
+            start.0,
+            start.1,
+            end.0,
+            end.1,
+            "$value",
+            PositionEncoding::Utf16,
+        );
 
         let source = parser.source();
         assert!(source.contains("\"🇺🇸 👨‍👩‍👧‍👦 👍🏽 ❤️ e\u{0301}\"; $value = 1;"));
@@ -274,7 +508,7 @@ mod tests {
         let mut parser = FileParser::new();
         parser.parse_full("<?php\n$value = \"😀\";\n");
 
-        parser.apply_edit(1, 9, 1, 13, "\"ok\"");
+        parser.apply_edit(1, 9, 1, 13, "\"ok\"", PositionEncoding::Utf16);
 
         let source = parser.source();
         assert!(source.contains("$value = \"ok\";"));
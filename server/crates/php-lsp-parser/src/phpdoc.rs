@@ -22,6 +22,10 @@ pub fn parse_phpdoc(comment: &str) -> PhpDoc {
 
     let mut summary_lines: Vec<String> = Vec::new();
     let mut in_summary = true;
+    // Paragraphs of free text after the summary; only text before the first
+    // tag counts as description.
+    let mut description_paragraphs: Vec<Vec<String>> = Vec::new();
+    let mut seen_tag = false;
 
     let mut pending_tag: Option<String> = None;
 
@@ -35,6 +39,12 @@ pub fn parse_phpdoc(comment: &str) -> PhpDoc {
             if in_summary && !summary_lines.is_empty() {
                 in_summary = false;
             }
+            if description_paragraphs
+                .last()
+                .is_some_and(|paragraph| !paragraph.is_empty())
+            {
+                description_paragraphs.push(Vec::new());
+            }
             continue;
         }
 
@@ -43,12 +53,18 @@ pub fn parse_phpdoc(comment: &str) -> PhpDoc {
                 parse_tag(&tag, &mut doc);
             }
             in_summary = false;
+            seen_tag = true;
             pending_tag = Some(trimmed.to_string());
         } else if let Some(tag) = pending_tag.as_mut() {
             tag.push(' ');
             tag.push_str(trimmed);
         } else if in_summary {
             summary_lines.push(trimmed.to_string());
+        } else if !seen_tag {
+            match description_paragraphs.last_mut() {
+                Some(paragraph) => paragraph.push(trimmed.to_string()),
+                None => description_paragraphs.push(vec![trimmed.to_string()]),
+            }
         }
     }
 
@@ -60,9 +76,65 @@ pub fn parse_phpdoc(comment: &str) -> PhpDoc {
         doc.summary = Some(summary_lines.join(" "));
     }
 
+    let description = description_paragraphs
+        .iter()
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.join(" "))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if !description.is_empty() {
+        doc.description = Some(description);
+    }
+
     doc
 }
 
+/// Rewrite the types of `@param`, `@return` and `@var` tags in a raw doc
+/// comment, leaving all other text untouched. Used to bind template types
+/// (`@return TValue` → `@return \App\User`) on materialized members.
+pub fn map_phpdoc_tag_types(comment: &str, map: impl Fn(&TypeInfo) -> TypeInfo) -> String {
+    let mut result = String::with_capacity(comment.len());
+    for (idx, line) in comment.split('\n').enumerate() {
+        if idx > 0 {
+            result.push('\n');
+        }
+        let Some(tag_start) = line.find('@') else {
+            result.push_str(line);
+            continue;
+        };
+        let tagged = &line[tag_start..];
+        let Some(rest) = ["@param", "@return", "@var"].into_iter().find_map(|tag| {
+            tagged
+                .strip_prefix(tag)
+                .filter(|_| strip_exact_tag(tagged, tag).is_some())
+        }) else {
+            result.push_str(line);
+            continue;
+        };
+        let type_start = line.len() - rest.trim_start().len();
+        let type_text = &line[type_start..];
+        let type_end = if type_text.starts_with(['$', '&']) || type_text.starts_with("...") {
+            None
+        } else {
+            consume_type_expr(type_text)
+        };
+        let Some(type_end) = type_end else {
+            result.push_str(line);
+            continue;
+        };
+        let original = parse_type_string(type_text[..type_end].trim());
+        let mapped = map(&original);
+        if mapped == original {
+            result.push_str(line);
+            continue;
+        }
+        result.push_str(&line[..type_start]);
+        result.push_str(&mapped.to_string());
+        result.push_str(&type_text[type_end..]);
+    }
+    result
+}
+
 fn strip_comment_markers(comment: &str) -> Vec<String> {
     let mut lines = Vec::new();
     for line in comment.lines() {
@@ -99,10 +171,11 @@ fn parse_tag(line: &str, doc: &mut PhpDoc) {
         parse_param_tag(rest.trim(), doc);
     } else if let Some(rest) = strip_exact_tag(line, "@return") {
         let rest = rest.trim();
-        if let Some((type_str, _)) = split_type_prefix(rest) {
+        if let Some((type_str, description)) = split_type_prefix(rest) {
             doc.return_type = Some(parse_type_string(type_str));
+            doc.return_description = description;
         }
-    } else if let Some(rest) = strip_exact_tag(line, "@var") {
+    } else if let Some(rest) = var_tag(line) {
         parse_var_tag(rest.trim(), doc);
     } else if let Some(rest) = strip_exact_tag(line, "@throws") {
         let rest = rest.trim();
@@ -116,6 +189,18 @@ fn parse_tag(line: &str, doc: &mut PhpDoc) {
         } else {
             rest.to_string()
         });
+    } else if let Some(rest) = strip_exact_tag(line, "@since") {
+        if !rest.is_empty() {
+            doc.since = Some(rest.to_string());
+        }
+    } else if let Some(rest) = strip_exact_tag(line, "@see") {
+        if !rest.is_empty() {
+            doc.see.push(rest.to_string());
+        }
+    } else if let Some(rest) = strip_exact_tag(line, "@link") {
+        if !rest.is_empty() {
+            doc.links.push(rest.to_string());
+        }
     } else if let Some(rest) = strip_exact_tag(line, "@property-read") {
         parse_property_tag(rest.trim(), doc, PhpDocPropertyAccess::ReadOnly);
     } else if let Some(rest) = strip_exact_tag(line, "@property-write") {
@@ -204,6 +289,16 @@ fn type_alias_tag(line: &str) -> Option<&str> {
     None
 }
 
+pub(crate) fn var_tag(line: &str) -> Option<&str> {
+    for tag in ["@var", "@phpstan-var", "@psalm-var"] {
+        if let Some(rest) = strip_exact_tag(line, tag) {
+            return Some(rest);
+        }
+    }
+
+    None
+}
+
 fn type_alias_import_tag(line: &str) -> Option<&str> {
     for tag in ["@phpstan-import-type", "@psalm-import-type"] {
         if let Some(rest) = strip_exact_tag(line, tag) {
@@ -377,7 +472,14 @@ fn parse_type_alias_import_tag(rest: &str, doc: &mut PhpDoc) {
 }
 
 fn parse_var_tag(rest: &str, doc: &mut PhpDoc) {
-    if let Some((type_str, _, _)) = split_type_variable_description(rest) {
+    if let Some((type_str, _, description)) = split_type_variable_description(rest) {
+        // Legacy docblocks put the variable first: `@var $items Item[]`.
+        let type_str = type_str.or_else(|| {
+            description
+                .as_deref()
+                .and_then(split_type_prefix)
+                .map(|(type_str, _)| type_str)
+        });
         if let Some(type_str) = type_str {
             doc.var_type = Some(parse_type_string(type_str));
         }
@@ -483,6 +585,7 @@ fn parse_method_param(param: &str) -> Option<ParamInfo> {
         is_variadic: name_str.contains("..."),
         is_by_ref: name_str.contains('&'),
         is_promoted: false,
+        attributes: vec![],
     })
 }
 
@@ -785,7 +888,8 @@ fn parse_type_string(s: &str) -> TypeInfo {
         "never" => TypeInfo::Never,
         "mixed" => TypeInfo::Mixed,
         "self" => TypeInfo::Self_,
-        "static" => TypeInfo::Static_,
+        // `@return $this` on fluent methods binds like `static`.
+        "static" | "$this" => TypeInfo::Static_,
         "parent" => TypeInfo::Parent_,
         "class-string" => TypeInfo::ClassString(None),
         _ => TypeInfo::Simple(s.to_string()),
@@ -1556,6 +1660,28 @@ mod tests {
         assert_eq!(doc.summary.as_deref(), Some("First line. Second line."));
     }
 
+    #[test]
+    fn test_parse_description_return_description_and_reference_tags() {
+        let doc = parse_phpdoc(
+            "/**\n * Applies the callback.\n *\n * First paragraph\n * continues.\n *\n * Second paragraph.\n * @link https://php.net/manual/en/function.array-map.php\n * @see array_walk()\n * @since 4.0.6\n * @return array the mapped\n * values.\n */",
+        );
+        assert_eq!(doc.summary.as_deref(), Some("Applies the callback."));
+        assert_eq!(
+            doc.description.as_deref(),
+            Some("First paragraph continues.\n\nSecond paragraph.")
+        );
+        assert_eq!(
+            doc.return_description.as_deref(),
+            Some("the mapped values.")
+        );
+        assert_eq!(
+            doc.links,
+            vec!["https://php.net/manual/en/function.array-map.php".to_string()]
+        );
+        assert_eq!(doc.see, vec!["array_walk()".to_string()]);
+        assert_eq!(doc.since.as_deref(), Some("4.0.6"));
+    }
+
     #[test]
     fn test_parse_param() {
         let doc = parse_phpdoc("/**\n * @param string $name The name\n * @param int $age\n */");
@@ -1575,6 +1701,29 @@ mod tests {
         assert!(matches!(doc.return_type, Some(TypeInfo::Union(_))));
     }
 
+    #[test]
+    fn test_map_phpdoc_tag_types_rewrites_only_tag_types() {
+        let comment =
+            "/**\n * Returns a TValue.\n * @param TKey $key The key\n * @return TValue\n */";
+        let mapped = map_phpdoc_tag_types(comment, |type_info| match type_info {
+            TypeInfo::Simple(name) if name == "TKey" => TypeInfo::Simple("int".to_string()),
+            TypeInfo::Simple(name) if name == "TValue" => {
+                TypeInfo::Simple("\\App\\User".to_string())
+            }
+            other => other.clone(),
+        });
+        assert_eq!(
+            mapped,
+            "/**\n * Returns a TValue.\n * @param int $key The key\n * @return \\App\\User\n */"
+        );
+    }
+
+    #[test]
+    fn test_parse_return_this_as_static() {
+        let doc = parse_phpdoc("/**\n * @return $this\n */");
+        assert_eq!(doc.return_type, Some(TypeInfo::Static_));
+    }
+
     #[test]
     fn test_parse_conditional_return_type() {
         let doc = parse_phpdoc("/**\n * @return ($class is class-string<T> ? T : object)\n */");
@@ -1602,6 +1751,20 @@ mod tests {
         assert_eq!(doc.var_type, Some(TypeInfo::Simple("int".to_string())));
     }
 
+    #[test]
+    fn test_parse_var_legacy_variable_first_and_tool_prefixed_tags() {
+        let doc = parse_phpdoc("/** @var $items Item[] the items */");
+        assert_eq!(
+            doc.var_type.map(|t| t.to_string()).as_deref(),
+            Some("array<Item>")
+        );
+
+        for comment in ["/** @phpstan-var Item */", "/** @psalm-var Item $item */"] {
+            let doc = parse_phpdoc(comment);
+            assert_eq!(doc.var_type, Some(TypeInfo::Simple("Item".to_string())));
+        }
+    }
+
     #[test]
     fn test_parse_throws() {
         let doc = parse_phpdoc(
@@ -0,0 +1,448 @@
+//! LSP position ↔ byte offset conversion.
+//!
+//! LSP `Position.character` counts code units of the position encoding
+//! negotiated during `initialize` (UTF-16 unless the client offers UTF-8 or
+//! UTF-32). Tree-sitter uses byte offsets for `Point.column`. Every conversion
+//! between the two goes through this module and takes the encoding as an
+//! argument, since it belongs to a client connection rather than the process.
+
+/// Column unit of LSP positions (`PositionEncodingKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Parse an LSP `PositionEncodingKind` value such as `"utf-16"`.
+    pub fn from_lsp_kind(kind: &str) -> Option<Self> {
+        match kind {
+            "utf-8" => Some(Self::Utf8),
+            "utf-16" => Some(Self::Utf16),
+            "utf-32" => Some(Self::Utf32),
+            _ => None,
+        }
+    }
+
+    pub fn as_lsp_kind(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf16 => "utf-16",
+            Self::Utf32 => "utf-32",
+        }
+    }
+
+    /// Pick the first encoding the client offers, in the client's preference
+    /// order. Clients that offer nothing get the mandatory UTF-16.
+    pub fn negotiate<'a>(offered: impl IntoIterator<Item = &'a str>) -> Self {
+        offered
+            .into_iter()
+            .find_map(Self::from_lsp_kind)
+            .unwrap_or_default()
+    }
+
+    /// Number of code units `ch` occupies in this encoding.
+    pub fn char_len(self, ch: char) -> usize {
+        match self {
+            Self::Utf8 => ch.len_utf8(),
+            Self::Utf16 => ch.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Number of code units `text` occupies in this encoding.
+    pub fn text_len(self, text: &str) -> u32 {
+        match self {
+            Self::Utf8 => text.len() as u32,
+            Self::Utf16 => text.encode_utf16().count() as u32,
+            Self::Utf32 => text.chars().count() as u32,
+        }
+    }
+}
+
+/// Build a line-indexed lookup table for a source string.
+///
+/// Each entry in the returned Vec corresponds to a source line and contains
+/// a list of (byte_offset_in_line, lsp_offset_in_line) for every UTF-8
+/// character boundary. For ASCII-only lines the entry is empty, making the
+/// common case essentially free.
+///
+/// Prefer building this once per file version and reusing it for all position
+/// conversions in that file.
+pub struct LspLineIndex {
+    /// For each line, a sorted list of (byte_offset, lsp_offset) at UTF-8
+    /// character boundaries. Empty for ASCII-only lines.
+    lines: Vec<Vec<(usize, usize)>>,
+    /// UTF-8 byte length for each indexed line.
+    line_byte_lengths: Vec<usize>,
+}
+
+impl LspLineIndex {
+    /// Build the index from source text for the given encoding.
+    pub fn new(source: &str, encoding: PositionEncoding) -> Self {
+        let mut lines = Vec::new();
+        let mut line_byte_lengths = Vec::new();
+        for line_text in source.split('\n') {
+            line_byte_lengths.push(line_text.len());
+            // ASCII-only lines and UTF-8 columns need no mapping (fast path)
+            if line_text.is_ascii() || encoding == PositionEncoding::Utf8 {
+                lines.push(Vec::new());
+            } else {
+                let mut mappings = Vec::new();
+                let mut byte_off = 0usize;
+                let mut lsp_off = 0usize;
+                for ch in line_text.chars() {
+                    byte_off += ch.len_utf8();
+                    lsp_off += encoding.char_len(ch);
+                    mappings.push((byte_off, lsp_off));
+                }
+                lines.push(mappings);
+            }
+        }
+        LspLineIndex {
+            lines,
+            line_byte_lengths,
+        }
+    }
+
+    /// Convert a tree-sitter byte column to an LSP column.
+    pub fn byte_col_to_lsp(&self, line: u32, byte_col: u32) -> u32 {
+        let line = line as usize;
+        if line >= self.lines.len() {
+            return byte_col;
+        }
+        let mappings = &self.lines[line];
+        if mappings.is_empty() {
+            // ASCII-only line or UTF-8 columns: byte offset == LSP offset
+            return byte_col.min(self.line_len_utf8(line));
+        }
+        let byte_col = byte_col as usize;
+        let mut lsp_col = byte_col;
+        for &(b, u) in mappings.iter() {
+            if byte_col < b {
+                break;
+            }
+            if byte_col == b {
+                lsp_col = u;
+                break;
+            } else {
+                lsp_col = u;
+            }
+        }
+        lsp_col as u32
+    }
+
+    fn line_len_utf8(&self, line: usize) -> u32 {
+        self.line_byte_lengths
+            .get(line)
+            .copied()
+            .unwrap_or(u32::MAX as usize) as u32
+    }
+}
+
+/// Convert a single tree-sitter byte column to an LSP column given source text.
+///
+/// Use this when you only need a one-off conversion and don't want to build
+/// the full index.
+pub fn byte_col_to_lsp(source: &str, line: u32, byte_col: u32, encoding: PositionEncoding) -> u32 {
+    let line_text = match source.split('\n').nth(line as usize) {
+        Some(l) => l,
+        None => return byte_col,
+    };
+
+    if line_text.is_ascii() {
+        return byte_col.min(line_text.len() as u32);
+    }
+
+    let byte_col = byte_col as usize;
+    let mut byte_off = 0usize;
+    let mut lsp_off = 0usize;
+
+    for ch in line_text.chars() {
+        if byte_col <= byte_off {
+            break;
+        }
+        // A column inside a multi-byte character snaps back to its start.
+        if byte_col < byte_off + ch.len_utf8() {
+            break;
+        }
+        byte_off += ch.len_utf8();
+        lsp_off += encoding.char_len(ch);
+    }
+
+    lsp_off as u32
+}
+
+/// Convert a (start_line, start_col, end_line, end_col) range from byte columns
+/// to LSP columns.
+pub fn range_byte_to_lsp(
+    source: &str,
+    range: (u32, u32, u32, u32),
+    encoding: PositionEncoding,
+) -> (u32, u32, u32, u32) {
+    (
+        range.0,
+        byte_col_to_lsp(source, range.0, range.1, encoding),
+        range.2,
+        byte_col_to_lsp(source, range.2, range.3, encoding),
+    )
+}
+
+/// Convert an LSP column (from `Position.character`) to a byte column for use
+/// with tree-sitter.
+pub fn lsp_col_to_byte(source: &str, line: u32, lsp_col: u32, encoding: PositionEncoding) -> u32 {
+    let line_text = match source.split('\n').nth(line as usize) {
+        Some(l) => l,
+        None => return lsp_col,
+    };
+
+    if line_text.is_ascii() {
+        return lsp_col.min(line_text.len() as u32);
+    }
+
+    let lsp_col = lsp_col as usize;
+    let mut byte_off = 0usize;
+    let mut lsp_off = 0usize;
+
+    for ch in line_text.chars() {
+        if lsp_col <= lsp_off {
+            break;
+        }
+        if lsp_col < lsp_off + encoding.char_len(ch) {
+            break;
+        }
+        byte_off += ch.len_utf8();
+        lsp_off += encoding.char_len(ch);
+    }
+
+    byte_off as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UTF16: PositionEncoding = PositionEncoding::Utf16;
+
+    fn reference_byte_to_utf16(line_text: &str, byte_col: u32) -> u32 {
+        let byte_col = byte_col as usize;
+        let mut byte_off = 0usize;
+        let mut utf16_off = 0usize;
+        for ch in line_text.chars() {
+            if byte_col <= byte_off {
+                break;
+            }
+            if byte_col < byte_off + ch.len_utf8() {
+                break;
+            }
+            byte_off += ch.len_utf8();
+            utf16_off += ch.len_utf16();
+        }
+        utf16_off as u32
+    }
+
+    fn reference_utf16_to_byte(line_text: &str, utf16_col: u32) -> u32 {
+        let utf16_col = utf16_col as usize;
+        let mut byte_off = 0usize;
+        let mut utf16_off = 0usize;
+        for ch in line_text.chars() {
+            if utf16_col <= utf16_off {
+                break;
+            }
+            if utf16_col < utf16_off + ch.len_utf16() {
+                break;
+            }
+            byte_off += ch.len_utf8();
+            utf16_off += ch.len_utf16();
+        }
+        byte_off as u32
+    }
+
+    fn line_text(source: &str, line: u32) -> &str {
+        source.split('\n').nth(line as usize).unwrap_or("")
+    }
+
+    fn assert_line_conversions(source: &str, line: u32) {
+        let idx = LspLineIndex::new(source, UTF16);
+        let text = line_text(source, line);
+        let max_byte = text.len() as u32 + 4;
+        let max_utf16 = text.encode_utf16().count() as u32 + 4;
+
+        for byte_col in 0..=max_byte {
+            let expected = reference_byte_to_utf16(text, byte_col);
+            assert_eq!(
+                byte_col_to_lsp(source, line, byte_col, UTF16),
+                expected,
+                "one-off byte->utf16 mismatch for line {line}, byte_col {byte_col}, text {text:?}"
+            );
+            assert_eq!(
+                idx.byte_col_to_lsp(line, byte_col),
+                expected,
+                "indexed byte->utf16 mismatch for line {line}, byte_col {byte_col}, text {text:?}"
+            );
+        }
+
+        for utf16_col in 0..=max_utf16 {
+            assert_eq!(
+                lsp_col_to_byte(source, line, utf16_col, UTF16),
+                reference_utf16_to_byte(text, utf16_col),
+                "utf16->byte mismatch for line {line}, utf16_col {utf16_col}, text {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ascii_only() {
+        let source = "hello world\nfoo bar\n";
+        assert_eq!(byte_col_to_lsp(source, 0, 5, UTF16), 5);
+        assert_eq!(byte_col_to_lsp(source, 1, 3, UTF16), 3);
+    }
+
+    #[test]
+    fn test_cyrillic() {
+        // Cyrillic: each char is 2 bytes UTF-8, 1 code unit UTF-16
+        let source = "<?php\n$x = 'Тест';\n";
+        // Line 1: $x = 'Тест';
+        // bytes:  $ x   =   '  Т(2B) е(2B) с(2B) т(2B) '  ;
+        // byte:   0 1 2 3 4 5 6   8   10  12  14 15 16
+        // utf16:  0 1 2 3 4 5 6   7    8   9  10 11 12
+        // The semicolon is at byte 16, utf16 12
+        assert_eq!(byte_col_to_lsp(source, 1, 6, UTF16), 6); // start of Т
+        assert_eq!(byte_col_to_lsp(source, 1, 8, UTF16), 7); // after Т
+        assert_eq!(byte_col_to_lsp(source, 1, 14, UTF16), 10); // after т
+    }
+
+    #[test]
+    fn test_index_matches_function() {
+        let source = "<?php\n$msg = 'Привет мир';\necho $msg;\n";
+        let idx = LspLineIndex::new(source, UTF16);
+        for line in 0..3u32 {
+            for col in 0..30u32 {
+                assert_eq!(
+                    idx.byte_col_to_lsp(line, col),
+                    byte_col_to_lsp(source, line, col, UTF16),
+                    "mismatch at line={}, col={}",
+                    line,
+                    col
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_clamps_inside_multibyte_character() {
+        let source = "<?php\n$x = '😀';\n";
+        let idx = LspLineIndex::new(source, UTF16);
+
+        assert_eq!(byte_col_to_lsp(source, 1, 7, UTF16), 6);
+        assert_eq!(byte_col_to_lsp(source, 1, 8, UTF16), 6);
+        assert_eq!(byte_col_to_lsp(source, 1, 9, UTF16), 6);
+        for col in 0..16 {
+            assert_eq!(
+                idx.byte_col_to_lsp(1, col),
+                byte_col_to_lsp(source, 1, col, UTF16),
+                "mismatch at byte col {col}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_exhaustive_unicode_line_conversions() {
+        let cases = [
+            "plain ascii",
+            "кириллица",
+            "Greek αβγ and Hebrew שלום",
+            "latin combining e\u{0301} a\u{0308}",
+            "precomposed é ü ñ",
+            "emoji 😀😇🚀",
+            "american flag 🇺🇸",
+            "skin tones 👍🏽👩🏾",
+            "zwj family 👨\u{200d}👩\u{200d}👧\u{200d}👦",
+            "variation heart ♥\u{fe0f} text ♥",
+            "mixed $var = Привет😀e\u{0301}👩\u{200d}💻;",
+            "tabs\tand\tunicode\t😀",
+        ];
+
+        for case in cases {
+            let source = format!("<?php\n// {case}\n$value = '{case}';\n");
+            assert_line_conversions(&source, 1);
+            assert_line_conversions(&source, 2);
+        }
+    }
+
+    #[test]
+    fn test_crlf_empty_lines_and_eof_conversions() {
+        let source = "<?php\r\n\r\n$emoji = '😀';\r\n";
+
+        assert_line_conversions(source, 0);
+        assert_line_conversions(source, 1);
+        assert_line_conversions(source, 2);
+        assert_eq!(byte_col_to_lsp(source, 99, 7, UTF16), 7);
+        assert_eq!(lsp_col_to_byte(source, 99, 7, UTF16), 7);
+    }
+
+    #[test]
+    fn test_negotiate_prefers_first_supported_client_encoding() {
+        assert_eq!(
+            PositionEncoding::negotiate(["utf-8", "utf-16"]),
+            PositionEncoding::Utf8
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(["latin-1", "utf-32"]),
+            PositionEncoding::Utf32
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(["latin-1"]),
+            PositionEncoding::Utf16
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(std::iter::empty()),
+            PositionEncoding::Utf16
+        );
+    }
+
+    #[test]
+    fn test_utf8_and_utf32_column_conversions() {
+        let source = "<?php\n$x = '😀é'; $y;\n";
+        let y_byte = "$x = '😀é'; ".len() as u32;
+
+        assert_eq!(
+            byte_col_to_lsp(source, 1, y_byte, PositionEncoding::Utf8),
+            y_byte
+        );
+        assert_eq!(
+            byte_col_to_lsp(source, 1, y_byte, PositionEncoding::Utf16),
+            12
+        );
+        assert_eq!(
+            byte_col_to_lsp(source, 1, y_byte, PositionEncoding::Utf32),
+            11
+        );
+        for encoding in [
+            PositionEncoding::Utf8,
+            PositionEncoding::Utf16,
+            PositionEncoding::Utf32,
+        ] {
+            let column = byte_col_to_lsp(source, 1, y_byte, encoding);
+            assert_eq!(lsp_col_to_byte(source, 1, column, encoding), y_byte);
+            let index = LspLineIndex::new(source, encoding);
+            assert_eq!(index.byte_col_to_lsp(1, y_byte), column);
+            assert_eq!(encoding.text_len("$x = '😀é'; "), column);
+        }
+
+        // Columns inside a multi-byte character snap back to its start.
+        assert_eq!(lsp_col_to_byte(source, 1, 7, PositionEncoding::Utf8), 6);
+    }
+
+    #[test]
+    fn test_range_byte_to_utf16_multiline_unicode() {
+        let source = "<?php\n$one = 'Привет';\n$two = '😀';\n";
+
+        assert_eq!(
+            range_byte_to_lsp(source, (1, 8, 2, 11), UTF16),
+            (1, 8, 2, 8)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Precompiled tree-sitter queries.
+//!
+//! Each query is compiled on first use and shared for the rest of the
+//! process. The queries here only list node kinds: they stand in for
+//! recursive walks that dispatch on `node.kind()`, and return the nodes in
+//! the order a pre-order walk visits them, without growing the stack on
+//! deeply nested code.
+//!
+//! A query visits the whole subtree, so walks that skip nested scopes (the
+//! variable walks) stay hand-written. So do the single-target walks behind
+//! `find_references_in_file` and `collect_aliased_class_fqns`: they do
+//! little work per node, and `benches/extraction.rs` shows a query costing
+//! them 1.5-2x. The indexing passes spend their time per match and run the
+//! same either way.
+
+use std::sync::LazyLock;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Query, QueryCursor};
+
+/// Declarations `symbols.rs` extracts, plus calls that may be `define()`.
+pub(crate) static SYMBOL_DECLARATIONS: LazyLock<Query> = LazyLock::new(|| {
+    compile(
+        "[
+            (namespace_use_declaration)
+            (class_declaration)
+            (interface_declaration)
+            (trait_declaration)
+            (enum_declaration)
+            (function_definition)
+            (const_declaration)
+            (function_call_expression)
+        ] @node",
+    )
+});
+
+/// Sites `references.rs` indexes for workspace-wide reference lookups.
+pub(crate) static SYMBOL_REFERENCE_SITES: LazyLock<Query> = LazyLock::new(|| {
+    compile(
+        "[
+            (object_creation_expression)
+            (scoped_call_expression)
+            (scoped_property_access_expression)
+            (member_access_expression)
+            (nullsafe_member_access_expression)
+            (member_call_expression)
+            (nullsafe_member_call_expression)
+            (class_constant_access_expression)
+            (function_call_expression)
+            (named_type)
+            (base_clause)
+            (class_interface_clause)
+            (attribute)
+            (string)
+            (encapsed_string)
+            (catch_clause)
+            (name)
+            (qualified_name)
+        ] @node",
+    )
+});
+
+/// Nodes `semantic.rs` checks against the index.
+pub(crate) static SEMANTIC_CHECK_SITES: LazyLock<Query> = LazyLock::new(|| {
+    compile(
+        "[
+            (object_creation_expression)
+            (named_type)
+            (optional_type)
+            (base_clause)
+            (class_interface_clause)
+            (function_call_expression)
+        ] @node",
+    )
+});
+
+fn compile(source: &str) -> Query {
+    Query::new(&tree_sitter_php::LANGUAGE_PHP.into(), source)
+        .unwrap_or_else(|err| panic!("invalid built-in query: {err}"))
+}
+
+/// Call `f` with every node `query` captures in the subtree of `node`,
+/// including `node` itself, in document order.
+pub(crate) fn for_each_capture<'tree>(
+    query: &Query,
+    node: Node<'tree>,
+    source: &str,
+    mut f: impl FnMut(Node<'tree>),
+) {
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(query, node, source.as_bytes());
+    while let Some((query_match, index)) = captures.next() {
+        f(query_match.captures[*index].node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::FileParser;
+
+    #[test]
+    fn test_builtin_queries_compile() {
+        for query in [
+            &SYMBOL_DECLARATIONS,
+            &SYMBOL_REFERENCE_SITES,
+            &SEMANTIC_CHECK_SITES,
+        ] {
+            assert_eq!(query.capture_names(), ["node"]);
+        }
+    }
+
+    #[test]
+    fn test_captures_follow_pre_order() {
+        let source = "<?php\nif (true) { foo(bar())(); function f() {} }\n";
+        let mut parser = FileParser::new();
+        parser.parse_full(source);
+        let root = parser.tree().unwrap().root_node();
+
+        let mut nodes = Vec::new();
+        for_each_capture(&SYMBOL_DECLARATIONS, root, source, |node| {
+            nodes.push(&source[node.byte_range()]);
+        });
+        assert_eq!(
+            nodes,
+            ["foo(bar())()", "foo(bar())", "bar()", "function f() {}"]
+        );
+    }
+}
@@ -3,21 +3,78 @@
 //! Given a target FQN and the file's CST + symbols, returns all locations
 //! in the file that reference the target.
 
-use crate::cst::{ancestor_field_contains, is_foreach_header_declared_variable};
+use crate::cst::{
+    ancestor_field_contains, is_foreach_header_declared_variable, is_qualified_identifier,
+    is_trait_adaptation_method, plain_string_literal_content,
+};
+use crate::position::{LspLineIndex, PositionEncoding};
+use crate::queries;
 use crate::resolve::{
     resolve_scope_class_name_pub, symbol_at_position_with_resolvers, CallableParamTypeResolver,
     MemberTypeResolver, RefKind,
 };
-use crate::utf16::range_byte_to_utf16;
 use php_lsp_types::{
-    FileSymbols, PhpSymbolKind, SymbolReference, SymbolReferenceReceiver, UseKind,
+    FileSymbols, PhpSymbolKind, SymbolReference, SymbolReferenceReceiver, SymbolReferenceUsage,
+    UseKind,
 };
 use tree_sitter::{Node, Point, Tree};
 
+/// How a reference site uses its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    /// Any other use: variable and property reads, `Foo::CONST`, `instanceof Foo`.
+    Read,
+    /// Assignment or increment target, foreach/catch binding, or the declaration itself.
+    Write,
+    /// Function, method or static method call.
+    Call,
+    /// `new Foo()`, including constructor references.
+    Instantiate,
+    /// Parameter, return, property or catch type.
+    TypeHint,
+}
+
 /// A location within a file where a reference was found.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReferenceLocation {
     pub range: (u32, u32, u32, u32),
+    pub kind: ReferenceKind,
+}
+
+/// String literals whose whole content is `member_name`, such as the `'save'`
+/// in `[$this, 'save']` or the `'users'` in `compact('users')` and
+/// `$obj->{'users'}`. Dynamic accesses (`$obj->$name`, `call_user_func`) get
+/// member names from literals like these, so a rename can offer them for
+/// review. Ranges cover the literal content without its quotes.
+pub fn find_member_name_string_literals(
+    tree: &Tree,
+    source: &str,
+    member_name: &str,
+    case_insensitive: bool,
+) -> Vec<ReferenceLocation> {
+    let mut results = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if let Some(content) = plain_string_literal_content(node, source) {
+            let matches = if case_insensitive {
+                content.eq_ignore_ascii_case(member_name)
+            } else {
+                content == member_name
+            };
+            if matches {
+                let (start_row, start_col, end_row, end_col) = node_range(node);
+                results.push(ReferenceLocation {
+                    range: (start_row, start_col + 1, end_row, end_col - 1),
+                    kind: ReferenceKind::Read,
+                });
+            }
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    results.sort_by_key(|reference| reference.range);
+    results
 }
 
 /// Find local variable references in the same lexical scope at cursor position.
@@ -66,10 +123,23 @@ pub fn find_variable_references_at_position(
 
     let var_name = normalize_var_name(&source[node.byte_range()]);
     let scope = find_variable_scope(node).unwrap_or(root);
+    // File-level variables are shared with every function that imports them
+    // through `global $name;`.
+    let scopes = if scope.id() == root.id() || scope_declares_global(scope, source, &var_name) {
+        let mut scopes = vec![root];
+        collect_global_importing_scopes(root, source, &var_name, &mut scopes);
+        scopes
+    } else {
+        vec![scope]
+    };
 
     let mut refs: Vec<ReferenceLocation> = Vec::new();
     let mut declarations: Vec<(u32, u32, u32, u32)> = Vec::new();
-    walk_variable_refs(scope, source, &var_name, &mut refs, &mut declarations);
+    for scope in scopes {
+        walk_variable_refs(scope, source, &var_name, &mut refs, &mut declarations);
+    }
+    refs.sort_by_key(|r| r.range);
+    refs.dedup();
 
     if include_declaration {
         refs
@@ -160,18 +230,28 @@ pub fn collect_symbol_references_in_file(
     tree: &Tree,
     source: &str,
     file_symbols: &FileSymbols,
+    encoding: PositionEncoding,
 ) -> Vec<SymbolReference> {
-    collect_symbol_references_in_file_with_resolvers(tree, source, file_symbols, None, None)
+    collect_symbol_references_in_file_with_resolvers(
+        tree,
+        source,
+        file_symbols,
+        None,
+        None,
+        encoding,
+    )
 }
 
 /// Collect non-local symbol occurrences, using optional resolvers for receiver
-/// type inference when indexing member references.
+/// type inference when indexing member references. Ranges are LSP positions
+/// in `encoding`.
 pub fn collect_symbol_references_in_file_with_resolvers(
     tree: &Tree,
     source: &str,
     file_symbols: &FileSymbols,
     resolver: Option<MemberTypeResolver<'_>>,
     callable_resolver: Option<CallableParamTypeResolver<'_>>,
+    encoding: PositionEncoding,
 ) -> Vec<SymbolReference> {
     let mut references = Vec::new();
 
@@ -180,24 +260,43 @@ pub fn collect_symbol_references_in_file_with_resolvers(
             continue;
         }
         references.push(SymbolReference {
-            target_fqn: symbol.fqn.clone(),
+            target_fqn: symbol.fqn.to_string(),
             target_kind: symbol.kind,
-            range: range_byte_to_utf16(source, symbol.selection_range),
+            range: symbol.selection_range,
             is_declaration: true,
             starts_with_dollar: symbol.kind == PhpSymbolKind::Property,
             receiver: SymbolReferenceReceiver::None,
+            is_string_literal: false,
+            usage: SymbolReferenceUsage::Other,
         });
     }
 
-    collect_symbol_references_walk(
-        tree,
+    queries::for_each_capture(
+        &queries::SYMBOL_REFERENCE_SITES,
         tree.root_node(),
         source,
-        file_symbols,
-        &mut references,
-        resolver,
-        callable_resolver,
+        |node| {
+            collect_symbol_references_at(
+                tree,
+                node,
+                source,
+                file_symbols,
+                &mut references,
+                resolver,
+                callable_resolver,
+            );
+        },
     );
+    let line_index = LspLineIndex::new(source, encoding);
+    for reference in &mut references {
+        let (start_line, start_col, end_line, end_col) = reference.range;
+        reference.range = (
+            start_line,
+            line_index.byte_col_to_lsp(start_line, start_col),
+            end_line,
+            line_index.byte_col_to_lsp(end_line, end_col),
+        );
+    }
     sort_and_dedup_symbol_references(&mut references);
     references
 }
@@ -250,7 +349,7 @@ fn symbol_reference_kind_rank(kind: PhpSymbolKind) -> u8 {
     }
 }
 
-fn collect_symbol_references_walk(
+fn collect_symbol_references_at(
     tree: &Tree,
     node: Node,
     source: &str,
@@ -264,7 +363,14 @@ fn collect_symbol_references_walk(
             let mut cursor = node.walk();
             for child in node.named_children(&mut cursor) {
                 if child.kind() == "name" || child.kind() == "qualified_name" {
-                    push_class_reference(child, source, file_symbols, references);
+                    push_class_reference_with_usage(
+                        child,
+                        source,
+                        file_symbols,
+                        references,
+                        SymbolReferenceUsage::Instantiation,
+                    );
+                    push_constructor_reference(node, child, source, file_symbols, references);
                     break;
                 }
             }
@@ -280,7 +386,7 @@ fn collect_symbol_references_walk(
                         references,
                         format!("{}::{}", scope_fqn, member_name),
                         PhpSymbolKind::Method,
-                        reference_range(source, name_node),
+                        node_range(name_node),
                         false,
                         false,
                         SymbolReferenceReceiver::StaticClass {
@@ -292,7 +398,7 @@ fn collect_symbol_references_walk(
                         references,
                         format!("::{}", member_name),
                         PhpSymbolKind::Method,
-                        reference_range(source, name_node),
+                        node_range(name_node),
                         false,
                         false,
                         SymbolReferenceReceiver::Unresolved,
@@ -322,7 +428,7 @@ fn collect_symbol_references_walk(
                         references,
                         format!("{}::{}", scope_fqn, member),
                         kind,
-                        reference_range(source, name_node),
+                        node_range(name_node),
                         false,
                         raw_name.starts_with('$'),
                         SymbolReferenceReceiver::StaticClass {
@@ -334,7 +440,7 @@ fn collect_symbol_references_walk(
                         references,
                         format!("::{}", member),
                         kind,
-                        reference_range(source, name_node),
+                        node_range(name_node),
                         false,
                         raw_name.starts_with('$'),
                         SymbolReferenceReceiver::Unresolved,
@@ -364,7 +470,7 @@ fn collect_symbol_references_walk(
                     references,
                     target_fqn,
                     PhpSymbolKind::Property,
-                    reference_range(source, name_node),
+                    node_range(name_node),
                     false,
                     false,
                     receiver,
@@ -388,7 +494,7 @@ fn collect_symbol_references_walk(
                     references,
                     target_fqn,
                     PhpSymbolKind::Method,
-                    reference_range(source, name_node),
+                    node_range(name_node),
                     false,
                     false,
                     receiver,
@@ -416,11 +522,16 @@ fn collect_symbol_references_walk(
                 } else {
                     (format!("::{}", text), SymbolReferenceReceiver::Unresolved)
                 };
+                let kind = if is_trait_adaptation_method(node) {
+                    PhpSymbolKind::Method
+                } else {
+                    PhpSymbolKind::ClassConstant
+                };
                 push_symbol_reference(
                     references,
                     target,
-                    PhpSymbolKind::ClassConstant,
-                    reference_range(source, name_node),
+                    kind,
+                    node_range(name_node),
                     false,
                     false,
                     receiver,
@@ -434,7 +545,7 @@ fn collect_symbol_references_walk(
                     references,
                     resolve_function_name_to_fqn(text, file_symbols),
                     PhpSymbolKind::Function,
-                    reference_range(source, func_node),
+                    node_range(func_node),
                     false,
                     false,
                     SymbolReferenceReceiver::None,
@@ -442,10 +553,15 @@ fn collect_symbol_references_walk(
             }
         }
         "named_type" | "base_clause" | "class_interface_clause" => {
+            let usage = if node.kind() == "named_type" {
+                SymbolReferenceUsage::TypeHint
+            } else {
+                SymbolReferenceUsage::Other
+            };
             let mut cursor = node.walk();
             for child in node.named_children(&mut cursor) {
                 if child.kind() == "name" || child.kind() == "qualified_name" {
-                    push_class_reference(child, source, file_symbols, references);
+                    push_class_reference_with_usage(child, source, file_symbols, references, usage);
                 }
             }
             if node.named_child_count() == 0
@@ -454,17 +570,23 @@ fn collect_symbol_references_walk(
                 push_class_reference(node, source, file_symbols, references);
             }
         }
-        "instanceof_expression" => {
-            if let Some(right) = node.child_by_field_name("right") {
-                push_class_reference(right, source, file_symbols, references);
+        "attribute" => {
+            if let Some(name_node) = attribute_name_node(node) {
+                push_class_reference(name_node, source, file_symbols, references);
             }
         }
-        "trait_use_clause" => {
-            let mut cursor = node.walk();
-            for child in node.named_children(&mut cursor) {
-                if child.kind() == "name" || child.kind() == "qualified_name" {
-                    push_class_reference(child, source, file_symbols, references);
-                }
+        "string" | "encapsed_string" => {
+            if let Some((fqn, range)) = class_name_in_string_literal(node, source) {
+                references.push(SymbolReference {
+                    target_fqn: fqn,
+                    target_kind: PhpSymbolKind::Class,
+                    range,
+                    is_declaration: false,
+                    starts_with_dollar: false,
+                    receiver: SymbolReferenceReceiver::None,
+                    is_string_literal: true,
+                    usage: SymbolReferenceUsage::Other,
+                });
             }
         }
         "catch_clause" => {
@@ -485,19 +607,6 @@ fn collect_symbol_references_walk(
         }
         _ => {}
     }
-
-    let mut cursor = node.walk();
-    for child in node.named_children(&mut cursor) {
-        collect_symbol_references_walk(
-            tree,
-            child,
-            source,
-            file_symbols,
-            references,
-            resolver,
-            callable_resolver,
-        );
-    }
 }
 
 fn push_class_reference(
@@ -505,21 +614,64 @@ fn push_class_reference(
     source: &str,
     file_symbols: &FileSymbols,
     references: &mut Vec<SymbolReference>,
+) {
+    push_class_reference_with_usage(
+        node,
+        source,
+        file_symbols,
+        references,
+        SymbolReferenceUsage::Other,
+    );
+}
+
+fn push_class_reference_with_usage(
+    node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+    references: &mut Vec<SymbolReference>,
+    usage: SymbolReferenceUsage,
 ) {
     let text = &source[node.byte_range()];
     let resolved = resolve_name_to_fqn(text, file_symbols);
     if is_builtin_or_relative_class_name(&resolved) {
         return;
     }
-    push_symbol_reference(
-        references,
-        resolved,
-        PhpSymbolKind::Class,
-        reference_range(source, node),
-        false,
-        false,
-        SymbolReferenceReceiver::None,
-    );
+    references.push(SymbolReference {
+        target_fqn: resolved,
+        target_kind: PhpSymbolKind::Class,
+        range: node_range(node),
+        is_declaration: false,
+        starts_with_dollar: false,
+        receiver: SymbolReferenceReceiver::None,
+        is_string_literal: false,
+        usage,
+    });
+}
+
+/// `new ClassName()` also counts as a call to `ClassName::__construct`, with
+/// the class as receiver so inherited constructors match through the hierarchy.
+fn push_constructor_reference(
+    creation_node: Node,
+    class_node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+    references: &mut Vec<SymbolReference>,
+) {
+    let Some(class_fqn) =
+        scoped_member_reference_class_from_scope(class_node, creation_node, source, file_symbols)
+    else {
+        return;
+    };
+    references.push(SymbolReference {
+        target_fqn: format!("{}::__construct", class_fqn),
+        target_kind: PhpSymbolKind::Method,
+        range: node_range(class_node),
+        is_declaration: false,
+        starts_with_dollar: false,
+        receiver: SymbolReferenceReceiver::StaticClass { class_fqn },
+        is_string_literal: false,
+        usage: SymbolReferenceUsage::Instantiation,
+    });
 }
 
 fn scoped_member_reference_class(
@@ -569,6 +721,8 @@ fn push_constant_reference_if_plain_name(
             | "class_constant_access_expression"
             | "member_access_expression"
             | "member_call_expression"
+            | "nullsafe_member_access_expression"
+            | "nullsafe_member_call_expression"
     ) {
         return;
     }
@@ -581,7 +735,7 @@ fn push_constant_reference_if_plain_name(
         references,
         resolve_constant_name_to_fqn(text, file_symbols),
         PhpSymbolKind::GlobalConstant,
-        reference_range(source, node),
+        node_range(node),
         false,
         false,
         SymbolReferenceReceiver::None,
@@ -678,13 +832,11 @@ fn push_symbol_reference(
         is_declaration,
         starts_with_dollar,
         receiver,
+        is_string_literal: false,
+        usage: SymbolReferenceUsage::Other,
     });
 }
 
-fn reference_range(source: &str, node: Node) -> (u32, u32, u32, u32) {
-    range_byte_to_utf16(source, node_range(node))
-}
-
 /// Find all references to a class/interface/trait/enum in a file.
 fn find_class_references(
     root: Node,
@@ -708,6 +860,7 @@ fn find_class_references(
             {
                 results.push(ReferenceLocation {
                     range: sym.selection_range,
+                    kind: ReferenceKind::Write,
                 });
             }
         }
@@ -780,6 +933,25 @@ fn walk_for_class_refs(
             }
         }
 
+        // #[ClassName(...)]
+        "attribute" => {
+            if let Some(name_node) = attribute_name_node(node) {
+                check_class_name_ref(name_node, source, file_symbols, target_fqn, results);
+            }
+        }
+
+        // 'App\ClassName' in container definitions and similar
+        "string" | "encapsed_string" => {
+            if let Some((fqn, range)) = class_name_in_string_literal(node, source) {
+                if fqn == target_fqn {
+                    results.push(ReferenceLocation {
+                        range,
+                        kind: ReferenceKind::Read,
+                    });
+                }
+            }
+        }
+
         // catch clause
         "catch_clause" => {
             if let Some(type_node) = node.child_by_field_name("type") {
@@ -806,6 +978,40 @@ fn walk_for_class_refs(
     }
 }
 
+fn attribute_name_node(attribute: Node) -> Option<Node> {
+    let mut cursor = attribute.walk();
+    let name_node = attribute
+        .named_children(&mut cursor)
+        .find(|child| matches!(child.kind(), "name" | "qualified_name"));
+    name_node
+}
+
+/// A namespaced class name spelled as a string literal, e.g.
+/// `'App\Service\Mailer'`, with the byte range of the name inside the quotes.
+/// Bare words are skipped since they are rarely class references.
+fn class_name_in_string_literal(
+    node: Node,
+    source: &str,
+) -> Option<(String, (u32, u32, u32, u32))> {
+    let content = plain_string_literal_content(node, source)?;
+    let name = content.strip_prefix('\\').unwrap_or(&content);
+    if !name.contains('\\') || !is_qualified_identifier(name) {
+        return None;
+    }
+
+    let start = node.start_position();
+    let end = node.end_position();
+    Some((
+        name.to_string(),
+        (
+            start.row as u32,
+            start.column as u32 + 1,
+            end.row as u32,
+            end.column as u32 - 1,
+        ),
+    ))
+}
+
 /// Check if a node is a class name reference to the target FQN.
 fn check_class_name_ref(
     node: Node,
@@ -827,6 +1033,7 @@ fn check_class_name_ref(
                 end.row as u32,
                 end.column as u32,
             ),
+            kind: reference_kind(node, source),
         });
     }
 }
@@ -892,6 +1099,7 @@ fn find_function_references(
             if sym.fqn == target_fqn && sym.kind == PhpSymbolKind::Function {
                 results.push(ReferenceLocation {
                     range: sym.selection_range,
+                    kind: ReferenceKind::Write,
                 });
             }
         }
@@ -922,6 +1130,7 @@ fn walk_for_function_refs(
                         end.row as u32,
                         end.column as u32,
                     ),
+                    kind: reference_kind(func_node, source),
                 });
             }
         }
@@ -1022,6 +1231,7 @@ fn find_member_references(
             if sym.fqn == target_fqn {
                 results.push(ReferenceLocation {
                     range: sym.selection_range,
+                    kind: ReferenceKind::Write,
                 });
             }
         }
@@ -1067,6 +1277,7 @@ fn walk_for_member_refs(
                             end.row as u32,
                             end.column as u32,
                         ),
+                        kind: reference_kind(name_node, source),
                     });
                 }
             }
@@ -1088,6 +1299,7 @@ fn walk_for_member_refs(
                             end.row as u32,
                             end.column as u32,
                         ),
+                        kind: reference_kind(name_node, source),
                     });
                 }
             }
@@ -1107,9 +1319,10 @@ fn walk_for_member_refs(
                         let expected_class = &target_fqn[..target_fqn.rfind("::").unwrap_or(0)];
 
                         if scope_fqn == expected_class
-                            || scope_text == "self"
-                            || scope_text == "static"
-                            || scope_text == "parent"
+                            || matches!(
+                                scope_text.to_ascii_lowercase().as_str(),
+                                "self" | "static" | "parent"
+                            )
                         {
                             let start = name_node.start_position();
                             let end = name_node.end_position();
@@ -1120,6 +1333,7 @@ fn walk_for_member_refs(
                                     end.row as u32,
                                     end.column as u32,
                                 ),
+                                kind: reference_kind(name_node, source),
                             });
                         }
                     }
@@ -1127,6 +1341,29 @@ fn walk_for_member_refs(
             }
         }
 
+        // new ClassName() calls ClassName::__construct
+        "object_creation_expression"
+            if target_kind == PhpSymbolKind::Method
+                && member_name.eq_ignore_ascii_case("__construct") =>
+        {
+            let mut cursor = node.walk();
+            let class_node = node
+                .named_children(&mut cursor)
+                .find(|child| matches!(child.kind(), "name" | "qualified_name"));
+            if let Some(class_node) = class_node {
+                let class_text = &source[class_node.byte_range()];
+                let class_fqn =
+                    resolve_scope_class_name_pub(class_text, node, source, _file_symbols);
+                let expected_class = &target_fqn[..target_fqn.rfind("::").unwrap_or(0)];
+                if class_fqn == expected_class {
+                    results.push(ReferenceLocation {
+                        range: node_range(class_node),
+                        kind: reference_kind(class_node, source),
+                    });
+                }
+            }
+        }
+
         // ClassName::$prop or ClassName::CONST
         "scoped_property_access_expression" => {
             if let Some(name_node) = node.child_by_field_name("name") {
@@ -1148,9 +1385,10 @@ fn walk_for_member_refs(
                         let expected_class = &target_fqn[..target_fqn.rfind("::").unwrap_or(0)];
 
                         if scope_fqn == expected_class
-                            || scope_text == "self"
-                            || scope_text == "static"
-                            || scope_text == "parent"
+                            || matches!(
+                                scope_text.to_ascii_lowercase().as_str(),
+                                "self" | "static" | "parent"
+                            )
                         {
                             let start = name_node.start_position();
                             let end = name_node.end_position();
@@ -1161,6 +1399,7 @@ fn walk_for_member_refs(
                                     end.row as u32,
                                     end.column as u32,
                                 ),
+                                kind: reference_kind(name_node, source),
                             });
                         }
                     }
@@ -1170,10 +1409,15 @@ fn walk_for_member_refs(
 
         // self::CONST / ClassName::CONST
         "class_constant_access_expression" => {
-            if !matches!(
-                target_kind,
-                PhpSymbolKind::ClassConstant | PhpSymbolKind::EnumCase
-            ) {
+            let expected_kind_matches = if is_trait_adaptation_method(node) {
+                target_kind == PhpSymbolKind::Method
+            } else {
+                matches!(
+                    target_kind,
+                    PhpSymbolKind::ClassConstant | PhpSymbolKind::EnumCase
+                )
+            };
+            if !expected_kind_matches {
                 // Method/property targets should not match class constant access.
             } else if let (Some(scope_node), Some(name_node)) =
                 (node.named_child(0), node.named_child(1))
@@ -1185,9 +1429,10 @@ fn walk_for_member_refs(
                     let expected_class = &target_fqn[..target_fqn.rfind("::").unwrap_or(0)];
 
                     if scope_fqn == expected_class
-                        || scope_text == "self"
-                        || scope_text == "static"
-                        || scope_text == "parent"
+                        || matches!(
+                            scope_text.to_ascii_lowercase().as_str(),
+                            "self" | "static" | "parent"
+                        )
                     {
                         let start = name_node.start_position();
                         let end = name_node.end_position();
@@ -1198,6 +1443,7 @@ fn walk_for_member_refs(
                                 end.row as u32,
                                 end.column as u32,
                             ),
+                            kind: reference_kind(name_node, source),
                         });
                     }
                 }
@@ -1250,6 +1496,7 @@ fn find_constant_references(
             if sym.fqn == target_fqn && sym.kind == PhpSymbolKind::GlobalConstant {
                 results.push(ReferenceLocation {
                     range: sym.selection_range,
+                    kind: ReferenceKind::Write,
                 });
             }
         }
@@ -1297,6 +1544,7 @@ fn walk_for_constant_refs(
                         end.row as u32,
                         end.column as u32,
                     ),
+                    kind: reference_kind(node, source),
                 });
             }
         }
@@ -1319,7 +1567,10 @@ fn walk_variable_refs(
         let text = normalize_var_name(&source[node.byte_range()]);
         if text == var_name {
             let range = node_range(node);
-            refs.push(ReferenceLocation { range });
+            refs.push(ReferenceLocation {
+                range,
+                kind: reference_kind(node, source),
+            });
             if is_variable_declaration(node, source, var_name) {
                 declarations.push(range);
             }
@@ -1328,7 +1579,78 @@ fn walk_variable_refs(
 
     let cursor = &mut node.walk();
     for child in node.named_children(cursor) {
-        walk_variable_refs(child, source, var_name, refs, declarations);
+        if !is_nested_variable_scope(child, source, var_name) {
+            walk_variable_refs(child, source, var_name, refs, declarations);
+        }
+    }
+}
+
+/// Functions and methods get their own variables. Closures only share the
+/// variables they capture with `use (...)`; arrow functions share all of them.
+fn is_nested_variable_scope(node: Node, source: &str, var_name: &str) -> bool {
+    match node.kind() {
+        "function_definition" | "method_declaration" => true,
+        "anonymous_function" | "anonymous_function_creation_expression" => {
+            let cursor = &mut node.walk();
+            let captures = node
+                .named_children(cursor)
+                .filter(|child| child.kind() == "anonymous_function_use_clause")
+                .any(|use_clause| {
+                    let cursor = &mut use_clause.walk();
+                    let captured = use_clause.named_children(cursor).any(|captured| {
+                        normalize_var_name(source[captured.byte_range()].trim_start_matches('&'))
+                            == var_name
+                    });
+                    captured
+                });
+            !captures
+        }
+        _ => false,
+    }
+}
+
+fn scope_declares_global(scope: Node, source: &str, var_name: &str) -> bool {
+    if scope.kind() == "global_declaration" {
+        let cursor = &mut scope.walk();
+        let declared = scope.named_children(cursor).any(|var_node| {
+            var_node.kind() == "variable_name"
+                && normalize_var_name(&source[var_node.byte_range()]) == var_name
+        });
+        return declared;
+    }
+    let cursor = &mut scope.walk();
+    let declared = scope.named_children(cursor).any(|child| {
+        !matches!(
+            child.kind(),
+            "function_definition"
+                | "method_declaration"
+                | "anonymous_function"
+                | "anonymous_function_creation_expression"
+                | "arrow_function"
+        ) && scope_declares_global(child, source, var_name)
+    });
+    declared
+}
+
+fn collect_global_importing_scopes<'tree>(
+    node: Node<'tree>,
+    source: &str,
+    var_name: &str,
+    scopes: &mut Vec<Node<'tree>>,
+) {
+    let cursor = &mut node.walk();
+    for child in node.named_children(cursor) {
+        if matches!(
+            child.kind(),
+            "function_definition"
+                | "method_declaration"
+                | "anonymous_function"
+                | "anonymous_function_creation_expression"
+        ) && scope_declares_global(child, source, var_name)
+        {
+            scopes.push(child);
+        }
+        collect_global_importing_scopes(child, source, var_name, scopes);
     }
 }
 
@@ -1359,7 +1681,84 @@ fn is_variable_declaration(node: Node, source: &str, var_name: &str) -> bool {
                 .map(|n| n.id() == node.id())
                 .unwrap_or(false)
         }),
-        "anonymous_function_use_clause" => true,
+        "anonymous_function_use_clause" | "global_declaration" => true,
+        "static_variable_declaration" => parent
+            .child_by_field_name("name")
+            .is_some_and(|n| n.id() == node.id()),
+        _ => false,
+    }
+}
+
+/// Classifies the reference whose reported range is `node`: a name, class
+/// name or variable node.
+fn reference_kind(node: Node, source: &str) -> ReferenceKind {
+    let Some(parent) = node.parent() else {
+        return ReferenceKind::Read;
+    };
+    let is_field = |field: &str| {
+        parent
+            .child_by_field_name(field)
+            .is_some_and(|child| child.id() == node.id())
+    };
+    match parent.kind() {
+        "function_call_expression" if is_field("function") => return ReferenceKind::Call,
+        "member_call_expression" | "nullsafe_member_call_expression" | "scoped_call_expression"
+            if is_field("name") =>
+        {
+            return ReferenceKind::Call
+        }
+        "object_creation_expression" => return ReferenceKind::Instantiate,
+        "named_type" | "optional_type" | "union_type" | "intersection_type" | "type_list" => {
+            return ReferenceKind::TypeHint
+        }
+        _ => {}
+    }
+
+    // Writes are judged on the whole accessed expression: `$a`, `$this->a`, `A::$a`.
+    let target = match parent.kind() {
+        "member_access_expression"
+        | "nullsafe_member_access_expression"
+        | "scoped_property_access_expression"
+            if is_field("name") =>
+        {
+            parent
+        }
+        _ => node,
+    };
+    if is_write_target(target, source) {
+        ReferenceKind::Write
+    } else {
+        ReferenceKind::Read
+    }
+}
+
+fn is_write_target(node: Node, source: &str) -> bool {
+    if is_foreach_header_declared_variable(node, source) {
+        return true;
+    }
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let is_field = |field: &str| {
+        parent
+            .child_by_field_name(field)
+            .is_some_and(|child| child.id() == node.id())
+    };
+    match parent.kind() {
+        "assignment_expression"
+        | "reference_assignment_expression"
+        | "augmented_assignment_expression" => is_field("left"),
+        "update_expression" => true,
+        "simple_parameter"
+        | "variadic_parameter"
+        | "property_promotion_parameter"
+        | "static_variable_declaration" => is_field("name"),
+        "catch_clause" => is_field("name") || is_field("variable"),
+        "property_element" | "global_declaration" => true,
+        // `[$a, $b] = ...`, `list($a) = ...`, `&$value`
+        "array_element_initializer" | "array_creation_expression" | "list_literal" | "by_ref" => {
+            is_write_target(parent, source)
+        }
         _ => false,
     }
 }
@@ -1417,7 +1816,7 @@ mod tests {
         parser.parse_full(code);
         let tree = parser.tree().unwrap();
         let file_symbols = extract_file_symbols(tree, code, "file:///test.php");
-        collect_symbol_references_in_file(tree, code, &file_symbols)
+        collect_symbol_references_in_file(tree, code, &file_symbols, PositionEncoding::Utf16)
     }
 
     fn synthetic_symbol_reference(
@@ -1433,6 +1832,8 @@ mod tests {
             receiver: SymbolReferenceReceiver::ResolvedType {
                 type_fqn: "App\\Target".to_string(),
             },
+            is_string_literal: false,
+            usage: SymbolReferenceUsage::Other,
         }
     }
 
@@ -1486,6 +1887,79 @@ $svc2 = new UserService();
         );
     }
 
+    #[test]
+    fn test_collects_attribute_and_string_class_references() {
+        let code = r#"<?php
+namespace App;
+
+use App\Service\Mailer;
+
+#[Mailer]
+class Config {
+    public array $definitions = [
+        'mailer' => 'App\Service\Mailer',
+        'escaped' => "\\App\\Service\\Mailer",
+        'text' => 'Mailer',
+        'dynamic' => "App\\Service\\{$name}",
+    ];
+}
+"#;
+        let refs: Vec<_> = collect_refs(code)
+            .into_iter()
+            .filter(|reference| reference.target_fqn == "App\\Service\\Mailer")
+            .map(|reference| (reference.range, reference.is_string_literal))
+            .collect();
+
+        assert_eq!(
+            refs,
+            vec![
+                ((5, 2, 5, 8), false),
+                ((8, 21, 8, 39), true),
+                ((9, 22, 9, 44), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_expressions_reference_constructor_and_record_usage() {
+        let code = r#"<?php
+namespace App;
+
+class Mailer {
+    public function make(Mailer $other): Mailer {
+        return new Mailer();
+    }
+}
+"#;
+        let refs = collect_refs(code);
+        let usages: Vec<_> = refs
+            .iter()
+            .filter(|reference| reference.target_fqn == "App\\Mailer" && !reference.is_declaration)
+            .map(|reference| (reference.range.0, reference.usage))
+            .collect();
+        assert_eq!(
+            usages,
+            vec![
+                (4, SymbolReferenceUsage::TypeHint),
+                (4, SymbolReferenceUsage::TypeHint),
+                (5, SymbolReferenceUsage::Instantiation),
+            ]
+        );
+
+        let constructor = refs
+            .iter()
+            .find(|reference| reference.target_fqn == "App\\Mailer::__construct")
+            .expect("new expression should reference the constructor");
+        assert_eq!(constructor.target_kind, PhpSymbolKind::Method);
+        assert_eq!(constructor.range, (5, 19, 5, 25));
+        assert_eq!(
+            constructor.receiver,
+            SymbolReferenceReceiver::StaticClass {
+                class_fqn: "App\\Mailer".to_string()
+            }
+        );
+    }
+
     #[test]
     fn test_variable_reference_ranges_are_utf16_after_emoji() {
         let code = "<?php\n$emoji = \"😀\"; $target = 1; echo $target;\n";
@@ -1526,6 +2000,96 @@ class Controller {
         );
     }
 
+    #[test]
+    fn test_reference_kinds_distinguish_reads_writes_calls_and_instantiations() {
+        let code = r#"<?php
+namespace App;
+
+class Counter {
+    public int $count = 0;
+
+    public function make(Counter $other): Counter {
+        $this->count = $other->count;
+        $this->count++;
+        $this->make(new Counter());
+        foreach ([1, 2] as $step) {
+            [$left, $step] = [$step, 1];
+        }
+        return Counter::create();
+    }
+}
+"#;
+        let kinds = |target: &str, kind: PhpSymbolKind| -> Vec<(u32, ReferenceKind)> {
+            let mut refs: Vec<(u32, ReferenceKind)> = find_refs(code, target, kind)
+                .into_iter()
+                .map(|reference| (reference.range.0, reference.kind))
+                .collect();
+            refs.sort_by_key(|(line, _)| *line);
+            refs
+        };
+
+        let class_kinds = kinds("App\\Counter", PhpSymbolKind::Class);
+        assert_eq!(
+            class_kinds,
+            [
+                (3, ReferenceKind::Write),
+                (6, ReferenceKind::TypeHint),
+                (6, ReferenceKind::TypeHint),
+                (9, ReferenceKind::Instantiate),
+                (13, ReferenceKind::Read),
+            ]
+        );
+        assert_eq!(
+            kinds("App\\Counter::make", PhpSymbolKind::Method),
+            [(6, ReferenceKind::Write), (9, ReferenceKind::Call)]
+        );
+        assert_eq!(
+            kinds("App\\Counter::$count", PhpSymbolKind::Property),
+            [
+                (4, ReferenceKind::Write),
+                (7, ReferenceKind::Write),
+                (7, ReferenceKind::Read),
+                (8, ReferenceKind::Write),
+            ]
+        );
+
+        let step_col = code.lines().nth(10).unwrap().find("$step").unwrap() as u32;
+        let step_kinds: Vec<(u32, u32, ReferenceKind)> = find_var_refs_at(code, 10, step_col, true)
+            .into_iter()
+            .map(|reference| (reference.range.0, reference.range.1, reference.kind))
+            .collect();
+        assert_eq!(
+            step_kinds,
+            [
+                (10, 27, ReferenceKind::Write),
+                (11, 20, ReferenceKind::Write),
+                (11, 30, ReferenceKind::Read),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_member_name_string_literals() {
+        let code = r#"<?php
+$callback = [$this, 'save'];
+$value = $user->{'users'};
+extract(compact('users'));
+echo "users: {$count}", 'Save', 'user';
+"#;
+        let mut parser = FileParser::new();
+        parser.parse_full(code);
+        let tree = parser.tree().unwrap();
+
+        let ranges: Vec<_> = find_member_name_string_literals(tree, code, "users", false)
+            .into_iter()
+            .map(|reference| reference.range)
+            .collect();
+        assert_eq!(ranges, [(2, 18, 2, 23), (3, 17, 3, 22)]);
+
+        let methods = find_member_name_string_literals(tree, code, "save", true);
+        assert_eq!(methods.len(), 2, "got {methods:?}");
+    }
+
     #[test]
     fn test_find_function_references() {
         let code = r#"<?php
@@ -1608,6 +2172,41 @@ function run(Baz $baz): void {
         );
     }
 
+    #[test]
+    fn test_find_member_references_through_nullsafe_access() {
+        let code = r#"<?php
+namespace App;
+
+class Baz {
+    public ?Baz $next = null;
+    public function test(): string { return 'ok'; }
+}
+
+function run(?Baz $baz): void {
+    echo $baz?->next?->test();
+    echo $baz?->next->next;
+}
+"#;
+
+        let property_refs = find_refs(code, "App\\Baz::$next", PhpSymbolKind::Property);
+        assert_eq!(
+            property_refs.len(),
+            4,
+            "declaration + three nullsafe usages"
+        );
+        let method_refs = find_refs(code, "App\\Baz::test", PhpSymbolKind::Method);
+        assert_eq!(method_refs.len(), 2, "declaration + one nullsafe call");
+        assert!(
+            !collect_refs(code).iter().any(|reference| {
+                // Lines 9-10 hold the nullsafe chains inside `run()`.
+                reference.target_kind == PhpSymbolKind::GlobalConstant
+                    && (9..=10).contains(&reference.range.0)
+                    && matches!(reference.target_fqn.as_str(), "App\\next" | "App\\test")
+            }),
+            "nullsafe member names must not be collected as constant references"
+        );
+    }
+
     #[test]
     fn test_find_class_constant_references() {
         let code = r#"<?php
@@ -1867,4 +2466,44 @@ echo $x;
         // only inner assignment + inner usage
         assert_eq!(refs.len(), 2);
     }
+
+    #[test]
+    fn test_find_variable_references_follow_global_imports() {
+        let code = r#"<?php
+$db = connect();
+function query(): void {
+    global $db;
+    $db->query();
+}
+function local(): void {
+    $db = 'local';
+}
+function cached(): int {
+    static $hits = 0;
+    return ++$hits;
+}
+echo $db;
+"#;
+        let lines = |refs: Vec<ReferenceLocation>| -> Vec<u32> {
+            refs.into_iter().map(|r| r.range.0).collect()
+        };
+
+        let (line, col) = find_line_col(code, "$db->query");
+        assert_eq!(
+            lines(find_var_refs_at(code, line, col + 1, true)),
+            vec![1, 3, 4, 13]
+        );
+        let (line, col) = find_line_col(code, "echo $db");
+        assert_eq!(
+            lines(find_var_refs_at(code, line, col + 6, false)),
+            vec![4, 13],
+            "the `global` statement is a declaration"
+        );
+
+        let (line, col) = find_line_col(code, "++$hits");
+        assert_eq!(
+            lines(find_var_refs_at(code, line, col + 3, false)),
+            vec![11]
+        );
+    }
 }
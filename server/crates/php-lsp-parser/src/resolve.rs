@@ -4,9 +4,10 @@
 //! position and resolves it to an identifier name, considering namespace context
 //! and use statements.
 
-use crate::cst::{argument_index, argument_name, is_by_ref_output_argument_variable};
-use crate::phpdoc::{parse_phpdoc, strip_exact_tag};
-use crate::utf16::utf16_col_to_byte;
+use crate::cst::{
+    argument_index, argument_name, is_by_ref_output_argument_variable, is_trait_adaptation_method,
+};
+use crate::phpdoc::{parse_phpdoc, var_tag};
 use php_lsp_types::{
     normalize_shape_key_text, FileSymbols, Signature, SymbolInfo, TypeInfo, UseKind,
 };
@@ -246,6 +247,17 @@ pub fn symbol_at_position_with_full_resolvers(
 
     // Find the most specific node at the position
     let node = find_node_at_point(root, point)?;
+    if matches!(node.kind(), "string_content" | "escape_sequence") {
+        return resolve_callable_string(
+            node,
+            point,
+            source,
+            file_symbols,
+            resolver,
+            callable_resolver,
+            function_resolver,
+        );
+    }
 
     resolve_node(
         node,
@@ -293,7 +305,10 @@ pub fn variable_definition_at_position(
 /// Collect local variables declared before a position in the current scope.
 ///
 /// This supports the same declaration forms as local goto-definition, including
-/// by-reference output arguments such as `preg_match(..., $matches)`.
+/// by-reference output arguments such as `preg_match(..., $matches)`, plus
+/// closure `use` variables, destructuring, and `global`/`static` statements.
+/// Nested functions and classes are skipped, arrow functions also see the
+/// enclosing scope, and `$this` comes first inside non-static methods.
 pub fn local_variable_names_at_position(
     tree: &Tree,
     source: &str,
@@ -301,13 +316,34 @@ pub fn local_variable_names_at_position(
     character: u32,
 ) -> Vec<String> {
     let root = tree.root_node();
-    let point = Point::new(line as usize, character as usize);
+    // Look at the character before the cursor: the end of a half-typed
+    // `$name` would otherwise resolve to whatever follows it.
+    let point = Point::new(line as usize, character.saturating_sub(1) as usize);
     let node = find_node_at_point(root, point).unwrap_or(root);
-    let usage_start = position_to_byte(source, line, character);
-    let scope = find_enclosing_function(node).unwrap_or(root);
+    let mut usage_start = position_to_byte(source, line, character);
+    let mut scope = find_enclosing_function(node);
 
     let mut vars = Vec::new();
-    collect_variable_declarations_before(scope, usage_start, source, &mut vars);
+    if this_is_available(scope) {
+        vars.push((0, "$this".to_string()));
+    }
+    loop {
+        let scope_node = scope.unwrap_or(root);
+        collect_variable_declarations_before(
+            scope_node,
+            scope_node,
+            usage_start,
+            source,
+            &mut vars,
+        );
+        match scope {
+            Some(arrow) if arrow.kind() == "arrow_function" => {
+                usage_start = arrow.start_byte();
+                scope = find_enclosing_function(arrow);
+            }
+            _ => break,
+        }
+    }
 
     let mut seen = HashSet::new();
     vars.into_iter()
@@ -315,6 +351,26 @@ pub fn local_variable_names_at_position(
         .collect()
 }
 
+/// Whether `$this` is bound in `scope`: a non-static method, or a non-static
+/// closure nested in one.
+fn this_is_available(mut scope: Option<Node>) -> bool {
+    while let Some(function) = scope {
+        let mut cursor = function.walk();
+        if function
+            .children(&mut cursor)
+            .any(|child| child.kind() == "static_modifier")
+        {
+            return false;
+        }
+        match function.kind() {
+            "method_declaration" => return true,
+            "function_definition" => return false,
+            _ => scope = find_enclosing_function(function),
+        }
+    }
+    false
+}
+
 /// Infer variable type by name before a given position.
 ///
 /// This is used by completion to resolve `$var->...` when cursor is at `...`.
@@ -481,7 +537,9 @@ fn infer_variable_type_at_position_internal(
     let point = Point::new(line as usize, character as usize);
     let node = find_node_at_point(root, point).unwrap_or(root);
     let usage_start = position_to_byte(source, line, character);
-    let scope = find_enclosing_function(node).unwrap_or_else(|| find_root_node(node));
+    let scope = arrow_function_ending_at(root, line, character, usage_start)
+        .or_else(|| find_enclosing_function(node))
+        .unwrap_or_else(|| find_root_node(node));
     infer_textual_expression_type_info(
         scope,
         var_name,
@@ -790,6 +848,7 @@ fn is_inside_class_reference_context(start: Node) -> bool {
                     | "class_constant_access_expression"
                     | "scoped_call_expression"
                     | "scoped_property_access_expression"
+                    | "attribute"
             )
         })
         .unwrap_or(false)
@@ -805,7 +864,316 @@ fn is_inside_object_creation_context(start: Node) -> bool {
         .unwrap_or(false)
 }
 
+/// The class that owns `member_name` on `object`. For a union such as
+/// `$x = $flag ? new Admin() : new Guest()` this is the first member class
+/// known to declare the member, falling back to the first class.
+fn member_owner_fqn(
+    object: Node,
+    member_name: &str,
+    source: &str,
+    file_symbols: &FileSymbols,
+    resolver: Option<MemberTypeResolver<'_>>,
+    callable_resolver: Option<CallableParamTypeResolver<'_>>,
+    function_resolver: Option<FunctionTypeResolver<'_>>,
+) -> Option<String> {
+    let declares_member = |class_fqn: &str| {
+        let member_fqn = format!("{}::{}", class_fqn, member_name);
+        file_symbols
+            .symbols
+            .iter()
+            .any(|symbol| symbol.fqn.eq_ignore_ascii_case(&member_fqn))
+            || resolver.is_some_and(|resolve_fn| resolve_fn(class_fqn, member_name).is_some())
+    };
+
+    let primary = try_resolve_object_type(
+        object,
+        source,
+        file_symbols,
+        resolver,
+        callable_resolver,
+        function_resolver,
+    );
+    if primary.as_deref().is_some_and(declares_member) {
+        return primary;
+    }
+
+    let Some(type_info) = infer_expression_type_info_with_function_resolver(
+        object,
+        source,
+        file_symbols,
+        resolver,
+        callable_resolver,
+        function_resolver,
+    ) else {
+        return primary;
+    };
+    let members = type_check_members(&type_info);
+    if members.len() < 2 {
+        return primary;
+    }
+    members
+        .iter()
+        .filter_map(|member| resolve_phpdoc_var_type(member, object, source, file_symbols))
+        .find(|class_fqn| declares_member(class_fqn))
+        .or(primary)
+}
+
 /// Resolve a CST node to symbol information.
+/// Built-in functions taking a callback, with the callback's argument index.
+const CALLBACK_ARGUMENT_FUNCTIONS: &[(&str, usize)] = &[
+    ("array_filter", 1),
+    ("array_map", 0),
+    ("array_reduce", 1),
+    ("array_walk", 1),
+    ("array_walk_recursive", 1),
+    ("call_user_func", 0),
+    ("call_user_func_array", 0),
+    ("forward_static_call", 0),
+    ("forward_static_call_array", 0),
+    ("is_callable", 0),
+    ("iterator_apply", 1),
+    ("preg_replace_callback", 1),
+    ("register_shutdown_function", 0),
+    ("set_error_handler", 0),
+    ("set_exception_handler", 0),
+    ("spl_autoload_register", 0),
+    ("uasort", 1),
+    ("uksort", 1),
+    ("usort", 1),
+];
+
+/// Resolve a string literal used as a callable argument.
+///
+/// `'App\Mailer::send'` resolves to the class or the method depending on the
+/// cursor, and `[$this, 'handle']`, `['Foo', 'bar']` and `[Foo::class, 'bar']`
+/// resolve their method (and string class) halves. Bare function names such as
+/// `'strlen'` only count in the callback argument of a known built-in, so plain
+/// string arguments don't turn into function references. Strings are never
+/// resolved against `use` imports, matching PHP's runtime behaviour.
+fn resolve_callable_string(
+    node: Node,
+    point: Point,
+    source: &str,
+    file_symbols: &FileSymbols,
+    resolver: Option<MemberTypeResolver<'_>>,
+    callable_resolver: Option<CallableParamTypeResolver<'_>>,
+    function_resolver: Option<FunctionTypeResolver<'_>>,
+) -> Option<SymbolAtPosition> {
+    let string_node = node.parent()?;
+    let (text, start) = string_literal_content(string_node, source)?;
+    if !is_callable_string_text(text) {
+        return None;
+    }
+    let container = string_node.parent()?;
+
+    if container.kind() == "argument" {
+        if let Some((class_name, method_name)) = text.split_once("::") {
+            let class_fqn = if matches!(
+                class_name.to_ascii_lowercase().as_str(),
+                "self" | "static" | "parent"
+            ) {
+                resolve_scope_class_name(class_name, string_node, source, file_symbols)
+            } else {
+                callable_string_class_fqn(class_name)
+            };
+            let offset = point.column.saturating_sub(start.column);
+            if offset < class_name.len() {
+                return Some(SymbolAtPosition {
+                    fqn: class_fqn,
+                    name: class_name.to_string(),
+                    ref_kind: RefKind::ClassName,
+                    object_expr: None,
+                    range: string_content_range(start, 0, class_name.len()),
+                });
+            }
+            return Some(SymbolAtPosition {
+                fqn: format!("{}::{}", class_fqn, method_name),
+                name: method_name.to_string(),
+                ref_kind: RefKind::MethodCall,
+                object_expr: Some(class_name.to_string()),
+                range: string_content_range(start, class_name.len() + 2, text.len()),
+            });
+        }
+        if !is_builtin_callback_argument(container, source) {
+            return None;
+        }
+        return Some(SymbolAtPosition {
+            fqn: callable_string_class_fqn(text),
+            name: text.to_string(),
+            ref_kind: RefKind::FunctionCall,
+            object_expr: None,
+            range: string_content_range(start, 0, text.len()),
+        });
+    }
+
+    // `[$target, 'method']` passed as a call argument.
+    let array = container
+        .parent()
+        .filter(|array| array.kind() == "array_creation_expression")?;
+    if array.parent()?.kind() != "argument" {
+        return None;
+    }
+    let mut cursor = array.walk();
+    let elements: Vec<Node> = array
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "array_element_initializer")
+        .collect();
+    let [target, method] = elements.as_slice() else {
+        return None;
+    };
+    if target.named_child_count() != 1 || method.named_child_count() != 1 {
+        return None;
+    }
+    let target_value = target.named_child(0)?;
+    let method_value = method.named_child(0)?;
+    let (method_name, _) = string_literal_content(method_value, source)
+        .filter(|(name, _)| is_callable_string_text(name) && !name.contains(['\\', ':']))?;
+
+    if container.id() == target.id() {
+        if text.contains("::") {
+            return None;
+        }
+        return Some(SymbolAtPosition {
+            fqn: callable_string_class_fqn(text),
+            name: text.to_string(),
+            ref_kind: RefKind::ClassName,
+            object_expr: None,
+            range: string_content_range(start, 0, text.len()),
+        });
+    }
+    if container.id() != method.id() {
+        return None;
+    }
+
+    let class_fqn = match target_value.kind() {
+        "string" | "encapsed_string" => string_literal_content(target_value, source)
+            .map(|(class_name, _)| callable_string_class_fqn(class_name)),
+        "class_constant_access_expression" => {
+            let mut cursor = target_value.walk();
+            let parts: Vec<Node> = target_value.named_children(&mut cursor).collect();
+            match parts.as_slice() {
+                [scope, constant]
+                    if source[constant.byte_range()].eq_ignore_ascii_case("class") =>
+                {
+                    Some(resolve_scope_class_name(
+                        &source[scope.byte_range()],
+                        target_value,
+                        source,
+                        file_symbols,
+                    ))
+                }
+                _ => None,
+            }
+        }
+        _ => member_owner_fqn(
+            target_value,
+            method_name,
+            source,
+            file_symbols,
+            resolver,
+            callable_resolver,
+            function_resolver,
+        ),
+    };
+    Some(SymbolAtPosition {
+        fqn: class_fqn.map_or_else(
+            || method_name.to_string(),
+            |class_fqn| format!("{}::{}", class_fqn, method_name),
+        ),
+        name: method_name.to_string(),
+        ref_kind: RefKind::MethodCall,
+        object_expr: Some(source[target_value.byte_range()].to_string()),
+        range: string_content_range(start, 0, text.len()),
+    })
+}
+
+/// `name`, `Ns\name` or `Ns\Class::method`, with optional leading or doubled
+/// backslashes.
+fn is_callable_string_text(text: &str) -> bool {
+    let is_identifier = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    let is_qualified_name = |name: &str| {
+        let parts: Vec<&str> = name.split('\\').filter(|part| !part.is_empty()).collect();
+        !parts.is_empty() && parts.into_iter().all(is_identifier)
+    };
+    match text.split_once("::") {
+        Some((class_name, member_name)) => {
+            is_qualified_name(class_name) && is_identifier(member_name)
+        }
+        None => is_qualified_name(text),
+    }
+}
+
+/// Fully qualified name written in a string: `'\\App\\Mailer'` and
+/// `'App\Mailer'` both name `App\Mailer`.
+fn callable_string_class_fqn(name: &str) -> String {
+    name.replace("\\\\", "\\")
+        .trim_start_matches('\\')
+        .to_string()
+}
+
+/// Text between the quotes of a plain string literal and where it starts.
+/// Escape sequences are kept as written; interpolated strings are rejected.
+fn string_literal_content<'a>(string_node: Node, source: &'a str) -> Option<(&'a str, Point)> {
+    if !matches!(string_node.kind(), "string" | "encapsed_string") {
+        return None;
+    }
+    let mut cursor = string_node.walk();
+    let parts: Vec<Node> = string_node.named_children(&mut cursor).collect();
+    if parts
+        .iter()
+        .any(|part| !matches!(part.kind(), "string_content" | "escape_sequence"))
+    {
+        return None;
+    }
+    let (first, last) = (parts.first()?, parts.last()?);
+    Some((
+        &source[first.start_byte()..last.end_byte()],
+        first.start_position(),
+    ))
+}
+
+fn is_builtin_callback_argument(argument: Node, source: &str) -> bool {
+    if argument_name(argument, source).is_some() {
+        return false;
+    }
+    let Some(arguments) = argument.parent() else {
+        return false;
+    };
+    let Some(function) = arguments
+        .parent()
+        .filter(|call| call.kind() == "function_call_expression")
+        .and_then(|call| call.child_by_field_name("function"))
+    else {
+        return false;
+    };
+    let function_text = &source[function.byte_range()];
+    let function_name = function_text
+        .rsplit('\\')
+        .next()
+        .unwrap_or(function_text)
+        .to_ascii_lowercase();
+    let Some(index) = argument_index(arguments, argument) else {
+        return false;
+    };
+    CALLBACK_ARGUMENT_FUNCTIONS
+        .iter()
+        .any(|(name, callback_index)| *name == function_name && *callback_index == index)
+}
+
+fn string_content_range(start: Point, from: usize, to: usize) -> (u32, u32, u32, u32) {
+    (
+        start.row as u32,
+        (start.column + from) as u32,
+        start.row as u32,
+        (start.column + to) as u32,
+    )
+}
+
 fn resolve_node(
     node: Node,
     source: &str,
@@ -818,6 +1186,14 @@ fn resolve_node(
     let node_text = &source[node.byte_range()];
     let parent_kind = parent.kind();
 
+    // Names inside `use` declarations resolve to the imported symbol before
+    // the generic qualified-name arms below get a chance to misread them.
+    if matches!(node.kind(), "name" | "qualified_name" | "namespace_name") {
+        if let Some(symbol) = resolve_use_declaration_name(node, source) {
+            return Some(symbol);
+        }
+    }
+
     match parent_kind {
         // Member access: $obj->method() or $obj->property
         "member_access_expression" | "nullsafe_member_access_expression" => {
@@ -836,8 +1212,9 @@ fn resolve_node(
                     format!("${}", node_text)
                 };
                 let class_fqn = object_field.and_then(|o| {
-                    try_resolve_object_type(
+                    member_owner_fqn(
                         o,
+                        &property_name,
                         source,
                         file_symbols,
                         resolver,
@@ -873,8 +1250,9 @@ fn resolve_node(
                 let object_text = object_field.map(|o| source[o.byte_range()].to_string());
                 // Try to resolve object type to build a proper FQN
                 let class_fqn = object_field.and_then(|o| {
-                    try_resolve_object_type(
+                    member_owner_fqn(
                         o,
+                        node_text,
                         source,
                         file_symbols,
                         resolver,
@@ -1092,7 +1470,11 @@ fn resolve_node(
                         format!("{}::{}", scope_fqn, node_text)
                     },
                     name: node_text.to_string(),
-                    ref_kind: RefKind::ClassConstant,
+                    ref_kind: if is_trait_adaptation_method(parent) {
+                        RefKind::MethodCall
+                    } else {
+                        RefKind::ClassConstant
+                    },
                     object_expr: scope_text,
                     range: node_range(node),
                 });
@@ -1114,7 +1496,7 @@ fn resolve_node(
 
         // Object creation expression: new ClassName()
         "object_creation_expression" => {
-            let resolved = resolve_class_name(node_text, file_symbols);
+            let resolved = resolve_scope_class_name(node_text, node, source, file_symbols);
             Some(SymbolAtPosition {
                 fqn: format!("{}::__construct", resolved),
                 name: node_text.to_string(),
@@ -1200,6 +1582,20 @@ fn resolve_node(
             None
         }
 
+        // Property declaration: `public array $items = [];`
+        "property_element" if node.kind() == "variable_name" => {
+            let fqn = find_parent_class_fqn(parent, source, file_symbols)
+                .map(|cls| format!("{}::{}", cls, node_text))
+                .unwrap_or_else(|| node_text.to_string());
+            Some(SymbolAtPosition {
+                fqn,
+                name: node_text.to_string(),
+                ref_kind: RefKind::PropertyAccess,
+                object_expr: None,
+                range: node_range(node),
+            })
+        }
+
         // Variable
         _ if node.kind() == "variable_name"
             || (node.kind() == "name" && node_text.starts_with('$')) =>
@@ -1214,19 +1610,7 @@ fn resolve_node(
         }
 
         // Qualified name used as type or reference
-        // Check if this is inside a use statement (qualified_name → namespace_use_clause)
         _ if node.kind() == "qualified_name" || node.kind() == "name" => {
-            if is_inside_use_clause(node, parent) {
-                // Extract the full FQN from the qualified_name or namespace_use_clause
-                let fqn = extract_use_clause_fqn(node, parent, source);
-                return Some(SymbolAtPosition {
-                    fqn: fqn.trim_start_matches('\\').to_string(),
-                    name: node_text.to_string(),
-                    ref_kind: RefKind::ClassName,
-                    object_expr: None,
-                    range: node_range(node),
-                });
-            }
             resolve_name_node(node, source, file_symbols)
         }
 
@@ -1297,7 +1681,12 @@ fn try_resolve_object_type_inner<'a>(
                     match child.kind() {
                         "name" | "qualified_name" => {
                             let class_name = &source[child.byte_range()];
-                            return Some(resolve_class_name(class_name, file_symbols));
+                            return Some(resolve_scope_class_name(
+                                class_name,
+                                object_node,
+                                source,
+                                file_symbols,
+                            ));
                         }
                         _ => {}
                     }
@@ -1532,11 +1921,52 @@ fn try_resolve_object_type_inner<'a>(
             let value_type = iterable_value_type_info(&base_type, key_text.as_deref())?;
             resolve_phpdoc_var_type(&value_type, object_node, source, file_symbols)
         }
-        // Static call: Foo::create() — can't resolve return type without full type info
+        // Static call: Foo::create(), static::create(), parent::create()
+        "scoped_call_expression" => {
+            let type_info = infer_scoped_call_expression_type_info(
+                object_node,
+                source,
+                file_symbols,
+                resolver,
+            )?;
+            object_fqn_from_resolved_member_type_info(&type_info, object_node, source, file_symbols)
+        }
+        // Enum case: Suit::Hearts
+        "class_constant_access_expression" => {
+            enum_case_access_class_fqn(object_node, source, file_symbols, resolver)
+        }
         _ => None,
     }
 }
 
+/// Class of `Suit::Hearts` when the accessed constant is an enum case, which
+/// evaluates to an instance of the enum itself.
+fn enum_case_access_class_fqn(
+    node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+    resolver: Option<MemberTypeResolver<'_>>,
+) -> Option<String> {
+    let scope = node.named_child(0)?;
+    let name = node.named_child(1)?;
+    let case_name = &source[name.byte_range()];
+    if case_name.eq_ignore_ascii_case("class") {
+        return None;
+    }
+    let class_fqn =
+        resolve_scope_class_name(&source[scope.byte_range()], node, source, file_symbols);
+    let case_fqn = format!("{}::{}", class_fqn.trim_start_matches('\\'), case_name);
+    if file_symbols
+        .symbols
+        .iter()
+        .any(|sym| sym.kind == php_lsp_types::PhpSymbolKind::EnumCase && sym.fqn == case_fqn)
+    {
+        return Some(class_fqn);
+    }
+    let type_text = resolver?(class_fqn.trim_start_matches('\\'), case_name)?;
+    resolve_object_fqn_from_member_type_text(&type_text, node, source, file_symbols)
+}
+
 fn resolve_object_fqn_from_member_type_text(
     type_text: &str,
     context_node: Node,
@@ -1693,6 +2123,26 @@ fn find_enclosing_function(node: Node) -> Option<Node> {
     None
 }
 
+/// An arrow function whose body ends right at the cursor, as in
+/// `array_filter($users, fn ($user) => $user->|)`. Arrow functions have no
+/// closing delimiter, so the node at the cursor belongs to the outer scope.
+fn arrow_function_ending_at(
+    root: Node,
+    line: u32,
+    character: u32,
+    usage_start: usize,
+) -> Option<Node> {
+    let before = Point::new(line as usize, character.checked_sub(1)? as usize);
+    let mut current = find_node_at_point(root, before);
+    while let Some(node) = current {
+        if node.kind() == "arrow_function" {
+            return (node.end_byte() == usage_start).then_some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
 /// Find the enclosing class/interface/trait declaration node.
 fn find_enclosing_class_node(node: Node) -> Option<Node> {
     let mut current = node.parent();
@@ -1786,10 +2236,14 @@ fn resolved_fqn_type_info(resolved: &str) -> TypeInfo {
 }
 
 /// Scan a compound_statement for `$var = new ClassName()` before the usage point.
+/// `declared` is the variable's type on entry to `body`, e.g. its parameter
+/// type, which guards such as `if ($v === null) { return; }` narrow.
+#[allow(clippy::too_many_arguments)]
 fn find_variable_inference_before_usage(
     body: Node,
     var_name: &str,
     usage_start: usize,
+    declared: Option<&TypeInfo>,
     source: &str,
     file_symbols: &FileSymbols,
     resolver: Option<MemberTypeResolver<'_>>,
@@ -1825,8 +2279,11 @@ fn find_variable_inference_before_usage(
             continue;
         }
 
+        let previous = inferred
+            .as_ref()
+            .map_or(declared, |(_, info)| info.type_info.as_ref());
         if let Some(guard_info) =
-            instanceof_guard_inference(stmt, var_name, usage_start, source, file_symbols)
+            type_guard_inference(stmt, var_name, usage_start, previous, source, file_symbols)
         {
             inferred = Some((stmt.start_byte(), guard_info));
             continue;
@@ -1835,6 +2292,9 @@ fn find_variable_inference_before_usage(
         if let Some(array_write_info) = array_write_inference_for_var(
             stmt,
             var_name,
+            inferred
+                .as_ref()
+                .and_then(|(_, info)| info.type_info.as_ref()),
             source,
             file_symbols,
             resolver,
@@ -1883,7 +2343,14 @@ fn find_variable_inference_before_usage(
                 callable_resolver,
                 None,
             ) {
-                let type_info = Some(resolved_fqn_type_info(&resolved));
+                let type_info = Some(assigned_object_type_info(
+                    right,
+                    &resolved,
+                    source,
+                    file_symbols,
+                    resolver,
+                    callable_resolver,
+                ));
                 inferred = Some((
                     stmt.start_byte(),
                     VariableInference {
@@ -1901,7 +2368,10 @@ fn find_variable_inference_before_usage(
                 inferred = Some((
                     stmt.start_byte(),
                     VariableInference {
-                        type_display: Some(type_info.to_string()),
+                        type_display: Some(short_type_display(
+                            &type_info.to_string(),
+                            file_symbols,
+                        )),
                         resolved_type_fqn,
                         phpdoc_comment: None,
                         type_info: Some(type_info),
@@ -1934,10 +2404,14 @@ fn find_variable_inference_before_usage(
         // containing branch/block. This finds assignments in the active nested
         // scope without borrowing types from sibling branches.
         if stmt.end_byte() > usage_start {
+            let previous = inferred
+                .as_ref()
+                .map_or(declared, |(_, info)| info.type_info.as_ref());
             if let Some(stmt_info) = find_variable_inference_before_usage(
                 stmt,
                 var_name,
                 usage_start,
+                previous,
                 source,
                 file_symbols,
                 resolver,
@@ -1952,6 +2426,34 @@ fn find_variable_inference_before_usage(
     inferred.map(|(_, info)| info)
 }
 
+/// Type of an object-valued assignment, keeping generic arguments such as
+/// `Collection<int, User>` from a call's return type so template-typed
+/// members of the variable still bind.
+fn assigned_object_type_info(
+    right: Node,
+    resolved: &str,
+    source: &str,
+    file_symbols: &FileSymbols,
+    resolver: Option<MemberTypeResolver<'_>>,
+    callable_resolver: Option<CallableParamTypeResolver<'_>>,
+) -> TypeInfo {
+    if matches!(
+        right.kind(),
+        "member_call_expression"
+            | "nullsafe_member_call_expression"
+            | "function_call_expression"
+            | "scoped_call_expression"
+    ) {
+        if let Some(type_info) =
+            infer_expression_type_info(right, source, file_symbols, resolver, callable_resolver)
+                .filter(|type_info| type_info_has_generic_base_fqn(type_info, resolved))
+        {
+            return type_info;
+        }
+    }
+    resolved_fqn_type_info(resolved)
+}
+
 fn find_nested_variable_inference_before_usage(
     node: Node,
     var_name: &str,
@@ -1989,6 +2491,9 @@ fn find_nested_variable_inference_before_usage(
         } else if let Some(array_write_info) = array_write_inference_for_var(
             child,
             var_name,
+            inferred
+                .as_ref()
+                .and_then(|(_, info)| info.type_info.as_ref()),
             source,
             file_symbols,
             resolver,
@@ -2026,7 +2531,14 @@ fn find_nested_variable_inference_before_usage(
                         type_display: Some(resolved.clone()),
                         resolved_type_fqn: Some(resolved.clone()),
                         phpdoc_comment: None,
-                        type_info: Some(resolved_fqn_type_info(&resolved)),
+                        type_info: Some(assigned_object_type_info(
+                            right,
+                            &resolved,
+                            source,
+                            file_symbols,
+                            resolver,
+                            callable_resolver,
+                        )),
                     },
                 ));
             } else if let Some(type_info) =
@@ -2079,44 +2591,78 @@ fn is_variable_inference_scope_boundary(node: Node) -> bool {
     )
 }
 
-fn instanceof_guard_inference(
+/// A runtime type check on a variable, as written in an `if` condition.
+#[derive(Debug, Clone)]
+enum VariableTypeCheck {
+    /// `$var instanceof Foo`, with the class as written and resolved.
+    Instanceof {
+        class_name: String,
+        resolved: String,
+    },
+    /// `is_string($var)` and friends, keyed by the canonical function name.
+    Function(&'static str),
+    /// `$var === null` / `null === $var`.
+    Null,
+}
+
+/// Narrow a variable from `instanceof`, `is_*()` and `=== null` checks:
+/// inside a positive `if`/`elseif` branch containing the usage, or after an
+/// early-return guard such as `if (!is_string($v)) { return; }` or
+/// `if ($v === null) { return; }` which removes a member from `previous`.
+fn type_guard_inference(
     stmt: Node,
     var_name: &str,
     usage_start: usize,
+    previous: Option<&TypeInfo>,
     source: &str,
     file_symbols: &FileSymbols,
 ) -> Option<VariableInference> {
     if stmt.kind() != "if_statement" || stmt.end_byte() > usage_start {
-        return positive_instanceof_branch_inference(
+        return positive_type_check_branch_inference(
             stmt,
             var_name,
             usage_start,
+            previous,
             source,
             file_symbols,
         );
     }
 
-    let condition = if_condition_text(stmt, source)?;
-    if !negative_instanceof_guard_for_var(condition, var_name)
-        || !if_then_branch_exits(stmt, source)
-    {
+    if !if_then_branch_exits(stmt, source) {
         return None;
     }
+    let condition = if_condition_node(stmt)?;
+    if let Some(check) = negated_type_check_for_var(condition, source, var_name, file_symbols) {
+        return Some(type_check_variable_inference(
+            &check,
+            checked_type_info(&check, previous, file_symbols),
+            stmt,
+            source,
+            file_symbols,
+        ));
+    }
 
-    let class_name = class_name_after_instanceof(condition)?;
-    let resolved = resolve_class_name(&class_name, file_symbols);
-    Some(VariableInference {
-        type_display: Some(class_name),
-        resolved_type_fqn: Some(resolved.clone()),
-        phpdoc_comment: None,
-        type_info: Some(resolved_fqn_type_info(&resolved)),
-    })
+    let check = positive_type_check_expression_for_var(
+        unwrap_parenthesized_condition(condition),
+        source,
+        var_name,
+        file_symbols,
+    )?;
+    let type_info = excluded_type_info(&check, previous?, file_symbols)?;
+    Some(type_check_variable_inference(
+        &check,
+        type_info,
+        stmt,
+        source,
+        file_symbols,
+    ))
 }
 
-fn positive_instanceof_branch_inference(
+fn positive_type_check_branch_inference(
     stmt: Node,
     var_name: &str,
     usage_start: usize,
+    previous: Option<&TypeInfo>,
     source: &str,
     file_symbols: &FileSymbols,
 ) -> Option<VariableInference> {
@@ -2124,16 +2670,16 @@ fn positive_instanceof_branch_inference(
         if let Some(body) = stmt.child_by_field_name("body") {
             if usage_start >= body.start_byte() && usage_start <= body.end_byte() {
                 let condition = if_condition_node(stmt)?;
-                if let Some(class_name) =
-                    positive_instanceof_class_for_var(condition, source, var_name)
+                if let Some(check) =
+                    positive_type_check_for_var(condition, source, var_name, file_symbols)
                 {
-                    let resolved = resolve_class_name(&class_name, file_symbols);
-                    return Some(VariableInference {
-                        type_display: Some(class_name),
-                        resolved_type_fqn: Some(resolved.clone()),
-                        phpdoc_comment: None,
-                        type_info: Some(resolved_fqn_type_info(&resolved)),
-                    });
+                    return Some(type_check_variable_inference(
+                        &check,
+                        checked_type_info(&check, previous, file_symbols),
+                        stmt,
+                        source,
+                        file_symbols,
+                    ));
                 }
             }
         }
@@ -2142,10 +2688,11 @@ fn positive_instanceof_branch_inference(
     let mut cursor = stmt.walk();
     for child in stmt.named_children(&mut cursor) {
         if usage_start >= child.start_byte() && usage_start <= child.end_byte() {
-            return positive_instanceof_branch_inference(
+            return positive_type_check_branch_inference(
                 child,
                 var_name,
                 usage_start,
+                previous,
                 source,
                 file_symbols,
             );
@@ -2155,6 +2702,73 @@ fn positive_instanceof_branch_inference(
     None
 }
 
+fn type_check_variable_inference(
+    check: &VariableTypeCheck,
+    type_info: TypeInfo,
+    context_node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> VariableInference {
+    if let VariableTypeCheck::Instanceof {
+        class_name,
+        resolved,
+    } = check
+    {
+        if type_info == resolved_fqn_type_info(resolved) {
+            return VariableInference {
+                type_display: Some(class_name.clone()),
+                resolved_type_fqn: Some(resolved.clone()),
+                phpdoc_comment: None,
+                type_info: Some(type_info),
+            };
+        }
+    }
+
+    VariableInference {
+        type_display: Some(short_type_display(&type_info.to_string(), file_symbols)),
+        resolved_type_fqn: resolve_phpdoc_var_type(&type_info, context_node, source, file_symbols),
+        phpdoc_comment: None,
+        type_info: Some(type_info),
+    }
+}
+
+/// Display a resolved type the way the file would write it, e.g.
+/// `list<\App\User>|null` as `list<User>|null` inside `namespace App`.
+fn short_type_display(display: &str, file_symbols: &FileSymbols) -> String {
+    let mut shortened = String::new();
+    let mut name = String::new();
+    for ch in display.chars().chain(std::iter::once(' ')) {
+        if ch.is_alphanumeric() || ch == '_' || ch == '\\' {
+            name.push(ch);
+            continue;
+        }
+        if let Some(fqn) = name.strip_prefix('\\') {
+            let alias =
+                file_symbols.use_statements.iter().find_map(|use_stmt| {
+                    (use_stmt.kind == UseKind::Class && use_stmt.fqn.eq_ignore_ascii_case(fqn))
+                        .then(|| {
+                            use_stmt.alias.clone().unwrap_or_else(|| {
+                                fqn.rsplit('\\').next().unwrap_or(fqn).to_string()
+                            })
+                        })
+                });
+            let relative = file_symbols.namespace.as_deref().and_then(|namespace| {
+                fqn.strip_prefix(namespace)
+                    .and_then(|rest| rest.strip_prefix('\\'))
+                    .filter(|rest| !rest.contains('\\'))
+                    .map(str::to_string)
+            });
+            shortened.push_str(&alias.or(relative).unwrap_or_else(|| name.clone()));
+        } else {
+            shortened.push_str(&name);
+        }
+        name.clear();
+        shortened.push(ch);
+    }
+    shortened.pop();
+    shortened
+}
+
 fn if_condition_node(stmt: Node) -> Option<Node> {
     if let Some(condition) = stmt.child_by_field_name("condition") {
         return Some(condition);
@@ -2171,36 +2785,14 @@ fn if_condition_node(stmt: Node) -> Option<Node> {
         .max_by_key(|child| child.start_byte())
 }
 
-fn if_condition_text<'a>(stmt: Node, source: &'a str) -> Option<&'a str> {
-    let text = &source[stmt.byte_range()];
-    let start = text.find('(')?;
-    let mut depth = 0usize;
-    for (offset, ch) in text[start..].char_indices() {
-        match ch {
-            '(' => depth += 1,
-            ')' => {
-                depth = depth.saturating_sub(1);
-                if depth == 0 {
-                    return Some(&text[start + 1..start + offset]);
-                }
-            }
-            _ => {}
-        }
-    }
-    None
-}
-
-fn negative_instanceof_guard_for_var(condition: &str, var_name: &str) -> bool {
-    let compact: String = condition.chars().filter(|ch| !ch.is_whitespace()).collect();
-    compact.starts_with(&format!("!{}instanceof", var_name))
-        || compact.starts_with(&format!("!({}instanceof", var_name))
-}
-
-fn positive_instanceof_class_for_var(
+/// A check that holds whenever `condition` is true; `&&` conjunctions are
+/// searched operand by operand.
+fn positive_type_check_for_var(
     condition: Node,
     source: &str,
     var_name: &str,
-) -> Option<String> {
+    file_symbols: &FileSymbols,
+) -> Option<VariableTypeCheck> {
     let condition = unwrap_parenthesized_condition(condition);
     if condition.kind() == "binary_expression"
         && matches!(
@@ -2214,11 +2806,54 @@ fn positive_instanceof_class_for_var(
         let right = condition
             .child_by_field_name("right")
             .or_else(|| condition.named_child(1))?;
-        return positive_instanceof_class_for_var(left, source, var_name)
-            .or_else(|| positive_instanceof_class_for_var(right, source, var_name));
+        return positive_type_check_for_var(left, source, var_name, file_symbols)
+            .or_else(|| positive_type_check_for_var(right, source, var_name, file_symbols));
     }
 
-    positive_instanceof_expression_class_for_var(condition, source, var_name)
+    positive_type_check_expression_for_var(condition, source, var_name, file_symbols)
+}
+
+/// A check that holds whenever `condition` is false: `!check`, `$v !== null`,
+/// or any such operand of an `||` disjunction.
+fn negated_type_check_for_var(
+    condition: Node,
+    source: &str,
+    var_name: &str,
+    file_symbols: &FileSymbols,
+) -> Option<VariableTypeCheck> {
+    let condition = unwrap_parenthesized_condition(condition);
+    match condition.kind() {
+        "binary_expression" => match binary_operator_text(condition, source)? {
+            "||" | "or" => {
+                let left = condition
+                    .child_by_field_name("left")
+                    .or_else(|| condition.named_child(0))?;
+                let right = condition
+                    .child_by_field_name("right")
+                    .or_else(|| condition.named_child(1))?;
+                negated_type_check_for_var(left, source, var_name, file_symbols)
+                    .or_else(|| negated_type_check_for_var(right, source, var_name, file_symbols))
+            }
+            "!==" | "!=" => null_comparison_for_var(condition, source, var_name)
+                .then_some(VariableTypeCheck::Null),
+            _ => None,
+        },
+        "unary_op_expression" => {
+            if !source[condition.byte_range()].trim_start().starts_with('!') {
+                return None;
+            }
+            let argument = condition
+                .child_by_field_name("argument")
+                .or_else(|| condition.named_child(0))?;
+            positive_type_check_expression_for_var(
+                unwrap_parenthesized_condition(argument),
+                source,
+                var_name,
+                file_symbols,
+            )
+        }
+        _ => None,
+    }
 }
 
 fn unwrap_parenthesized_condition(mut node: Node) -> Node {
@@ -2232,16 +2867,27 @@ fn unwrap_parenthesized_condition(mut node: Node) -> Node {
     node
 }
 
-fn positive_instanceof_expression_class_for_var(
+fn positive_type_check_expression_for_var(
     node: Node,
     source: &str,
     var_name: &str,
-) -> Option<String> {
+    file_symbols: &FileSymbols,
+) -> Option<VariableTypeCheck> {
     match node.kind() {
         "instanceof_expression" => {}
-        "binary_expression"
-            if binary_operator_text(node, source)
-                .is_some_and(|operator| operator.eq_ignore_ascii_case("instanceof")) => {}
+        "binary_expression" => {
+            let operator = binary_operator_text(node, source)?;
+            if matches!(operator, "===" | "==") {
+                return null_comparison_for_var(node, source, var_name)
+                    .then_some(VariableTypeCheck::Null);
+            }
+            if !operator.eq_ignore_ascii_case("instanceof") {
+                return None;
+            }
+        }
+        "function_call_expression" => {
+            return type_check_function_call_for_var(node, source, var_name)
+        }
         _ => return None,
     }
 
@@ -2256,7 +2902,196 @@ fn positive_instanceof_expression_class_for_var(
         .child_by_field_name("right")
         .or_else(|| node.named_child(1))?;
     let class_name = source.get(right.byte_range())?.trim();
-    (!class_name.is_empty()).then(|| class_name.to_string())
+    if class_name.is_empty() || class_name.starts_with('$') {
+        return None;
+    }
+    Some(VariableTypeCheck::Instanceof {
+        class_name: class_name.to_string(),
+        resolved: resolve_class_name(class_name, file_symbols),
+    })
+}
+
+fn null_comparison_for_var(node: Node, source: &str, var_name: &str) -> bool {
+    let (Some(left), Some(right)) = (
+        node.child_by_field_name("left")
+            .or_else(|| node.named_child(0)),
+        node.child_by_field_name("right")
+            .or_else(|| node.named_child(1)),
+    ) else {
+        return false;
+    };
+    let left = source[left.byte_range()].trim();
+    let right = source[right.byte_range()].trim();
+    (left == var_name && right.eq_ignore_ascii_case("null"))
+        || (right == var_name && left.eq_ignore_ascii_case("null"))
+}
+
+fn type_check_function_call_for_var(
+    node: Node,
+    source: &str,
+    var_name: &str,
+) -> Option<VariableTypeCheck> {
+    let function = node.child_by_field_name("function")?;
+    let name = source[function.byte_range()]
+        .trim()
+        .trim_start_matches('\\')
+        .to_ascii_lowercase();
+    let canonical = match name.as_str() {
+        "is_string" => "is_string",
+        "is_int" | "is_integer" | "is_long" => "is_int",
+        "is_float" | "is_double" => "is_float",
+        "is_bool" => "is_bool",
+        "is_array" => "is_array",
+        "is_object" => "is_object",
+        "is_null" => "is_null",
+        "is_callable" => "is_callable",
+        "is_iterable" => "is_iterable",
+        _ => return None,
+    };
+    let arguments = call_arguments(node, source);
+    let [argument] = arguments.as_slice() else {
+        return None;
+    };
+    (source[argument.value_node.byte_range()].trim() == var_name)
+        .then_some(VariableTypeCheck::Function(canonical))
+}
+
+fn type_check_members(type_info: &TypeInfo) -> Vec<TypeInfo> {
+    match type_info {
+        TypeInfo::Union(types) => types.iter().flat_map(type_check_members).collect(),
+        TypeInfo::Nullable(inner) => {
+            let mut members = type_check_members(inner);
+            members.push(TypeInfo::LiteralNull);
+            members
+        }
+        other => vec![other.clone()],
+    }
+}
+
+fn type_info_from_members(mut members: Vec<TypeInfo>) -> TypeInfo {
+    if members.len() == 1 {
+        members.remove(0)
+    } else {
+        TypeInfo::Union(members)
+    }
+}
+
+fn type_check_matches(
+    check: &VariableTypeCheck,
+    member: &TypeInfo,
+    file_symbols: &FileSymbols,
+) -> bool {
+    let simple_name = match member {
+        TypeInfo::Simple(name) => Some(name.trim_start_matches('\\').to_ascii_lowercase()),
+        TypeInfo::Generic { base, .. } => Some(base.trim_start_matches('\\').to_ascii_lowercase()),
+        _ => None,
+    };
+    let is_array_like = matches!(member, TypeInfo::ArrayShape(_))
+        || simple_name.as_deref().is_some_and(|name| {
+            matches!(
+                name,
+                "array" | "list" | "non-empty-array" | "non-empty-list"
+            ) || name.ends_with("[]")
+        });
+    let is_null = matches!(member, TypeInfo::LiteralNull) || simple_name.as_deref() == Some("null");
+
+    match check {
+        VariableTypeCheck::Null => is_null,
+        VariableTypeCheck::Instanceof { resolved, .. } => {
+            !is_array_like
+                && simple_name.is_some_and(|name| {
+                    !is_builtin_non_object_type(&name)
+                        && resolve_class_name(&name, file_symbols)
+                            .eq_ignore_ascii_case(resolved.trim_start_matches('\\'))
+                })
+        }
+        VariableTypeCheck::Function(function) => match *function {
+            "is_null" => is_null,
+            "is_array" => is_array_like,
+            "is_iterable" => is_array_like || simple_name.as_deref() == Some("iterable"),
+            "is_string" => {
+                matches!(
+                    member,
+                    TypeInfo::LiteralString(_) | TypeInfo::ClassString(_)
+                ) || simple_name
+                    .as_deref()
+                    .is_some_and(|name| name == "string" || name.ends_with("-string"))
+            }
+            "is_int" => {
+                matches!(member, TypeInfo::LiteralInt(_))
+                    || simple_name
+                        .as_deref()
+                        .is_some_and(|name| name == "int" || name.ends_with("-int"))
+            }
+            "is_float" => {
+                matches!(member, TypeInfo::LiteralFloat(_))
+                    || matches!(simple_name.as_deref(), Some("float" | "double"))
+            }
+            "is_bool" => {
+                matches!(member, TypeInfo::LiteralBool(_))
+                    || matches!(
+                        simple_name.as_deref(),
+                        Some("bool" | "boolean" | "true" | "false")
+                    )
+            }
+            "is_callable" => {
+                matches!(member, TypeInfo::Callable { .. })
+                    || matches!(simple_name.as_deref(), Some("callable" | "closure"))
+            }
+            "is_object" => {
+                matches!(
+                    member,
+                    TypeInfo::ObjectShape(_) | TypeInfo::Self_ | TypeInfo::Static_
+                ) || simple_name.as_deref().is_some_and(|name| {
+                    name == "object" || (!is_array_like && !is_builtin_non_object_type(name))
+                })
+            }
+            _ => false,
+        },
+    }
+}
+
+/// The variable's type where `check` holds: the matching members of
+/// `previous` when it names any, otherwise the checked type itself.
+fn checked_type_info(
+    check: &VariableTypeCheck,
+    previous: Option<&TypeInfo>,
+    file_symbols: &FileSymbols,
+) -> TypeInfo {
+    let matching: Vec<TypeInfo> = previous
+        .map(type_check_members)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|member| type_check_matches(check, member, file_symbols))
+        .collect();
+    if !matching.is_empty() {
+        return type_info_from_members(matching);
+    }
+
+    match check {
+        VariableTypeCheck::Instanceof { resolved, .. } => resolved_fqn_type_info(resolved),
+        VariableTypeCheck::Null | VariableTypeCheck::Function("is_null") => TypeInfo::LiteralNull,
+        VariableTypeCheck::Function(function) => {
+            TypeInfo::Simple(function.trim_start_matches("is_").to_string())
+        }
+    }
+}
+
+/// The variable's type where `check` fails, when that removes some but not
+/// all members of a union or nullable `previous`.
+fn excluded_type_info(
+    check: &VariableTypeCheck,
+    previous: &TypeInfo,
+    file_symbols: &FileSymbols,
+) -> Option<TypeInfo> {
+    let members = type_check_members(previous);
+    let remaining: Vec<TypeInfo> = members
+        .iter()
+        .filter(|member| !type_check_matches(check, member, file_symbols))
+        .cloned()
+        .collect();
+    (!remaining.is_empty() && remaining.len() < members.len())
+        .then(|| type_info_from_members(remaining))
 }
 
 fn binary_operator_text<'a>(node: Node, source: &'a str) -> Option<&'a str> {
@@ -2283,16 +3118,6 @@ fn if_then_branch_exits(stmt: Node, source: &str) -> bool {
         || then_text.contains("die(")
 }
 
-fn class_name_after_instanceof(condition: &str) -> Option<String> {
-    let (_, after) = condition.split_once("instanceof")?;
-    let class_name: String = after
-        .trim_start()
-        .chars()
-        .take_while(|ch| ch.is_ascii_alphanumeric() || *ch == '_' || *ch == '\\')
-        .collect();
-    (!class_name.is_empty()).then_some(class_name)
-}
-
 fn assignment_rhs_for_var<'a>(stmt: Node<'a>, var_name: &str, source: &str) -> Option<Node<'a>> {
     if stmt.kind() != "expression_statement" {
         return None;
@@ -2313,9 +3138,13 @@ fn assignment_rhs_for_var<'a>(stmt: Node<'a>, var_name: &str, source: &str) -> O
     }
 }
 
+/// Infer the array type after `$var[key] = value;`. A literal key written to
+/// an array whose literal shape is known extends that shape.
+#[allow(clippy::too_many_arguments)]
 fn array_write_inference_for_var(
     stmt: Node,
     var_name: &str,
+    previous: Option<&TypeInfo>,
     source: &str,
     file_symbols: &FileSymbols,
     resolver: Option<MemberTypeResolver<'_>>,
@@ -2337,9 +3166,6 @@ fn array_write_inference_for_var(
         return None;
     }
 
-    let key_type =
-        infer_array_key_expression_type(key, source, file_symbols, resolver, callable_resolver)
-            .unwrap_or_else(|| TypeInfo::Simple("array-key".to_string()));
     let value_type =
         infer_expression_type_info(right, source, file_symbols, resolver, callable_resolver)
             .unwrap_or_else(|| {
@@ -2352,9 +3178,54 @@ fn array_write_inference_for_var(
                     callable_resolver,
                 )
             });
-    let type_info = TypeInfo::Generic {
-        base: "array".to_string(),
-        args: vec![key_type, value_type],
+    let Some(key) = key else {
+        // `$var[] = ...` only turns a known-empty literal into a list.
+        return matches!(previous, Some(TypeInfo::ArrayShape(items)) if items.is_empty()).then(
+            || {
+                let type_info = TypeInfo::Generic {
+                    base: "list".to_string(),
+                    args: vec![value_type],
+                };
+                VariableInference {
+                    type_display: Some(type_info.to_string()),
+                    resolved_type_fqn: None,
+                    phpdoc_comment: None,
+                    type_info: Some(type_info),
+                }
+            },
+        );
+    };
+    let literal_key = matches!(key.kind(), "string" | "integer")
+        || (key.kind() == "encapsed_string" && !source[key.byte_range()].contains('$'));
+    let type_info = match previous {
+        Some(TypeInfo::ArrayShape(items)) if literal_key => {
+            let key = normalize_array_access_key(&source[key.byte_range()])?;
+            let mut items: Vec<_> = items
+                .iter()
+                .filter(|item| item.key.as_deref() != Some(key.as_str()))
+                .cloned()
+                .collect();
+            items.push(php_lsp_types::ArrayShapeItem {
+                key: Some(key),
+                optional: false,
+                value: value_type,
+            });
+            TypeInfo::ArrayShape(items)
+        }
+        _ => {
+            let key_type = infer_array_key_expression_type(
+                key,
+                source,
+                file_symbols,
+                resolver,
+                callable_resolver,
+            )
+            .unwrap_or_else(|| TypeInfo::Simple("array-key".to_string()));
+            TypeInfo::Generic {
+                base: "array".to_string(),
+                args: vec![key_type, value_type],
+            }
+        }
     };
 
     Some(VariableInference {
@@ -2365,7 +3236,8 @@ fn array_write_inference_for_var(
     })
 }
 
-fn subscript_assignment_base_and_key(left: Node) -> Option<(Node, Node)> {
+/// Split `$var[key]` into its base variable and key; `$var[]` has no key.
+fn subscript_assignment_base_and_key(left: Node) -> Option<(Node, Option<Node>)> {
     if left.kind() != "subscript_expression" {
         return None;
     }
@@ -2374,8 +3246,7 @@ fn subscript_assignment_base_and_key(left: Node) -> Option<(Node, Node)> {
     if base.kind() != "variable_name" {
         return None;
     }
-    let key = left.named_child(1)?;
-    Some((base, key))
+    Some((base, left.named_child(1)))
 }
 
 fn infer_array_key_expression_type(
@@ -2558,6 +3429,7 @@ fn infer_variable_in_scope(
     callable_resolver: Option<CallableParamTypeResolver<'_>>,
 ) -> VariableInference {
     let mut inferred = VariableInference::default();
+    let mut declared = None;
 
     // 1. Check function parameters for typed variables.
     if let Some(params) = scope_node.child_by_field_name("parameters") {
@@ -2570,6 +3442,13 @@ fn infer_variable_in_scope(
                         let param_name = normalize_var_name(&source[name_node.byte_range()]);
                         if param_name == var_name {
                             if let Some(type_node) = param.child_by_field_name("type") {
+                                if matches!(type_node.kind(), "union_type" | "optional_type") {
+                                    declared = declared_param_type_info(
+                                        scope_node,
+                                        var_name,
+                                        file_symbols,
+                                    );
+                                }
                                 inferred.type_display =
                                     Some(source[type_node.byte_range()].trim().to_string());
                                 if let Some(class_name) = extract_type_name(type_node, source) {
@@ -2580,7 +3459,28 @@ fn infer_variable_in_scope(
                                         file_symbols,
                                     );
                                     inferred.resolved_type_fqn = Some(resolved.clone());
-                                    inferred.type_info = Some(resolved_fqn_type_info(&resolved));
+                                    inferred.type_info = declared
+                                        .clone()
+                                        .or_else(|| Some(resolved_fqn_type_info(&resolved)));
+                                }
+                            }
+                            if let Some(doc_info) = phpdoc_param_inference(
+                                scope_node,
+                                param,
+                                var_name,
+                                source,
+                                file_symbols,
+                            ) {
+                                let doc_is_more_specific =
+                                    inferred.type_info.as_ref().is_none_or(|native| {
+                                        doc_info.type_info.as_ref().is_some_and(|doc| {
+                                            parser_type_info_specificity_score(doc)
+                                                > parser_type_info_specificity_score(native)
+                                        })
+                                    });
+                                if doc_is_more_specific {
+                                    declared = None;
+                                    inferred = doc_info;
                                 }
                             }
                             if inferred.type_info.is_none() {
@@ -2611,15 +3511,84 @@ fn infer_variable_in_scope(
         statements,
         var_name,
         usage_start,
+        declared.as_ref().or(inferred.type_info.as_ref()),
         source,
         file_symbols,
-        resolver,
-        callable_resolver,
-    ) {
-        inferred = stmt_info;
-    }
+        resolver,
+        callable_resolver,
+    ) {
+        inferred = stmt_info;
+    } else if inferred.type_info.is_none() {
+        // A plain `A|B` parameter has no single class name to extract.
+        inferred.type_info = declared;
+    }
+
+    inferred
+}
+
+/// Infer a parameter from the `@param` tag on its declaring function, e.g.
+/// `@param list<User> $users` on a native `array $users`.
+fn phpdoc_param_inference(
+    scope_node: Node,
+    param_node: Node,
+    var_name: &str,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> Option<VariableInference> {
+    let comment = crate::symbols::find_doc_comment(scope_node, source)?;
+    let phpdoc = parse_phpdoc(&comment);
+    let param_name = var_name.trim_start_matches('$');
+    let type_info = phpdoc
+        .params
+        .into_iter()
+        .find(|param| param.name.trim_start_matches('$') == param_name)?
+        .type_info?;
+    let type_info = expand_file_type_aliases(&type_info, file_symbols, &mut Vec::new());
+    let resolved_type_fqn = resolve_phpdoc_var_type(&type_info, param_node, source, file_symbols);
+    let type_display = type_info.to_string();
+    let type_info = declaring_function_symbol(scope_node, file_symbols)
+        .map(|symbol| resolve_type_info_relative_to_symbol(&type_info, symbol, file_symbols))
+        .unwrap_or(type_info);
+    Some(VariableInference {
+        type_display: Some(type_display),
+        resolved_type_fqn,
+        phpdoc_comment: None,
+        type_info: Some(type_info),
+    })
+}
+
+/// The full native type of a union or nullable parameter, e.g. `User|string`,
+/// qualified relative to the declaring function.
+fn declared_param_type_info(
+    scope_node: Node,
+    var_name: &str,
+    file_symbols: &FileSymbols,
+) -> Option<TypeInfo> {
+    let symbol = declaring_function_symbol(scope_node, file_symbols)?;
+    let type_info = symbol
+        .signature
+        .as_ref()?
+        .params
+        .iter()
+        .find(|param| normalize_var_name(&param.name) == var_name)?
+        .type_info
+        .as_ref()?;
+    Some(resolve_type_info_relative_to_symbol(
+        type_info,
+        symbol,
+        file_symbols,
+    ))
+}
 
-    inferred
+fn declaring_function_symbol<'a>(
+    scope_node: Node,
+    file_symbols: &'a FileSymbols,
+) -> Option<&'a SymbolInfo> {
+    let start = scope_node.start_position();
+    file_symbols.symbols.iter().find(|symbol| {
+        symbol.signature.is_some()
+            && (symbol.range.0, symbol.range.1) == (start.row as u32, start.column as u32)
+    })
 }
 
 struct CallableArgumentSite {
@@ -2720,6 +3689,9 @@ fn infer_callable_parameter_inference(
             receiver_type: site.receiver_type.as_ref(),
             argument_types: &site.argument_types,
         })
+    })
+    .or_else(|| {
+        builtin_array_callback_param_type(scope_node, &site, parameter_index, source, file_symbols)
     })?;
 
     let resolved_type_fqn = resolve_phpdoc_var_type(&type_info, param_node, source, file_symbols);
@@ -2731,6 +3703,84 @@ fn infer_callable_parameter_inference(
     })
 }
 
+/// Parameter types of a closure passed to a built-in array function, taken
+/// from the array argument: `array_map(fn ($user) => ..., $users)` binds
+/// `$user` to the element type of `$users`.
+fn builtin_array_callback_param_type(
+    closure_node: Node,
+    site: &CallableArgumentSite,
+    parameter_index: usize,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> Option<TypeInfo> {
+    let is_user_function = file_symbols
+        .symbols
+        .iter()
+        .any(|symbol| symbol.fqn.eq_ignore_ascii_case(&site.target_fqn));
+    if is_user_function || site.argument_name.is_some() {
+        return None;
+    }
+
+    enum Element {
+        Value(usize),
+        Key(usize),
+        Argument(usize),
+    }
+    let function_name = site
+        .target_fqn
+        .rsplit('\\')
+        .next()
+        .unwrap_or(&site.target_fqn)
+        .to_ascii_lowercase();
+    let (callback_index, elements) = match function_name.as_str() {
+        "array_map" => (0, vec![Element::Value(1 + parameter_index)]),
+        "array_filter" => {
+            let mode = enclosing_argument_for_closure(closure_node)
+                .and_then(|argument| argument.parent())
+                .and_then(|arguments| arguments.parent())
+                .and_then(|call_node| call_arguments(call_node, source).into_iter().nth(2))
+                .map(|argument| source[argument.value_node.byte_range()].to_string())
+                .unwrap_or_default();
+            if mode.ends_with("ARRAY_FILTER_USE_KEY") {
+                (1, vec![Element::Key(0)])
+            } else if mode.ends_with("ARRAY_FILTER_USE_BOTH") {
+                (1, vec![Element::Value(0), Element::Key(0)])
+            } else {
+                (1, vec![Element::Value(0)])
+            }
+        }
+        "usort" | "uasort" => (1, vec![Element::Value(0), Element::Value(0)]),
+        "uksort" => (1, vec![Element::Key(0), Element::Key(0)]),
+        "array_walk" | "array_find" | "array_find_key" | "array_any" | "array_all" => {
+            (1, vec![Element::Value(0), Element::Key(0)])
+        }
+        "array_reduce" => (1, vec![Element::Argument(2), Element::Value(0)]),
+        _ => return None,
+    };
+    if site.argument_index != callback_index {
+        return None;
+    }
+    // `array_map` callbacks take one parameter per array argument.
+    let element_index = if function_name == "array_map" {
+        0
+    } else {
+        parameter_index
+    };
+
+    let argument_type = |index: usize| {
+        site.argument_types
+            .iter()
+            .find(|argument| argument.argument_index == index)
+            .map(|argument| &argument.type_info)
+    };
+    let type_info = match elements.get(element_index)? {
+        Element::Value(index) => iterable_value_type_info(argument_type(*index)?, None)?,
+        Element::Key(index) => iterable_key_type_info(argument_type(*index)?)?,
+        Element::Argument(index) => argument_type(*index)?.clone(),
+    };
+    (type_info != TypeInfo::Mixed).then_some(type_info)
+}
+
 fn is_closure_scope_node(node: Node) -> bool {
     matches!(
         node.kind(),
@@ -3210,7 +4260,8 @@ fn find_preceding_phpdoc_comment<'a>(node: Node<'a>, source: &'a str) -> Option<
             };
         }
         // A statement between comment and target means comment is not attached.
-        if p.is_named() {
+        // Attribute lists sit between a PHPDoc and the declaration they annotate.
+        if p.is_named() && p.kind() != "attribute_list" {
             return None;
         }
         prev = p.prev_sibling();
@@ -3230,7 +4281,7 @@ fn parse_tagged_var_name(comment: &str) -> Option<String> {
         if line.starts_with("*/") || line.is_empty() {
             continue;
         }
-        if let Some(rest) = strip_exact_tag(line, "@var") {
+        if let Some(rest) = var_tag(line) {
             for token in rest.split_whitespace() {
                 if let Some(name) = normalize_doc_var_token(token) {
                     return Some(name);
@@ -3324,6 +4375,10 @@ fn is_builtin_non_object_type(name: &str) -> bool {
             | "bool"
             | "boolean"
             | "array"
+            | "list"
+            | "non-empty-array"
+            | "non-empty-list"
+            | "array-key"
             | "object"
             | "null"
             | "void"
@@ -4587,6 +5642,41 @@ fn type_info_from_type_text(type_text: &str) -> TypeInfo {
     }
 }
 
+/// Make the type of a member access or call nullable when any hop of its
+/// chain uses `?->`, which short-circuits the whole chain to `null`.
+pub fn nullsafe_expression_type_info(node: Node, type_info: TypeInfo) -> TypeInfo {
+    if !member_chain_has_nullsafe_hop(node) {
+        return type_info;
+    }
+    match type_info {
+        TypeInfo::Mixed | TypeInfo::LiteralNull | TypeInfo::Nullable(_) => type_info,
+        TypeInfo::Union(mut types) => {
+            if !types.contains(&TypeInfo::LiteralNull) {
+                types.push(TypeInfo::LiteralNull);
+            }
+            TypeInfo::Union(types)
+        }
+        other => TypeInfo::Nullable(Box::new(other)),
+    }
+}
+
+fn member_chain_has_nullsafe_hop(node: Node) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        match node.kind() {
+            "nullsafe_member_access_expression" | "nullsafe_member_call_expression" => {
+                return true;
+            }
+            "member_access_expression" | "member_call_expression" => {
+                current = node.child_by_field_name("object");
+            }
+            "parenthesized_expression" => current = node.named_child(0),
+            _ => return false,
+        }
+    }
+    false
+}
+
 fn type_info_without_null(type_info: &TypeInfo) -> Option<TypeInfo> {
     match type_info {
         TypeInfo::LiteralNull => None,
@@ -4701,8 +5791,10 @@ fn infer_expression_type_info_with_function_resolver(
             for i in 0..node.named_child_count() {
                 if let Some(child) = node.named_child(i) {
                     if matches!(child.kind(), "name" | "qualified_name") {
-                        return Some(TypeInfo::Simple(resolve_class_name(
+                        return Some(resolved_fqn_type_info(&resolve_scope_class_name(
                             &source[child.byte_range()],
+                            node,
+                            source,
                             file_symbols,
                         )));
                     }
@@ -4721,6 +5813,10 @@ fn infer_expression_type_info_with_function_resolver(
         "scoped_call_expression" => {
             infer_scoped_call_expression_type_info(node, source, file_symbols, resolver)
         }
+        "class_constant_access_expression" => {
+            enum_case_access_class_fqn(node, source, file_symbols, resolver)
+                .map(|class_fqn| resolved_fqn_type_info(&class_fqn))
+        }
         "cast_expression" => infer_cast_expression_type_info(node, source),
         "conditional_expression" => infer_conditional_expression_type_info(
             node,
@@ -4730,6 +5826,14 @@ fn infer_expression_type_info_with_function_resolver(
             callable_resolver,
             function_resolver,
         ),
+        "match_expression" => infer_match_expression_type_info(
+            node,
+            source,
+            file_symbols,
+            resolver,
+            callable_resolver,
+            function_resolver,
+        ),
         "binary_expression" => infer_binary_expression_type_info(
             node,
             source,
@@ -4776,6 +5880,7 @@ fn infer_expression_type_info_with_function_resolver(
                             })
                     })
                 })
+                .map(|type_info| nullsafe_expression_type_info(node, type_info))
         }
         "member_call_expression" | "nullsafe_member_call_expression" => {
             let object = node.child_by_field_name("object")?;
@@ -4814,6 +5919,7 @@ fn infer_expression_type_info_with_function_resolver(
                         })
                     })
                 })
+                .map(|type_info| nullsafe_expression_type_info(node, type_info))
         }
         "subscript_expression" => {
             let base = node.named_child(0)?;
@@ -4847,6 +5953,10 @@ fn infer_scoped_call_expression_type_info(
     if class_fqn.is_empty() || matches!(class_fqn.as_str(), "self" | "static" | "parent") {
         return None;
     }
+    let bind_return_type = |return_type: TypeInfo| {
+        let return_type = bind_forwarded_static_type_info(node, &return_type, source, file_symbols);
+        scope_return_type_info_for_owner(&return_type, &class_fqn)
+    };
 
     let method_name = source[name.byte_range()].trim();
     let method_fqn = format!("{class_fqn}::{method_name}");
@@ -4857,13 +5967,92 @@ fn infer_scoped_call_expression_type_info(
     {
         let return_type = symbol_effective_type_info(symbol, file_symbols)?;
         let return_type = resolve_type_info_relative_to_symbol(&return_type, symbol, file_symbols);
-        return Some(scope_return_type_info_for_owner(&return_type, &class_fqn));
+        return Some(bind_return_type(return_type));
+    }
+
+    let resolver_type_info = |owner_fqn: &str| {
+        resolver
+            .and_then(|resolve_fn| resolve_fn(owner_fqn, method_name))
+            .map(|type_text| type_info_from_type_text(&resolver_type_text_for_parser(&type_text)))
+    };
+    // Resolver results already bind `static` to the queried class, so a
+    // forwarding `parent::` call asks about the calling class first.
+    if let Some(called_class_fqn) = forwarded_static_class_fqn(node, source, file_symbols) {
+        if let Some(return_type) = resolver_type_info(&called_class_fqn).filter(|return_type| {
+            object_fqn_from_resolved_member_type_info(return_type, node, source, file_symbols)
+                .is_some_and(|fqn| fqn == called_class_fqn.trim_start_matches('\\'))
+        }) {
+            return Some(bind_return_type(return_type));
+        }
+    }
+
+    resolver_type_info(&class_fqn).map(bind_return_type)
+}
+
+/// The calling class of a `parent::` call, which forwards the late static
+/// binding.
+fn forwarded_static_class_fqn(
+    call_node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> Option<String> {
+    let scope = call_node.child_by_field_name("scope")?;
+    if call_node.kind() != "scoped_call_expression"
+        || !source[scope.byte_range()].eq_ignore_ascii_case("parent")
+    {
+        return None;
     }
+    find_parent_class_fqn(call_node, source, file_symbols)
+}
+
+/// Bind `static` in the return type of a `parent::` call to the calling
+/// class, since `parent::` forwards the late static binding. Other calls are
+/// returned unchanged.
+pub fn bind_forwarded_static_type_info(
+    call_node: Node,
+    type_info: &TypeInfo,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> TypeInfo {
+    match forwarded_static_class_fqn(call_node, source, file_symbols) {
+        Some(called_class_fqn) => bind_late_static_type_info(type_info, &called_class_fqn),
+        None => type_info.clone(),
+    }
+}
 
-    resolver
-        .and_then(|resolve_fn| resolve_fn(&class_fqn, method_name))
-        .map(|type_text| type_info_from_type_text(&resolver_type_text_for_parser(&type_text)))
-        .map(|return_type| scope_return_type_info_for_owner(&return_type, &class_fqn))
+/// Replace `static` / `$this` with the class the call was made on, leaving
+/// `self` bound to the declaring class.
+fn bind_late_static_type_info(type_info: &TypeInfo, called_class_fqn: &str) -> TypeInfo {
+    match type_info {
+        TypeInfo::Static_ => resolved_fqn_type_info(called_class_fqn),
+        TypeInfo::Simple(name) if matches!(name.as_str(), "$this" | "static") => {
+            resolved_fqn_type_info(called_class_fqn)
+        }
+        TypeInfo::Nullable(inner) => TypeInfo::Nullable(Box::new(bind_late_static_type_info(
+            inner,
+            called_class_fqn,
+        ))),
+        TypeInfo::Union(types) => TypeInfo::Union(
+            types
+                .iter()
+                .map(|ty| bind_late_static_type_info(ty, called_class_fqn))
+                .collect(),
+        ),
+        TypeInfo::Intersection(types) => TypeInfo::Intersection(
+            types
+                .iter()
+                .map(|ty| bind_late_static_type_info(ty, called_class_fqn))
+                .collect(),
+        ),
+        TypeInfo::Generic { base, args } => TypeInfo::Generic {
+            base: base.clone(),
+            args: args
+                .iter()
+                .map(|arg| bind_late_static_type_info(arg, called_class_fqn))
+                .collect(),
+        },
+        _ => type_info.clone(),
+    }
 }
 
 fn scope_return_type_info_for_owner(type_info: &TypeInfo, owner_fqn: &str) -> TypeInfo {
@@ -4878,7 +6067,7 @@ fn prefer_top_level_scope_return_type_info(
     type_info: &TypeInfo,
     owner_fqn: &str,
 ) -> Option<TypeInfo> {
-    let owner = TypeInfo::Simple(owner_fqn.trim_start_matches('\\').to_string());
+    let owner = resolved_fqn_type_info(owner_fqn);
     match type_info {
         TypeInfo::Self_ | TypeInfo::Static_ => Some(owner),
         TypeInfo::Simple(name) if matches!(name.as_str(), "$this" | "self" | "static") => {
@@ -4905,15 +6094,13 @@ fn prefer_top_level_scope_return_type_info(
 
 fn substitute_scope_type_info(type_info: &TypeInfo, owner_fqn: &str) -> TypeInfo {
     match type_info {
-        TypeInfo::Self_ | TypeInfo::Static_ => {
-            TypeInfo::Simple(owner_fqn.trim_start_matches('\\').to_string())
-        }
+        TypeInfo::Self_ | TypeInfo::Static_ => resolved_fqn_type_info(owner_fqn),
         TypeInfo::Simple(name) if matches!(name.as_str(), "$this" | "self" | "static") => {
-            TypeInfo::Simple(owner_fqn.trim_start_matches('\\').to_string())
+            resolved_fqn_type_info(owner_fqn)
         }
         TypeInfo::Generic { base, args } => TypeInfo::Generic {
             base: if matches!(base.as_str(), "$this" | "self" | "static") {
-                owner_fqn.trim_start_matches('\\').to_string()
+                format!("\\{}", owner_fqn.trim_start_matches('\\'))
             } else {
                 base.clone()
             },
@@ -5006,6 +6193,33 @@ fn infer_conditional_expression_type_info(
     function_resolver: Option<FunctionTypeResolver<'_>>,
 ) -> Option<TypeInfo> {
     let child_count = node.named_child_count();
+    if child_count == 2 {
+        // Short ternary `$a ?: $b`: the condition itself when it is truthy.
+        let condition = node
+            .child_by_field_name("condition")
+            .or_else(|| node.named_child(0))?;
+        let falsy = node
+            .child_by_field_name("alternative")
+            .or_else(|| node.named_child(1))?;
+        let truthy_type = infer_expression_type_info_with_function_resolver(
+            condition,
+            source,
+            file_symbols,
+            resolver,
+            callable_resolver,
+            function_resolver,
+        )
+        .and_then(|type_info| type_info_without_null(&type_info));
+        let falsy_type = infer_expression_type_info_with_function_resolver(
+            falsy,
+            source,
+            file_symbols,
+            resolver,
+            callable_resolver,
+            function_resolver,
+        );
+        return merge_optional_type_infos(truthy_type, falsy_type);
+    }
     if child_count < 3 {
         return None;
     }
@@ -5041,6 +6255,41 @@ fn infer_conditional_expression_type_info(
     merge_optional_type_infos(truthy_type, falsy_type)
 }
 
+/// The union of every arm's result type in `match (...) { ... }`. Arms
+/// whose type cannot be inferred, such as `throw`, do not contribute.
+fn infer_match_expression_type_info(
+    node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+    resolver: Option<MemberTypeResolver<'_>>,
+    callable_resolver: Option<CallableParamTypeResolver<'_>>,
+    function_resolver: Option<FunctionTypeResolver<'_>>,
+) -> Option<TypeInfo> {
+    let body = node.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let arm_types: Vec<TypeInfo> = body
+        .named_children(&mut cursor)
+        .filter(|arm| {
+            matches!(
+                arm.kind(),
+                "match_conditional_expression" | "match_default_expression"
+            )
+        })
+        .filter_map(|arm| arm.child_by_field_name("return_expression"))
+        .filter_map(|expression| {
+            infer_expression_type_info_with_function_resolver(
+                expression,
+                source,
+                file_symbols,
+                resolver,
+                callable_resolver,
+                function_resolver,
+            )
+        })
+        .collect();
+    (!arm_types.is_empty()).then(|| merge_type_infos(arm_types))
+}
+
 fn class_string_type_info_from_expression(
     node: Node,
     source: &str,
@@ -5110,7 +6359,7 @@ fn infer_literal_array_shape_text(
         });
     }
 
-    (!items.is_empty()).then_some(TypeInfo::ArrayShape(items))
+    Some(TypeInfo::ArrayShape(items))
 }
 
 fn infer_literal_value_type_text(
@@ -5328,14 +6577,18 @@ fn foreach_iterable_node(stmt: Node) -> Option<Node> {
 }
 
 fn foreach_value_variable_node<'a>(stmt: Node<'a>, source: &str) -> Option<Node<'a>> {
-    let value_expr = match stmt.named_child(1)? {
+    variable_node_in_foreach_part(foreach_value_node(stmt)?, source)
+}
+
+/// The value part of `foreach (... as $key => <value>)`.
+fn foreach_value_node(stmt: Node) -> Option<Node> {
+    match stmt.named_child(1)? {
         pair if pair.kind() == "pair" => {
             let count = pair.named_child_count();
-            pair.named_child(count.saturating_sub(1))?
+            pair.named_child(count.saturating_sub(1))
         }
-        value => value,
-    };
-    variable_node_in_foreach_part(value_expr, source)
+        value => Some(value),
+    }
 }
 
 fn foreach_key_variable_node<'a>(stmt: Node<'a>, source: &str) -> Option<Node<'a>> {
@@ -5652,14 +6905,14 @@ fn normalize_array_access_key(raw: &str) -> Option<String> {
     (!trimmed.is_empty()).then(|| trimmed.to_string())
 }
 
-fn position_to_byte(source: &str, line: u32, character: u32) -> usize {
+/// Byte offset of a tree-sitter (line, byte column) position.
+fn position_to_byte(source: &str, line: u32, byte_col: u32) -> usize {
     let mut offset = 0usize;
     let line_idx = line as usize;
     for (i, row) in source.split_inclusive('\n').enumerate() {
         let line_text = row.strip_suffix('\n').unwrap_or(row);
         if i == line_idx {
-            let byte_col = utf16_col_to_byte(source, line, character) as usize;
-            return offset + byte_col.min(line_text.len());
+            return offset + (byte_col as usize).min(line_text.len());
         }
         offset += row.len();
     }
@@ -5690,6 +6943,23 @@ fn resolve_name_node(
         });
     }
 
+    // `\\Ns\\NAME`: the cursor sits on the last segment, which resolves
+    // through the whole qualified name.
+    if parent_kind == "qualified_name" {
+        if let Some(qualified) = node.parent().filter(|qualified| {
+            is_constant_reference_context(qualified.parent().map(|p| p.kind()).unwrap_or_default())
+        }) {
+            let resolved = resolve_constant_name(&source[qualified.byte_range()], file_symbols);
+            return Some(SymbolAtPosition {
+                fqn: resolved,
+                name: text.to_string(),
+                ref_kind: RefKind::GlobalConstant,
+                object_expr: None,
+                range: node_range(node),
+            });
+        }
+    }
+
     // Resolve as global/user constant in expression-like contexts.
     if is_constant_reference_context(parent_kind) {
         let resolved = resolve_constant_name(text, file_symbols);
@@ -5877,60 +7147,100 @@ fn is_constant_reference_context(parent_kind: &str) -> bool {
             | "namespace_use_clause"
             | "namespace_definition"
             | "use_declaration"
+            | "attribute"
     )
 }
 
-/// Check if a node is inside a `namespace_use_clause` (a use statement).
-fn is_inside_use_clause(node: Node, parent: Node) -> bool {
-    // Walk up a few levels looking for namespace_use_clause.
-    // Possible structures:
-    //   name → namespace_use_clause  (single-segment)
-    //   name → qualified_name → namespace_use_clause
-    //   name → namespace_name → qualified_name → namespace_use_clause
-    let _ = node; // suppress unused warning
-    let mut current = parent;
-    for _ in 0..3 {
-        if current.kind() == "namespace_use_clause" {
-            return true;
-        }
-        match current.parent() {
-            Some(p) => current = p,
-            None => break,
+/// Resolve a name inside a `use` declaration to the symbol it imports.
+///
+/// Every segment of `use Doctrine\ORM\EntityManagerInterface as Em;`, the
+/// alias included, resolves to `Doctrine\ORM\EntityManagerInterface`. Group
+/// clauses get the group prefix prepended, `function` / `const` imports
+/// (declaration-wide or per clause) resolve as functions and constants, and
+/// the prefix of a group use resolves as a namespace name.
+fn resolve_use_declaration_name(node: Node, source: &str) -> Option<SymbolAtPosition> {
+    let mut child = node;
+    let mut current = node.parent()?;
+    loop {
+        match current.kind() {
+            "namespace_use_clause" => break,
+            "namespace_use_declaration" => {
+                return Some(SymbolAtPosition {
+                    fqn: source[child.byte_range()].trim_matches('\\').to_string(),
+                    name: source[node.byte_range()].to_string(),
+                    ref_kind: RefKind::NamespaceName,
+                    object_expr: None,
+                    range: node_range(node),
+                });
+            }
+            "qualified_name" | "namespace_name" => {
+                child = current;
+                current = current.parent()?;
+            }
+            _ => return None,
         }
     }
-    false
+
+    let clause = current;
+    let mut cursor = clause.walk();
+    let imported = clause
+        .children(&mut cursor)
+        .find(|c| matches!(c.kind(), "qualified_name" | "namespace_name" | "name"))?;
+    let imported = source[imported.byte_range()].trim_start_matches('\\');
+
+    let group = clause
+        .parent()
+        .filter(|p| p.kind() == "namespace_use_group");
+    let declaration = match group {
+        Some(group) => group.parent()?,
+        None => clause.parent()?,
+    };
+    let fqn = match group.and_then(|group| use_group_prefix(declaration, group, source)) {
+        Some(prefix) if !prefix.is_empty() => format!("{}\\{}", prefix, imported),
+        _ => imported.to_string(),
+    };
+
+    let kind = use_keyword_kind(clause)
+        .or_else(|| use_keyword_kind(declaration))
+        .unwrap_or(UseKind::Class);
+    Some(SymbolAtPosition {
+        fqn,
+        name: source[node.byte_range()].to_string(),
+        ref_kind: match kind {
+            UseKind::Class => RefKind::ClassName,
+            UseKind::Function => RefKind::FunctionCall,
+            UseKind::Constant => RefKind::GlobalConstant,
+        },
+        object_expr: None,
+        range: node_range(node),
+    })
 }
 
-/// Extract the full FQN string from a use clause.
-///
-/// For `use Doctrine\ORM\EntityManagerInterface;`, returns
-/// `Doctrine\ORM\EntityManagerInterface` regardless of which segment
-/// the cursor is on.
-fn extract_use_clause_fqn(node: Node, parent: Node, source: &str) -> String {
-    // Walk up to find the namespace_use_clause node
-    let _ = node; // suppress unused
-    let mut current = parent;
-    for _ in 0..4 {
-        if current.kind() == "namespace_use_clause" {
-            // The namespace_use_clause contains a qualified_name or name child
-            for i in 0..current.named_child_count() {
-                if let Some(child) = current.named_child(i) {
-                    match child.kind() {
-                        "qualified_name" | "name" => {
-                            return source[child.byte_range()].to_string();
-                        }
-                        _ => {}
-                    }
-                }
-            }
-            return source[current.byte_range()].to_string();
-        }
-        match current.parent() {
-            Some(p) => current = p,
-            None => break,
+/// The `function` / `const` keyword in front of a use declaration's clauses,
+/// or of a single clause inside a group.
+fn use_keyword_kind(node: Node) -> Option<UseKind> {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "function" => return Some(UseKind::Function),
+            "const" => return Some(UseKind::Constant),
+            "namespace_use_clause" | "namespace_use_group" | "qualified_name" | "name" => break,
+            _ => {}
         }
     }
-    source[parent.byte_range()].to_string()
+    None
+}
+
+/// The namespace prefix in front of a group use (`App\Models` in
+/// `use App\Models\{User, Post};`).
+fn use_group_prefix(declaration: Node, group: Node, source: &str) -> Option<String> {
+    let mut cursor = declaration.walk();
+    let prefix = declaration
+        .children(&mut cursor)
+        .take_while(|child| child.id() != group.id())
+        .filter(|child| matches!(child.kind(), "namespace_name" | "qualified_name" | "name"))
+        .last()?;
+    Some(source[prefix.byte_range()].trim_matches('\\').to_string())
 }
 
 #[allow(clippy::type_complexity)]
@@ -5946,12 +7256,13 @@ fn find_variable_definition_before(
     }
 
     match node.kind() {
-        "variable_name" if is_by_ref_output_argument_variable(node, source) => {
-            if normalize_var_name(&source[node.byte_range()]) == var_name {
-                let start = node.start_byte();
-                if start < usage_start {
-                    *best = Some((start, node_range(node)));
-                }
+        "variable_name"
+            if is_by_ref_output_argument_variable(node, source)
+                && normalize_var_name(&source[node.byte_range()]) == var_name =>
+        {
+            let start = node.start_byte();
+            if start < usage_start {
+                *best = Some((start, node_range(node)));
             }
         }
         "simple_parameter" | "property_promotion_parameter" => {
@@ -6002,6 +7313,23 @@ fn find_variable_definition_before(
                 }
             }
         }
+        "global_declaration" => {
+            let cursor = &mut node.walk();
+            for var_node in node.named_children(cursor) {
+                if var_node.kind() == "variable_name"
+                    && normalize_var_name(&source[var_node.byte_range()]) == var_name
+                {
+                    *best = Some((var_node.start_byte(), node_range(var_node)));
+                }
+            }
+        }
+        "static_variable_declaration" => {
+            if let Some(name_node) = node.child_by_field_name("name") {
+                if normalize_var_name(&source[name_node.byte_range()]) == var_name {
+                    *best = Some((name_node.start_byte(), node_range(name_node)));
+                }
+            }
+        }
         _ => {}
     }
 
@@ -6013,6 +7341,7 @@ fn find_variable_definition_before(
 
 fn collect_variable_declarations_before(
     node: Node,
+    scope: Node,
     usage_start: usize,
     source: &str,
     vars: &mut Vec<(usize, String)>,
@@ -6020,30 +7349,40 @@ fn collect_variable_declarations_before(
     if node.start_byte() >= usage_start {
         return;
     }
+    if node.id() != scope.id()
+        && matches!(
+            node.kind(),
+            "function_definition"
+                | "method_declaration"
+                | "anonymous_function"
+                | "anonymous_function_creation_expression"
+                | "arrow_function"
+                | "declaration_list"
+        )
+    {
+        return;
+    }
 
     match node.kind() {
         "variable_name" if is_by_ref_output_argument_variable(node, source) => {
             collect_variable_node(node, usage_start, source, vars);
         }
-        "simple_parameter" | "property_promotion_parameter" => {
+        "simple_parameter" | "property_promotion_parameter" | "variadic_parameter" => {
             if let Some(name_node) = node.child_by_field_name("name") {
                 collect_variable_node(name_node, usage_start, source, vars);
             }
         }
         "assignment_expression" | "by_ref_assignment_expression" => {
             if let Some(left) = node.child_by_field_name("left") {
-                collect_variable_node(left, usage_start, source, vars);
+                collect_destructured_variables(left, usage_start, source, vars);
             }
         }
         "foreach_statement" => {
-            for var_node in [
-                foreach_key_variable_node(node, source),
-                foreach_value_variable_node(node, source),
-            ]
-            .into_iter()
-            .flatten()
-            {
-                collect_variable_node(var_node, usage_start, source, vars);
+            if let Some(key) = foreach_key_variable_node(node, source) {
+                collect_variable_node(key, usage_start, source, vars);
+            }
+            if let Some(value) = foreach_value_node(node) {
+                collect_destructured_variables(value, usage_start, source, vars);
             }
         }
         "catch_clause" => {
@@ -6053,12 +7392,49 @@ fn collect_variable_declarations_before(
                 }
             }
         }
+        "anonymous_function_use_clause" | "global_declaration" => {
+            collect_destructured_variables(node, usage_start, source, vars);
+        }
+        "static_variable_declaration" => {
+            if let Some(name_node) = node.child_by_field_name("name") {
+                collect_variable_node(name_node, usage_start, source, vars);
+            }
+        }
         _ => {}
     }
-
+
+    let cursor = &mut node.walk();
+    for child in node.named_children(cursor) {
+        collect_variable_declarations_before(child, scope, usage_start, source, vars);
+    }
+}
+
+/// Collect every variable in an assignment target, `list()`/`[...]`
+/// destructuring and `&$var` included.
+fn collect_destructured_variables(
+    node: Node,
+    usage_start: usize,
+    source: &str,
+    vars: &mut Vec<(usize, String)>,
+) {
+    if node.kind() == "variable_name" {
+        collect_variable_node(node, usage_start, source, vars);
+        return;
+    }
+    if !matches!(
+        node.kind(),
+        "list_literal"
+            | "array_creation_expression"
+            | "array_element_initializer"
+            | "by_ref"
+            | "anonymous_function_use_clause"
+            | "global_declaration"
+    ) {
+        return;
+    }
     let cursor = &mut node.walk();
     for child in node.named_children(cursor) {
-        collect_variable_declarations_before(child, usage_start, source, vars);
+        collect_destructured_variables(child, usage_start, source, vars);
     }
 }
 
@@ -6177,11 +7553,14 @@ fn resolve_scope_class_name(
     source: &str,
     file_symbols: &FileSymbols,
 ) -> String {
-    match scope_name {
-        "self" | "static" => find_parent_class_fqn(context_node, source, file_symbols)
-            .unwrap_or_else(|| scope_name.to_string()),
-        "parent" => find_extended_parent_class_fqn(context_node, source, file_symbols)
-            .unwrap_or_else(|| scope_name.to_string()),
+    let keyword = scope_name.to_ascii_lowercase();
+    match keyword.as_str() {
+        "self" | "static" => {
+            find_parent_class_fqn(context_node, source, file_symbols).unwrap_or(keyword)
+        }
+        "parent" => {
+            find_extended_parent_class_fqn(context_node, source, file_symbols).unwrap_or(keyword)
+        }
         _ => resolve_class_name(scope_name, file_symbols),
     }
 }
@@ -6192,7 +7571,11 @@ fn resolve_type_name_in_context(
     source: &str,
     file_symbols: &FileSymbols,
 ) -> String {
-    match type_name.trim_start_matches('\\') {
+    match type_name
+        .trim_start_matches('\\')
+        .to_ascii_lowercase()
+        .as_str()
+    {
         "self" | "static" => find_parent_class_fqn(context_node, source, file_symbols)
             .unwrap_or_else(|| type_name.to_string()),
         "parent" => find_extended_parent_class_fqn(context_node, source, file_symbols)
@@ -6263,6 +7646,7 @@ mod tests {
             is_variadic: false,
             is_by_ref: false,
             is_promoted: false,
+            attributes: vec![],
         }
     }
 
@@ -6338,7 +7722,7 @@ mod tests {
     }
 
     #[test]
-    fn test_position_to_byte_converts_utf16_character() {
+    fn test_position_to_byte_skips_multibyte_lines() {
         let source = "<?php\n$emoji = \"😀\";\n$result = $emoji;\n";
         let expected = source.find("$result").unwrap();
 
@@ -6450,6 +7834,42 @@ class DeleteMultipleVCard
         }
     }
 
+    #[test]
+    fn test_resolve_attribute_names_as_classes() {
+        let code = r#"<?php
+namespace App\Http;
+
+use Symfony\Component\Routing\Attribute\Route;
+
+#[Route('/users'), \Attribute(\Attribute::TARGET_CLASS)]
+final class UserController
+{
+    #[Route('/users/{id}')]
+    public function show(#[Local\Param] int $id): void {}
+}
+"#;
+        let cases = [
+            (
+                "Route('/users')",
+                "Symfony\\Component\\Routing\\Attribute\\Route",
+            ),
+            ("Attribute(", "Attribute"),
+            (
+                "Route('/users/{id}')",
+                "Symfony\\Component\\Routing\\Attribute\\Route",
+            ),
+            ("Local\\Param", "App\\Http\\Local\\Param"),
+        ];
+
+        for (needle, expected_fqn) in cases {
+            let (line, col) = find_line_col(code, needle);
+            let sym = parse_and_resolve(code, line, col).expect("attribute name should resolve");
+
+            assert_eq!(sym.ref_kind, RefKind::ClassName, "{needle}");
+            assert_eq!(sym.fqn, expected_fqn, "{needle}");
+        }
+    }
+
     #[test]
     fn test_resolve_function_call() {
         let code = "<?php\nnamespace App;\n\nstrlen('hello');\n";
@@ -6534,6 +7954,7 @@ class UserRepository {
         let signature = Signature {
             params: vec![test_param("prefix"), test_param("abstract")],
             return_type: None,
+            throws: Vec::new(),
         };
         let function_resolver = |function_name: &str| -> Option<ResolvedFunctionType> {
             (function_name == "App\\helper").then(|| {
@@ -6600,10 +8021,12 @@ helper('service', Backend::class)->ping();
         let response_signature = Signature {
             params: vec![defaulted_test_param("content", "null")],
             return_type: None,
+            throws: Vec::new(),
         };
         let redirect_signature = Signature {
             params: vec![defaulted_test_param("to", "null")],
             return_type: None,
+            throws: Vec::new(),
         };
         let function_resolver = |function_name: &str| -> Option<ResolvedFunctionType> {
             match function_name {
@@ -6737,6 +8160,35 @@ class Child extends Base {
         assert_eq!(method.fqn, "App\\Base::run");
     }
 
+    #[test]
+    fn test_resolve_parent_scope_keyword_case_insensitively() {
+        let code = r#"<?php
+namespace App;
+
+class Base {
+    const VERSION = 1;
+    public function __construct() {}
+}
+
+class Child extends Base {
+    public function __construct() {
+        Parent::__construct();
+        echo PARENT::VERSION;
+    }
+}
+"#;
+        let (line, col) = find_line_col(code, "Parent::__construct");
+        let method = parse_and_resolve(code, line, col + "Parent::".len() as u32)
+            .expect("Parent::__construct should resolve");
+        assert_eq!(method.fqn, "App\\Base::__construct");
+        assert_eq!(method.object_expr.as_deref(), Some("Parent"));
+
+        let (line, col) = find_line_col(code, "PARENT::VERSION");
+        let constant = parse_and_resolve(code, line, col + "PARENT::".len() as u32)
+            .expect("PARENT::VERSION should resolve");
+        assert_eq!(constant.fqn, "App\\Base::VERSION");
+    }
+
     #[test]
     fn test_resolve_parent_scope_inside_anonymous_class() {
         let code = r#"<?php
@@ -7043,6 +8495,107 @@ function run(): void {
         assert_eq!(expire_method.fqn, "App\\ExpireDate::getTimestamp");
     }
 
+    #[test]
+    fn test_late_static_binding_resolves_to_calling_class() {
+        let code = r#"<?php
+namespace App;
+
+class Model {
+    public static function create(): static { return new static(); }
+    public static function copy(): self { return new self(); }
+    public static function query() {
+        $query = new static();
+        $query->save();
+    }
+    public function save(): void {}
+}
+class User extends Model {
+    public function posts(): void {}
+    public static function make() {
+        $fresh = new static();
+        $fresh;
+        $forwarded = parent::create();
+        $forwarded;
+        $copied = parent::copy();
+        $copied;
+        $fresh->posts();
+        $forwarded->posts();
+        parent::create()->posts();
+    }
+}
+"#;
+        let (line, col) = find_line_col(code, "$fresh;");
+        let result = parse_and_infer_var_type_at(code, line, col + 1, "$fresh");
+        assert_eq!(result.as_deref(), Some("App\\User"));
+
+        let (line, col) = find_line_col(code, "$forwarded;");
+        let result = parse_and_infer_var_type_at(code, line, col + 1, "$forwarded");
+        assert_eq!(result.as_deref(), Some("App\\User"));
+
+        let (line, col) = find_line_col(code, "$copied;");
+        let result = parse_and_infer_var_type_at(code, line, col + 1, "$copied");
+        assert_eq!(result.as_deref(), Some("App\\Model"));
+
+        let (line, col) = find_line_col(code, "save();");
+        let method = parse_and_resolve(code, line, col).expect("save should resolve");
+        assert_eq!(method.fqn, "App\\Model::save");
+
+        for receiver in ["$fresh->", "$forwarded->", "parent::create()->"] {
+            let (line, col) = find_line_col(code, &format!("{receiver}posts();"));
+            let method = parse_and_resolve(code, line, col + receiver.len() as u32)
+                .expect("posts should resolve");
+            assert_eq!(method.fqn, "App\\User::posts", "{receiver}");
+        }
+    }
+
+    #[test]
+    fn test_infer_match_and_short_ternary_assignments_as_unions() {
+        let code = r#"<?php
+namespace App;
+
+class Admin {
+    public function permissions(): array { return []; }
+}
+class Guest {
+    public function visit(): void {}
+}
+
+function run(int $kind, ?Admin $current): void {
+    $subject = match ($kind) {
+        1, 2 => new Admin(),
+        3 => throw new \LogicException(),
+        default => new Guest(),
+    };
+    $subject;
+    $fallback = $current ?: new Guest();
+    $fallback;
+    $subject->visit();
+    $fallback->permissions();
+}
+"#;
+        let (line, col) = find_line_col(code, "$subject;");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$subject");
+        assert_eq!(
+            result.map(|type_info| type_info.to_string()).as_deref(),
+            Some("\\App\\Admin|\\App\\Guest")
+        );
+
+        let (line, col) = find_line_col(code, "$fallback;");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$fallback");
+        assert_eq!(
+            result.map(|type_info| type_info.to_string()).as_deref(),
+            Some("\\App\\Admin|\\App\\Guest")
+        );
+
+        let (line, col) = find_line_col(code, "visit();");
+        let method = parse_and_resolve(code, line, col).expect("visit should resolve");
+        assert_eq!(method.fqn, "App\\Guest::visit");
+
+        let (line, col) = find_line_col(code, "permissions();");
+        let method = parse_and_resolve(code, line, col).expect("permissions should resolve");
+        assert_eq!(method.fqn, "App\\Admin::permissions");
+    }
+
     #[test]
     fn test_laravel_optional_proxy_resolves_wrapped_static_finder_assignment() {
         let code = r#"<?php
@@ -7311,6 +8864,42 @@ function run(bool $enabled): void
         assert_eq!(result.fqn, "App\\Session::get");
     }
 
+    #[test]
+    fn test_nullsafe_chain_infers_nullable_types() {
+        let code = r#"<?php
+namespace App;
+
+class Profile {
+    public string $name = '';
+    public function getBio(): string|int { return ''; }
+}
+
+class User {
+    public Profile $profile;
+}
+
+function run(?User $user, User $known): void
+{
+    $name = $user?->profile->name;
+    $bio = $user?->profile?->getBio();
+    $plain = $known->profile->name;
+    echo $name, $bio, $plain;
+}
+"#;
+        let (line, col) = find_line_col(code, "echo $name");
+        let inferred = |var_name: &str, col: u32| {
+            parse_and_infer_var_type_info_at(code, line, col, var_name)
+                .map(|type_info| type_info.to_string())
+        };
+
+        assert_eq!(inferred("$name", col + 5).as_deref(), Some("?string"));
+        assert_eq!(
+            inferred("$bio", col + 12).as_deref(),
+            Some("string|int|null")
+        );
+        assert_eq!(inferred("$plain", col + 18).as_deref(), Some("string"));
+    }
+
     #[test]
     fn test_resolve_self_reassignment_rhs_does_not_recurse() {
         let code = r#"<?php
@@ -7911,6 +9500,51 @@ function run(): void {
         assert_eq!(items[0].key.as_deref(), Some("city"));
     }
 
+    #[test]
+    fn test_literal_key_writes_extend_literal_array_shape() {
+        let code = r#"<?php
+function run(string $name): void {
+    $opts = [];
+    $opts['debug'] = true;
+    $opts["level"] = 1;
+    $opts['debug'] = 'verbose';
+    $opts;
+    $names = [];
+    $names[] = 'first';
+    $names;
+    $byName = ['a' => 1];
+    $byName[$name] = 2;
+    $byName;
+}
+"#;
+        let (line, col) = find_line_col(code, "$opts;");
+        let Some(TypeInfo::ArrayShape(items)) =
+            parse_and_infer_var_type_info_at(code, line, col, "$opts")
+        else {
+            panic!("expected a shape from literal key writes");
+        };
+        let keys: Vec<_> = items
+            .iter()
+            .filter_map(|item| item.key.as_deref())
+            .collect();
+        assert_eq!(keys, vec!["level", "debug"]);
+        assert_eq!(items[1].value, TypeInfo::Simple("string".to_string()));
+
+        let (line, col) = find_line_col(code, "$names;");
+        assert_eq!(
+            parse_and_infer_var_type_info_at(code, line, col, "$names")
+                .map(|type_info| type_info.to_string())
+                .as_deref(),
+            Some("list<string>")
+        );
+
+        let (line, col) = find_line_col(code, "$byName;");
+        assert!(matches!(
+            parse_and_infer_var_type_info_at(code, line, col, "$byName"),
+            Some(TypeInfo::Generic { .. })
+        ));
+    }
+
     #[test]
     fn test_infer_variable_type_inside_positive_instanceof_branch() {
         let code = r#"<?php
@@ -8021,6 +9655,99 @@ class UserRepository {
         assert_ne!(result.as_deref(), Some("App\\Entity\\OAuth2User"));
     }
 
+    #[test]
+    fn test_infer_variable_type_inside_positive_type_check_branch() {
+        let code = r#"<?php
+namespace App;
+
+use App\Entity\User;
+
+class Importer {
+    /** @param list<User>|string $rows */
+    public function import($rows, int|string|null $limit): void {
+        if (is_array($rows)) {
+            $rows;
+        }
+        if (\is_int($limit)) {
+            $limit;
+        }
+    }
+}
+"#;
+        let (line, col) = find_line_col(code, "$rows;");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$rows");
+        assert_eq!(
+            result.map(|type_info| type_info.to_string()).as_deref(),
+            Some("list<\\App\\Entity\\User>")
+        );
+
+        let (line, col) = find_line_col(code, "$limit;");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$limit");
+        assert_eq!(result, Some(TypeInfo::Simple("int".to_string())));
+    }
+
+    #[test]
+    fn test_infer_variable_type_after_type_check_early_return_guards() {
+        let code = r#"<?php
+namespace App;
+
+use App\Entity\User;
+use App\Entity\Post;
+
+function render(User|Post|null $subject, mixed $title, User|string $owner): void {
+    if ($subject === null) {
+        return;
+    }
+    if (!is_string($title)) {
+        throw new \InvalidArgumentException();
+    }
+    if (is_string($owner)) {
+        return;
+    }
+    $subject;
+    $title;
+    $owner->getName();
+}
+"#;
+        let (line, col) = find_line_col(code, "$subject;");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$subject");
+        assert_eq!(
+            result.map(|type_info| type_info.to_string()).as_deref(),
+            Some("\\App\\Entity\\User|\\App\\Entity\\Post")
+        );
+
+        let (line, col) = find_line_col(code, "$title;");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$title");
+        assert_eq!(result, Some(TypeInfo::Simple("string".to_string())));
+
+        let (line, col) = find_line_col(code, "getName");
+        let result = parse_and_infer_var_type_at(code, line, col, "$owner");
+        assert_eq!(result.as_deref(), Some("App\\Entity\\User"));
+    }
+
+    #[test]
+    fn test_type_check_guard_ignores_non_exiting_and_conjunction_guards() {
+        let code = r#"<?php
+namespace App;
+
+use App\Entity\User;
+use App\Entity\Post;
+
+function render(User|Post $subject, bool $force): void {
+    if (!$subject instanceof Post && $force) {
+        return;
+    }
+    if (!$subject instanceof Post) {
+        log($subject);
+    }
+    $subject->getTitle();
+}
+"#;
+        let (line, col) = find_line_col(code, "getTitle");
+        let result = parse_and_infer_var_type_at(code, line, col, "$subject");
+        assert_ne!(result.as_deref(), Some("App\\Entity\\Post"));
+    }
+
     #[test]
     fn test_resolve_property_access_type_from_property_phpdoc_var() {
         let code = r#"<?php
@@ -8079,6 +9806,68 @@ class Holder {
         assert_eq!(sym.fqn, "App\\BUILD");
     }
 
+    #[test]
+    fn test_resolve_qualified_global_constant_reference() {
+        let code = "<?php\nnamespace App;\n\necho \\Legacy\\DEBUG_MODE;\n";
+        let sym = parse_and_resolve(code, 3, 15).expect("qualified constant should resolve");
+        assert_eq!(sym.ref_kind, RefKind::GlobalConstant);
+        assert_eq!(sym.fqn, "Legacy\\DEBUG_MODE");
+    }
+
+    #[test]
+    fn test_resolve_enum_case_access_as_enum_instance() {
+        let code = "<?php\nnamespace App;\nenum Suit: string {\n    case Hearts = 'H';\n}\n$x = Suit::Hearts;\n$x->value;\nSuit::Hearts->name;\n";
+        let sym = parse_and_resolve(code, 6, 5).expect("property on enum case variable");
+        assert_eq!(sym.fqn, "App\\Suit::$value");
+        let sym = parse_and_resolve(code, 7, 15).expect("property on enum case access");
+        assert_eq!(sym.fqn, "App\\Suit::$name");
+    }
+
+    #[test]
+    fn test_resolve_callable_strings() {
+        let code = r#"<?php
+namespace App;
+class Listener {
+    public function handle(): void {}
+    public function register(): void {
+        call_user_func('\Vendor\Mailer::send');
+        usort($items, [$this, 'handle']);
+        usort($items, [Mailer::class, 'compare']);
+        array_map('Vendor\format', $items);
+        str_replace('strlen', '', $text);
+    }
+}
+"#;
+        let (line, col) = find_line_col(code, "\\Vendor\\Mailer::send");
+        let sym = parse_and_resolve(code, line, col + 1).unwrap();
+        assert_eq!(sym.fqn, "Vendor\\Mailer");
+        assert_eq!(sym.ref_kind, RefKind::ClassName);
+        assert_eq!(
+            sym.range,
+            (line, col, line, col + "\\Vendor\\Mailer".len() as u32)
+        );
+        let sym = parse_and_resolve(code, line, col + 17).unwrap();
+        assert_eq!(sym.fqn, "Vendor\\Mailer::send");
+        assert_eq!(sym.ref_kind, RefKind::MethodCall);
+
+        let (line, col) = find_line_col(code, "handle']");
+        let sym = parse_and_resolve(code, line, col + 1).unwrap();
+        assert_eq!(sym.fqn, "App\\Listener::handle");
+        assert_eq!(sym.object_expr.as_deref(), Some("$this"));
+
+        let (line, col) = find_line_col(code, "compare']");
+        let sym = parse_and_resolve(code, line, col + 1).unwrap();
+        assert_eq!(sym.fqn, "App\\Mailer::compare");
+
+        let (line, col) = find_line_col(code, "format'");
+        let sym = parse_and_resolve(code, line, col + 1).unwrap();
+        assert_eq!(sym.fqn, "Vendor\\format");
+        assert_eq!(sym.ref_kind, RefKind::FunctionCall);
+
+        let (line, col) = find_line_col(code, "strlen'");
+        assert!(parse_and_resolve(code, line, col + 1).is_none());
+    }
+
     #[test]
     fn test_find_variable_definition_assignment() {
         let code = "<?php\nfunction demo(): void {\n    $value = 1;\n    echo $value;\n}\n";
@@ -8131,6 +9920,29 @@ function demo(array $items): void {
         assert_eq!(def.1, col);
     }
 
+    #[test]
+    fn test_find_variable_definition_global_and_static_statements() {
+        let code = r#"<?php
+function demo(): int {
+    global $db;
+    static $hits = 0;
+    $db->query();
+    return ++$hits;
+}
+"#;
+        let (line, col) = find_line_col(code, "$db->query");
+        let def = parse_and_find_var_def(code, line, col + 2)
+            .expect("global variable definition should be found");
+        let (def_line, def_col) = find_line_col(code, "$db;");
+        assert_eq!((def.0, def.1), (def_line, def_col));
+
+        let (line, col) = find_line_col(code, "++$hits");
+        let def = parse_and_find_var_def(code, line, col + 4)
+            .expect("static variable definition should be found");
+        let (def_line, def_col) = find_line_col(code, "$hits = 0");
+        assert_eq!((def.0, def.1), (def_line, def_col));
+    }
+
     #[test]
     fn test_find_variable_definition_preg_match_output_argument() {
         let code = r#"<?php
@@ -8168,6 +9980,54 @@ function demo(string $value): void {
         );
     }
 
+    #[test]
+    fn test_local_variable_names_follow_function_scopes() {
+        let code = r#"<?php
+$global = 1;
+function helper(int $other): void { $inner = 2; }
+final class Report {
+    public function build(array $rows, string ...$tags): void {
+        [$first, [$second]] = $rows;
+        foreach ($rows as $key => ['id' => $id]) {}
+        static $cache = [];
+        $mapper = function (int $row) use ($first, &$total) {
+            $mapped = $row;
+            $in;
+        };
+        $fn = fn ($item) => $it;
+        $later = 3;
+        $ou;
+    }
+    public static function make(): void { $th; }
+}
+"#;
+        let names_at = |needle: &str| {
+            let (line, col) = find_line_col(code, needle);
+            parse_and_local_variable_names(
+                code,
+                line,
+                col + needle.trim_end_matches(';').len() as u32,
+            )
+        };
+
+        assert_eq!(
+            names_at("$ou;"),
+            vec![
+                "$this", "$rows", "$tags", "$first", "$second", "$key", "$id", "$cache", "$mapper",
+                "$fn", "$later"
+            ]
+        );
+        assert_eq!(
+            names_at("$in;"),
+            vec!["$this", "$row", "$first", "$total", "$mapped"]
+        );
+        let arrow = names_at("=> $it;");
+        assert_eq!(&arrow[..2], ["$this", "$item"]);
+        assert!(arrow.contains(&"$rows".to_string()), "{arrow:?}");
+        assert!(!arrow.contains(&"$later".to_string()), "{arrow:?}");
+        assert!(names_at("$th;").is_empty());
+    }
+
     #[test]
     fn test_resolve_global_constant_in_method_body() {
         let code = "<?php\nnamespace App;\n\nconst BUILD = 'dev';\n\nclass Demo {\n    public const VERSION = '1.0';\n\n    public function run(): string {\n        $value = BUILD;\n        return self::VERSION . $value;\n    }\n}\n";
@@ -8297,6 +10157,69 @@ class MyTest {
         assert_eq!(result4.ref_kind, RefKind::ClassName);
     }
 
+    #[test]
+    fn test_resolve_use_statement_alias_group_and_kinds() {
+        let resolve = |code: &str, needle: &str| {
+            let (line, col) = find_line_col(code, needle);
+            let result = parse_and_resolve(code, line, col).unwrap();
+            (result.fqn, result.ref_kind)
+        };
+
+        let aliased = "<?php\nuse App\\Service\\UserService as Users;\n";
+        assert_eq!(
+            resolve(aliased, "Users;"),
+            ("App\\Service\\UserService".to_string(), RefKind::ClassName)
+        );
+
+        let group = "<?php\nuse App\\{Service\\UserService, Mailer as Mail};\n";
+        assert_eq!(
+            resolve(group, "UserService"),
+            ("App\\Service\\UserService".to_string(), RefKind::ClassName)
+        );
+        assert_eq!(
+            resolve(group, "Service\\"),
+            ("App\\Service\\UserService".to_string(), RefKind::ClassName)
+        );
+        assert_eq!(
+            resolve(group, "Mailer"),
+            ("App\\Mailer".to_string(), RefKind::ClassName)
+        );
+        assert_eq!(
+            resolve(group, "App"),
+            ("App".to_string(), RefKind::NamespaceName)
+        );
+
+        let function = "<?php\nuse function App\\Support\\helper;\n";
+        assert_eq!(
+            resolve(function, "helper"),
+            ("App\\Support\\helper".to_string(), RefKind::FunctionCall)
+        );
+        assert_eq!(
+            resolve(function, "Support"),
+            ("App\\Support\\helper".to_string(), RefKind::FunctionCall)
+        );
+
+        let constant = "<?php\nuse const App\\Support\\{LIMIT, OFFSET};\n";
+        assert_eq!(
+            resolve(constant, "OFFSET"),
+            ("App\\Support\\OFFSET".to_string(), RefKind::GlobalConstant)
+        );
+
+        let mixed = "<?php\nuse App\\Support\\{function helper, const LIMIT, Config};\n";
+        assert_eq!(
+            resolve(mixed, "helper"),
+            ("App\\Support\\helper".to_string(), RefKind::FunctionCall)
+        );
+        assert_eq!(
+            resolve(mixed, "LIMIT"),
+            ("App\\Support\\LIMIT".to_string(), RefKind::GlobalConstant)
+        );
+        assert_eq!(
+            resolve(mixed, "Config"),
+            ("App\\Support\\Config".to_string(), RefKind::ClassName)
+        );
+    }
+
     #[test]
     fn test_resolve_new_qualified_name() {
         // new Assert\NotBlank — qualified name in object_creation_expression
@@ -8378,6 +10301,46 @@ class Foo {
         assert_eq!(result.ref_kind, RefKind::MethodCall);
     }
 
+    #[test]
+    fn test_resolve_closure_params_of_builtin_array_callbacks() {
+        let code = r#"<?php
+namespace App;
+
+use App\Entity\User;
+use App\Entity\Group;
+
+class Foo {
+    /**
+     * @param list<User> $users
+     * @param array<string, Group> $groups
+     */
+    public function build(array $users, array $groups): void {
+        array_map(fn ($user, $group) => $user->getName() . $group->getLabel(), $users, $groups);
+        array_filter($users, function ($user) { return $user->isActive(); });
+        array_filter($groups, fn ($name) => $name->notAnObject(), ARRAY_FILTER_USE_KEY);
+        usort($users, static fn ($left, $right) => $left->getAge() <=> $right->getRank());
+        array_reduce($groups, fn ($carry, $group) => $group->getSize(), 0);
+    }
+}
+"#;
+        for (needle, expected) in [
+            ("getName", "App\\Entity\\User::getName"),
+            ("getLabel", "App\\Entity\\Group::getLabel"),
+            ("isActive", "App\\Entity\\User::isActive"),
+            ("getAge", "App\\Entity\\User::getAge"),
+            ("getRank", "App\\Entity\\User::getRank"),
+            ("getSize", "App\\Entity\\Group::getSize"),
+        ] {
+            let (line, col) = find_line_col(code, needle);
+            let result = parse_and_resolve(code, line, col).unwrap();
+            assert_eq!(result.fqn, expected, "{needle}");
+        }
+
+        let (line, col) = find_line_col(code, "$name->");
+        let result = parse_and_infer_var_type_info_at(code, line, col + 1, "$name");
+        assert_eq!(result, Some(TypeInfo::Simple("string".to_string())));
+    }
+
     #[test]
     fn test_resolve_method_chain_static_return_type() {
         // Method chain: $qb->orderBy(...)->addOrderBy(...)
@@ -7,6 +7,7 @@ use crate::cst::{
     ancestor_field_contains, has_ancestor_before_scope, is_by_ref_output_argument_variable,
     is_foreach_header_declared_variable, node_contains,
 };
+use crate::queries;
 use php_lsp_types::{FileSymbols, PhpDoc, SymbolInfo, TypeInfo, UseKind};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -76,8 +77,10 @@ where
     // Check use statements
     check_use_statements(file_symbols, &resolver, &mut diagnostics);
 
-    // Walk CST for class and function references
-    walk_node_for_diagnostics(root, source, file_symbols, &resolver, &mut diagnostics);
+    // Check class and function references
+    queries::for_each_capture(&queries::SEMANTIC_CHECK_SITES, root, source, |node| {
+        check_node_for_diagnostics(node, source, file_symbols, &resolver, &mut diagnostics);
+    });
     check_unused_imports(root, source, file_symbols, &mut diagnostics);
     check_variable_diagnostics(root, source, file_symbols, &resolver, &mut diagnostics);
     check_duplicate_symbols_in_file(file_symbols, &mut diagnostics);
@@ -128,8 +131,8 @@ fn check_use_statements<F>(
     }
 }
 
-/// Recursively walk CST nodes to find class/function references.
-fn walk_node_for_diagnostics<F>(
+/// Check one class or function reference against the index.
+fn check_node_for_diagnostics<F>(
     node: tree_sitter::Node,
     source: &str,
     file_symbols: &FileSymbols,
@@ -159,14 +162,6 @@ fn walk_node_for_diagnostics<F>(
         }
         _ => {}
     }
-
-    // Recurse into children
-    let child_count = node.child_count();
-    for i in 0..child_count {
-        if let Some(child) = node.child(i) {
-            walk_node_for_diagnostics(child, source, file_symbols, resolver, diagnostics);
-        }
-    }
 }
 
 /// Check a class name in `new ClassName(...)`.
@@ -821,6 +816,9 @@ enum VariableDeclarationKind {
     Variable,
     ClosureUse,
     PromotedProperty,
+    /// `global $x;` or `static $x;`: the value outlives the call, so a
+    /// write-only binding is not dead.
+    GlobalOrStatic,
 }
 
 #[derive(Debug, Clone)]
@@ -1327,7 +1325,9 @@ fn report_variable_diagnostics<F>(
                 kind: SemanticDiagnosticKind::UnusedVariable,
             }),
             Some(
-                VariableDeclarationKind::ClosureUse | VariableDeclarationKind::PromotedProperty,
+                VariableDeclarationKind::ClosureUse
+                | VariableDeclarationKind::PromotedProperty
+                | VariableDeclarationKind::GlobalOrStatic,
             )
             | None => {}
         }
@@ -1517,7 +1517,7 @@ fn variable_declaration_kind(
             .is_some_and(|left| left.id() == node.id() || node_contains(left, node))
             .then_some(VariableDeclarationKind::Variable),
         "global_declaration" | "static_variable_declaration" => {
-            Some(VariableDeclarationKind::Variable)
+            Some(VariableDeclarationKind::GlobalOrStatic)
         }
         "anonymous_function_use_clause" => Some(VariableDeclarationKind::ClosureUse),
         _ if normalize_var_name(&source[parent.byte_range()]) == var_name
@@ -1713,21 +1713,23 @@ mod tests {
         Arc::new(SymbolInfo {
             name: String::new(),
             kind: PhpSymbolKind::Class,
-            fqn: String::new(),
+            fqn: Default::default(),
             range: (0, 0, 0, 0),
             selection_range: (0, 0, 0, 0),
-            uri: String::new(),
+            uri: Default::default(),
             visibility: php_lsp_types::Visibility::Public,
             modifiers: Default::default(),
             attributes: vec![],
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         })
     }
 
@@ -1735,10 +1737,10 @@ mod tests {
         Arc::new(SymbolInfo {
             name: fqn.rsplit('\\').next().unwrap_or(fqn).to_string(),
             kind: PhpSymbolKind::Function,
-            fqn: fqn.to_string(),
+            fqn: fqn.into(),
             range: (0, 0, 0, 0),
             selection_range: (0, 0, 0, 0),
-            uri: String::new(),
+            uri: Default::default(),
             visibility: php_lsp_types::Visibility::Public,
             modifiers: Default::default(),
             attributes: vec![],
@@ -1746,13 +1748,16 @@ mod tests {
             signature: Some(Signature {
                 params,
                 return_type: None,
+                throws: Vec::new(),
             }),
+            value: None,
             parent_fqn: None,
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         })
     }
 
@@ -1764,6 +1769,7 @@ mod tests {
             is_variadic: true,
             is_by_ref: false,
             is_promoted: false,
+            attributes: vec![],
         }
     }
 
@@ -2053,6 +2059,7 @@ helper();
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                         ParamInfo {
                             name: "b".to_string(),
@@ -2061,6 +2068,7 @@ helper();
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                     ],
                 ))
@@ -2102,6 +2110,7 @@ helper("x", "y");
                         is_variadic: false,
                         is_by_ref: false,
                         is_promoted: false,
+                        attributes: vec![],
                     }],
                 ))
             } else {
@@ -2219,6 +2228,7 @@ preg_replace_callback('/x/', function(){}, 'input');
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                         ParamInfo {
                             name: "callback".to_string(),
@@ -2227,6 +2237,7 @@ preg_replace_callback('/x/', function(){}, 'input');
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                         ParamInfo {
                             name: "subject".to_string(),
@@ -2235,6 +2246,7 @@ preg_replace_callback('/x/', function(){}, 'input');
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                         ParamInfo {
                             name: "limit".to_string(),
@@ -2243,6 +2255,7 @@ preg_replace_callback('/x/', function(){}, 'input');
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                         ParamInfo {
                             name: "count".to_string(),
@@ -2251,6 +2264,7 @@ preg_replace_callback('/x/', function(){}, 'input');
                             is_variadic: false,
                             is_by_ref: true,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                         ParamInfo {
                             name: "flags".to_string(),
@@ -2259,6 +2273,7 @@ preg_replace_callback('/x/', function(){}, 'input');
                             is_variadic: false,
                             is_by_ref: false,
                             is_promoted: false,
+                            attributes: vec![],
                         },
                     ],
                 ))
@@ -2430,6 +2445,43 @@ function run(string $used, string $unusedParam): void {
         );
     }
 
+    #[test]
+    fn test_global_and_static_variables_are_declarations() {
+        let code = r#"<?php
+function connect(): void {
+    global $db, $config;
+    $db = new PDO($config['dsn']);
+}
+
+function hits(): int {
+    static $count = 0, $seen;
+    $seen = true;
+    return ++$count;
+}
+
+function remember(string $key, $value): void {
+    static $cache = [];
+    $cache[$key] = $value;
+}
+"#;
+        let diags = parse_and_check(code, |_fqn| Some(dummy_symbol()));
+        let variable_diags: Vec<_> = diags
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d.kind,
+                    SemanticDiagnosticKind::UndefinedVariable
+                        | SemanticDiagnosticKind::UnusedVariable
+                )
+            })
+            .collect();
+        assert!(
+            variable_diags.is_empty(),
+            "global/static variables should not be reported, got: {:?}",
+            variable_diags
+        );
+    }
+
     #[test]
     fn test_null_coalesce_probe_does_not_report_undefined_variable() {
         let code = r#"<?php
@@ -3,7 +3,7 @@
 //! Produces LSP-compatible relative token data while keeping the parser crate
 //! independent from the concrete server transport crate.
 
-use crate::utf16::Utf16LineIndex;
+use crate::position::{LspLineIndex, PositionEncoding};
 use tree_sitter::{Node, Tree};
 
 pub const SEMANTIC_TOKEN_TYPES: &[&str] = &[
@@ -81,10 +81,14 @@ struct AbsoluteSemanticToken {
     token_modifiers_bitset: u32,
 }
 
-pub fn extract_semantic_tokens(tree: &Tree, source: &str) -> Vec<SemanticTokenData> {
-    let utf16_index = Utf16LineIndex::new(source);
+pub fn extract_semantic_tokens(
+    tree: &Tree,
+    source: &str,
+    encoding: PositionEncoding,
+) -> Vec<SemanticTokenData> {
+    let line_index = LspLineIndex::new(source, encoding);
     let mut absolute_tokens = Vec::new();
-    collect_node_tokens(tree.root_node(), source, &utf16_index, &mut absolute_tokens);
+    collect_node_tokens(tree.root_node(), source, &line_index, &mut absolute_tokens);
 
     let absolute_tokens = normalize_tokens(absolute_tokens);
     encode_relative_tokens(&absolute_tokens)
@@ -93,14 +97,14 @@ pub fn extract_semantic_tokens(tree: &Tree, source: &str) -> Vec<SemanticTokenDa
 fn collect_node_tokens(
     node: Node,
     source: &str,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     tokens: &mut Vec<AbsoluteSemanticToken>,
 ) {
     if let Some((token_type, token_modifiers_bitset)) = classify_node(node, source) {
         push_node_tokens(
             node,
             source,
-            utf16_index,
+            line_index,
             token_type,
             token_modifiers_bitset,
             tokens,
@@ -110,14 +114,14 @@ fn collect_node_tokens(
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_node_tokens(child, source, utf16_index, tokens);
+        collect_node_tokens(child, source, line_index, tokens);
     }
 }
 
 fn push_node_tokens(
     node: Node,
     source: &str,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     token_type: u32,
     token_modifiers_bitset: u32,
     tokens: &mut Vec<AbsoluteSemanticToken>,
@@ -132,7 +136,7 @@ fn push_node_tokens(
             start_line,
             start.column as u32,
             end.column as u32,
-            utf16_index,
+            line_index,
             token_type,
             token_modifiers_bitset,
             tokens,
@@ -155,7 +159,7 @@ fn push_node_tokens(
             line,
             start_col,
             end_col,
-            utf16_index,
+            line_index,
             token_type,
             token_modifiers_bitset,
             tokens,
@@ -167,7 +171,7 @@ fn push_line_token(
     line: u32,
     start_byte_col: u32,
     end_byte_col: u32,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     token_type: u32,
     token_modifiers_bitset: u32,
     tokens: &mut Vec<AbsoluteSemanticToken>,
@@ -176,8 +180,8 @@ fn push_line_token(
         return;
     }
 
-    let start = utf16_index.byte_col_to_utf16(line, start_byte_col);
-    let end = utf16_index.byte_col_to_utf16(line, end_byte_col);
+    let start = line_index.byte_col_to_lsp(line, start_byte_col);
+    let end = line_index.byte_col_to_lsp(line, end_byte_col);
     let length = end.saturating_sub(start);
     if length == 0 {
         return;
@@ -637,7 +641,7 @@ mod tests {
         let mut parser = FileParser::new();
         parser.parse_full(source);
         let tree = parser.tree().expect("tree");
-        let relative = extract_semantic_tokens(tree, source);
+        let relative = extract_semantic_tokens(tree, source, PositionEncoding::Utf16);
 
         let mut line = 0u32;
         let mut start = 0u32;
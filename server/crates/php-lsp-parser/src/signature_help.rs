@@ -14,6 +14,8 @@ pub struct SignatureHelpContext {
     pub symbol: SymbolAtPosition,
     /// Zero-based active parameter index.
     pub active_parameter: usize,
+    /// Number of arguments in the call, counting the one being typed.
+    pub argument_count: usize,
 }
 
 /// Find signature-help context at a source position.
@@ -46,9 +48,13 @@ pub fn signature_help_context_at_position(
                         file_symbols,
                         resolver,
                     )?;
+                    let active_parameter = active_parameter_index(arguments, source, offset);
+                    let argument_count =
+                        active_parameter_index(arguments, source, arguments.end_byte()) + 1;
                     return Some(SignatureHelpContext {
                         symbol,
-                        active_parameter: active_parameter_index(arguments, source, offset),
+                        active_parameter,
+                        argument_count: argument_count.max(active_parameter + 1),
                     });
                 }
             }
@@ -165,6 +171,15 @@ mod tests {
         let ctx = context_for(source, 2, 7);
         assert_eq!(ctx.symbol.fqn, "foo");
         assert_eq!(ctx.active_parameter, 1);
+        assert_eq!(ctx.argument_count, 2);
+    }
+
+    #[test]
+    fn counts_arguments_after_the_cursor() {
+        let source = "<?php\nfunction foo($a, $b, $c) {}\nfoo(1, [2, 3], 4);\n";
+        let ctx = context_for(source, 2, 4);
+        assert_eq!(ctx.active_parameter, 0);
+        assert_eq!(ctx.argument_count, 3);
     }
 
     #[test]
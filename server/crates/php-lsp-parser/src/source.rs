@@ -0,0 +1,124 @@
+//! Decoding PHP files read from disk.
+//!
+//! PHP itself treats source as bytes, so legacy projects still ship Latin-1
+//! files and files saved with a UTF-8 byte order mark. Editors decode both
+//! before handing text to the server, so on-disk reads must match what the
+//! editor shows or every indexed position on the affected lines drifts:
+//!
+//! - A leading UTF-8 BOM is stripped; editors hide it and do not count it
+//!   in line 0 columns.
+//! - Invalid UTF-8 is decoded as Latin-1, one character per byte, instead of
+//!   `U+FFFD` replacement, which keeps identifiers intact and columns aligned
+//!   with an editor that opens the file as Latin-1.
+//! - Files with NUL bytes are treated as binary and rejected.
+//!
+//! A `#!` shebang line needs no special handling: tree-sitter parses it as
+//! inline text before the opening `<?php` tag.
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// How much of a file is inspected for NUL bytes when sniffing binary content.
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Character encoding a source file was decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceEncoding {
+    Utf8,
+    Latin1,
+}
+
+/// Why a file could not be decoded as PHP source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceDecodeError {
+    Binary,
+}
+
+impl std::fmt::Display for SourceDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Binary => f.write_str("file looks binary (contains NUL bytes)"),
+        }
+    }
+}
+
+impl std::error::Error for SourceDecodeError {}
+
+/// Source text decoded from file bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedSource {
+    pub text: String,
+    pub encoding: SourceEncoding,
+    /// Whether a UTF-8 BOM was stripped from the start of the file.
+    pub had_bom: bool,
+}
+
+/// Decode PHP file bytes the way an editor would show them.
+pub fn decode_php_source(bytes: &[u8]) -> Result<DecodedSource, SourceDecodeError> {
+    let (bytes, had_bom) = match bytes.strip_prefix(UTF8_BOM) {
+        Some(rest) => (rest, true),
+        None => (bytes, false),
+    };
+    if bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+        return Err(SourceDecodeError::Binary);
+    }
+
+    let (text, encoding) = match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), SourceEncoding::Utf8),
+        Err(_) => (
+            bytes.iter().map(|&byte| char::from(byte)).collect(),
+            SourceEncoding::Latin1,
+        ),
+    };
+    Ok(DecodedSource {
+        text,
+        encoding,
+        had_bom,
+    })
+}
+
+/// Read and decode a PHP file. Binary files fail with `InvalidData`.
+pub fn read_php_source(path: &std::path::Path) -> std::io::Result<DecodedSource> {
+    let bytes = std::fs::read(path)?;
+    decode_php_source(&bytes)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_strips_utf8_bom() {
+        let decoded = decode_php_source(b"\xEF\xBB\xBF<?php\nclass A {}\n").unwrap();
+
+        assert_eq!(decoded.text, "<?php\nclass A {}\n");
+        assert_eq!(decoded.encoding, SourceEncoding::Utf8);
+        assert!(decoded.had_bom);
+    }
+
+    #[test]
+    fn test_decode_falls_back_to_latin1_one_char_per_byte() {
+        let decoded = decode_php_source(b"<?php\n$caf\xe9 = '\xff';\n").unwrap();
+
+        assert_eq!(decoded.text, "<?php\n$café = 'ÿ';\n");
+        assert_eq!(decoded.encoding, SourceEncoding::Latin1);
+        assert!(!decoded.had_bom);
+    }
+
+    #[test]
+    fn test_decode_keeps_valid_utf8_and_shebang() {
+        let source = "#!/usr/bin/env php\n<?php\necho 'привет';\n";
+        let decoded = decode_php_source(source.as_bytes()).unwrap();
+
+        assert_eq!(decoded.text, source);
+        assert_eq!(decoded.encoding, SourceEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_decode_rejects_binary_content() {
+        assert_eq!(
+            decode_php_source(b"\x7fELF\x02\x01\x01\x00\x00"),
+            Err(SourceDecodeError::Binary)
+        );
+    }
+}
@@ -3,9 +3,11 @@
 //! Walks the CST and produces `FileSymbols` containing all classes, interfaces,
 //! traits, enums, functions, methods, properties, constants, namespace and use statements.
 
+use crate::cst::{is_qualified_identifier, plain_string_literal_content};
+use crate::queries;
 use php_lsp_types::*;
-use std::collections::HashSet;
-use tree_sitter::{Node, Tree};
+use std::collections::{HashMap, HashSet};
+use tree_sitter::{InputEdit, Node, Range, Tree};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct PhpSymbolExtractionVersion {
@@ -34,37 +36,318 @@ fn extract_file_symbols_with_php_version(
     uri: &str,
     php_version: Option<PhpSymbolExtractionVersion>,
 ) -> FileSymbols {
+    extract_symbol_chunks(tree, source, uri, php_version).0
+}
+
+/// The symbols and use statements one statement contributed to a
+/// `FileSymbols`, in extraction order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SymbolChunk {
+    start_byte: usize,
+    end_byte: usize,
+    scope: SymbolScope,
+    symbols: usize,
+    use_statements: usize,
+}
+
+/// Namespace context a statement's names are resolved in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SymbolScope {
+    /// Namespace the statement is declared in.
+    namespace: Option<String>,
+    /// `FileSymbols::namespace` at that point: the last namespace name seen.
+    file_namespace: Option<String>,
+}
+
+/// A statement that owns symbols: a top-level statement or a statement in a
+/// braced namespace body.
+struct SymbolUnit<'t> {
+    node: Node<'t>,
+    scope: SymbolScope,
+}
+
+/// Extract all symbols along with the chunk each statement contributed.
+/// Chunks are `None` when enum member recovery moved symbols across
+/// statements, which rules out patching.
+pub(crate) fn extract_file_symbol_chunks(
+    tree: &Tree,
+    source: &str,
+    uri: &str,
+) -> (FileSymbols, Option<Vec<SymbolChunk>>) {
+    extract_symbol_chunks(tree, source, uri, None)
+}
+
+fn extract_symbol_chunks(
+    tree: &Tree,
+    source: &str,
+    uri: &str,
+    php_version: Option<PhpSymbolExtractionVersion>,
+) -> (FileSymbols, Option<Vec<SymbolChunk>>) {
     let mut result = FileSymbols::default();
     let root = tree.root_node();
     extract_file_level_phpdoc_aliases(root, source, &mut result);
 
-    // Walk top-level children of program node.
-    // Handle namespace-without-braces by tracking current namespace.
-    let mut current_ns: Option<String> = None;
+    let (units, file_namespace) = symbol_units(root, source);
+    let mut chunks = Vec::with_capacity(units.len());
+    for unit in units {
+        let (symbols, use_statements) = (result.symbols.len(), result.use_statements.len());
+        extract_symbol_unit(&unit, source, uri, &mut result, php_version);
+        chunks.push(SymbolChunk {
+            start_byte: unit.node.start_byte(),
+            end_byte: unit.node.end_byte(),
+            scope: unit.scope,
+            symbols: result.symbols.len() - symbols,
+            use_statements: result.use_statements.len() - use_statements,
+        });
+    }
+    result.namespace = file_namespace;
+    let chunks = finish_symbol_chunks(&mut result, chunks);
+
+    (result, chunks)
+}
+
+/// Re-extract symbols after an incremental edit, only for the statements
+/// that overlap the edit or a range tree-sitter reports as changed. Symbols
+/// of every other statement are moved over from `previous`, shifted by the
+/// lines the edit added or removed.
+///
+/// Names resolve against the use statements before them, so an edit that
+/// touches a use statement, like one that truncates an enum body, returns
+/// `None` and the file has to be extracted in full.
+pub(crate) fn patch_file_symbols(
+    previous: FileSymbols,
+    chunks: Vec<SymbolChunk>,
+    tree: &Tree,
+    source: &str,
+    uri: &str,
+    edit: &InputEdit,
+    changed_ranges: &[Range],
+) -> Option<(FileSymbols, Option<Vec<SymbolChunk>>)> {
+    let mut result = FileSymbols::default();
+    let root = tree.root_node();
+    extract_file_level_phpdoc_aliases(root, source, &mut result);
+
+    let (units, file_namespace) = symbol_units(root, source);
+    // A truncated enum body takes the statements after it as members.
+    if units
+        .iter()
+        .any(|unit| unit.node.kind() == "enum_declaration" && unit.node.has_error())
+    {
+        return None;
+    }
+
+    let line_delta = edit.new_end_position.row as i64 - edit.old_end_position.row as i64;
+    let mut previous_symbols = previous.symbols.into_iter();
+    let mut previous_uses = previous.use_statements.into_iter();
+    let mut previous_chunks = chunks.into_iter().peekable();
+    let mut new_chunks = Vec::with_capacity(units.len());
+
+    for unit in units {
+        let node = unit.node;
+        let (symbols, use_statements) = (result.symbols.len(), result.use_statements.len());
+        let mut reused = false;
+        if !symbol_unit_is_dirty(node, edit, changed_ranges) {
+            // Chunks are ordered like the statements, so skip the ones whose
+            // statement was removed or re-extracted.
+            while let Some(chunk) = previous_chunks.next_if(|chunk| {
+                chunk_start_after_edit(chunk, edit).is_none_or(|start| start <= node.start_byte())
+            }) {
+                let matches = chunk_start_after_edit(&chunk, edit) == Some(node.start_byte())
+                    && chunk.end_byte - chunk.start_byte == node.byte_range().len()
+                    && chunk.scope == unit.scope;
+                if !matches {
+                    if chunk.use_statements > 0 {
+                        return None;
+                    }
+                    previous_symbols.by_ref().take(chunk.symbols).for_each(drop);
+                    continue;
+                }
+                let delta = if chunk.start_byte >= edit.old_end_byte {
+                    line_delta
+                } else {
+                    0
+                };
+                result.symbols.extend(
+                    previous_symbols
+                        .by_ref()
+                        .take(chunk.symbols)
+                        .map(|symbol| shift_symbol_lines(symbol, delta)),
+                );
+                result.use_statements.extend(
+                    previous_uses
+                        .by_ref()
+                        .take(chunk.use_statements)
+                        .map(|mut use_stmt| {
+                            shift_range_lines(&mut use_stmt.range, delta);
+                            use_stmt
+                        }),
+                );
+                reused = true;
+                break;
+            }
+        }
+        if !reused {
+            extract_symbol_unit(&unit, source, uri, &mut result, None);
+            if result.use_statements.len() > use_statements {
+                return None;
+            }
+        }
+        new_chunks.push(SymbolChunk {
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            scope: unit.scope,
+            symbols: result.symbols.len() - symbols,
+            use_statements: result.use_statements.len() - use_statements,
+        });
+    }
+    // Use statements of removed statements are gone for good.
+    if previous_uses.next().is_some() {
+        return None;
+    }
+    // A class declared twice skips PHPDoc members the first one already has.
+    let mut class_likes = HashSet::new();
+    if !result
+        .symbols
+        .iter()
+        .filter(|symbol| {
+            matches!(
+                symbol.kind,
+                PhpSymbolKind::Class
+                    | PhpSymbolKind::Interface
+                    | PhpSymbolKind::Trait
+                    | PhpSymbolKind::Enum
+            )
+        })
+        .all(|symbol| class_likes.insert(&symbol.fqn))
+    {
+        return None;
+    }
+    result.namespace = file_namespace;
+    let chunks = finish_symbol_chunks(&mut result, new_chunks);
+
+    Some((result, chunks))
+}
+
+fn extract_symbol_unit(
+    unit: &SymbolUnit,
+    source: &str,
+    uri: &str,
+    result: &mut FileSymbols,
+    php_version: Option<PhpSymbolExtractionVersion>,
+) {
+    if result.namespace != unit.scope.file_namespace {
+        result.namespace = unit.scope.file_namespace.clone();
+    }
+    extract_from_node(
+        unit.node,
+        source,
+        uri,
+        result,
+        &unit.scope.namespace,
+        php_version,
+    );
+}
+
+/// Walk top-level children of the program node, returning the statements
+/// with their scope and the namespace of the file. Handle
+/// namespace-without-braces by tracking the current namespace.
+fn symbol_units<'t>(root: Node<'t>, source: &str) -> (Vec<SymbolUnit<'t>>, Option<String>) {
+    let mut units = Vec::new();
+    let mut scope = SymbolScope::default();
     let mut cursor = root.walk();
     for child in root.children(&mut cursor) {
         match child.kind() {
             "namespace_definition" => {
                 // Extract namespace name from the namespace_name child
                 let ns_name = find_namespace_name(child, source);
-                if let Some(ns) = &ns_name {
-                    result.namespace = Some(ns.clone());
+                if ns_name.is_some() {
+                    scope.file_namespace = ns_name.clone();
                 }
-                current_ns = ns_name.clone();
+                scope.namespace = ns_name;
 
                 // If namespace has braces, recurse into body
                 if let Some(body) = child.child_by_field_name("body") {
-                    extract_children(body, source, uri, &mut result, &ns_name, php_version);
+                    let mut body_cursor = body.walk();
+                    units.extend(body.children(&mut body_cursor).map(|node| SymbolUnit {
+                        node,
+                        scope: scope.clone(),
+                    }));
                 }
-                // If no body — namespace applies to rest of file (current_ns is set)
-            }
-            _ => {
-                extract_from_node(child, source, uri, &mut result, &current_ns, php_version);
+                // If no body — namespace applies to rest of file (scope is set)
             }
+            _ => units.push(SymbolUnit {
+                node: child,
+                scope: scope.clone(),
+            }),
         }
     }
+    (units, scope.file_namespace)
+}
 
-    result
+fn finish_symbol_chunks(
+    result: &mut FileSymbols,
+    chunks: Vec<SymbolChunk>,
+) -> Option<Vec<SymbolChunk>> {
+    let extracted = result.symbols.len();
+    drop_recovered_enum_members(result);
+    (result.symbols.len() == extracted).then_some(chunks)
+}
+
+/// Whether a statement, or the comments and attributes in front of it that
+/// its extraction reads, overlaps the edit or a changed range. Statements
+/// after the edit must also start below its last line, so that only their
+/// lines and not their columns moved.
+fn symbol_unit_is_dirty(node: Node, edit: &InputEdit, changed_ranges: &[Range]) -> bool {
+    let mut start = node;
+    while let Some(prev) = start.prev_sibling() {
+        if prev.is_named() && !matches!(prev.kind(), "comment" | "attribute_list" | "ERROR") {
+            break;
+        }
+        start = prev;
+    }
+    let (start_byte, end_byte) = (start.start_byte(), node.end_byte());
+    let overlaps = |from: usize, to: usize| start_byte <= to && from <= end_byte;
+
+    overlaps(edit.start_byte, edit.new_end_byte)
+        || changed_ranges
+            .iter()
+            .any(|range| overlaps(range.start_byte, range.end_byte))
+        || (start_byte > edit.new_end_byte
+            && start.start_position().row <= edit.new_end_position.row)
+}
+
+/// Where a chunk of the pre-edit symbols starts after the edit, or `None`
+/// when the edit overlaps it.
+fn chunk_start_after_edit(chunk: &SymbolChunk, edit: &InputEdit) -> Option<usize> {
+    if chunk.end_byte < edit.start_byte {
+        Some(chunk.start_byte)
+    } else if chunk.start_byte > edit.old_end_byte {
+        Some(chunk.start_byte - edit.old_end_byte + edit.new_end_byte)
+    } else {
+        None
+    }
+}
+
+fn shift_symbol_lines(mut symbol: SymbolInfo, delta: i64) -> SymbolInfo {
+    if delta == 0 {
+        return symbol;
+    }
+    shift_range_lines(&mut symbol.range, delta);
+    shift_range_lines(&mut symbol.selection_range, delta);
+    let param_attributes = symbol
+        .signature
+        .iter_mut()
+        .flat_map(|signature| &mut signature.params)
+        .flat_map(|param| &mut param.attributes);
+    for attribute in symbol.attributes.iter_mut().chain(param_attributes) {
+        shift_range_lines(&mut attribute.range, delta);
+    }
+    symbol
+}
+
+fn shift_range_lines(range: &mut (u32, u32, u32, u32), delta: i64) {
+    range.0 = (range.0 as i64 + delta) as u32;
+    range.2 = (range.2 as i64 + delta) as u32;
 }
 
 fn extract_file_level_phpdoc_aliases(root: Node, source: &str, result: &mut FileSymbols) {
@@ -130,6 +413,8 @@ fn find_namespace_name(node: Node, source: &str) -> Option<String> {
     None
 }
 
+/// Extract the declarations in the subtree of `node`. A declaration is not
+/// searched for nested declarations: those belong to its body.
 fn extract_from_node(
     node: Node,
     source: &str,
@@ -138,6 +423,30 @@ fn extract_from_node(
     current_ns: &Option<String>,
     php_version: Option<PhpSymbolExtractionVersion>,
 ) {
+    // Most statements are declarations themselves; skip the query for those.
+    if extract_declaration(node, source, uri, result, current_ns, php_version) {
+        return;
+    }
+    let mut extracted_end = 0;
+    queries::for_each_capture(&queries::SYMBOL_DECLARATIONS, node, source, |decl| {
+        if decl.start_byte() < extracted_end {
+            return;
+        }
+        if extract_declaration(decl, source, uri, result, current_ns, php_version) {
+            extracted_end = decl.end_byte();
+        }
+    });
+}
+
+/// Extract one declaration, returning `false` for a call that is not `define()`.
+fn extract_declaration(
+    node: Node,
+    source: &str,
+    uri: &str,
+    result: &mut FileSymbols,
+    current_ns: &Option<String>,
+    php_version: Option<PhpSymbolExtractionVersion>,
+) -> bool {
     match node.kind() {
         "namespace_use_declaration" => {
             extract_use_statements(node, source, result, current_ns);
@@ -192,25 +501,12 @@ fn extract_from_node(
         "const_declaration" => {
             extract_global_constants(node, source, uri, result, current_ns, php_version);
         }
-        _ => {
-            // Recurse into children
-            extract_children(node, source, uri, result, current_ns, php_version);
+        "function_call_expression" if is_define_call(node, source) => {
+            extract_define_constant(node, source, uri, result, current_ns);
         }
+        _ => return false,
     }
-}
-
-fn extract_children(
-    node: Node,
-    source: &str,
-    uri: &str,
-    result: &mut FileSymbols,
-    current_ns: &Option<String>,
-    php_version: Option<PhpSymbolExtractionVersion>,
-) {
-    let mut cursor = node.walk();
-    for child in node.children(&mut cursor) {
-        extract_from_node(child, source, uri, result, current_ns, php_version);
-    }
+    true
 }
 
 /// Extract use statements from a `namespace_use_declaration`.
@@ -242,7 +538,19 @@ fn extract_single_use_clause(
     kind: UseKind,
     current_ns: &Option<String>,
 ) {
-    let mut fqn: Option<String> = None;
+    if let Some((fqn, alias)) = use_clause_name_and_alias(clause, source) {
+        result.use_statements.push(UseStatement {
+            fqn,
+            alias,
+            kind,
+            namespace: current_ns.clone(),
+            range: use_clause_range(clause),
+        });
+    }
+}
+
+fn use_clause_name_and_alias(clause: Node, source: &str) -> Option<(String, Option<String>)> {
+    let mut name: Option<String> = None;
     let mut alias: Option<String> = None;
     let mut saw_as = false;
 
@@ -250,7 +558,7 @@ fn extract_single_use_clause(
     for child in clause.children(&mut cursor) {
         match child.kind() {
             "qualified_name" | "namespace_name" | "name" if !saw_as => {
-                fqn = Some(node_text(child, source).to_string());
+                name = Some(node_text(child, source).to_string());
             }
             "as" => {
                 saw_as = true;
@@ -262,25 +570,22 @@ fn extract_single_use_clause(
         }
     }
 
-    if let Some(fqn) = fqn {
-        let sp = clause.start_position();
-        let ep = clause.end_position();
-        let range = (
-            sp.row as u32,
-            sp.column as u32,
-            ep.row as u32,
-            ep.column as u32,
-        );
-        result.use_statements.push(UseStatement {
-            fqn,
-            alias,
-            kind,
-            namespace: current_ns.clone(),
-            range,
-        });
-    }
+    name.map(|name| (name, alias))
 }
 
+fn use_clause_range(clause: Node) -> (u32, u32, u32, u32) {
+    let sp = clause.start_position();
+    let ep = clause.end_position();
+    (
+        sp.row as u32,
+        sp.column as u32,
+        ep.row as u32,
+        ep.column as u32,
+    )
+}
+
+/// Extract the clauses of a group use. The CST structure is:
+/// namespace_use_declaration -> namespace_name(prefix), namespace_use_group
 fn extract_use_group(
     group: Node,
     parent: Node,
@@ -289,43 +594,44 @@ fn extract_use_group(
     kind: UseKind,
     current_ns: &Option<String>,
 ) {
-    // Get prefix from parent
+    // The prefix is not always exposed as a field, so fall back to the name
+    // node in front of the group.
     let prefix = parent
         .child_by_field_name("prefix")
-        .map(|n| node_text(n, source).to_string())
+        .or_else(|| {
+            let mut cursor = parent.walk();
+            let prefix = parent
+                .children(&mut cursor)
+                .take_while(|child| child.id() != group.id())
+                .filter(|child| {
+                    matches!(child.kind(), "namespace_name" | "qualified_name" | "name")
+                })
+                .last();
+            prefix
+        })
+        .map(|n| node_text(n, source).trim_end_matches('\\').to_string())
         .unwrap_or_default();
 
     let mut cursor = group.walk();
     for child in group.children(&mut cursor) {
-        if child.kind() == "namespace_use_clause" {
-            if let Some(name_node) = child.child_by_field_name("name") {
-                let name = node_text(name_node, source);
-                let fqn = if prefix.is_empty() {
-                    name.to_string()
-                } else {
-                    format!("{}\\{}", prefix, name)
-                };
-                let alias = child
-                    .child_by_field_name("alias")
-                    .map(|n| node_text(n, source).to_string());
-
-                let sp = child.start_position();
-                let ep = child.end_position();
-                let range = (
-                    sp.row as u32,
-                    sp.column as u32,
-                    ep.row as u32,
-                    ep.column as u32,
-                );
-                result.use_statements.push(UseStatement {
-                    fqn,
-                    alias,
-                    kind,
-                    namespace: current_ns.clone(),
-                    range,
-                });
-            }
+        if child.kind() != "namespace_use_clause" {
+            continue;
         }
+        let Some((name, alias)) = use_clause_name_and_alias(child, source) else {
+            continue;
+        };
+        let fqn = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}\\{}", prefix, name)
+        };
+        result.use_statements.push(UseStatement {
+            fqn,
+            alias,
+            kind,
+            namespace: current_ns.clone(),
+            range: use_clause_range(child),
+        });
     }
 }
 
@@ -447,6 +753,99 @@ fn extract_trait_use_clauses(body: Node, source: &str, file_symbols: &FileSymbol
     result
 }
 
+/// Extract `insteadof`/`as` rules from trait `use A, B { ... }` blocks.
+fn extract_trait_adaptations(
+    body: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> Vec<TraitAdaptation> {
+    let mut result = Vec::new();
+    let mut cursor = body.walk();
+    for use_list in body
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "use_declaration")
+        .filter_map(|declaration| {
+            let mut inner_cursor = declaration.walk();
+            let use_list = declaration
+                .children(&mut inner_cursor)
+                .find(|inner| inner.kind() == "use_list");
+            use_list
+        })
+    {
+        let mut clause_cursor = use_list.walk();
+        for clause in use_list.named_children(&mut clause_cursor) {
+            let mut parts_cursor = clause.walk();
+            let parts: Vec<Node> = clause.named_children(&mut parts_cursor).collect();
+            let Some((target, rest)) = parts.split_first() else {
+                continue;
+            };
+            let (trait_fqn, method) = match target.kind() {
+                "class_constant_access_expression" => {
+                    let mut target_cursor = target.walk();
+                    let names: Vec<Node> = target.named_children(&mut target_cursor).collect();
+                    let [trait_name, method_name] = names.as_slice() else {
+                        continue;
+                    };
+                    (
+                        Some(resolve_class_name_in_file(
+                            node_text(*trait_name, source),
+                            file_symbols,
+                        )),
+                        node_text(*method_name, source).to_string(),
+                    )
+                }
+                "name" => (None, node_text(*target, source).to_string()),
+                _ => continue,
+            };
+
+            let kind = match clause.kind() {
+                "use_instead_of_clause" => {
+                    // tree-sitter-php wraps all but the last name of
+                    // `insteadof B, C` in ERROR nodes.
+                    let mut excluded = Vec::new();
+                    for part in rest {
+                        let mut error_cursor = part.walk();
+                        let names: Vec<Node> = if part.kind() == "ERROR" {
+                            part.named_children(&mut error_cursor).collect()
+                        } else {
+                            vec![*part]
+                        };
+                        excluded.extend(
+                            names
+                                .into_iter()
+                                .filter(|name| matches!(name.kind(), "name" | "qualified_name"))
+                                .map(|name| {
+                                    resolve_class_name_in_file(
+                                        node_text(name, source),
+                                        file_symbols,
+                                    )
+                                }),
+                        );
+                    }
+                    TraitAdaptationKind::InsteadOf { excluded }
+                }
+                "use_as_clause" => TraitAdaptationKind::Alias {
+                    alias: rest
+                        .iter()
+                        .find(|part| part.kind() == "name")
+                        .map(|name| node_text(*name, source).to_string()),
+                    visibility: rest
+                        .iter()
+                        .any(|part| part.kind() == "visibility_modifier")
+                        .then(|| extract_visibility(clause, source)),
+                },
+                _ => continue,
+            };
+            result.push(TraitAdaptation {
+                trait_fqn,
+                method,
+                kind,
+            });
+        }
+    }
+    result
+}
+
 /// Extract a class-like declaration (class, interface, trait, enum).
 fn extract_class_like(
     node: Node,
@@ -469,7 +868,7 @@ fn extract_class_like(
     let fqn = make_fqn(current_ns, &name);
 
     let modifiers = extract_modifiers(node, source);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
     let doc_comment_node = find_doc_comment_node(node, source);
     let doc_comment = doc_comment_node
         .as_ref()
@@ -498,12 +897,15 @@ fn extract_class_like(
     let trait_fqns = body_node
         .map(|body| extract_trait_use_clauses(body, source, result))
         .unwrap_or_default();
+    let trait_adaptations = body_node
+        .map(|body| extract_trait_adaptations(body, source, result))
+        .unwrap_or_default();
 
     let sym = SymbolInfo {
         name: name.clone(),
-        fqn: fqn.clone(),
+        fqn: fqn.clone().into(),
         kind,
-        uri: uri.to_string(),
+        uri: uri.into(),
         range: node_range(node),
         selection_range: node_range(name_node),
         visibility: Visibility::Public,
@@ -511,12 +913,14 @@ fn extract_class_like(
         attributes,
         doc_comment: doc_comment.clone(),
         signature: None,
+        value: None,
         parent_fqn: None,
         extends: extends_fqns,
         implements: implements_fqns,
         traits: trait_fqns,
         templates,
         template_bindings,
+        trait_adaptations,
     };
     result.symbols.push(sym);
 
@@ -526,7 +930,18 @@ fn extract_class_like(
     }
 
     if kind == PhpSymbolKind::Enum {
-        extract_enum_builtin_properties(node, source, uri, result, &fqn, name_node, body_node);
+        if let Some(body) = body_node {
+            extract_enum_members_after_truncated_body(
+                node,
+                body,
+                source,
+                uri,
+                result,
+                &fqn,
+                php_version,
+            );
+        }
+        extract_enum_builtin_members(node, source, uri, result, &fqn, name_node, body_node);
     }
 
     if let (Some(doc), Some(doc_node)) = (doc_comment.as_deref(), doc_comment_node) {
@@ -586,9 +1001,9 @@ fn extract_phpdoc_virtual_properties(
 
         result.symbols.push(SymbolInfo {
             name: property.name.clone(),
-            fqn: format!("{}::${}", parent_fqn, property.name),
+            fqn: format!("{}::${}", parent_fqn, property.name).into(),
             kind: PhpSymbolKind::Property,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: property_range,
             selection_range: property_range,
             visibility: Visibility::Public,
@@ -598,13 +1013,16 @@ fn extract_phpdoc_virtual_properties(
             signature: Some(Signature {
                 params: vec![],
                 return_type: Some(type_info),
+                throws: Vec::new(),
             }),
-            parent_fqn: Some(parent_fqn.to_string()),
+            value: None,
+            parent_fqn: Some(parent_fqn.into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         });
     }
 }
@@ -642,9 +1060,9 @@ fn extract_phpdoc_virtual_methods(
 
         result.symbols.push(SymbolInfo {
             name: method.name.clone(),
-            fqn: format!("{}::{}", parent_fqn, method.name),
+            fqn: format!("{}::{}", parent_fqn, method.name).into(),
             kind: PhpSymbolKind::Method,
-            uri: uri.to_string(),
+            uri: uri.into(),
             range: method_range,
             selection_range: method_range,
             visibility: Visibility::Public,
@@ -657,18 +1075,120 @@ fn extract_phpdoc_virtual_methods(
             signature: Some(Signature {
                 params: method.params,
                 return_type,
+                throws: Vec::new(),
             }),
-            parent_fqn: Some(parent_fqn.to_string()),
+            value: None,
+            parent_fqn: Some(parent_fqn.into()),
             extends: vec![],
             implements: vec![],
             traits: vec![],
             templates: vec![],
             template_bindings: vec![],
+            trait_adaptations: vec![],
         });
     }
 }
 
-fn extract_enum_builtin_properties(
+/// tree-sitter-php does not accept constants in enum bodies: the first
+/// `const` closes the body with a MISSING `}` and the remaining members parse
+/// as top-level statements up to the stray `}`. Those statements are taken
+/// as members of the enum here; `drop_recovered_enum_members` removes the
+/// top-level copies once the whole file has been walked.
+fn extract_enum_members_after_truncated_body(
+    node: Node,
+    body: Node,
+    source: &str,
+    uri: &str,
+    result: &mut FileSymbols,
+    parent_fqn: &str,
+    php_version: Option<PhpSymbolExtractionVersion>,
+) {
+    let truncated = body
+        .child(body.child_count().saturating_sub(1))
+        .is_some_and(|last| last.is_missing());
+    if !truncated {
+        return;
+    }
+
+    // A doc comment on the first recovered member is still inside the body,
+    // and method modifiers end up in an ERROR node just before the function.
+    let mut body_doc_comment = (0..body.child_count())
+        .rev()
+        .filter_map(|i| body.child(i))
+        .find(|child| !child.is_missing())
+        .filter(|child| child.kind() == "comment" && node_text(*child, source).starts_with("/**"))
+        .map(|comment| node_text(comment, source).to_string());
+    let mut modifiers_node: Option<Node> = None;
+    let mut sibling = node.next_sibling();
+    while let Some(member) = sibling {
+        let first_new = result.symbols.len();
+        match member.kind() {
+            "const_declaration" => {
+                extract_class_constants(member, source, uri, result, parent_fqn, php_version);
+            }
+            "function_definition" => {
+                extract_method(member, source, uri, result, parent_fqn, php_version);
+                if let (Some(prefix), Some(method)) =
+                    (modifiers_node.take(), result.symbols.get_mut(first_new))
+                {
+                    method.visibility = extract_visibility(prefix, source);
+                    method.modifiers = extract_modifiers(prefix, source);
+                }
+            }
+            "ERROR" if node_text(member, source).trim() == "}" => break,
+            "ERROR" => modifiers_node = Some(member),
+            "comment" => {}
+            _ => break,
+        }
+        if let Some(doc) = body_doc_comment.take_if(|_| result.symbols.len() > first_new) {
+            for symbol in &mut result.symbols[first_new..] {
+                if symbol.doc_comment.is_none()
+                    && symbol.parent_fqn.as_deref() == Some(parent_fqn)
+                    && matches!(
+                        symbol.kind,
+                        PhpSymbolKind::ClassConstant | PhpSymbolKind::Method
+                    )
+                {
+                    symbol.doc_comment = Some(doc.clone());
+                }
+            }
+        }
+        sibling = member.next_sibling();
+    }
+}
+
+/// Drop top-level functions and constants that were also recovered as enum
+/// members by `extract_enum_members_after_truncated_body`.
+fn drop_recovered_enum_members(result: &mut FileSymbols) {
+    let member_ranges: HashSet<_> = result
+        .symbols
+        .iter()
+        .filter(|sym| {
+            sym.parent_fqn.is_some()
+                && matches!(
+                    sym.kind,
+                    PhpSymbolKind::Method | PhpSymbolKind::ClassConstant
+                )
+        })
+        .map(|sym| (sym.range, sym.selection_range))
+        .collect();
+    if member_ranges.is_empty() {
+        return;
+    }
+    result.symbols.retain(|sym| {
+        !(sym.parent_fqn.is_none()
+            && matches!(
+                sym.kind,
+                PhpSymbolKind::Function | PhpSymbolKind::GlobalConstant
+            )
+            && member_ranges.contains(&(sym.range, sym.selection_range)))
+    });
+}
+
+/// Members every enum gets from `UnitEnum` (`$name`, `cases()`) and, for
+/// backed enums, `BackedEnum` (`$value`, `from()`, `tryFrom()`). Declared
+/// members with the same name win.
+fn extract_enum_builtin_members(
     node: Node,
     source: &str,
     uri: &str,
@@ -686,6 +1206,18 @@ fn extract_enum_builtin_properties(
         TypeInfo::Simple("string".to_string()),
         fallback_range,
     );
+    push_enum_builtin_method(
+        result,
+        uri,
+        parent_fqn,
+        "cases",
+        vec![],
+        TypeInfo::Generic {
+            base: "list".to_string(),
+            args: vec![TypeInfo::Static_],
+        },
+        fallback_range,
+    );
 
     let Some(body) = body_node else {
         return;
@@ -712,6 +1244,36 @@ fn extract_enum_builtin_properties(
         TypeInfo::Simple(backing_type.to_string()),
         fallback_range,
     );
+    let value_param = ParamInfo {
+        name: "value".to_string(),
+        type_info: Some(TypeInfo::Union(vec![
+            TypeInfo::Simple("int".to_string()),
+            TypeInfo::Simple("string".to_string()),
+        ])),
+        default_value: None,
+        is_variadic: false,
+        is_by_ref: false,
+        is_promoted: false,
+        attributes: vec![],
+    };
+    push_enum_builtin_method(
+        result,
+        uri,
+        parent_fqn,
+        "from",
+        vec![value_param.clone()],
+        TypeInfo::Static_,
+        fallback_range,
+    );
+    push_enum_builtin_method(
+        result,
+        uri,
+        parent_fqn,
+        "tryFrom",
+        vec![value_param],
+        TypeInfo::Nullable(Box::new(TypeInfo::Static_)),
+        fallback_range,
+    );
 }
 
 fn push_enum_builtin_property(
@@ -732,14 +1294,15 @@ fn push_enum_builtin_property(
 
     result.symbols.push(SymbolInfo {
         name: name.to_string(),
-        fqn: format!("{}::${}", parent_fqn, name),
+        fqn: format!("{}::${}", parent_fqn, name).into(),
         kind: PhpSymbolKind::Property,
-        uri: uri.to_string(),
+        uri: uri.into(),
         range: fallback_range,
         selection_range: fallback_range,
         visibility: Visibility::Public,
         modifiers: SymbolModifiers {
             is_readonly: true,
+            is_builtin: true,
             ..SymbolModifiers::default()
         },
         attributes: vec![],
@@ -747,13 +1310,64 @@ fn push_enum_builtin_property(
         signature: Some(Signature {
             params: vec![],
             return_type: Some(type_info),
+            throws: Vec::new(),
         }),
-        parent_fqn: Some(parent_fqn.to_string()),
+        value: None,
+        parent_fqn: Some(parent_fqn.into()),
         extends: vec![],
         implements: vec![],
         traits: vec![],
         templates: vec![],
         template_bindings: vec![],
+        trait_adaptations: vec![],
+    });
+}
+
+fn push_enum_builtin_method(
+    result: &mut FileSymbols,
+    uri: &str,
+    parent_fqn: &str,
+    name: &str,
+    params: Vec<ParamInfo>,
+    return_type: TypeInfo,
+    fallback_range: (u32, u32, u32, u32),
+) {
+    if result.symbols.iter().any(|symbol| {
+        symbol.kind == PhpSymbolKind::Method
+            && symbol.parent_fqn.as_deref() == Some(parent_fqn)
+            && symbol.name.eq_ignore_ascii_case(name)
+    }) {
+        return;
+    }
+
+    result.symbols.push(SymbolInfo {
+        name: name.to_string(),
+        fqn: format!("{}::{}", parent_fqn, name).into(),
+        kind: PhpSymbolKind::Method,
+        uri: uri.into(),
+        range: fallback_range,
+        selection_range: fallback_range,
+        visibility: Visibility::Public,
+        modifiers: SymbolModifiers {
+            is_static: true,
+            is_builtin: true,
+            ..SymbolModifiers::default()
+        },
+        attributes: vec![],
+        doc_comment: None,
+        signature: Some(Signature {
+            params,
+            return_type: Some(return_type),
+            throws: Vec::new(),
+        }),
+        value: None,
+        parent_fqn: Some(parent_fqn.into()),
+        extends: vec![],
+        implements: vec![],
+        traits: vec![],
+        templates: vec![],
+        template_bindings: vec![],
+        trait_adaptations: vec![],
     });
 }
 
@@ -842,11 +1456,134 @@ fn extract_class_body(
                 extract_enum_case(child, source, uri, result, parent_fqn, php_version);
             }
             "use_declaration" => {
-                // Trait use — ignore for now (could track trait usage)
+                // Trait uses and their adaptations are recorded on the class
+                // symbol and resolved by the index.
             }
             _ => {}
         }
     }
+
+    infer_untyped_property_types_from_constructor(body, source, result, parent_fqn);
+}
+
+/// Fill in the type of untyped, undocumented properties from the first
+/// `$this->prop = new Foo()` or `$this->prop = $typedParam` assignment in
+/// the class constructor.
+fn infer_untyped_property_types_from_constructor(
+    body: Node,
+    source: &str,
+    result: &mut FileSymbols,
+    parent_fqn: &str,
+) {
+    let mut cursor = body.walk();
+    let Some(constructor_body) = body
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "method_declaration")
+        .find(|method| {
+            method
+                .child_by_field_name("name")
+                .is_some_and(|name| node_text(name, source).eq_ignore_ascii_case("__construct"))
+        })
+        .and_then(|method| method.child_by_field_name("body"))
+    else {
+        return;
+    };
+
+    let constructor_params: Vec<ParamInfo> = result
+        .symbols
+        .iter()
+        .find(|sym| {
+            sym.kind == PhpSymbolKind::Method
+                && sym.parent_fqn.as_deref() == Some(parent_fqn)
+                && sym.name.eq_ignore_ascii_case("__construct")
+        })
+        .and_then(|sym| sym.signature.as_ref())
+        .map(|sig| sig.params.clone())
+        .unwrap_or_default();
+
+    let mut inferred: HashMap<String, TypeInfo> = HashMap::new();
+    let mut stack = vec![constructor_body];
+    while let Some(node) = stack.pop() {
+        if matches!(
+            node.kind(),
+            "anonymous_function" | "arrow_function" | "declaration_list"
+        ) {
+            continue;
+        }
+        if node.kind() == "assignment_expression" {
+            if let Some((property, type_info)) =
+                constructor_property_assignment(node, source, &constructor_params)
+            {
+                inferred.entry(property).or_insert(type_info);
+            }
+        }
+        let mut child_cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut child_cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+
+    for sym in &mut result.symbols {
+        if sym.kind != PhpSymbolKind::Property
+            || sym.parent_fqn.as_deref() != Some(parent_fqn)
+            || sym.signature.is_some()
+        {
+            continue;
+        }
+        if let Some(type_info) = inferred.remove(&sym.name) {
+            sym.signature = Some(Signature {
+                params: vec![],
+                return_type: Some(type_info),
+                throws: Vec::new(),
+            });
+        }
+    }
+}
+
+fn constructor_property_assignment(
+    node: Node,
+    source: &str,
+    constructor_params: &[ParamInfo],
+) -> Option<(String, TypeInfo)> {
+    let left = node.child_by_field_name("left")?;
+    if left.kind() != "member_access_expression"
+        || left
+            .child_by_field_name("object")
+            .is_none_or(|object| node_text(object, source) != "$this")
+    {
+        return None;
+    }
+    let name = left.child_by_field_name("name")?;
+    if name.kind() != "name" {
+        return None;
+    }
+    let property = node_text(name, source).to_string();
+
+    let right = node.child_by_field_name("right")?;
+    let type_info = match right.kind() {
+        "object_creation_expression" => {
+            let mut cursor = right.walk();
+            let class_node = right
+                .named_children(&mut cursor)
+                .find(|child| matches!(child.kind(), "name" | "qualified_name"))?;
+            let class_name = node_text(class_node, source);
+            match class_name.to_ascii_lowercase().as_str() {
+                "self" => TypeInfo::Self_,
+                "static" => TypeInfo::Static_,
+                "parent" => TypeInfo::Parent_,
+                _ => TypeInfo::Simple(class_name.to_string()),
+            }
+        }
+        "variable_name" => {
+            let variable = node_text(right, source).trim_start_matches('$');
+            constructor_params
+                .iter()
+                .find(|param| param.name == variable)
+                .and_then(|param| param.type_info.clone())?
+        }
+        _ => return None,
+    };
+
+    Some((property, type_info))
 }
 
 fn extract_method(
@@ -870,21 +1607,23 @@ fn extract_method(
 
     let visibility = extract_visibility(node, source);
     let modifiers = extract_modifiers(node, source);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
     let doc_comment = find_doc_comment(node, source);
     let templates = phpdoc_templates(doc_comment.as_deref());
-    let mut signature = extract_signature(node, source, php_version);
+    let mut signature = extract_signature(node, source, result, php_version);
 
     // Apply PHPDoc fallbacks: @return type and [optional] params
     if let Some(ref doc) = doc_comment {
         apply_phpdoc_to_signature(&mut signature, doc);
     }
+    signature.throws = extract_thrown_exceptions(node, source, doc_comment.as_deref(), result);
+    let constructor_phpdoc = doc_comment.as_deref().map(crate::phpdoc::parse_phpdoc);
 
     result.symbols.push(SymbolInfo {
         name,
-        fqn,
+        fqn: fqn.into(),
         kind: PhpSymbolKind::Method,
-        uri: uri.to_string(),
+        uri: uri.into(),
         range: node_range(node),
         selection_range: node_range(name_node),
         visibility,
@@ -892,12 +1631,14 @@ fn extract_method(
         attributes,
         doc_comment,
         signature: Some(signature),
-        parent_fqn: Some(parent_fqn.to_string()),
+        value: None,
+        parent_fqn: Some(parent_fqn.into()),
         extends: vec![],
         implements: vec![],
         traits: vec![],
         templates,
         template_bindings: vec![],
+        trait_adaptations: vec![],
     });
 
     // Emit Property symbols for promoted constructor parameters.
@@ -913,7 +1654,7 @@ fn extract_method(
                 }
                 let prop_vis = extract_visibility(child, source);
                 let prop_mods = extract_modifiers(child, source);
-                let prop_attributes = attribute_groups_for_node(child, source);
+                let prop_attributes = attribute_groups_for_node(child, source, result);
                 let prop_type = child
                     .child_by_field_name("type")
                     .map(|t| parse_type_node(t, source));
@@ -921,28 +1662,36 @@ fn extract_method(
                     let raw_name = node_text(name_node, source);
                     let prop_name = raw_name.strip_prefix('$').unwrap_or(raw_name).to_string();
                     let prop_fqn = format!("{}::${}", parent_fqn, prop_name);
+                    let prop_doc = find_doc_comment(child, source).or_else(|| {
+                        constructor_phpdoc
+                            .as_ref()
+                            .and_then(|phpdoc| promoted_property_doc_comment(phpdoc, &prop_name))
+                    });
 
                     result.symbols.push(SymbolInfo {
                         name: prop_name,
-                        fqn: prop_fqn,
+                        fqn: prop_fqn.into(),
                         kind: PhpSymbolKind::Property,
-                        uri: uri.to_string(),
+                        uri: uri.into(),
                         range: node_range(child),
                         selection_range: node_range(name_node),
                         visibility: prop_vis,
                         modifiers: prop_mods,
                         attributes: prop_attributes,
-                        doc_comment: None,
+                        doc_comment: prop_doc,
                         signature: prop_type.map(|t| Signature {
                             params: vec![],
                             return_type: Some(t),
+                            throws: Vec::new(),
                         }),
-                        parent_fqn: Some(parent_fqn.to_string()),
+                        value: None,
+                        parent_fqn: Some(parent_fqn.into()),
                         extends: vec![],
                         implements: vec![],
                         traits: vec![],
                         templates: vec![],
                         template_bindings: vec![],
+                        trait_adaptations: vec![],
                     });
                 }
             }
@@ -968,21 +1717,22 @@ fn extract_function(
     };
     let name = node_text(name_node, source).to_string();
     let fqn = make_fqn(current_ns, &name);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
     let doc_comment = find_doc_comment(node, source);
     let templates = phpdoc_templates(doc_comment.as_deref());
-    let mut signature = extract_signature(node, source, php_version);
+    let mut signature = extract_signature(node, source, result, php_version);
 
     // Apply PHPDoc fallbacks: @return type and [optional] params
     if let Some(ref doc) = doc_comment {
         apply_phpdoc_to_signature(&mut signature, doc);
     }
+    signature.throws = extract_thrown_exceptions(node, source, doc_comment.as_deref(), result);
 
     result.symbols.push(SymbolInfo {
         name,
-        fqn,
+        fqn: fqn.into(),
         kind: PhpSymbolKind::Function,
-        uri: uri.to_string(),
+        uri: uri.into(),
         range: node_range(node),
         selection_range: node_range(name_node),
         visibility: Visibility::Public,
@@ -990,12 +1740,14 @@ fn extract_function(
         attributes,
         doc_comment,
         signature: Some(signature),
+        value: None,
         parent_fqn: None,
         extends: vec![],
         implements: vec![],
         traits: vec![],
         templates,
         template_bindings: vec![],
+        trait_adaptations: vec![],
     });
 }
 
@@ -1015,7 +1767,7 @@ fn extract_properties(
     let modifiers = extract_modifiers(node, source);
     let doc_comment = find_doc_comment(node, source);
     let attribute_text = attribute_prefix_for_node(node, source);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
 
     // Extract type annotation if present
     let native_type_info = node
@@ -1041,9 +1793,9 @@ fn extract_properties(
 
                 result.symbols.push(SymbolInfo {
                     name,
-                    fqn,
+                    fqn: fqn.into(),
                     kind: PhpSymbolKind::Property,
-                    uri: uri.to_string(),
+                    uri: uri.into(),
                     range: node_range(node),
                     selection_range: node_range(name_node),
                     visibility,
@@ -1053,13 +1805,16 @@ fn extract_properties(
                     signature: type_info.as_ref().map(|t| Signature {
                         params: vec![],
                         return_type: Some(t.clone()),
+                        throws: Vec::new(),
                     }),
-                    parent_fqn: Some(parent_fqn.to_string()),
+                    value: None,
+                    parent_fqn: Some(parent_fqn.into()),
                     extends: vec![],
                     implements: vec![],
                     traits: vec![],
                     templates: vec![],
                     template_bindings: vec![],
+                    trait_adaptations: vec![],
                 });
             }
         }
@@ -1095,14 +1850,33 @@ fn refine_doctrine_collection_type_from_target_entity(
 }
 
 fn attribute_prefix_for_node(node: Node, source: &str) -> String {
-    attribute_groups_for_node(node, source)
+    raw_attribute_groups_for_node(node, source)
         .into_iter()
         .map(|attribute| attribute.text)
         .collect::<Vec<_>>()
         .join(" ")
 }
 
-fn attribute_groups_for_node(node: Node, source: &str) -> Vec<SymbolAttribute> {
+fn attribute_groups_for_node(
+    node: Node,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> Vec<SymbolAttribute> {
+    let mut groups = raw_attribute_groups_for_node(node, source);
+    for group in &mut groups {
+        group.names = attribute_group_names(&group.text)
+            .into_iter()
+            .map(|name| {
+                resolve_class_name_in_file(name, file_symbols)
+                    .trim_start_matches('\\')
+                    .to_string()
+            })
+            .collect();
+    }
+    groups
+}
+
+fn raw_attribute_groups_for_node(node: Node, source: &str) -> Vec<SymbolAttribute> {
     let mut groups = immediate_attribute_groups(source, node.start_byte());
     groups.extend(leading_attribute_groups_for_node(node, source));
     groups.sort_by_key(|attribute| {
@@ -1118,6 +1892,57 @@ fn attribute_groups_for_node(node: Node, source: &str) -> Vec<SymbolAttribute> {
     groups
 }
 
+/// Class names of the attributes in a raw `#[A(...), B]` group, as written.
+fn attribute_group_names(text: &str) -> Vec<&str> {
+    let Some(inner) = text
+        .strip_prefix("#[")
+        .and_then(|inner| inner.strip_suffix(']'))
+    else {
+        return Vec::new();
+    };
+
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escape = false;
+    let mut item_start = Some(0);
+
+    for (idx, ch) in inner.char_indices() {
+        if let Some(active_quote) = quote {
+            if escape {
+                escape = false;
+            } else if ch == '\\' {
+                escape = true;
+            } else if ch == active_quote {
+                quote = None;
+            }
+            continue;
+        }
+
+        match ch {
+            '\'' | '"' => quote = Some(ch),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                item_start = Some(idx + 1);
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(start) = item_start.take() {
+            let item = inner[start..].trim_start();
+            let len = item
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '\\'))
+                .unwrap_or(item.len());
+            if len > 0 {
+                names.push(&item[..len]);
+            }
+        }
+    }
+
+    names
+}
+
 fn immediate_attribute_groups(source: &str, start_byte: usize) -> Vec<SymbolAttribute> {
     let mut end = start_byte.min(source.len());
     let mut ranges = Vec::new();
@@ -1177,6 +2002,7 @@ fn symbol_attribute_from_byte_range(source: &str, start: usize, end: usize) -> S
     SymbolAttribute {
         text: source[start..end].to_string(),
         range: (start_line, start_col, end_line, end_col),
+        names: Vec::new(),
     }
 }
 
@@ -1259,7 +2085,7 @@ fn extract_class_constants(
     let visibility = extract_visibility(node, source);
     let modifiers = extract_modifiers(node, source);
     let doc_comment = find_doc_comment(node, source);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
@@ -1274,12 +2100,13 @@ fn extract_class_constants(
             if let Some(name_node) = name_node {
                 let name = node_text(name_node, source).to_string();
                 let fqn = format!("{}::{}", parent_fqn, name);
+                let value = const_element_value(child, name_node, source);
 
                 result.symbols.push(SymbolInfo {
                     name,
-                    fqn,
+                    fqn: fqn.into(),
                     kind: PhpSymbolKind::ClassConstant,
-                    uri: uri.to_string(),
+                    uri: uri.into(),
                     range: node_range(node),
                     selection_range: node_range(name_node),
                     visibility,
@@ -1287,12 +2114,14 @@ fn extract_class_constants(
                     attributes: attributes.clone(),
                     doc_comment: doc_comment.clone(),
                     signature: None,
-                    parent_fqn: Some(parent_fqn.to_string()),
+                    value,
+                    parent_fqn: Some(parent_fqn.into()),
                     extends: vec![],
                     implements: vec![],
                     traits: vec![],
                     templates: vec![],
                     template_bindings: vec![],
+                    trait_adaptations: vec![],
                 });
             }
         }
@@ -1312,7 +2141,7 @@ fn extract_global_constants(
     }
 
     let doc_comment = find_doc_comment(node, source);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
@@ -1325,12 +2154,13 @@ fn extract_global_constants(
             if let Some(name_node) = name_node {
                 let name = node_text(name_node, source).to_string();
                 let fqn = make_fqn(current_ns, &name);
+                let value = const_element_value(child, name_node, source);
 
                 result.symbols.push(SymbolInfo {
                     name,
-                    fqn,
+                    fqn: fqn.into(),
                     kind: PhpSymbolKind::GlobalConstant,
-                    uri: uri.to_string(),
+                    uri: uri.into(),
                     range: node_range(node),
                     selection_range: node_range(name_node),
                     visibility: Visibility::Public,
@@ -1338,18 +2168,118 @@ fn extract_global_constants(
                     attributes: attributes.clone(),
                     doc_comment: doc_comment.clone(),
                     signature: None,
+                    value,
                     parent_fqn: None,
                     extends: vec![],
                     implements: vec![],
                     traits: vec![],
                     templates: vec![],
                     template_bindings: vec![],
+                    trait_adaptations: vec![],
                 });
             }
         }
     }
 }
 
+fn is_define_call(node: Node, source: &str) -> bool {
+    node.child_by_field_name("function")
+        .is_some_and(|function| {
+            node_text(function, source)
+                .trim_start_matches('\\')
+                .eq_ignore_ascii_case("define")
+        })
+}
+
+/// Extract `define('NAME', value)` as a global constant.
+///
+/// The name string is already fully qualified, so the enclosing namespace
+/// only applies through `__NAMESPACE__ . '\\NAME'`.
+fn extract_define_constant(
+    node: Node,
+    source: &str,
+    uri: &str,
+    result: &mut FileSymbols,
+    current_ns: &Option<String>,
+) {
+    let Some(arguments) = node.child_by_field_name("arguments") else {
+        return;
+    };
+    let mut cursor = arguments.walk();
+    let mut args = arguments
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "argument")
+        .filter_map(|argument| argument.named_child(0));
+    let (Some(name_arg), value_arg) = (args.next(), args.next()) else {
+        return;
+    };
+
+    let (fqn, string_node) = match name_arg.kind() {
+        "binary_expression" => {
+            let (Some(left), Some(right)) = (
+                name_arg.child_by_field_name("left"),
+                name_arg.child_by_field_name("right"),
+            ) else {
+                return;
+            };
+            if !node_text(left, source).eq_ignore_ascii_case("__NAMESPACE__") {
+                return;
+            }
+            let Some(suffix) = plain_string_literal_content(right, source) else {
+                return;
+            };
+            let fqn = match current_ns {
+                Some(ns) => format!("{}{}", ns, suffix),
+                None => suffix.trim_start_matches('\\').to_string(),
+            };
+            (fqn, right)
+        }
+        _ => {
+            let Some(content) = plain_string_literal_content(name_arg, source) else {
+                return;
+            };
+            (content.trim_start_matches('\\').to_string(), name_arg)
+        }
+    };
+    if !is_qualified_identifier(&fqn) {
+        return;
+    }
+    let name = fqn.rsplit('\\').next().unwrap_or(&fqn).to_string();
+
+    // Select the short name, which sits just before the closing quote.
+    let (row, end_col) = (
+        string_node.end_position().row as u32,
+        string_node.end_position().column as u32 - 1,
+    );
+    let selection_range = (row, end_col - name.len() as u32, row, end_col);
+    let statement = node
+        .parent()
+        .filter(|parent| parent.kind() == "expression_statement")
+        .unwrap_or(node);
+
+    result.symbols.push(SymbolInfo {
+        name,
+        fqn: fqn.into(),
+        kind: PhpSymbolKind::GlobalConstant,
+        uri: uri.into(),
+        range: node_range(statement),
+        selection_range,
+        visibility: Visibility::Public,
+        modifiers: SymbolModifiers::default(),
+        attributes: vec![],
+        doc_comment: find_doc_comment(statement, source),
+        signature: None,
+        value: value_arg.map(|value| initializer_text(value, source)),
+        parent_fqn: None,
+        extends: vec![],
+        implements: vec![],
+        traits: vec![],
+        templates: vec![],
+        template_bindings: vec![],
+        trait_adaptations: vec![],
+    });
+}
+
 fn extract_enum_case(
     node: Node,
     source: &str,
@@ -1369,13 +2299,16 @@ fn extract_enum_case(
     let name = node_text(name_node, source).to_string();
     let fqn = format!("{}::{}", parent_fqn, name);
     let doc_comment = find_doc_comment(node, source);
-    let attributes = attribute_groups_for_node(node, source);
+    let attributes = attribute_groups_for_node(node, source, result);
+    let value = node
+        .child_by_field_name("value")
+        .map(|value_node| initializer_text(value_node, source));
 
     result.symbols.push(SymbolInfo {
         name,
-        fqn,
+        fqn: fqn.into(),
         kind: PhpSymbolKind::EnumCase,
-        uri: uri.to_string(),
+        uri: uri.into(),
         range: node_range(node),
         selection_range: node_range(name_node),
         visibility: Visibility::Public,
@@ -1383,15 +2316,52 @@ fn extract_enum_case(
         attributes,
         doc_comment,
         signature: None,
-        parent_fqn: Some(parent_fqn.to_string()),
+        value,
+        parent_fqn: Some(parent_fqn.into()),
         extends: vec![],
         implements: vec![],
         traits: vec![],
         templates: vec![],
         template_bindings: vec![],
+        trait_adaptations: vec![],
     });
 }
 
+/// Initializer expression of a `const_element` (`NAME = expr`).
+fn const_element_value(node: Node, name_node: Node, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let value = node
+        .named_children(&mut cursor)
+        .filter(|child| child.id() != name_node.id() && child.kind() != "comment")
+        .last();
+    value.map(|value_node| initializer_text(value_node, source))
+}
+
+/// Source text of an initializer with whitespace runs collapsed, so
+/// multi-line array literals render on one line.
+fn initializer_text(node: Node, source: &str) -> String {
+    node_text(node, source)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Promoted properties take their documented type from the constructor's
+/// `@param` tag, rewritten as a `@var` docblock for the property symbol.
+fn promoted_property_doc_comment(constructor_phpdoc: &PhpDoc, prop_name: &str) -> Option<String> {
+    let param = constructor_phpdoc
+        .params
+        .iter()
+        .find(|param| param.name == prop_name)?;
+    let type_info = param.type_info.as_ref()?;
+    Some(match param.description.as_deref() {
+        Some(description) if !description.is_empty() => {
+            format!("/** @var {} {} */", type_info, description)
+        }
+        _ => format!("/** @var {} */", type_info),
+    })
+}
+
 /// Apply PHPDoc information to a signature:
 /// - Use `@return` as fallback when PHP return type is absent.
 /// - Mark params as optional (set synthetic default) when PHPDoc description
@@ -1658,7 +2628,7 @@ fn phpdoc_tagged_var_name(doc_comment: &str) -> Option<String> {
         if line.starts_with("*/") || line.is_empty() {
             continue;
         }
-        if let Some(rest) = crate::phpdoc::strip_exact_tag(line, "@var") {
+        if let Some(rest) = crate::phpdoc::var_tag(line) {
             for token in rest.split_whitespace() {
                 if let Some(name) = phpdoc_var_token(token) {
                     return Some(name);
@@ -1690,6 +2660,7 @@ fn phpdoc_var_token(token: &str) -> Option<String> {
 fn extract_signature(
     node: Node,
     source: &str,
+    file_symbols: &FileSymbols,
     php_version: Option<PhpSymbolExtractionVersion>,
 ) -> Signature {
     let mut params = Vec::new();
@@ -1704,7 +2675,7 @@ fn extract_signature(
                 if !node_is_available_for_php_version(child, source, php_version) {
                     continue;
                 }
-                let param = extract_param(child, source);
+                let param = extract_param(child, source, file_symbols);
                 params.push(param);
             }
         }
@@ -1719,7 +2690,115 @@ fn extract_signature(
     Signature {
         params,
         return_type,
+        throws: Vec::new(),
+    }
+}
+
+/// Exceptions a function or method declares with `@throws` or throws with
+/// `throw new X` outside a `try` whose `catch` names `X`, `Exception` or
+/// `Throwable`. Nested closures and classes are not part of the body.
+fn extract_thrown_exceptions(
+    node: Node,
+    source: &str,
+    doc_comment: Option<&str>,
+    file_symbols: &FileSymbols,
+) -> Vec<String> {
+    let mut throws: Vec<String> = Vec::new();
+    let mut push = |fqn: String| {
+        if !throws.iter().any(|seen| seen.eq_ignore_ascii_case(&fqn)) {
+            throws.push(fqn);
+        }
+    };
+
+    if let Some(doc) = doc_comment {
+        for type_info in crate::phpdoc::parse_phpdoc(doc).throws {
+            let names = match type_info {
+                TypeInfo::Union(members) => members,
+                other => vec![other],
+            };
+            for name in names {
+                if let TypeInfo::Simple(name) = name {
+                    push(resolve_class_name_in_file(&name, file_symbols));
+                }
+            }
+        }
+    }
+
+    if let Some(body) = node.child_by_field_name("body") {
+        let mut stack = vec![body];
+        while let Some(current) = stack.pop() {
+            match current.kind() {
+                "function_definition"
+                | "method_declaration"
+                | "anonymous_function"
+                | "anonymous_function_creation_expression"
+                | "arrow_function"
+                | "class_declaration"
+                | "anonymous_class" => continue,
+                "throw_expression" => {
+                    let class_name = current
+                        .named_child(0)
+                        .filter(|thrown| thrown.kind() == "object_creation_expression")
+                        .and_then(|creation| creation.named_child(0))
+                        .filter(|class| matches!(class.kind(), "name" | "qualified_name"));
+                    if let Some(class_name) = class_name {
+                        let fqn =
+                            resolve_class_name_in_file(node_text(class_name, source), file_symbols);
+                        if !throw_is_caught(current, node, &fqn, source, file_symbols) {
+                            push(fqn);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let mut cursor = current.walk();
+            stack.extend(current.named_children(&mut cursor));
+        }
+    }
+
+    throws
+}
+
+fn throw_is_caught(
+    throw_node: Node,
+    function_node: Node,
+    fqn: &str,
+    source: &str,
+    file_symbols: &FileSymbols,
+) -> bool {
+    let mut child = throw_node;
+    while let Some(parent) = child.parent() {
+        if parent.id() == function_node.id() {
+            break;
+        }
+        let in_try_body = parent.kind() == "try_statement"
+            && parent
+                .child_by_field_name("body")
+                .is_some_and(|body| body.id() == child.id());
+        if in_try_body {
+            let mut cursor = parent.walk();
+            let caught = parent
+                .named_children(&mut cursor)
+                .filter(|clause| clause.kind() == "catch_clause")
+                .filter_map(|clause| clause.child_by_field_name("type"))
+                .flat_map(|types| {
+                    let mut cursor = types.walk();
+                    types.named_children(&mut cursor).collect::<Vec<_>>()
+                })
+                .any(|caught| {
+                    let caught =
+                        resolve_class_name_in_file(node_text(caught, source), file_symbols);
+                    caught.eq_ignore_ascii_case(fqn)
+                        || caught.eq_ignore_ascii_case("Exception")
+                        || caught.eq_ignore_ascii_case("Throwable")
+                });
+            if caught {
+                return true;
+            }
+        }
+        child = parent;
     }
+    false
 }
 
 /// Normalize signature parameters to handle version-gated stub overloads.
@@ -1748,6 +2827,9 @@ fn normalize_signature_params(params: Vec<ParamInfo>) -> Vec<ParamInfo> {
             existing.is_variadic = existing.is_variadic || p.is_variadic;
             existing.is_by_ref = existing.is_by_ref || p.is_by_ref;
             existing.is_promoted = existing.is_promoted || p.is_promoted;
+            if existing.attributes.is_empty() {
+                existing.attributes = p.attributes;
+            }
         } else {
             let idx = out.len();
             index_by_name.insert(p.name.clone(), idx);
@@ -1758,7 +2840,7 @@ fn normalize_signature_params(params: Vec<ParamInfo>) -> Vec<ParamInfo> {
     out
 }
 
-fn extract_param(node: Node, source: &str) -> ParamInfo {
+fn extract_param(node: Node, source: &str, file_symbols: &FileSymbols) -> ParamInfo {
     let name_node = node.child_by_field_name("name");
     let raw_name = name_node
         .map(|n| node_text(n, source))
@@ -1776,6 +2858,7 @@ fn extract_param(node: Node, source: &str) -> ParamInfo {
     let is_variadic = node.kind() == "variadic_parameter";
     let is_by_ref = has_child_kind(node, "reference_modifier");
     let is_promoted = node.kind() == "property_promotion_parameter";
+    let attributes = attribute_groups_for_node(node, source, file_symbols);
 
     ParamInfo {
         name,
@@ -1784,6 +2867,7 @@ fn extract_param(node: Node, source: &str) -> ParamInfo {
         is_variadic,
         is_by_ref,
         is_promoted,
+        attributes,
     }
 }
 
@@ -2079,11 +3163,18 @@ fn has_child_kind(node: Node, kind: &str) -> bool {
 }
 
 /// Find the doc comment (PHPDoc) immediately preceding a node.
-fn find_doc_comment(node: Node, source: &str) -> Option<String> {
+pub(crate) fn find_doc_comment(node: Node, source: &str) -> Option<String> {
     find_doc_comment_node(node, source).map(|comment| node_text(comment, source).to_string())
 }
 
 fn find_doc_comment_node<'a>(node: Node<'a>, source: &str) -> Option<Node<'a>> {
+    // `#[Attr] /** doc */ class Foo` parses the PHPDoc as a child of the
+    // declaration after its attribute list, or inside the list when it sits
+    // between attribute groups.
+    if let Some(comment) = doc_comment_after_attributes(node, source) {
+        return Some(comment);
+    }
+
     // Look for a comment node as a previous sibling
     let mut prev = node.prev_sibling();
     while let Some(p) = prev {
@@ -2095,10 +3186,10 @@ fn find_doc_comment_node<'a>(node: Node<'a>, source: &str) -> Option<Node<'a>> {
             // Non-PHPDoc comment — stop looking
             return None;
         }
-        // Skip only structural/unnamed trivia between comment and declaration.
-        // A named sibling means another declaration or statement owns any
-        // earlier PHPDoc and this node is undocumented.
-        if p.is_named() {
+        // Skip only attributes and structural/unnamed trivia between comment
+        // and declaration. Any other named sibling means another declaration
+        // or statement owns any earlier PHPDoc and this node is undocumented.
+        if p.is_named() && p.kind() != "attribute_list" {
             return None;
         }
         prev = p.prev_sibling();
@@ -2106,6 +3197,26 @@ fn find_doc_comment_node<'a>(node: Node<'a>, source: &str) -> Option<Node<'a>> {
     None
 }
 
+fn doc_comment_after_attributes<'a>(node: Node<'a>, source: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let mut doc = None;
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "attribute_list" => {
+                let mut list_cursor = child.walk();
+                for item in child.children(&mut list_cursor) {
+                    if item.kind() == "comment" {
+                        doc = node_text(item, source).starts_with("/**").then_some(item);
+                    }
+                }
+            }
+            "comment" => doc = node_text(child, source).starts_with("/**").then_some(child),
+            _ => break,
+        }
+    }
+    doc
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2215,6 +3326,88 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_extract_enum_builtin_methods() {
+        let syms = parse_and_extract(
+            "<?php\nnamespace App;\nenum Level: int { case Info = 200; }\nenum Plain { case Only; public static function cases(): array { return []; } }\n",
+        );
+        let method = |fqn: &str| {
+            syms.symbols
+                .iter()
+                .find(|s| s.kind == PhpSymbolKind::Method && s.fqn == fqn)
+        };
+
+        let cases = method("App\\Level::cases").expect("cases() should be synthesized");
+        assert!(cases.modifiers.is_static && cases.modifiers.is_builtin);
+        assert_eq!(
+            cases
+                .signature
+                .as_ref()
+                .and_then(|sig| sig.return_type.as_ref())
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("list<static>")
+        );
+        let try_from = method("App\\Level::tryFrom").expect("tryFrom() should be synthesized");
+        assert_eq!(
+            try_from
+                .signature
+                .as_ref()
+                .and_then(|sig| sig.return_type.as_ref())
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("?static")
+        );
+        assert!(method("App\\Level::from").is_some());
+
+        assert!(
+            !method("App\\Plain::cases").unwrap().modifiers.is_builtin,
+            "declared cases() wins over the synthesized one"
+        );
+        assert!(method("App\\Plain::from").is_none());
+        assert!(method("App\\Plain::tryFrom").is_none());
+    }
+
+    #[test]
+    fn test_extract_enum_members_after_constant() {
+        let syms = parse_and_extract(
+            "<?php\nnamespace App;\nenum Suit: string {\n    case Hearts = 'H';\n    /** Joker. */\n    const Wild = self::Hearts;\n    public function label(): string { return ''; }\n    private static function make(): self { return self::Hearts; }\n}\nfunction after(): void {}\n",
+        );
+
+        let wild = syms
+            .symbols
+            .iter()
+            .find(|s| s.fqn == "App\\Suit::Wild")
+            .expect("constant after a case should belong to the enum");
+        assert_eq!(wild.kind, PhpSymbolKind::ClassConstant);
+        assert_eq!(wild.doc_comment.as_deref(), Some("/** Joker. */"));
+
+        let label = syms
+            .symbols
+            .iter()
+            .find(|s| s.fqn == "App\\Suit::label")
+            .expect("method after a constant should belong to the enum");
+        assert_eq!(label.kind, PhpSymbolKind::Method);
+        assert_eq!(label.visibility, Visibility::Public);
+        let make = syms
+            .symbols
+            .iter()
+            .find(|s| s.fqn == "App\\Suit::make")
+            .expect("static method after a constant should belong to the enum");
+        assert_eq!(make.visibility, Visibility::Private);
+        assert!(make.modifiers.is_static);
+
+        assert!(
+            !syms
+                .symbols
+                .iter()
+                .any(|s| s.parent_fqn.is_none()
+                    && matches!(s.name.as_str(), "Wild" | "label" | "make")),
+            "recovered members should not stay top-level"
+        );
+        assert!(syms.symbols.iter().any(|s| s.fqn == "App\\after"));
+    }
+
     #[test]
     fn test_extract_function() {
         let syms = parse_and_extract(
@@ -2315,6 +3508,30 @@ mod tests {
         assert_eq!(consts.len(), 2);
         assert_eq!(consts[0].name, "VERSION");
         assert_eq!(consts[0].fqn, "Foo::VERSION");
+        assert_eq!(consts[0].value.as_deref(), Some("'1.0'"));
+        assert_eq!(consts[1].value.as_deref(), Some("100"));
+    }
+
+    #[test]
+    fn test_extract_constant_and_enum_case_values() {
+        let syms = parse_and_extract(
+            "<?php\nconst LIMITS = [\n    'min' => 1,\n    'max' => 10,\n];\nenum Suit: string {\n    case Hearts = 'H';\n}\nclass Deck {\n    const WILD = Suit::Hearts;\n}\nenum Plain {\n    case Only;\n}\n",
+        );
+        let value_of = |fqn: &str| {
+            syms.symbols
+                .iter()
+                .find(|s| s.fqn == fqn)
+                .unwrap_or_else(|| panic!("{fqn} should be extracted"))
+                .value
+                .clone()
+        };
+        assert_eq!(
+            value_of("LIMITS").as_deref(),
+            Some("[ 'min' => 1, 'max' => 10, ]")
+        );
+        assert_eq!(value_of("Suit::Hearts").as_deref(), Some("'H'"));
+        assert_eq!(value_of("Deck::WILD").as_deref(), Some("Suit::Hearts"));
+        assert_eq!(value_of("Plain::Only"), None);
     }
 
     #[test]
@@ -2336,6 +3553,18 @@ mod tests {
         assert_eq!(syms.use_statements[2].namespace, None);
     }
 
+    #[test]
+    fn test_extract_group_use_statements() {
+        let syms = parse_and_extract("<?php\nuse App\\Model\\{User as Member, Sub\\Post};\n");
+        assert_eq!(syms.use_statements.len(), 2);
+        assert_eq!(syms.use_statements[0].fqn, "App\\Model\\User");
+        assert_eq!(syms.use_statements[0].alias, Some("Member".to_string()));
+        assert_eq!(syms.use_statements[0].kind, UseKind::Class);
+        assert_eq!(syms.use_statements[1].fqn, "App\\Model\\Sub\\Post");
+        assert_eq!(syms.use_statements[1].alias, None);
+        assert_eq!(syms.use_statements[1].range, (1, 31, 1, 39));
+    }
+
     #[test]
     fn test_extract_use_statement_namespace_scopes() {
         let syms = parse_and_extract(
@@ -2379,6 +3608,36 @@ use App\Attribute\Route as LocalRoute;
         );
     }
 
+    #[test]
+    fn test_extract_doc_comment_around_attributes() {
+        let syms = parse_and_extract(
+            r#"<?php
+/** Before attribute. */
+#[Attr]
+class Foo {
+    #[Attr]
+    /** After attribute. */
+    public function bar(): void {}
+}
+
+#[First]
+/** Between attributes. */
+#[Second]
+function baz() {}
+"#,
+        );
+
+        let doc = |name: &str| {
+            syms.symbols
+                .iter()
+                .find(|symbol| symbol.name == name)
+                .and_then(|symbol| symbol.doc_comment.as_deref())
+        };
+        assert_eq!(doc("Foo"), Some("/** Before attribute. */"));
+        assert_eq!(doc("bar"), Some("/** After attribute. */"));
+        assert_eq!(doc("baz"), Some("/** Between attributes. */"));
+    }
+
     #[test]
     fn test_method_does_not_inherit_previous_method_doc_comment() {
         let syms = parse_and_extract(
@@ -2572,6 +3831,55 @@ function demo(
             .expect("global constant should be extracted");
         assert_eq!(c.name, "BUILD");
         assert_eq!(c.fqn, "App\\BUILD");
+        assert_eq!(c.value.as_deref(), Some("'dev'"));
+    }
+
+    #[test]
+    fn test_extract_define_constants() {
+        let syms = parse_and_extract(
+            r#"<?php
+namespace Legacy;
+
+/** Current application version. */
+define('APP_VERSION', '1.0');
+if (!defined('Legacy\DEBUG_MODE')) {
+    \define(__NAMESPACE__ . '\DEBUG_MODE', true);
+}
+define("Vendor\\LIMIT", 10);
+define('not a constant', 1);
+define($dynamic, 2);
+"#,
+        );
+        let constants: Vec<_> = syms
+            .symbols
+            .iter()
+            .filter(|s| s.kind == PhpSymbolKind::GlobalConstant)
+            .map(|s| {
+                (
+                    s.name.as_str(),
+                    s.fqn.as_str(),
+                    s.value.as_deref(),
+                    s.selection_range,
+                )
+            })
+            .collect();
+        assert_eq!(
+            constants,
+            vec![
+                ("APP_VERSION", "APP_VERSION", Some("'1.0'"), (4, 8, 4, 19)),
+                (
+                    "DEBUG_MODE",
+                    "Legacy\\DEBUG_MODE",
+                    Some("true"),
+                    (6, 30, 6, 40)
+                ),
+                ("LIMIT", "Vendor\\LIMIT", Some("10"), (8, 16, 8, 21)),
+            ]
+        );
+        assert_eq!(
+            syms.symbols[0].doc_comment.as_deref(),
+            Some("/** Current application version. */")
+        );
     }
 
     #[test]
@@ -2757,6 +4065,96 @@ class FileController {
         );
     }
 
+    #[test]
+    fn test_extract_symbol_attribute_names_resolve_against_imports() {
+        let syms = parse_and_extract(
+            r#"<?php
+namespace App\Attribute;
+
+use Doctrine\ORM\Mapping as ORM;
+
+#[\Attribute(\Attribute::TARGET_METHOD), ORM\Entity(options: ['a' => [1, 2]])]
+#[Local('x, y')]
+final class Marker {}
+"#,
+        );
+        let class = syms.symbols.iter().find(|s| s.name == "Marker").unwrap();
+
+        assert_eq!(
+            class
+                .attributes
+                .iter()
+                .map(|attribute| attribute.names.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                vec![
+                    "Attribute".to_string(),
+                    "Doctrine\\ORM\\Mapping\\Entity".to_string(),
+                ],
+                vec!["App\\Attribute\\Local".to_string()],
+            ]
+        );
+        assert!(class.attributes[0].has_name("\\Attribute"));
+        assert!(!class.attributes[1].has_name("Attribute"));
+    }
+
+    #[test]
+    fn test_extract_parameter_attribute_metadata() {
+        let syms = parse_and_extract(
+            r#"<?php
+namespace App\Controller;
+
+use Symfony\Component\DependencyInjection\Attribute\Autowire;
+
+final class LoginController {
+    public function __construct(
+        #[Autowire('%kernel.secret%')] private string $secret,
+    ) {}
+
+    public function login(#[\SensitiveParameter] string $password, int $attempts): void {}
+}
+"#,
+        );
+        let param_attributes = |method: &str| -> Vec<(String, Vec<String>)> {
+            let method = syms.symbols.iter().find(|s| s.name == method).unwrap();
+            method
+                .signature
+                .as_ref()
+                .unwrap()
+                .params
+                .iter()
+                .map(|param| {
+                    (
+                        param.name.clone(),
+                        param
+                            .attributes
+                            .iter()
+                            .flat_map(|attribute| attribute.names.clone())
+                            .collect(),
+                    )
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            param_attributes("__construct"),
+            vec![(
+                "secret".to_string(),
+                vec!["Symfony\\Component\\DependencyInjection\\Attribute\\Autowire".to_string()]
+            )]
+        );
+        assert_eq!(
+            param_attributes("login"),
+            vec![
+                (
+                    "password".to_string(),
+                    vec!["SensitiveParameter".to_string()]
+                ),
+                ("attempts".to_string(), vec![]),
+            ]
+        );
+    }
+
     #[test]
     fn test_extract_function_and_method_templates() {
         let syms = parse_and_extract(
@@ -2844,6 +4242,54 @@ class Factory {
         );
     }
 
+    #[test]
+    fn test_extract_trait_adaptations() {
+        let syms = parse_and_extract(
+            "<?php\nnamespace App;\n\nclass Foo {\n    use A, B, C {\n        A::hello insteadof B, C;\n        B::hello as protected helloB;\n        world as private;\n    }\n}\n",
+        );
+        let cls = syms
+            .symbols
+            .iter()
+            .find(|s| s.kind == PhpSymbolKind::Class)
+            .unwrap();
+        assert_eq!(
+            cls.traits,
+            vec![
+                "App\\A".to_string(),
+                "App\\B".to_string(),
+                "App\\C".to_string()
+            ]
+        );
+        assert_eq!(
+            cls.trait_adaptations,
+            vec![
+                TraitAdaptation {
+                    trait_fqn: Some("App\\A".to_string()),
+                    method: "hello".to_string(),
+                    kind: TraitAdaptationKind::InsteadOf {
+                        excluded: vec!["App\\B".to_string(), "App\\C".to_string()],
+                    },
+                },
+                TraitAdaptation {
+                    trait_fqn: Some("App\\B".to_string()),
+                    method: "hello".to_string(),
+                    kind: TraitAdaptationKind::Alias {
+                        alias: Some("helloB".to_string()),
+                        visibility: Some(Visibility::Protected),
+                    },
+                },
+                TraitAdaptation {
+                    trait_fqn: None,
+                    method: "world".to_string(),
+                    kind: TraitAdaptationKind::Alias {
+                        alias: None,
+                        visibility: Some(Visibility::Private),
+                    },
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_extract_class_extends_and_implements() {
         let syms = parse_and_extract("<?php\nclass Child extends Parent_ implements Foo, Bar {}\n");
@@ -2897,6 +4343,48 @@ function mb_strtolower(string $string, ?string $encoding): string {}
         );
     }
 
+    #[test]
+    fn test_signature_throws_aggregates_phpdoc_and_uncaught_throw_statements() {
+        let syms = parse_and_extract(
+            r#"<?php
+namespace App;
+
+use Vendor\NotFound;
+
+class Repo {
+    /**
+     * @throws NotFound
+     */
+    public function find(int $id): void {
+        if ($id < 0) {
+            throw new \InvalidArgumentException('negative');
+        }
+        try {
+            throw new StaleRead();
+        } catch (StaleRead $e) {
+        }
+        $retry = function () {
+            throw new \LogicException();
+        };
+        throw new NotFound();
+    }
+}
+"#,
+        );
+        let method = syms
+            .symbols
+            .iter()
+            .find(|s| s.kind == PhpSymbolKind::Method && s.name == "find")
+            .unwrap();
+        assert_eq!(
+            method.signature.as_ref().unwrap().throws,
+            vec![
+                "Vendor\\NotFound".to_string(),
+                "InvalidArgumentException".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_phpdoc_optional_on_byref_param() {
         // Simulates str_replace stub: &$count has no default but PHPDoc says [optional]
@@ -2995,6 +4483,94 @@ class MyService {
         );
     }
 
+    #[test]
+    fn test_promoted_property_doc_comment_from_constructor_param_tag() {
+        let syms = parse_and_extract(
+            r#"<?php
+namespace App;
+
+class Team {
+    /**
+     * @param list<User> $members Team members.
+     * @param int $size
+     */
+    public function __construct(
+        private array $members,
+        /** @var positive-int */
+        public int $size,
+        public string $label,
+    ) {}
+}
+"#,
+        );
+
+        let doc_for = |fqn: &str| {
+            syms.symbols
+                .iter()
+                .find(|s| s.kind == PhpSymbolKind::Property && s.fqn == fqn)
+                .and_then(|s| s.doc_comment.clone())
+        };
+        assert_eq!(
+            doc_for("App\\Team::$members").as_deref(),
+            Some("/** @var list<User> Team members. */")
+        );
+        assert_eq!(
+            doc_for("App\\Team::$size").as_deref(),
+            Some("/** @var positive-int */")
+        );
+        assert_eq!(doc_for("App\\Team::$label"), None);
+    }
+
+    #[test]
+    fn test_untyped_property_type_inferred_from_constructor_assignment() {
+        let syms = parse_and_extract(
+            r#"<?php
+namespace App;
+
+class MyService {
+    private $logger;
+    protected $mailer;
+    private $callback;
+    /** @var Cache */
+    private $cache;
+
+    public function __construct(Mailer $mailer, $untyped) {
+        $this->logger = new NullLogger();
+        $this->mailer = $mailer;
+        $this->cache = new ArrayCache();
+        $this->callback = function () {
+            $this->logger = new FileLogger();
+        };
+        $this->logger = $untyped;
+    }
+}
+"#,
+        );
+
+        let property_type = |fqn: &str| {
+            syms.symbols
+                .iter()
+                .find(|s| s.kind == PhpSymbolKind::Property && s.fqn == fqn)
+                .and_then(|s| s.signature.as_ref())
+                .and_then(|sig| sig.return_type.clone())
+        };
+
+        assert_eq!(
+            property_type("App\\MyService::$logger"),
+            Some(TypeInfo::Simple("NullLogger".to_string()))
+        );
+        assert_eq!(
+            property_type("App\\MyService::$mailer"),
+            Some(TypeInfo::Simple("Mailer".to_string()))
+        );
+        assert_eq!(
+            property_type("App\\MyService::$cache"),
+            Some(TypeInfo::Simple("Cache".to_string())),
+            "PHPDoc @var should win over the constructor assignment"
+        );
+        assert_eq!(property_type("App\\MyService::$callback"), None);
+    }
+
     #[test]
     fn test_property_phpdoc_var_sets_property_type_when_native_type_is_missing() {
         let syms = parse_and_extract(
@@ -3009,6 +4585,12 @@ class Holder {
 
     /** @var User $native */
     private Account $native;
+
+    /** @var $legacy User */
+    var $legacy;
+
+    /** @psalm-var User */
+    protected $prefixed;
 }
 "#,
         );
@@ -3034,6 +4616,22 @@ class Holder {
             .as_ref()
             .and_then(|sig| sig.return_type.as_ref());
         assert!(matches!(native_type, Some(TypeInfo::Simple(name)) if name == "Account"));
+
+        for fqn in ["App\\Holder::$legacy", "App\\Holder::$prefixed"] {
+            let prop = syms
+                .symbols
+                .iter()
+                .find(|s| s.kind == PhpSymbolKind::Property && s.fqn == fqn)
+                .expect("docblock-typed property should be extracted");
+            let prop_type = prop
+                .signature
+                .as_ref()
+                .and_then(|sig| sig.return_type.as_ref());
+            assert!(
+                matches!(prop_type, Some(TypeInfo::Simple(name)) if name == "User"),
+                "{fqn} should take its type from the docblock, got {prop_type:?}"
+            );
+        }
     }
 
     #[test]
@@ -1,18 +1,23 @@
+use crate::baseline::{baseline_root, DiagnosticBaseline};
 use crate::server::{
-    collect_php_files, compute_diagnostics_with_runtime_config,
-    diagnostic_budget_config_from_settings, discover_workspace_root_config,
-    lazy_resolvable_diagnostic_fqn, lazy_resolved_symbol_diagnostic_is_satisfied,
-    load_configured_stubs, load_effective_configuration_settings, normalize_config_paths,
-    parse_vendor_autoload_map, path_is_excluded, resolve_vendor_paths_from_map,
-    vendor_autoload_file_paths_from_map, vendor_namespace_exists_from_map,
-    workspace_index_directories, DiagnosticBudgetConfig, DiagnosticSeverityConfig, DiagnosticsMode,
-    DiagnosticsRuntimeConfig, PhpVersion, VendorAutoloadMap,
+    collect_php_files, compute_diagnostics_with_runtime_config, configured_stub_extensions,
+    default_php_file_extensions, diagnostic_budget_config_from_settings,
+    discover_workspace_root_config, lazy_resolvable_diagnostic_fqn,
+    lazy_resolved_symbol_diagnostic_is_satisfied, load_configured_stubs,
+    load_effective_configuration_settings, normalize_config_paths, normalize_php_file_extensions,
+    parse_vendor_autoload_map, path_has_php_extension, path_is_excluded,
+    resolve_vendor_paths_from_map, vendor_autoload_file_paths_from_map,
+    vendor_namespace_exists_from_map, workspace_exclude_paths, workspace_index_directories,
+    DiagnosticBudgetConfig, DiagnosticSeverityConfig, DiagnosticsMode, DiagnosticsRuntimeConfig,
+    PhpVersion, VendorAutoloadMap, VendorIndexing,
 };
 use crate::util::uri::path_to_uri;
 use php_lsp_index::workspace::WorkspaceIndex;
 use php_lsp_parser::parser::FileParser;
+use php_lsp_parser::position::PositionEncoding;
 use php_lsp_parser::references::collect_symbol_references_in_file;
 use php_lsp_parser::semantic::collect_aliased_class_fqns;
+use php_lsp_parser::source::read_php_source;
 use php_lsp_parser::symbols::extract_file_symbols;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
@@ -24,14 +29,16 @@ pub enum AnalyzeFormat {
     Table,
     Json,
     Github,
+    Checkstyle,
 }
 
 impl AnalyzeFormat {
     fn parse(raw: &str) -> Option<Self> {
         match raw.trim().to_ascii_lowercase().as_str() {
-            "table" => Some(Self::Table),
+            "table" | "human" => Some(Self::Table),
             "json" => Some(Self::Json),
             "github" => Some(Self::Github),
+            "checkstyle" => Some(Self::Checkstyle),
             _ => None,
         }
     }
@@ -89,19 +96,29 @@ impl AnalyzeSeverity {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AnalyzeArgs {
-    pub path: Option<PathBuf>,
+    /// Files or directories to report on; the project root when empty.
+    pub paths: Vec<PathBuf>,
     pub project_root: Option<PathBuf>,
     pub severity: AnalyzeSeverity,
     pub format: AnalyzeFormat,
+    /// Lowest reported severity that makes the command exit with code `2`.
+    pub fail_on: AnalyzeSeverity,
+    /// Baseline file whose diagnostics are hidden from the report.
+    pub baseline: Option<PathBuf>,
+    /// Write current diagnostics to this baseline file instead of reporting them.
+    pub generate_baseline: Option<PathBuf>,
 }
 
 impl Default for AnalyzeArgs {
     fn default() -> Self {
         Self {
-            path: None,
+            paths: Vec::new(),
             project_root: None,
             severity: AnalyzeSeverity::All,
             format: AnalyzeFormat::Table,
+            fail_on: AnalyzeSeverity::All,
+            baseline: None,
+            generate_baseline: None,
         }
     }
 }
@@ -152,6 +169,8 @@ struct AnalyzeRuntimeConfig {
     stub_extensions: Option<Vec<String>>,
     include_paths: Vec<PathBuf>,
     exclude_paths: Vec<PathBuf>,
+    respect_gitignore: bool,
+    file_extensions: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -164,9 +183,10 @@ struct AnalyzeDiagnostic {
 #[derive(Debug)]
 struct AnalyzeReport {
     project_root: PathBuf,
-    target: PathBuf,
+    targets: Vec<PathBuf>,
     files_analyzed: usize,
     diagnostics: Vec<AnalyzeDiagnostic>,
+    baselined: usize,
 }
 
 struct AnalyzeLazyIndexContext<'a> {
@@ -183,6 +203,7 @@ struct JsonAnalyzeReport {
     schema_version: u32,
     project_root: String,
     target: String,
+    targets: Vec<String>,
     summary: JsonAnalyzeSummary,
     diagnostics: Vec<JsonAnalyzeDiagnostic>,
 }
@@ -192,6 +213,7 @@ struct JsonAnalyzeReport {
 struct JsonAnalyzeSummary {
     files_analyzed: usize,
     diagnostics: usize,
+    baselined: usize,
     errors: usize,
     warnings: usize,
     information: usize,
@@ -225,7 +247,31 @@ struct JsonAnalyzePosition {
 }
 
 pub fn parse_analyze_args(raw_args: Vec<String>) -> Result<AnalyzeArgs, AnalyzeError> {
-    let mut parsed = AnalyzeArgs::default();
+    let parsed = parse_diagnostics_args(raw_args, AnalyzeArgs::default(), analyze_help())?;
+    if parsed.paths.len() > 1 {
+        return Err(AnalyzeError::new(format!(
+            "Unexpected extra analyze path `{}`",
+            parsed.paths[1].display()
+        )));
+    }
+    Ok(parsed)
+}
+
+/// Arguments for `php-lsp check`: like `analyze`, but it accepts several
+/// paths and only fails on error diagnostics unless `--fail-on` says otherwise.
+pub fn parse_check_args(raw_args: Vec<String>) -> Result<AnalyzeArgs, AnalyzeError> {
+    let defaults = AnalyzeArgs {
+        fail_on: AnalyzeSeverity::Error,
+        ..AnalyzeArgs::default()
+    };
+    parse_diagnostics_args(raw_args, defaults, check_help())
+}
+
+fn parse_diagnostics_args(
+    raw_args: Vec<String>,
+    mut parsed: AnalyzeArgs,
+    help: &str,
+) -> Result<AnalyzeArgs, AnalyzeError> {
     let mut iter = raw_args.into_iter();
 
     while let Some(arg) = iter.next() {
@@ -252,25 +298,40 @@ pub fn parse_analyze_args(raw_args: Vec<String>) -> Result<AnalyzeArgs, AnalyzeE
                     .ok_or_else(|| AnalyzeError::new("Missing value for --format"))?;
                 parsed.format = AnalyzeFormat::parse(&value).ok_or_else(|| {
                     AnalyzeError::new(format!(
-                        "Invalid --format `{value}`; expected table, json, or github"
+                        "Invalid --format `{value}`; expected table, json, github, or checkstyle"
                     ))
                 })?;
             }
+            "--fail-on" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| AnalyzeError::new("Missing value for --fail-on"))?;
+                parsed.fail_on = AnalyzeSeverity::parse(&value).ok_or_else(|| {
+                    AnalyzeError::new(format!(
+                        "Invalid --fail-on `{value}`; expected all, hint, info, warning, or error"
+                    ))
+                })?;
+            }
+            "--baseline" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| AnalyzeError::new("Missing value for --baseline"))?;
+                parsed.baseline = Some(PathBuf::from(value));
+            }
+            "--generate-baseline" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| AnalyzeError::new("Missing value for --generate-baseline"))?;
+                parsed.generate_baseline = Some(PathBuf::from(value));
+            }
             "--help" | "-h" => {
-                return Err(AnalyzeError::new(analyze_help()));
+                return Err(AnalyzeError::new(help));
             }
             value if value.starts_with('-') => {
-                return Err(AnalyzeError::new(format!(
-                    "Unknown analyze option `{value}`"
-                )));
+                return Err(AnalyzeError::new(format!("Unknown option `{value}`")));
             }
             value => {
-                if parsed.path.is_some() {
-                    return Err(AnalyzeError::new(format!(
-                        "Unexpected extra analyze path `{value}`"
-                    )));
-                }
-                parsed.path = Some(PathBuf::from(value));
+                parsed.paths.push(PathBuf::from(value));
             }
         }
     }
@@ -279,22 +340,43 @@ pub fn parse_analyze_args(raw_args: Vec<String>) -> Result<AnalyzeArgs, AnalyzeE
 }
 
 pub fn analyze_help() -> &'static str {
-    "Usage:\n  php-lsp analyze [PATH] [--project-root <DIR>] [--severity <all|hint|info|warning|error>] [--format <table|json|github>]"
+    "Usage:\n  php-lsp analyze [PATH] [--project-root <DIR>] [--severity <all|hint|info|warning|error>] [--format <table|json|github|checkstyle>] [--fail-on <all|hint|info|warning|error>] [--baseline <FILE>] [--generate-baseline <FILE>]"
+}
+
+pub fn check_help() -> &'static str {
+    "Usage:\n  php-lsp check [PATH...] [--project-root <DIR>] [--severity <all|hint|info|warning|error>] [--format <human|json|github|checkstyle>] [--fail-on <all|hint|info|warning|error>] [--baseline <FILE>]"
 }
 
 pub fn run_analyze_cli(raw_args: Vec<String>) -> AnalyzeCliResult {
+    run_diagnostics_cli(raw_args, analyze_help(), parse_analyze_args)
+}
+
+/// Headless CI check: index the project, report diagnostics for the given
+/// paths, and exit with `2` when any error diagnostic is found.
+pub fn run_check_cli(raw_args: Vec<String>) -> AnalyzeCliResult {
+    run_diagnostics_cli(raw_args, check_help(), parse_check_args)
+}
+
+fn run_diagnostics_cli(
+    raw_args: Vec<String>,
+    help: &str,
+    parse_args: fn(Vec<String>) -> Result<AnalyzeArgs, AnalyzeError>,
+) -> AnalyzeCliResult {
+    // Headless runs have no client to negotiate with, so positions use the
+    // LSP default.
+    let encoding = PositionEncoding::default();
     if raw_args
         .iter()
         .any(|arg| matches!(arg.as_str(), "--help" | "-h" | "help"))
     {
         return AnalyzeCliResult {
             exit_code: 0,
-            stdout: format!("{}\n", analyze_help()),
+            stdout: format!("{help}\n"),
             stderr: String::new(),
         };
     }
 
-    let args = match parse_analyze_args(raw_args) {
+    let args = match parse_args(raw_args) {
         Ok(args) => args,
         Err(err) => {
             return AnalyzeCliResult {
@@ -305,10 +387,29 @@ pub fn run_analyze_cli(raw_args: Vec<String>) -> AnalyzeCliResult {
         }
     };
 
-    match run_analyze(&args) {
+    if let Some(baseline_path) = args.generate_baseline.as_ref() {
+        return match generate_analyze_baseline(&args, baseline_path, encoding) {
+            Ok(message) => AnalyzeCliResult {
+                exit_code: 0,
+                stdout: message,
+                stderr: String::new(),
+            },
+            Err(err) => AnalyzeCliResult {
+                exit_code: 1,
+                stdout: String::new(),
+                stderr: format!("{err}\n"),
+            },
+        };
+    }
+
+    match run_analyze(&args, encoding) {
         Ok(report) => {
             let stdout = render_report(&report, args.format);
-            let exit_code = if report.diagnostics.is_empty() { 0 } else { 2 };
+            let failed = report
+                .diagnostics
+                .iter()
+                .any(|item| args.fail_on.includes(item.diagnostic.severity));
+            let exit_code = if failed { 2 } else { 0 };
             AnalyzeCliResult {
                 exit_code,
                 stdout,
@@ -323,20 +424,79 @@ pub fn run_analyze_cli(raw_args: Vec<String>) -> AnalyzeCliResult {
     }
 }
 
-fn run_analyze(args: &AnalyzeArgs) -> Result<AnalyzeReport, AnalyzeError> {
-    let cwd = std::env::current_dir()
-        .map_err(|err| AnalyzeError::new(format!("Failed to read current directory: {err}")))?;
+fn generate_analyze_baseline(
+    args: &AnalyzeArgs,
+    baseline_path: &Path,
+    encoding: PositionEncoding,
+) -> Result<String, AnalyzeError> {
+    let baseline_args = AnalyzeArgs {
+        baseline: None,
+        generate_baseline: None,
+        ..args.clone()
+    };
+    let report = run_analyze(&baseline_args, encoding)?;
+    let cwd = current_dir()?;
+    let baseline_path = resolve_baseline_path(&cwd, args, baseline_path)?;
+    let mut baseline = DiagnosticBaseline::new(baseline_root(&baseline_path));
+    for item in &report.diagnostics {
+        baseline.add(&item.path, &item.diagnostic);
+    }
+    baseline.write(&baseline_path).map_err(AnalyzeError::new)?;
+    Ok(format!(
+        "Wrote {} diagnostics to baseline {}\n",
+        baseline.len(),
+        baseline_path.display()
+    ))
+}
+
+/// Resolve a baseline path the same way as the analyze target: relative to
+/// `--project-root` when given, otherwise to the current directory. The file
+/// itself may not exist yet, so only its directory is canonicalized.
+fn resolve_baseline_path(
+    cwd: &Path,
+    args: &AnalyzeArgs,
+    path: &Path,
+) -> Result<PathBuf, AnalyzeError> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else if let Some(project_root) = args.project_root.as_ref() {
+        resolve_existing_path(cwd, project_root)?.join(path)
+    } else {
+        cwd.join(path)
+    };
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| AnalyzeError::new(format!("Invalid baseline path {}", path.display())))?;
+    Ok(resolve_existing_path(cwd, &baseline_root(&path))?.join(file_name))
+}
+
+fn current_dir() -> Result<PathBuf, AnalyzeError> {
+    std::env::current_dir()
+        .map_err(|err| AnalyzeError::new(format!("Failed to read current directory: {err}")))
+}
+
+fn run_analyze(
+    args: &AnalyzeArgs,
+    encoding: PositionEncoding,
+) -> Result<AnalyzeReport, AnalyzeError> {
+    let cwd = current_dir()?;
     let explicit_project_root = args.project_root.is_some();
-    let default_target = args.path.is_none();
     let requested_project_root = args.project_root.clone().unwrap_or_else(|| cwd.clone());
     let requested_project_root = resolve_existing_path(&cwd, &requested_project_root)?;
-    let requested_target = match args.path.as_ref() {
-        Some(path) if path.is_absolute() => path.clone(),
-        Some(path) if explicit_project_root => requested_project_root.join(path),
-        Some(path) => cwd.join(path),
-        None => requested_project_root.clone(),
-    };
-    let requested_target = resolve_existing_path(&cwd, &requested_target)?;
+    let requested_targets = args
+        .paths
+        .iter()
+        .map(|path| {
+            let path = if path.is_absolute() {
+                path.clone()
+            } else if explicit_project_root {
+                requested_project_root.join(path)
+            } else {
+                cwd.join(path)
+            };
+            resolve_existing_path(&cwd, &path)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     let (settings, messages) = load_effective_configuration_settings(
         std::slice::from_ref(&requested_project_root),
@@ -350,22 +510,35 @@ fn run_analyze(args: &AnalyzeArgs) -> Result<AnalyzeReport, AnalyzeError> {
     let workspace_config =
         discover_workspace_root_config(&requested_project_root, runtime_config.composer_enabled);
     let project_root = workspace_config.root;
-    let requested_target = if default_target {
-        project_root.clone()
+    let requested_targets = if requested_targets.is_empty() {
+        vec![project_root.clone()]
     } else {
-        requested_target
+        requested_targets
     };
+    let exclude_paths = workspace_exclude_paths(
+        &project_root,
+        &runtime_config.exclude_paths,
+        runtime_config.respect_gitignore,
+    );
     let workspace_files = collect_workspace_analyze_files(
         &project_root,
         workspace_config.namespace_map.as_ref(),
         &runtime_config.include_paths,
-        &runtime_config.exclude_paths,
+        &exclude_paths,
+        &runtime_config.file_extensions,
     );
-    let target_files = collect_target_analyze_files(
-        &requested_target,
-        &project_root,
-        &runtime_config.exclude_paths,
-    )?;
+    let mut target_files = Vec::new();
+    for requested_target in &requested_targets {
+        for file in collect_target_analyze_files(
+            requested_target,
+            &project_root,
+            &exclude_paths,
+            &runtime_config.file_extensions,
+        )? {
+            push_unique_path(&mut target_files, file);
+        }
+    }
+    target_files.sort();
 
     let mut all_files = workspace_files.clone();
     for file in &target_files {
@@ -387,13 +560,19 @@ fn run_analyze(args: &AnalyzeArgs) -> Result<AnalyzeReport, AnalyzeError> {
         .then(|| parse_vendor_autoload_map(&project_root.join("vendor")))
         .flatten();
     if let Some(vendor_map) = vendor_autoload_map.as_ref() {
-        preload_analyze_vendor_entrypoints(&index, &project_root, &runtime_config, vendor_map);
+        preload_analyze_vendor_entrypoints(
+            &index,
+            &project_root,
+            &runtime_config,
+            vendor_map,
+            encoding,
+        );
     }
 
     let mut parsed_by_path = HashMap::new();
     for path in all_files {
         let parsed = parse_analyze_file(&path)?;
-        index_analyze_file(&index, &parsed);
+        index_analyze_file(&index, &parsed, encoding);
         parsed_by_path.insert(path, parsed);
     }
 
@@ -409,9 +588,18 @@ fn run_analyze(args: &AnalyzeArgs) -> Result<AnalyzeReport, AnalyzeError> {
         let parsed = parsed_by_path.get(target_file).ok_or_else(|| {
             AnalyzeError::new(format!("Failed to parse {}", target_file.display()))
         })?;
-        pre_resolve_analyze_file_dependencies(parsed, &lazy_index_context);
+        pre_resolve_analyze_file_dependencies(parsed, &lazy_index_context, encoding);
     }
 
+    let baseline = args
+        .baseline
+        .as_ref()
+        .map(|path| {
+            let path = resolve_baseline_path(&cwd, args, path)?;
+            DiagnosticBaseline::load(&path).map_err(AnalyzeError::new)
+        })
+        .transpose()?;
+    let mut baselined = 0;
     let mut diagnostics = Vec::new();
     for target_file in &target_files {
         let parsed = parsed_by_path.get(target_file).ok_or_else(|| {
@@ -426,20 +614,36 @@ fn run_analyze(args: &AnalyzeArgs) -> Result<AnalyzeReport, AnalyzeError> {
                 severity: runtime_config.diagnostic_severity,
                 budget: runtime_config.diagnostic_budget,
                 php_version: runtime_config.php_version,
+                encoding,
             },
             None,
         );
-        let file_diagnostics =
-            filter_analyze_lazy_resolved_symbol_diagnostics(file_diagnostics, &lazy_index_context);
-        diagnostics.extend(file_diagnostics.into_iter().filter_map(|diagnostic| {
-            args.severity
-                .includes(diagnostic.severity)
-                .then(|| AnalyzeDiagnostic {
+        let file_diagnostics: Vec<Diagnostic> = filter_analyze_lazy_resolved_symbol_diagnostics(
+            file_diagnostics,
+            &lazy_index_context,
+            encoding,
+        )
+        .into_iter()
+        .filter(|diagnostic| args.severity.includes(diagnostic.severity))
+        .collect();
+        let file_diagnostics = match baseline.as_ref() {
+            Some(baseline) => {
+                let before = file_diagnostics.len();
+                let kept = baseline.filter(&parsed.path, file_diagnostics);
+                baselined += before - kept.len();
+                kept
+            }
+            None => file_diagnostics,
+        };
+        diagnostics.extend(
+            file_diagnostics
+                .into_iter()
+                .map(|diagnostic| AnalyzeDiagnostic {
                     path: parsed.path.clone(),
                     uri: parsed.uri.clone(),
                     diagnostic,
-                })
-        }));
+                }),
+        );
     }
 
     diagnostics.sort_by(|left, right| {
@@ -461,9 +665,10 @@ fn run_analyze(args: &AnalyzeArgs) -> Result<AnalyzeReport, AnalyzeError> {
 
     Ok(AnalyzeReport {
         project_root,
-        target: requested_target,
+        targets: requested_targets,
         files_analyzed: target_files.len(),
         diagnostics,
+        baselined,
     })
 }
 
@@ -485,7 +690,11 @@ fn analyze_runtime_config(settings: &serde_json::Value) -> AnalyzeRuntimeConfig
     let diagnostic_budget = diagnostic_budget_config_from_settings(settings);
     let composer_enabled =
         settings_bool(settings, "composerEnabled", &["composer", "enabled"]).unwrap_or(true);
-    let index_vendor = settings_bool(settings, "indexVendor", &["indexVendor"]).unwrap_or(true);
+    let index_vendor = settings_string(settings, "vendorIndexing", &["vendorIndexing"])
+        .and_then(VendorIndexing::parse)
+        .map(VendorIndexing::is_enabled)
+        .or_else(|| settings_bool(settings, "indexVendor", &["indexVendor"]))
+        .unwrap_or(true);
     let stubs_path = settings_string_any(
         settings,
         "stubsPath",
@@ -495,14 +704,24 @@ fn analyze_runtime_config(settings: &serde_json::Value) -> AnalyzeRuntimeConfig
         let trimmed = path.trim();
         (!trimmed.is_empty()).then(|| PathBuf::from(trimmed))
     });
-    let stub_extensions =
-        settings_string_array(settings, "stubExtensions", &["stubs", "extensions"]);
+    let stub_extensions = configured_stub_extensions(
+        settings_string_array(settings, "stubExtensions", &["stubs", "extensions"]),
+        settings_string_array(settings, "stubExtensionsEnable", &["stubs", "enable"])
+            .unwrap_or_default(),
+        settings_string_array(settings, "stubExtensionsDisable", &["stubs", "disable"])
+            .unwrap_or_default(),
+    );
     let include_paths = settings_string_array(settings, "includePaths", &["includePaths"])
         .map(normalize_config_paths)
         .unwrap_or_default();
     let exclude_paths = settings_string_array(settings, "excludePaths", &["excludePaths"])
         .map(normalize_config_paths)
         .unwrap_or_default();
+    let respect_gitignore =
+        settings_bool(settings, "respectGitignore", &["respectGitignore"]).unwrap_or(false);
+    let file_extensions = settings_string_array(settings, "fileExtensions", &["fileExtensions"])
+        .map(normalize_php_file_extensions)
+        .unwrap_or_else(default_php_file_extensions);
 
     AnalyzeRuntimeConfig {
         php_version,
@@ -515,6 +734,8 @@ fn analyze_runtime_config(settings: &serde_json::Value) -> AnalyzeRuntimeConfig
         stub_extensions,
         include_paths,
         exclude_paths,
+        respect_gitignore,
+        file_extensions,
     }
 }
 
@@ -595,9 +816,10 @@ fn collect_workspace_analyze_files(
     namespace_map: Option<&php_lsp_index::composer::NamespaceMap>,
     include_paths: &[PathBuf],
     exclude_paths: &[PathBuf],
+    file_extensions: &[String],
 ) -> Vec<PathBuf> {
     let source_dirs = workspace_index_directories(project_root, namespace_map, include_paths);
-    let mut files = collect_php_files(&source_dirs, project_root, exclude_paths);
+    let mut files = collect_php_files(&source_dirs, project_root, exclude_paths, file_extensions);
     if let Some(namespace_map) = namespace_map {
         for file_path in &namespace_map.files {
             let abs = if file_path.is_absolute() {
@@ -618,9 +840,10 @@ fn collect_target_analyze_files(
     target: &Path,
     project_root: &Path,
     exclude_paths: &[PathBuf],
+    file_extensions: &[String],
 ) -> Result<Vec<PathBuf>, AnalyzeError> {
     if target.is_file() {
-        return if target.extension().and_then(|ext| ext.to_str()) == Some("php") {
+        return if path_has_php_extension(target, file_extensions) {
             Ok(vec![target.to_path_buf()])
         } else {
             Err(AnalyzeError::new(format!(
@@ -630,7 +853,12 @@ fn collect_target_analyze_files(
         };
     }
     if target.is_dir() {
-        let mut files = collect_php_files(&[target.to_path_buf()], project_root, exclude_paths);
+        let mut files = collect_php_files(
+            &[target.to_path_buf()],
+            project_root,
+            exclude_paths,
+            file_extensions,
+        );
         files.sort();
         return Ok(files);
     }
@@ -642,9 +870,9 @@ fn collect_target_analyze_files(
 }
 
 fn parse_analyze_file(path: &Path) -> Result<ParsedAnalyzeFile, AnalyzeError> {
-    let bytes = std::fs::read(path)
-        .map_err(|err| AnalyzeError::new(format!("Failed to read {}: {err}", path.display())))?;
-    let source = String::from_utf8_lossy(&bytes).into_owned();
+    let source = read_php_source(path)
+        .map_err(|err| AnalyzeError::new(format!("Failed to read {}: {err}", path.display())))?
+        .text;
     let mut parser = FileParser::new();
     parser.parse_full(&source);
     if parser.tree().is_none() {
@@ -660,25 +888,34 @@ fn parse_analyze_file(path: &Path) -> Result<ParsedAnalyzeFile, AnalyzeError> {
     })
 }
 
-fn index_analyze_file(index: &WorkspaceIndex, parsed: &ParsedAnalyzeFile) {
+fn index_analyze_file(
+    index: &WorkspaceIndex,
+    parsed: &ParsedAnalyzeFile,
+    encoding: PositionEncoding,
+) {
     let source = parsed.parser.source();
     let Some(tree) = parsed.parser.tree() else {
         return;
     };
     let file_symbols = extract_file_symbols(tree, &source, &parsed.uri);
-    let references = collect_symbol_references_in_file(tree, &source, &file_symbols);
+    let references = collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
     index.update_file_with_references(&parsed.uri, file_symbols, references);
 }
 
-fn parse_and_index_analyze_php_file(index: &WorkspaceIndex, file_path: &Path) -> bool {
-    if !file_path.is_file() || file_path.extension().and_then(|ext| ext.to_str()) != Some("php") {
+fn parse_and_index_analyze_php_file(
+    index: &WorkspaceIndex,
+    file_path: &Path,
+    file_extensions: &[String],
+    encoding: PositionEncoding,
+) -> bool {
+    if !file_path.is_file() || !path_has_php_extension(file_path, file_extensions) {
         return false;
     }
 
     let Ok(parsed) = parse_analyze_file(file_path) else {
         return false;
     };
-    index_analyze_file(index, &parsed);
+    index_analyze_file(index, &parsed, encoding);
     true
 }
 
@@ -687,17 +924,27 @@ fn preload_analyze_vendor_entrypoints(
     project_root: &Path,
     runtime_config: &AnalyzeRuntimeConfig,
     vendor_map: &VendorAutoloadMap,
+    encoding: PositionEncoding,
 ) {
-    for file_path in
-        vendor_autoload_file_paths_from_map(vendor_map, project_root, &runtime_config.exclude_paths)
-    {
-        parse_and_index_analyze_php_file(index, &file_path);
+    for file_path in vendor_autoload_file_paths_from_map(
+        vendor_map,
+        project_root,
+        &runtime_config.exclude_paths,
+        &runtime_config.file_extensions,
+    ) {
+        parse_and_index_analyze_php_file(
+            index,
+            &file_path,
+            &runtime_config.file_extensions,
+            encoding,
+        );
     }
 }
 
 fn pre_resolve_analyze_file_dependencies(
     parsed: &ParsedAnalyzeFile,
     context: &AnalyzeLazyIndexContext<'_>,
+    encoding: PositionEncoding,
 ) {
     let Some(tree) = parsed.parser.tree() else {
         return;
@@ -721,13 +968,14 @@ fn pre_resolve_analyze_file_dependencies(
     }
 
     for fqn in fqns {
-        analyze_index_class_dependencies(context, &fqn);
+        analyze_index_class_dependencies(context, &fqn, encoding);
     }
 }
 
 fn filter_analyze_lazy_resolved_symbol_diagnostics(
     diagnostics: Vec<Diagnostic>,
     context: &AnalyzeLazyIndexContext<'_>,
+    encoding: PositionEncoding,
 ) -> Vec<Diagnostic> {
     let mut filtered = Vec::with_capacity(diagnostics.len());
 
@@ -736,7 +984,7 @@ fn filter_analyze_lazy_resolved_symbol_diagnostics(
             if let Some(fqn) = lazy_resolvable_diagnostic_fqn(&diagnostic.message) {
                 let unresolved_use_statement =
                     diagnostic.message.starts_with("Unresolved use statement: ");
-                analyze_index_class_dependencies(context, &fqn);
+                analyze_index_class_dependencies(context, &fqn, encoding);
                 if lazy_resolved_symbol_diagnostic_is_satisfied(
                     context.index,
                     &diagnostic.message,
@@ -762,9 +1010,10 @@ fn filter_analyze_lazy_resolved_symbol_diagnostics(
 fn analyze_index_class_dependencies(
     context: &AnalyzeLazyIndexContext<'_>,
     class_or_member_fqn: &str,
+    encoding: PositionEncoding,
 ) {
     let mut visited = HashSet::new();
-    analyze_index_class_dependencies_inner(context, class_or_member_fqn, &mut visited, 0);
+    analyze_index_class_dependencies_inner(context, class_or_member_fqn, &mut visited, 0, encoding);
 }
 
 fn analyze_index_class_dependencies_inner(
@@ -772,6 +1021,7 @@ fn analyze_index_class_dependencies_inner(
     class_or_member_fqn: &str,
     visited: &mut HashSet<String>,
     depth: usize,
+    encoding: PositionEncoding,
 ) {
     const MAX_DEPTH: usize = 10;
     if depth >= MAX_DEPTH {
@@ -783,12 +1033,12 @@ fn analyze_index_class_dependencies_inner(
         return;
     }
 
-    analyze_index_class(context, &class_fqn);
+    analyze_index_class(context, &class_fqn, encoding);
 
     let parent_fqns: Vec<String> = context
         .index
         .types
-        .get(&class_fqn)
+        .get(class_fqn.as_str())
         .map(|symbol| {
             symbol
                 .extends
@@ -801,11 +1051,15 @@ fn analyze_index_class_dependencies_inner(
         .unwrap_or_default();
 
     for parent_fqn in parent_fqns {
-        analyze_index_class_dependencies_inner(context, &parent_fqn, visited, depth + 1);
+        analyze_index_class_dependencies_inner(context, &parent_fqn, visited, depth + 1, encoding);
     }
 }
 
-fn analyze_index_class(context: &AnalyzeLazyIndexContext<'_>, class_fqn: &str) -> bool {
+fn analyze_index_class(
+    context: &AnalyzeLazyIndexContext<'_>,
+    class_fqn: &str,
+    encoding: PositionEncoding,
+) -> bool {
     if context.index.types.contains_key(class_fqn) {
         return false;
     }
@@ -816,7 +1070,11 @@ fn analyze_index_class(context: &AnalyzeLazyIndexContext<'_>, class_fqn: &str) -
         .unwrap_or_default();
     if paths.is_empty() {
         if let Some(vendor_map) = context.vendor_map {
-            if let Some(vendor_paths) = resolve_vendor_paths_from_map(class_fqn, vendor_map) {
+            if let Some(vendor_paths) = resolve_vendor_paths_from_map(
+                class_fqn,
+                vendor_map,
+                &context.runtime_config.file_extensions,
+            ) {
                 paths.extend(vendor_paths);
             }
         }
@@ -835,8 +1093,12 @@ fn analyze_index_class(context: &AnalyzeLazyIndexContext<'_>, class_fqn: &str) -
         ) {
             continue;
         }
-        if parse_and_index_analyze_php_file(context.index, &abs)
-            && context.index.types.contains_key(class_fqn)
+        if parse_and_index_analyze_php_file(
+            context.index,
+            &abs,
+            &context.runtime_config.file_extensions,
+            encoding,
+        ) && context.index.types.contains_key(class_fqn)
         {
             return true;
         }
@@ -870,6 +1132,7 @@ fn render_report(report: &AnalyzeReport, format: AnalyzeFormat) -> String {
         AnalyzeFormat::Table => render_table_report(report),
         AnalyzeFormat::Json => render_json_report(report),
         AnalyzeFormat::Github => render_github_report(report),
+        AnalyzeFormat::Checkstyle => render_checkstyle_report(report),
     }
 }
 
@@ -899,7 +1162,12 @@ fn render_json_report(report: &AnalyzeReport) -> String {
     let json_report = JsonAnalyzeReport {
         schema_version: 1,
         project_root: report.project_root.display().to_string(),
-        target: report.target.display().to_string(),
+        target: report.targets[0].display().to_string(),
+        targets: report
+            .targets
+            .iter()
+            .map(|target| target.display().to_string())
+            .collect(),
         summary: json_summary(report),
         diagnostics: report
             .diagnostics
@@ -960,10 +1228,50 @@ fn render_github_report(report: &AnalyzeReport) -> String {
     out
 }
 
+/// Checkstyle XML, grouped per file, as consumed by Jenkins, GitLab code
+/// quality converters and reviewdog.
+fn render_checkstyle_report(report: &AnalyzeReport) -> String {
+    let mut out =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+    let mut current_path: Option<String> = None;
+    for item in &report.diagnostics {
+        let diagnostic = &item.diagnostic;
+        let path = display_path(&item.path, &report.project_root);
+        if current_path.as_deref() != Some(path.as_str()) {
+            if current_path.is_some() {
+                out.push_str("  </file>\n");
+            }
+            out.push_str(&format!("  <file name=\"{}\">\n", xml_escape(&path)));
+            current_path = Some(path);
+        }
+        let severity = match diagnostic.severity {
+            Some(DiagnosticSeverity::ERROR) => "error",
+            Some(DiagnosticSeverity::WARNING) => "warning",
+            _ => "info",
+        };
+        let source = diagnostic_code(diagnostic)
+            .or_else(|| diagnostic.source.clone())
+            .unwrap_or_else(|| "php-lsp".to_string());
+        out.push_str(&format!(
+            "    <error line=\"{}\" column=\"{}\" severity=\"{severity}\" message=\"{}\" source=\"{}\"/>\n",
+            diagnostic.range.start.line + 1,
+            diagnostic.range.start.character + 1,
+            xml_escape(&compact_message(&diagnostic.message)),
+            xml_escape(&source)
+        ));
+    }
+    if current_path.is_some() {
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</checkstyle>\n");
+    out
+}
+
 fn json_summary(report: &AnalyzeReport) -> JsonAnalyzeSummary {
     JsonAnalyzeSummary {
         files_analyzed: report.files_analyzed,
         diagnostics: report.diagnostics.len(),
+        baselined: report.baselined,
         errors: report
             .diagnostics
             .iter()
@@ -1025,6 +1333,15 @@ fn compact_message(message: &str) -> String {
     message.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 fn github_escape_property(value: &str) -> String {
     github_escape_message(value)
         .replace(':', "%3A")
@@ -1056,7 +1373,7 @@ mod tests {
         ])
         .unwrap();
 
-        assert_eq!(args.path, Some(PathBuf::from("src")));
+        assert_eq!(args.paths, vec![PathBuf::from("src")]);
         assert_eq!(args.project_root, Some(PathBuf::from("/tmp/project")));
         assert_eq!(args.severity, AnalyzeSeverity::Warning);
         assert_eq!(args.format, AnalyzeFormat::Json);
@@ -1125,6 +1442,86 @@ mod tests {
         let _ = std::fs::remove_dir_all(broken_root);
     }
 
+    #[test]
+    fn check_accepts_several_paths_and_fails_only_on_errors() {
+        let root = temp_dir("check-fail-on");
+        std::fs::write(
+            root.join("Warn.php"),
+            "<?php\nnamespace App;\nfunction warn(): void { new MissingClass(); }\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("Clean.php"), "<?php\nclass Clean {}\n").unwrap();
+        std::fs::write(root.join("Broken.php"), "<?php\nclass Broken {\n").unwrap();
+        let check = |paths: &[&str], extra: &[&str]| {
+            let mut args = vec!["--project-root".to_string(), root.display().to_string()];
+            args.extend(paths.iter().chain(extra).map(|arg| arg.to_string()));
+            run_check_cli(args)
+        };
+
+        let warnings_only = check(&["Warn.php", "Clean.php"], &["--format", "json"]);
+        assert_eq!(
+            warnings_only.exit_code, 0,
+            "stderr: {}",
+            warnings_only.stderr
+        );
+        let value: serde_json::Value = serde_json::from_str(&warnings_only.stdout).unwrap();
+        assert_eq!(value["summary"]["filesAnalyzed"], 2);
+        assert_eq!(value["summary"]["warnings"], 1);
+        assert_eq!(value["targets"].as_array().unwrap().len(), 2);
+
+        let strict = check(&["Warn.php"], &["--fail-on", "warning"]);
+        assert_eq!(strict.exit_code, 2, "stderr: {}", strict.stderr);
+
+        let with_errors = check(&["Warn.php", "Broken.php"], &[]);
+        assert_eq!(with_errors.exit_code, 2, "stderr: {}", with_errors.stderr);
+        assert!(with_errors.stdout.contains("Broken.php:"));
+
+        let analyze = run_analyze_cli(vec!["Warn.php".to_string(), "Clean.php".to_string()]);
+        assert_eq!(analyze.exit_code, 1);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn checkstyle_output_groups_escaped_diagnostics_by_file() {
+        let root = temp_dir("checkstyle");
+        std::fs::write(root.join("A.php"), "<?php\nclass A {\n").unwrap();
+        std::fs::write(
+            root.join("B.php"),
+            "<?php\nnamespace App;\nfunction b(): void { new MissingClass(); }\n",
+        )
+        .unwrap();
+
+        let result = run_check_cli(vec![
+            "--project-root".to_string(),
+            root.display().to_string(),
+            "--format".to_string(),
+            "checkstyle".to_string(),
+        ]);
+
+        assert!(result
+            .stdout
+            .starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n  <file name=\"A.php\">\n"));
+        assert!(result
+            .stdout
+            .contains("  </file>\n  <file name=\"B.php\">\n"));
+        assert!(
+            result.stdout.contains("severity=\"error\""),
+            "{}",
+            result.stdout
+        );
+        assert!(result
+            .stdout
+            .contains("severity=\"warning\" message=\"Unknown class"));
+        assert_eq!(
+            xml_escape("a < b && \"c\" > 'd'"),
+            "a &lt; b &amp;&amp; &quot;c&quot; &gt; &apos;d&apos;"
+        );
+        assert!(result.stdout.ends_with("  </file>\n</checkstyle>\n"));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
     #[test]
     fn analyze_resolves_vendor_psr4_symbols_from_composer_installed_metadata() {
         let root = temp_dir("vendor-psr4");
@@ -1492,6 +1889,58 @@ mod tests {
         let _ = std::fs::remove_dir_all(root);
     }
 
+    #[test]
+    fn analyze_baseline_hides_existing_diagnostics_and_reports_new_ones() {
+        let root = temp_dir("baseline");
+        let file = root.join("Legacy.php");
+        std::fs::write(
+            &file,
+            "<?php\nnamespace App;\nfunction demo(): void { new MissingClass(); }\n",
+        )
+        .unwrap();
+        let root_arg = root.display().to_string();
+
+        let generated = run_analyze_cli(vec![
+            "--project-root".to_string(),
+            root_arg.clone(),
+            "--generate-baseline".to_string(),
+            "php-lsp-baseline.json".to_string(),
+        ]);
+        assert_eq!(generated.exit_code, 0, "stderr: {}", generated.stderr);
+        assert!(generated.stdout.contains("Wrote 1 diagnostics"));
+        assert!(root.join("php-lsp-baseline.json").is_file());
+
+        let baselined = run_analyze_cli(vec![
+            "--project-root".to_string(),
+            root_arg.clone(),
+            "--baseline".to_string(),
+            "php-lsp-baseline.json".to_string(),
+            "--format".to_string(),
+            "json".to_string(),
+        ]);
+        assert_eq!(baselined.exit_code, 0, "stdout: {}", baselined.stdout);
+        let value: serde_json::Value = serde_json::from_str(&baselined.stdout).unwrap();
+        assert_eq!(value["summary"]["diagnostics"], 0);
+        assert_eq!(value["summary"]["baselined"], 1);
+
+        std::fs::write(
+            &file,
+            "<?php\nnamespace App;\n\nfunction demo(): void { new MissingClass(); new OtherMissing(); }\n",
+        )
+        .unwrap();
+        let regressed = run_analyze_cli(vec![
+            "--project-root".to_string(),
+            root_arg,
+            "--baseline".to_string(),
+            "php-lsp-baseline.json".to_string(),
+        ]);
+        assert_eq!(regressed.exit_code, 2, "stderr: {}", regressed.stderr);
+        assert!(regressed.stdout.contains("OtherMissing"));
+        assert!(!regressed.stdout.contains("App\\MissingClass"));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
     fn temp_dir(label: &str) -> PathBuf {
         let dir = std::env::temp_dir().join(format!(
             "php-lsp-analyze-{label}-{}",
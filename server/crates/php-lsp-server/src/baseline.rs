@@ -0,0 +1,307 @@
+//! Diagnostics baseline files.
+//!
+//! A baseline records diagnostics that already exist in a project so that the
+//! CLI analyzer and the language server can hide them and only report new
+//! issues. Entries are keyed by file path (relative to the baseline file's
+//! directory), diagnostic source, code, and message, with an occurrence count,
+//! so they survive unrelated edits that shift line numbers.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tower_lsp::ls_types::{Diagnostic, NumberOrString};
+
+pub const DEFAULT_BASELINE_FILE_NAME: &str = "php-lsp-baseline.json";
+
+const BASELINE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct BaselineKey {
+    source: String,
+    code: String,
+    message: String,
+}
+
+impl BaselineKey {
+    fn from_diagnostic(diagnostic: &Diagnostic) -> Self {
+        Self {
+            source: diagnostic.source.clone().unwrap_or_default(),
+            code: match diagnostic.code.as_ref() {
+                Some(NumberOrString::String(value)) => value.clone(),
+                Some(NumberOrString::Number(value)) => value.to_string(),
+                None => String::new(),
+            },
+            message: normalize_message(&diagnostic.message),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BaselineFile {
+    schema_version: u32,
+    #[serde(default)]
+    entries: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BaselineEntry {
+    path: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    source: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    code: String,
+    message: String,
+    #[serde(default = "default_entry_count")]
+    count: usize,
+}
+
+fn default_entry_count() -> usize {
+    1
+}
+
+/// Baselined diagnostics grouped by root-relative file path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticBaseline {
+    root: PathBuf,
+    files: HashMap<String, BTreeMap<BaselineKey, usize>>,
+}
+
+impl DiagnosticBaseline {
+    /// Create an empty baseline whose paths are relative to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Load a baseline file. Paths inside it are relative to its directory.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read baseline {}: {}", path.display(), err))?;
+        let file: BaselineFile = serde_json::from_str(&content)
+            .map_err(|err| format!("failed to parse baseline {}: {}", path.display(), err))?;
+        if file.schema_version != BASELINE_SCHEMA_VERSION {
+            return Err(format!(
+                "unsupported baseline schema version {} in {}",
+                file.schema_version,
+                path.display()
+            ));
+        }
+
+        let mut baseline = Self::new(baseline_root(path));
+        for entry in file.entries {
+            let key = BaselineKey {
+                source: entry.source,
+                code: entry.code,
+                message: normalize_message(&entry.message),
+            };
+            *baseline
+                .files
+                .entry(normalize_relative_path(&entry.path))
+                .or_default()
+                .entry(key)
+                .or_default() += entry.count.max(1);
+        }
+        Ok(baseline)
+    }
+
+    /// Write the baseline as pretty-printed JSON with a stable entry order.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let mut out = self.to_json();
+        out.push('\n');
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create {}: {}", parent.display(), err))?;
+        }
+        std::fs::write(path, out)
+            .map_err(|err| format!("failed to write baseline {}: {}", path.display(), err))
+    }
+
+    fn to_json(&self) -> String {
+        let mut paths: Vec<&String> = self.files.keys().collect();
+        paths.sort();
+        let entries = paths
+            .into_iter()
+            .flat_map(|path| {
+                self.files[path]
+                    .iter()
+                    .map(move |(key, count)| BaselineEntry {
+                        path: path.clone(),
+                        source: key.source.clone(),
+                        code: key.code.clone(),
+                        message: key.message.clone(),
+                        count: *count,
+                    })
+            })
+            .collect();
+        serde_json::to_string_pretty(&BaselineFile {
+            schema_version: BASELINE_SCHEMA_VERSION,
+            entries,
+        })
+        .expect("JSON serialization for diagnostics baseline should not fail")
+    }
+
+    /// Record one diagnostic for `file_path`.
+    pub fn add(&mut self, file_path: &Path, diagnostic: &Diagnostic) {
+        let Some(relative) = self.relative_key(file_path) else {
+            return;
+        };
+        *self
+            .files
+            .entry(relative)
+            .or_default()
+            .entry(BaselineKey::from_diagnostic(diagnostic))
+            .or_default() += 1;
+    }
+
+    /// Number of baselined diagnostics.
+    pub fn len(&self) -> usize {
+        self.files
+            .values()
+            .flat_map(|entries| entries.values())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop diagnostics for `file_path` that are covered by the baseline.
+    ///
+    /// Each entry suppresses at most `count` matching diagnostics, so new
+    /// occurrences of an already-baselined message are still reported.
+    pub fn filter(&self, file_path: &Path, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let Some(entries) = self
+            .relative_key(file_path)
+            .and_then(|relative| self.files.get(&relative))
+        else {
+            return diagnostics;
+        };
+
+        let mut remaining = entries.clone();
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| {
+                match remaining.get_mut(&BaselineKey::from_diagnostic(diagnostic)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+
+    fn relative_key(&self, file_path: &Path) -> Option<String> {
+        let relative = file_path.strip_prefix(&self.root).ok()?;
+        Some(normalize_relative_path(&relative.to_string_lossy()))
+    }
+}
+
+/// Directory that baseline paths are relative to.
+pub fn baseline_root(path: &Path) -> PathBuf {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn normalize_relative_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+fn normalize_message(message: &str) -> String {
+    message.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::ls_types::{Position, Range};
+
+    fn diagnostic(code: &str, message: &str, line: u32) -> Diagnostic {
+        Diagnostic {
+            range: Range {
+                start: Position::new(line, 0),
+                end: Position::new(line, 1),
+            },
+            source: Some("php-lsp".to_string()),
+            code: Some(NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn baseline_filters_known_diagnostics_by_count_ignoring_lines() {
+        let root = PathBuf::from("/project");
+        let file = root.join("src/Foo.php");
+        let mut baseline = DiagnosticBaseline::new(&root);
+        baseline.add(
+            &file,
+            &diagnostic("php-lsp.unknownClass", "Unknown class: App\\Missing", 3),
+        );
+
+        let filtered = baseline.filter(
+            &file,
+            vec![
+                diagnostic("php-lsp.unknownClass", "Unknown class: App\\Missing", 10),
+                diagnostic("php-lsp.unknownClass", "Unknown class: App\\Missing", 12),
+                diagnostic("php-lsp.unusedImport", "Unused import: App\\Bar", 1),
+            ],
+        );
+
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].range.start.line, 12);
+        assert_eq!(filtered[1].message, "Unused import: App\\Bar");
+        assert_eq!(
+            baseline.filter(&root.join("src/Other.php"), filtered).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn baseline_round_trips_through_json_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "php-lsp-baseline-test-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DEFAULT_BASELINE_FILE_NAME);
+        let file = dir.join("src/Foo.php");
+
+        let mut baseline = DiagnosticBaseline::new(baseline_root(&path));
+        baseline.add(
+            &file,
+            &diagnostic("php-lsp.unusedVariable", "Unused variable: $a", 4),
+        );
+        baseline.add(
+            &file,
+            &diagnostic("php-lsp.unusedVariable", "Unused variable: $a", 8),
+        );
+        baseline.write(&path).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["schemaVersion"], 1);
+        assert_eq!(json["entries"][0]["path"], "src/Foo.php");
+        assert_eq!(json["entries"][0]["count"], 2);
+
+        let loaded = DiagnosticBaseline::load(&path).unwrap();
+        assert_eq!(loaded, baseline);
+        assert_eq!(loaded.len(), 2);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
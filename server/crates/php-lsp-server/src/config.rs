@@ -15,6 +15,12 @@ mode = "basic-semantic"
 # Set to 0 to disable the budget cap for this project.
 memberTypeNodeBudget = 512
 partialAnalysisDiagnostic = true
+# Quiet period after the last edit before diagnostics are recomputed.
+debounceMs = 180
+# Only run syntax diagnostics while typing; semantic diagnostics on save.
+semanticOnSave = false
+# Hide diagnostics recorded by `php-lsp analyze --generate-baseline <FILE>`.
+# baseline = "php-lsp-baseline.json"
 
 [diagnostics.severity]
 unknownSymbols = "warning"
@@ -30,6 +36,8 @@ composer = true
 vendor = true
 include = []
 exclude = []
+gitignore = false
+extensions = ["php"]
 
 [stubs]
 # Omit `extensions` to discover all available stub extension directories. Set `extensions = []` to disable stubs.
@@ -50,12 +58,45 @@ timeoutMs = 30000
 memory_limit = ""
 
 [psalm]
+# "auto" runs Psalm only when psalm.xml or psalm.xml.dist is in the workspace root.
 enabled = false
 command = "vendor/bin/psalm --output-format=json --no-progress {file}"
 timeoutMs = 30000
 
+[phpcs]
+# phpcs violations are published as information diagnostics; phpcbf backs the
+# "Fix with phpcbf" and source.fixAll.phpcbf code actions.
+enabled = false
+command = "vendor/bin/phpcs --report=json -q {file}"
+fixCommand = "vendor/bin/phpcbf -q {file}"
+timeoutMs = 30000
+
+[runtimeStubs]
+# Generate stubs with Reflection for loaded extensions phpstorm-stubs does not
+# cover. Ignored from project config unless commands are trusted.
+enabled = false
+phpExecutable = "php"
+timeoutMs = 10000
+
 [analyzerCodeActions]
 enabled = false
+
+[completion]
+# Snippet completions: class skeletons with the PSR-4 namespace, pubf/prof/prif
+# method skeletons, getter/setter templates, and control-flow blocks.
+snippets = true
+# Insert `name($1)$0` call snippets (or `name()` without parameters) for
+# functions and methods and open signature help.
+callSnippets = false
+# Maximum items per completion response. Longer lists are cut to the best
+# ranked items and marked incomplete so the client asks again as you type.
+maxItems = 100
+
+[newFiles]
+# Fill newly created empty PHP files with `<?php`, the PSR-4 namespace and a
+# class named after the file.
+scaffold = true
+strictTypes = true
 "#;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -172,6 +213,10 @@ pub fn normalize_project_config_settings(raw: &Value) -> Value {
         {
             diagnostics_settings.insert("memberTypeNodeBudget".to_string(), Value::from(budget));
         }
+        if let Some(baseline) = diagnostics.get("baseline").and_then(Value::as_str) {
+            diagnostics_settings
+                .insert("baseline".to_string(), Value::String(baseline.to_string()));
+        }
         if let Some(enabled) = diagnostics
             .get("partialAnalysisDiagnostic")
             .and_then(Value::as_bool)
@@ -181,17 +226,23 @@ pub fn normalize_project_config_settings(raw: &Value) -> Value {
                 Value::Bool(enabled),
             );
         }
+        if let Some(debounce_ms) = diagnostics.get("debounceMs").and_then(Value::as_u64) {
+            diagnostics_settings.insert("debounceMs".to_string(), Value::from(debounce_ms));
+        }
+        if let Some(enabled) = diagnostics.get("semanticOnSave").and_then(Value::as_bool) {
+            diagnostics_settings.insert("semanticOnSave".to_string(), Value::Bool(enabled));
+        }
 
         let mut severity = Map::new();
         if let Some(severity_object) = diagnostics.get("severity").and_then(Value::as_object) {
             for (key, value) in severity_object {
-                if let Some(level) = value.as_str() {
-                    severity.insert(key.clone(), Value::String(level.to_string()));
+                if value.is_string() || value.is_boolean() {
+                    severity.insert(key.clone(), value.clone());
                 }
             }
         }
         for (key, value) in diagnostics {
-            if key == "mode" || key == "severity" {
+            if key == "mode" || key == "severity" || key == "baseline" {
                 continue;
             }
             if is_diagnostic_category_key(key) {
@@ -218,8 +269,20 @@ pub fn normalize_project_config_settings(raw: &Value) -> Value {
         if let Some(exclude) = string_array_value(indexing.get("exclude")) {
             settings.insert("excludePaths".to_string(), exclude);
         }
-        if let Some(vendor) = indexing.get("vendor").and_then(Value::as_bool) {
-            settings.insert("indexVendor".to_string(), Value::Bool(vendor));
+        if let Some(extensions) = string_array_value(indexing.get("extensions")) {
+            settings.insert("fileExtensions".to_string(), extensions);
+        }
+        if let Some(gitignore) = indexing.get("gitignore").and_then(Value::as_bool) {
+            settings.insert("respectGitignore".to_string(), Value::Bool(gitignore));
+        }
+        match indexing.get("vendor") {
+            Some(Value::Bool(vendor)) => {
+                settings.insert("indexVendor".to_string(), Value::Bool(*vendor));
+            }
+            Some(Value::String(mode)) => {
+                settings.insert("vendorIndexing".to_string(), Value::String(mode.clone()));
+            }
+            _ => {}
         }
         if let Some(composer) = indexing.get("composer").and_then(Value::as_bool) {
             settings.insert(
@@ -240,8 +303,10 @@ pub fn normalize_project_config_settings(raw: &Value) -> Value {
 
     if let Some(stubs) = raw.get("stubs").and_then(Value::as_object) {
         let mut stubs_settings = Map::new();
-        if let Some(extensions) = string_array_value(stubs.get("extensions")) {
-            stubs_settings.insert("extensions".to_string(), extensions);
+        for key in ["extensions", "enable", "disable"] {
+            if let Some(extensions) = string_array_value(stubs.get(key)) {
+                stubs_settings.insert(key.to_string(), extensions);
+            }
         }
         if let Some(path) = stubs.get("path").and_then(Value::as_str) {
             stubs_settings.insert("path".to_string(), Value::String(path.to_string()));
@@ -281,7 +346,26 @@ pub fn normalize_project_config_settings(raw: &Value) -> Value {
         "psalm",
         &["enabled", "command", "timeoutMs", "timeout"],
     );
+    copy_section(
+        raw,
+        &mut settings,
+        "phpcs",
+        &["enabled", "command", "fixCommand", "timeoutMs", "timeout"],
+    );
+    copy_section(
+        raw,
+        &mut settings,
+        "runtimeStubs",
+        &["enabled", "phpExecutable", "timeoutMs"],
+    );
     copy_section(raw, &mut settings, "analyzerCodeActions", &["enabled"]);
+    copy_section(
+        raw,
+        &mut settings,
+        "completion",
+        &["snippets", "callSnippets", "maxItems"],
+    );
+    copy_section(raw, &mut settings, "newFiles", &["scaffold", "strictTypes"]);
 
     Value::Object(settings)
 }
@@ -354,6 +438,8 @@ fn is_diagnostic_category_key(key: &str) -> bool {
             | "override_signatures"
             | "phpVersion"
             | "php_version"
+            | "unhandledExceptions"
+            | "unhandled_exceptions"
     )
 }
 
@@ -369,16 +455,26 @@ mod tests {
                 "mode": "syntax-only",
                 "memberTypeNodeBudget": 128,
                 "partialAnalysisDiagnostic": false,
+                "debounceMs": 300,
+                "semanticOnSave": true,
+                "baseline": "php-lsp-baseline.json",
                 "unknown_symbols": "off",
-                "severity": { "members": "error" }
+                "severity": { "members": "error", "unresolvedUse": false }
             },
             "indexing": {
                 "composer": false,
                 "vendor": false,
                 "include": ["src"],
-                "exclude": ["vendor"]
+                "exclude": ["vendor"],
+                "gitignore": true,
+                "extensions": ["php", "inc"]
+            },
+            "stubs": {
+                "path": "/tmp/stubs",
+                "extensions": ["Core"],
+                "enable": ["redis"],
+                "disable": ["oci8"]
             },
-            "stubs": { "path": "/tmp/stubs", "extensions": ["Core"] },
             "security": { "allowProjectCommands": true },
             "formatting": { "provider": "custom", "command": "fmt {file}", "timeoutMs": 1000 },
             "phpstan": { "enabled": true, "memory_limit": "1G" }
@@ -390,21 +486,38 @@ mod tests {
         assert_eq!(settings["diagnostics"]["mode"], "syntax-only");
         assert_eq!(settings["diagnostics"]["memberTypeNodeBudget"], 128);
         assert_eq!(settings["diagnostics"]["partialAnalysisDiagnostic"], false);
+        assert_eq!(settings["diagnostics"]["debounceMs"], 300);
+        assert_eq!(settings["diagnostics"]["semanticOnSave"], true);
+        assert_eq!(settings["diagnostics"]["baseline"], "php-lsp-baseline.json");
         assert_eq!(
             settings["diagnostics"]["severity"]["unknown_symbols"],
             "off"
         );
         assert_eq!(settings["diagnostics"]["severity"]["members"], "error");
+        assert_eq!(settings["diagnostics"]["severity"]["unresolvedUse"], false);
         assert_eq!(settings["composer"]["enabled"], false);
         assert_eq!(settings["indexVendor"], false);
         assert_eq!(settings["includePaths"][0], "src");
         assert_eq!(settings["excludePaths"][0], "vendor");
+        assert_eq!(settings["respectGitignore"], true);
+        assert_eq!(settings["fileExtensions"][1], "inc");
         assert_eq!(settings["stubs"]["path"], "/tmp/stubs");
         assert_eq!(settings["stubs"]["extensions"][0], "Core");
+        assert_eq!(settings["stubs"]["enable"][0], "redis");
+        assert_eq!(settings["stubs"]["disable"][0], "oci8");
         assert_eq!(settings["formatting"]["provider"], "custom");
         assert_eq!(settings["phpstan"]["memory_limit"], "1G");
     }
 
+    #[test]
+    fn normalizes_vendor_indexing_mode_from_project_config() {
+        let raw = serde_json::json!({ "indexing": { "vendor": "full" } });
+
+        let settings = normalize_project_config_settings(&raw);
+        assert_eq!(settings["vendorIndexing"], "full");
+        assert!(settings.get("indexVendor").is_none());
+    }
+
     #[test]
     fn recursive_merge_preserves_nested_settings() {
         let mut base = serde_json::json!({
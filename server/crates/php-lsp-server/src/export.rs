@@ -0,0 +1,1089 @@
+//! `php-lsp export`: code-intelligence index export for code hosts.
+//!
+//! Walks the workspace the same way indexing does, extracts symbols and
+//! precomputed references with the parser, and writes them as SCIP (protobuf)
+//! or LSIF (JSON lines). Definitions come from `SymbolReference::is_declaration`
+//! occurrences, so export, find-references and rename agree on what is a
+//! declaration.
+//!
+//! Every symbol is named inside the project's Composer package. Vendor code is
+//! not walked, so references into dependencies point at symbols without a
+//! definition in the export rather than at the dependency's own package.
+
+use crate::server::{
+    collect_php_files, default_php_file_extensions, discover_workspace_root_config,
+    load_effective_configuration_settings, normalize_config_paths, normalize_php_file_extensions,
+    workspace_exclude_paths, workspace_index_directories,
+};
+use crate::util::uri::path_to_uri;
+use php_lsp_parser::parser::FileParser;
+use php_lsp_parser::phpdoc::parse_phpdoc;
+use php_lsp_parser::position::PositionEncoding;
+use php_lsp_parser::references::collect_symbol_references_in_file;
+use php_lsp_parser::source::read_php_source;
+use php_lsp_parser::symbols::extract_file_symbols;
+use php_lsp_types::{PhpSymbolKind, SymbolInfo, SymbolReference};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Scip,
+    Lsif,
+}
+
+impl ExportFormat {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "scip" => Some(Self::Scip),
+            "lsif" => Some(Self::Lsif),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Scip => "scip",
+            Self::Lsif => "lsif",
+        }
+    }
+
+    fn default_output(self) -> &'static str {
+        match self {
+            Self::Scip => "index.scip",
+            Self::Lsif => "dump.lsif",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportArgs {
+    pub project_root: Option<PathBuf>,
+    pub format: ExportFormat,
+    /// Defaults to `index.scip` / `dump.lsif` in the current directory.
+    pub output: Option<PathBuf>,
+}
+
+impl Default for ExportArgs {
+    fn default() -> Self {
+        Self {
+            project_root: None,
+            format: ExportFormat::Scip,
+            output: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportCliResult {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug)]
+pub struct ExportError {
+    message: String,
+}
+
+impl ExportError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// One parsed workspace file, ready to be written in either format.
+struct ExportDocument {
+    /// Project-relative path with `/` separators.
+    relative_path: String,
+    uri: String,
+    symbols: Vec<SymbolInfo>,
+    references: Vec<SymbolReference>,
+}
+
+#[derive(Debug)]
+struct ExportSummary {
+    output: PathBuf,
+    documents: usize,
+    symbols: usize,
+    occurrences: usize,
+}
+
+pub fn parse_export_args(raw_args: Vec<String>) -> Result<ExportArgs, ExportError> {
+    let mut parsed = ExportArgs::default();
+    let mut iter = raw_args.into_iter();
+
+    while let Some(arg) = iter.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .map(str::to_string)
+                .or_else(|| iter.next())
+                .ok_or_else(|| ExportError::new(format!("Missing value for {name}")))
+        };
+        match flag.as_str() {
+            "--project-root" => {
+                parsed.project_root = Some(PathBuf::from(value("--project-root")?));
+            }
+            "--format" => {
+                let raw = value("--format")?;
+                parsed.format = ExportFormat::parse(&raw).ok_or_else(|| {
+                    ExportError::new(format!("Invalid --format `{raw}`; expected scip or lsif"))
+                })?;
+            }
+            "--output" | "-o" => {
+                parsed.output = Some(PathBuf::from(value("--output")?));
+            }
+            value if value.starts_with('-') => {
+                return Err(ExportError::new(format!("Unknown export option `{value}`")));
+            }
+            value => {
+                if parsed.project_root.is_some() {
+                    return Err(ExportError::new(format!(
+                        "Unexpected extra export path `{value}`"
+                    )));
+                }
+                parsed.project_root = Some(PathBuf::from(value));
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub fn export_help() -> &'static str {
+    "Usage:\n  php-lsp export [PROJECT_ROOT] [--format <scip|lsif>] [--output <FILE>]"
+}
+
+pub fn run_export_cli(raw_args: Vec<String>) -> ExportCliResult {
+    // Headless runs have no client to negotiate with, so positions use the
+    // LSP default.
+    let encoding = PositionEncoding::default();
+    if raw_args
+        .iter()
+        .any(|arg| matches!(arg.as_str(), "--help" | "-h" | "help"))
+    {
+        return ExportCliResult {
+            exit_code: 0,
+            stdout: format!("{}\n", export_help()),
+            stderr: String::new(),
+        };
+    }
+
+    let result =
+        parse_export_args(raw_args).and_then(|args| run_export(&args, encoding).map(|s| (args, s)));
+    match result {
+        Ok((args, summary)) => ExportCliResult {
+            exit_code: 0,
+            stdout: format!(
+                "Exported {} documents, {} symbols and {} occurrences as {} to {}\n",
+                summary.documents,
+                summary.symbols,
+                summary.occurrences,
+                args.format.as_str(),
+                summary.output.display()
+            ),
+            stderr: String::new(),
+        },
+        Err(err) => ExportCliResult {
+            exit_code: 1,
+            stdout: String::new(),
+            stderr: format!("{err}\n"),
+        },
+    }
+}
+
+fn run_export(args: &ExportArgs, encoding: PositionEncoding) -> Result<ExportSummary, ExportError> {
+    let cwd = std::env::current_dir()
+        .map_err(|err| ExportError::new(format!("Failed to read current directory: {err}")))?;
+    let requested_root = args.project_root.clone().unwrap_or_else(|| cwd.clone());
+    let requested_root = if requested_root.is_absolute() {
+        requested_root
+    } else {
+        cwd.join(requested_root)
+    };
+    let requested_root = requested_root.canonicalize().map_err(|err| {
+        ExportError::new(format!("Invalid path {}: {err}", requested_root.display()))
+    })?;
+
+    let (project_root, files) = workspace_php_files(&requested_root).map_err(ExportError::new)?;
+
+    let documents = files
+        .iter()
+        .filter_map(
+            |path| match export_document(path, &project_root, encoding) {
+                Ok(document) => Some(document),
+                Err(err) => {
+                    tracing::warn!("Skipping {} in export: {}", path.display(), err);
+                    None
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(args.format.default_output()));
+    let output = if output.is_absolute() {
+        output
+    } else {
+        cwd.join(output)
+    };
+    let package = ScipPackage::for_project(&project_root);
+    let project_root_uri =
+        path_to_uri(&project_root).map_err(|err| ExportError::new(err.to_string()))?;
+    let bytes = match args.format {
+        ExportFormat::Scip => encode_scip_index(&documents, &project_root_uri, &package, encoding),
+        ExportFormat::Lsif => encode_lsif_dump(&documents, &project_root_uri, &package, encoding),
+    };
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            ExportError::new(format!("Failed to create {}: {err}", parent.display()))
+        })?;
+    }
+    std::fs::write(&output, bytes)
+        .map_err(|err| ExportError::new(format!("Failed to write {}: {err}", output.display())))?;
+
+    Ok(ExportSummary {
+        output,
+        documents: documents.len(),
+        symbols: documents
+            .iter()
+            .map(|document| document.symbols.len())
+            .sum(),
+        occurrences: documents
+            .iter()
+            .map(|document| document.references.len())
+            .sum(),
+    })
+}
+
+/// Resolve the effective project root for `requested_root` and list the PHP
+/// files the language server would index there, sorted.
+pub(crate) fn workspace_php_files(
+    requested_root: &Path,
+) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    let (settings, messages) = load_effective_configuration_settings(
+        &[requested_root.to_path_buf()],
+        &serde_json::json!({}),
+    );
+    if let Some(message) = messages.iter().find(|message| message.contains("failed")) {
+        return Err(message.clone());
+    }
+    let settings = settings.get("phpLsp").unwrap_or(&settings);
+    let string_array = |key: &str| {
+        settings
+            .get(key)
+            .and_then(|value| value.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+    };
+    let composer_enabled = settings
+        .get("composerEnabled")
+        .or_else(|| settings.get("composer").and_then(|c| c.get("enabled")))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true);
+    let respect_gitignore = settings
+        .get("respectGitignore")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    let include_paths = string_array("includePaths")
+        .map(normalize_config_paths)
+        .unwrap_or_default();
+    let exclude_paths = string_array("excludePaths")
+        .map(normalize_config_paths)
+        .unwrap_or_default();
+    let file_extensions = string_array("fileExtensions")
+        .map(normalize_php_file_extensions)
+        .unwrap_or_else(default_php_file_extensions);
+
+    let workspace_config = discover_workspace_root_config(requested_root, composer_enabled);
+    let project_root = workspace_config.root;
+    let exclude_paths = workspace_exclude_paths(&project_root, &exclude_paths, respect_gitignore);
+    let source_dirs = workspace_index_directories(
+        &project_root,
+        workspace_config.namespace_map.as_ref(),
+        &include_paths,
+    );
+    let mut files = collect_php_files(
+        &source_dirs,
+        &project_root,
+        &exclude_paths,
+        &file_extensions,
+    );
+    if let Some(namespace_map) = workspace_config.namespace_map.as_ref() {
+        files.extend(
+            namespace_map
+                .files
+                .iter()
+                .map(|file| project_root.join(file))
+                .filter(|file| file.is_file()),
+        );
+    }
+    files.sort();
+    files.dedup();
+    Ok((project_root, files))
+}
+
+fn export_document(
+    path: &Path,
+    project_root: &Path,
+    encoding: PositionEncoding,
+) -> Result<ExportDocument, ExportError> {
+    let source = read_php_source(path)
+        .map_err(|err| ExportError::new(format!("Failed to read {}: {err}", path.display())))?
+        .text;
+    let mut parser = FileParser::new();
+    parser.parse_full(&source);
+    let tree = parser.tree().ok_or_else(|| {
+        ExportError::new(format!(
+            "Parser did not produce a syntax tree for {}",
+            path.display()
+        ))
+    })?;
+    let uri = path_to_uri(path).map_err(|err| ExportError::new(err.to_string()))?;
+    let file_symbols = extract_file_symbols(tree, &source, &uri);
+    let mut references = collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
+    references.sort_by_key(|reference| reference.range);
+    let relative_path = path
+        .strip_prefix(project_root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Ok(ExportDocument {
+        relative_path,
+        uri,
+        symbols: file_symbols
+            .symbols
+            .into_iter()
+            .filter(|symbol| symbol.kind != PhpSymbolKind::Namespace)
+            .collect(),
+        references,
+    })
+}
+
+/// The `<manager> <name> <version>` part of every SCIP symbol.
+struct ScipPackage {
+    name: String,
+    version: String,
+}
+
+impl ScipPackage {
+    /// Uses `name`/`version` from the project's composer.json, with the SCIP
+    /// `.` placeholder for anything missing.
+    fn for_project(project_root: &Path) -> Self {
+        let composer = std::fs::read_to_string(project_root.join("composer.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+        let field = |key: &str| {
+            composer
+                .as_ref()
+                .and_then(|composer| composer.get(key))
+                .and_then(|value| value.as_str())
+                .filter(|value| !value.is_empty() && !value.contains(' '))
+                .unwrap_or(".")
+                .to_string()
+        };
+        Self {
+            name: field("name"),
+            version: field("version"),
+        }
+    }
+
+    fn symbol(&self, fqn: &str, kind: PhpSymbolKind) -> String {
+        format!(
+            "php-lsp composer {} {} {}",
+            self.name,
+            self.version,
+            scip_descriptors(fqn, kind)
+        )
+    }
+}
+
+/// SCIP descriptors for a php-lsp FQN: namespaces become `/`, class-likes `#`,
+/// functions and methods `().`, and constants and properties `.`.
+fn scip_descriptors(fqn: &str, kind: PhpSymbolKind) -> String {
+    let fqn = fqn.trim_start_matches('\\');
+    let (owner, member) = match fqn.split_once("::") {
+        Some((owner, member)) => (owner, Some(member.trim_start_matches('$'))),
+        None => (fqn, None),
+    };
+    let mut segments = owner.split('\\').collect::<Vec<_>>();
+    let last = segments.pop().unwrap_or_default();
+    let mut out = String::new();
+    for namespace in segments {
+        out.push_str(&scip_name(namespace));
+        out.push('/');
+    }
+
+    let Some(member) = member else {
+        out.push_str(&scip_name(last));
+        out.push_str(match kind {
+            PhpSymbolKind::Function => "().",
+            PhpSymbolKind::GlobalConstant => ".",
+            PhpSymbolKind::Namespace => "/",
+            _ => "#",
+        });
+        return out;
+    };
+    out.push_str(&scip_name(last));
+    out.push('#');
+    out.push_str(&scip_name(member));
+    out.push_str(if kind == PhpSymbolKind::Method {
+        "()."
+    } else {
+        "."
+    });
+    out
+}
+
+/// Backtick-escape names outside SCIP's simple identifier alphabet.
+fn scip_name(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-' | '$'))
+    {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+fn symbol_summary(symbol: &SymbolInfo) -> Option<String> {
+    symbol
+        .doc_comment
+        .as_deref()
+        .and_then(|comment| parse_phpdoc(comment).summary)
+        .filter(|summary| !summary.is_empty())
+}
+
+// --- SCIP -----------------------------------------------------------------
+
+/// Field numbers and enum values from `scip.proto`.
+mod scip {
+    pub(super) const INDEX_METADATA: u32 = 1;
+    pub(super) const INDEX_DOCUMENTS: u32 = 2;
+    pub(super) const METADATA_TOOL_INFO: u32 = 2;
+    pub(super) const METADATA_PROJECT_ROOT: u32 = 3;
+    pub(super) const METADATA_TEXT_DOCUMENT_ENCODING: u32 = 4;
+    pub(super) const TOOL_INFO_NAME: u32 = 1;
+    pub(super) const TOOL_INFO_VERSION: u32 = 2;
+    pub(super) const DOCUMENT_RELATIVE_PATH: u32 = 1;
+    pub(super) const DOCUMENT_OCCURRENCES: u32 = 2;
+    pub(super) const DOCUMENT_SYMBOLS: u32 = 3;
+    pub(super) const DOCUMENT_LANGUAGE: u32 = 4;
+    pub(super) const DOCUMENT_POSITION_ENCODING: u32 = 6;
+    pub(super) const OCCURRENCE_RANGE: u32 = 1;
+    pub(super) const OCCURRENCE_SYMBOL: u32 = 2;
+    pub(super) const OCCURRENCE_SYMBOL_ROLES: u32 = 3;
+    pub(super) const SYMBOL_SYMBOL: u32 = 1;
+    pub(super) const SYMBOL_DOCUMENTATION: u32 = 3;
+    pub(super) const SYMBOL_RELATIONSHIPS: u32 = 4;
+    pub(super) const SYMBOL_KIND: u32 = 5;
+    pub(super) const SYMBOL_DISPLAY_NAME: u32 = 6;
+    pub(super) const RELATIONSHIP_SYMBOL: u32 = 1;
+    pub(super) const RELATIONSHIP_IS_IMPLEMENTATION: u32 = 3;
+
+    pub(super) const TEXT_ENCODING_UTF8: u64 = 1;
+    pub(super) const ROLE_DEFINITION: u64 = 0x1;
+}
+
+fn encode_scip_index(
+    documents: &[ExportDocument],
+    project_root_uri: &str,
+    package: &ScipPackage,
+    encoding: PositionEncoding,
+) -> Vec<u8> {
+    let mut tool_info = ProtoWriter::default();
+    tool_info.string(scip::TOOL_INFO_NAME, "php-lsp");
+    tool_info.string(scip::TOOL_INFO_VERSION, env!("CARGO_PKG_VERSION"));
+    let mut metadata = ProtoWriter::default();
+    metadata.message(scip::METADATA_TOOL_INFO, tool_info);
+    metadata.string(scip::METADATA_PROJECT_ROOT, project_root_uri);
+    metadata.varint(
+        scip::METADATA_TEXT_DOCUMENT_ENCODING,
+        scip::TEXT_ENCODING_UTF8,
+    );
+
+    let mut index = ProtoWriter::default();
+    index.message(scip::INDEX_METADATA, metadata);
+    for document in documents {
+        index.message(
+            scip::INDEX_DOCUMENTS,
+            encode_scip_document(document, package, encoding),
+        );
+    }
+    index.buf
+}
+
+fn encode_scip_document(
+    document: &ExportDocument,
+    package: &ScipPackage,
+    encoding: PositionEncoding,
+) -> ProtoWriter {
+    let mut out = ProtoWriter::default();
+    out.string(scip::DOCUMENT_RELATIVE_PATH, &document.relative_path);
+    for reference in &document.references {
+        let (start_line, start_char, end_line, end_char) = reference.range;
+        let range = if start_line == end_line {
+            vec![start_line, start_char, end_char]
+        } else {
+            vec![start_line, start_char, end_line, end_char]
+        };
+        let mut occurrence = ProtoWriter::default();
+        occurrence.packed(scip::OCCURRENCE_RANGE, &range);
+        occurrence.string(
+            scip::OCCURRENCE_SYMBOL,
+            &package.symbol(&reference.target_fqn, reference.target_kind),
+        );
+        if reference.is_declaration {
+            occurrence.varint(scip::OCCURRENCE_SYMBOL_ROLES, scip::ROLE_DEFINITION);
+        }
+        out.message(scip::DOCUMENT_OCCURRENCES, occurrence);
+    }
+    for symbol in &document.symbols {
+        let mut info = ProtoWriter::default();
+        info.string(
+            scip::SYMBOL_SYMBOL,
+            &package.symbol(&symbol.fqn, symbol.kind),
+        );
+        if let Some(summary) = symbol_summary(symbol) {
+            info.string(scip::SYMBOL_DOCUMENTATION, &summary);
+        }
+        for parent in symbol.extends.iter().chain(&symbol.implements) {
+            let mut relationship = ProtoWriter::default();
+            relationship.string(
+                scip::RELATIONSHIP_SYMBOL,
+                &package.symbol(parent, PhpSymbolKind::Class),
+            );
+            relationship.varint(scip::RELATIONSHIP_IS_IMPLEMENTATION, 1);
+            info.message(scip::SYMBOL_RELATIONSHIPS, relationship);
+        }
+        info.varint(scip::SYMBOL_KIND, scip_symbol_kind(symbol.kind));
+        info.string(scip::SYMBOL_DISPLAY_NAME, &symbol.name);
+        out.message(scip::DOCUMENT_SYMBOLS, info);
+    }
+    out.string(scip::DOCUMENT_LANGUAGE, "php");
+    out.varint(
+        scip::DOCUMENT_POSITION_ENCODING,
+        scip_position_encoding(encoding),
+    );
+    out
+}
+
+/// `SymbolInformation.Kind` values from `scip.proto`.
+fn scip_symbol_kind(kind: PhpSymbolKind) -> u64 {
+    match kind {
+        PhpSymbolKind::Class => 7,
+        PhpSymbolKind::ClassConstant | PhpSymbolKind::GlobalConstant => 8,
+        PhpSymbolKind::Enum => 11,
+        PhpSymbolKind::EnumCase => 12,
+        PhpSymbolKind::Function => 17,
+        PhpSymbolKind::Interface => 21,
+        PhpSymbolKind::Method => 26,
+        PhpSymbolKind::Namespace => 30,
+        PhpSymbolKind::Property => 41,
+        PhpSymbolKind::Trait => 53,
+    }
+}
+
+/// `Document.position_encoding`: occurrence columns use the same unit as
+/// the LSP server, UTF-16 unless a client negotiated otherwise.
+fn scip_position_encoding(encoding: PositionEncoding) -> u64 {
+    use php_lsp_parser::position::PositionEncoding;
+    match encoding {
+        PositionEncoding::Utf8 => 1,
+        PositionEncoding::Utf16 => 2,
+        PositionEncoding::Utf32 => 3,
+    }
+}
+
+/// Minimal proto3 writer: only the wire types SCIP needs, default values
+/// omitted like a generated encoder would.
+#[derive(Default)]
+struct ProtoWriter {
+    buf: Vec<u8>,
+}
+
+impl ProtoWriter {
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn tag(&mut self, field: u32, wire_type: u8) {
+        self.raw_varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn varint(&mut self, field: u32, value: u64) {
+        if value != 0 {
+            self.tag(field, 0);
+            self.raw_varint(value);
+        }
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.tag(field, 2);
+        self.raw_varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+    }
+
+    fn message(&mut self, field: u32, message: ProtoWriter) {
+        self.bytes(field, &message.buf);
+    }
+
+    fn packed(&mut self, field: u32, values: &[u32]) {
+        let mut packed = ProtoWriter::default();
+        for value in values {
+            packed.raw_varint(u64::from(*value));
+        }
+        self.bytes(field, &packed.buf);
+    }
+}
+
+// --- LSIF -----------------------------------------------------------------
+
+/// Writes an LSIF 0.4.3 dump: one vertex or edge per line, every vertex
+/// before the first edge that references it.
+fn encode_lsif_dump(
+    documents: &[ExportDocument],
+    project_root_uri: &str,
+    package: &ScipPackage,
+    encoding: PositionEncoding,
+) -> Vec<u8> {
+    let mut out = LsifWriter::default();
+    out.vertex(
+        "metaData",
+        serde_json::json!({
+            "version": "0.4.3",
+            "projectRoot": project_root_uri,
+            "positionEncoding": encoding.as_lsp_kind(),
+            "toolInfo": { "name": "php-lsp", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    );
+    let project = out.vertex("project", serde_json::json!({ "kind": "php" }));
+
+    // Symbol key -> (document id, range id, is definition) for every occurrence.
+    let mut occurrences: BTreeMap<String, Vec<(u64, u64, bool)>> = BTreeMap::new();
+    let mut hovers: BTreeMap<String, String> = BTreeMap::new();
+    let mut document_ids = Vec::with_capacity(documents.len());
+    for document in documents {
+        let document_id = out.vertex(
+            "document",
+            serde_json::json!({ "uri": document.uri, "languageId": "php" }),
+        );
+        document_ids.push(document_id);
+        let mut range_ids = Vec::with_capacity(document.references.len());
+        for reference in &document.references {
+            let (start_line, start_char, end_line, end_char) = reference.range;
+            let range_id = out.vertex(
+                "range",
+                serde_json::json!({
+                    "start": { "line": start_line, "character": start_char },
+                    "end": { "line": end_line, "character": end_char },
+                }),
+            );
+            range_ids.push(range_id);
+            occurrences
+                .entry(package.symbol(&reference.target_fqn, reference.target_kind))
+                .or_default()
+                .push((document_id, range_id, reference.is_declaration));
+        }
+        if !range_ids.is_empty() {
+            out.edge_many("contains", document_id, &range_ids, None);
+        }
+        for symbol in &document.symbols {
+            if let Some(summary) = symbol_summary(symbol) {
+                hovers.insert(package.symbol(&symbol.fqn, symbol.kind), summary);
+            }
+        }
+    }
+    if !document_ids.is_empty() {
+        out.edge_many("contains", project, &document_ids, None);
+    }
+
+    for (symbol, ranges) in &occurrences {
+        let result_set = out.vertex("resultSet", serde_json::json!({}));
+        for (_, range_id, _) in ranges {
+            out.edge("next", *range_id, result_set);
+        }
+        let moniker = out.vertex(
+            "moniker",
+            serde_json::json!({ "scheme": "php-lsp", "identifier": symbol, "kind": "export" }),
+        );
+        out.edge("moniker", result_set, moniker);
+        if let Some(summary) = hovers.get(symbol) {
+            let hover = out.vertex(
+                "hoverResult",
+                serde_json::json!({ "result": { "contents": { "kind": "markdown", "value": summary } } }),
+            );
+            out.edge("textDocument/hover", result_set, hover);
+        }
+
+        let definitions = ranges
+            .iter()
+            .filter(|(_, _, is_definition)| *is_definition)
+            .collect::<Vec<_>>();
+        if !definitions.is_empty() {
+            let definition_result = out.vertex("definitionResult", serde_json::json!({}));
+            out.edge("textDocument/definition", result_set, definition_result);
+            for (document_id, group) in group_by_document(&definitions) {
+                out.edge_many(
+                    "item",
+                    definition_result,
+                    &group,
+                    Some(serde_json::json!({ "document": document_id })),
+                );
+            }
+        }
+
+        let reference_result = out.vertex("referenceResult", serde_json::json!({}));
+        out.edge("textDocument/references", result_set, reference_result);
+        let all = ranges.iter().collect::<Vec<_>>();
+        for (document_id, group) in group_by_document(&definitions) {
+            out.edge_many(
+                "item",
+                reference_result,
+                &group,
+                Some(serde_json::json!({ "document": document_id, "property": "definitions" })),
+            );
+        }
+        let references = all
+            .into_iter()
+            .filter(|(_, _, is_definition)| !*is_definition)
+            .collect::<Vec<_>>();
+        for (document_id, group) in group_by_document(&references) {
+            out.edge_many(
+                "item",
+                reference_result,
+                &group,
+                Some(serde_json::json!({ "document": document_id, "property": "references" })),
+            );
+        }
+    }
+
+    out.buf
+}
+
+fn group_by_document(ranges: &[&(u64, u64, bool)]) -> BTreeMap<u64, Vec<u64>> {
+    let mut grouped: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+    for (document_id, range_id, _) in ranges {
+        grouped.entry(*document_id).or_default().push(*range_id);
+    }
+    grouped
+}
+
+#[derive(Default)]
+struct LsifWriter {
+    next_id: u64,
+    buf: Vec<u8>,
+}
+
+impl LsifWriter {
+    fn emit(&mut self, element: &str, label: &str, mut fields: serde_json::Value) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        if let serde_json::Value::Object(map) = &mut fields {
+            map.insert("id".to_string(), id.into());
+            map.insert("type".to_string(), element.into());
+            map.insert("label".to_string(), label.into());
+        }
+        // Writing to a Vec cannot fail.
+        let _ = serde_json::to_writer(&mut self.buf, &fields);
+        let _ = self.buf.write_all(b"\n");
+        id
+    }
+
+    fn vertex(&mut self, label: &str, fields: serde_json::Value) -> u64 {
+        self.emit("vertex", label, fields)
+    }
+
+    fn edge(&mut self, label: &str, out_v: u64, in_v: u64) {
+        self.emit(
+            "edge",
+            label,
+            serde_json::json!({ "outV": out_v, "inV": in_v }),
+        );
+    }
+
+    fn edge_many(
+        &mut self,
+        label: &str,
+        out_v: u64,
+        in_vs: &[u64],
+        extra: Option<serde_json::Value>,
+    ) {
+        let mut fields = serde_json::json!({ "outV": out_v, "inVs": in_vs });
+        if let (Some(serde_json::Value::Object(extra)), serde_json::Value::Object(map)) =
+            (extra, &mut fields)
+        {
+            map.extend(extra);
+        }
+        self.emit("edge", label, fields);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::temp_project::TempProject;
+
+    /// Reads `(field, wire_type, payload)` triples from a protobuf message.
+    fn proto_fields(mut bytes: &[u8]) -> Vec<(u32, u8, Vec<u8>)> {
+        fn varint(bytes: &mut &[u8]) -> u64 {
+            let mut value = 0u64;
+            let mut shift = 0;
+            loop {
+                let byte = bytes[0];
+                *bytes = &bytes[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                if byte < 0x80 {
+                    return value;
+                }
+                shift += 7;
+            }
+        }
+        let mut fields = Vec::new();
+        while !bytes.is_empty() {
+            let tag = varint(&mut bytes);
+            let (field, wire_type) = ((tag >> 3) as u32, (tag & 7) as u8);
+            let payload = if wire_type == 2 {
+                let len = varint(&mut bytes) as usize;
+                let payload = bytes[..len].to_vec();
+                bytes = &bytes[len..];
+                payload
+            } else {
+                varint(&mut bytes).to_le_bytes().to_vec()
+            };
+            fields.push((field, wire_type, payload));
+        }
+        fields
+    }
+
+    fn proto_strings(bytes: &[u8], field: u32) -> Vec<String> {
+        proto_fields(bytes)
+            .into_iter()
+            .filter(|(number, _, _)| *number == field)
+            .map(|(_, _, payload)| String::from_utf8(payload).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_export_args_accepts_inline_and_separate_values() {
+        let args = parse_export_args(vec![
+            "--format=lsif".to_string(),
+            "-o".to_string(),
+            "out/dump.lsif".to_string(),
+            "project".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(args.format, ExportFormat::Lsif);
+        assert_eq!(args.output, Some(PathBuf::from("out/dump.lsif")));
+        assert_eq!(args.project_root, Some(PathBuf::from("project")));
+        assert!(parse_export_args(vec!["--format".to_string(), "csv".to_string()]).is_err());
+    }
+
+    #[test]
+    fn scip_descriptors_follow_symbol_kinds() {
+        assert_eq!(
+            scip_descriptors("App\\Service\\Mailer", PhpSymbolKind::Class),
+            "App/Service/Mailer#"
+        );
+        assert_eq!(
+            scip_descriptors("App\\Service\\Mailer::send", PhpSymbolKind::Method),
+            "App/Service/Mailer#send()."
+        );
+        assert_eq!(
+            scip_descriptors("App\\Service\\Mailer::$from", PhpSymbolKind::Property),
+            "App/Service/Mailer#from."
+        );
+        assert_eq!(
+            scip_descriptors("\\App\\helper", PhpSymbolKind::Function),
+            "App/helper()."
+        );
+        assert_eq!(
+            scip_descriptors("Größe", PhpSymbolKind::GlobalConstant),
+            "`Größe`."
+        );
+    }
+
+    #[test]
+    fn scip_export_writes_documents_occurrences_and_symbols() {
+        let project = TempProject::new(
+            "export",
+            &[
+            ("composer.json", r#"{"name": "acme/shop"}"#),
+            (
+                "src/Cart.php",
+                "<?php\nnamespace Acme;\n\n/** Shopping cart. */\nclass Cart extends Base {\n    public function total(): int { return 0; }\n}\n",
+            ),
+            (
+                "src/Checkout.php",
+                "<?php\nnamespace Acme;\n\nfunction checkout(Cart $cart): int {\n    return $cart->total();\n}\n",
+            ),
+        ]);
+        let root = project.root().to_path_buf();
+        let output = root.join("out/index.scip");
+
+        let summary = run_export(
+            &ExportArgs {
+                project_root: Some(root.clone()),
+                format: ExportFormat::Scip,
+                output: Some(output.clone()),
+            },
+            PositionEncoding::Utf16,
+        )
+        .unwrap();
+        assert_eq!(summary.documents, 2);
+
+        let bytes = std::fs::read(&output).unwrap();
+        let index = proto_fields(&bytes);
+        let metadata = &index.iter().find(|(field, _, _)| *field == 1).unwrap().2;
+        let tool_info = &proto_fields(metadata)
+            .into_iter()
+            .find(|(field, _, _)| *field == 2)
+            .unwrap()
+            .2;
+        assert_eq!(proto_strings(tool_info, 1), vec!["php-lsp"]);
+
+        let documents = index
+            .iter()
+            .filter(|(field, _, _)| *field == 2)
+            .map(|(_, _, payload)| payload.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            documents
+                .iter()
+                .flat_map(|document| proto_strings(document, 1))
+                .collect::<Vec<_>>(),
+            vec!["src/Cart.php", "src/Checkout.php"]
+        );
+
+        let cart_class = "php-lsp composer acme/shop . Acme/Cart#";
+        let cart_symbols = proto_fields(&documents[0])
+            .into_iter()
+            .filter(|(field, _, _)| *field == 3)
+            .map(|(_, _, payload)| payload)
+            .collect::<Vec<_>>();
+        let cart_info = cart_symbols
+            .iter()
+            .find(|info| proto_strings(info, 1) == vec![cart_class])
+            .expect("Cart symbol information");
+        assert_eq!(proto_strings(cart_info, 3), vec!["Shopping cart."]);
+        assert_eq!(proto_strings(cart_info, 6), vec!["Cart"]);
+
+        let checkout_occurrences = proto_fields(&documents[1])
+            .into_iter()
+            .filter(|(field, _, _)| *field == 2)
+            .flat_map(|(_, _, payload)| proto_strings(&payload, 2))
+            .collect::<Vec<_>>();
+        assert!(
+            checkout_occurrences.contains(&cart_class.to_string()),
+            "{checkout_occurrences:?}"
+        );
+        assert!(checkout_occurrences
+            .contains(&"php-lsp composer acme/shop . Acme/Cart#total().".to_string()));
+    }
+
+    #[test]
+    fn lsif_export_links_references_to_definitions() {
+        let project = TempProject::new(
+            "export",
+            &[
+                ("src/Cart.php", "<?php\nclass Cart {}\n"),
+                ("src/use.php", "<?php\nnew Cart();\n"),
+            ],
+        );
+        let root = project.root().to_path_buf();
+        let output = root.join("dump.lsif");
+
+        run_export(
+            &ExportArgs {
+                project_root: Some(root.clone()),
+                format: ExportFormat::Lsif,
+                output: Some(output.clone()),
+            },
+            PositionEncoding::Utf16,
+        )
+        .unwrap();
+
+        let lines = std::fs::read_to_string(&output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0]["label"], "metaData");
+        let mut seen = std::collections::HashSet::new();
+        for line in &lines {
+            if line["type"] == "edge" {
+                let targets = line["inVs"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_else(|| vec![line["inV"].clone()]);
+                assert!(seen.contains(&line["outV"]), "edge before vertex: {line}");
+                assert!(targets.iter().all(|id| seen.contains(id)), "{line}");
+            } else {
+                seen.insert(line["id"].clone());
+            }
+        }
+
+        let moniker = lines
+            .iter()
+            .find(|line| {
+                line["label"] == "moniker" && line["identifier"] == "php-lsp composer . . Cart#"
+            })
+            .expect("Cart moniker");
+        let result_set = lines
+            .iter()
+            .find(|line| {
+                line["label"] == "moniker" && line["type"] == "edge" && line["inV"] == moniker["id"]
+            })
+            .unwrap()["outV"]
+            .clone();
+        let reference_result = lines
+            .iter()
+            .find(|line| line["label"] == "textDocument/references" && line["outV"] == result_set)
+            .unwrap()["inV"]
+            .clone();
+        let properties = lines
+            .iter()
+            .filter(|line| line["label"] == "item" && line["outV"] == reference_result)
+            .map(|line| line["property"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(properties, vec!["definitions", "references"]);
+    }
+}
@@ -1,18 +1,21 @@
 use crate::server::{
     build_organize_imports_edit, collect_php_files, compute_diagnostics_with_runtime_config,
+    configured_stub_extensions, default_php_file_extensions,
     diagnostic_budget_config_from_settings, discover_workspace_root_config,
     is_unused_import_diagnostic, load_configured_stubs, load_effective_configuration_settings,
-    normalize_config_paths, return_type_hint, workspace_index_directories, DiagnosticBudgetConfig,
+    normalize_config_paths, normalize_php_file_extensions, path_has_php_extension,
+    return_type_hint, workspace_exclude_paths, workspace_index_directories, DiagnosticBudgetConfig,
     DiagnosticSeverityConfig, DiagnosticsMode, DiagnosticsRuntimeConfig, PhpVersion,
 };
 use crate::util::lsp_text::{lsp_position_to_byte, text_at_lsp_range};
 use crate::util::uri::path_to_uri;
 use php_lsp_index::workspace::WorkspaceIndex;
 use php_lsp_parser::parser::FileParser;
+use php_lsp_parser::position::{LspLineIndex, PositionEncoding};
 use php_lsp_parser::references::collect_symbol_references_in_file;
 use php_lsp_parser::return_type::find_missing_return_type_candidates;
+use php_lsp_parser::source::read_php_source;
 use php_lsp_parser::symbols::extract_file_symbols;
-use php_lsp_parser::utf16::Utf16LineIndex;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -132,6 +135,8 @@ struct FixRuntimeConfig {
     stub_extensions: Option<Vec<String>>,
     include_paths: Vec<PathBuf>,
     exclude_paths: Vec<PathBuf>,
+    respect_gitignore: bool,
+    file_extensions: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -303,6 +308,9 @@ pub fn fix_help() -> &'static str {
 }
 
 pub fn run_fix_cli(raw_args: Vec<String>) -> FixCliResult {
+    // Headless runs have no client to negotiate with, so positions use the
+    // LSP default.
+    let encoding = PositionEncoding::default();
     if raw_args
         .iter()
         .any(|arg| matches!(arg.as_str(), "--help" | "-h" | "help"))
@@ -334,7 +342,7 @@ pub fn run_fix_cli(raw_args: Vec<String>) -> FixCliResult {
         };
     }
 
-    match run_fix(&args) {
+    match run_fix(&args, encoding) {
         Ok(report) => {
             let stdout = render_report(&report, args.format);
             let exit_code = if report.total_edits() == 0 { 0 } else { 2 };
@@ -352,7 +360,7 @@ pub fn run_fix_cli(raw_args: Vec<String>) -> FixCliResult {
     }
 }
 
-fn run_fix(args: &FixArgs) -> Result<FixReport, FixError> {
+fn run_fix(args: &FixArgs, encoding: PositionEncoding) -> Result<FixReport, FixError> {
     let cwd = std::env::current_dir()
         .map_err(|err| FixError::new(format!("Failed to read current directory: {err}")))?;
     let explicit_project_root = args.project_root.is_some();
@@ -384,16 +392,23 @@ fn run_fix(args: &FixArgs) -> Result<FixReport, FixError> {
     } else {
         requested_target
     };
+    let exclude_paths = workspace_exclude_paths(
+        &project_root,
+        &runtime_config.exclude_paths,
+        runtime_config.respect_gitignore,
+    );
     let workspace_files = collect_workspace_fix_files(
         &project_root,
         workspace_config.namespace_map.as_ref(),
         &runtime_config.include_paths,
-        &runtime_config.exclude_paths,
+        &exclude_paths,
+        &runtime_config.file_extensions,
     );
     let target_files = collect_target_fix_files(
         &requested_target,
         &project_root,
-        &runtime_config.exclude_paths,
+        &exclude_paths,
+        &runtime_config.file_extensions,
     )?;
 
     let mut all_files = workspace_files.clone();
@@ -416,7 +431,8 @@ fn run_fix(args: &FixArgs) -> Result<FixReport, FixError> {
         let parsed = parse_fix_file(&path)?;
         let source = parsed.parser.source();
         let tree = parsed.parser.tree().expect("parsed file has a tree");
-        let references = collect_symbol_references_in_file(tree, &source, &parsed.file_symbols);
+        let references =
+            collect_symbol_references_in_file(tree, &source, &parsed.file_symbols, encoding);
         index.update_file_with_references(&parsed.uri, parsed.file_symbols.clone(), references);
         parsed_by_path.insert(path, parsed);
     }
@@ -426,7 +442,8 @@ fn run_fix(args: &FixArgs) -> Result<FixReport, FixError> {
         let parsed = parsed_by_path
             .get(target_file)
             .ok_or_else(|| FixError::new(format!("Failed to parse {}", target_file.display())))?;
-        if let Some(file_report) = collect_file_fixes(parsed, &index, &runtime_config, &args.rules)?
+        if let Some(file_report) =
+            collect_file_fixes(parsed, &index, &runtime_config, &args.rules, encoding)?
         {
             files.push(file_report);
         }
@@ -451,6 +468,7 @@ fn collect_file_fixes(
     index: &WorkspaceIndex,
     runtime_config: &FixRuntimeConfig,
     rules: &[FixRule],
+    encoding: PositionEncoding,
 ) -> Result<Option<FixFileReport>, FixError> {
     let uri = parsed
         .uri
@@ -469,11 +487,12 @@ fn collect_file_fixes(
             &parsed.file_symbols,
             FixRule::OrganizeImports,
             "Organize imports",
+            encoding,
         )? {
             actions.push(action);
         }
     } else if rules.contains(&FixRule::UnusedImports) {
-        let diagnostics = compute_file_diagnostics(parsed, index, runtime_config);
+        let diagnostics = compute_file_diagnostics(parsed, index, runtime_config, encoding);
         if diagnostics.iter().any(is_unused_import_diagnostic) {
             if let Some(action) = organize_imports_action(
                 uri.clone(),
@@ -483,6 +502,7 @@ fn collect_file_fixes(
                 &parsed.file_symbols,
                 FixRule::UnusedImports,
                 "Remove unused imports",
+                encoding,
             )? {
                 actions.push(action);
             }
@@ -494,6 +514,7 @@ fn collect_file_fixes(
             &source,
             tree,
             runtime_config.php_version,
+            encoding,
         ));
     }
 
@@ -505,7 +526,7 @@ fn collect_file_fixes(
         .iter()
         .flat_map(|action| action.edits.iter().cloned())
         .collect::<Vec<_>>();
-    let new_source = apply_fix_edits(&source, &edits)?;
+    let new_source = apply_fix_edits(&source, &edits, encoding)?;
     if new_source == source {
         return Ok(None);
     }
@@ -517,6 +538,7 @@ fn collect_file_fixes(
     }))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn organize_imports_action(
     uri: Uri,
     uri_str: &str,
@@ -525,11 +547,14 @@ fn organize_imports_action(
     file_symbols: &php_lsp_types::FileSymbols,
     rule: FixRule,
     title: &str,
+    encoding: PositionEncoding,
 ) -> Result<Option<FixAction>, FixError> {
-    let Some(workspace_edit) = build_organize_imports_edit(uri, source, tree, file_symbols) else {
+    let Some(workspace_edit) =
+        build_organize_imports_edit(uri, source, tree, file_symbols, encoding)
+    else {
         return Ok(None);
     };
-    let edits = workspace_edit_to_fix_edits(uri_str, source, workspace_edit)?;
+    let edits = workspace_edit_to_fix_edits(uri_str, source, workspace_edit, encoding)?;
     if edits.is_empty() {
         return Ok(None);
     }
@@ -544,16 +569,17 @@ fn add_return_type_actions(
     source: &str,
     tree: &tree_sitter::Tree,
     php_version: PhpVersion,
+    encoding: PositionEncoding,
 ) -> Vec<FixAction> {
-    let utf16_index = Utf16LineIndex::new(source);
+    let line_index = LspLineIndex::new(source, encoding);
     find_missing_return_type_candidates(tree, source, (0, 0, u32::MAX, u32::MAX))
         .into_iter()
         .filter_map(|candidate| {
             let hint = return_type_hint(&candidate.return_type, php_version)?;
             let position = Position::new(
                 candidate.insert_position.0,
-                utf16_index
-                    .byte_col_to_utf16(candidate.insert_position.0, candidate.insert_position.1),
+                line_index
+                    .byte_col_to_lsp(candidate.insert_position.0, candidate.insert_position.1),
             );
             Some(FixAction {
                 rule: FixRule::AddReturnType,
@@ -575,6 +601,7 @@ fn compute_file_diagnostics(
     parsed: &ParsedFixFile,
     index: &WorkspaceIndex,
     runtime_config: &FixRuntimeConfig,
+    encoding: PositionEncoding,
 ) -> Vec<Diagnostic> {
     compute_diagnostics_with_runtime_config(
         &parsed.uri,
@@ -585,6 +612,7 @@ fn compute_file_diagnostics(
             severity: runtime_config.diagnostic_severity,
             budget: runtime_config.diagnostic_budget,
             php_version: runtime_config.php_version,
+            encoding,
         },
         None,
     )
@@ -594,6 +622,7 @@ fn workspace_edit_to_fix_edits(
     uri_str: &str,
     source: &str,
     workspace_edit: WorkspaceEdit,
+    encoding: PositionEncoding,
 ) -> Result<Vec<FixEdit>, FixError> {
     let mut edits = Vec::new();
     if let Some(changes) = workspace_edit.changes {
@@ -602,15 +631,19 @@ fn workspace_edit_to_fix_edits(
                 continue;
             }
             for edit in text_edits {
-                edits.push(fix_edit_from_text_edit(source, edit)?);
+                edits.push(fix_edit_from_text_edit(source, edit, encoding)?);
             }
         }
     }
     Ok(edits)
 }
 
-fn fix_edit_from_text_edit(source: &str, edit: TextEdit) -> Result<FixEdit, FixError> {
-    let old_text = text_at_lsp_range(source, edit.range)
+fn fix_edit_from_text_edit(
+    source: &str,
+    edit: TextEdit,
+    encoding: PositionEncoding,
+) -> Result<FixEdit, FixError> {
+    let old_text = text_at_lsp_range(source, edit.range, encoding)
         .ok_or_else(|| FixError::new("Generated fix contains an invalid text edit range"))?
         .to_string();
     Ok(FixEdit {
@@ -620,13 +653,17 @@ fn fix_edit_from_text_edit(source: &str, edit: TextEdit) -> Result<FixEdit, FixE
     })
 }
 
-fn apply_fix_edits(source: &str, edits: &[FixEdit]) -> Result<String, FixError> {
+fn apply_fix_edits(
+    source: &str,
+    edits: &[FixEdit],
+    encoding: PositionEncoding,
+) -> Result<String, FixError> {
     let mut spans = Vec::new();
     for edit in edits {
-        let start = lsp_position_to_byte(source, edit.range.start).ok_or_else(|| {
+        let start = lsp_position_to_byte(source, edit.range.start, encoding).ok_or_else(|| {
             FixError::new("Generated fix contains an invalid text edit start position")
         })?;
-        let end = lsp_position_to_byte(source, edit.range.end).ok_or_else(|| {
+        let end = lsp_position_to_byte(source, edit.range.end, encoding).ok_or_else(|| {
             FixError::new("Generated fix contains an invalid text edit end position")
         })?;
         if end < start {
@@ -682,14 +719,24 @@ fn fix_runtime_config(settings: &serde_json::Value) -> FixRuntimeConfig {
         let trimmed = path.trim();
         (!trimmed.is_empty()).then(|| PathBuf::from(trimmed))
     });
-    let stub_extensions =
-        settings_string_array(settings, "stubExtensions", &["stubs", "extensions"]);
+    let stub_extensions = configured_stub_extensions(
+        settings_string_array(settings, "stubExtensions", &["stubs", "extensions"]),
+        settings_string_array(settings, "stubExtensionsEnable", &["stubs", "enable"])
+            .unwrap_or_default(),
+        settings_string_array(settings, "stubExtensionsDisable", &["stubs", "disable"])
+            .unwrap_or_default(),
+    );
     let include_paths = settings_string_array(settings, "includePaths", &["includePaths"])
         .map(normalize_config_paths)
         .unwrap_or_default();
     let exclude_paths = settings_string_array(settings, "excludePaths", &["excludePaths"])
         .map(normalize_config_paths)
         .unwrap_or_default();
+    let respect_gitignore =
+        settings_bool(settings, "respectGitignore", &["respectGitignore"]).unwrap_or(false);
+    let file_extensions = settings_string_array(settings, "fileExtensions", &["fileExtensions"])
+        .map(normalize_php_file_extensions)
+        .unwrap_or_else(default_php_file_extensions);
 
     FixRuntimeConfig {
         php_version,
@@ -701,6 +748,8 @@ fn fix_runtime_config(settings: &serde_json::Value) -> FixRuntimeConfig {
         stub_extensions,
         include_paths,
         exclude_paths,
+        respect_gitignore,
+        file_extensions,
     }
 }
 
@@ -781,9 +830,10 @@ fn collect_workspace_fix_files(
     namespace_map: Option<&php_lsp_index::composer::NamespaceMap>,
     include_paths: &[PathBuf],
     exclude_paths: &[PathBuf],
+    file_extensions: &[String],
 ) -> Vec<PathBuf> {
     let source_dirs = workspace_index_directories(project_root, namespace_map, include_paths);
-    let mut files = collect_php_files(&source_dirs, project_root, exclude_paths);
+    let mut files = collect_php_files(&source_dirs, project_root, exclude_paths, file_extensions);
     if let Some(namespace_map) = namespace_map {
         for file_path in &namespace_map.files {
             let abs = if file_path.is_absolute() {
@@ -804,9 +854,10 @@ fn collect_target_fix_files(
     target: &Path,
     project_root: &Path,
     exclude_paths: &[PathBuf],
+    file_extensions: &[String],
 ) -> Result<Vec<PathBuf>, FixError> {
     if target.is_file() {
-        return if target.extension().and_then(|ext| ext.to_str()) == Some("php") {
+        return if path_has_php_extension(target, file_extensions) {
             Ok(vec![target.to_path_buf()])
         } else {
             Err(FixError::new(format!(
@@ -816,7 +867,12 @@ fn collect_target_fix_files(
         };
     }
     if target.is_dir() {
-        let mut files = collect_php_files(&[target.to_path_buf()], project_root, exclude_paths);
+        let mut files = collect_php_files(
+            &[target.to_path_buf()],
+            project_root,
+            exclude_paths,
+            file_extensions,
+        );
         files.sort();
         return Ok(files);
     }
@@ -828,9 +884,9 @@ fn collect_target_fix_files(
 }
 
 fn parse_fix_file(path: &Path) -> Result<ParsedFixFile, FixError> {
-    let bytes = std::fs::read(path)
-        .map_err(|err| FixError::new(format!("Failed to read {}: {err}", path.display())))?;
-    let source = String::from_utf8_lossy(&bytes).into_owned();
+    let source = read_php_source(path)
+        .map_err(|err| FixError::new(format!("Failed to read {}: {err}", path.display())))?
+        .text;
     let mut parser = FileParser::new();
     parser.parse_full(&source);
     let tree = parser.tree().ok_or_else(|| {
@@ -1097,10 +1153,13 @@ echo Used::class;
             dry_run: true,
             ..FixArgs::default()
         };
-        let first = run_fix(&FixArgs {
-            rules: DEFAULT_FIX_RULES.to_vec(),
-            ..args.clone()
-        })
+        let first = run_fix(
+            &FixArgs {
+                rules: DEFAULT_FIX_RULES.to_vec(),
+                ..args.clone()
+            },
+            PositionEncoding::Utf16,
+        )
         .unwrap();
         assert_eq!(first.total_fixes(), 2);
         assert_eq!(first.files.len(), 1);
@@ -1110,13 +1169,16 @@ echo Used::class;
             .iter()
             .flat_map(|action| action.edits.iter().cloned())
             .collect::<Vec<_>>();
-        let new_source = apply_fix_edits(&original, &edits).unwrap();
+        let new_source = apply_fix_edits(&original, &edits, PositionEncoding::Utf16).unwrap();
         std::fs::write(&path, new_source).unwrap();
 
-        let second = run_fix(&FixArgs {
-            rules: DEFAULT_FIX_RULES.to_vec(),
-            ..args
-        })
+        let second = run_fix(
+            &FixArgs {
+                rules: DEFAULT_FIX_RULES.to_vec(),
+                ..args
+            },
+            PositionEncoding::Utf16,
+        )
         .unwrap();
         assert_eq!(second.total_edits(), 0);
 
@@ -364,6 +364,14 @@ impl<'a> FrameworkProviderRegistry<'a> {
         Self { providers }
     }
 
+    /// Stable ids of the registered providers in evaluation order.
+    pub(crate) fn provider_ids(&self) -> Vec<&'static str> {
+        self.providers
+            .iter()
+            .map(|provider| provider.id())
+            .collect()
+    }
+
     pub(crate) fn virtual_members(
         &self,
         ctx: &FrameworkProviderContext<'_>,
@@ -2200,7 +2208,7 @@ fn collect_laravel_scope_methods(
         );
         member.type_info = return_type.clone();
         member.sources.push(VirtualMemberSource::SourceRange {
-            uri: method.uri.clone(),
+            uri: method.uri.to_string(),
             range: method.selection_range,
         });
         push_virtual_member(methods, seen, member);
@@ -2369,7 +2377,7 @@ fn laravel_relation_forwarded_builder_virtual_member(
     member.type_info =
         laravel_relation_forwarded_builder_return_type(ctx, relation_fqn, builder_symbol);
     member.sources.push(VirtualMemberSource::SourceRange {
-        uri: builder_symbol.uri.clone(),
+        uri: builder_symbol.uri.to_string(),
         range: builder_symbol.selection_range,
     });
     member
@@ -2773,7 +2781,7 @@ fn laravel_model_for_builder(
             .as_deref()
             .is_some_and(|custom_builder| fqn_matches(custom_builder, builder_fqn))
         {
-            return Some(symbol.fqn.clone());
+            return Some(symbol.fqn.to_string());
         }
     }
 
@@ -2793,7 +2801,7 @@ fn type_info_to_fqn(
             .iter()
             .find_map(|type_info| type_info_to_fqn(ctx, owner, type_info)),
         TypeInfo::ClassString(Some(inner)) => type_info_to_fqn(ctx, owner, inner),
-        TypeInfo::Self_ | TypeInfo::Static_ => Some(owner.fqn.clone()),
+        TypeInfo::Self_ | TypeInfo::Static_ => Some(owner.fqn.to_string()),
         TypeInfo::Parent_ => owner.extends.first().cloned(),
         _ => None,
     }
@@ -3025,7 +3033,7 @@ fn laravel_property_from_symbol(
     member.type_info = type_info;
     if let Some(source_symbol) = source_symbol {
         member.sources.push(VirtualMemberSource::SourceRange {
-            uri: source_symbol.uri.clone(),
+            uri: source_symbol.uri.to_string(),
             range: source_symbol.selection_range,
         });
     }
@@ -3055,7 +3063,7 @@ fn laravel_property_from_source(
 
 fn property_source_range(symbol: &SymbolInfo) -> Option<VirtualMemberSource> {
     Some(VirtualMemberSource::SourceRange {
-        uri: symbol.uri.clone(),
+        uri: symbol.uri.to_string(),
         range: symbol.selection_range,
     })
 }
@@ -4025,9 +4033,9 @@ mod tests {
     fn class_symbol(fqn: &str, extends: Vec<&str>) -> SymbolInfo {
         SymbolInfo {
             name: fqn.rsplit('\\').next().unwrap_or(fqn).to_string(),
-            fqn: fqn.to_string(),
+            fqn: fqn.into(),
             kind: PhpSymbolKind::Class,
-            uri: "file:///test.php".to_string(),
+            uri: "file:///test.php".into(),
             range: (0, 0, 0, 0),
             selection_range: (0, 0, 0, 0),
             visibility: php_lsp_types::Visibility::Public,
@@ -4035,12 +4043,14 @@ mod tests {
             attributes: Vec::new(),
             doc_comment: None,
             signature: None,
+            value: None,
             parent_fqn: None,
             extends: extends.into_iter().map(str::to_string).collect(),
             implements: Vec::new(),
             traits: Vec::new(),
             templates: Vec::new(),
             template_bindings: Vec::new(),
+            trait_adaptations: Vec::new(),
         }
     }
 
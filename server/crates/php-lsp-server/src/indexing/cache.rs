@@ -76,7 +76,14 @@ pub(crate) fn cache_path_label(path: &Path) -> String {
 
 pub(crate) fn effective_stub_extensions(stub_extensions: Option<&[String]>) -> Vec<String> {
     match stub_extensions {
-        Some(extensions) => extensions.to_vec(),
+        Some(entries) => {
+            let (extensions, overrides) = split_stub_extension_overrides(entries);
+            if extensions.is_empty() && !overrides.is_empty() {
+                apply_stub_extension_overrides(effective_stub_extensions(None), &overrides)
+            } else {
+                apply_stub_extension_overrides(extensions, &overrides)
+            }
+        }
         None => stubs::DEFAULT_EXTENSIONS
             .iter()
             .map(|ext| (*ext).to_string())
@@ -88,16 +95,51 @@ pub(crate) fn effective_stub_extensions_for_path(
     stubs_path: &Path,
     stub_extensions: Option<&[String]>,
 ) -> Vec<String> {
-    if stub_extensions.is_some() {
-        return effective_stub_extensions(stub_extensions);
-    }
+    let overrides = match stub_extensions {
+        Some(entries) => {
+            let (extensions, overrides) = split_stub_extension_overrides(entries);
+            if !extensions.is_empty() || overrides.is_empty() {
+                return effective_stub_extensions(stub_extensions);
+            }
+            overrides
+        }
+        None => Vec::new(),
+    };
 
     let discovered = stubs::discover_stub_extensions(stubs_path);
-    if discovered.is_empty() {
+    let base = if discovered.is_empty() {
         effective_stub_extensions(None)
     } else {
         discovered
+    };
+    apply_stub_extension_overrides(base, &overrides)
+}
+
+/// Splits configured entries into plain extension names and `+name`/`-name`
+/// overrides.
+fn split_stub_extension_overrides(entries: &[String]) -> (Vec<String>, Vec<String>) {
+    entries
+        .iter()
+        .cloned()
+        .partition(|entry| !entry.starts_with(['+', '-']))
+}
+
+/// Adds `+name` entries to and removes `-name` entries from `extensions`.
+/// Removal ignores ASCII case so `-pdo` also drops the `PDO` directory.
+pub(crate) fn apply_stub_extension_overrides(
+    mut extensions: Vec<String>,
+    overrides: &[String],
+) -> Vec<String> {
+    for entry in overrides {
+        if let Some(extension) = entry.strip_prefix('+') {
+            if !extensions.iter().any(|existing| existing == extension) {
+                extensions.push(extension.to_string());
+            }
+        } else if let Some(extension) = entry.strip_prefix('-') {
+            extensions.retain(|existing| !existing.eq_ignore_ascii_case(extension));
+        }
     }
+    extensions
 }
 
 fn workspace_stub_extensions_for_cache(stub_extensions: Option<&[String]>) -> Vec<String> {
@@ -1,6 +1,8 @@
 //! Workspace indexing and file-operation handler modules.
 
 pub(super) mod cache;
+pub(super) mod phar;
+pub(super) mod runtime_stubs;
 pub(super) mod stubs;
 pub(super) mod vendor;
 pub(super) mod workspace;
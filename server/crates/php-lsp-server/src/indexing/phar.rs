@@ -0,0 +1,428 @@
+//! Read-only access to classes shipped inside PHAR archives, such as
+//! `phpunit.phar` or `vendor/phpstan/phpstan/phpstan.phar`.
+//!
+//! Archive entries use PHP's own stream syntax as their URI,
+//! `phar:///abs/path/tool.phar/src/Foo.php`, so definitions can point into an
+//! archive and `php-lsp/pharContent` can serve the text back. Only the native
+//! PHAR format is read; tar/zip based archives and entries stored with gzip or
+//! bzip2 compression are skipped.
+
+use super::super::*;
+use serde_json::{json, Value};
+
+pub(in crate::server) const PHAR_URI_PREFIX: &str = "phar://";
+
+/// Custom request method name registered on the LSP service.
+pub const PHAR_CONTENT_METHOD: &str = "php-lsp/pharContent";
+
+const HALT_COMPILER: &[u8] = b"__HALT_COMPILER();";
+const PHAR_ENTRY_COMPRESSED_MASK: u32 = 0x0000_F000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PharEntry {
+    pub(crate) name: String,
+    offset: usize,
+    size: usize,
+    compressed: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PharManifest {
+    pub(crate) entries: Vec<PharEntry>,
+}
+
+/// Lazy PHAR lookup state: archives found per workspace root, parsed
+/// manifests invalidated by modification time, and classes no archive
+/// declares. Cleared on reindex and vendor indexing mode changes.
+#[derive(Debug, Default)]
+pub(crate) struct PharManifestCache {
+    archives_by_root: HashMap<PathBuf, Arc<Vec<PathBuf>>>,
+    by_archive: HashMap<PathBuf, CachedPharManifest>,
+    missing_classes: HashSet<String>,
+}
+
+type CachedPharManifest = (Option<std::time::SystemTime>, Arc<PharManifest>);
+
+impl PharManifestCache {
+    pub(crate) fn clear(&mut self) {
+        self.archives_by_root.clear();
+        self.by_archive.clear();
+        self.missing_classes.clear();
+    }
+}
+
+/// Symbols of the archive entry that declares a looked-up class.
+struct PharClassEntry {
+    uri: String,
+    file_symbols: php_lsp_types::FileSymbols,
+    references: Vec<php_lsp_types::SymbolReference>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PharContentParams {
+    pub uri: String,
+}
+
+/// Parses the manifest that follows the stub's `__HALT_COMPILER();`.
+pub(crate) fn parse_phar_manifest(bytes: &[u8]) -> Option<PharManifest> {
+    let halt = bytes
+        .windows(HALT_COMPILER.len())
+        .position(|window| window == HALT_COMPILER)?;
+    let mut start = halt + HALT_COMPILER.len();
+    for suffix in [&b" ?>\r\n"[..], b" ?>\n", b" ?>", b"\r\n", b"\n"] {
+        if bytes[start..].starts_with(suffix) {
+            start += suffix.len();
+            break;
+        }
+    }
+
+    let mut reader = ManifestReader { bytes, pos: start };
+    let manifest_len = reader.u32()? as usize;
+    let data_start = start.checked_add(4)?.checked_add(manifest_len)?;
+    let file_count = reader.u32()?;
+    reader.skip(2)?; // API version
+    reader.skip(4)?; // global flags
+    let alias_len = reader.u32()? as usize;
+    reader.skip(alias_len)?;
+    let metadata_len = reader.u32()? as usize;
+    reader.skip(metadata_len)?;
+
+    let mut entries = Vec::new();
+    let mut offset = data_start;
+    for _ in 0..file_count {
+        let name_len = reader.u32()? as usize;
+        let name = String::from_utf8_lossy(reader.take(name_len)?).into_owned();
+        reader.skip(4)?; // uncompressed size
+        reader.skip(4)?; // timestamp
+        let stored_size = reader.u32()? as usize;
+        reader.skip(4)?; // crc32
+        let flags = reader.u32()?;
+        let entry_metadata_len = reader.u32()? as usize;
+        reader.skip(entry_metadata_len)?;
+        entries.push(PharEntry {
+            name: name.trim_start_matches('/').to_string(),
+            offset,
+            size: stored_size,
+            compressed: flags & PHAR_ENTRY_COMPRESSED_MASK != 0,
+        });
+        offset = offset.checked_add(stored_size)?;
+    }
+    (offset <= bytes.len()).then_some(PharManifest { entries })
+}
+
+struct ManifestReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ManifestReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let slice = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take(4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// Text of an uncompressed entry.
+pub(crate) fn phar_entry_source(bytes: &[u8], entry: &PharEntry) -> Option<String> {
+    if entry.compressed {
+        return None;
+    }
+    let content = bytes.get(entry.offset..entry.offset.checked_add(entry.size)?)?;
+    String::from_utf8(content.to_vec()).ok()
+}
+
+/// The archive path is encoded like its `file://` URI, so paths with spaces
+/// round-trip through clients.
+pub(crate) fn phar_entry_uri(archive: &Path, entry: &str) -> String {
+    let archive_uri = php_lsp_types::uri::path_to_uri(archive)
+        .unwrap_or_else(|_| format!("file://{}", archive.to_string_lossy()));
+    format!(
+        "{PHAR_URI_PREFIX}{}/{}",
+        archive_uri.trim_start_matches("file://"),
+        entry.trim_start_matches('/')
+    )
+}
+
+/// Archive path and entry name of a `phar://` URI.
+pub(crate) fn split_phar_uri(uri: &str) -> Option<(PathBuf, String)> {
+    let rest = uri.strip_prefix(PHAR_URI_PREFIX)?;
+    let split = rest.find(".phar/")? + ".phar".len();
+    let entry = &rest[split + 1..];
+    if entry.is_empty()
+        || entry
+            .split('/')
+            .any(|component| component.is_empty() || component == "." || component == "..")
+    {
+        return None;
+    }
+    let archive = uri_to_path(&format!("file://{}", &rest[..split]))?;
+    Some((archive, entry.to_string()))
+}
+
+/// `*.phar` files where Composer and tool installers usually put them: the
+/// root, `tools/`, `vendor/bin/` and the top of each vendor package.
+pub(crate) fn discover_phar_archives(root: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![
+        root.to_path_buf(),
+        root.join("tools"),
+        root.join("vendor/bin"),
+    ];
+    if let Ok(vendors) = std::fs::read_dir(root.join("vendor")) {
+        for vendor in vendors.flatten() {
+            if let Ok(packages) = std::fs::read_dir(vendor.path()) {
+                dirs.extend(packages.flatten().map(|package| package.path()));
+            }
+        }
+    }
+
+    let mut archives: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "phar") && path.is_file())
+        .collect();
+    archives.sort();
+    archives.dedup();
+    archives
+}
+
+/// Entries that may declare `class_fqn`: files named after its short name,
+/// best namespace-path match first.
+pub(crate) fn phar_class_entry_candidates<'a>(
+    manifest: &'a PharManifest,
+    class_fqn: &str,
+) -> Vec<&'a PharEntry> {
+    let segments: Vec<&str> = class_fqn.trim_start_matches('\\').split('\\').collect();
+    let Some(short_name) = segments.last() else {
+        return Vec::new();
+    };
+    let file_name = format!("{short_name}.php");
+    let mut candidates: Vec<(usize, &PharEntry)> = manifest
+        .entries
+        .iter()
+        .filter(|entry| !entry.compressed)
+        .filter(|entry| entry.name.rsplit('/').next() == Some(file_name.as_str()))
+        .map(|entry| {
+            let matched = entry
+                .name
+                .trim_end_matches(".php")
+                .rsplit('/')
+                .zip(segments.iter().rev())
+                .take_while(|(dir, segment)| dir.eq_ignore_ascii_case(segment))
+                .count();
+            (matched, entry)
+        })
+        .collect();
+    candidates.sort_by(|(left, left_entry), (right, right_entry)| {
+        right
+            .cmp(left)
+            .then_with(|| left_entry.name.cmp(&right_entry.name))
+    });
+    candidates.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Reads one entry of `archive`.
+pub(crate) fn read_phar_entry(archive: &Path, entry_name: &str) -> Option<String> {
+    let bytes = std::fs::read(archive).ok()?;
+    let manifest = parse_phar_manifest(&bytes)?;
+    let entry = manifest
+        .entries
+        .iter()
+        .find(|entry| entry.name == entry_name)?;
+    phar_entry_source(&bytes, entry)
+}
+
+/// Finds the entry of `archive` declaring `class_fqn`. The archive is read and
+/// its manifest parsed at most once, and not at all when a still-fresh cached
+/// manifest has no entry named after the class. Returns the manifest to cache.
+fn lookup_phar_class_entry(
+    archive: &Path,
+    cached: Option<CachedPharManifest>,
+    class_fqn: &str,
+    encoding: PositionEncoding,
+) -> (CachedPharManifest, Option<PharClassEntry>) {
+    let modified = std::fs::metadata(archive)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let fresh = cached
+        .filter(|(cached_modified, _)| *cached_modified == modified)
+        .map(|(_, manifest)| manifest);
+    if let Some(manifest) = &fresh {
+        if phar_class_entry_candidates(manifest, class_fqn).is_empty() {
+            return ((modified, manifest.clone()), None);
+        }
+    }
+
+    let Ok(bytes) = std::fs::read(archive) else {
+        return ((modified, fresh.unwrap_or_default()), None);
+    };
+    let manifest =
+        fresh.unwrap_or_else(|| Arc::new(parse_phar_manifest(&bytes).unwrap_or_default()));
+    let found = phar_class_entry_candidates(&manifest, class_fqn)
+        .into_iter()
+        .find_map(|entry| {
+            let source = phar_entry_source(&bytes, entry)?;
+            let uri = phar_entry_uri(archive, &entry.name);
+            let mut parser = FileParser::new();
+            parser.parse_full(&source);
+            let tree = parser.tree()?;
+            let file_symbols = extract_file_symbols(tree, &source, &uri);
+            if !file_symbols
+                .symbols
+                .iter()
+                .any(|symbol| is_class_like_kind(symbol.kind) && symbol.fqn == class_fqn)
+            {
+                return None;
+            }
+            let references =
+                collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
+            Some(PharClassEntry {
+                uri,
+                file_symbols,
+                references,
+            })
+        });
+    ((modified, manifest), found)
+}
+
+async fn cached_phar_archives(context: &VendorLazyIndexContext, root: &Path) -> Arc<Vec<PathBuf>> {
+    if let Some(archives) = context
+        .phar_manifest_cache
+        .lock()
+        .await
+        .archives_by_root
+        .get(root)
+    {
+        return archives.clone();
+    }
+
+    let discover_root = root.to_path_buf();
+    let exclude_paths = context.exclude_paths.clone();
+    let archives = tokio::task::spawn_blocking(move || {
+        discover_phar_archives(&discover_root)
+            .into_iter()
+            .filter(|archive| !path_is_excluded(archive, &discover_root, &exclude_paths))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map(Arc::new)
+    .unwrap_or_default();
+    context
+        .phar_manifest_cache
+        .lock()
+        .await
+        .archives_by_root
+        .insert(root.to_path_buf(), archives.clone());
+    archives
+}
+
+/// Indexes the archive entry declaring `class_fqn`, trying the archives of
+/// every workspace root. Only runs with vendor indexing enabled; entries are
+/// tracked by the vendor LRU and classes no archive declares are remembered.
+pub(in crate::server) async fn lazy_index_phar_class(
+    context: &VendorLazyIndexContext,
+    class_fqn: &str,
+) -> bool {
+    if !context.index_vendor {
+        return false;
+    }
+    let miss_key = class_fqn.to_ascii_lowercase();
+    if context
+        .phar_manifest_cache
+        .lock()
+        .await
+        .missing_classes
+        .contains(&miss_key)
+    {
+        return false;
+    }
+
+    for config in &context.workspace_configs {
+        for archive in cached_phar_archives(context, &config.root).await.iter() {
+            let cached = context
+                .phar_manifest_cache
+                .lock()
+                .await
+                .by_archive
+                .get(archive)
+                .cloned();
+            let lookup_archive = archive.clone();
+            let lookup_fqn = class_fqn.to_string();
+            let encoding = context.encoding;
+            let Ok((manifest, found)) = tokio::task::spawn_blocking(move || {
+                lookup_phar_class_entry(&lookup_archive, cached, &lookup_fqn, encoding)
+            })
+            .await
+            else {
+                continue;
+            };
+            context
+                .phar_manifest_cache
+                .lock()
+                .await
+                .by_archive
+                .insert(archive.clone(), manifest);
+
+            let Some(entry) = found else {
+                continue;
+            };
+            context.index.update_file_with_references(
+                &entry.uri,
+                entry.file_symbols,
+                entry.references,
+            );
+            let evicted = context
+                .vendor_file_lru
+                .lock()
+                .await
+                .touch(entry.uri.clone());
+            for uri in evicted {
+                context.index.remove_file(&uri);
+            }
+            tracing::debug!("Lazy-indexed {} from PHAR entry {}", class_fqn, entry.uri);
+            return true;
+        }
+    }
+
+    context
+        .phar_manifest_cache
+        .lock()
+        .await
+        .missing_classes
+        .insert(miss_key);
+    false
+}
+
+impl PhpLspBackend {
+    /// Handle `php-lsp/pharContent`: the text of a `phar://` entry, or null.
+    pub async fn phar_content(&self, params: PharContentParams) -> Result<Option<Value>> {
+        Ok(self
+            .phar_source_for_uri(&params.uri, "PHAR entry read")
+            .await
+            .map(|text| json!({ "text": text })))
+    }
+
+    pub(in crate::server) async fn phar_source_for_uri(
+        &self,
+        uri_str: &str,
+        label: &'static str,
+    ) -> Option<String> {
+        let (archive, entry) = split_phar_uri(uri_str)?;
+        let path_label = archive.display().to_string();
+        run_file_io_blocking(label, path_label, move || read_phar_entry(&archive, &entry))
+            .await
+            .ok()
+            .flatten()
+    }
+}
@@ -0,0 +1,259 @@
+<?php
+// Prints a JSON object mapping every loaded extension that phpstorm-stubs does
+// not cover to generated PHP stub source. $argv[1] is a comma-separated list of
+// covered extension names. Kept compatible with PHP 7.0+ runtimes.
+
+function php_lsp_named_type($type)
+{
+    $name = $type->getName();
+    if (!$type->isBuiltin() && !in_array(strtolower($name), array('self', 'static', 'parent'), true)) {
+        return '\\' . ltrim($name, '\\');
+    }
+    return $name;
+}
+
+function php_lsp_type($type)
+{
+    if ($type === null) {
+        return '';
+    }
+    if ($type instanceof ReflectionNamedType) {
+        $name = php_lsp_named_type($type);
+        $nullable = $type->allowsNull() && !in_array(strtolower($name), array('mixed', 'null'), true);
+        return ($nullable ? '?' : '') . $name;
+    }
+    if ($type instanceof ReflectionUnionType || $type instanceof ReflectionIntersectionType) {
+        $parts = array();
+        foreach ($type->getTypes() as $part) {
+            $parts[] = $part instanceof ReflectionNamedType
+                ? php_lsp_named_type($part)
+                : '(' . php_lsp_type($part) . ')';
+        }
+        return implode($type instanceof ReflectionUnionType ? '|' : '&', $parts);
+    }
+    return (string) $type;
+}
+
+function php_lsp_value($value)
+{
+    if ($value === null || is_scalar($value) || is_array($value)) {
+        return var_export($value, true);
+    }
+    return 'null';
+}
+
+function php_lsp_visibility($member)
+{
+    if ($member->isPrivate()) {
+        return 'private ';
+    }
+    if ($member->isProtected()) {
+        return 'protected ';
+    }
+    return 'public ';
+}
+
+function php_lsp_params($function)
+{
+    $params = array();
+    foreach ($function->getParameters() as $param) {
+        $code = $param->hasType() ? php_lsp_type($param->getType()) . ' ' : '';
+        if ($param->isPassedByReference()) {
+            $code .= '&';
+        }
+        if ($param->isVariadic()) {
+            $code .= '...';
+        }
+        $code .= '$' . $param->getName();
+        if (!$param->isVariadic() && $param->isOptional()) {
+            $default = 'null';
+            try {
+                if ($param->isDefaultValueAvailable()) {
+                    if ($param->isDefaultValueConstant()) {
+                        $constant = $param->getDefaultValueConstantName();
+                        $default = preg_match('/^(self|static|parent)::/i', $constant)
+                            ? $constant
+                            : '\\' . ltrim($constant, '\\');
+                    } else {
+                        $default = php_lsp_value($param->getDefaultValue());
+                    }
+                }
+            } catch (Throwable $e) {
+            }
+            $code .= ' = ' . $default;
+        }
+        $params[] = $code;
+    }
+    return implode(', ', $params);
+}
+
+function php_lsp_return_type($function)
+{
+    $type = null;
+    if ($function->hasReturnType()) {
+        $type = $function->getReturnType();
+    } elseif (method_exists($function, 'hasTentativeReturnType') && $function->hasTentativeReturnType()) {
+        $type = $function->getTentativeReturnType();
+    }
+    $code = php_lsp_type($type);
+    return $code === '' ? '' : ': ' . $code;
+}
+
+function php_lsp_signature($function)
+{
+    return 'function ' . ($function->returnsReference() ? '&' : '') . $function->getShortName()
+        . '(' . php_lsp_params($function) . ')' . php_lsp_return_type($function);
+}
+
+function php_lsp_deprecated($function)
+{
+    return $function->isDeprecated() ? "/** @deprecated */\n" : '';
+}
+
+function php_lsp_class($class)
+{
+    $name = $class->getName();
+    $enum = null;
+    $code = '';
+    if ($class->isInterface()) {
+        $kind = 'interface';
+    } elseif ($class->isTrait()) {
+        $kind = 'trait';
+    } elseif (method_exists($class, 'isEnum') && $class->isEnum()) {
+        $kind = 'enum';
+        $enum = new ReflectionEnum($name);
+    } else {
+        $kind = 'class';
+        if ($class->isAbstract()) {
+            $code .= 'abstract ';
+        }
+        if ($class->isFinal()) {
+            $code .= 'final ';
+        }
+        if (method_exists($class, 'isReadOnly') && $class->isReadOnly()) {
+            $code .= 'readonly ';
+        }
+    }
+
+    $code .= $kind . ' ' . $class->getShortName();
+    if ($enum !== null && $enum->isBacked()) {
+        $code .= ': ' . php_lsp_type($enum->getBackingType());
+    }
+    $parent = $class->getParentClass();
+    if ($parent && $kind === 'class') {
+        $code .= ' extends \\' . $parent->getName();
+    }
+    $interfaces = array();
+    foreach ($class->getInterfaceNames() as $interface) {
+        if ($enum === null || !in_array($interface, array('UnitEnum', 'BackedEnum'), true)) {
+            $interfaces[] = '\\' . $interface;
+        }
+    }
+    if ($interfaces) {
+        $code .= ($kind === 'interface' ? ' extends ' : ' implements ') . implode(', ', $interfaces);
+    }
+    $code .= "\n{\n";
+
+    foreach ($class->getReflectionConstants() as $constant) {
+        if ($constant->getDeclaringClass()->getName() !== $name) {
+            continue;
+        }
+        if ($enum !== null && method_exists($constant, 'isEnumCase') && $constant->isEnumCase()) {
+            $code .= '    case ' . $constant->getName();
+            if ($enum->isBacked()) {
+                $code .= ' = ' . php_lsp_value($constant->getValue()->value);
+            }
+            $code .= ";\n";
+            continue;
+        }
+        $code .= '    ' . php_lsp_visibility($constant) . 'const ' . $constant->getName()
+            . ' = ' . php_lsp_value($constant->getValue()) . ";\n";
+    }
+
+    if ($enum === null) {
+        foreach ($class->getProperties() as $property) {
+            if ($property->getDeclaringClass()->getName() !== $name) {
+                continue;
+            }
+            $code .= '    ' . php_lsp_visibility($property);
+            if ($property->isStatic()) {
+                $code .= 'static ';
+            }
+            if (method_exists($property, 'isReadOnly') && $property->isReadOnly()) {
+                $code .= 'readonly ';
+            }
+            if (method_exists($property, 'hasType') && $property->hasType()) {
+                $code .= php_lsp_type($property->getType()) . ' ';
+            }
+            $code .= '$' . $property->getName() . ";\n";
+        }
+    }
+
+    foreach ($class->getMethods() as $method) {
+        if ($method->getDeclaringClass()->getName() !== $name) {
+            continue;
+        }
+        if ($enum !== null && in_array($method->getName(), array('cases', 'from', 'tryFrom'), true)) {
+            continue;
+        }
+        $code .= '    ' . str_replace("\n", "\n    ", php_lsp_deprecated($method));
+        if ($method->isAbstract() && $kind !== 'interface') {
+            $code .= 'abstract ';
+        }
+        if ($method->isFinal()) {
+            $code .= 'final ';
+        }
+        $code .= php_lsp_visibility($method);
+        if ($method->isStatic()) {
+            $code .= 'static ';
+        }
+        $code .= php_lsp_signature($method);
+        $code .= $kind === 'interface' || $method->isAbstract() ? ";\n" : " {}\n";
+    }
+
+    return $code . "}\n";
+}
+
+$covered = array();
+foreach (explode(',', isset($argv[1]) ? $argv[1] : '') as $covered_name) {
+    $covered[strtolower(trim($covered_name))] = true;
+}
+
+$stubs = array();
+foreach (get_loaded_extensions() as $extension) {
+    if (isset($covered[strtolower($extension)])) {
+        continue;
+    }
+    try {
+        $reflection = new ReflectionExtension($extension);
+    } catch (Exception $e) {
+        continue;
+    }
+
+    $blocks = array();
+    foreach ($reflection->getConstants() as $constant => $value) {
+        $separator = strrpos($constant, '\\');
+        $namespace = $separator === false ? '' : substr($constant, 0, $separator);
+        $short_name = $separator === false ? $constant : substr($constant, $separator + 1);
+        $blocks[$namespace][] = 'const ' . $short_name . ' = ' . php_lsp_value($value) . ";\n";
+    }
+    foreach ($reflection->getFunctions() as $function) {
+        $blocks[$function->getNamespaceName()][] = php_lsp_deprecated($function)
+            . php_lsp_signature($function) . " {}\n";
+    }
+    foreach ($reflection->getClasses() as $class) {
+        $blocks[$class->getNamespaceName()][] = php_lsp_class($class);
+    }
+    if (!$blocks) {
+        continue;
+    }
+
+    $source = "<?php\n\n// Generated by php-lsp from the local PHP runtime (extension " . $extension . ").\n";
+    foreach ($blocks as $namespace => $declarations) {
+        $source .= "\nnamespace" . ($namespace === '' ? '' : ' ' . $namespace) . " {\n\n"
+            . implode("\n", $declarations) . "\n}\n";
+    }
+    $stubs[$extension] = $source;
+}
+
+echo json_encode($stubs, defined('JSON_INVALID_UTF8_SUBSTITUTE') ? JSON_INVALID_UTF8_SUBSTITUTE : 0);
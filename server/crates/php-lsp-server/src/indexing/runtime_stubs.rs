@@ -0,0 +1,182 @@
+//! Stub generation from the local PHP runtime.
+//!
+//! Runs the configured `php` binary with a Reflection script that renders stub
+//! source for loaded extensions phpstorm-stubs does not cover (custom PECL
+//! modules, in-house extensions). Generated files are written below the
+//! workspace stubs cache directory in the phpstorm-stubs layout and indexed as
+//! built-in `phpstub://` symbols, so definitions can read them back.
+
+use super::super::*;
+
+const RUNTIME_STUBS_SCRIPT: &str = include_str!("runtime_stubs.php");
+
+impl PhpLspBackend {
+    /// Generates and indexes runtime stubs when enabled. Returns the number of
+    /// generated stub files loaded.
+    pub(in crate::server) async fn load_runtime_stubs(
+        &self,
+        root: PathBuf,
+        php_version: PhpVersion,
+    ) -> usize {
+        let config = self.runtime_stubs_config.lock().await.clone();
+        if !config.enabled {
+            return 0;
+        }
+
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
+        let covered_root = root.clone();
+        let covered = tokio::task::spawn_blocking(move || {
+            usable_stubs_path(&covered_root, client_stubs_path)
+                .map(|stubs_path| stubs::discover_stub_extensions(&stubs_path))
+                .unwrap_or_default()
+        })
+        .await
+        .unwrap_or_default();
+
+        let generated = match generate_runtime_stubs(&config, &covered).await {
+            Ok(generated) => generated,
+            Err(err) => {
+                let message = format!("php-lsp: runtime stub generation failed: {err}");
+                tracing::warn!("{}", message);
+                self.client.log_message(MessageType::WARNING, message).await;
+                return 0;
+            }
+        };
+
+        let index = self.index.clone();
+        let loaded = tokio::task::spawn_blocking(move || {
+            load_runtime_stub_sources(&index, &runtime_stubs_dir(&root), &generated, php_version)
+        })
+        .await
+        .unwrap_or(0);
+        tracing::info!("Loaded {} runtime stub files", loaded);
+        loaded
+    }
+}
+
+/// Directory holding generated runtime stubs for a workspace.
+pub(crate) fn runtime_stubs_dir(root: &Path) -> PathBuf {
+    cache::cache_file_path_for_namespace(root, CacheNamespace::Stubs).with_file_name("runtime")
+}
+
+/// Runs the Reflection script and returns `(extension, stub source)` pairs for
+/// loaded extensions missing from `covered`.
+pub(in crate::server) async fn generate_runtime_stubs(
+    config: &RuntimeStubsConfig,
+    covered: &[String],
+) -> std::result::Result<Vec<(String, String)>, String> {
+    let script = RUNTIME_STUBS_SCRIPT
+        .trim_start()
+        .strip_prefix("<?php")
+        .unwrap_or(RUNTIME_STUBS_SCRIPT);
+    let mut command = tokio::process::Command::new(&config.php_executable);
+    command
+        .arg("-d")
+        .arg("display_errors=stderr")
+        .arg("-r")
+        .arg(script)
+        .arg(covered.join(","))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    let child = command
+        .spawn()
+        .map_err(|err| format!("failed to start {}: {}", config.php_executable, err))?;
+    let output = tokio::time::timeout(
+        Duration::from_millis(config.timeout_ms),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| {
+        format!(
+            "{} timed out after {}ms",
+            config.php_executable, config.timeout_ms
+        )
+    })?
+    .map_err(|err| format!("failed to wait for {}: {}", config.php_executable, err))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} exited with {}: {}",
+            config.php_executable,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_runtime_stubs_output(&output.stdout)
+}
+
+pub(in crate::server) fn parse_runtime_stubs_output(
+    stdout: &[u8],
+) -> std::result::Result<Vec<(String, String)>, String> {
+    let value: serde_json::Value = serde_json::from_slice(stdout)
+        .map_err(|err| format!("invalid runtime stub output: {err}"))?;
+    match value {
+        // PHP encodes an empty array as `[]` when every extension is covered.
+        serde_json::Value::Array(items) if items.is_empty() => Ok(Vec::new()),
+        serde_json::Value::Object(stubs) => Ok(stubs
+            .into_iter()
+            .filter_map(|(extension, source)| Some((extension, source.as_str()?.to_string())))
+            .collect()),
+        _ => Err("invalid runtime stub output: expected a JSON object".to_string()),
+    }
+}
+
+/// Writes generated stubs to `dir`, replacing earlier output, and indexes them
+/// as built-in symbols. Returns the number of stub files loaded.
+pub(crate) fn load_runtime_stub_sources(
+    index: &WorkspaceIndex,
+    dir: &Path,
+    generated: &[(String, String)],
+    php_version: PhpVersion,
+) -> usize {
+    let _ = std::fs::remove_dir_all(dir);
+    let stub_php_version = stubs::StubPhpVersion {
+        major: php_version.major,
+        minor: php_version.minor,
+    };
+
+    let mut loaded = 0;
+    for (extension, source) in generated {
+        let Some(ext_name) = runtime_stub_extension_dir_name(extension) else {
+            continue;
+        };
+        let path = dir.join(&ext_name).join(format!("{ext_name}.php"));
+        let written = std::fs::create_dir_all(dir.join(&ext_name))
+            .and_then(|()| std::fs::write(&path, source));
+        if let Err(err) = written {
+            tracing::warn!("Failed to write runtime stub {}: {}", path.display(), err);
+            continue;
+        }
+        if stubs::load_stub_file_for_php_version(
+            index,
+            dir,
+            &ext_name,
+            &path,
+            Some(stub_php_version),
+        )
+        .is_some()
+        {
+            loaded += 1;
+        }
+    }
+    loaded
+}
+
+/// Extension names such as `Zend OPcache` become `Zend_OPcache`, which keeps
+/// them usable as `phpstub://` URI segments.
+fn runtime_stub_extension_dir_name(extension: &str) -> Option<String> {
+    let name: String = extension
+        .trim()
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    (!name.trim_matches('_').is_empty()).then_some(name)
+}
@@ -78,7 +78,7 @@ fn push_candidate_path(candidate_paths: &mut Vec<PathBuf>, path: PathBuf) {
     }
 }
 
-fn lexically_normalize_path(path: &Path) -> PathBuf {
+pub(in crate::server) fn lexically_normalize_path(path: &Path) -> PathBuf {
     let mut normalized = PathBuf::new();
     for component in path.components() {
         match component {
@@ -101,6 +101,16 @@ fn lexically_normalize_path(path: &Path) -> PathBuf {
     normalized
 }
 
+/// First candidate stubs root that `load_configured_stubs` would load from.
+pub(crate) fn usable_stubs_path(
+    root: &Path,
+    client_stubs_path: Option<PathBuf>,
+) -> Option<PathBuf> {
+    candidate_stubs_paths(root, client_stubs_path)
+        .into_iter()
+        .find(|stubs_path| stubs_path.is_dir() && unusable_stubs_path_reason(stubs_path).is_none())
+}
+
 pub(crate) fn load_configured_stubs(
     index: &WorkspaceIndex,
     root: &Path,
@@ -28,11 +28,15 @@ fn member_kinds_for_ref_kind(ref_kind: RefKind) -> Option<&'static [php_lsp_type
 #[derive(Debug, Default)]
 pub(crate) struct VendorAutoloadCache {
     pub(crate) by_vendor_dir: HashMap<PathBuf, VendorAutoloadCacheEntry>,
+    /// Bumped on every clear so maps parsed before an invalidation are not
+    /// stored afterwards.
+    generation: u64,
 }
 
 impl VendorAutoloadCache {
     pub(crate) fn clear(&mut self) {
         self.by_vendor_dir.clear();
+        self.generation = self.generation.wrapping_add(1);
     }
 }
 
@@ -91,10 +95,13 @@ pub(in crate::server) struct VendorLazyIndexContext {
     pub(in crate::server) index: Arc<WorkspaceIndex>,
     pub(in crate::server) workspace_configs: Vec<WorkspaceRootConfig>,
     pub(in crate::server) exclude_paths: Vec<PathBuf>,
+    pub(in crate::server) file_extensions: Vec<String>,
     pub(in crate::server) php_version: PhpVersion,
     pub(in crate::server) index_vendor: bool,
     pub(in crate::server) vendor_autoload_cache: Arc<Mutex<VendorAutoloadCache>>,
     pub(in crate::server) vendor_file_lru: Arc<Mutex<VendorFileLru>>,
+    pub(in crate::server) phar_manifest_cache: Arc<Mutex<PharManifestCache>>,
+    pub(in crate::server) encoding: PositionEncoding,
 }
 
 pub(crate) fn parse_vendor_autoload_map(vendor_dir: &Path) -> Option<VendorAutoloadMap> {
@@ -120,6 +127,25 @@ pub(crate) fn parse_vendor_autoload_map(vendor_dir: &Path) -> Option<VendorAutol
             .and_then(|v| v.as_str())
             .unwrap_or("");
         let pkg_dir = vendor_package_dir(vendor_dir, install_path);
+        map.packages.push(VendorPackage {
+            name: pkg
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            version: pkg
+                .get("version_normalized")
+                .or_else(|| pkg.get("version"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            reference: ["dist", "source"]
+                .iter()
+                .find_map(|key| pkg.get(*key)?.get("reference")?.as_str())
+                .unwrap_or("")
+                .to_string(),
+            dir: lexically_normalize_path(&pkg_dir),
+        });
 
         if let Some(autoload) = pkg.get("autoload") {
             append_vendor_autoload(&mut map, &pkg_dir, autoload);
@@ -132,6 +158,56 @@ pub(crate) fn parse_vendor_autoload_map(vendor_dir: &Path) -> Option<VendorAutol
     Some(map)
 }
 
+/// Install directories of packages that were removed, moved, or changed
+/// version or source reference between two installed.json snapshots.
+pub(crate) fn changed_vendor_package_dirs(
+    previous: &VendorAutoloadMap,
+    current: &VendorAutoloadMap,
+) -> Vec<PathBuf> {
+    let current_by_name: HashMap<&str, &VendorPackage> = current
+        .packages
+        .iter()
+        .map(|package| (package.name.as_str(), package))
+        .collect();
+    let mut dirs = Vec::new();
+    for package in &previous.packages {
+        if current_by_name.get(package.name.as_str()) != Some(&package) {
+            push_unique_path(&mut dirs, package.dir.clone());
+        }
+    }
+    dirs
+}
+
+/// Drop indexed files under the given package directories, including their
+/// lazy-index LRU entries. Returns the number of files removed.
+pub(in crate::server) fn remove_indexed_vendor_package_files(
+    index: &WorkspaceIndex,
+    vendor_file_lru: &mut VendorFileLru,
+    package_dirs: &[PathBuf],
+) -> usize {
+    if package_dirs.is_empty() {
+        return 0;
+    }
+    let uris: Vec<String> = index
+        .file_symbols
+        .iter()
+        .filter_map(|entry| {
+            let path = uri_to_path(entry.key())?;
+            package_dirs
+                .iter()
+                .any(|dir| path.starts_with(dir))
+                .then(|| entry.key().clone())
+        })
+        .collect();
+
+    let removed = uris.len();
+    for uri in uris {
+        vendor_file_lru.remove(&uri);
+        index.remove_file(&uri);
+    }
+    removed
+}
+
 pub(in crate::server) async fn parse_vendor_autoload_map_blocking(
     vendor_dir: PathBuf,
 ) -> Option<VendorAutoloadMap> {
@@ -165,9 +241,11 @@ pub(in crate::server) async fn lazy_index_class_with_context(
             if let Some(vendor_map) =
                 cached_vendor_autoload_map(&context.vendor_autoload_cache, &vendor_dir).await
             {
-                if let Some(vendor_paths) =
-                    resolve_vendor_paths_from_map(requested_class_fqn, &vendor_map)
-                {
+                if let Some(vendor_paths) = resolve_vendor_paths_from_map(
+                    requested_class_fqn,
+                    &vendor_map,
+                    &context.file_extensions,
+                ) {
                     all_paths.extend(vendor_paths);
                 }
             }
@@ -215,6 +293,7 @@ pub(in crate::server) async fn lazy_index_class_with_context(
                 context.index.clone(),
                 abs.clone(),
                 "lazy PHP file index",
+                context.encoding,
             )
             .await
             {
@@ -244,7 +323,7 @@ pub(in crate::server) async fn lazy_index_class_with_context(
         }
     }
 
-    false
+    lazy_index_phar_class(context, requested_class_fqn).await
 }
 
 pub(in crate::server) fn lazy_index_parents_with_context<'a>(
@@ -366,6 +445,7 @@ pub(in crate::server) fn vendor_package_dir(vendor_dir: &Path, install_path: &st
 pub(crate) fn resolve_vendor_paths_from_map(
     fqn: &str,
     map: &VendorAutoloadMap,
+    extensions: &[String],
 ) -> Option<Vec<PathBuf>> {
     let normalized_fqn = fqn.trim_start_matches('\\');
     let mut paths = Vec::new();
@@ -378,7 +458,7 @@ pub(crate) fn resolve_vendor_paths_from_map(
             push_unique_path(&mut paths, directory.join(&relative_path));
         }
     }
-    for path in classmap_candidate_paths_for_fqn(normalized_fqn, map) {
+    for path in classmap_candidate_paths_for_fqn(normalized_fqn, map, extensions) {
         push_unique_path(&mut paths, path);
     }
 
@@ -393,6 +473,7 @@ pub(crate) fn vendor_autoload_file_paths_from_map(
     map: &VendorAutoloadMap,
     project_root: &Path,
     exclude_paths: &[PathBuf],
+    extensions: &[String],
 ) -> Vec<PathBuf> {
     let mut paths = Vec::new();
     for file_path in &map.files {
@@ -400,6 +481,7 @@ pub(crate) fn vendor_autoload_file_paths_from_map(
             file_path,
             project_root,
             exclude_paths,
+            extensions,
             &mut paths,
             0,
         );
@@ -407,16 +489,34 @@ pub(crate) fn vendor_autoload_file_paths_from_map(
     paths
 }
 
+/// Directories and files holding a vendor map's autoloaded sources: PSR-4
+/// directories, classmap entries and `files` entrypoints.
+pub(in crate::server) fn vendor_source_paths_from_map(map: &VendorAutoloadMap) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for mapping in &map.psr4 {
+        for directory in &mapping.directories {
+            push_unique_path(&mut paths, directory.clone());
+        }
+    }
+    for path in map.classmap.iter().chain(&map.files) {
+        push_unique_path(&mut paths, path.clone());
+    }
+    paths
+}
+
 pub(in crate::server) async fn vendor_autoload_file_paths_from_map_blocking(
     map: VendorAutoloadMap,
     project_root: PathBuf,
     exclude_paths: Vec<PathBuf>,
+    extensions: Vec<String>,
 ) -> Vec<PathBuf> {
     let path_label = project_root.display().to_string();
     match run_file_io_blocking(
         "vendor autoload file discovery",
         path_label.clone(),
-        move || vendor_autoload_file_paths_from_map(&map, &project_root, &exclude_paths),
+        move || {
+            vendor_autoload_file_paths_from_map(&map, &project_root, &exclude_paths, &extensions)
+        },
     )
     .await
     {
@@ -436,13 +536,14 @@ fn push_vendor_autoload_file_and_static_includes(
     file_path: &Path,
     project_root: &Path,
     exclude_paths: &[PathBuf],
+    extensions: &[String],
     paths: &mut Vec<PathBuf>,
     depth: usize,
 ) {
     const MAX_STATIC_INCLUDE_DEPTH: usize = 8;
 
     if depth > MAX_STATIC_INCLUDE_DEPTH
-        || !is_php_file_path(file_path)
+        || !path_has_php_extension(file_path, extensions)
         || path_is_excluded(file_path, project_root, exclude_paths)
     {
         return;
@@ -459,6 +560,7 @@ fn push_vendor_autoload_file_and_static_includes(
             &include_path,
             project_root,
             exclude_paths,
+            extensions,
             paths,
             depth + 1,
         );
@@ -518,13 +620,23 @@ pub(crate) fn vendor_namespace_exists_from_map(fqn: &str, map: &VendorAutoloadMa
     false
 }
 
-fn classmap_candidate_paths_for_fqn(fqn: &str, map: &VendorAutoloadMap) -> Vec<PathBuf> {
+fn classmap_candidate_paths_for_fqn(
+    fqn: &str,
+    map: &VendorAutoloadMap,
+    extensions: &[String],
+) -> Vec<PathBuf> {
     let class_basename = fqn.rsplit('\\').next().unwrap_or(fqn);
     let mut matching = Vec::new();
     let mut fallback = Vec::new();
 
     for path in &map.classmap {
-        collect_classmap_php_files(path, class_basename, &mut matching, &mut fallback);
+        collect_classmap_php_files(
+            path,
+            class_basename,
+            extensions,
+            &mut matching,
+            &mut fallback,
+        );
     }
 
     matching.extend(fallback);
@@ -534,11 +646,12 @@ fn classmap_candidate_paths_for_fqn(fqn: &str, map: &VendorAutoloadMap) -> Vec<P
 fn collect_classmap_php_files(
     path: &Path,
     class_basename: &str,
+    extensions: &[String],
     matching: &mut Vec<PathBuf>,
     fallback: &mut Vec<PathBuf>,
 ) {
     if path.is_file() {
-        push_classmap_candidate(path, class_basename, matching, fallback);
+        push_classmap_candidate(path, class_basename, extensions, matching, fallback);
         return;
     }
 
@@ -555,17 +668,18 @@ fn collect_classmap_php_files(
     entries.sort();
 
     for entry in entries {
-        collect_classmap_php_files(&entry, class_basename, matching, fallback);
+        collect_classmap_php_files(&entry, class_basename, extensions, matching, fallback);
     }
 }
 
 fn push_classmap_candidate(
     path: &Path,
     class_basename: &str,
+    extensions: &[String],
     matching: &mut Vec<PathBuf>,
     fallback: &mut Vec<PathBuf>,
 ) {
-    if !is_php_file_path(path) {
+    if !path_has_php_extension(path, extensions) {
         return;
     }
 
@@ -580,32 +694,30 @@ fn push_classmap_candidate(
     }
 }
 
-fn is_php_file_path(path: &Path) -> bool {
-    path.extension()
-        .and_then(|extension| extension.to_str())
-        .is_some_and(|extension| extension.eq_ignore_ascii_case("php"))
-}
-
 pub(in crate::server) async fn cached_vendor_autoload_map(
     cache: &Arc<Mutex<VendorAutoloadCache>>,
     vendor_dir: &Path,
 ) -> Option<VendorAutoloadMap> {
-    {
+    let generation = {
         let cache = cache.lock().await;
         if let Some(entry) = cache.by_vendor_dir.get(vendor_dir) {
             return Some(entry.map.clone());
         }
-    }
+        cache.generation
+    };
 
     let Some(map) = parse_vendor_autoload_map_blocking(vendor_dir.to_path_buf()).await else {
         cache.lock().await.by_vendor_dir.remove(vendor_dir);
         return None;
     };
 
-    cache.lock().await.by_vendor_dir.insert(
-        vendor_dir.to_path_buf(),
-        VendorAutoloadCacheEntry { map: map.clone() },
-    );
+    let mut cache = cache.lock().await;
+    if cache.generation == generation {
+        cache.by_vendor_dir.insert(
+            vendor_dir.to_path_buf(),
+            VendorAutoloadCacheEntry { map: map.clone() },
+        );
+    }
     Some(map)
 }
 
@@ -616,22 +728,22 @@ pub(in crate::server) fn resolve_vendor_paths(
     vendor_dir: &Path,
 ) -> Option<Vec<PathBuf>> {
     let map = parse_vendor_autoload_map(vendor_dir)?;
-    resolve_vendor_paths_from_map(fqn, &map)
+    resolve_vendor_paths_from_map(fqn, &map, &default_php_file_extensions())
 }
 
 impl PhpLspBackend {
     pub(in crate::server) async fn vendor_lazy_index_context(&self) -> VendorLazyIndexContext {
         let mut workspace_configs = self.workspace_configs.lock().await.clone();
         let exclude_paths = self.exclude_paths.lock().await.clone();
+        let file_extensions = self.php_file_extensions.lock().await.clone();
         let php_version = *self.php_version.lock().await;
-        let index_vendor = *self.index_vendor.lock().await;
+        let index_vendor = self.vendor_indexing.lock().await.is_enabled();
         if workspace_configs.is_empty() {
-            let root = self.workspace_root.lock().await.clone();
-            let namespace_map = self.namespace_map.lock().await.clone();
-            if let Some(root) = root {
+            let snapshot = self.config_snapshot.load();
+            if let Some(root) = snapshot.workspace_root.clone() {
                 workspace_configs.push(WorkspaceRootConfig {
                     root,
-                    namespace_map,
+                    namespace_map: snapshot.namespace_map.clone(),
                 });
             }
         }
@@ -640,27 +752,73 @@ impl PhpLspBackend {
             index: self.index.clone(),
             workspace_configs,
             exclude_paths,
+            file_extensions,
             php_version,
             index_vendor,
             vendor_autoload_cache: self.vendor_autoload_cache.clone(),
             vendor_file_lru: self.vendor_file_lru.clone(),
+            phar_manifest_cache: self.phar_manifest_cache.clone(),
+            encoding: self.position_encoding(),
+        }
+    }
+
+    /// Evict only the indexed files of vendor packages whose installed.json
+    /// entry changed, and store the new autoload maps. Returns `None` when a
+    /// vendor directory has no earlier snapshot to compare against, so the
+    /// caller falls back to dropping every indexed vendor file.
+    pub(in crate::server) async fn evict_changed_vendor_packages(&self) -> Option<usize> {
+        let previous = self
+            .vendor_autoload_cache
+            .lock()
+            .await
+            .by_vendor_dir
+            .clone();
+        let mut snapshots = Vec::new();
+        for root in self.current_workspace_roots().await {
+            let vendor_dir = root.join("vendor");
+            let previous_entry = previous.get(&vendor_dir);
+            if previous_entry.is_none() && !vendor_dir.is_dir() {
+                continue;
+            }
+            let previous_map = previous_entry?.map.clone();
+            let current_map = parse_vendor_autoload_map_blocking(vendor_dir.clone()).await?;
+            snapshots.push((vendor_dir, previous_map, current_map));
+        }
+
+        let mut package_dirs = Vec::new();
+        for (_, previous_map, current_map) in &snapshots {
+            for dir in changed_vendor_package_dirs(previous_map, current_map) {
+                push_unique_path(&mut package_dirs, dir);
+            }
+        }
+        let removed = {
+            let mut vendor_file_lru = self.vendor_file_lru.lock().await;
+            remove_indexed_vendor_package_files(&self.index, &mut vendor_file_lru, &package_dirs)
+        };
+
+        let mut cache = self.vendor_autoload_cache.lock().await;
+        cache.clear();
+        for (vendor_dir, _, map) in snapshots {
+            cache
+                .by_vendor_dir
+                .insert(vendor_dir, VendorAutoloadCacheEntry { map });
         }
+        Some(removed)
     }
 
     pub(in crate::server) async fn vendor_namespace_exists_lazy(&self, fqn: &str) -> bool {
-        let index_vendor = *self.index_vendor.lock().await;
+        let index_vendor = self.vendor_indexing.lock().await.is_enabled();
         if !index_vendor {
             return false;
         }
 
         let mut configs = self.workspace_configs.lock().await.clone();
         if configs.is_empty() {
-            let root = self.workspace_root.lock().await.clone();
-            let namespace_map = self.namespace_map.lock().await.clone();
-            if let Some(root) = root {
+            let snapshot = self.config_snapshot.load();
+            if let Some(root) = snapshot.workspace_root.clone() {
                 configs.push(WorkspaceRootConfig {
                     root,
-                    namespace_map,
+                    namespace_map: snapshot.namespace_map.clone(),
                 });
             }
         }
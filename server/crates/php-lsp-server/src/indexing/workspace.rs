@@ -3,10 +3,12 @@
 use crate::util::uri::path_to_uri;
 
 use super::super::*;
+use tower_lsp::{Bounded, Cancellable, OngoingProgress};
 use tracing::Instrument;
 
 impl PhpLspBackend {
     pub(crate) async fn lsp_initialized(&self, _params: InitializedParams) {
+        let encoding = self.position_encoding();
         tracing::info!("php-lsp: initialized");
         let indexing_run_state = self.indexing_run.clone();
         let indexing_token = self.start_indexing_run().await;
@@ -17,7 +19,7 @@ impl PhpLspBackend {
 
         let mut roots = self.workspace_roots.lock().await.clone();
         if roots.is_empty() {
-            if let Some(root) = self.workspace_root.lock().await.clone() {
+            if let Some(root) = self.config_snapshot.load().workspace_root.clone() {
                 roots.push(root);
             }
         }
@@ -50,14 +52,16 @@ impl PhpLspBackend {
         let effective_roots: Vec<PathBuf> =
             configs.iter().map(|config| config.root.clone()).collect();
 
-        if let Some(first_root) = effective_roots.first() {
-            *self.workspace_root.lock().await = Some(first_root.clone());
-        }
+        self.config_snapshot.update(|config| {
+            if let Some(first_root) = effective_roots.first() {
+                config.workspace_root = Some(first_root.clone());
+            }
+            config.namespace_map = configs
+                .iter()
+                .find_map(|config| config.namespace_map.clone());
+        });
         *self.workspace_roots.lock().await = effective_roots;
         *self.workspace_configs.lock().await = configs.clone();
-        *self.namespace_map.lock().await = configs
-            .iter()
-            .find_map(|config| config.namespace_map.clone());
 
         // Load phpstorm-stubs for built-in PHP functions/classes.
         let stubs_index = self.index.clone();
@@ -66,7 +70,7 @@ impl PhpLspBackend {
             .map(|config| config.root.clone())
             .unwrap_or_default();
         let stubs_root_label = stubs_root.display().to_string();
-        let client_stubs_path = self.stubs_path.lock().await.clone();
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
         let stub_extensions = self.stub_extensions.lock().await.clone();
         let php_version = *self.php_version.lock().await;
 
@@ -82,10 +86,12 @@ impl PhpLspBackend {
 
         let load_client_stubs_path = client_stubs_path.clone();
         let load_stub_extensions = stub_extensions.clone();
+        let load_stubs_root = stubs_root.clone();
+        let stubs_started_at = Instant::now();
         let loaded_stubs = tokio::task::spawn_blocking(move || {
             load_configured_stubs(
                 &stubs_index,
-                &stubs_root,
+                &load_stubs_root,
                 load_client_stubs_path,
                 load_stub_extensions,
                 php_version,
@@ -93,7 +99,9 @@ impl PhpLspBackend {
             )
         })
         .await
-        .unwrap_or(0);
+        .unwrap_or(0)
+            + self.load_runtime_stubs(stubs_root, php_version).await;
+        self.record_stub_load(loaded_stubs, stubs_started_at).await;
 
         send_indexing_status(
             &self.client,
@@ -118,18 +126,24 @@ impl PhpLspBackend {
         let diagnostics_mode = *self.diagnostics_mode.lock().await;
         let diagnostic_severity = *self.diagnostic_severity.lock().await;
         let diagnostic_budget = *self.diagnostic_budget.lock().await;
+        let diagnostic_baseline = self.diagnostic_baseline.lock().await.clone();
         let diagnostics_config = DiagnosticsRuntimeConfig {
             mode: diagnostics_mode,
             severity: diagnostic_severity,
             budget: diagnostic_budget,
             php_version,
+            encoding,
         };
-        let index_vendor = *self.index_vendor.lock().await;
+        let vendor_indexing = *self.vendor_indexing.lock().await;
+        let index_vendor = vendor_indexing.is_enabled();
         let vendor_autoload_cache = self.vendor_autoload_cache.clone();
         let vendor_file_lru = self.vendor_file_lru.clone();
+        let phar_manifest_cache = self.phar_manifest_cache.clone();
         let work_done_progress_supported = *self.work_done_progress_supported.lock().await;
         let include_paths = self.include_paths.lock().await.clone();
         let exclude_paths = self.exclude_paths.lock().await.clone();
+        let respect_gitignore = *self.respect_gitignore.lock().await;
+        let file_extensions = self.php_file_extensions.lock().await.clone();
         let cache_config = workspace_index_cache_config(
             configs.first().map(|config| config.root.as_path()),
             php_version,
@@ -141,18 +155,26 @@ impl PhpLspBackend {
         let indexing_options = WorkspaceIndexingOptions {
             include_paths,
             exclude_paths,
+            respect_gitignore,
+            file_extensions,
             cache_config,
             work_done_progress_supported,
+            progress_cancellations: self.indexing_progress_cancellations.clone(),
         };
         let vendor_lazy_context = VendorLazyIndexContext {
             index: index.clone(),
             workspace_configs: configs.clone(),
             exclude_paths: indexing_options.exclude_paths.clone(),
+            file_extensions: indexing_options.file_extensions.clone(),
             php_version,
             index_vendor,
             vendor_autoload_cache: vendor_autoload_cache.clone(),
             vendor_file_lru: vendor_file_lru.clone(),
+            phar_manifest_cache: phar_manifest_cache.clone(),
+            encoding,
         };
+        let indexing_timings = self.indexing_timings.clone();
+        let indexing_started_at = Instant::now();
         tokio::spawn(async move {
             for config in &configs {
                 if finish_indexing_run_if_cancelled(&indexing_run_state, &indexing_token).await {
@@ -165,6 +187,7 @@ impl PhpLspBackend {
                     config.namespace_map.as_ref(),
                     &indexing_options,
                     &indexing_token,
+                    encoding,
                 )
                 .await
                 {
@@ -193,9 +216,24 @@ impl PhpLspBackend {
                         index.clone(),
                         &config.root,
                         &indexing_options.exclude_paths,
+                        &indexing_options.file_extensions,
                         php_version,
                         &vendor_autoload_cache,
                         &vendor_file_lru,
+                        encoding,
+                    )
+                    .await;
+                }
+                if vendor_indexing == VendorIndexing::Full {
+                    index_vendor_sources(
+                        index.clone(),
+                        &config.root,
+                        &indexing_options.exclude_paths,
+                        &indexing_options.file_extensions,
+                        php_version,
+                        &vendor_autoload_cache,
+                        &indexing_token,
+                        encoding,
                     )
                     .await;
                 }
@@ -205,6 +243,7 @@ impl PhpLspBackend {
             if finish_indexing_run_if_cancelled(&indexing_run_state, &indexing_token).await {
                 return;
             }
+            indexing_timings.lock().await.workspace_ms = Some(elapsed_ms(indexing_started_at));
             finish_indexing_run_state(&indexing_run_state, &indexing_token).await;
 
             let workspace_roots: Vec<PathBuf> =
@@ -217,6 +256,7 @@ impl PhpLspBackend {
                 &workspace_roots,
                 &twig_context_disk_cache,
                 &semantic_tokens_cache,
+                encoding,
             )
             .await;
             if finish_indexing_run_if_cancelled(&indexing_run_state, &indexing_token).await {
@@ -256,6 +296,7 @@ impl PhpLspBackend {
                         diags = template.map_diagnostics_to_original(
                             diags,
                             diagnostics_config.mode == DiagnosticsMode::Off,
+                            encoding,
                         );
                     } else if diagnostics_config.mode == DiagnosticsMode::BasicSemantic
                         && index_vendor
@@ -279,6 +320,11 @@ impl PhpLspBackend {
                             version = ?version,
                             duration_ms = tracing::field::Empty,
                         );
+                        let diags = filter_baselined_diagnostics(
+                            diagnostic_baseline.as_deref(),
+                            &uri_str,
+                            diags,
+                        );
                         async {
                             reindex_client
                                 .publish_diagnostics(uri, diags, version)
@@ -298,6 +344,7 @@ impl PhpLspBackend {
         &self,
         params: DidChangeWorkspaceFoldersParams,
     ) {
+        let encoding = self.position_encoding();
         tracing::debug!("didChangeWorkspaceFolders");
 
         let removed_roots: Vec<PathBuf> = params
@@ -327,8 +374,10 @@ impl PhpLspBackend {
                     .iter()
                     .find_map(|config| config.namespace_map.clone())
             };
-            *self.workspace_root.lock().await = first_root;
-            *self.namespace_map.lock().await = first_namespace_map;
+            self.config_snapshot.update(|config| {
+                config.workspace_root = first_root;
+                config.namespace_map = first_namespace_map;
+            });
 
             let removed_files = remove_indexed_files_under_roots(&self.index, &removed_roots);
             self.client
@@ -367,11 +416,11 @@ impl PhpLspBackend {
             }
             roots.first().cloned()
         };
-        let mut workspace_root = self.workspace_root.lock().await;
-        if workspace_root.is_none() {
-            *workspace_root = first_root;
-        }
-        drop(workspace_root);
+        self.config_snapshot.update(|config| {
+            if config.workspace_root.is_none() {
+                config.workspace_root = first_root;
+            }
+        });
 
         let first_namespace_map = {
             let mut configs = self.workspace_configs.lock().await;
@@ -384,19 +433,23 @@ impl PhpLspBackend {
                 .iter()
                 .find_map(|config| config.namespace_map.clone())
         };
-        *self.namespace_map.lock().await = first_namespace_map;
+        self.config_snapshot
+            .update(|config| config.namespace_map = first_namespace_map);
 
         let client = self.client.clone();
         let index = self.index.clone();
         let work_done_progress_supported = *self.work_done_progress_supported.lock().await;
         let include_paths = self.include_paths.lock().await.clone();
         let exclude_paths = self.exclude_paths.lock().await.clone();
+        let respect_gitignore = *self.respect_gitignore.lock().await;
+        let file_extensions = self.php_file_extensions.lock().await.clone();
         let php_version = *self.php_version.lock().await;
-        let index_vendor = *self.index_vendor.lock().await;
+        let vendor_indexing = *self.vendor_indexing.lock().await;
+        let index_vendor = vendor_indexing.is_enabled();
         let vendor_autoload_cache = self.vendor_autoload_cache.clone();
         let vendor_file_lru = self.vendor_file_lru.clone();
         let stub_extensions = self.stub_extensions.lock().await.clone();
-        let client_stubs_path = self.stubs_path.lock().await.clone();
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
         let cache_config = workspace_index_cache_config(
             added_configs.first().map(|config| config.root.as_path()),
             php_version,
@@ -408,8 +461,11 @@ impl PhpLspBackend {
         let indexing_options = WorkspaceIndexingOptions {
             include_paths,
             exclude_paths,
+            respect_gitignore,
+            file_extensions,
             cache_config,
             work_done_progress_supported,
+            progress_cancellations: self.indexing_progress_cancellations.clone(),
         };
         let indexing_run_state = self.indexing_run.clone();
         let indexing_token = self.start_indexing_run().await;
@@ -425,6 +481,7 @@ impl PhpLspBackend {
                     config.namespace_map.as_ref(),
                     &indexing_options,
                     &indexing_token,
+                    encoding,
                 )
                 .await
                 {
@@ -455,9 +512,24 @@ impl PhpLspBackend {
                         index.clone(),
                         &config.root,
                         &indexing_options.exclude_paths,
+                        &indexing_options.file_extensions,
                         php_version,
                         &vendor_autoload_cache,
                         &vendor_file_lru,
+                        encoding,
+                    )
+                    .await;
+                }
+                if vendor_indexing == VendorIndexing::Full {
+                    index_vendor_sources(
+                        index.clone(),
+                        &config.root,
+                        &indexing_options.exclude_paths,
+                        &indexing_options.file_extensions,
+                        php_version,
+                        &vendor_autoload_cache,
+                        &indexing_token,
+                        encoding,
                     )
                     .await;
                 }
@@ -534,18 +606,24 @@ impl PhpLspBackend {
             self.invalidate_request_fs_caches().await;
         }
 
+        let scaffold = self.new_file_scaffold.lock().await.enabled;
         for file in params.files {
             if let Ok(uri) = file.uri.parse::<Uri>() {
                 self.reindex_php_file(&uri).await;
+                if scaffold {
+                    self.apply_new_file_scaffold(&uri).await;
+                }
             }
         }
     }
 
     pub(crate) async fn lsp_will_rename_files(
         &self,
-        _params: RenameFilesParams,
+        params: RenameFilesParams,
     ) -> Result<Option<WorkspaceEdit>> {
-        Ok(None)
+        tracing::debug!("willRenameFiles: {} file(s)", params.files.len());
+
+        Ok(self.file_move_namespace_edits(&params.files).await)
     }
 
     pub(crate) async fn lsp_did_rename_files(&self, params: RenameFilesParams) {
@@ -609,6 +687,21 @@ pub(crate) fn path_is_excluded(path: &Path, root: &Path, exclude_paths: &[PathBu
             return false;
         }
 
+        if is_glob_path(exclude_path) {
+            // A matching directory excludes everything below it.
+            let candidate = if exclude_path.is_absolute() {
+                Some(absolute_path.as_path())
+            } else {
+                relative_path.as_deref()
+            };
+            return candidate.is_some_and(|candidate| {
+                candidate
+                    .ancestors()
+                    .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                    .any(|ancestor| glob_path_matches(exclude_path, ancestor))
+            });
+        }
+
         let absolute_exclude = resolve_config_path(root, exclude_path);
         if absolute_path == absolute_exclude || absolute_path.starts_with(&absolute_exclude) {
             return true;
@@ -647,13 +740,21 @@ pub(crate) fn workspace_index_directories(
 }
 
 /// Collect all .php files from the given directories.
+///
+/// Glob entries such as `tools/**` walk their literal base directory and keep
+/// only the files matching the pattern.
 pub(crate) fn collect_php_files(
     directories: &[PathBuf],
     root: &Path,
     exclude_paths: &[PathBuf],
+    extensions: &[String],
 ) -> Vec<PathBuf> {
     let mut files = Vec::new();
     for dir in directories {
+        if is_glob_path(dir) {
+            collect_php_files_matching_glob(dir, root, exclude_paths, extensions, &mut files);
+            continue;
+        }
         let abs_dir = if dir.is_absolute() {
             dir.to_path_buf()
         } else {
@@ -663,31 +764,183 @@ pub(crate) fn collect_php_files(
             continue;
         }
         if abs_dir.is_dir() {
-            collect_php_files_recursive(&abs_dir, root, exclude_paths, &mut files);
-        } else if abs_dir.extension().and_then(|e| e.to_str()) == Some("php") {
+            collect_php_files_recursive(&abs_dir, root, exclude_paths, extensions, &mut files);
+        } else if path_has_php_extension(&abs_dir, extensions) {
             push_unique_path(&mut files, abs_dir);
         }
     }
     files
 }
 
+fn collect_php_files_matching_glob(
+    pattern: &Path,
+    root: &Path,
+    exclude_paths: &[PathBuf],
+    extensions: &[String],
+    files: &mut Vec<PathBuf>,
+) {
+    let base: PathBuf = pattern
+        .components()
+        .take_while(|component| !is_glob_path(Path::new(component.as_os_str())))
+        .collect();
+    let base = resolve_config_path(root, &base);
+    if !base.is_dir() || path_is_excluded(&base, root, exclude_paths) {
+        return;
+    }
+
+    let mut candidates = Vec::new();
+    collect_php_files_recursive(&base, root, exclude_paths, extensions, &mut candidates);
+    for file in candidates {
+        let matches = if pattern.is_absolute() {
+            glob_path_matches(pattern, &file)
+        } else {
+            file.strip_prefix(root)
+                .is_ok_and(|relative| glob_path_matches(pattern, relative))
+        };
+        if matches {
+            push_unique_path(files, file);
+        }
+    }
+}
+
+/// Whether a configured include/exclude entry is a glob pattern rather than a
+/// literal path.
+pub(crate) fn is_glob_path(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Match `path` against a glob `pattern` segment by segment. `**` spans any
+/// number of segments, `*` and `?` stay within one segment, and `[...]`
+/// matches a character class.
+pub(crate) fn glob_path_matches(pattern: &Path, path: &Path) -> bool {
+    let pattern_segments: Vec<String> = path_segments(pattern);
+    let path_segments: Vec<String> = path_segments(path);
+    glob_segments_match(&pattern_segments, &path_segments)
+}
+
+fn path_segments(path: &Path) -> Vec<String> {
+    path.to_string_lossy()
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .map(str::to_string)
+        .collect()
+}
+
+fn glob_segments_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| glob_segments_match(rest, &path[skip..]))
+        }
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, path_rest)| {
+            glob_segment_matches(first.as_bytes(), segment.as_bytes())
+                && glob_segments_match(rest, path_rest)
+        }),
+    }
+}
+
+fn glob_segment_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => {
+            (0..=text.len()).any(|skip| glob_segment_matches(rest, &text[skip..]))
+        }
+        Some((b'?', rest)) => !text.is_empty() && glob_segment_matches(rest, &text[1..]),
+        Some((b'[', rest)) => {
+            let Some(close) = rest.iter().skip(1).position(|&byte| byte == b']') else {
+                return text.first() == Some(&b'[') && glob_segment_matches(rest, &text[1..]);
+            };
+            let class = &rest[..close + 1];
+            let Some((&ch, text_rest)) = text.split_first() else {
+                return false;
+            };
+            let (negated, class) = match class.split_first() {
+                Some((b'!' | b'^', class)) => (true, class),
+                _ => (false, class),
+            };
+            let mut matched = false;
+            let mut index = 0;
+            while index < class.len() {
+                if index + 2 < class.len() && class[index + 1] == b'-' {
+                    matched |= class[index] <= ch && ch <= class[index + 2];
+                    index += 3;
+                } else {
+                    matched |= class[index] == ch;
+                    index += 1;
+                }
+            }
+            matched != negated && glob_segment_matches(&rest[close + 2..], text_rest)
+        }
+        Some((&byte, rest)) => {
+            text.first() == Some(&byte) && glob_segment_matches(rest, &text[1..])
+        }
+    }
+}
+
+/// Translate the root `.gitignore` into exclude entries understood by
+/// [`path_is_excluded`]. Negated patterns are not supported and are skipped.
+pub(crate) fn gitignore_exclude_paths(root: &Path) -> Vec<PathBuf> {
+    let Ok(contents) = std::fs::read_to_string(root.join(".gitignore")) else {
+        return Vec::new();
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                return None;
+            }
+            let line = line.trim_end_matches('/');
+            let anchored = line.starts_with('/') || line.contains('/');
+            let line = line.trim_start_matches('/');
+            if line.is_empty() {
+                return None;
+            }
+            Some(if anchored {
+                PathBuf::from(line)
+            } else {
+                PathBuf::from(format!("**/{line}"))
+            })
+        })
+        .collect()
+}
+
+/// Configured exclude entries for one workspace root, extended with the root
+/// `.gitignore` when `respect_gitignore` is enabled.
+pub(crate) fn workspace_exclude_paths(
+    root: &Path,
+    exclude_paths: &[PathBuf],
+    respect_gitignore: bool,
+) -> Vec<PathBuf> {
+    let mut paths = exclude_paths.to_vec();
+    if respect_gitignore {
+        for path in gitignore_exclude_paths(root) {
+            push_unique_path(&mut paths, path);
+        }
+    }
+    paths
+}
+
 pub(in crate::server) async fn collect_php_files_blocking(
     directories: Vec<PathBuf>,
     root: PathBuf,
     exclude_paths: Vec<PathBuf>,
+    extensions: Vec<String>,
 ) -> std::result::Result<Vec<PathBuf>, String> {
     let path_label = root.display().to_string();
     run_file_io_blocking("workspace PHP file discovery", path_label, move || {
-        collect_php_files(&directories, &root, &exclude_paths)
+        collect_php_files(&directories, &root, &exclude_paths, &extensions)
     })
     .await
 }
 
-/// Recursively collect .php files from a directory.
+/// Recursively collect PHP source files from a directory.
 pub(in crate::server) fn collect_php_files_recursive(
     dir: &Path,
     root: &Path,
     exclude_paths: &[PathBuf],
+    extensions: &[String],
     files: &mut Vec<PathBuf>,
 ) {
     let entries = match std::fs::read_dir(dir) {
@@ -710,22 +963,41 @@ pub(in crate::server) fn collect_php_files_recursive(
             if name_str.starts_with('.') || name_str == "vendor" || name_str == "node_modules" {
                 continue;
             }
-            collect_php_files_recursive(&path, root, exclude_paths, files);
-        } else if path.extension().and_then(|e| e.to_str()) == Some("php") {
+            collect_php_files_recursive(&path, root, exclude_paths, extensions, files);
+        } else if path_has_php_extension(&path, extensions) {
             push_unique_path(files, path);
         }
     }
 }
 
-pub(in crate::server) fn uri_is_php_file(uri: &Uri) -> bool {
+/// Whether `path` ends in one of the configured PHP source extensions.
+pub(crate) fn path_has_php_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|extension| ext.eq_ignore_ascii_case(extension))
+        })
+}
+
+pub(in crate::server) fn uri_is_php_file(uri: &Uri, extensions: &[String]) -> bool {
     if let Some(path) = uri_to_path(uri.as_str()) {
-        return path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .is_some_and(|ext| ext.eq_ignore_ascii_case("php"));
+        return path_has_php_extension(&path, extensions);
     }
 
-    uri.as_str().to_ascii_lowercase().ends_with(".php")
+    let uri = uri.as_str().to_ascii_lowercase();
+    extensions
+        .iter()
+        .any(|extension| uri.ends_with(&format!(".{extension}")))
+}
+
+/// True for documents that only exist as editor buffers, such as `untitled:`
+/// or remote file-system URIs, so nothing on disk backs them once closed.
+pub(in crate::server) fn uri_is_buffer_only(uri: &str) -> bool {
+    uri_to_path(uri).is_none()
+        && !uri.starts_with(PHAR_URI_PREFIX)
+        && !uri.starts_with("phpstub://")
 }
 
 pub(in crate::server) fn push_unique_path(paths: &mut Vec<PathBuf>, path: PathBuf) {
@@ -872,7 +1144,12 @@ pub(in crate::server) fn sanitize_project_settings_for_command_trust(
         blocked.push("phpstan.command");
     }
 
-    if nested_bool(settings, "psalm", "enabled") == Some(true) {
+    if settings
+        .get("psalm")
+        .and_then(|psalm| psalm.get("enabled"))
+        .and_then(AnalyzerActivation::parse)
+        .is_some_and(|activation| activation != AnalyzerActivation::Off)
+    {
         remove_section_key(settings, "psalm", "enabled");
         blocked.push("psalm.enabled");
     }
@@ -880,6 +1157,25 @@ pub(in crate::server) fn sanitize_project_settings_for_command_trust(
         blocked.push("psalm.command");
     }
 
+    if nested_bool(settings, "phpcs", "enabled") == Some(true) {
+        remove_section_key(settings, "phpcs", "enabled");
+        blocked.push("phpcs.enabled");
+    }
+    if remove_section_key(settings, "phpcs", "command").is_some() {
+        blocked.push("phpcs.command");
+    }
+    if remove_section_key(settings, "phpcs", "fixCommand").is_some() {
+        blocked.push("phpcs.fixCommand");
+    }
+
+    if nested_bool(settings, "runtimeStubs", "enabled") == Some(true) {
+        remove_section_key(settings, "runtimeStubs", "enabled");
+        blocked.push("runtimeStubs.enabled");
+    }
+    if remove_section_key(settings, "runtimeStubs", "phpExecutable").is_some() {
+        blocked.push("runtimeStubs.phpExecutable");
+    }
+
     if blocked.is_empty() {
         return None;
     }
@@ -1261,14 +1557,23 @@ pub(in crate::server) fn find_composer_json(root: &Path) -> Option<PathBuf> {
     }
 }
 
-pub(in crate::server) fn read_php_source_lossy(file_path: &Path) -> std::io::Result<String> {
-    let bytes = std::fs::read(file_path)?;
-    Ok(String::from_utf8_lossy(&bytes).into_owned())
+/// Read a PHP file for indexing. BOMs are stripped and non-UTF-8 files are
+/// decoded as Latin-1 (see [`php_lsp_parser::source`]); binary files fail.
+pub(in crate::server) fn read_php_source_file(file_path: &Path) -> std::io::Result<String> {
+    let decoded = read_php_source(file_path)?;
+    if decoded.encoding == SourceEncoding::Latin1 {
+        tracing::debug!(
+            "{} is not valid UTF-8, decoded as Latin-1",
+            file_path.display()
+        );
+    }
+    Ok(decoded.text)
 }
 
 pub(in crate::server) fn parse_and_index_php_file(
     index: &WorkspaceIndex,
     file_path: &Path,
+    encoding: PositionEncoding,
 ) -> bool {
     let uri = match path_to_uri(file_path) {
         Ok(uri) => uri,
@@ -1277,8 +1582,12 @@ pub(in crate::server) fn parse_and_index_php_file(
             return false;
         }
     };
-    let Ok(source) = read_php_source_lossy(file_path) else {
-        return false;
+    let source = match read_php_source_file(file_path) {
+        Ok(source) => source,
+        Err(err) => {
+            tracing::warn!("Skipping {}: {}", file_path.display(), err);
+            return false;
+        }
     };
     let mut parser = FileParser::new();
     parser.parse_full(&source);
@@ -1287,13 +1596,14 @@ pub(in crate::server) fn parse_and_index_php_file(
     };
 
     let file_symbols = extract_file_symbols(tree, &source, &uri);
-    let references = collect_symbol_references_in_file(tree, &source, &file_symbols);
+    let references = collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
     index.update_file_with_references(&uri, file_symbols, references);
     true
 }
 
 pub(in crate::server) fn parse_workspace_file_for_index(
     file_path: PathBuf,
+    encoding: PositionEncoding,
 ) -> WorkspaceParseResult {
     let uri = match path_to_uri(&file_path) {
         Ok(uri) => uri,
@@ -1308,7 +1618,7 @@ pub(in crate::server) fn parse_workspace_file_for_index(
             };
         }
     };
-    let source = match read_php_source_lossy(&file_path) {
+    let source = match read_php_source_file(&file_path) {
         Ok(source) => source,
         Err(err) => {
             return WorkspaceParseResult {
@@ -1336,7 +1646,7 @@ pub(in crate::server) fn parse_workspace_file_for_index(
     };
 
     let file_symbols = extract_file_symbols(tree, &source, &uri);
-    let references = collect_symbol_references_in_file(tree, &source, &file_symbols);
+    let references = collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
     let symbol_count = file_symbols.symbols.len();
     WorkspaceParseResult {
         path: file_path,
@@ -1351,10 +1661,11 @@ pub(in crate::server) fn parse_workspace_file_for_index(
 pub(in crate::server) async fn parse_workspace_file_for_index_blocking(
     file_path: PathBuf,
     label: &'static str,
+    encoding: PositionEncoding,
 ) -> std::result::Result<WorkspaceParseResult, String> {
     let path_label = file_path.display().to_string();
     run_file_io_blocking(label, path_label, move || {
-        parse_workspace_file_for_index(file_path)
+        parse_workspace_file_for_index(file_path, encoding)
     })
     .await
 }
@@ -1363,10 +1674,11 @@ pub(in crate::server) async fn parse_and_index_php_file_blocking(
     index: Arc<WorkspaceIndex>,
     file_path: PathBuf,
     label: &'static str,
+    encoding: PositionEncoding,
 ) -> bool {
     let path_label = file_path.display().to_string();
     match run_file_io_blocking(label, path_label.clone(), move || {
-        parse_and_index_php_file(&index, &file_path)
+        parse_and_index_php_file(&index, &file_path, encoding)
     })
     .await
     {
@@ -1509,13 +1821,16 @@ pub(in crate::server) fn indexed_vendor_cache_sources(
     sources
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(in crate::server) async fn preload_vendor_entrypoints(
     index: Arc<WorkspaceIndex>,
     root: &Path,
     exclude_paths: &[PathBuf],
+    file_extensions: &[String],
     php_version: PhpVersion,
     vendor_autoload_cache: &Arc<Mutex<VendorAutoloadCache>>,
     vendor_file_lru: &Arc<Mutex<VendorFileLru>>,
+    encoding: PositionEncoding,
 ) -> usize {
     let vendor_dir = root.join("vendor");
     if !vendor_dir.is_dir() {
@@ -1530,6 +1845,7 @@ pub(in crate::server) async fn preload_vendor_entrypoints(
         autoload,
         root.to_path_buf(),
         exclude_paths.to_vec(),
+        file_extensions.to_vec(),
     )
     .await;
     if entrypoint_files.is_empty() {
@@ -1555,6 +1871,7 @@ pub(in crate::server) async fn preload_vendor_entrypoints(
                 index.clone(),
                 file_path.clone(),
                 "vendor preload PHP file index",
+                encoding,
             )
             .await
         {
@@ -1574,6 +1891,91 @@ pub(in crate::server) async fn preload_vendor_entrypoints(
     loaded
 }
 
+/// Index every autoloaded vendor source for `vendorIndexing: "full"`.
+///
+/// Files bypass the vendor LRU so they stay indexed for workspace symbols and
+/// references; files already loaded lazily or by the entrypoint preload are
+/// kept as they are.
+#[allow(clippy::too_many_arguments)]
+pub(in crate::server) async fn index_vendor_sources(
+    index: Arc<WorkspaceIndex>,
+    root: &Path,
+    exclude_paths: &[PathBuf],
+    file_extensions: &[String],
+    php_version: PhpVersion,
+    vendor_autoload_cache: &Arc<Mutex<VendorAutoloadCache>>,
+    cancellation: &OperationCancellationToken,
+    encoding: PositionEncoding,
+) -> usize {
+    let vendor_dir = root.join("vendor");
+    if !vendor_dir.is_dir() {
+        return 0;
+    }
+
+    let Some(autoload) = cached_vendor_autoload_map(vendor_autoload_cache, &vendor_dir).await
+    else {
+        return 0;
+    };
+    let source_files = match collect_php_files_blocking(
+        vendor_source_paths_from_map(&autoload),
+        root.to_path_buf(),
+        exclude_paths.to_vec(),
+        file_extensions.to_vec(),
+    )
+    .await
+    {
+        Ok(files) => files,
+        Err(e) => {
+            tracing::warn!(
+                "Vendor source discovery failed for {}: {}",
+                root.display(),
+                e
+            );
+            return 0;
+        }
+    };
+
+    let cache_config = vendor_index_cache_config(root, php_version, exclude_paths);
+    let mut loaded = 0;
+    for file_path in source_files {
+        if cancellation.is_cancelled() {
+            break;
+        }
+        if path_to_uri(&file_path).is_ok_and(|uri| index.file_symbols.contains_key(&uri)) {
+            continue;
+        }
+
+        let from_cache = load_cached_vendor_file_blocking(
+            index.clone(),
+            root.to_path_buf(),
+            file_path.clone(),
+            cache_config.clone(),
+        )
+        .await;
+        if from_cache
+            || parse_and_index_php_file_blocking(
+                index.clone(),
+                file_path,
+                "vendor full PHP file index",
+                encoding,
+            )
+            .await
+        {
+            loaded += 1;
+        }
+    }
+
+    if loaded > 0 {
+        save_vendor_index_cache_blocking(index, root.to_path_buf(), cache_config).await;
+        tracing::info!(
+            "Indexed {} vendor source file(s) for {}",
+            loaded,
+            root.display()
+        );
+    }
+    loaded
+}
+
 /// Background workspace indexing.
 ///
 /// Scans PHP files in the workspace and adds their symbols to the index.
@@ -1584,6 +1986,7 @@ pub(in crate::server) async fn index_workspace(
     namespace_map: Option<&NamespaceMap>,
     options: &WorkspaceIndexingOptions,
     cancellation: &OperationCancellationToken,
+    encoding: PositionEncoding,
 ) -> std::result::Result<(), String> {
     let root_label = root.display().to_string();
     let started_at = Instant::now();
@@ -1606,7 +2009,8 @@ pub(in crate::server) async fn index_workspace(
     .await;
 
     // Create progress token
-    let progress_token = ProgressToken::String(format!("php-lsp-indexing-{}", root.display()));
+    let progress_key = format!("php-lsp-indexing-{}", root.display());
+    let progress_token = ProgressToken::String(progress_key.clone());
 
     // Request progress support from client (with timeout to avoid hanging if client doesn't respond)
     let progress_supported = if options.work_done_progress_supported {
@@ -1621,11 +2025,19 @@ pub(in crate::server) async fn index_workspace(
         false
     };
 
-    // Start progress reporting (Bounded with percentage)
+    // Start progress reporting (Bounded with percentage). The client may cancel
+    // it, which stops this root only and keeps the files indexed so far.
+    let user_cancellation = OperationCancellationToken::new();
     let ongoing = if progress_supported {
+        options
+            .progress_cancellations
+            .lock()
+            .await
+            .insert(progress_key.clone(), user_cancellation.clone());
         let progress = client
             .progress(progress_token, "Indexing PHP workspace")
             .with_percentage(0)
+            .with_cancel_button()
             .with_message("Discovering files...");
         Some(progress.begin().await)
     } else {
@@ -1634,17 +2046,30 @@ pub(in crate::server) async fn index_workspace(
 
     // Collect PHP files
     let source_dirs = workspace_index_directories(root, namespace_map, &options.include_paths);
+    let exclude_paths =
+        workspace_exclude_paths(root, &options.exclude_paths, options.respect_gitignore);
     let php_files = collect_php_files_blocking(
         source_dirs,
         root.to_path_buf(),
-        options.exclude_paths.clone(),
+        exclude_paths.clone(),
+        options.file_extensions.clone(),
     )
     .await?;
-    if cancellation.is_cancelled() {
+    if cancellation.is_cancelled() || user_cancellation.is_cancelled() {
         tracing::debug!(
             "Workspace indexing cancelled after discovery: {}",
             root_label
         );
+        finish_cancelled_workspace_indexing(
+            client,
+            ongoing,
+            options,
+            &progress_key,
+            &root_label,
+            user_cancellation.is_cancelled(),
+            (0, php_files.len()),
+        )
+        .await;
         return Ok(());
     }
 
@@ -1658,7 +2083,7 @@ pub(in crate::server) async fn index_workspace(
                 root.join(file_path)
             };
             if abs.exists()
-                && !path_is_excluded(&abs, root, &options.exclude_paths)
+                && !path_is_excluded(&abs, root, &exclude_paths)
                 && !all_files.contains(&abs)
             {
                 all_files.push(abs);
@@ -1673,11 +2098,21 @@ pub(in crate::server) async fn index_workspace(
     let cache_path = cache::cache_file_path(root);
     let cache_report =
         cache::load_valid_cached_files(index, &cache_path, root, &all_files, &options.cache_config);
-    if cancellation.is_cancelled() {
+    if cancellation.is_cancelled() || user_cancellation.is_cancelled() {
         tracing::debug!(
             "Workspace indexing cancelled after cache load: {}",
             root_label
         );
+        finish_cancelled_workspace_indexing(
+            client,
+            ongoing,
+            options,
+            &progress_key,
+            &root_label,
+            user_cancellation.is_cancelled(),
+            (cache_report.loaded_files, total),
+        )
+        .await;
         return Ok(());
     }
     if let Some(reason) = cache_report.miss_reason.as_deref() {
@@ -1729,7 +2164,7 @@ pub(in crate::server) async fn index_workspace(
     .await;
 
     if let Some(ref p) = ongoing {
-        p.report_with_message(format!("Indexing {} files...", total), 0)
+        p.report_with_message(format!("Indexing {} files...", total), 0, None)
             .await;
     }
 
@@ -1740,13 +2175,13 @@ pub(in crate::server) async fn index_workspace(
         let Some(file_path) = pending_files.next() else {
             break;
         };
-        parse_tasks.spawn_blocking(move || parse_workspace_file_for_index(file_path));
+        parse_tasks.spawn_blocking(move || parse_workspace_file_for_index(file_path, encoding));
     }
 
     let mut done = loaded_from_cache;
     let mut parse_errors = 0usize;
     while let Some(result) = parse_tasks.join_next().await {
-        if cancellation.is_cancelled() {
+        if cancellation.is_cancelled() || user_cancellation.is_cancelled() {
             parse_tasks.abort_all();
             tracing::debug!(
                 "Workspace indexing cancelled after {}/{} files: {}",
@@ -1754,6 +2189,16 @@ pub(in crate::server) async fn index_workspace(
                 total,
                 root_label
             );
+            finish_cancelled_workspace_indexing(
+                client,
+                ongoing,
+                options,
+                &progress_key,
+                &root_label,
+                user_cancellation.is_cancelled(),
+                (done, total),
+            )
+            .await;
             return Ok(());
         }
 
@@ -1797,18 +2242,28 @@ pub(in crate::server) async fn index_workspace(
         done += 1;
 
         while parse_tasks.len() < parse_concurrency {
-            if cancellation.is_cancelled() {
+            if cancellation.is_cancelled() || user_cancellation.is_cancelled() {
                 parse_tasks.abort_all();
                 tracing::debug!(
                     "Workspace indexing cancelled before scheduling more parse tasks: {}",
                     root_label
                 );
+                finish_cancelled_workspace_indexing(
+                    client,
+                    ongoing,
+                    options,
+                    &progress_key,
+                    &root_label,
+                    user_cancellation.is_cancelled(),
+                    (done, total),
+                )
+                .await;
                 return Ok(());
             }
             let Some(file_path) = pending_files.next() else {
                 break;
             };
-            parse_tasks.spawn_blocking(move || parse_workspace_file_for_index(file_path));
+            parse_tasks.spawn_blocking(move || parse_workspace_file_for_index(file_path, encoding));
         }
 
         if let Some(ref p) = ongoing {
@@ -1818,8 +2273,12 @@ pub(in crate::server) async fn index_workspace(
                 } else {
                     100
                 };
-                p.report_with_message(format!("Indexed {}/{} files", done, total), percentage)
-                    .await;
+                p.report_with_message(
+                    format!("Indexed {}/{} files", done, total),
+                    percentage,
+                    None,
+                )
+                .await;
             }
         }
         if done % 10 == 0 || done == total {
@@ -1852,6 +2311,11 @@ pub(in crate::server) async fn index_workspace(
     }
 
     // End progress
+    options
+        .progress_cancellations
+        .lock()
+        .await
+        .remove(&progress_key);
     if let Some(p) = ongoing {
         p.finish_with_message(format!("Indexed {} files", total))
             .await;
@@ -1900,10 +2364,56 @@ pub(in crate::server) async fn index_workspace(
     Ok(())
 }
 
+/// End the progress of a workspace scan that stopped early. A client cancel
+/// leaves the partially filled index in place and reports the root as ready;
+/// a superseded run stays quiet because the next run reports its own status.
+async fn finish_cancelled_workspace_indexing(
+    client: &Client,
+    ongoing: Option<OngoingProgress<Bounded, Cancellable>>,
+    options: &WorkspaceIndexingOptions,
+    progress_key: &str,
+    root_label: &str,
+    user_cancelled: bool,
+    (done, total): (usize, usize),
+) {
+    options
+        .progress_cancellations
+        .lock()
+        .await
+        .remove(progress_key);
+    let message = format!("Indexing cancelled after {}/{} files", done, total);
+    if let Some(p) = ongoing {
+        p.finish_with_message(message.clone()).await;
+    }
+    if !user_cancelled {
+        return;
+    }
+
+    tracing::info!("{} for {}", message, root_label);
+    send_indexing_status(
+        client,
+        serde_json::json!({
+            "phase": "ready",
+            "root": root_label,
+            "message": format!("{}; using the partial index", message),
+            "indexedFiles": done,
+            "totalFiles": total,
+            "cancelled": true
+        }),
+    )
+    .await;
+}
+
 impl PhpLspBackend {
+    /// Whether `uri` names a PHP source under the configured file extensions.
+    pub(in crate::server) async fn uri_is_php_source(&self, uri: &Uri) -> bool {
+        uri_is_php_file(uri, &self.php_file_extensions.lock().await)
+    }
+
     pub(in crate::server) async fn path_is_excluded_by_config(&self, path: &Path) -> bool {
         let exclude_paths = self.exclude_paths.lock().await.clone();
-        if exclude_paths.is_empty() {
+        let respect_gitignore = *self.respect_gitignore.lock().await;
+        if exclude_paths.is_empty() && !respect_gitignore {
             return false;
         }
 
@@ -1916,21 +2426,26 @@ impl PhpLspBackend {
             .collect();
 
         if roots.is_empty() {
-            if let Some(root) = self.workspace_root.lock().await.clone() {
+            if let Some(root) = self.config_snapshot.load().workspace_root.clone() {
                 roots.push(root);
             }
         }
 
-        roots
-            .iter()
-            .any(|root| path_is_excluded(path, root, &exclude_paths))
+        roots.iter().any(|root| {
+            path_is_excluded(
+                path,
+                root,
+                &workspace_exclude_paths(root, &exclude_paths, respect_gitignore),
+            )
+        })
     }
 
     /// Reindex one changed PHP file from the open buffer when available,
     /// otherwise from disk.
     pub(in crate::server) async fn reindex_php_file(&self, uri: &Uri) {
+        let encoding = self.position_encoding();
         let uri_str = uri.as_str().to_string();
-        if !uri_is_php_file(uri) {
+        if !self.uri_is_php_source(uri).await {
             return;
         }
         let refresh_twig_contexts = !is_blade_template_uri(&uri_str);
@@ -1961,8 +2476,9 @@ impl PhpLspBackend {
             self.open_files.get(&uri_str).and_then(|parser| {
                 let tree = parser.tree()?;
                 let source = parser.source();
-                let file_symbols = extract_file_symbols(tree, &source, &uri_str);
-                let references = collect_symbol_references_in_file(tree, &source, &file_symbols);
+                let file_symbols = parser.file_symbols(&uri_str)?.into_owned();
+                let references =
+                    collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
                 Some((file_symbols, references))
             })
         };
@@ -1983,8 +2499,12 @@ impl PhpLspBackend {
             return;
         };
 
-        match parse_workspace_file_for_index_blocking(path.clone(), "watched PHP file reindex")
-            .await
+        match parse_workspace_file_for_index_blocking(
+            path.clone(),
+            "watched PHP file reindex",
+            encoding,
+        )
+        .await
         {
             Ok(parsed) => {
                 if let Some(file_symbols) = parsed.file_symbols {
@@ -2023,7 +2543,7 @@ impl PhpLspBackend {
 
     /// Remove one PHP file from all server-side caches/indexes.
     pub(in crate::server) async fn remove_php_file(&self, uri: &Uri) {
-        if !uri_is_php_file(uri) {
+        if !self.uri_is_php_source(uri).await {
             return;
         }
 
@@ -2034,7 +2554,7 @@ impl PhpLspBackend {
         self.template_documents.remove(&uri_str);
         self.document_versions.remove(&uri_str);
         self.cancel_debounced_diagnostics(&uri_str).await;
-        self.cancel_analyzer_run(&uri_str).await;
+        self.clear_analyzer_diagnostics(&uri_str).await;
         self.cancel_formatter_run(&uri_str).await;
         self.semantic_tokens_cache.lock().await.remove(&uri_str);
         self.client
@@ -2047,8 +2567,9 @@ impl PhpLspBackend {
     }
 
     pub(in crate::server) async fn rename_php_file(&self, old_uri: &Uri, new_uri: &Uri) {
-        let old_is_php = uri_is_php_file(old_uri);
-        let new_is_php = uri_is_php_file(new_uri);
+        let encoding = self.position_encoding();
+        let old_is_php = self.uri_is_php_source(old_uri).await;
+        let new_is_php = self.uri_is_php_source(new_uri).await;
 
         if !old_is_php && !new_is_php {
             return;
@@ -2068,8 +2589,8 @@ impl PhpLspBackend {
             .remove(&old_uri_str)
             .map(|(_, version)| version);
         self.cancel_debounced_diagnostics(&old_uri_str).await;
-        self.cancel_analyzer_run(&old_uri_str).await;
-        self.cancel_analyzer_run(new_uri.as_str()).await;
+        self.clear_analyzer_diagnostics(&old_uri_str).await;
+        self.clear_analyzer_diagnostics(new_uri.as_str()).await;
         self.cancel_formatter_run(&old_uri_str).await;
         self.cancel_formatter_run(new_uri.as_str()).await;
         if old_is_php {
@@ -2138,7 +2659,8 @@ impl PhpLspBackend {
             if let Some(tree) = parser.tree() {
                 let source = parser.source();
                 let file_symbols = extract_file_symbols(tree, &source, &new_uri_str);
-                let references = collect_symbol_references_in_file(tree, &source, &file_symbols);
+                let references =
+                    collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
                 self.index
                     .update_file_with_references(&new_uri_str, file_symbols, references);
             }
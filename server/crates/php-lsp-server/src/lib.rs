@@ -3,11 +3,15 @@
 //! Re-exports the server module for integration testing.
 
 pub mod analyze;
+pub mod baseline;
 pub mod config;
+pub mod export;
 pub mod fix;
 mod framework;
 mod server;
+pub mod tags;
 mod template;
 pub(crate) mod util;
 
 pub use server::PhpLspBackend;
+pub use util::uri::normalize_request_uris;
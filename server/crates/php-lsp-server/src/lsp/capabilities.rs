@@ -0,0 +1,182 @@
+//! `php-lsp/capabilities` custom request: a machine-readable report of the
+//! features, framework providers, diagnostic rules, stubs, and index backend
+//! that are active for the current session.
+
+use super::super::*;
+use serde_json::{json, Value};
+
+/// Custom request method name registered on the LSP service.
+pub const CAPABILITIES_METHOD: &str = "php-lsp/capabilities";
+
+/// Bumped when fields are removed or change meaning; new fields are additive.
+const CAPABILITIES_SCHEMA_VERSION: u64 = 1;
+
+/// Index backend label. The symbol index lives in memory and is persisted to
+/// the on-disk cache between sessions.
+const INDEX_BACKEND: &str = "in-memory";
+
+impl PhpLspBackend {
+    /// Handle `php-lsp/capabilities`.
+    pub async fn capabilities_report(&self) -> Result<Value> {
+        let workspace_root = self.config_snapshot.load().workspace_root.clone();
+        let diagnostics_mode = *self.diagnostics_mode.lock().await;
+        let severity = *self.diagnostic_severity.lock().await;
+        let baseline = self.diagnostic_baseline.lock().await.is_some();
+        let phpstan_enabled = self.phpstan_config.lock().await.enabled;
+        let psalm_activation = self.psalm_config.lock().await.enabled;
+        let phpcs_enabled = self.phpcs_config.lock().await.enabled;
+        let analyzer_code_actions = self.analyzer_code_actions.lock().await.enabled;
+        let composer_enabled = *self.composer_enabled.lock().await;
+        let vendor_indexing = *self.vendor_indexing.lock().await;
+        let formatting = self
+            .formatting_config
+            .lock()
+            .await
+            .clone()
+            .resolve_for_workspace_blocking(workspace_root.as_deref())
+            .await;
+
+        let psalm_active = match psalm_activation {
+            AnalyzerActivation::Off => false,
+            AnalyzerActivation::On => true,
+            AnalyzerActivation::Auto => workspace_root.as_deref().is_some_and(psalm_config_exists),
+        };
+        let formatting_provider = match formatting.command_template() {
+            Some(_) => formatting.provider.as_str(),
+            None if formatting.uses_builtin_formatter() => "built-in",
+            None => "none",
+        };
+
+        Ok(json!({
+            "schemaVersion": CAPABILITIES_SCHEMA_VERSION,
+            "server": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "features": {
+                "diagnostics": diagnostics_mode_label(diagnostics_mode),
+                "diagnosticsBaseline": baseline,
+                "formatting": formatting_provider,
+                "phpstan": phpstan_enabled,
+                "psalm": {
+                    "enabled": analyzer_activation_label(psalm_activation),
+                    "active": psalm_active,
+                },
+                "phpcs": phpcs_enabled,
+                "analyzerCodeActions": analyzer_code_actions,
+                "composer": composer_enabled,
+                "vendorIndex": vendor_indexing.is_enabled(),
+                "vendorIndexing": vendor_indexing_label(vendor_indexing),
+            },
+            "frameworkPlugins": crate::framework::default_framework_provider_registry()
+                .provider_ids(),
+            "rules": diagnostic_rules_report(severity),
+            "stubs": self.stubs_report(workspace_root).await,
+            "index": {
+                "backend": INDEX_BACKEND,
+                "files": self.index.file_symbols.len(),
+                "types": self.index.types.len(),
+                "functions": self.index.functions.len(),
+                "constants": self.index.constants.len(),
+            },
+        }))
+    }
+
+    async fn stubs_report(&self, workspace_root: Option<PathBuf>) -> Value {
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
+        let stub_extensions = self.stub_extensions.lock().await.clone();
+        let loaded_files = self
+            .index
+            .file_symbols
+            .iter()
+            .filter(|entry| entry.key().starts_with("phpstub://"))
+            .count();
+
+        let root = workspace_root.or_else(|| std::env::current_dir().ok());
+        let resolved = match root {
+            Some(root) => run_file_io_blocking(
+                "stubs capability report",
+                root.display().to_string(),
+                move || {
+                    let stubs_path = usable_stubs_path(&root, client_stubs_path)?;
+                    let extensions =
+                        effective_stub_extensions_for_path(&stubs_path, stub_extensions.as_deref());
+                    let fingerprint =
+                        stubs_cache_hash_for_path_with_extensions(&stubs_path, &extensions);
+                    Some((stubs_path, extensions, fingerprint))
+                },
+            )
+            .await
+            .ok()
+            .flatten(),
+            None => None,
+        };
+
+        match resolved {
+            Some((stubs_path, extensions, fingerprint)) => json!({
+                "path": stubs_path.display().to_string(),
+                "extensions": extensions,
+                "version": format!("{:016x}", fingerprint),
+                "loadedFiles": loaded_files,
+            }),
+            None => json!({
+                "path": Value::Null,
+                "extensions": Value::Array(Vec::new()),
+                "version": Value::Null,
+                "loadedFiles": loaded_files,
+            }),
+        }
+    }
+}
+
+fn diagnostics_mode_label(mode: DiagnosticsMode) -> &'static str {
+    match mode {
+        DiagnosticsMode::Off => "off",
+        DiagnosticsMode::SyntaxOnly => "syntax-only",
+        DiagnosticsMode::BasicSemantic => "basic-semantic",
+    }
+}
+
+fn analyzer_activation_label(activation: AnalyzerActivation) -> &'static str {
+    match activation {
+        AnalyzerActivation::Off => "off",
+        AnalyzerActivation::On => "on",
+        AnalyzerActivation::Auto => "auto",
+    }
+}
+
+fn vendor_indexing_label(vendor_indexing: VendorIndexing) -> &'static str {
+    match vendor_indexing {
+        VendorIndexing::Off => "off",
+        VendorIndexing::Lazy => "lazy",
+        VendorIndexing::Full => "full",
+    }
+}
+
+/// Category and per-rule severities keyed by published diagnostic code.
+/// Rules without an override report their category's severity.
+fn diagnostic_rules_report(config: DiagnosticSeverityConfig) -> Value {
+    let categories: serde_json::Map<String, Value> = DiagnosticCategory::ALL
+        .iter()
+        .map(|category| {
+            (
+                category.code().to_string(),
+                json!(config.level(*category).label()),
+            )
+        })
+        .collect();
+    let rules: serde_json::Map<String, Value> = DiagnosticRule::ALL
+        .iter()
+        .map(|rule| {
+            (
+                rule.code().to_string(),
+                json!(config.rule_level(*rule).label()),
+            )
+        })
+        .collect();
+
+    json!({
+        "categories": categories,
+        "rules": rules,
+    })
+}
@@ -44,6 +44,32 @@ pub(crate) fn short_name(fqn: &str) -> &str {
         .unwrap_or(fqn)
 }
 
+/// Indexed, non-builtin symbols named `unresolved_short` that an unknown class
+/// or function could import, sorted by FQN and capped at five. Looked up by
+/// short name, so the cost does not grow with the size of the index.
+pub(crate) fn import_candidates(
+    index: &WorkspaceIndex,
+    import_kind: ImportKind,
+    unresolved_short: &str,
+) -> Vec<std::sync::Arc<php_lsp_types::SymbolInfo>> {
+    let named = match import_kind {
+        ImportKind::Class => index.types_named(unresolved_short),
+        ImportKind::Function => index.functions_named(unresolved_short),
+        ImportKind::Constant => return Vec::new(),
+    };
+    let mut candidates: Vec<std::sync::Arc<php_lsp_types::SymbolInfo>> = named
+        .into_iter()
+        .filter(|sym| {
+            !sym.modifiers.is_builtin
+                && (sym.name == unresolved_short || short_name(&sym.fqn) == unresolved_short)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.fqn.cmp(&b.fqn));
+    candidates.dedup_by(|a, b| a.fqn == b.fqn);
+    candidates.truncate(5);
+    candidates
+}
+
 pub(crate) fn use_kind_for_ref_kind(ref_kind: RefKind) -> Option<php_lsp_types::UseKind> {
     match ref_kind {
         RefKind::ClassName | RefKind::Constructor => Some(php_lsp_types::UseKind::Class),
@@ -84,6 +110,10 @@ pub(crate) fn is_builtin_type_name(name: &str) -> bool {
             | "bool"
             | "boolean"
             | "array"
+            | "list"
+            | "non-empty-array"
+            | "non-empty-list"
+            | "array-key"
             | "object"
             | "null"
             | "void"
@@ -266,15 +296,13 @@ pub(crate) fn find_use_insert_line(source: &str, file_symbols: &php_lsp_types::F
         }
     }
 
-    if source
+    // Insert after the opening tag, which may follow a `#!` shebang line.
+    source
         .lines()
-        .next()
-        .is_some_and(|line| line.trim() == "<?php")
-    {
-        1
-    } else {
-        0
-    }
+        .enumerate()
+        .find(|(idx, line)| !(*idx == 0 && line.starts_with("#!")))
+        .filter(|(_, line)| line.trim_start_matches('\u{feff}').trim() == "<?php")
+        .map_or(0, |(idx, _)| idx as u32 + 1)
 }
 
 pub(crate) fn build_use_statement(
@@ -387,6 +415,10 @@ fn reference_matches_import(
     reference: &php_lsp_types::SymbolReference,
     import: &OrganizableImport,
 ) -> bool {
+    // String literals are always fully qualified, so they never use an import.
+    if reference.is_string_literal {
+        return false;
+    }
     let target_fqn = reference.target_fqn.trim_start_matches('\\');
     let import_fqn = import.fqn.trim_start_matches('\\');
 
@@ -583,13 +615,14 @@ pub(crate) fn build_organize_imports_edit(
     source: &str,
     tree: &tree_sitter::Tree,
     file_symbols: &php_lsp_types::FileSymbols,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     if file_symbols.use_statements.is_empty() {
         return None;
     }
 
     let (start_line, end_line) = find_organizable_use_block(source, file_symbols)?;
-    let references = collect_symbol_references_in_file(tree, source, file_symbols);
+    let references = collect_symbol_references_in_file(tree, source, file_symbols, encoding);
 
     let mut imports: Vec<OrganizableImport> = file_symbols
         .use_statements
@@ -638,7 +671,7 @@ pub(crate) fn build_organize_imports_edit(
         start: Position::new(start_line, 0),
         end: Position::new(end_line, 0),
     };
-    if text_at_lsp_range(source, range)
+    if text_at_lsp_range(source, range, encoding)
         .map(|old_text| old_text == new_text)
         .unwrap_or(false)
     {
@@ -654,12 +687,16 @@ pub(crate) fn build_organize_imports_edit(
     })
 }
 
-pub(crate) fn lsp_range_to_byte_range(source: &str, range: Range) -> (u32, u32, u32, u32) {
+pub(crate) fn lsp_range_to_byte_range(
+    source: &str,
+    range: Range,
+    encoding: PositionEncoding,
+) -> (u32, u32, u32, u32) {
     (
         range.start.line,
-        utf16_col_to_byte(source, range.start.line, range.start.character),
+        lsp_col_to_byte(source, range.start.line, range.start.character, encoding),
         range.end.line,
-        utf16_col_to_byte(source, range.end.line, range.end.character),
+        lsp_col_to_byte(source, range.end.line, range.end.character, encoding),
     )
 }
 
@@ -837,6 +874,7 @@ pub(crate) enum CodeActionDataKind {
     ExtractVariable,
     ExtractConstant,
     InlineVariable,
+    FixWithPhpcbf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -886,6 +924,7 @@ pub(crate) enum CodeActionDataExtra {
     InlineVariable {
         variable_name: String,
     },
+    FixWithPhpcbf,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -915,11 +954,12 @@ pub(crate) fn add_return_type_edit(
     source: &str,
     hint: &str,
     insert_position: CodeActionInsertPosition,
+    encoding: PositionEncoding,
 ) -> WorkspaceEdit {
-    let utf16_index = Utf16LineIndex::new(source);
+    let line_index = LspLineIndex::new(source, encoding);
     let position = Position::new(
         insert_position.line,
-        utf16_index.byte_col_to_utf16(insert_position.line, insert_position.byte_character),
+        line_index.byte_col_to_lsp(insert_position.line, insert_position.byte_character),
     );
 
     let mut changes = HashMap::new();
@@ -958,7 +998,7 @@ pub(crate) fn build_implement_missing_methods_action(
         range: request_range,
         document_version,
         extra: CodeActionDataExtra::ImplementMissingMethods {
-            class_fqn: class_sym.fqn.clone(),
+            class_fqn: class_sym.fqn.to_string(),
         },
     })
     .ok()?;
@@ -981,6 +1021,56 @@ pub(crate) fn build_implement_missing_methods_action(
     }))
 }
 
+/// Code action kind for whole-file phpcbf fixes, usable from
+/// `editor.codeActionsOnSave`.
+pub(crate) const SOURCE_FIX_ALL_PHPCBF: &str = "source.fixAll.phpcbf";
+
+pub(crate) fn is_fixable_phpcs_diagnostic(diagnostic: &Diagnostic) -> bool {
+    diagnostic.source.as_deref() == Some("phpcs")
+        && diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("fixable"))
+            .and_then(|fixable| fixable.as_bool())
+            .unwrap_or(false)
+}
+
+/// phpcbf action whose whole-document edit is computed on resolve, since it
+/// runs an external process.
+pub(crate) fn build_fix_with_phpcbf_action(
+    uri: Uri,
+    kind: CodeActionKind,
+    diagnostics: Option<Vec<Diagnostic>>,
+    request_range: Range,
+    document_version: Option<i32>,
+) -> Option<CodeActionOrCommand> {
+    let data = serde_json::to_value(CodeActionData {
+        action_kind: CodeActionDataKind::FixWithPhpcbf,
+        uri: uri.as_str().to_string(),
+        range: request_range,
+        document_version,
+        extra: CodeActionDataExtra::FixWithPhpcbf,
+    })
+    .ok()?;
+
+    let title = if kind == CodeActionKind::QUICKFIX {
+        "Fix with phpcbf"
+    } else {
+        "Fix all with phpcbf"
+    };
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.to_string(),
+        kind: Some(kind),
+        diagnostics,
+        edit: None,
+        command: None,
+        is_preferred: Some(false),
+        disabled: None,
+        data: Some(data),
+    }))
+}
+
 pub(crate) fn build_generate_constructor_action(
     uri: Uri,
     source: &str,
@@ -1001,7 +1091,7 @@ pub(crate) fn build_generate_constructor_action(
         range: request_range,
         document_version,
         extra: CodeActionDataExtra::GenerateConstructor {
-            class_fqn: class_sym.fqn.clone(),
+            class_fqn: class_sym.fqn.to_string(),
         },
     })
     .ok()?;
@@ -1036,7 +1126,7 @@ pub(crate) fn build_generate_accessor_action(
         range: request_range,
         document_version,
         extra: CodeActionDataExtra::GenerateAccessor {
-            property_fqn: property.fqn.clone(),
+            property_fqn: property.fqn.to_string(),
             accessor_kind,
             method_name: method_name.clone(),
         },
@@ -1178,7 +1268,7 @@ pub(crate) fn collect_method_contract_visibilities(
 
     let Some(type_sym) = index
         .types
-        .get(&normalized_type)
+        .get(normalized_type.as_str())
         .map(|entry| entry.value().clone())
     else {
         return;
@@ -1306,7 +1396,7 @@ pub(crate) fn build_change_visibility_actions(
             range: request_range,
             document_version,
             extra: CodeActionDataExtra::ChangeVisibility {
-                symbol_fqn: symbol.fqn.clone(),
+                symbol_fqn: symbol.fqn.to_string(),
                 target_visibility,
             },
         })
@@ -1386,7 +1476,7 @@ pub(crate) fn collect_concrete_methods_from_type(
 
     let Some(type_sym) = index
         .types
-        .get(&normalized_type)
+        .get(normalized_type.as_str())
         .map(|entry| entry.value().clone())
     else {
         return;
@@ -1420,7 +1510,7 @@ pub(crate) fn collect_required_methods_from_type(
 
     let Some(type_sym) = index
         .types
-        .get(&normalized_type)
+        .get(normalized_type.as_str())
         .map(|entry| entry.value().clone())
     else {
         return;
@@ -1785,13 +1875,34 @@ pub(crate) fn render_missing_method_stub(
         .unwrap_or(php_lsp_types::Signature {
             params: Vec::new(),
             return_type: None,
+            throws: Vec::new(),
         });
+    let declaring_fqn = method.parent_fqn.as_deref();
     let params = signature
         .params
         .iter()
-        .map(|param| render_method_param(param, php_version))
+        .map(|param| {
+            let mut param = param.clone();
+            param.type_info = param.type_info.as_ref().map(|type_info| {
+                inherited_signature_type(
+                    type_info,
+                    declaring_fqn,
+                    php_version,
+                    TypeHintPosition::Parameter,
+                )
+            });
+            render_method_param(&param, php_version)
+        })
         .collect::<Vec<_>>()
         .join(", ");
+    let return_type = signature.return_type.as_ref().map(|type_info| {
+        inherited_signature_type(
+            type_info,
+            declaring_fqn,
+            php_version,
+            TypeHintPosition::Return,
+        )
+    });
 
     let mut text = String::new();
     if let Some(metadata) = metadata {
@@ -1817,7 +1928,7 @@ pub(crate) fn render_missing_method_stub(
     text.push('(');
     text.push_str(&params);
     text.push(')');
-    if let Some(return_type) = signature.return_type.as_ref().and_then(|return_type| {
+    if let Some(return_type) = return_type.as_ref().and_then(|return_type| {
         native_type_hint_text(return_type, php_version, TypeHintPosition::Return)
     }) {
         text.push_str(": ");
@@ -1833,6 +1944,43 @@ pub(crate) fn render_missing_method_stub(
     text
 }
 
+/// Translate `self` in an inherited method signature for the implementing class.
+///
+/// `self` in the child would name the child, so parameters spell out the
+/// declaring type to keep the contract's contravariance. Returns become
+/// `static` on PHP 8.0+, which stays covariant with both `self` and `static`
+/// contracts; older targets keep `self`.
+pub(crate) fn inherited_signature_type(
+    type_info: &php_lsp_types::TypeInfo,
+    declaring_fqn: Option<&str>,
+    php_version: PhpVersion,
+    position: TypeHintPosition,
+) -> php_lsp_types::TypeInfo {
+    use php_lsp_types::TypeInfo;
+
+    let translate = |type_info: &TypeInfo| {
+        inherited_signature_type(type_info, declaring_fqn, php_version, position)
+    };
+    match type_info {
+        TypeInfo::Self_ => match (position, declaring_fqn) {
+            (TypeHintPosition::Return, _) if php_version.at_least(8, 0) => TypeInfo::Static_,
+            (TypeHintPosition::Parameter, Some(owner)) => {
+                TypeInfo::Simple(format!("\\{}", owner.trim_start_matches('\\')))
+            }
+            _ => TypeInfo::Self_,
+        },
+        TypeInfo::Simple(name) if name.eq_ignore_ascii_case("self") => translate(&TypeInfo::Self_),
+        TypeInfo::Nullable(inner) => TypeInfo::Nullable(Box::new(translate(inner))),
+        TypeInfo::Union(types) => TypeInfo::Union(types.iter().map(translate).collect()),
+        // `static` is not allowed inside intersections, so only parameters
+        // are rewritten there.
+        TypeInfo::Intersection(types) if position == TypeHintPosition::Parameter => {
+            TypeInfo::Intersection(types.iter().map(translate).collect())
+        }
+        _ => type_info.clone(),
+    }
+}
+
 pub(crate) fn line_start_offsets(source: &str) -> Vec<usize> {
     let mut offsets = vec![0];
     for (idx, byte) in source.bytes().enumerate() {
@@ -1924,12 +2072,13 @@ pub(crate) struct ClassMethodInsertion {
 pub(crate) fn class_method_insertion(
     source: &str,
     class_sym: &php_lsp_types::SymbolInfo,
+    encoding: PositionEncoding,
 ) -> Option<ClassMethodInsertion> {
     let (closing_line, closing_col) = class_closing_brace_position(source, class_sym)?;
-    let utf16_index = Utf16LineIndex::new(source);
+    let line_index = LspLineIndex::new(source, encoding);
     let position = Position::new(
         closing_line,
-        utf16_index.byte_col_to_utf16(closing_line, closing_col),
+        line_index.byte_col_to_lsp(closing_line, closing_col),
     );
     let close_line = line_text(source, closing_line);
     let close_indent = leading_ascii_whitespace(line_prefix_by_byte_col(close_line, closing_col));
@@ -1990,18 +2139,19 @@ pub(crate) fn implement_missing_methods_edit(
     missing_methods: &[Arc<php_lsp_types::SymbolInfo>],
     metadata_by_fqn: &HashMap<String, MethodContractMetadata>,
     php_version: PhpVersion,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     if missing_methods.is_empty() {
         return Some(empty_workspace_edit());
     }
 
-    let insertion = class_method_insertion(source, class_sym)?;
+    let insertion = class_method_insertion(source, class_sym, encoding)?;
     let rendered_methods = missing_methods
         .iter()
         .map(|method| {
             render_missing_method_stub(
                 method,
-                metadata_by_fqn.get(&method.fqn),
+                metadata_by_fqn.get(method.fqn.as_str()),
                 &insertion.method_indent,
                 &insertion.body_indent,
                 php_version,
@@ -2085,7 +2235,6 @@ pub(crate) struct PropertyDocType {
 
 pub(crate) fn property_doc_type(property: &php_lsp_types::SymbolInfo) -> Option<PropertyDocType> {
     let doc_comment = property.doc_comment.as_deref()?;
-    let parsed = parse_phpdoc(doc_comment);
 
     for tag in ["@var", "@phpstan-var", "@psalm-var"] {
         for line in phpdoc_content_lines(doc_comment) {
@@ -2114,7 +2263,7 @@ pub(crate) fn property_doc_type(property: &php_lsp_types::SymbolInfo) -> Option<
             }
 
             return Some(PropertyDocType {
-                type_info: (tag == "@var").then(|| parsed.var_type.clone()).flatten(),
+                type_info: parse_phpdoc(&format!("/** @var {type_text} */")).var_type,
                 type_text: type_text.to_string(),
                 description: (!description.is_empty()).then(|| description.to_string()),
             });
@@ -2599,6 +2748,7 @@ pub(crate) fn generate_constructor_edit(
     file_symbols: &php_lsp_types::FileSymbols,
     class_sym: &php_lsp_types::SymbolInfo,
     php_version: PhpVersion,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     if direct_method_name_exists(file_symbols, &class_sym.fqn, "__construct") {
         return Some(empty_workspace_edit());
@@ -2608,7 +2758,7 @@ pub(crate) fn generate_constructor_edit(
         return Some(empty_workspace_edit());
     }
 
-    let insertion = class_method_insertion(source, class_sym)?;
+    let insertion = class_method_insertion(source, class_sym, encoding)?;
     let constructor = render_constructor_method(
         &properties,
         &insertion.method_indent,
@@ -2622,6 +2772,7 @@ pub(crate) fn generate_constructor_edit(
     ))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn generate_accessor_edit(
     uri: Uri,
     source: &str,
@@ -2630,6 +2781,7 @@ pub(crate) fn generate_accessor_edit(
     accessor_kind: AccessorKind,
     method_name: &str,
     php_version: PhpVersion,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     if accessor_kind == AccessorKind::Setter && property.modifiers.is_readonly {
         return Some(empty_workspace_edit());
@@ -2644,7 +2796,7 @@ pub(crate) fn generate_accessor_edit(
         .symbols
         .iter()
         .find(|sym| sym.fqn == class_fqn && sym.kind == php_lsp_types::PhpSymbolKind::Class)?;
-    let insertion = class_method_insertion(source, class_sym)?;
+    let insertion = class_method_insertion(source, class_sym, encoding)?;
     let accessor = render_accessor_method(
         property,
         accessor_kind,
@@ -2661,19 +2813,21 @@ pub(crate) fn generate_accessor_edit(
     ))
 }
 
-pub(crate) fn lsp_range_for_byte_offsets(source: &str, start: usize, end: usize) -> Range {
+pub(crate) fn lsp_range_for_byte_offsets(
+    source: &str,
+    start: usize,
+    end: usize,
+    encoding: PositionEncoding,
+) -> Range {
     let (start_line, start_byte_col) = line_col_for_byte_offset(source, start);
     let (end_line, end_byte_col) = line_col_for_byte_offset(source, end);
-    let utf16_index = Utf16LineIndex::new(source);
+    let line_index = LspLineIndex::new(source, encoding);
     Range {
         start: Position::new(
             start_line,
-            utf16_index.byte_col_to_utf16(start_line, start_byte_col),
-        ),
-        end: Position::new(
-            end_line,
-            utf16_index.byte_col_to_utf16(end_line, end_byte_col),
+            line_index.byte_col_to_lsp(start_line, start_byte_col),
         ),
+        end: Position::new(end_line, line_index.byte_col_to_lsp(end_line, end_byte_col)),
     }
 }
 
@@ -2716,6 +2870,7 @@ pub(crate) fn change_visibility_edit(
     source: &str,
     symbol: &php_lsp_types::SymbolInfo,
     target_visibility: php_lsp_types::Visibility,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     if !visibility_change_is_safe(index, file_symbols, symbol, target_visibility) {
         return Some(empty_workspace_edit());
@@ -2741,7 +2896,7 @@ pub(crate) fn change_visibility_edit(
     changes.insert(
         uri,
         vec![TextEdit {
-            range: lsp_range_for_byte_offsets(source, start, end),
+            range: lsp_range_for_byte_offsets(source, start, end, encoding),
             new_text,
         }],
     );
@@ -3220,7 +3375,7 @@ pub(crate) fn build_promote_constructor_parameter_action(
         range: request_range,
         document_version,
         extra: CodeActionDataExtra::PromoteConstructorParameter {
-            property_fqn: property.fqn.clone(),
+            property_fqn: property.fqn.to_string(),
         },
     })
     .ok()?;
@@ -3242,11 +3397,17 @@ pub(crate) fn promote_constructor_parameter_edit(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     property: &php_lsp_types::SymbolInfo,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     let plan = promote_constructor_parameter_plan(source, file_symbols, property)?;
     let mut edits = vec![
         TextEdit {
-            range: lsp_range_for_byte_offsets(source, plan.param_replace.0, plan.param_replace.1),
+            range: lsp_range_for_byte_offsets(
+                source,
+                plan.param_replace.0,
+                plan.param_replace.1,
+                encoding,
+            ),
             new_text: plan.param_replace.2,
         },
         TextEdit {
@@ -3254,6 +3415,7 @@ pub(crate) fn promote_constructor_parameter_edit(
                 source,
                 plan.assignment_delete.0,
                 plan.assignment_delete.1,
+                encoding,
             ),
             new_text: String::new(),
         },
@@ -3262,6 +3424,7 @@ pub(crate) fn promote_constructor_parameter_edit(
                 source,
                 plan.property_delete.0,
                 plan.property_delete.1,
+                encoding,
             ),
             new_text: String::new(),
         },
@@ -3554,8 +3717,9 @@ pub(crate) fn build_extract_variable_action(
     source: &str,
     request_range: Range,
     document_version: Option<i32>,
+    encoding: PositionEncoding,
 ) -> Option<CodeActionOrCommand> {
-    let range = lsp_range_to_byte_range(source, request_range);
+    let range = lsp_range_to_byte_range(source, request_range, encoding);
     let plan = extract_variable_plan(tree, source, range, None)?;
     let data = serde_json::to_value(CodeActionData {
         action_kind: CodeActionDataKind::ExtractVariable,
@@ -3601,6 +3765,7 @@ pub(crate) fn extract_variable_edit(
     source: &str,
     range: (u32, u32, u32, u32),
     variable_name: &str,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     let plan = extract_variable_plan(tree, source, range, Some(variable_name))?;
     Some(workspace_edit_from_text_edits(
@@ -3611,6 +3776,7 @@ pub(crate) fn extract_variable_edit(
                     source,
                     plan.assignment_insert,
                     plan.assignment_insert,
+                    encoding,
                 ),
                 new_text: plan.assignment_text,
             },
@@ -3619,6 +3785,7 @@ pub(crate) fn extract_variable_edit(
                     source,
                     plan.expression_start,
                     plan.expression_end,
+                    encoding,
                 ),
                 new_text: format!("${}", plan.variable_name),
             },
@@ -3706,6 +3873,7 @@ pub(crate) struct ClassConstantInsertion {
 pub(crate) fn class_constant_insertion(
     source: &str,
     class_sym: &php_lsp_types::SymbolInfo,
+    encoding: PositionEncoding,
 ) -> Option<ClassConstantInsertion> {
     let start = byte_offset_for_line_col(source, class_sym.range.0, class_sym.range.1)?;
     let end = byte_offset_for_line_col(source, class_sym.range.2, class_sym.range.3)?;
@@ -3719,8 +3887,8 @@ pub(crate) fn class_constant_insertion(
         open_brace + 1
     };
     let (line, byte_col) = line_col_for_byte_offset(source, insert_byte);
-    let utf16_index = Utf16LineIndex::new(source);
-    let position = Position::new(line, utf16_index.byte_col_to_utf16(line, byte_col));
+    let line_index = LspLineIndex::new(source, encoding);
+    let position = Position::new(line, line_index.byte_col_to_lsp(line, byte_col));
 
     let open_line = line_text(source, line_col_for_byte_offset(source, open_brace).0);
     let open_col = line_col_for_byte_offset(source, open_brace).1;
@@ -3761,6 +3929,7 @@ pub(crate) fn extract_constant_plan(
     file_symbols: &php_lsp_types::FileSymbols,
     range: (u32, u32, u32, u32),
     constant_name: Option<&str>,
+    encoding: PositionEncoding,
 ) -> Option<ExtractConstantPlan> {
     let literal = selected_named_node_exact(tree, source, range)?;
     if !is_extract_constant_literal_node(source, literal) {
@@ -3780,7 +3949,7 @@ pub(crate) fn extract_constant_plan(
         return None;
     }
 
-    let insertion = class_constant_insertion(source, class_sym)?;
+    let insertion = class_constant_insertion(source, class_sym, encoding)?;
     let mut insert_text = String::new();
     if insertion.needs_leading_newline {
         insert_text.push('\n');
@@ -3811,9 +3980,10 @@ pub(crate) fn build_extract_constant_action(
     file_symbols: &php_lsp_types::FileSymbols,
     request_range: Range,
     document_version: Option<i32>,
+    encoding: PositionEncoding,
 ) -> Option<CodeActionOrCommand> {
-    let range = lsp_range_to_byte_range(source, request_range);
-    let plan = extract_constant_plan(tree, source, file_symbols, range, None)?;
+    let range = lsp_range_to_byte_range(source, request_range, encoding);
+    let plan = extract_constant_plan(tree, source, file_symbols, range, None, encoding)?;
     let data = serde_json::to_value(CodeActionData {
         action_kind: CodeActionDataKind::ExtractConstant,
         uri: uri.as_str().to_string(),
@@ -3844,8 +4014,16 @@ pub(crate) fn extract_constant_edit(
     file_symbols: &php_lsp_types::FileSymbols,
     range: (u32, u32, u32, u32),
     constant_name: &str,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
-    let plan = extract_constant_plan(tree, source, file_symbols, range, Some(constant_name))?;
+    let plan = extract_constant_plan(
+        tree,
+        source,
+        file_symbols,
+        range,
+        Some(constant_name),
+        encoding,
+    )?;
     Some(workspace_edit_from_text_edits(
         uri,
         vec![
@@ -3861,6 +4039,7 @@ pub(crate) fn extract_constant_edit(
                     source,
                     plan.expression_start,
                     plan.expression_end,
+                    encoding,
                 ),
                 new_text: format!("self::{}", plan.constant_name),
             },
@@ -4120,8 +4299,9 @@ pub(crate) fn build_inline_variable_action(
     source: &str,
     request_range: Range,
     document_version: Option<i32>,
+    encoding: PositionEncoding,
 ) -> Option<CodeActionOrCommand> {
-    let range = lsp_range_to_byte_range(source, request_range);
+    let range = lsp_range_to_byte_range(source, request_range, encoding);
     let plan = inline_variable_plan(tree, source, range, None)?;
     let data = serde_json::to_value(CodeActionData {
         action_kind: CodeActionDataKind::InlineVariable,
@@ -4152,13 +4332,14 @@ pub(crate) fn inline_variable_edit(
     source: &str,
     range: (u32, u32, u32, u32),
     variable_name: &str,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     let plan = inline_variable_plan(tree, source, range, Some(variable_name))?;
     let mut edits = plan
         .usage_replacements
         .into_iter()
         .map(|(start, end, replacement)| TextEdit {
-            range: lsp_range_for_byte_offsets(source, start, end),
+            range: lsp_range_for_byte_offsets(source, start, end, encoding),
             new_text: replacement,
         })
         .collect::<Vec<_>>();
@@ -4167,6 +4348,7 @@ pub(crate) fn inline_variable_edit(
             source,
             plan.assignment_delete.0,
             plan.assignment_delete.1,
+            encoding,
         ),
         new_text: String::new(),
     });
@@ -5022,7 +5204,7 @@ pub(crate) fn build_update_phpdoc_from_signature_action(
         range: request_range,
         document_version,
         extra: CodeActionDataExtra::UpdatePhpDoc {
-            symbol_fqn: symbol.fqn.clone(),
+            symbol_fqn: symbol.fqn.to_string(),
         },
     })
     .ok()?;
@@ -5043,13 +5225,14 @@ pub(crate) fn update_phpdoc_from_signature_edit(
     uri: Uri,
     source: &str,
     symbol: &php_lsp_types::SymbolInfo,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     let plan = update_phpdoc_from_signature_plan(source, symbol)?;
     let mut changes = HashMap::new();
     changes.insert(
         uri,
         vec![TextEdit {
-            range: lsp_range_for_byte_offsets(source, plan.start, plan.end),
+            range: lsp_range_for_byte_offsets(source, plan.start, plan.end, encoding),
             new_text: plan.new_text,
         }],
     );
@@ -5068,6 +5251,7 @@ pub(crate) fn build_add_import_edit(
     import_fqn: &str,
     import_kind: ImportKind,
     diagnostic_range: Range,
+    encoding: PositionEncoding,
 ) -> Option<(WorkspaceEdit, Option<String>)> {
     if let Some(existing) = existing_import_for_fqn(file_symbols, import_fqn, import_kind) {
         if let Some(alias) = existing.alias.clone() {
@@ -5116,7 +5300,7 @@ pub(crate) fn build_add_import_edit(
 
     let replacement_name = alias.as_deref().unwrap_or(import_short_name);
     if alias.is_some()
-        || text_at_lsp_range(source, diagnostic_range)
+        || text_at_lsp_range(source, diagnostic_range, encoding)
             .map(|text| text.trim_start_matches('\\') != replacement_name)
             .unwrap_or(false)
     {
@@ -5230,9 +5414,13 @@ pub(crate) fn is_unused_import_diagnostic(diagnostic: &Diagnostic) -> bool {
             || diagnostic.message.starts_with("Unused import: "))
 }
 
-pub(crate) fn diagnostic_range_byte_offsets(source: &str, range: Range) -> Option<(usize, usize)> {
-    let start = lsp_position_to_byte(source, range.start)?;
-    let end = lsp_position_to_byte(source, range.end)?;
+pub(crate) fn diagnostic_range_byte_offsets(
+    source: &str,
+    range: Range,
+    encoding: PositionEncoding,
+) -> Option<(usize, usize)> {
+    let start = lsp_position_to_byte(source, range.start, encoding)?;
+    let end = lsp_position_to_byte(source, range.end, encoding)?;
     Some((start.min(source.len()), end.min(source.len())))
 }
 
@@ -5240,13 +5428,14 @@ pub(crate) fn remove_unused_import_edit(
     uri: Uri,
     source: &str,
     range: Range,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
-    let (start, end) = diagnostic_range_byte_offsets(source, range)?;
+    let (start, end) = diagnostic_range_byte_offsets(source, range, encoding)?;
     let (start, end) = line_full_span(source, start, end);
     Some(workspace_edit_from_text_edits(
         uri,
         vec![TextEdit {
-            range: lsp_range_for_byte_offsets(source, start, end),
+            range: lsp_range_for_byte_offsets(source, start, end, encoding),
             new_text: String::new(),
         }],
     ))
@@ -5257,12 +5446,18 @@ pub(crate) fn build_remove_unused_import_action(
     source: &str,
     diagnostic: &Diagnostic,
     is_preferred: bool,
+    encoding: PositionEncoding,
 ) -> Option<CodeActionOrCommand> {
     Some(CodeActionOrCommand::CodeAction(CodeAction {
         title: "Remove unused import".to_string(),
         kind: Some(CodeActionKind::QUICKFIX),
         diagnostics: Some(vec![diagnostic.clone()]),
-        edit: Some(remove_unused_import_edit(uri, source, diagnostic.range)?),
+        edit: Some(remove_unused_import_edit(
+            uri,
+            source,
+            diagnostic.range,
+            encoding,
+        )?),
         command: None,
         is_preferred: Some(is_preferred),
         disabled: None,
@@ -5301,6 +5496,7 @@ pub(crate) fn build_diagnostic_replacement_action(
     diagnostic: &Diagnostic,
     replacement: &DiagnosticReplacement,
     is_preferred: bool,
+    encoding: PositionEncoding,
 ) -> Option<CodeActionOrCommand> {
     if replacement.new_text.trim().is_empty() {
         return None;
@@ -5323,8 +5519,9 @@ pub(crate) fn build_diagnostic_replacement_action(
             vec![TextEdit {
                 range: lsp_range_for_byte_offsets(
                     source,
-                    diagnostic_range_byte_offsets(source, range)?.0,
-                    diagnostic_range_byte_offsets(source, range)?.1,
+                    diagnostic_range_byte_offsets(source, range, encoding)?.0,
+                    diagnostic_range_byte_offsets(source, range, encoding)?.1,
+                    encoding,
                 ),
                 new_text: replacement.new_text.clone(),
             }],
@@ -5462,6 +5659,7 @@ pub(crate) fn add_throws_phpdoc_edit(
     source: &str,
     symbol: &php_lsp_types::SymbolInfo,
     exception: &str,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     let exception = exception.trim();
     if exception.is_empty() {
@@ -5487,7 +5685,7 @@ pub(crate) fn add_throws_phpdoc_edit(
     Some(workspace_edit_from_text_edits(
         uri,
         vec![TextEdit {
-            range: lsp_range_for_byte_offsets(source, plan.start, plan.end),
+            range: lsp_range_for_byte_offsets(source, plan.start, plan.end, encoding),
             new_text: plan.new_text,
         }],
     ))
@@ -5565,6 +5763,7 @@ pub(crate) fn add_iterable_value_type_phpdoc_edit(
     symbol: &php_lsp_types::SymbolInfo,
     variable: &str,
     type_text: &str,
+    encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     let variable = normalize_phpdoc_variable_name(variable)?;
     let type_text = type_text.trim();
@@ -5587,7 +5786,7 @@ pub(crate) fn add_iterable_value_type_phpdoc_edit(
     Some(workspace_edit_from_text_edits(
         uri,
         vec![TextEdit {
-            range: lsp_range_for_byte_offsets(source, plan.start, plan.end),
+            range: lsp_range_for_byte_offsets(source, plan.start, plan.end, encoding),
             new_text: plan.new_text,
         }],
     ))
@@ -5600,6 +5799,7 @@ pub(crate) fn build_external_analyzer_fix_actions(
     diagnostic: &Diagnostic,
     analyzer: ExternalAnalyzer,
     data: Option<&PhpLspDiagnosticData>,
+    encoding: PositionEncoding,
 ) -> Vec<CodeActionOrCommand> {
     let mut actions = Vec::new();
     if let Some(action) =
@@ -5608,7 +5808,7 @@ pub(crate) fn build_external_analyzer_fix_actions(
         actions.push(action);
     }
 
-    let range = lsp_range_to_byte_range(source, diagnostic.range);
+    let range = lsp_range_to_byte_range(source, diagnostic.range, encoding);
     let callable = callable_symbol_containing_range(file_symbols, range);
     let fixes = data
         .into_iter()
@@ -5621,9 +5821,13 @@ pub(crate) fn build_external_analyzer_fix_actions(
                 let Some(symbol) = callable else {
                     continue;
                 };
-                if let Some(edit) =
-                    add_throws_phpdoc_edit(uri.clone(), source, symbol, exception.as_str())
-                {
+                if let Some(edit) = add_throws_phpdoc_edit(
+                    uri.clone(),
+                    source,
+                    symbol,
+                    exception.as_str(),
+                    encoding,
+                ) {
                     actions.push(CodeActionOrCommand::CodeAction(CodeAction {
                         title: format!("Add @throws {}", exception.trim()),
                         kind: Some(CodeActionKind::QUICKFIX),
@@ -5649,6 +5853,7 @@ pub(crate) fn build_external_analyzer_fix_actions(
                     symbol,
                     variable.as_str(),
                     type_text.as_str(),
+                    encoding,
                 ) {
                     let variable = normalize_phpdoc_variable_name(&variable)
                         .map(|name| format!("${name}"))
@@ -5670,7 +5875,8 @@ pub(crate) fn build_external_analyzer_fix_actions(
                     continue;
                 }
                 let range = range.unwrap_or(diagnostic.range);
-                let Some((start, end)) = diagnostic_range_byte_offsets(source, range) else {
+                let Some((start, end)) = diagnostic_range_byte_offsets(source, range, encoding)
+                else {
                     continue;
                 };
                 actions.push(CodeActionOrCommand::CodeAction(CodeAction {
@@ -5680,7 +5886,7 @@ pub(crate) fn build_external_analyzer_fix_actions(
                     edit: Some(workspace_edit_from_text_edits(
                         uri.clone(),
                         vec![TextEdit {
-                            range: lsp_range_for_byte_offsets(source, start, end),
+                            range: lsp_range_for_byte_offsets(source, start, end, encoding),
                             new_text: replacement,
                         }],
                     )),
@@ -5709,6 +5915,7 @@ impl PhpLspBackend {
         &self,
         params: CodeActionParams,
     ) -> Result<Option<CodeActionResponse>> {
+        let encoding = self.position_encoding();
         let wants_quickfix =
             code_action_kind_allowed(params.context.only.as_ref(), &CodeActionKind::QUICKFIX);
         let wants_organize_imports = code_action_kind_allowed(
@@ -5733,8 +5940,15 @@ impl PhpLspBackend {
         );
         let wants_implement_missing_methods =
             code_action_kind_allowed(params.context.only.as_ref(), &CodeActionKind::QUICKFIX);
+        let phpcs_enabled = self.phpcs_config.lock().await.enabled;
+        let wants_phpcbf_fix_all = phpcs_enabled
+            && code_action_kind_allowed(
+                params.context.only.as_ref(),
+                &CodeActionKind::new(SOURCE_FIX_ALL_PHPCBF),
+            );
 
         if !wants_quickfix
+            && !wants_phpcbf_fix_all
             && !wants_organize_imports
             && !wants_add_return_type
             && !wants_generate_members
@@ -5777,12 +5991,12 @@ impl PhpLspBackend {
                 .map(|entry| entry.value().clone())
                 .unwrap_or_else(|| extract_file_symbols(tree, &source, &uri_str));
             let organize_imports_edit = if wants_organize_imports || wants_quickfix {
-                build_organize_imports_edit(uri.clone(), &source, tree, &file_symbols)
+                build_organize_imports_edit(uri.clone(), &source, tree, &file_symbols, encoding)
             } else {
                 None
             };
             let add_return_type_actions = if wants_add_return_type {
-                let range = lsp_range_to_byte_range(&source, params.range);
+                let range = lsp_range_to_byte_range(&source, params.range, encoding);
                 find_missing_return_type_candidates(tree, &source, range)
                     .into_iter()
                     .filter_map(|candidate| {
@@ -5799,7 +6013,7 @@ impl PhpLspBackend {
                 Vec::new()
             };
             let generate_member_actions = if wants_generate_members {
-                let range = lsp_range_to_byte_range(&source, params.range);
+                let range = lsp_range_to_byte_range(&source, params.range, encoding);
                 let mut actions = Vec::new();
                 let visibility_symbol = property_symbol_at_range(&file_symbols, range)
                     .or_else(|| member_symbol_at_range(&file_symbols, range));
@@ -5882,6 +6096,7 @@ impl PhpLspBackend {
                     &source,
                     params.range,
                     document_version,
+                    encoding,
                 ) {
                     actions.push(action);
                 }
@@ -5892,6 +6107,7 @@ impl PhpLspBackend {
                     &file_symbols,
                     params.range,
                     document_version,
+                    encoding,
                 ) {
                     actions.push(action);
                 }
@@ -5906,6 +6122,7 @@ impl PhpLspBackend {
                     &source,
                     params.range,
                     document_version,
+                    encoding,
                 )
                 .into_iter()
                 .collect()
@@ -5913,7 +6130,7 @@ impl PhpLspBackend {
                 Vec::new()
             };
             let implement_missing_methods_actions = if wants_implement_missing_methods {
-                let range = lsp_range_to_byte_range(&source, params.range);
+                let range = lsp_range_to_byte_range(&source, params.range, encoding);
                 concrete_class_symbol_at_range(&file_symbols, range)
                     .and_then(|class_sym| {
                         let missing_methods =
@@ -5965,10 +6182,41 @@ impl PhpLspBackend {
             }
         }
 
+        if wants_phpcbf_fix_all && self.uri_is_php_source(&uri).await {
+            actions.extend(build_fix_with_phpcbf_action(
+                uri.clone(),
+                CodeActionKind::new(SOURCE_FIX_ALL_PHPCBF),
+                None,
+                params.range,
+                document_version,
+            ));
+        }
+
         if !wants_quickfix {
             return Ok(Some(actions));
         }
 
+        let phpcbf_diagnostics: Vec<Diagnostic> = if phpcs_enabled {
+            params
+                .context
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| is_fixable_phpcs_diagnostic(diagnostic))
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if !phpcbf_diagnostics.is_empty() {
+            actions.extend(build_fix_with_phpcbf_action(
+                uri.clone(),
+                CodeActionKind::QUICKFIX,
+                Some(phpcbf_diagnostics),
+                params.range,
+                document_version,
+            ));
+        }
+
         let diagnostics = if params.context.diagnostics.is_empty() {
             let parser = match self.open_files.get(&uri_str) {
                 Some(p) => p,
@@ -5986,6 +6234,7 @@ impl PhpLspBackend {
                     severity: diagnostic_severity,
                     budget: diagnostic_budget,
                     php_version,
+                    encoding,
                 },
                 self.current_document_version(&uri_str),
             )
@@ -6012,6 +6261,7 @@ impl PhpLspBackend {
                         &diagnostic,
                         replacement,
                         quickfix_count == 0,
+                        encoding,
                     ) {
                         actions.push(action);
                         quickfix_count += 1;
@@ -6025,6 +6275,7 @@ impl PhpLspBackend {
                     &source,
                     &diagnostic,
                     quickfix_count == 0,
+                    encoding,
                 ) {
                     actions.push(action);
                     quickfix_count += 1;
@@ -6040,6 +6291,7 @@ impl PhpLspBackend {
                         &diagnostic,
                         analyzer,
                         data.as_ref(),
+                        encoding,
                     );
                     quickfix_count += analyzer_actions.len();
                     actions.extend(analyzer_actions);
@@ -6053,36 +6305,7 @@ impl PhpLspBackend {
             };
             let unresolved_short = short_name(&unresolved_fqn);
 
-            let mut candidates: Vec<std::sync::Arc<php_lsp_types::SymbolInfo>> = match import_kind {
-                ImportKind::Class => self
-                    .index
-                    .types
-                    .iter()
-                    .filter(|entry| {
-                        let sym = entry.value();
-                        !sym.modifiers.is_builtin
-                            && (sym.name == unresolved_short
-                                || short_name(&sym.fqn) == unresolved_short)
-                    })
-                    .map(|entry| entry.value().clone())
-                    .collect(),
-                ImportKind::Function => self
-                    .index
-                    .functions
-                    .iter()
-                    .filter(|entry| {
-                        let sym = entry.value();
-                        !sym.modifiers.is_builtin
-                            && (sym.name == unresolved_short
-                                || short_name(&sym.fqn) == unresolved_short)
-                    })
-                    .map(|entry| entry.value().clone())
-                    .collect(),
-                ImportKind::Constant => Vec::new(),
-            };
-            candidates.sort_by(|a, b| a.fqn.cmp(&b.fqn));
-            candidates.dedup_by(|a, b| a.fqn == b.fqn);
-            candidates.truncate(5);
+            let candidates = import_candidates(&self.index, import_kind, unresolved_short);
 
             for candidate in candidates {
                 let Some((edit, alias)) = build_add_import_edit(
@@ -6092,6 +6315,7 @@ impl PhpLspBackend {
                     &candidate.fqn,
                     import_kind,
                     diagnostic.range,
+                    encoding,
                 ) else {
                     continue;
                 };
@@ -6130,6 +6354,7 @@ impl PhpLspBackend {
         &self,
         mut params: CodeAction,
     ) -> Result<CodeAction> {
+        let encoding = self.position_encoding();
         let Some(data_value) = params.data.clone() else {
             return Ok(params);
         };
@@ -6176,6 +6401,7 @@ impl PhpLspBackend {
                     &source,
                     &hint,
                     insert_position,
+                    encoding,
                 ));
             }
             (
@@ -6231,7 +6457,7 @@ impl PhpLspBackend {
                         .source_for_uri(&method.uri, "implement missing methods source read")
                         .await;
                     metadata_by_fqn.insert(
-                        method.fqn.clone(),
+                        method.fqn.to_string(),
                         method_contract_metadata(method, declaration_source.as_deref()),
                     );
                 }
@@ -6242,6 +6468,7 @@ impl PhpLspBackend {
                     &missing_methods,
                     &metadata_by_fqn,
                     php_version,
+                    encoding,
                 )
                 .or_else(|| Some(empty_workspace_edit()));
             }
@@ -6296,6 +6523,7 @@ impl PhpLspBackend {
                     &file_symbols,
                     class_sym,
                     php_version,
+                    encoding,
                 )
                 .or_else(|| Some(empty_workspace_edit()));
             }
@@ -6356,6 +6584,7 @@ impl PhpLspBackend {
                     accessor_kind,
                     &method_name,
                     php_version,
+                    encoding,
                 )
                 .or_else(|| Some(empty_workspace_edit()));
             }
@@ -6415,6 +6644,7 @@ impl PhpLspBackend {
                     &source,
                     symbol,
                     target_visibility,
+                    encoding,
                 )
                 .or_else(|| Some(empty_workspace_edit()));
             }
@@ -6462,9 +6692,14 @@ impl PhpLspBackend {
                     return Ok(params);
                 };
 
-                params.edit =
-                    promote_constructor_parameter_edit(uri_value, &source, &file_symbols, property)
-                        .or_else(|| Some(empty_workspace_edit()));
+                params.edit = promote_constructor_parameter_edit(
+                    uri_value,
+                    &source,
+                    &file_symbols,
+                    property,
+                    encoding,
+                )
+                .or_else(|| Some(empty_workspace_edit()));
             }
             (
                 CodeActionDataKind::UpdatePhpDoc,
@@ -6515,8 +6750,9 @@ impl PhpLspBackend {
                     return Ok(params);
                 };
 
-                params.edit = update_phpdoc_from_signature_edit(uri_value, &source, symbol)
-                    .or_else(|| Some(empty_workspace_edit()));
+                params.edit =
+                    update_phpdoc_from_signature_edit(uri_value, &source, symbol, encoding)
+                        .or_else(|| Some(empty_workspace_edit()));
             }
             (
                 CodeActionDataKind::ExtractVariable,
@@ -6541,10 +6777,16 @@ impl PhpLspBackend {
                     params.edit = Some(empty_workspace_edit());
                     return Ok(params);
                 };
-                let range = lsp_range_to_byte_range(&source, requested_range);
-                params.edit =
-                    extract_variable_edit(uri_value, tree, &source, range, &variable_name)
-                        .or_else(|| Some(empty_workspace_edit()));
+                let range = lsp_range_to_byte_range(&source, requested_range, encoding);
+                params.edit = extract_variable_edit(
+                    uri_value,
+                    tree,
+                    &source,
+                    range,
+                    &variable_name,
+                    encoding,
+                )
+                .or_else(|| Some(empty_workspace_edit()));
             }
             (
                 CodeActionDataKind::ExtractConstant,
@@ -6575,7 +6817,7 @@ impl PhpLspBackend {
                     .get(&uri)
                     .map(|entry| entry.value().clone())
                     .unwrap_or_else(|| extract_file_symbols(tree, &source, &uri));
-                let range = lsp_range_to_byte_range(&source, requested_range);
+                let range = lsp_range_to_byte_range(&source, requested_range, encoding);
                 params.edit = extract_constant_edit(
                     uri_value,
                     tree,
@@ -6583,6 +6825,7 @@ impl PhpLspBackend {
                     &file_symbols,
                     range,
                     &constant_name,
+                    encoding,
                 )
                 .or_else(|| Some(empty_workspace_edit()));
             }
@@ -6609,9 +6852,47 @@ impl PhpLspBackend {
                     params.edit = Some(empty_workspace_edit());
                     return Ok(params);
                 };
-                let range = lsp_range_to_byte_range(&source, requested_range);
-                params.edit = inline_variable_edit(uri_value, tree, &source, range, &variable_name)
-                    .or_else(|| Some(empty_workspace_edit()));
+                let range = lsp_range_to_byte_range(&source, requested_range, encoding);
+                params.edit =
+                    inline_variable_edit(uri_value, tree, &source, range, &variable_name, encoding)
+                        .or_else(|| Some(empty_workspace_edit()));
+            }
+            (CodeActionDataKind::FixWithPhpcbf, CodeActionDataExtra::FixWithPhpcbf) => {
+                if self.current_document_version(&uri) != document_version {
+                    params.edit = Some(empty_workspace_edit());
+                    return Ok(params);
+                }
+
+                let Ok(uri_value) = uri.parse::<Uri>() else {
+                    params.edit = Some(empty_workspace_edit());
+                    return Ok(params);
+                };
+
+                let Some(source) = self.open_files.get(&uri).map(|parser| parser.source()) else {
+                    params.edit = Some(empty_workspace_edit());
+                    return Ok(params);
+                };
+
+                params.edit = match self.phpcbf_fix_edit(&uri, source).await {
+                    Ok(Some(edit)) => Some(workspace_edit_from_text_edits(uri_value, vec![edit])),
+                    Ok(None) => Some(empty_workspace_edit()),
+                    Err(message) => {
+                        if !message.contains("command cancelled") {
+                            tracing::warn!("phpcbf fix failed for {}: {}", uri, message);
+                            self.client
+                                .log_message(
+                                    MessageType::WARNING,
+                                    format!("php-lsp phpcbf fix failed: {}", message),
+                                )
+                                .await;
+                        }
+                        Some(empty_workspace_edit())
+                    }
+                };
+                // Reject the edit if the document changed while phpcbf ran.
+                if self.current_document_version(&uri) != document_version {
+                    params.edit = Some(empty_workspace_edit());
+                }
             }
             _ => {
                 params.edit = Some(empty_workspace_edit());
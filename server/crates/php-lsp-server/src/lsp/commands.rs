@@ -0,0 +1,257 @@
+//! `workspace/executeCommand` handlers.
+//!
+//! `php-lsp.reindex` forces a rescan after large git operations without
+//! restarting the editor. Without arguments it drops the workspace index cache
+//! and reindexes every root; with file URI arguments (strings or `{ "uri": ...
+//! }` objects) it reindexes only those files from the open buffer or disk.
+//!
+//! `php-lsp.dumpIndex` exports the current index for debugging and offline
+//! analysis; see `index_dump.rs` for the formats.
+//!
+//! `php-lsp.newClass` fills an empty PHP file (URI argument) with the PSR-4
+//! class scaffold from `scaffold.rs`, even when automatic scaffolding of
+//! created files is turned off.
+//!
+//! `php-lsp.findUnusedSymbols` lists public symbols without workspace
+//! references (see `unused_symbols.rs`). An optional `{ "exclude": [...] }`
+//! argument replaces the default entry point and test patterns.
+
+use super::super::*;
+use super::index_dump::{write_index_dump, IndexDumpFormat};
+use super::unused_symbols::{find_unused_symbols, DEFAULT_UNUSED_SYMBOL_EXCLUDES};
+
+/// Command ids advertised through `executeCommandProvider`.
+pub const REINDEX_COMMAND: &str = "php-lsp.reindex";
+pub const DUMP_INDEX_COMMAND: &str = "php-lsp.dumpIndex";
+pub const NEW_CLASS_COMMAND: &str = "php-lsp.newClass";
+pub const FIND_UNUSED_SYMBOLS_COMMAND: &str = "php-lsp.findUnusedSymbols";
+
+pub(crate) fn execute_command_options() -> ExecuteCommandOptions {
+    ExecuteCommandOptions {
+        commands: vec![
+            REINDEX_COMMAND.to_string(),
+            DUMP_INDEX_COMMAND.to_string(),
+            NEW_CLASS_COMMAND.to_string(),
+            FIND_UNUSED_SYMBOLS_COMMAND.to_string(),
+        ],
+        work_done_progress_options: WorkDoneProgressOptions::default(),
+    }
+}
+
+impl PhpLspBackend {
+    pub(crate) async fn lsp_execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        match params.command.as_str() {
+            REINDEX_COMMAND => self.execute_reindex_command(&params.arguments).await,
+            DUMP_INDEX_COMMAND => self.execute_dump_index_command(&params.arguments).await,
+            NEW_CLASS_COMMAND => self.execute_new_class_command(&params.arguments).await,
+            FIND_UNUSED_SYMBOLS_COMMAND => {
+                self.execute_find_unused_symbols_command(&params.arguments)
+                    .await
+            }
+            _ => Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "Unknown command: {}",
+                params.command
+            ))),
+        }
+    }
+
+    async fn execute_reindex_command(
+        &self,
+        arguments: &[serde_json::Value],
+    ) -> Result<Option<serde_json::Value>> {
+        let uris = command_uris(REINDEX_COMMAND, arguments)?;
+        if uris.is_empty() {
+            self.clear_workspace_index_caches().await;
+            self.reindex_workspaces("on request").await;
+            return Ok(Some(serde_json::json!({ "scope": "workspace" })));
+        }
+
+        for uri in &uris {
+            self.reindex_php_file(uri).await;
+        }
+        Ok(Some(serde_json::json!({
+            "scope": "files",
+            "files": uris.len(),
+        })))
+    }
+
+    async fn execute_new_class_command(
+        &self,
+        arguments: &[serde_json::Value],
+    ) -> Result<Option<serde_json::Value>> {
+        let uris = command_uris(NEW_CLASS_COMMAND, arguments)?;
+        let [uri] = uris.as_slice() else {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                "php-lsp.newClass expects exactly one file URI",
+            ));
+        };
+        let applied = self.apply_new_file_scaffold(uri).await;
+        Ok(Some(serde_json::json!({
+            "uri": uri.as_str(),
+            "applied": applied,
+        })))
+    }
+
+    /// Handle `php-lsp.dumpIndex` with an optional `{ "path", "format",
+    /// "references" }` argument. The format defaults from the path extension,
+    /// and the path defaults to `index-dump.<ext>` in the workspace cache dir.
+    async fn execute_dump_index_command(
+        &self,
+        arguments: &[serde_json::Value],
+    ) -> Result<Option<serde_json::Value>> {
+        let options = arguments.first().cloned().unwrap_or_default();
+        let path = options
+            .get("path")
+            .and_then(serde_json::Value::as_str)
+            .map(PathBuf::from);
+        let format = match options.get("format").and_then(serde_json::Value::as_str) {
+            Some(format) => IndexDumpFormat::parse(format).ok_or_else(|| {
+                tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Unsupported index dump format: {format} (expected json or sql)"
+                ))
+            })?,
+            None => path
+                .as_deref()
+                .and_then(Path::extension)
+                .and_then(|extension| extension.to_str())
+                .and_then(IndexDumpFormat::parse)
+                .unwrap_or(IndexDumpFormat::Json),
+        };
+        let include_references = options
+            .get("references")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let root = self
+                    .config_snapshot
+                    .load()
+                    .workspace_root
+                    .clone()
+                    .ok_or_else(|| {
+                        tower_lsp::jsonrpc::Error::invalid_params(
+                            "php-lsp.dumpIndex needs a path when no workspace is open",
+                        )
+                    })?;
+                cache::cache_file_path(&root)
+                    .with_file_name(format!("index-dump.{}", format.extension()))
+            }
+        };
+
+        let index = self.index.clone();
+        let dump_path = path.clone();
+        let summary = tokio::task::spawn_blocking(move || {
+            write_index_dump(&index, &dump_path, format, include_references)
+        })
+        .await
+        .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?
+        .map_err(|err| {
+            let mut error = tower_lsp::jsonrpc::Error::internal_error();
+            error.message = format!("Failed to write {}: {}", path.display(), err).into();
+            error
+        })?;
+
+        tracing::info!(
+            "Dumped {} indexed files ({} symbols) to {}",
+            summary.files,
+            summary.symbols,
+            path.display()
+        );
+        Ok(Some(serde_json::json!({
+            "path": path.display().to_string(),
+            "format": format.extension(),
+            "files": summary.files,
+            "symbols": summary.symbols,
+            "references": include_references.then_some(summary.references),
+        })))
+    }
+
+    /// Handle `php-lsp.findUnusedSymbols`. The report groups hint diagnostics
+    /// by file, like `publishDiagnostics`, without publishing them.
+    async fn execute_find_unused_symbols_command(
+        &self,
+        arguments: &[serde_json::Value],
+    ) -> Result<Option<serde_json::Value>> {
+        let encoding = self.position_encoding();
+        let exclude = match arguments.first().and_then(|options| options.get("exclude")) {
+            Some(exclude) => {
+                let patterns: Vec<String> =
+                    serde_json::from_value(exclude.clone()).map_err(|_| {
+                        tower_lsp::jsonrpc::Error::invalid_params(
+                            "php-lsp.findUnusedSymbols exclude must be a list of path patterns",
+                        )
+                    })?;
+                normalize_config_paths(patterns)
+            }
+            None => normalize_config_paths(
+                DEFAULT_UNUSED_SYMBOL_EXCLUDES
+                    .iter()
+                    .map(|pattern| pattern.to_string())
+                    .collect(),
+            ),
+        };
+        let roots = self.workspace_roots.lock().await.clone();
+
+        let index = self.index.clone();
+        let open_files = self.open_files.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            find_unused_symbols(&index, &open_files, &roots, &exclude, encoding)
+        })
+        .await
+        .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?;
+
+        let symbols: usize = report
+            .iter()
+            .map(|(_, diagnostics)| diagnostics.len())
+            .sum();
+        let files: Vec<serde_json::Value> = report
+            .into_iter()
+            .map(|(uri, diagnostics)| serde_json::json!({ "uri": uri, "diagnostics": diagnostics }))
+            .collect();
+        Ok(Some(serde_json::json!({
+            "symbols": symbols,
+            "files": files,
+        })))
+    }
+
+    /// Delete the on-disk workspace index caches so the next scan reparses
+    /// every file instead of trusting cached entries.
+    async fn clear_workspace_index_caches(&self) {
+        let roots = self.workspace_roots.lock().await.clone();
+        for root in roots {
+            let cache_path = cache::cache_file_path(&root);
+            match std::fs::remove_file(&cache_path) {
+                Ok(()) => tracing::info!("Removed workspace cache {}", cache_path.display()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => tracing::warn!(
+                    "Failed to remove workspace cache {}: {}",
+                    cache_path.display(),
+                    err
+                ),
+            }
+        }
+    }
+}
+
+fn command_uris(command: &str, arguments: &[serde_json::Value]) -> Result<Vec<Uri>> {
+    arguments
+        .iter()
+        .map(|argument| {
+            let uri = argument
+                .as_str()
+                .or_else(|| argument.get("uri").and_then(serde_json::Value::as_str))
+                .ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params(format!(
+                        "{command} arguments must be file URIs or {{ \"uri\": ... }} objects"
+                    ))
+                })?;
+            uri.parse::<Uri>().map_err(|_| {
+                tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid file URI: {uri}"))
+            })
+        })
+        .collect()
+}
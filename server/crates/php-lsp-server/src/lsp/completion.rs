@@ -7,6 +7,7 @@ impl PhpLspBackend {
         &self,
         params: SignatureHelpParams,
     ) -> Result<Option<SignatureHelp>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position_params
             .text_document
@@ -16,7 +17,7 @@ impl PhpLspBackend {
         let pos = params.text_document_position_params.position;
         tracing::debug!("signatureHelp: {}:{}:{}", uri_str, pos.line, pos.character);
 
-        let (sym_at_pos, active_parameter) = {
+        let (sym_at_pos, active_parameter, argument_count) = {
             let parser = match self.open_files.get(&uri_str) {
                 Some(p) => p,
                 None => return Ok(None),
@@ -26,7 +27,7 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
             let file_symbols = self
                 .index
                 .file_symbols
@@ -50,7 +51,11 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
 
-            (context.symbol, context.active_parameter)
+            (
+                context.symbol,
+                context.active_parameter,
+                context.argument_count,
+            )
         };
 
         let symbol_info = self
@@ -68,13 +73,64 @@ impl PhpLspBackend {
             symbol_info
         };
 
-        Ok(symbol_info.and_then(|sym| build_signature_help(&sym, active_parameter)))
+        Ok(symbol_info.and_then(|sym| {
+            let overloads = phpdoc_method_overloads(&self.index, &sym);
+            build_signature_help(&sym, &overloads, active_parameter, argument_count)
+        }))
+    }
+
+    /// Snippet settings for a completion. Class-like snippets default to the
+    /// file name and, in files without a `namespace` statement, declare the
+    /// namespace PSR-4 autoload expects. Call parentheses are left out in
+    /// templates and where a `(` already follows the cursor.
+    async fn completion_snippet_options(
+        &self,
+        uri_str: &str,
+        file_symbols: &php_lsp_types::FileSymbols,
+        is_template: bool,
+        before_call_parentheses: bool,
+    ) -> SnippetOptions {
+        let enabled = *self.completion_snippets.lock().await;
+        let call_parentheses =
+            *self.completion_call_snippets.lock().await && !is_template && !before_call_parentheses;
+        let path = uri_to_path(uri_str).filter(|_| enabled && !is_template);
+        let Some(path) = path else {
+            return SnippetOptions {
+                enabled,
+                call_parentheses,
+                ..SnippetOptions::default()
+            };
+        };
+
+        let class_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| super::rename::is_php_identifier(stem))
+            .map(str::to_string);
+        let class_namespace = if file_symbols.namespace.is_none() {
+            self.config_snapshot
+                .load()
+                .namespace_map
+                .as_ref()
+                .and_then(|map| map.class_name_for_path(&path))
+                .and_then(|fqn| fqn.rsplit_once('\\').map(|(ns, _)| ns.to_string()))
+        } else {
+            None
+        };
+
+        SnippetOptions {
+            enabled,
+            class_namespace,
+            class_name,
+            call_parentheses,
+        }
     }
 
     pub(crate) async fn lsp_completion(
         &self,
         params: CompletionParams,
     ) -> Result<Option<CompletionResponse>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position
             .text_document
@@ -85,10 +141,10 @@ impl PhpLspBackend {
         let template_document = self.template_document(&uri_str);
         if let Some(template) = &template_document {
             if let Some(path_context) =
-                template.twig_template_path_context_at_position(original_pos)
+                template.twig_template_path_context_at_position(original_pos, encoding)
             {
                 let workspace_root = self.workspace_root_for_uri(&uri_str).await;
-                let namespace_map = self.namespace_map.lock().await.clone();
+                let namespace_map = self.config_snapshot.load().namespace_map.clone();
                 let file_symbols = php_lsp_types::FileSymbols::default();
                 let context = FrameworkStringKeyAtPosition {
                     domain: "twig",
@@ -116,7 +172,7 @@ impl PhpLspBackend {
             }
         }
         let pos = if let Some(template) = &template_document {
-            match template.map_original_position_to_virtual(original_pos) {
+            match template.map_original_position_to_virtual(original_pos, encoding) {
                 Some(pos) => pos,
                 None => return Ok(None),
             }
@@ -136,7 +192,23 @@ impl PhpLspBackend {
             };
             (tree, parser.source())
         };
-        let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
+        let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+        // An unterminated string swallows the rest of the file; close it after
+        // the cursor so interpolated variables can still be typed.
+        let (tree, source) = match line_col_to_byte_offset(&source, pos.line, byte_col)
+            .filter(|_| tree.root_node().has_error())
+            .and_then(|offset| php_lsp_completion::strings::close_strings_at(&source, offset))
+        {
+            Some(repaired) => {
+                let mut parser = FileParser::new();
+                parser.parse_full(&repaired);
+                match parser.tree() {
+                    Some(repaired_tree) => (repaired_tree.clone(), repaired),
+                    None => (tree, source),
+                }
+            }
+            None => (tree, source),
+        };
         let file_symbols = extract_file_symbols(&tree, &source, &uri_str);
         let framework_string_key_context =
             framework_string_key_context_at_position(&source, pos.line, byte_col);
@@ -144,7 +216,7 @@ impl PhpLspBackend {
             if framework_string_key_context.is_some() {
                 (
                     self.workspace_root_for_uri(&uri_str).await,
-                    self.namespace_map.lock().await.clone(),
+                    self.config_snapshot.load().namespace_map.clone(),
                 )
             } else {
                 (None, None)
@@ -163,6 +235,21 @@ impl PhpLspBackend {
             } else {
                 Vec::new()
             };
+        let before_call_parentheses = line_col_to_byte_offset(&source, pos.line, byte_col)
+            .and_then(|offset| source.get(offset..))
+            .is_some_and(|rest| {
+                rest.trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                    .starts_with('(')
+            });
+        let snippet_options = self
+            .completion_snippet_options(
+                &uri_str,
+                &file_symbols,
+                template_document.is_some(),
+                before_call_parentheses,
+            )
+            .await;
+        let max_items = *self.completion_max_items.lock().await;
         let type_cache = RequestTypeCache::new(&uri_str, self.current_document_version(&uri_str));
 
         // Detect completion context
@@ -214,6 +301,29 @@ impl PhpLspBackend {
         if let Some(class_fqn) = completion_class_fqn {
             self.lazy_index_class_dependencies(&class_fqn).await;
         }
+        let union_class_fqns = match &context {
+            php_lsp_completion::context::CompletionContext::MemberAccess {
+                object_expr,
+                class_fqn: Some(class_fqn),
+                ..
+            } => self.union_completion_class_fqns(
+                &CompletionInferenceContext {
+                    tree: &tree,
+                    source_uri: &uri_str,
+                    source: &source,
+                    file_symbols: &file_symbols,
+                    type_cache: &type_cache,
+                    line: pos.line,
+                    byte_col,
+                },
+                object_expr,
+                class_fqn,
+            ),
+            _ => Vec::new(),
+        };
+        for union_class_fqn in &union_class_fqns {
+            self.lazy_index_class_dependencies(union_class_fqn).await;
+        }
 
         let inference_ctx = CompletionInferenceContext {
             tree: &tree,
@@ -242,22 +352,46 @@ impl PhpLspBackend {
                     &self.index,
                     &file_symbols,
                     (pos.line, byte_col, pos.line, byte_col),
+                    &snippet_options,
                 ),
             }
         };
+        if let php_lsp_completion::context::CompletionContext::MemberAccess {
+            object_expr,
+            member_prefix,
+            access_mode,
+            ..
+        } = &context
+        {
+            let mut seen_labels: HashSet<String> =
+                lsp_items.iter().map(|item| item.label.clone()).collect();
+            for union_class_fqn in union_class_fqns {
+                let union_context = php_lsp_completion::context::CompletionContext::MemberAccess {
+                    object_expr: object_expr.clone(),
+                    class_fqn: Some(union_class_fqn),
+                    member_prefix: member_prefix.clone(),
+                    access_mode: *access_mode,
+                };
+                for item in provide_completions_at_range(
+                    &union_context,
+                    &self.index,
+                    &file_symbols,
+                    (pos.line, byte_col, pos.line, byte_col),
+                    &snippet_options,
+                ) {
+                    if seen_labels.insert(item.label.clone()) {
+                        lsp_items.push(item);
+                    }
+                }
+            }
+        }
+        // Provider items arrive best-ranked first; keep only the head of the
+        // list and let the client re-request as the prefix narrows.
+        let is_incomplete = lsp_items.len() > max_items;
+        lsp_items.truncate(max_items);
         if framework_string_key_context.is_some() {
             lsp_items.extend(framework_string_key_items);
         }
-        if let php_lsp_completion::context::CompletionContext::Variable { prefix } = &context {
-            add_local_variable_completion_items(
-                &mut lsp_items,
-                &tree,
-                &source,
-                pos.line,
-                byte_col,
-                prefix,
-            );
-        }
         if let php_lsp_completion::context::CompletionContext::MemberAccess {
             object_expr,
             member_prefix,
@@ -326,7 +460,43 @@ impl PhpLspBackend {
                 context,
                 php_lsp_completion::context::CompletionContext::Free { .. }
                     | php_lsp_completion::context::CompletionContext::Namespace { .. }
+                    | php_lsp_completion::context::CompletionContext::Attribute { .. }
+                    | php_lsp_completion::context::CompletionContext::PhpDoc {
+                        position: php_lsp_completion::context::PhpDocPosition::Type,
+                        ..
+                    }
             );
+        // Replace the partially typed name rather than inserting next to it.
+        // Template cursors map into a virtual document and framework string
+        // keys insert only their missing suffix, so both keep plain inserts.
+        let replace_start = if template_document.is_none() && framework_string_key_context.is_none()
+        {
+            completion_replace_start_byte_col(&source, pos.line, byte_col, &context)
+        } else {
+            None
+        };
+        let replace_range = replace_start.map(|start| Range {
+            start: Position::new(
+                pos.line,
+                byte_col_to_lsp(&source, pos.line, start, encoding),
+            ),
+            end: pos,
+        });
+        let typed = replace_start
+            .and_then(|start| line_col_to_byte_offset(&source, pos.line, start))
+            .zip(line_col_to_byte_offset(&source, pos.line, byte_col))
+            .and_then(|(start, end)| source.get(start..end))
+            .unwrap_or_default();
+        let typed_qualified_name = typed.contains('\\')
+            && (enable_auto_imports
+                || matches!(
+                    context,
+                    php_lsp_completion::context::CompletionContext::UseStatement { .. }
+                ));
+        let strip_variable_sigil = matches!(
+            context,
+            php_lsp_completion::context::CompletionContext::Variable { .. }
+        ) && !typed.starts_with('$');
 
         // Convert lsp_types::CompletionItem to ls_types::CompletionItem
         // We need to map between the two different type systems
@@ -347,7 +517,39 @@ impl PhpLspBackend {
                         .collect()
                 });
 
-                let auto_import_edit = if enable_auto_imports {
+                // A qualified name is replaced by the symbol's FQN, keeping a
+                // typed leading `\`, and needs no import.
+                let qualified_fqn = item
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.as_str())
+                    .filter(|_| typed_qualified_name)
+                    .map(|fqn| {
+                        let leading = if typed.starts_with('\\') { "\\" } else { "" };
+                        format!("{leading}{}", fqn.trim_start_matches('\\'))
+                    });
+                let text_edit = replace_range.map(|range| {
+                    let new_text = match &qualified_fqn {
+                        Some(fqn) => fqn.clone(),
+                        None => {
+                            let text = item
+                                .insert_text
+                                .clone()
+                                .unwrap_or_else(|| item.label.clone());
+                            if strip_variable_sigil {
+                                text.trim_start_matches('$').to_string()
+                            } else {
+                                text
+                            }
+                        }
+                    };
+                    CompletionTextEdit::Edit(TextEdit { range, new_text })
+                });
+                if qualified_fqn.is_some() {
+                    item.filter_text = qualified_fqn.clone();
+                }
+
+                let auto_import_edit = if enable_auto_imports && qualified_fqn.is_none() {
                     item.data
                         .as_ref()
                         .and_then(|data| data.as_str())
@@ -379,9 +581,16 @@ impl PhpLspBackend {
                     filter_text: item.filter_text,
                     insert_text: item.insert_text,
                     insert_text_format: item.insert_text_format.map(lsp_insert_text_format_to_ls),
+                    text_edit,
                     additional_text_edits,
                     commit_characters: item.commit_characters,
                     tags,
+                    preselect: item.preselect,
+                    command: item.command.map(|command| Command {
+                        title: command.title,
+                        command: command.command,
+                        arguments: command.arguments,
+                    }),
                     data: item.data,
                     ..Default::default()
                 }
@@ -391,7 +600,10 @@ impl PhpLspBackend {
         if items.is_empty() {
             Ok(None)
         } else {
-            Ok(Some(CompletionResponse::Array(items)))
+            Ok(Some(CompletionResponse::List(CompletionList {
+                is_incomplete,
+                items,
+            })))
         }
     }
 
@@ -706,7 +918,19 @@ impl PhpLspBackend {
             object_expr,
             || {
                 let object_expr = object_expr.trim();
-                if let Some(class_fqn) = infer_new_expression_type(object_expr, file_symbols) {
+                let context_node = completion_context_node_at_byte_col(tree, line, byte_col)?;
+                if let Some(class_fqn) =
+                    infer_new_expression_type(object_expr, file_symbols, source, context_node)
+                {
+                    return Some(class_fqn);
+                }
+                if let Some(class_fqn) = infer_forwarded_static_call_expression_type(
+                    object_expr,
+                    &self.index,
+                    file_symbols,
+                    source,
+                    context_node,
+                ) {
                     return Some(class_fqn);
                 }
                 if let Some(class_fqn) = infer_static_call_expression_type(
@@ -731,6 +955,16 @@ impl PhpLspBackend {
                     return Some(class_fqn);
                 }
 
+                if let Some(class_fqn) = infer_enum_case_expression_type(
+                    object_expr,
+                    &self.index,
+                    file_symbols,
+                    source,
+                    context_node,
+                ) {
+                    return Some(class_fqn);
+                }
+
                 if object_expr.contains("->") || object_expr.contains("?->") {
                     return self.infer_completion_member_chain_type(
                         object_expr,
@@ -836,16 +1070,17 @@ impl PhpLspBackend {
         var_name: &str,
         type_cache: &RequestTypeCache,
     ) -> Option<String> {
+        let encoding = self.position_encoding();
         let variable_node =
             completion_variable_node_before_position(tree, source, line, byte_col, var_name)?;
-        let utf16_index = Utf16LineIndex::new(source);
+        let line_index = LspLineIndex::new(source, encoding);
         let ctx = InlayHintContext {
             tree,
             source,
             file_symbols,
             index: &self.index,
             type_cache,
-            utf16_index: &utf16_index,
+            line_index: &line_index,
             requested_range: (0, 0, u32::MAX, u32::MAX),
             allow_twig_property_accessors: true,
             allow_blocking_file_io: false,
@@ -882,45 +1117,78 @@ impl PhpLspBackend {
                 let mut class_fqn = if base_expr == "$this" {
                     current_class_fqn_at_range(file_symbols, (line, byte_col, line, byte_col))?
                 } else if base_expr.starts_with('$') {
-                    self.infer_completion_variable_type(
+                    // Keep generic receivers such as `Collection<int, User>` intact so
+                    // the first member binds the class's `@template` parameters.
+                    let inference_ctx = CompletionInferenceContext {
                         tree,
                         source_uri,
                         source,
                         file_symbols,
+                        type_cache,
                         line,
                         byte_col,
-                        base_expr,
-                        type_cache,
-                    )?
+                    };
+                    self.infer_completion_type_info(&inference_ctx, base_expr)
+                        .filter(|type_info| {
+                            matches!(
+                                type_info,
+                                php_lsp_types::TypeInfo::Generic { base, .. }
+                                    if !is_builtin_type_name(base)
+                            )
+                        })
+                        .map(|type_info| type_info.to_string())
+                        .or_else(|| {
+                            self.infer_completion_variable_type(
+                                tree,
+                                source_uri,
+                                source,
+                                file_symbols,
+                                line,
+                                byte_col,
+                                base_expr,
+                                type_cache,
+                            )
+                        })?
                 } else {
-                    infer_new_expression_type(base_expr, file_symbols).or_else(|| {
-                        infer_static_call_expression_type(
-                            base_expr,
-                            file_symbols,
-                            source,
-                            completion_context_node_at_byte_col(tree, line, byte_col)?,
-                            |class_fqn, method_name| {
-                                self.resolve_completion_member_type_cached(
-                                    class_fqn,
-                                    method_name,
-                                    file_symbols,
-                                    Some(source_uri),
-                                    Some(source),
-                                    type_cache,
-                                )
-                                .and_then(|type_text| {
-                                    completion_member_type_text_to_object_fqn(
-                                        &self.index,
-                                        &type_text,
+                    let context_node = completion_context_node_at_byte_col(tree, line, byte_col)?;
+                    infer_new_expression_type(base_expr, file_symbols, source, context_node)
+                        .or_else(|| {
+                            infer_forwarded_static_call_expression_type(
+                                base_expr,
+                                &self.index,
+                                file_symbols,
+                                source,
+                                context_node,
+                            )
+                        })
+                        .or_else(|| {
+                            infer_static_call_expression_type(
+                                base_expr,
+                                file_symbols,
+                                source,
+                                context_node,
+                                |class_fqn, method_name| {
+                                    self.resolve_completion_member_type_cached(
+                                        class_fqn,
+                                        method_name,
+                                        file_symbols,
+                                        Some(source_uri),
+                                        Some(source),
+                                        type_cache,
                                     )
-                                })
-                            },
-                        )
-                    })?
+                                    .and_then(|type_text| {
+                                        completion_member_type_text_to_object_fqn(
+                                            &self.index,
+                                            &type_text,
+                                        )
+                                    })
+                                },
+                            )
+                        })?
                 };
 
                 for raw_member in parts {
-                    let member = raw_member.trim();
+                    let (member, subscripts) = split_member_subscripts(raw_member.trim());
                     if member.is_empty() {
                         return None;
                     }
@@ -969,6 +1237,16 @@ impl PhpLspBackend {
                             type_cache,
                         )?
                     };
+                    let member_type_text = if subscripts.is_empty() {
+                        member_type_text
+                    } else {
+                        let mut type_info =
+                            parse_phpdoc(&format!("/** @var {member_type_text} */")).var_type?;
+                        for key in subscripts {
+                            type_info = iterable_value_type_info(&type_info, Some(key))?;
+                        }
+                        type_info.to_string()
+                    };
                     class_fqn =
                         completion_member_type_text_to_object_fqn(&self.index, &member_type_text)?;
                 }
@@ -1027,7 +1305,10 @@ impl PhpLspBackend {
         key_prefix: &str,
         quote: Option<char>,
     ) -> Vec<lsp_types::CompletionItem> {
-        let Some(type_info) = self.infer_completion_type_info(ctx, array_expr) else {
+        let Some(type_info) = self
+            .infer_completion_type_info(ctx, array_expr)
+            .or_else(|| php_lsp_completion::superglobals::superglobal_array_shape(array_expr))
+        else {
             return Vec::new();
         };
 
@@ -1039,6 +1320,51 @@ impl PhpLspBackend {
         )
     }
 
+    /// Classes other than `primary_fqn` in a union-typed variable such as
+    /// `$x = match (...) { 1 => new Admin(), default => new Guest() }`, whose
+    /// members complete alongside the primary class.
+    pub(in crate::server) fn union_completion_class_fqns(
+        &self,
+        ctx: &CompletionInferenceContext<'_>,
+        object_expr: &str,
+        primary_fqn: &str,
+    ) -> Vec<String> {
+        if !object_expr.starts_with('$') || object_expr == "$this" || object_expr.contains("->") {
+            return Vec::new();
+        }
+        let Some(type_info) = self.infer_completion_type_info(ctx, object_expr) else {
+            return Vec::new();
+        };
+        let members = match type_info {
+            php_lsp_types::TypeInfo::Union(members) => members,
+            _ => return Vec::new(),
+        };
+
+        let mut class_fqns: Vec<String> = Vec::new();
+        for member in &members {
+            // Inline `@var A|B` members stay as written; qualify them here.
+            let class_fqn = type_info_fqn_from_index(&self.index, "", "", member)
+                .filter(|class_fqn| self.index.resolve_fqn(class_fqn).is_some())
+                .or_else(|| match member {
+                    php_lsp_types::TypeInfo::Simple(name) if !is_builtin_type_name(name) => Some(
+                        php_lsp_parser::resolve::resolve_class_name(name, ctx.file_symbols),
+                    ),
+                    _ => None,
+                });
+            let Some(class_fqn) = class_fqn else {
+                continue;
+            };
+            if !class_fqn.eq_ignore_ascii_case(primary_fqn)
+                && !class_fqns
+                    .iter()
+                    .any(|seen| seen.eq_ignore_ascii_case(&class_fqn))
+            {
+                class_fqns.push(class_fqn);
+            }
+        }
+        class_fqns
+    }
+
     pub(in crate::server) fn add_object_shape_completion_items(
         &self,
         items: &mut Vec<lsp_types::CompletionItem>,
@@ -1067,6 +1393,38 @@ impl PhpLspBackend {
     }
 }
 
+/// Split `items[0]['key']` into the member and its subscript keys.
+fn split_member_subscripts(member: &str) -> (&str, Vec<&str>) {
+    let mut keys = Vec::new();
+    let mut rest = member;
+    while let Some(open) = rest
+        .strip_suffix(']')
+        .and_then(|_| matching_subscript_open(rest))
+    {
+        keys.push(rest[open + 1..rest.len() - 1].trim());
+        rest = rest[..open].trim_end();
+    }
+    keys.reverse();
+    (rest, keys)
+}
+
+fn matching_subscript_open(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, ch) in text.char_indices().rev() {
+        match ch {
+            ']' => depth += 1,
+            '[' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn completion_member_type_text_to_object_fqn(
     index: &WorkspaceIndex,
     type_text: &str,
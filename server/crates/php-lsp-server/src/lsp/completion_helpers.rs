@@ -20,6 +20,9 @@ pub(in crate::server) struct PhpDocVirtualMember {
     pub(in crate::server) params: Vec<php_lsp_types::ParamInfo>,
     pub(in crate::server) description: Option<String>,
     pub(in crate::server) is_static: bool,
+    /// Property or return type resolved against the owner's imports, used for
+    /// type links instead of the tag's raw type when known.
+    pub(in crate::server) resolved_type: Option<php_lsp_types::TypeInfo>,
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +48,40 @@ pub(in crate::server) fn phpdoc_virtual_member_for_symbol(
     phpdoc_virtual_member(index, class_fqn, member_name, kind)
 }
 
+/// The PHPDoc tag behind an index member synthesized from a class-level
+/// `@property`/`@method` tag. Such members point into the owner's docblock,
+/// which precedes the declaration itself.
+pub(in crate::server) fn phpdoc_virtual_member_for_indexed_symbol(
+    index: &WorkspaceIndex,
+    sym: &php_lsp_types::SymbolInfo,
+) -> Option<PhpDocVirtualMember> {
+    let kind = match sym.kind {
+        php_lsp_types::PhpSymbolKind::Property => PhpDocVirtualMemberKind::Property,
+        php_lsp_types::PhpSymbolKind::Method => PhpDocVirtualMemberKind::Method,
+        _ => return None,
+    };
+    let owner_fqn = sym.parent_fqn.as_deref()?;
+    let owner = index.types.get(owner_fqn)?.value().clone();
+    if owner.uri != sym.uri || (sym.range.0, sym.range.1) >= (owner.range.0, owner.range.1) {
+        return None;
+    }
+    let mut member =
+        phpdoc_virtual_member(index, owner_fqn, sym.name.trim_start_matches('$'), kind)
+            .filter(|member| member.owner.fqn == owner.fqn)?;
+    let declared_type = match kind {
+        PhpDocVirtualMemberKind::Property => member.type_info.as_ref(),
+        PhpDocVirtualMemberKind::Method => member.return_type.as_ref(),
+    };
+    // The index already resolved the tag type against the owner's imports.
+    if declared_type.is_some() {
+        member.resolved_type = sym
+            .signature
+            .as_ref()
+            .and_then(|signature| signature.return_type.clone());
+    }
+    Some(member)
+}
+
 pub(in crate::server) fn phpdoc_virtual_member(
     index: &WorkspaceIndex,
     class_fqn: &str,
@@ -73,6 +110,7 @@ pub(in crate::server) fn phpdoc_virtual_member(
                         params: Vec::new(),
                         description: property.description,
                         is_static: false,
+                        resolved_type: None,
                     });
                 }
             }
@@ -92,6 +130,7 @@ pub(in crate::server) fn phpdoc_virtual_member(
                         params: method.params,
                         description: method.description,
                         is_static: method.is_static,
+                        resolved_type: None,
                     });
                 }
             }
@@ -291,7 +330,7 @@ pub(in crate::server) fn phpdoc_virtual_member_markdown_with_links(
     append_class_fqn_link_line(&mut content, "Declared in", index, owner_fqn, owner_fqn);
     match member.kind {
         PhpDocVirtualMemberKind::Property => {
-            if let Some(ref type_info) = member.type_info {
+            if let Some(type_info) = member.resolved_type.as_ref().or(member.type_info.as_ref()) {
                 append_type_link_line(
                     &mut content,
                     "Type",
@@ -304,7 +343,11 @@ pub(in crate::server) fn phpdoc_virtual_member_markdown_with_links(
             }
         }
         PhpDocVirtualMemberKind::Method => {
-            if let Some(ref return_type) = member.return_type {
+            if let Some(return_type) = member
+                .resolved_type
+                .as_ref()
+                .or(member.return_type.as_ref())
+            {
                 append_type_link_line(
                     &mut content,
                     "Returns",
@@ -1148,7 +1191,7 @@ fn signature_parameter_markdown_line(
     ));
     if let Some(description) = phpdoc_param.and_then(|doc| doc.description.as_deref()) {
         line.push_str(" — ");
-        line.push_str(description);
+        line.push_str(&phpdoc_text_markdown(description));
     }
     line
 }
@@ -1170,11 +1213,53 @@ fn phpdoc_parameter_markdown_line(
     ));
     if let Some(description) = param.description.as_deref() {
         line.push_str(" — ");
-        line.push_str(description);
+        line.push_str(&phpdoc_text_markdown(description));
     }
     line
 }
 
+/// Convert the inline HTML used by phpstorm-stubs docblocks (`<p>`, `<b>`,
+/// `<code>`, entities, ...) to Markdown. Unknown tags are kept as written.
+pub(in crate::server) fn phpdoc_text_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let Some(end) = after.find('>') else {
+            out.push_str(after);
+            rest = "";
+            break;
+        };
+        let tag = after[1..end].trim().trim_end_matches('/').trim();
+        let name = tag
+            .trim_start_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "b" | "strong" => out.push_str("**"),
+            "i" | "em" => out.push('*'),
+            "code" | "tt" => out.push('`'),
+            "p" | "br" | "pre" | "ul" | "ol" | "li" | "span" | "blockquote" => out.push(' '),
+            _ => out.push_str(&after[..=end]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    let out = out
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#039;", "'")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 fn parameter_type_markdown(
     index: &WorkspaceIndex,
     file_symbols: &php_lsp_types::FileSymbols,
@@ -1523,6 +1608,42 @@ pub(in crate::server) fn line_bounds_at(source: &str, line: u32) -> Option<(usiz
     None
 }
 
+/// Byte column where the text a completion replaces starts: the partially
+/// typed name before the cursor, including namespace separators and a
+/// leading `$`. Quoted array keys replace the typed key text instead.
+pub(in crate::server) fn completion_replace_start_byte_col(
+    source: &str,
+    line: u32,
+    byte_col: u32,
+    context: &php_lsp_completion::context::CompletionContext,
+) -> Option<u32> {
+    let (line_start, _) = line_bounds_at(source, line)?;
+    let before_cursor = source.get(line_start..line_start + byte_col as usize)?;
+    if let php_lsp_completion::context::CompletionContext::ArrayKey {
+        key_prefix,
+        quote: Some(_),
+        ..
+    } = context
+    {
+        return before_cursor
+            .ends_with(key_prefix.as_str())
+            .then(|| byte_col - key_prefix.len() as u32);
+    }
+
+    let word = before_cursor
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '\\') || !c.is_ascii())
+        .last()
+        .map_or(before_cursor.len(), |(idx, _)| idx);
+    let start = if before_cursor[..word].ends_with('$') {
+        word - 1
+    } else {
+        word
+    };
+    Some(start as u32)
+}
+
 pub(in crate::server) fn array_shape_key_access_at_position(
     source: &str,
     line: u32,
@@ -2153,42 +2274,11 @@ pub(in crate::server) fn find_matching_pair(
     None
 }
 
-pub(in crate::server) fn add_local_variable_completion_items(
-    items: &mut Vec<lsp_types::CompletionItem>,
-    tree: &tree_sitter::Tree,
-    source: &str,
-    line: u32,
-    byte_col: u32,
-    prefix: &str,
-) {
-    let prefix_lower = prefix.to_ascii_lowercase();
-    let mut seen: HashSet<String> = items.iter().map(|item| item.label.clone()).collect();
-
-    for var_name in local_variable_names_at_position(tree, source, line, byte_col) {
-        let name_without_dollar = var_name.trim_start_matches('$');
-        if !name_without_dollar
-            .to_ascii_lowercase()
-            .starts_with(&prefix_lower)
-        {
-            continue;
-        }
-        if !seen.insert(var_name.clone()) {
-            continue;
-        }
-
-        items.push(lsp_types::CompletionItem {
-            label: var_name.clone(),
-            kind: Some(lsp_types::CompletionItemKind::VARIABLE),
-            sort_text: Some(format!("0102_{}", name_without_dollar.to_ascii_lowercase())),
-            filter_text: Some(format!("{} {}", var_name, name_without_dollar)),
-            ..Default::default()
-        });
-    }
-}
-
 pub(in crate::server) fn infer_new_expression_type(
     expr: &str,
     file_symbols: &php_lsp_types::FileSymbols,
+    source: &str,
+    context_node: tree_sitter::Node<'_>,
 ) -> Option<String> {
     let expr = trim_balanced_outer_parens(expr.trim());
     let rest = expr.strip_prefix("new")?;
@@ -2206,11 +2296,15 @@ pub(in crate::server) fn infer_new_expression_type(
         return None;
     }
 
-    Some(
-        resolve_class_name_pub(class_name, file_symbols)
-            .trim_start_matches('\\')
-            .to_string(),
+    let class_fqn = php_lsp_parser::resolve::resolve_scope_class_name_pub(
+        class_name,
+        context_node,
+        source,
+        file_symbols,
     )
+    .trim_start_matches('\\')
+    .to_string();
+    (!matches!(class_fqn.as_str(), "self" | "static" | "parent")).then_some(class_fqn)
 }
 
 pub(in crate::server) fn infer_static_call_expression_type<F>(
@@ -2254,6 +2348,93 @@ where
     resolver(&class_fqn, method_name)
 }
 
+/// Type of an enum case receiver such as `Suit::Hearts`, which is an
+/// instance of the enum declaring the case.
+pub(in crate::server) fn infer_enum_case_expression_type(
+    expr: &str,
+    index: &WorkspaceIndex,
+    file_symbols: &php_lsp_types::FileSymbols,
+    source: &str,
+    context_node: tree_sitter::Node<'_>,
+) -> Option<String> {
+    let (class_expr, case_name) = trim_balanced_outer_parens(expr.trim()).split_once("::")?;
+    let case_name = case_name.trim();
+    if case_name.is_empty()
+        || !case_name
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || ch == '_')
+    {
+        return None;
+    }
+    let class_fqn = php_lsp_parser::resolve::resolve_scope_class_name_pub(
+        class_expr.trim(),
+        context_node,
+        source,
+        file_symbols,
+    )
+    .trim_start_matches('\\')
+    .to_string();
+    let case_fqn = format!("{}::{}", class_fqn, case_name);
+    let is_enum_case = file_symbols
+        .symbols
+        .iter()
+        .any(|sym| sym.kind == php_lsp_types::PhpSymbolKind::EnumCase && sym.fqn == case_fqn)
+        || index
+            .resolve_member_matching_kinds(&case_fqn, &[php_lsp_types::PhpSymbolKind::EnumCase])
+            .is_some();
+    is_enum_case.then_some(class_fqn)
+}
+
+/// Type of a `parent::method()` call whose return type is `static`, which
+/// forwards the late static binding to the calling class.
+pub(in crate::server) fn infer_forwarded_static_call_expression_type(
+    expr: &str,
+    index: &WorkspaceIndex,
+    file_symbols: &php_lsp_types::FileSymbols,
+    source: &str,
+    context_node: tree_sitter::Node<'_>,
+) -> Option<String> {
+    let (class_expr, _) = trim_balanced_outer_parens(expr.trim()).split_once("::")?;
+    if !class_expr.trim().eq_ignore_ascii_case("parent") {
+        return None;
+    }
+
+    infer_static_call_expression_type(
+        expr,
+        file_symbols,
+        source,
+        context_node,
+        |class_fqn, method_name| {
+            let sym = index.resolve_member_matching_kinds(
+                &format!("{class_fqn}::{method_name}"),
+                &[php_lsp_types::PhpSymbolKind::Method],
+            )?;
+            type_info_is_late_static(&symbol_effective_return_type(&sym)?).then(String::new)
+        },
+    )?;
+
+    let called_class_fqn = php_lsp_parser::resolve::resolve_scope_class_name_pub(
+        "static",
+        context_node,
+        source,
+        file_symbols,
+    )
+    .trim_start_matches('\\')
+    .to_string();
+    (!called_class_fqn.is_empty() && called_class_fqn != "static").then_some(called_class_fqn)
+}
+
+fn type_info_is_late_static(type_info: &php_lsp_types::TypeInfo) -> bool {
+    match type_info {
+        php_lsp_types::TypeInfo::Static_ => true,
+        php_lsp_types::TypeInfo::Simple(name) => {
+            matches!(name.trim_start_matches('\\'), "static" | "$this")
+        }
+        php_lsp_types::TypeInfo::Nullable(inner) => type_info_is_late_static(inner),
+        _ => false,
+    }
+}
+
 pub(in crate::server) fn trim_balanced_outer_parens(mut text: &str) -> &str {
     loop {
         let trimmed = text.trim();
@@ -2300,6 +2481,21 @@ pub(in crate::server) fn resolve_member_type_from_index(
     let sym = match index.resolve_member_matching_kinds(&member_fqn, &[expected_kind]) {
         Some(s) => s,
         None => {
+            // `Suit::Hearts` is an instance of the enum declaring the case.
+            if let Some(case) = (expected_kind == php_lsp_types::PhpSymbolKind::Method)
+                .then(|| {
+                    index.resolve_member_matching_kinds(
+                        &member_fqn,
+                        &[php_lsp_types::PhpSymbolKind::EnumCase],
+                    )
+                })
+                .flatten()
+            {
+                return case
+                    .parent_fqn
+                    .as_deref()
+                    .map(|enum_fqn| format!("\\{}", enum_fqn.trim_start_matches('\\')));
+            }
             tracing::debug!("resolve_member_type: {} not found in index", member_fqn);
             return None;
         }
@@ -2646,12 +2842,12 @@ pub(in crate::server) fn format_signature_param(param: &php_lsp_types::ParamInfo
         label.push_str(&type_info.to_string());
         label.push(' ');
     }
-    if param.is_variadic {
-        label.push_str("...");
-    }
     if param.is_by_ref {
         label.push('&');
     }
+    if param.is_variadic {
+        label.push_str("...");
+    }
     if param.name.starts_with('$') {
         label.push_str(&param.name);
     } else {
@@ -2665,21 +2861,84 @@ pub(in crate::server) fn format_signature_param(param: &php_lsp_types::ParamInfo
     label
 }
 
+/// Signature-help label for a parameter: optional and variadic parameters are
+/// wrapped in brackets the way the PHP manual marks optional arguments.
+fn signature_help_param_label(param: &php_lsp_types::ParamInfo) -> String {
+    let label = format_signature_param(param);
+    if param.default_value.is_some() || param.is_variadic {
+        format!("[{label}]")
+    } else {
+        label
+    }
+}
+
+/// Extra call signatures documented for a method as same-named `@method`
+/// tags on its class, such as the overloads phpstorm-stubs list for built-in
+/// classes. The method's own signature is not repeated.
+pub(in crate::server) fn phpdoc_method_overloads(
+    index: &WorkspaceIndex,
+    sym: &php_lsp_types::SymbolInfo,
+) -> Vec<php_lsp_types::Signature> {
+    if sym.kind != php_lsp_types::PhpSymbolKind::Method {
+        return Vec::new();
+    }
+    let Some(class_doc) = sym
+        .parent_fqn
+        .as_deref()
+        .and_then(|parent_fqn| index.resolve_fqn(parent_fqn))
+        .and_then(|class| class.doc_comment.clone())
+    else {
+        return Vec::new();
+    };
+
+    let mut overloads: Vec<php_lsp_types::Signature> = Vec::new();
+    for method in parse_phpdoc(&class_doc).methods {
+        if !method.name.eq_ignore_ascii_case(&sym.name) {
+            continue;
+        }
+        let signature = php_lsp_types::Signature {
+            params: method.params,
+            return_type: method.return_type,
+            throws: Vec::new(),
+        };
+        let is_own = sym
+            .signature
+            .as_ref()
+            .is_some_and(|own| own.params == signature.params);
+        if !is_own && !overloads.contains(&signature) {
+            overloads.push(signature);
+        }
+    }
+    overloads
+}
+
+fn signature_accepts_argument_count(sig: &php_lsp_types::Signature, argument_count: usize) -> bool {
+    argument_count <= sig.params.len() || sig.params.last().is_some_and(|param| param.is_variadic)
+}
+
+fn signature_active_parameter(
+    sig: &php_lsp_types::Signature,
+    active_parameter: usize,
+) -> Option<u32> {
+    if sig.params.is_empty() {
+        None
+    } else {
+        Some(active_parameter.min(sig.params.len() - 1) as u32)
+    }
+}
+
+/// Build signature help for `sym` and its `overloads`. The active signature
+/// is the first one that takes `argument_count` arguments.
 pub(in crate::server) fn build_signature_help(
     sym: &php_lsp_types::SymbolInfo,
+    overloads: &[php_lsp_types::Signature],
     active_parameter: usize,
+    argument_count: usize,
 ) -> Option<SignatureHelp> {
-    let sig = sym.signature.as_ref()?;
-    let param_labels: Vec<String> = sig.params.iter().map(format_signature_param).collect();
-
-    let mut label = String::new();
-    label.push_str(&sym.fqn);
-    label.push('(');
-    label.push_str(&param_labels.join(", "));
-    label.push(')');
-    if let Some(ref ret) = sig.return_type {
-        label.push_str(": ");
-        label.push_str(&ret.to_string());
+    let signatures: Vec<&php_lsp_types::Signature> =
+        sym.signature.iter().chain(overloads.iter()).collect();
+    if signatures.is_empty() {
+        return None;
     }
 
     let phpdoc = sym.doc_comment.as_ref().map(|doc| parse_phpdoc(doc));
@@ -2701,56 +2960,78 @@ pub(in crate::server) fn build_signature_help(
         }
     });
 
-    let parameters: Vec<ParameterInformation> = sig
-        .params
+    let informations = signatures
         .iter()
-        .zip(param_labels.iter())
-        .map(|(param, label)| {
-            let documentation = phpdoc.as_ref().and_then(|doc| {
-                doc.params
-                    .iter()
-                    .find(|p| p.name == param.name)
-                    .and_then(|p| {
-                        let mut parts = Vec::new();
-                        if let Some(ref type_info) = p.type_info {
-                            parts.push(format!("`{}`", type_info));
-                        }
-                        if let Some(ref desc) = p.description {
-                            parts.push(desc.clone());
-                        }
-                        if parts.is_empty() {
-                            None
-                        } else {
-                            Some(Documentation::MarkupContent(MarkupContent {
-                                kind: MarkupKind::Markdown,
-                                value: parts.join(" — "),
-                            }))
-                        }
-                    })
-            });
+        .map(|sig| {
+            let param_labels: Vec<String> =
+                sig.params.iter().map(signature_help_param_label).collect();
+
+            let mut label = String::new();
+            label.push_str(&sym.fqn);
+            label.push('(');
+            label.push_str(&param_labels.join(", "));
+            label.push(')');
+            if let Some(ref ret) = sig.return_type {
+                label.push_str(": ");
+                label.push_str(&ret.to_string());
+            }
+
+            let parameters: Vec<ParameterInformation> = sig
+                .params
+                .iter()
+                .zip(param_labels)
+                .map(|(param, label)| {
+                    let documentation = phpdoc.as_ref().and_then(|doc| {
+                        doc.params
+                            .iter()
+                            .find(|p| p.name == param.name)
+                            .and_then(|p| {
+                                let mut parts = Vec::new();
+                                if let Some(ref type_info) = p.type_info {
+                                    parts.push(format!("`{}`", type_info));
+                                }
+                                if let Some(ref desc) = p.description {
+                                    parts.push(desc.clone());
+                                }
+                                if parts.is_empty() {
+                                    None
+                                } else {
+                                    Some(Documentation::MarkupContent(MarkupContent {
+                                        kind: MarkupKind::Markdown,
+                                        value: parts.join(" — "),
+                                    }))
+                                }
+                            })
+                    });
 
-            ParameterInformation {
-                label: ParameterLabel::Simple(label.clone()),
-                documentation,
+                    ParameterInformation {
+                        label: ParameterLabel::Simple(label),
+                        documentation,
+                    }
+                })
+                .collect();
+
+            SignatureInformation {
+                label,
+                documentation: documentation.clone(),
+                parameters: Some(parameters),
+                active_parameter: signature_active_parameter(sig, active_parameter),
             }
         })
         .collect();
 
-    let active_parameter = if sig.params.is_empty() {
-        None
-    } else {
-        Some(active_parameter.min(sig.params.len() - 1) as u32)
-    };
+    let active_signature = signatures
+        .iter()
+        .position(|sig| signature_accepts_argument_count(sig, argument_count))
+        .unwrap_or(0);
 
     Some(SignatureHelp {
-        signatures: vec![SignatureInformation {
-            label,
-            documentation,
-            parameters: Some(parameters),
+        signatures: informations,
+        active_signature: Some(active_signature as u32),
+        active_parameter: signature_active_parameter(
+            signatures[active_signature],
             active_parameter,
-        }],
-        active_signature: Some(0),
-        active_parameter,
+        ),
     })
 }
 
@@ -32,6 +32,7 @@ impl PhpLspBackend {
         &self,
         params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
+        let encoding = self.position_encoding();
         let uri = params.text_document_position_params.text_document.uri;
         let uri_str = uri.as_str().to_string();
         let pos = params.text_document_position_params.position;
@@ -52,7 +53,7 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
             let file_symbols = self
                 .index
                 .file_symbols
@@ -161,6 +162,7 @@ impl PhpLspBackend {
         &self,
         params: GotoImplementationParams,
     ) -> Result<Option<GotoImplementationResponse>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position_params
             .text_document
@@ -185,7 +187,7 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
             let file_symbols = self
                 .index
                 .file_symbols
@@ -303,13 +305,14 @@ impl PhpLspBackend {
         &self,
         params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
+        let encoding = self.position_encoding();
         let uri = params.text_document_position_params.text_document.uri;
         let uri_str = uri.as_str().to_string();
         let original_pos = params.text_document_position_params.position;
         let template_document = self.template_document(&uri_str);
         if let Some(template) = &template_document {
             if let Some(path_context) =
-                template.twig_template_path_context_at_position(original_pos)
+                template.twig_template_path_context_at_position(original_pos, encoding)
             {
                 let key = if path_context.prefix.is_empty() {
                     path_context.key.as_str()
@@ -322,8 +325,12 @@ impl PhpLspBackend {
                     .map(GotoDefinitionResponse::Scalar));
             }
             let original_source = template.original_source();
-            let original_byte_col =
-                utf16_col_to_byte(original_source, original_pos.line, original_pos.character);
+            let original_byte_col = lsp_col_to_byte(
+                original_source,
+                original_pos.line,
+                original_pos.character,
+                encoding,
+            );
             if let Some(path_context) = twig_static_template_path_context_at_position(
                 original_source,
                 original_pos.line,
@@ -356,7 +363,7 @@ impl PhpLspBackend {
             }
         }
         let pos = if let Some(template) = &template_document {
-            match template.map_original_position_to_virtual(original_pos) {
+            match template.map_original_position_to_virtual(original_pos, encoding) {
                 Some(pos) => pos,
                 None => return Ok(None),
             }
@@ -387,8 +394,8 @@ impl PhpLspBackend {
             };
 
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-            let utf16_index = Utf16LineIndex::new(&source);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+            let line_index = LspLineIndex::new(&source, encoding);
             let type_cache =
                 RequestTypeCache::new(&uri_str, self.current_document_version(&uri_str));
 
@@ -408,9 +415,9 @@ impl PhpLspBackend {
             };
 
             let local_var_def = variable_definition_at_position(tree, &source, pos.line, byte_col)
-                .map(|d| range_byte_to_utf16(&source, d));
+                .map(|d| range_byte_to_lsp(&source, d, encoding));
             let shape_def = shape_definition_at_position(&source, pos.line, byte_col)
-                .map(|d| range_byte_to_utf16(&source, d));
+                .map(|d| range_byte_to_lsp(&source, d, encoding));
             let framework_string_key_context =
                 framework_string_key_context_at_position(&source, pos.line, byte_col);
 
@@ -420,7 +427,7 @@ impl PhpLspBackend {
                 file_symbols: &file_symbols,
                 index: &self.index,
                 type_cache: &type_cache,
-                utf16_index: &utf16_index,
+                line_index: &line_index,
                 requested_range: (0, 0, u32::MAX, u32::MAX),
                 allow_twig_property_accessors: template_document
                     .as_ref()
@@ -457,7 +464,7 @@ impl PhpLspBackend {
                     .map(|class_sym| {
                         (
                             class_sym.uri.clone(),
-                            range_byte_to_utf16(&source, class_sym.selection_range),
+                            range_byte_to_lsp(&source, class_sym.selection_range, encoding),
                         )
                     })
                 } else {
@@ -495,7 +502,7 @@ impl PhpLspBackend {
                 end: Position::new(def.2, def.3),
             };
             if let Some(template) = &template_document {
-                if let Some(mapped) = template.map_virtual_range_to_original(range) {
+                if let Some(mapped) = template.map_virtual_range_to_original(range, encoding) {
                     return Ok(Some(GotoDefinitionResponse::Scalar(Location {
                         uri,
                         range: mapped,
@@ -517,7 +524,7 @@ impl PhpLspBackend {
                 end: Position::new(def.2, def.3),
             };
             if let Some(template) = &template_document {
-                let Some(mapped) = template.map_virtual_range_to_original(range) else {
+                let Some(mapped) = template.map_virtual_range_to_original(range, encoding) else {
                     return Ok(None);
                 };
                 range = mapped;
@@ -535,16 +542,17 @@ impl PhpLspBackend {
                 end: Position::new(def.2, def.3),
             };
             if let Some(template) = &template_document {
-                if let Some(mapped) = template.map_virtual_range_to_original(range) {
+                if let Some(mapped) = template.map_virtual_range_to_original(range, encoding) {
                     range = mapped;
                 } else if template.kind() == crate::template::TemplateKind::Twig {
                     if let Some(current_variable) = sym_at_pos
                         .as_ref()
                         .filter(|sym| sym.ref_kind == RefKind::Variable)
                         .and_then(|sym| {
-                            template.map_virtual_range_to_original(range_from_byte_range(
-                                &source, sym.range,
-                            ))
+                            template.map_virtual_range_to_original(
+                                range_from_byte_range(&source, sym.range, encoding),
+                                encoding,
+                            )
                         })
                     {
                         range = current_variable;
@@ -567,9 +575,10 @@ impl PhpLspBackend {
                     .as_ref()
                     .filter(|sym| sym.ref_kind == RefKind::Variable)
                     .and_then(|sym| {
-                        template.map_virtual_range_to_original(range_from_byte_range(
-                            &source, sym.range,
-                        ))
+                        template.map_virtual_range_to_original(
+                            range_from_byte_range(&source, sym.range, encoding),
+                            encoding,
+                        )
                     })
                 {
                     return Ok(Some(GotoDefinitionResponse::Scalar(Location {
@@ -694,7 +703,7 @@ impl PhpLspBackend {
 
         Ok(result.map(|response| {
             if let Some(template) = &template_document {
-                map_goto_definition_response_for_template(&uri_str, template, response)
+                map_goto_definition_response_for_template(&uri_str, template, response, encoding)
             } else {
                 response
             }
@@ -708,15 +717,16 @@ impl PhpLspBackend {
         uri: &Uri,
         pos: Position,
     ) -> Option<GotoDefinitionResponse> {
+        let encoding = self.position_encoding();
         let uri_str = uri.as_str().to_string();
         let parser = self.open_files.get(&uri_str)?;
         let tree = parser.tree()?;
         let source = parser.source();
-        let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-        let file_symbols = extract_file_symbols(tree, &source, &uri_str);
+        let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+        let file_symbols = parser.file_symbols(&uri_str).unwrap_or_default();
         let sym = symbol_at_position(tree, &source, pos.line, byte_col, &file_symbols)?;
         let use_stmt = imported_use_statement_for_symbol(&file_symbols, &sym)?;
-        let range = range_byte_to_utf16(&source, use_stmt.range);
+        let range = range_byte_to_lsp(&source, use_stmt.range, encoding);
 
         Some(GotoDefinitionResponse::Scalar(Location {
             uri: uri.clone(),
@@ -736,9 +746,9 @@ impl PhpLspBackend {
         }
 
         let parser = self.open_files.get(uri_str)?;
-        let tree = parser.tree()?;
-        let source = parser.source();
-        Some(extract_file_symbols(tree, &source, uri_str))
+        parser
+            .file_symbols(uri_str)
+            .map(|symbols| symbols.into_owned())
     }
 
     pub(in crate::server) async fn source_for_uri(
@@ -749,6 +759,9 @@ impl PhpLspBackend {
         if uri_str.starts_with("phpstub://") {
             return self.stub_source_for_uri(uri_str, label).await;
         }
+        if uri_str.starts_with(PHAR_URI_PREFIX) {
+            return self.phar_source_for_uri(uri_str, label).await;
+        }
 
         if let Some(parser) = self.open_files.get(uri_str) {
             return Some(parser.source());
@@ -771,15 +784,19 @@ impl PhpLspBackend {
             return None;
         }
 
-        let client_stubs_path = self.stubs_path.lock().await.clone();
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
         let root = self
+            .config_snapshot
+            .load()
             .workspace_root
-            .lock()
-            .await
             .clone()
             .or_else(|| std::env::current_dir().ok())?;
 
-        for stubs_path in candidate_stubs_paths(&root, client_stubs_path.clone()) {
+        let runtime_stubs_path = runtime_stubs_dir(&root);
+        for stubs_path in candidate_stubs_paths(&root, client_stubs_path.clone())
+            .into_iter()
+            .chain(std::iter::once(runtime_stubs_path))
+        {
             let path = stubs_path.join(extension).join(relative_file);
             if path.is_file() {
                 if let Ok(source) = read_file_to_string_blocking(path, label).await {
@@ -796,10 +813,11 @@ impl PhpLspBackend {
         symbol: &php_lsp_types::SymbolInfo,
         label: &'static str,
     ) -> Option<Location> {
+        let encoding = self.position_encoding();
         let source = self.source_for_uri(&symbol.uri, label).await?;
         Some(Location {
             uri: symbol.uri.parse::<Uri>().ok()?,
-            range: range_from_byte_range(&source, symbol.selection_range),
+            range: range_from_byte_range(&source, symbol.selection_range, encoding),
         })
     }
 
@@ -807,13 +825,14 @@ impl PhpLspBackend {
         &self,
         member: &PhpDocVirtualMember,
     ) -> Option<Location> {
+        let encoding = self.position_encoding();
         let source = self
             .source_for_uri(&member.owner.uri, "phpdoc virtual member source read")
             .await?;
         let doc_comment = member.owner.doc_comment.as_ref()?;
         let doc_start = source.find(doc_comment)?;
         let range = phpdoc_virtual_member_range(&source, doc_comment, doc_start, member)?;
-        let utf16_range = range_byte_to_utf16(&source, range);
+        let utf16_range = range_byte_to_lsp(&source, range, encoding);
         Some(Location {
             uri: member.owner.uri.parse::<Uri>().ok()?,
             range: Range {
@@ -827,6 +846,7 @@ impl PhpLspBackend {
         &self,
         member: &crate::framework::VirtualMember,
     ) -> Option<Location> {
+        let encoding = self.position_encoding();
         let (uri, range) = member.sources.iter().find_map(|source| match source {
             crate::framework::VirtualMemberSource::SourceRange { uri, range } => {
                 Some((uri.clone(), *range))
@@ -836,7 +856,7 @@ impl PhpLspBackend {
         let source = self
             .source_for_uri(&uri, "framework virtual member source read")
             .await?;
-        let utf16_range = range_byte_to_utf16(&source, range);
+        let utf16_range = range_byte_to_lsp(&source, range, encoding);
         Some(Location {
             uri: uri.parse::<Uri>().ok()?,
             range: Range {
@@ -909,6 +929,7 @@ impl PhpLspBackend {
         _source: &str,
         context: &FrameworkStringKeyAtPosition,
     ) -> Option<Location> {
+        let encoding = self.position_encoding();
         let workspace_root = self.workspace_root_for_uri(uri_str).await?;
         let source_range = self
             .cached_framework_string_keys(&workspace_root, context.domain)
@@ -922,7 +943,7 @@ impl PhpLspBackend {
             .await?;
         Some(Location {
             uri: uri.parse::<Uri>().ok()?,
-            range: range_from_byte_range(&source, range),
+            range: range_from_byte_range(&source, range, encoding),
         })
     }
 
@@ -938,7 +959,7 @@ impl PhpLspBackend {
                 | php_lsp_types::PhpSymbolKind::Trait
                 | php_lsp_types::PhpSymbolKind::Enum
         ) {
-            return Some(symbol.fqn.clone());
+            return Some(symbol.fqn.to_string());
         }
 
         let return_type = symbol.signature.as_ref()?.return_type.as_ref()?;
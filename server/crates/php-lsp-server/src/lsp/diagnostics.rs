@@ -177,6 +177,115 @@ pub(in crate::server) async fn run_phpstan_for_file(
     })
 }
 
+fn phpcs_message_to_diagnostic(message: &serde_json::Value) -> Option<Diagnostic> {
+    let text = message.get("message")?.as_str()?.to_string();
+    let line = phpstan_json_message_u32(message, "line")
+        .unwrap_or(1)
+        .max(1);
+    let start_character = phpstan_json_message_u32(message, "column")
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let fixable = message
+        .get("fixable")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    Some(Diagnostic {
+        range: Range {
+            start: Position::new(line - 1, start_character),
+            end: Position::new(line - 1, start_character + 1),
+        },
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        code: message
+            .get("source")
+            .and_then(|value| value.as_str())
+            .map(|code| NumberOrString::String(code.to_string())),
+        source: Some("phpcs".to_string()),
+        message: text,
+        data: Some(serde_json::json!({ "fixable": fixable })),
+        ..Default::default()
+    })
+}
+
+/// Parse `phpcs --report=json` output. Every violation is published as an
+/// information diagnostic; `data.fixable` marks violations phpcbf can fix.
+pub(in crate::server) fn parse_phpcs_json_diagnostics(
+    stdout: &str,
+    file_path: &Path,
+) -> std::result::Result<Vec<Diagnostic>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(stdout).map_err(|err| format!("invalid phpcs JSON: {}", err))?;
+    let Some(files) = value.get("files").and_then(|files| files.as_object()) else {
+        return Ok(vec![]);
+    };
+
+    let mut diagnostics = Vec::new();
+    for (file_key, file_value) in files {
+        if files.len() != 1 && !phpstan_file_key_matches(file_key, file_path) {
+            continue;
+        }
+
+        let Some(messages) = file_value
+            .get("messages")
+            .and_then(|value| value.as_array())
+        else {
+            continue;
+        };
+
+        diagnostics.extend(messages.iter().filter_map(phpcs_message_to_diagnostic));
+    }
+
+    Ok(diagnostics)
+}
+
+pub(in crate::server) async fn run_phpcs_for_file(
+    config: PhpcsConfig,
+    file_path: PathBuf,
+    workspace_root: Option<PathBuf>,
+    cancellation: Option<OperationCancellationToken>,
+) -> std::result::Result<Vec<Diagnostic>, String> {
+    let command = build_analyzer_shell_command(&config.command, &file_path);
+    let output = run_shell_command_with_timeout(
+        "phpcs",
+        &command,
+        workspace_root.as_deref(),
+        config.timeout_ms,
+        cancellation,
+    )
+    .await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // phpcs exits non-zero whenever it reports violations, so only an empty
+    // or unparsable report is treated as a failure.
+    if stdout.trim().is_empty() {
+        if output.status.success() {
+            return Ok(vec![]);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let details = stderr.trim();
+        return Err(if details.is_empty() {
+            format!("phpcs command exited with {}", output.status)
+        } else {
+            format!("phpcs command exited with {}: {}", output.status, details)
+        });
+    }
+
+    parse_phpcs_json_diagnostics(&stdout, &file_path).map_err(|err| {
+        if output.status.success() {
+            err
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let details = stderr.trim();
+            if details.is_empty() {
+                format!("{} (exit {})", err, output.status)
+            } else {
+                format!("{} (exit {}: {})", err, output.status, details)
+            }
+        }
+    })
+}
+
 fn psalm_issue_u32(issue: &serde_json::Value, key: &str) -> Option<u32> {
     issue
         .get(key)
@@ -310,6 +419,7 @@ pub(in crate::server) async fn run_psalm_for_file(
 
 impl PhpLspBackend {
     pub(crate) async fn lsp_did_open(&self, params: DidOpenTextDocumentParams) {
+        let encoding = self.position_encoding();
         let uri = params.text_document.uri.clone();
         let uri_str = uri.as_str().to_string();
         let text = &params.text_document.text;
@@ -350,7 +460,8 @@ impl PhpLspBackend {
         if !excluded {
             if let Some(tree) = parser.tree() {
                 let file_symbols = extract_file_symbols(tree, text, &uri_str);
-                let references = collect_symbol_references_in_file(tree, text, &file_symbols);
+                let references =
+                    collect_symbol_references_in_file(tree, text, &file_symbols, encoding);
                 let sym_count = file_symbols.symbols.len();
                 self.index
                     .update_file_with_references(&uri_str, file_symbols, references);
@@ -364,7 +475,7 @@ impl PhpLspBackend {
         self.open_files.insert(uri_str, parser);
 
         self.publish_diagnostics(&uri).await;
-        if uri_is_php_file(&uri) {
+        if self.uri_is_php_source(&uri).await {
             self.invalidate_twig_context_disk_cache_for_source_uri(uri.as_str())
                 .await;
             self.refresh_open_twig_contexts_and_republish_diagnostics()
@@ -373,6 +484,7 @@ impl PhpLspBackend {
     }
 
     pub(crate) async fn lsp_did_change(&self, params: DidChangeTextDocumentParams) {
+        let encoding = self.position_encoding();
         let uri = params.text_document.uri.clone();
         let uri_str = uri.as_str().to_string();
         let version = params.text_document.version;
@@ -389,7 +501,7 @@ impl PhpLspBackend {
                 .content_changes
                 .iter()
                 .fold(template, |template, change| {
-                    template.apply_change(change.range, &change.text)
+                    template.apply_change(change.range, &change.text, encoding)
                 });
             let refresh_twig_contexts = updated.kind() == TemplateKind::Twig;
             let mut parser = FileParser::new();
@@ -421,6 +533,7 @@ impl PhpLspBackend {
                         range.end.line,
                         range.end.character,
                         &change.text,
+                        encoding,
                     );
                 } else {
                     // Full content replacement
@@ -433,14 +546,18 @@ impl PhpLspBackend {
                 self.index.remove_file(&uri_str);
             } else if let Some(tree) = parser.tree() {
                 let source = parser.source();
-                let file_symbols = extract_file_symbols(tree, &source, &uri_str);
-                let references = collect_symbol_references_in_file(tree, &source, &file_symbols);
+                let file_symbols = parser
+                    .file_symbols(&uri_str)
+                    .unwrap_or_default()
+                    .into_owned();
+                let references =
+                    collect_symbol_references_in_file(tree, &source, &file_symbols, encoding);
                 self.index
                     .update_file_with_references(&uri_str, file_symbols, references);
             }
         }
 
-        let refresh_twig_contexts = uri_is_php_file(&uri);
+        let refresh_twig_contexts = self.uri_is_php_source(&uri).await;
         self.schedule_fast_diagnostics(uri, version).await;
         if refresh_twig_contexts {
             self.invalidate_twig_context_disk_cache_for_source_uri(&uri_str)
@@ -455,12 +572,15 @@ impl PhpLspBackend {
         let uri_str = uri.as_str().to_string();
         tracing::debug!("didClose: {}", uri_str);
         let refresh_twig_contexts =
-            uri_is_php_file(&uri) && !self.template_documents.contains_key(&uri_str);
+            self.uri_is_php_source(&uri).await && !self.template_documents.contains_key(&uri_str);
         self.open_files.remove(&uri_str);
         self.template_documents.remove(&uri_str);
         self.document_versions.remove(&uri_str);
+        if uri_is_buffer_only(&uri_str) {
+            self.index.remove_file(&uri_str);
+        }
         self.cancel_debounced_diagnostics(&uri_str).await;
-        self.cancel_analyzer_run(&uri_str).await;
+        self.clear_analyzer_diagnostics(&uri_str).await;
         self.cancel_formatter_run(&uri_str).await;
         self.semantic_tokens_cache.lock().await.remove(&uri_str);
         // Clear diagnostics for closed file
@@ -473,7 +593,7 @@ impl PhpLspBackend {
 
     pub(crate) async fn lsp_did_save(&self, params: DidSaveTextDocumentParams) {
         tracing::debug!("didSave: {}", params.text_document.uri.as_str());
-        let refresh_twig_contexts = uri_is_php_file(&params.text_document.uri)
+        let refresh_twig_contexts = self.uri_is_php_source(&params.text_document.uri).await
             && !self
                 .template_documents
                 .contains_key(params.text_document.uri.as_str());
@@ -695,13 +815,67 @@ where
 pub(in crate::server) fn current_parser_symbol_references(
     uri_str: &str,
     parser: &FileParser,
+    encoding: PositionEncoding,
 ) -> Vec<php_lsp_types::SymbolReference> {
     let Some(tree) = parser.tree() else {
         return Vec::new();
     };
     let source = parser.source();
-    let file_symbols = extract_file_symbols(tree, &source, uri_str);
-    collect_symbol_references_in_file(tree, &source, &file_symbols)
+    let file_symbols = parser.file_symbols(uri_str).unwrap_or_default();
+    collect_symbol_references_in_file(tree, &source, &file_symbols, encoding)
+}
+
+/// Whether the built-in diagnostics report a syntax error, in which case
+/// external analyzers are skipped.
+pub(in crate::server) fn has_syntax_error_diagnostics(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| {
+        diagnostic.source.as_deref() == Some("php-lsp")
+            && diagnostic.severity == Some(DiagnosticSeverity::ERROR)
+    })
+}
+
+/// Drop diagnostics that are recorded in the configured baseline.
+pub(in crate::server) fn filter_baselined_diagnostics(
+    baseline: Option<&DiagnosticBaseline>,
+    uri_str: &str,
+    diagnostics: Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    match (baseline, uri_to_path(uri_str)) {
+        (Some(baseline), Some(path)) => baseline.filter(&path, diagnostics),
+        _ => diagnostics,
+    }
+}
+
+/// References in one file that match the target, preferring the live parser
+/// for open documents over the indexed references.
+pub(in crate::server) fn matching_references_for_file(
+    index: &WorkspaceIndex,
+    open_files: &DashMap<String, FileParser>,
+    file_uri: &str,
+    target_fqn: &str,
+    target_kind: php_lsp_types::PhpSymbolKind,
+    include_declaration: bool,
+    encoding: PositionEncoding,
+) -> Vec<php_lsp_types::SymbolReference> {
+    let mut refs = if let Some(parser) = open_files.get(file_uri) {
+        current_parser_symbol_references(file_uri, &parser, encoding)
+    } else {
+        index
+            .file_references
+            .get(file_uri)
+            .map(|entry| entry.value().clone())
+            .unwrap_or_default()
+    };
+    refs.retain(|reference| {
+        symbol_reference_matches(
+            index,
+            reference,
+            target_fqn,
+            target_kind,
+            include_declaration,
+        )
+    });
+    refs
 }
 
 pub(in crate::server) fn symbol_reference_matches(
@@ -749,7 +923,36 @@ fn member_declaration_matches_related_owner(
         return false;
     };
     member_names_match(reference_member, target_member, target_kind)
-        && related_member_owner_matches(index, reference_owner, target_owner)
+        && (related_member_owner_matches(index, reference_owner, target_owner)
+            || trait_member_implements_owner(index, reference_owner, target_owner))
+}
+
+/// A trait method is part of the hierarchy when a class using the trait
+/// extends or implements the target owner, e.g. a trait supplying the
+/// implementation of an interface method.
+fn trait_member_implements_owner(
+    index: &WorkspaceIndex,
+    trait_fqn: &str,
+    target_owner: &str,
+) -> bool {
+    let is_trait = index
+        .types
+        .get(trait_fqn.trim_start_matches('\\'))
+        .is_some_and(|entry| entry.kind == php_lsp_types::PhpSymbolKind::Trait);
+    if !is_trait {
+        return false;
+    }
+
+    let users: Vec<String> = index
+        .types
+        .iter()
+        .filter(|entry| entry.traits.iter().any(|used| fqn_matches(used, trait_fqn)))
+        .map(|entry| entry.key().to_string())
+        .collect();
+    users.iter().any(|user| {
+        fqn_matches(user, target_owner)
+            || class_extends_or_implements(index, user, target_owner, &mut Vec::new())
+    })
 }
 
 fn member_reference_matches_related_receiver(
@@ -915,19 +1118,20 @@ pub(in crate::server) fn compute_diagnostics_with_config_for_version(
         None => return vec![],
     };
     let source = parser.source();
-    let utf16_index = Utf16LineIndex::new(&source);
+    let encoding = diagnostics_config.encoding;
+    let line_index = LspLineIndex::new(&source, encoding);
     let type_cache = RequestTypeCache::new(uri_str, document_version);
     let framework_cache = crate::framework::FrameworkProviderCache::default();
 
     // Syntax errors (ERROR / MISSING nodes)
-    let lsp_diags = extract_syntax_errors(tree, &source);
+    let lsp_diags = extract_syntax_errors(tree, &source, encoding);
     let mut diagnostics: Vec<Diagnostic> = lsp_diags
         .into_iter()
         .map(|d| {
             // tree-sitter positions use byte columns; convert to UTF-16
             let start_char =
-                utf16_index.byte_col_to_utf16(d.range.start.line, d.range.start.character);
-            let end_char = utf16_index.byte_col_to_utf16(d.range.end.line, d.range.end.character);
+                line_index.byte_col_to_lsp(d.range.start.line, d.range.start.character);
+            let end_char = line_index.byte_col_to_lsp(d.range.end.line, d.range.end.character);
             Diagnostic {
                 range: Range {
                     start: Position::new(d.range.start.line, start_char),
@@ -962,10 +1166,28 @@ pub(in crate::server) fn compute_diagnostics_with_config_for_version(
         extract_semantic_diagnostics(tree, &source, &file_symbols, |fqn| index.resolve_fqn(fqn));
     warn_if_slow_diagnostic_phase(uri_str, "semantic", semantic_started);
 
+    let mut removed_function_ranges = HashSet::new();
+    let php_version_function_diagnostics = php_version_function_diagnostics(
+        tree,
+        &source,
+        &file_symbols,
+        index,
+        php_version,
+        &line_index,
+        &mut removed_function_ranges,
+    );
     for sd in sem_diags {
-        if let Some(diagnostic) =
-            semantic_diagnostic_to_lsp(sd, &utf16_index, diagnostics_config.severity)
+        if sd.kind == SemanticDiagnosticKind::UnknownFunction
+            && removed_function_ranges.contains(&sd.range)
+        {
+            continue;
+        }
+        let related_information =
+            semantic_related_information(&sd, uri_str, &file_symbols, index, &line_index);
+        if let Some(mut diagnostic) =
+            semantic_diagnostic_to_lsp(sd, &line_index, diagnostics_config.severity)
         {
+            diagnostic.related_information = related_information;
             diagnostics.push(diagnostic);
         }
     }
@@ -976,9 +1198,21 @@ pub(in crate::server) fn compute_diagnostics_with_config_for_version(
     );
     let skip_member_and_type_diagnostics = member_type_budget_exceeded.is_some();
 
-    diagnostics.extend(apply_diagnostic_category(
-        workspace_duplicate_symbol_diagnostics(uri_str, &file_symbols, index, &utf16_index),
-        DiagnosticCategory::DuplicateSymbols,
+    if let Some(severity) =
+        diagnostic_severity.semantic_severity(&SemanticDiagnosticKind::DuplicateSymbol)
+    {
+        diagnostics.extend(
+            workspace_duplicate_symbol_diagnostics(uri_str, &file_symbols, index, &line_index)
+                .into_iter()
+                .map(|mut diagnostic| {
+                    diagnostic.severity = Some(severity);
+                    diagnostic
+                }),
+        );
+    }
+    diagnostics.extend(apply_diagnostic_rule(
+        trait_method_collision_diagnostics(&file_symbols, index, &line_index),
+        DiagnosticRule::TraitMethodCollision,
         diagnostic_severity,
     ));
     if let Some(limit) = member_type_budget_exceeded {
@@ -991,59 +1225,82 @@ pub(in crate::server) fn compute_diagnostics_with_config_for_version(
             diagnostics.push(partial_analysis_budget_diagnostic(limit));
         }
     }
-    if diagnostic_severity
-        .severity(DiagnosticCategory::Members)
-        .is_some()
+    if diagnostic_severity.category_enabled(DiagnosticCategory::Members)
         && !skip_member_and_type_diagnostics
     {
         let members_started = Instant::now();
-        diagnostics.extend(apply_diagnostic_category(
+        diagnostics.extend(apply_diagnostic_rule(
             member_access_diagnostics(
                 uri_str,
                 tree,
                 &source,
                 &file_symbols,
                 index,
-                &utf16_index,
+                &line_index,
                 &type_cache,
                 &framework_cache,
             ),
-            DiagnosticCategory::Members,
+            DiagnosticRule::UnknownMember,
             diagnostic_severity,
         ));
         warn_if_slow_diagnostic_phase(uri_str, "members", members_started);
     }
-    if diagnostic_severity
-        .severity(DiagnosticCategory::TypeCompatibility)
-        .is_some()
+    if diagnostic_severity.category_enabled(DiagnosticCategory::TypeCompatibility)
         && !skip_member_and_type_diagnostics
     {
         let types_started = Instant::now();
-        diagnostics.extend(apply_diagnostic_category(
+        diagnostics.extend(apply_diagnostic_rule(
             type_compatibility_diagnostics(
                 tree,
                 &source,
                 &file_symbols,
                 index,
-                &utf16_index,
+                &line_index,
                 &type_cache,
             ),
-            DiagnosticCategory::TypeCompatibility,
+            DiagnosticRule::TypeMismatch,
             diagnostic_severity,
         ));
         warn_if_slow_diagnostic_phase(uri_str, "type compatibility", types_started);
     }
-    diagnostics.extend(apply_diagnostic_category(
-        override_signature_diagnostics(&file_symbols, index, &utf16_index),
-        DiagnosticCategory::OverrideSignatures,
+    diagnostics.extend(apply_diagnostic_rule(
+        override_signature_diagnostics(&file_symbols, index, &line_index),
+        DiagnosticRule::IncompatibleOverride,
+        diagnostic_severity,
+    ));
+    diagnostics.extend(apply_diagnostic_rule(
+        php_version_type_diagnostics(tree, &source, php_version, &line_index),
+        DiagnosticRule::UnsupportedPhpFeature,
+        diagnostic_severity,
+    ));
+    diagnostics.extend(apply_diagnostic_rule(
+        php_version_syntax_diagnostics(tree, &source, php_version, &line_index),
+        DiagnosticRule::UnsupportedPhpFeature,
         diagnostic_severity,
     ));
-    diagnostics.extend(apply_diagnostic_category(
-        php_version_type_diagnostics(tree, &source, php_version, &utf16_index),
-        DiagnosticCategory::PhpVersion,
+    diagnostics.extend(apply_diagnostic_rule(
+        php_version_function_diagnostics,
+        DiagnosticRule::RemovedFunction,
         diagnostic_severity,
     ));
 
+    if diagnostic_severity.category_enabled(DiagnosticCategory::UnhandledExceptions)
+        && !skip_member_and_type_diagnostics
+    {
+        diagnostics.extend(apply_diagnostic_rule(
+            unhandled_exception_diagnostics(
+                tree,
+                &source,
+                &file_symbols,
+                index,
+                &line_index,
+                &type_cache,
+            ),
+            DiagnosticRule::UnhandledException,
+            diagnostic_severity,
+        ));
+    }
+
     warn_if_slow_diagnostic_phase(uri_str, "total", diagnostics_started);
     diagnostics
 }
@@ -1117,20 +1374,19 @@ pub(in crate::server) fn warn_if_slow_diagnostic_phase(
 
 pub(in crate::server) fn semantic_diagnostic_to_lsp(
     diagnostic: SemanticDiagnostic,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     severity_config: DiagnosticSeverityConfig,
 ) -> Option<Diagnostic> {
-    let category = semantic_diagnostic_category(&diagnostic.kind);
-    let severity = severity_config.severity(category)?;
+    let severity = severity_config.semantic_severity(&diagnostic.kind)?;
     Some(Diagnostic {
         range: Range {
             start: Position::new(
                 diagnostic.range.0,
-                utf16_index.byte_col_to_utf16(diagnostic.range.0, diagnostic.range.1),
+                line_index.byte_col_to_lsp(diagnostic.range.0, diagnostic.range.1),
             ),
             end: Position::new(
                 diagnostic.range.2,
-                utf16_index.byte_col_to_utf16(diagnostic.range.2, diagnostic.range.3),
+                line_index.byte_col_to_lsp(diagnostic.range.2, diagnostic.range.3),
             ),
         },
         severity: Some(severity),
@@ -1143,66 +1399,126 @@ pub(in crate::server) fn semantic_diagnostic_to_lsp(
     })
 }
 
-pub(in crate::server) fn semantic_diagnostic_category(
-    kind: &SemanticDiagnosticKind,
-) -> DiagnosticCategory {
-    match kind {
-        SemanticDiagnosticKind::UnknownClass
-        | SemanticDiagnosticKind::UnknownFunction
-        | SemanticDiagnosticKind::UnresolvedUse => DiagnosticCategory::UnknownSymbols,
-        SemanticDiagnosticKind::ArgumentCountMismatch => DiagnosticCategory::TypeCompatibility,
-        SemanticDiagnosticKind::UndefinedVariable => DiagnosticCategory::UnknownSymbols,
-        SemanticDiagnosticKind::UnusedImport
-        | SemanticDiagnosticKind::UnusedVariable
-        | SemanticDiagnosticKind::UnusedParameter => DiagnosticCategory::Unused,
-        SemanticDiagnosticKind::DuplicateSymbol => DiagnosticCategory::DuplicateSymbols,
-    }
+pub(in crate::server) fn semantic_diagnostic_code(kind: &SemanticDiagnosticKind) -> &'static str {
+    DiagnosticRule::from_kind(kind).code()
 }
 
-pub(in crate::server) fn semantic_diagnostic_code(kind: &SemanticDiagnosticKind) -> &'static str {
-    match kind {
-        SemanticDiagnosticKind::UnknownClass => "php-lsp.unknownClass",
-        SemanticDiagnosticKind::UnknownFunction => "php-lsp.unknownFunction",
-        SemanticDiagnosticKind::UnresolvedUse => "php-lsp.unresolvedUse",
-        SemanticDiagnosticKind::ArgumentCountMismatch => "php-lsp.argumentCountMismatch",
-        SemanticDiagnosticKind::UndefinedVariable => "php-lsp.undefinedVariable",
-        SemanticDiagnosticKind::UnusedImport => "php-lsp.unusedImport",
-        SemanticDiagnosticKind::UnusedVariable => "php-lsp.unusedVariable",
-        SemanticDiagnosticKind::UnusedParameter => "php-lsp.unusedParameter",
-        SemanticDiagnosticKind::DuplicateSymbol => "php-lsp.duplicateSymbol",
-    }
+/// Clickable context for a semantic diagnostic: the other declarations of a
+/// duplicate symbol in the same file, or the indexed symbols an unknown class
+/// or function could be imported from.
+pub(in crate::server) fn semantic_related_information(
+    diagnostic: &SemanticDiagnostic,
+    uri_str: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    line_index: &LspLineIndex,
+) -> Option<Vec<DiagnosticRelatedInformation>> {
+    let related: Vec<DiagnosticRelatedInformation> = match diagnostic.kind {
+        SemanticDiagnosticKind::DuplicateSymbol => {
+            let duplicate = file_symbols.symbols.iter().find(|sym| {
+                sym.selection_range == diagnostic.range
+                    && is_duplicate_checked_symbol_kind(sym.kind)
+            })?;
+            file_symbols
+                .symbols
+                .iter()
+                .filter(|other| {
+                    other.kind == duplicate.kind
+                        && other.fqn == duplicate.fqn
+                        && other.selection_range != duplicate.selection_range
+                })
+                .filter_map(|other| {
+                    symbol_related_information(
+                        other,
+                        uri_str,
+                        line_index,
+                        format!("{} is also declared here", other.fqn),
+                    )
+                })
+                .take(MAX_DUPLICATE_RELATED_DECLARATIONS)
+                .collect()
+        }
+        SemanticDiagnosticKind::UnknownClass | SemanticDiagnosticKind::UnknownFunction => {
+            let (import_kind, unresolved_fqn) =
+                unknown_symbol_from_diagnostic(&diagnostic.message)?;
+            import_candidates(index, import_kind, short_name(&unresolved_fqn))
+                .iter()
+                .filter_map(|candidate| {
+                    symbol_related_information(
+                        candidate,
+                        uri_str,
+                        line_index,
+                        format!("Candidate import: {}", candidate.fqn),
+                    )
+                })
+                .collect()
+        }
+        _ => return None,
+    };
+    (!related.is_empty()).then_some(related)
+}
+
+/// Related information pointing at `symbol`'s name. Columns are converted with
+/// `line_index` only when the symbol lives in the diagnosed file.
+pub(in crate::server) fn symbol_related_information(
+    symbol: &php_lsp_types::SymbolInfo,
+    uri_str: &str,
+    line_index: &LspLineIndex,
+    message: String,
+) -> Option<DiagnosticRelatedInformation> {
+    let range = symbol.selection_range;
+    let (start, end) = if symbol.uri.as_ref() == uri_str {
+        (
+            line_index.byte_col_to_lsp(range.0, range.1),
+            line_index.byte_col_to_lsp(range.2, range.3),
+        )
+    } else {
+        (range.1, range.3)
+    };
+    Some(DiagnosticRelatedInformation {
+        location: Location {
+            uri: symbol.uri.parse::<Uri>().ok()?,
+            range: Range {
+                start: Position::new(range.0, start),
+                end: Position::new(range.2, end),
+            },
+        },
+        message,
+    })
 }
 
-pub(in crate::server) fn apply_diagnostic_category(
+/// Stamp `rule`'s code onto diagnostics that do not already carry one, and
+/// give each diagnostic the severity configured for its rule.
+pub(in crate::server) fn apply_diagnostic_rule(
     diagnostics: Vec<Diagnostic>,
-    category: DiagnosticCategory,
+    rule: DiagnosticRule,
     severity_config: DiagnosticSeverityConfig,
 ) -> Vec<Diagnostic> {
-    let Some(severity) = severity_config.severity(category) else {
-        return Vec::new();
-    };
-
     diagnostics
         .into_iter()
-        .map(|mut diagnostic| {
-            diagnostic.severity = Some(severity);
+        .filter_map(|mut diagnostic| {
+            let rule = match &diagnostic.code {
+                Some(NumberOrString::String(code)) => DiagnosticRule::parse(code).unwrap_or(rule),
+                _ => rule,
+            };
+            diagnostic.severity = Some(severity_config.rule_severity(rule)?);
             if diagnostic.code.is_none() {
-                diagnostic.code = Some(NumberOrString::String(category.code().to_string()));
+                diagnostic.code = Some(NumberOrString::String(rule.code().to_string()));
             }
-            diagnostic
+            Some(diagnostic)
         })
         .collect()
 }
 
 pub(in crate::server) fn diagnostic_at_byte_range(
     range: (u32, u32, u32, u32),
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     message: String,
 ) -> Diagnostic {
     Diagnostic {
         range: Range {
-            start: Position::new(range.0, utf16_index.byte_col_to_utf16(range.0, range.1)),
-            end: Position::new(range.2, utf16_index.byte_col_to_utf16(range.2, range.3)),
+            start: Position::new(range.0, line_index.byte_col_to_lsp(range.0, range.1)),
+            end: Position::new(range.2, line_index.byte_col_to_lsp(range.2, range.3)),
         },
         severity: Some(DiagnosticSeverity::WARNING),
         source: Some("php-lsp".to_string()),
@@ -1218,7 +1534,7 @@ pub(in crate::server) fn member_access_diagnostics(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     framework_cache: &crate::framework::FrameworkProviderCache,
 ) -> Vec<Diagnostic> {
@@ -1230,7 +1546,7 @@ pub(in crate::server) fn member_access_diagnostics(
         source,
         file_symbols,
         index,
-        utf16_index,
+        line_index,
         type_cache,
         framework_cache,
         &mut diagnostics,
@@ -1246,7 +1562,7 @@ pub(in crate::server) fn walk_member_access_diagnostics(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     framework_cache: &crate::framework::FrameworkProviderCache,
     diagnostics: &mut Vec<Diagnostic>,
@@ -1266,7 +1582,7 @@ pub(in crate::server) fn walk_member_access_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             framework_cache,
             diagnostics,
@@ -1282,7 +1598,7 @@ pub(in crate::server) fn walk_member_access_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             framework_cache,
             diagnostics,
@@ -1298,7 +1614,7 @@ pub(in crate::server) fn check_member_access_node(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     framework_cache: &crate::framework::FrameworkProviderCache,
     diagnostics: &mut Vec<Diagnostic>,
@@ -1357,8 +1673,51 @@ pub(in crate::server) fn check_member_access_node(
             | RefKind::PropertyAccess
             | RefKind::StaticPropertyAccess
             | RefKind::ClassConstant
-    ) || !sym_at_pos.fqn.contains("::")
-    {
+    ) {
+        return;
+    }
+    if let Some(var_name) = plain_receiver_variable(&sym_at_pos) {
+        let receiver_type = type_cache.cached_type_info(
+            sym_at_pos.range,
+            "diagnostic-receiver-type",
+            var_name,
+            || {
+                php_lsp_parser::resolve::infer_variable_type_info_at_position_with_resolvers(
+                    tree,
+                    source,
+                    file_symbols,
+                    pos.row as u32,
+                    pos.column as u32,
+                    var_name,
+                    Some(&member_type_resolver),
+                    Some(&callable_param_resolver),
+                )
+            },
+        );
+        if receiver_type.as_ref().is_some_and(type_info_may_be_null) {
+            if let Some(diagnostic) =
+                possible_null_dereference_diagnostic(node, var_name, source, line_index)
+            {
+                diagnostics.push(diagnostic);
+            }
+        }
+        if let Some(php_lsp_types::TypeInfo::Union(branches)) = receiver_type {
+            if check_union_member_access(
+                &branches,
+                uri_str,
+                source,
+                file_symbols,
+                index,
+                line_index,
+                framework_cache,
+                &sym_at_pos,
+                diagnostics,
+            ) {
+                return;
+            }
+        }
+    }
+    if !sym_at_pos.fqn.contains("::") {
         return;
     }
 
@@ -1388,66 +1747,601 @@ pub(in crate::server) fn check_member_access_node(
 
         diagnostics.push(member_diagnostic(
             &sym_at_pos,
-            utf16_index,
+            line_index,
             unknown_member_message(&sym_at_pos),
         ));
         return;
     };
 
+    let invalid_access = |message| {
+        let mut diagnostic = member_diagnostic(&sym_at_pos, line_index, message);
+        diagnostic.code = Some(NumberOrString::String(
+            DiagnosticRule::InvalidMemberAccess.code().to_string(),
+        ));
+        diagnostic
+    };
     if let Some(message) = static_instance_misuse_message(node.kind(), &sym_at_pos, &resolved) {
-        diagnostics.push(member_diagnostic(&sym_at_pos, utf16_index, message));
+        diagnostics.push(invalid_access(message));
     }
 
     if let Some(message) =
         visibility_violation_message(index, &resolved, file_symbols, sym_at_pos.range)
     {
-        diagnostics.push(member_diagnostic(&sym_at_pos, utf16_index, message));
+        diagnostics.push(invalid_access(message));
     }
 }
 
-pub(in crate::server) fn member_reference_name_node(
-    node: tree_sitter::Node,
-) -> Option<tree_sitter::Node> {
-    node.child_by_field_name("name").or_else(|| {
-        if node.kind() == "class_constant_access_expression" {
-            node.named_child(1)
-        } else {
-            None
-        }
-    })
+/// The plain variable an instance member is accessed on, for receiver checks.
+fn plain_receiver_variable(sym_at_pos: &SymbolAtPosition) -> Option<&str> {
+    if !matches!(
+        sym_at_pos.ref_kind,
+        RefKind::MethodCall | RefKind::PropertyAccess
+    ) {
+        return None;
+    }
+    sym_at_pos
+        .object_expr
+        .as_deref()
+        .filter(|expr| expr.starts_with('$') && *expr != "$this" && is_simple_variable(expr))
 }
 
-pub(in crate::server) fn is_phpunit_testcase_helper_call(
-    sym_at_pos: &SymbolAtPosition,
-    file_symbols: &php_lsp_types::FileSymbols,
-    index: &WorkspaceIndex,
-) -> bool {
-    if sym_at_pos.ref_kind != RefKind::MethodCall
-        || !file_is_phpunit_test_context(file_symbols, index)
-        || !phpunit_testcase_helper_method(&sym_at_pos.name)
-    {
-        return false;
-    }
+fn is_simple_variable(expr: &str) -> bool {
+    expr[1..]
+        .chars()
+        .all(|ch| ch == '_' || ch.is_alphanumeric())
+}
 
-    matches!(
-        sym_at_pos.object_expr.as_deref(),
-        Some("$this" | "self" | "static" | "parent")
-    )
+/// Whether `type_info` is `null`, `?A` or a union with a `null` branch. A
+/// plain `null` is what a receiver narrows to inside `if ($x === null)`.
+fn type_info_may_be_null(type_info: &php_lsp_types::TypeInfo) -> bool {
+    let is_null = |branch: &php_lsp_types::TypeInfo| match branch {
+        php_lsp_types::TypeInfo::LiteralNull => true,
+        php_lsp_types::TypeInfo::Simple(name) => name.eq_ignore_ascii_case("null"),
+        _ => false,
+    };
+    match type_info {
+        php_lsp_types::TypeInfo::Nullable(_) => true,
+        php_lsp_types::TypeInfo::Union(branches) => branches.iter().any(is_null),
+        other => is_null(other),
+    }
 }
 
-pub(in crate::server) fn file_is_phpunit_test_context(
-    file_symbols: &php_lsp_types::FileSymbols,
-    index: &WorkspaceIndex,
-) -> bool {
-    file_symbols.symbols.iter().any(|sym| {
-        matches!(sym.kind, php_lsp_types::PhpSymbolKind::Class)
-            && sym.extends.iter().any(|parent| {
-                is_phpunit_testcase_like_fqn(parent)
-                    || class_extends_or_implements(
-                        index,
-                        parent.trim_start_matches('\\'),
-                        "PHPUnit\\Framework\\TestCase",
-                        &mut Vec::new(),
+/// Warn about `$var->member` on a nullable `$var`, offering `?->` as the fix.
+///
+/// Nullsafe access is exempt, and so is an access that only runs once a null
+/// check on `$var` passed: inside an `if`, `while`, ternary or `&&`/`||` branch
+/// guarded by `!== null`, `instanceof`, `isset()` or the variable's truthiness,
+/// or after an `if`/`elseif` chain that exits on null, an `assert()` of such a
+/// check or a `??=`, with no assignment to `$var` in between.
+fn possible_null_dereference_diagnostic(
+    node: tree_sitter::Node,
+    var_name: &str,
+    source: &str,
+    line_index: &LspLineIndex,
+) -> Option<Diagnostic> {
+    if !matches!(
+        node.kind(),
+        "member_call_expression" | "member_access_expression"
+    ) {
+        return None;
+    }
+    let object = node.child_by_field_name("object")?;
+    let arrow = (0..node.child_count())
+        .filter_map(|i| node.child(i))
+        .find(|child| child.kind() == "->")?;
+    let scope = std::iter::successors(node.parent(), |current| current.parent())
+        .find(|ancestor| {
+            matches!(
+                ancestor.kind(),
+                "function_definition"
+                    | "method_declaration"
+                    | "anonymous_function"
+                    | "anonymous_function_creation_expression"
+                    | "arrow_function"
+            )
+        })
+        .unwrap_or_else(|| {
+            std::iter::successors(Some(node), |current| current.parent())
+                .last()
+                .unwrap_or(node)
+        });
+    if is_null_guarded(node, scope, var_name, source) {
+        return None;
+    }
+
+    let byte_range = |node: tree_sitter::Node| {
+        let (start, end) = (node.start_position(), node.end_position());
+        (
+            start.row as u32,
+            start.column as u32,
+            end.row as u32,
+            end.column as u32,
+        )
+    };
+    let arrow_range = diagnostic_at_byte_range(byte_range(arrow), line_index, String::new()).range;
+    let mut diagnostic = diagnostic_at_byte_range(
+        byte_range(object),
+        line_index,
+        format!("Possible null dereference: {var_name} may be null"),
+    );
+    diagnostic.code = Some(NumberOrString::String(
+        DiagnosticRule::PossibleNullDereference.code().to_string(),
+    ));
+    diagnostic.data = Some(serde_json::json!({
+        "replacement": {
+            "newText": "?->",
+            "title": "Use nullsafe operator `?->`",
+            "range": arrow_range,
+        }
+    }));
+    Some(diagnostic)
+}
+
+/// Whether a null check on `var_name` dominates `access` within `scope`, walking
+/// up from the access through enclosing branches and earlier statements. An
+/// assignment to the variable on the way up voids any guard before it.
+fn is_null_guarded(
+    access: tree_sitter::Node,
+    scope: tree_sitter::Node,
+    var_name: &str,
+    source: &str,
+) -> bool {
+    let mut child = access;
+    while child.id() != scope.id() {
+        let Some(parent) = child.parent() else {
+            break;
+        };
+        if branch_is_null_guarded(parent, child, var_name, source) {
+            return true;
+        }
+        let mut previous = child.prev_named_sibling();
+        while let Some(statement) = previous {
+            if statement_guards_following(statement, var_name, source) {
+                return true;
+            }
+            if assigns_variable(statement, var_name, source) {
+                return false;
+            }
+            previous = statement.prev_named_sibling();
+        }
+        child = parent;
+    }
+    false
+}
+
+/// Whether `child` of `parent` only runs once a null check on the variable
+/// passed, e.g. the body of `if ($x !== null)` or the right of `$x && ...`.
+fn branch_is_null_guarded(
+    parent: tree_sitter::Node,
+    child: tree_sitter::Node,
+    var_name: &str,
+    source: &str,
+) -> bool {
+    let is_field = |field: &str| {
+        parent
+            .child_by_field_name(field)
+            .is_some_and(|node| node.id() == child.id())
+    };
+    let condition = |field: &str| parent.child_by_field_name(field);
+    match parent.kind() {
+        "if_statement" if is_field("body") => condition("condition")
+            .is_some_and(|condition| check_passes_only_if_non_null(condition, var_name, source)),
+        "if_statement" if matches!(child.kind(), "else_clause" | "else_if_clause") => {
+            condition("condition")
+                .is_some_and(|condition| check_passes_if_null(condition, var_name, source))
+        }
+        "else_if_clause" | "while_statement" if is_field("body") => condition("condition")
+            .is_some_and(|condition| check_passes_only_if_non_null(condition, var_name, source)),
+        "conditional_expression" if is_field("body") => condition("condition")
+            .is_some_and(|condition| check_passes_only_if_non_null(condition, var_name, source)),
+        "conditional_expression" if is_field("alternative") => condition("condition")
+            .is_some_and(|condition| check_passes_if_null(condition, var_name, source)),
+        "binary_expression" if is_field("right") => {
+            let Some(left) = condition("left") else {
+                return false;
+            };
+            match operator_text(parent, source).to_ascii_lowercase().as_str() {
+                "&&" | "and" => check_passes_only_if_non_null(left, var_name, source),
+                "||" | "or" => check_passes_if_null(left, var_name, source),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether code after `statement` only runs with the variable non-null: an
+/// `if`/`elseif` chain that exits on null, `assert()` of a null check, or `??=`.
+fn statement_guards_following(statement: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    match statement.kind() {
+        "if_statement" => if_chain_exits_on_null(statement, var_name, source),
+        "expression_statement" => {
+            statement
+                .named_child(0)
+                .is_some_and(|expression| match expression.kind() {
+                    "function_call_expression" if is_call_to(expression, "assert", source) => {
+                        call_arguments(expression, source)
+                            .first()
+                            .is_some_and(|argument| {
+                                check_passes_only_if_non_null(argument.value_node, var_name, source)
+                            })
+                    }
+                    "augmented_assignment_expression" => {
+                        operator_text(expression, source) == "??="
+                            && expression
+                                .child_by_field_name("left")
+                                .is_some_and(|left| is_variable(left, var_name, source))
+                    }
+                    _ => false,
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Whether an `if`/`elseif` chain has a branch testing the variable for null
+/// and every branch that can be taken while it is null, up to and including
+/// that one, exits. Later branches and `else` only run with it non-null.
+fn if_chain_exits_on_null(statement: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    let mut cursor = statement.walk();
+    let branches = std::iter::once(statement).chain(
+        statement
+            .children_by_field_name("alternative", &mut cursor)
+            .filter(|alternative| alternative.kind() == "else_if_clause"),
+    );
+    for branch in branches {
+        let Some(condition) = branch.child_by_field_name("condition") else {
+            return false;
+        };
+        let exits = branch.child_by_field_name("body").is_some_and(always_exits);
+        if check_passes_if_null(condition, var_name, source) {
+            return exits;
+        }
+        if !exits && !check_passes_only_if_non_null(condition, var_name, source) {
+            return false;
+        }
+    }
+    false
+}
+
+/// Whether `node` assigns to or unsets the variable itself, outside any
+/// nested function.
+fn assigns_variable(node: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    let targets_variable = |target: tree_sitter::Node| {
+        is_variable(target, var_name, source)
+            || (matches!(target.kind(), "list_literal" | "array_creation_expression")
+                && node_contains_variable(target, var_name, source))
+    };
+    match node.kind() {
+        "anonymous_function" | "arrow_function" | "function_definition" | "class_declaration" => {
+            return false
+        }
+        "assignment_expression"
+        | "reference_assignment_expression"
+        | "augmented_assignment_expression"
+            if node
+                .child_by_field_name("left")
+                .is_some_and(targets_variable) =>
+        {
+            return true;
+        }
+        "unset_statement" if node_contains_variable(node, var_name, source) => return true,
+        _ => {}
+    }
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|child| assigns_variable(child, var_name, source))
+}
+
+fn node_contains_variable(node: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    is_variable(node, var_name, source)
+        || (0..node.named_child_count())
+            .filter_map(|i| node.named_child(i))
+            .any(|child| node_contains_variable(child, var_name, source))
+}
+
+/// Whether `check` can only be true when the variable is not null: `!== null`,
+/// `instanceof`, `isset()`, `!is_null()` or the variable's own truthiness.
+fn check_passes_only_if_non_null(check: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    match check.kind() {
+        "parenthesized_expression" => check
+            .named_child(0)
+            .is_some_and(|inner| check_passes_only_if_non_null(inner, var_name, source)),
+        "variable_name" => is_variable(check, var_name, source),
+        "unary_op_expression" => {
+            operator_text(check, source) == "!"
+                && check
+                    .child_by_field_name("argument")
+                    .is_some_and(|argument| check_passes_if_null(argument, var_name, source))
+        }
+        "function_call_expression" => {
+            is_call_to(check, "isset", source)
+                && call_arguments(check, source)
+                    .iter()
+                    .any(|argument| is_variable(argument.value_node, var_name, source))
+        }
+        "binary_expression" => {
+            let (Some(left), Some(right)) = (
+                check.child_by_field_name("left"),
+                check.child_by_field_name("right"),
+            ) else {
+                return false;
+            };
+            match operator_text(check, source).to_ascii_lowercase().as_str() {
+                "!==" | "!=" => compares_with_null(left, right, var_name, source),
+                "instanceof" => is_variable(left, var_name, source),
+                "&&" | "and" => {
+                    check_passes_only_if_non_null(left, var_name, source)
+                        || check_passes_only_if_non_null(right, var_name, source)
+                }
+                "||" | "or" => {
+                    check_passes_only_if_non_null(left, var_name, source)
+                        && check_passes_only_if_non_null(right, var_name, source)
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether `check` is always true when the variable is null, so getting past
+/// it as false proves the variable non-null.
+fn check_passes_if_null(check: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    match check.kind() {
+        "parenthesized_expression" => check
+            .named_child(0)
+            .is_some_and(|inner| check_passes_if_null(inner, var_name, source)),
+        "unary_op_expression" => {
+            operator_text(check, source) == "!"
+                && check
+                    .child_by_field_name("argument")
+                    .is_some_and(|argument| {
+                        check_passes_only_if_non_null(argument, var_name, source)
+                    })
+        }
+        "function_call_expression" => {
+            is_call_to(check, "is_null", source)
+                && call_arguments(check, source)
+                    .first()
+                    .is_some_and(|argument| is_variable(argument.value_node, var_name, source))
+        }
+        "binary_expression" => {
+            let (Some(left), Some(right)) = (
+                check.child_by_field_name("left"),
+                check.child_by_field_name("right"),
+            ) else {
+                return false;
+            };
+            match operator_text(check, source).to_ascii_lowercase().as_str() {
+                "===" | "==" => compares_with_null(left, right, var_name, source),
+                "||" | "or" => {
+                    check_passes_if_null(left, var_name, source)
+                        || check_passes_if_null(right, var_name, source)
+                }
+                "&&" | "and" => {
+                    check_passes_if_null(left, var_name, source)
+                        && check_passes_if_null(right, var_name, source)
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn compares_with_null(
+    left: tree_sitter::Node,
+    right: tree_sitter::Node,
+    var_name: &str,
+    source: &str,
+) -> bool {
+    (is_variable(left, var_name, source) && right.kind() == "null")
+        || (left.kind() == "null" && is_variable(right, var_name, source))
+}
+
+/// Whether every path through `body` leaves it via `return`, `throw`,
+/// `break`, `continue` or `exit`.
+fn always_exits(body: tree_sitter::Node) -> bool {
+    match body.kind() {
+        "compound_statement" => (0..body.named_child_count())
+            .rev()
+            .filter_map(|i| body.named_child(i))
+            .find(|statement| statement.kind() != "comment")
+            .is_some_and(always_exits),
+        "return_statement" | "break_statement" | "continue_statement" | "exit_statement" => true,
+        "expression_statement" => body
+            .named_child(0)
+            .is_some_and(|expression| expression.kind() == "throw_expression"),
+        _ => false,
+    }
+}
+
+fn is_variable(node: tree_sitter::Node, var_name: &str, source: &str) -> bool {
+    node.kind() == "variable_name" && &source[node.byte_range()] == var_name
+}
+
+fn is_call_to(node: tree_sitter::Node, name: &str, source: &str) -> bool {
+    node.kind() == "function_call_expression"
+        && node
+            .child_by_field_name("function")
+            .is_some_and(|function| {
+                function.utf8_text(source.as_bytes()).is_ok_and(|function| {
+                    function.trim_start_matches('\\').eq_ignore_ascii_case(name)
+                })
+            })
+}
+
+fn operator_text<'a>(node: tree_sitter::Node, source: &'a str) -> &'a str {
+    node.child_by_field_name("operator")
+        .and_then(|operator| operator.utf8_text(source.as_bytes()).ok())
+        .unwrap_or_default()
+}
+
+/// Report a member accessed on an `A|B` receiver that some class branch lacks.
+///
+/// Returns whether the receiver was a union of at least two indexed classes;
+/// otherwise the caller checks the access against its single resolved class.
+/// `null` and scalar branches are ignored. `instanceof` guards narrow the
+/// receiver before it gets here, so guarded accesses see one class.
+#[allow(clippy::too_many_arguments)]
+fn check_union_member_access(
+    branches: &[php_lsp_types::TypeInfo],
+    uri_str: &str,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    line_index: &LspLineIndex,
+    framework_cache: &crate::framework::FrameworkProviderCache,
+    sym_at_pos: &SymbolAtPosition,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> bool {
+    let mut classes: Vec<std::sync::Arc<php_lsp_types::SymbolInfo>> = Vec::new();
+    for branch in branches {
+        let class_name = match branch {
+            php_lsp_types::TypeInfo::Simple(name) if !is_builtin_type_name(name) => name,
+            php_lsp_types::TypeInfo::Simple(_)
+            | php_lsp_types::TypeInfo::LiteralString(_)
+            | php_lsp_types::TypeInfo::LiteralInt(_)
+            | php_lsp_types::TypeInfo::LiteralFloat(_)
+            | php_lsp_types::TypeInfo::LiteralBool(_)
+            | php_lsp_types::TypeInfo::LiteralNull => continue,
+            _ => return false,
+        };
+        let Some(class_sym) = index
+            .resolve_fqn(class_name.trim_start_matches('\\'))
+            .or_else(|| {
+                index.resolve_fqn(&php_lsp_parser::resolve::resolve_class_name(
+                    class_name,
+                    file_symbols,
+                ))
+            })
+        else {
+            return false;
+        };
+        if !classes.iter().any(|seen| seen.fqn == class_sym.fqn) {
+            classes.push(class_sym);
+        }
+    }
+    if classes.len() < 2 {
+        return false;
+    }
+
+    let member = sym_at_pos
+        .fqn
+        .rsplit_once("::")
+        .map_or(sym_at_pos.fqn.as_str(), |(_, member)| member);
+    let missing: Vec<&std::sync::Arc<php_lsp_types::SymbolInfo>> = classes
+        .iter()
+        .filter(|class_sym| {
+            let branch_sym = SymbolAtPosition {
+                fqn: format!("{}::{member}", class_sym.fqn),
+                ..sym_at_pos.clone()
+            };
+            resolve_member_for_ref_kind(index, &branch_sym).is_none()
+                && !is_dynamic_member_access(
+                    index,
+                    file_symbols,
+                    uri_str,
+                    source,
+                    &branch_sym,
+                    framework_cache,
+                )
+        })
+        .collect();
+    if missing.is_empty() {
+        return true;
+    }
+
+    let union_display = classes
+        .iter()
+        .map(|class_sym| class_sym.name.as_str())
+        .collect::<Vec<_>>()
+        .join("|");
+    let (message, member_label) = match sym_at_pos.ref_kind {
+        RefKind::MethodCall => (
+            format!(
+                "Method {}() does not exist on {union_display}",
+                sym_at_pos.name
+            ),
+            format!("{}()", sym_at_pos.name),
+        ),
+        _ => (
+            format!(
+                "Property ${} does not exist on {union_display}",
+                sym_at_pos.name
+            ),
+            format!("${}", sym_at_pos.name),
+        ),
+    };
+    let related_information = missing
+        .iter()
+        .filter_map(|class_sym| {
+            symbol_related_information(
+                class_sym,
+                uri_str,
+                line_index,
+                format!("{member_label} is not declared on {}", class_sym.fqn),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut diagnostic = member_diagnostic(sym_at_pos, line_index, message);
+    diagnostic.code = Some(NumberOrString::String(
+        DiagnosticRule::UnknownUnionMember.code().to_string(),
+    ));
+    diagnostic.related_information = Some(related_information);
+    diagnostics.push(diagnostic);
+    true
+}
+
+pub(in crate::server) fn member_reference_name_node(
+    node: tree_sitter::Node,
+) -> Option<tree_sitter::Node> {
+    node.child_by_field_name("name").or_else(|| {
+        if node.kind() == "class_constant_access_expression" {
+            node.named_child(1)
+        } else {
+            None
+        }
+    })
+}
+
+pub(in crate::server) fn is_phpunit_testcase_helper_call(
+    sym_at_pos: &SymbolAtPosition,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+) -> bool {
+    if sym_at_pos.ref_kind != RefKind::MethodCall
+        || !file_is_phpunit_test_context(file_symbols, index)
+        || !phpunit_testcase_helper_method(&sym_at_pos.name)
+    {
+        return false;
+    }
+
+    matches!(
+        sym_at_pos
+            .object_expr
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref(),
+        Some("$this" | "self" | "static" | "parent")
+    )
+}
+
+pub(in crate::server) fn file_is_phpunit_test_context(
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+) -> bool {
+    file_symbols.symbols.iter().any(|sym| {
+        matches!(sym.kind, php_lsp_types::PhpSymbolKind::Class)
+            && sym.extends.iter().any(|parent| {
+                is_phpunit_testcase_like_fqn(parent)
+                    || class_extends_or_implements(
+                        index,
+                        parent.trim_start_matches('\\'),
+                        "PHPUnit\\Framework\\TestCase",
+                        &mut Vec::new(),
                     )
             })
     }) || file_symbols.symbols.iter().any(|sym| {
@@ -1779,7 +2673,10 @@ pub(in crate::server) fn class_has_magic_get(index: &WorkspaceIndex, class_fqn:
 pub(in crate::server) fn is_missing_parent_constructor_call(sym_at_pos: &SymbolAtPosition) -> bool {
     sym_at_pos.ref_kind == RefKind::MethodCall
         && sym_at_pos.name == "__construct"
-        && sym_at_pos.object_expr.as_deref() == Some("parent")
+        && sym_at_pos
+            .object_expr
+            .as_deref()
+            .is_some_and(|scope| scope.eq_ignore_ascii_case("parent"))
 }
 
 pub(in crate::server) fn is_enum_builtin_method_call(
@@ -1883,10 +2780,10 @@ pub(in crate::server) fn is_magic_class_constant_access(
 
 pub(in crate::server) fn member_diagnostic(
     sym_at_pos: &SymbolAtPosition,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     message: String,
 ) -> Diagnostic {
-    diagnostic_at_byte_range(sym_at_pos.range, utf16_index, message)
+    diagnostic_at_byte_range(sym_at_pos.range, line_index, message)
 }
 
 pub(in crate::server) fn symbol_kind_matches_ref_kind(
@@ -2052,7 +2949,11 @@ pub(in crate::server) fn static_instance_misuse_message(
             )),
             ("scoped_call_expression", false)
                 if matches!(
-                    sym_at_pos.object_expr.as_deref(),
+                    sym_at_pos
+                        .object_expr
+                        .as_deref()
+                        .map(str::to_ascii_lowercase)
+                        .as_deref(),
                     Some("self" | "static" | "parent")
                 ) =>
             {
@@ -2109,7 +3010,7 @@ pub(in crate::server) fn current_class_fqn_at_range(
     file_symbols: &php_lsp_types::FileSymbols,
     range: (u32, u32, u32, u32),
 ) -> Option<String> {
-    current_class_symbol_at_range(file_symbols, range).map(|sym| sym.fqn.clone())
+    current_class_symbol_at_range(file_symbols, range).map(|sym| sym.fqn.to_string())
 }
 
 pub(in crate::server) fn current_class_symbol_at_range(
@@ -2295,12 +3196,220 @@ pub(in crate::server) struct InferredExprType {
     pub(in crate::server) range: (u32, u32, u32, u32),
 }
 
+/// Exceptions a callee throws (its `Signature::throws`) that the calling
+/// function or method neither catches nor declares with `@throws`. Calls in
+/// closures and top-level code are not checked.
+pub(in crate::server) fn unhandled_exception_diagnostics(
+    tree: &tree_sitter::Tree,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    line_index: &LspLineIndex,
+    type_cache: &RequestTypeCache,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if matches!(
+            node.kind(),
+            "function_call_expression"
+                | "member_call_expression"
+                | "nullsafe_member_call_expression"
+                | "scoped_call_expression"
+                | "object_creation_expression"
+        ) {
+            check_call_unhandled_exceptions(
+                tree,
+                node,
+                source,
+                file_symbols,
+                index,
+                line_index,
+                type_cache,
+                &mut diagnostics,
+            );
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    diagnostics.sort_by_key(|diagnostic| {
+        (
+            diagnostic.range.start.line,
+            diagnostic.range.start.character,
+        )
+    });
+    diagnostics
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_call_unhandled_exceptions(
+    tree: &tree_sitter::Tree,
+    node: tree_sitter::Node,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    line_index: &LspLineIndex,
+    type_cache: &RequestTypeCache,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(callable_node) = std::iter::successors(node.parent(), |current| current.parent())
+        .find(|ancestor| {
+            matches!(
+                ancestor.kind(),
+                "function_definition"
+                    | "method_declaration"
+                    | "anonymous_function"
+                    | "arrow_function"
+            )
+        })
+        .filter(|ancestor| {
+            matches!(
+                ancestor.kind(),
+                "function_definition" | "method_declaration"
+            )
+        })
+    else {
+        return;
+    };
+    let Some(caller) = containing_callable_symbol(file_symbols, node_range_node(node)) else {
+        return;
+    };
+
+    let name_node = if node.kind() == "object_creation_expression" {
+        object_creation_class_node(node)
+    } else {
+        member_reference_name_node(node).or_else(|| node.child_by_field_name("function"))
+    };
+    let Some(name_node) = name_node else {
+        return;
+    };
+    let Some((_, callee)) = resolve_reference_symbol_at_node_cached(
+        tree,
+        source,
+        name_node,
+        file_symbols,
+        index,
+        type_cache,
+    ) else {
+        return;
+    };
+    let callee = if node.kind() == "object_creation_expression"
+        && callee.kind != php_lsp_types::PhpSymbolKind::Method
+    {
+        let Some(constructor) = index.get_members(&callee.fqn).into_iter().find(|member| {
+            member.kind == php_lsp_types::PhpSymbolKind::Method
+                && member.name.eq_ignore_ascii_case("__construct")
+        }) else {
+            return;
+        };
+        constructor
+    } else {
+        callee
+    };
+    let Some(thrown) = callee
+        .signature
+        .as_ref()
+        .map(|sig| &sig.throws)
+        .filter(|throws| !throws.is_empty())
+    else {
+        return;
+    };
+
+    let declared = caller
+        .doc_comment
+        .as_deref()
+        .map(|doc| parse_phpdoc(doc).throws)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|type_info| match type_info {
+            php_lsp_types::TypeInfo::Union(members) => members,
+            other => vec![other],
+        })
+        .filter_map(|type_info| match type_info {
+            php_lsp_types::TypeInfo::Simple(name) => {
+                Some(resolve_class_name_pub(&name, file_symbols))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let caught = enclosing_catch_types(node, callable_node, source, file_symbols);
+
+    for exception in thrown {
+        let handled = declared
+            .iter()
+            .chain(caught.iter())
+            .any(|handler| exception_is_handled_by(index, exception, handler));
+        if handled {
+            continue;
+        }
+        diagnostics.push(diagnostic_at_byte_range(
+            node_range_node(name_node),
+            line_index,
+            format!(
+                "Unhandled exception: {exception} thrown by {}() is neither caught nor declared with @throws",
+                callee.name
+            ),
+        ));
+    }
+}
+
+/// Class names of the `catch` clauses of every `try` whose body contains `node`.
+fn enclosing_catch_types(
+    node: tree_sitter::Node,
+    callable_node: tree_sitter::Node,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+) -> Vec<String> {
+    let mut caught = Vec::new();
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        if parent.id() == callable_node.id() {
+            break;
+        }
+        let in_try_body = parent.kind() == "try_statement"
+            && parent
+                .child_by_field_name("body")
+                .is_some_and(|body| body.id() == child.id());
+        if in_try_body {
+            let mut cursor = parent.walk();
+            for clause in parent
+                .named_children(&mut cursor)
+                .filter(|clause| clause.kind() == "catch_clause")
+            {
+                let Some(types) = clause.child_by_field_name("type") else {
+                    continue;
+                };
+                let mut type_cursor = types.walk();
+                caught.extend(types.named_children(&mut type_cursor).map(|caught_type| {
+                    resolve_class_name_pub(&source[caught_type.byte_range()], file_symbols)
+                }));
+            }
+        }
+        child = parent;
+    }
+    caught
+}
+
+fn exception_is_handled_by(index: &WorkspaceIndex, exception: &str, handler: &str) -> bool {
+    if fqn_matches(exception, handler) || fqn_matches(handler, "Throwable") {
+        return true;
+    }
+    if index
+        .resolve_fqn(exception.trim_start_matches('\\'))
+        .is_none()
+    {
+        // Without the class hierarchy, only the catch-all `Exception` is known.
+        return fqn_matches(handler, "Exception");
+    }
+    class_extends_or_implements(index, exception, handler, &mut Vec::new())
+}
+
 pub(in crate::server) fn type_compatibility_diagnostics(
     tree: &tree_sitter::Tree,
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
@@ -2310,7 +3419,7 @@ pub(in crate::server) fn type_compatibility_diagnostics(
         source,
         file_symbols,
         index,
-        utf16_index,
+        line_index,
         type_cache,
         &mut diagnostics,
     );
@@ -2324,7 +3433,7 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2335,7 +3444,7 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         ),
@@ -2346,7 +3455,7 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
                 source,
                 file_symbols,
                 index,
-                utf16_index,
+                line_index,
                 type_cache,
                 diagnostics,
             )
@@ -2357,7 +3466,7 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         ),
@@ -2366,7 +3475,7 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         ),
@@ -2376,7 +3485,17 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
+            type_cache,
+            diagnostics,
+        ),
+        "match_expression" => check_match_enum_exhaustiveness(
+            tree,
+            node,
+            source,
+            file_symbols,
+            index,
+            line_index,
             type_cache,
             diagnostics,
         ),
@@ -2391,7 +3510,7 @@ pub(in crate::server) fn walk_type_compatibility_diagnostics(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         );
@@ -2405,7 +3524,7 @@ pub(in crate::server) fn check_function_call_type_compatibility(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2433,7 +3552,7 @@ pub(in crate::server) fn check_function_call_type_compatibility(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         );
@@ -2447,7 +3566,7 @@ pub(in crate::server) fn check_member_call_type_compatibility(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2472,13 +3591,232 @@ pub(in crate::server) fn check_member_call_type_compatibility(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         );
     }
 }
 
+/// Report enum cases a `match` on an enum-typed subject leaves unhandled.
+///
+/// Matches with a `default` arm, or with an arm condition other than a case of
+/// the subject's enum, are skipped. The diagnostic carries a replacement that
+/// appends throwing stub arms for the missing cases.
+#[allow(clippy::too_many_arguments)]
+pub(in crate::server) fn check_match_enum_exhaustiveness(
+    tree: &tree_sitter::Tree,
+    node: tree_sitter::Node,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    line_index: &LspLineIndex,
+    type_cache: &RequestTypeCache,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+    let arms = (0..body.named_child_count())
+        .filter_map(|i| body.named_child(i))
+        .collect::<Vec<_>>();
+    if arms
+        .iter()
+        .any(|arm| arm.kind() != "match_conditional_expression")
+    {
+        return;
+    }
+    let Some(subject) = node
+        .child_by_field_name("condition")
+        .map(normalized_expression_node)
+    else {
+        return;
+    };
+    let Some(enum_sym) = match_subject_enum(tree, subject, source, file_symbols, index, type_cache)
+    else {
+        return;
+    };
+
+    let mut handled = HashSet::new();
+    let mut written_enum_name = None;
+    for arm in &arms {
+        let Some(conditions) = arm.child_by_field_name("conditional_expressions") else {
+            return;
+        };
+        for condition in
+            (0..conditions.named_child_count()).filter_map(|i| conditions.named_child(i))
+        {
+            let case = (condition.kind() == "class_constant_access_expression")
+                .then(|| member_reference_name_node(condition))
+                .flatten()
+                .and_then(|name_node| {
+                    resolve_reference_symbol_at_node_cached(
+                        tree,
+                        source,
+                        name_node,
+                        file_symbols,
+                        index,
+                        type_cache,
+                    )
+                })
+                .map(|(_, case)| case)
+                .filter(|case| {
+                    case.kind == php_lsp_types::PhpSymbolKind::EnumCase
+                        && case.parent_fqn.as_deref() == Some(enum_sym.fqn.as_str())
+                });
+            let Some(case) = case else {
+                return;
+            };
+            handled.insert(case.name.clone());
+            written_enum_name.get_or_insert_with(|| {
+                source[condition.named_child(0).unwrap_or(condition).byte_range()].to_string()
+            });
+        }
+    }
+
+    let mut cases = index
+        .get_members(&enum_sym.fqn)
+        .into_iter()
+        .filter(|member| member.kind == php_lsp_types::PhpSymbolKind::EnumCase)
+        .collect::<Vec<_>>();
+    cases.sort_by_key(|case| (case.range.0, case.range.1));
+    let enum_name = written_enum_name.unwrap_or_else(|| enum_sym.name.clone());
+    let missing = cases
+        .iter()
+        .filter(|case| !handled.contains(&case.name))
+        .map(|case| format!("{enum_name}::{}", case.name))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return;
+    }
+
+    let keyword = node.child(0).unwrap_or(node);
+    let mut diagnostic = diagnostic_at_byte_range(
+        node_range_node(keyword),
+        line_index,
+        format!(
+            "Match on {} is not exhaustive; missing {}",
+            enum_sym.name,
+            missing.join(", ")
+        ),
+    );
+    diagnostic.code = Some(NumberOrString::String(
+        DiagnosticRule::NonExhaustiveMatch.code().to_string(),
+    ));
+    if let Some(replacement) =
+        match_missing_arms_replacement(body, &arms, &missing, source, line_index)
+    {
+        diagnostic.data = Some(serde_json::json!({ "replacement": replacement }));
+    }
+    diagnostics.push(diagnostic);
+}
+
+fn match_subject_enum(
+    tree: &tree_sitter::Tree,
+    subject: tree_sitter::Node,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    type_cache: &RequestTypeCache,
+) -> Option<Arc<php_lsp_types::SymbolInfo>> {
+    let type_fqn = match subject.kind() {
+        "variable_name" => {
+            let pos = subject.start_position();
+            let member_type_resolver = |class_fqn: &str, member_name: &str| {
+                resolve_member_type_from_index(index, class_fqn, member_name)
+            };
+            php_lsp_parser::resolve::infer_variable_type_at_position_with_resolver(
+                tree,
+                source,
+                file_symbols,
+                pos.row as u32,
+                pos.column as u32,
+                &source[subject.byte_range()],
+                &member_type_resolver,
+            )?
+        }
+        "member_access_expression"
+        | "member_call_expression"
+        | "nullsafe_member_access_expression"
+        | "nullsafe_member_call_expression"
+        | "scoped_call_expression"
+        | "scoped_property_access_expression"
+        | "function_call_expression" => {
+            let name_node = member_reference_name_node(subject)
+                .or_else(|| subject.child_by_field_name("function"))?;
+            let (_, sym) = resolve_reference_symbol_at_node_cached(
+                tree,
+                source,
+                name_node,
+                file_symbols,
+                index,
+                type_cache,
+            )?;
+            match sym.fqn.rsplit_once("::") {
+                Some((class_fqn, member_name)) => {
+                    resolve_member_type_from_index(index, class_fqn, member_name)?
+                }
+                None => resolve_function_return_type_from_index(index, &sym.fqn)?,
+            }
+        }
+        _ => return None,
+    };
+    index
+        .resolve_fqn(type_fqn.trim_start_matches('\\'))
+        .filter(|sym| sym.kind == php_lsp_types::PhpSymbolKind::Enum)
+}
+
+/// Replace the gap between the last arm and the closing brace with stub arms,
+/// keeping a trailing comma after every arm.
+fn match_missing_arms_replacement(
+    body: tree_sitter::Node,
+    arms: &[tree_sitter::Node],
+    missing: &[String],
+    source: &str,
+    line_index: &LspLineIndex,
+) -> Option<serde_json::Value> {
+    let close = body.child(body.child_count().checked_sub(1)?)?;
+    if close.kind() != "}" {
+        return None;
+    }
+    let start = arms
+        .last()
+        .map_or(body.start_byte() + 1, |arm| arm.end_byte());
+    let line_indent = |byte: usize| {
+        let line_start = source[..byte].rfind('\n').map_or(0, |pos| pos + 1);
+        source[line_start..]
+            .chars()
+            .take_while(|ch| *ch == ' ' || *ch == '\t')
+            .collect::<String>()
+    };
+    let closing_indent = line_indent(close.start_byte());
+    let arm_indent = arms.first().map_or_else(
+        || format!("{closing_indent}    "),
+        |arm| line_indent(arm.start_byte()),
+    );
+    let mut new_text = if arms.is_empty() { "\n" } else { ",\n" }.to_string();
+    for case in missing {
+        new_text.push_str(&format!(
+            "{arm_indent}{case} => throw new \\LogicException('Not implemented yet.'),\n"
+        ));
+    }
+    new_text.push_str(&closing_indent);
+
+    let position = |byte: usize| {
+        let line = source[..byte].matches('\n').count() as u32;
+        let line_start = source[..byte].rfind('\n').map_or(0, |pos| pos + 1);
+        Position::new(
+            line,
+            line_index.byte_col_to_lsp(line, (byte - line_start) as u32),
+        )
+    };
+    Some(serde_json::json!({
+        "newText": new_text,
+        "title": "Add missing match arms",
+        "range": Range::new(position(start), position(close.start_byte())),
+    }))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(in crate::server) fn check_constructor_type_compatibility(
     tree: &tree_sitter::Tree,
@@ -2486,7 +3824,7 @@ pub(in crate::server) fn check_constructor_type_compatibility(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2511,7 +3849,7 @@ pub(in crate::server) fn check_constructor_type_compatibility(
             source,
             file_symbols,
             index,
-            utf16_index,
+            line_index,
             type_cache,
             diagnostics,
         );
@@ -2525,7 +3863,7 @@ pub(in crate::server) fn check_call_argument_types(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2533,7 +3871,7 @@ pub(in crate::server) fn check_call_argument_types(
         return;
     };
 
-    let callable_file_symbols = index.file_symbols.get(&callable.uri);
+    let callable_file_symbols = index.file_symbols.get(callable.uri.as_str());
     let expected_file_symbols = callable_file_symbols
         .as_ref()
         .map(|entry| entry.value())
@@ -2560,7 +3898,7 @@ pub(in crate::server) fn check_call_argument_types(
         if !type_info_accepts_inferred_type(expected, &actual, expected_file_symbols, index) {
             diagnostics.push(diagnostic_at_byte_range(
                 actual.range,
-                utf16_index,
+                line_index,
                 format!(
                     "Type mismatch for {} argument ${}: expected {}, got {}",
                     callable.fqn, param.name, expected, actual.display
@@ -2575,7 +3913,7 @@ pub(in crate::server) fn check_return_type_compatibility(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2612,7 +3950,7 @@ pub(in crate::server) fn check_return_type_compatibility(
     if !type_info_accepts_inferred_type(expected, &actual, file_symbols, index) {
         diagnostics.push(diagnostic_at_byte_range(
             actual.range,
-            utf16_index,
+            line_index,
             format!(
                 "Return type mismatch in {}: expected {}, got {}",
                 callable.fqn, expected, actual.display
@@ -2628,7 +3966,7 @@ pub(in crate::server) fn check_property_assignment_type_compatibility(
     source: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     type_cache: &RequestTypeCache,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
@@ -2679,7 +4017,7 @@ pub(in crate::server) fn check_property_assignment_type_compatibility(
         return;
     };
 
-    let property_file_symbols = index.file_symbols.get(&property.uri);
+    let property_file_symbols = index.file_symbols.get(property.uri.as_str());
     let expected_file_symbols = property_file_symbols
         .as_ref()
         .map(|entry| entry.value())
@@ -2688,7 +4026,7 @@ pub(in crate::server) fn check_property_assignment_type_compatibility(
     if !type_info_accepts_inferred_type(expected, &actual, expected_file_symbols, index) {
         diagnostics.push(diagnostic_at_byte_range(
             actual.range,
-            utf16_index,
+            line_index,
             format!(
                 "Property assignment type mismatch for {}: expected {}, got {}",
                 property.fqn, expected, actual.display
@@ -3488,7 +4826,7 @@ pub(in crate::server) fn node_range_node(node: tree_sitter::Node) -> (u32, u32,
 pub(in crate::server) fn override_signature_diagnostics(
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
@@ -3524,7 +4862,7 @@ pub(in crate::server) fn override_signature_diagnostics(
                 if parent_method.kind != php_lsp_types::PhpSymbolKind::Method {
                     continue;
                 }
-                let parent_file_symbols_guard = index.file_symbols.get(&parent_method.uri);
+                let parent_file_symbols_guard = index.file_symbols.get(parent_method.uri.as_str());
                 let parent_file_symbols: &php_lsp_types::FileSymbols =
                     match parent_file_symbols_guard.as_ref() {
                         Some(entry) => entry.value(),
@@ -3539,7 +4877,7 @@ pub(in crate::server) fn override_signature_diagnostics(
                 ) {
                     diagnostics.push(diagnostic_at_byte_range(
                         child_method.selection_range,
-                        utf16_index,
+                        line_index,
                         format!(
                             "Incompatible override signature: {} differs from {}",
                             child_method.fqn, parent_method.fqn
@@ -3704,6 +5042,13 @@ pub(in crate::server) fn override_return_type_is_compatible(
     if type_info_is_mixed(parent_type) {
         return true;
     }
+    // A `static` contract can only be narrowed to `static` (or `never`); a
+    // concrete class or `self` would break late static binding in subclasses.
+    if let Some(parent_nullable) = static_return_nullability(parent_type) {
+        return matches!(child_type, php_lsp_types::TypeInfo::Never)
+            || static_return_nullability(child_type)
+                .is_some_and(|child_nullable| parent_nullable || !child_nullable);
+    }
 
     let child_normalized =
         normalized_type_info_for_override(child_type, child_file_symbols, child_owner_fqn);
@@ -3729,6 +5074,35 @@ pub(in crate::server) fn override_return_type_is_compatible(
     }
 }
 
+/// `Some(nullable)` when the type is `static`, `?static`, or `static|null`.
+pub(in crate::server) fn static_return_nullability(
+    type_info: &php_lsp_types::TypeInfo,
+) -> Option<bool> {
+    use php_lsp_types::TypeInfo;
+
+    let is_static = |type_info: &TypeInfo| match type_info {
+        TypeInfo::Static_ => true,
+        TypeInfo::Simple(name) => name.eq_ignore_ascii_case("static"),
+        _ => false,
+    };
+    let is_null = |type_info: &TypeInfo| match type_info {
+        TypeInfo::LiteralNull => true,
+        TypeInfo::Simple(name) => name.eq_ignore_ascii_case("null"),
+        _ => false,
+    };
+    match type_info {
+        type_info if is_static(type_info) => Some(false),
+        TypeInfo::Nullable(inner) if is_static(inner) => Some(true),
+        TypeInfo::Union(types)
+            if types.iter().any(is_static)
+                && types.iter().all(|ty| is_static(ty) || is_null(ty)) =>
+        {
+            Some(true)
+        }
+        _ => None,
+    }
+}
+
 pub(in crate::server) fn type_info_is_mixed(type_info: &php_lsp_types::TypeInfo) -> bool {
     match type_info {
         php_lsp_types::TypeInfo::Mixed => true,
@@ -3888,14 +5262,14 @@ pub(in crate::server) fn php_version_type_diagnostics(
     tree: &tree_sitter::Tree,
     source: &str,
     php_version: PhpVersion,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
     walk_php_version_type_diagnostics(
         tree.root_node(),
         source,
         php_version,
-        utf16_index,
+        line_index,
         &mut diagnostics,
     );
     diagnostics
@@ -3905,7 +5279,7 @@ pub(in crate::server) fn walk_php_version_type_diagnostics(
     node: tree_sitter::Node,
     source: &str,
     php_version: PhpVersion,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
     for (field_name, is_return_type) in [("type", false), ("return_type", true)] {
@@ -3915,7 +5289,7 @@ pub(in crate::server) fn walk_php_version_type_diagnostics(
                 source,
                 php_version,
                 is_return_type,
-                utf16_index,
+                line_index,
                 diagnostics,
             );
         }
@@ -3923,7 +5297,7 @@ pub(in crate::server) fn walk_php_version_type_diagnostics(
 
     let mut cursor = node.walk();
     for child in node.named_children(&mut cursor) {
-        walk_php_version_type_diagnostics(child, source, php_version, utf16_index, diagnostics);
+        walk_php_version_type_diagnostics(child, source, php_version, line_index, diagnostics);
     }
 }
 
@@ -3932,7 +5306,7 @@ pub(in crate::server) fn check_declared_type_php_version(
     source: &str,
     php_version: PhpVersion,
     is_return_type: bool,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
     let type_text = source[type_node.byte_range()].trim();
@@ -3942,7 +5316,7 @@ pub(in crate::server) fn check_declared_type_php_version(
 
     diagnostics.push(diagnostic_at_byte_range(
         node_range_node(type_node),
-        utf16_index,
+        line_index,
         format!(
             "Type is not supported by PHP {}: {}",
             php_version_label(php_version),
@@ -4045,11 +5419,407 @@ pub(in crate::server) fn php_version_label(php_version: PhpVersion) -> String {
     format!("{}.{}", php_version.major, php_version.minor)
 }
 
+pub(in crate::server) fn php_version_syntax_diagnostics(
+    tree: &tree_sitter::Tree,
+    source: &str,
+    php_version: PhpVersion,
+    line_index: &LspLineIndex,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    walk_php_version_syntax_diagnostics(
+        tree.root_node(),
+        source,
+        php_version,
+        line_index,
+        &mut diagnostics,
+    );
+    diagnostics
+}
+
+fn walk_php_version_syntax_diagnostics(
+    node: tree_sitter::Node,
+    source: &str,
+    php_version: PhpVersion,
+    line_index: &LspLineIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some((feature, range_node, (major, minor))) = php_version_syntax_feature(node, source) {
+        if !php_version.at_least(major, minor) {
+            diagnostics.push(diagnostic_at_byte_range(
+                node_range_node(range_node),
+                line_index,
+                format!(
+                    "{} requires PHP {}.{} (target is PHP {})",
+                    feature,
+                    major,
+                    minor,
+                    php_version_label(php_version)
+                ),
+            ));
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        walk_php_version_syntax_diagnostics(child, source, php_version, line_index, diagnostics);
+    }
+}
+
+/// Returns the feature name, the node to report and the minimum PHP version
+/// for syntax that older PHP versions cannot parse.
+fn php_version_syntax_feature<'a>(
+    node: tree_sitter::Node<'a>,
+    source: &str,
+) -> Option<(&'static str, tree_sitter::Node<'a>, (u16, u16))> {
+    let keyword_node = |node: tree_sitter::Node<'a>| node.child(0).unwrap_or(node);
+    match node.kind() {
+        "enum_declaration" => Some(("Enums", keyword_node(node), (8, 1))),
+        "match_expression" => Some(("Match expressions", keyword_node(node), (8, 0))),
+        "nullsafe_member_access_expression" | "nullsafe_member_call_expression" => {
+            let name_node = node.child_by_field_name("name").unwrap_or(node);
+            Some(("The nullsafe operator", name_node, (8, 0)))
+        }
+        "attribute_list" => Some(("Attributes", node, (8, 0))),
+        "readonly_modifier" => {
+            if node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "class_declaration")
+            {
+                Some(("Readonly classes", node, (8, 2)))
+            } else {
+                Some(("Readonly properties", node, (8, 1)))
+            }
+        }
+        "property_promotion_parameter" => {
+            let name_node = node.child_by_field_name("name").unwrap_or(node);
+            Some(("Constructor property promotion", name_node, (8, 0)))
+        }
+        "argument" => {
+            let name_node = node.child_by_field_name("name")?;
+            Some(("Named arguments", name_node, (8, 0)))
+        }
+        "variadic_placeholder" => Some(("First-class callable syntax", node, (8, 1))),
+        "arrow_function" => Some(("Arrow functions", keyword_node(node), (7, 4))),
+        "throw_expression"
+            if node
+                .parent()
+                .is_some_and(|parent| parent.kind() != "expression_statement") =>
+        {
+            Some(("Throw expressions", keyword_node(node), (8, 0)))
+        }
+        "class_constant_access_expression" => {
+            let receiver = node.named_child(0)?;
+            let member = node.named_child(1)?;
+            (receiver.kind() == "variable_name"
+                && source[member.byte_range()].eq_ignore_ascii_case("class"))
+            .then_some(("::class on objects", member, (8, 0)))
+        }
+        _ => None,
+    }
+}
+
+/// Deprecation and removal versions of a built-in function.
+struct PhpFunctionLifecycle {
+    name: &'static str,
+    deprecated: Option<(u16, u16)>,
+    removed: Option<(u16, u16)>,
+    replacement: &'static str,
+}
+
+const fn lifecycle(
+    name: &'static str,
+    deprecated: Option<(u16, u16)>,
+    removed: Option<(u16, u16)>,
+    replacement: &'static str,
+) -> PhpFunctionLifecycle {
+    PhpFunctionLifecycle {
+        name,
+        deprecated,
+        removed,
+        replacement,
+    }
+}
+
+const PHP_FUNCTION_LIFECYCLE: &[PhpFunctionLifecycle] = &[
+    lifecycle(
+        "call_user_method",
+        Some((4, 1)),
+        Some((7, 0)),
+        "call_user_func()",
+    ),
+    lifecycle(
+        "call_user_method_array",
+        Some((4, 1)),
+        Some((7, 0)),
+        "call_user_func_array()",
+    ),
+    lifecycle("ereg", Some((5, 3)), Some((7, 0)), "preg_match()"),
+    lifecycle("ereg_replace", Some((5, 3)), Some((7, 0)), "preg_replace()"),
+    lifecycle("eregi", Some((5, 3)), Some((7, 0)), "preg_match()"),
+    lifecycle(
+        "eregi_replace",
+        Some((5, 3)),
+        Some((7, 0)),
+        "preg_replace()",
+    ),
+    lifecycle("set_magic_quotes_runtime", Some((5, 3)), Some((7, 0)), ""),
+    lifecycle("split", Some((5, 3)), Some((7, 0)), "preg_split()"),
+    lifecycle("spliti", Some((5, 3)), Some((7, 0)), "preg_split()"),
+    lifecycle("sql_regcase", Some((5, 3)), Some((7, 0)), ""),
+    lifecycle(
+        "mysql_affected_rows",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle("mysql_close", Some((5, 5)), Some((7, 0)), "mysqli or PDO"),
+    lifecycle("mysql_connect", Some((5, 5)), Some((7, 0)), "mysqli or PDO"),
+    lifecycle("mysql_error", Some((5, 5)), Some((7, 0)), "mysqli or PDO"),
+    lifecycle(
+        "mysql_fetch_array",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle(
+        "mysql_fetch_assoc",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle(
+        "mysql_fetch_row",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle(
+        "mysql_insert_id",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle(
+        "mysql_num_rows",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle("mysql_query", Some((5, 5)), Some((7, 0)), "mysqli or PDO"),
+    lifecycle(
+        "mysql_real_escape_string",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle(
+        "mysql_select_db",
+        Some((5, 5)),
+        Some((7, 0)),
+        "mysqli or PDO",
+    ),
+    lifecycle(
+        "mcrypt_decrypt",
+        Some((7, 1)),
+        Some((7, 2)),
+        "openssl_decrypt() or sodium",
+    ),
+    lifecycle(
+        "mcrypt_encrypt",
+        Some((7, 1)),
+        Some((7, 2)),
+        "openssl_encrypt() or sodium",
+    ),
+    lifecycle(
+        "create_function",
+        Some((7, 2)),
+        Some((8, 0)),
+        "an anonymous function",
+    ),
+    lifecycle("each", Some((7, 2)), Some((8, 0)), "foreach"),
+    lifecycle(
+        "gmp_random",
+        Some((7, 2)),
+        Some((8, 0)),
+        "gmp_random_bits()",
+    ),
+    lifecycle("jpeg2wbmp", Some((7, 2)), Some((8, 0)), "imagewbmp()"),
+    lifecycle("png2wbmp", Some((7, 2)), Some((8, 0)), "imagewbmp()"),
+    lifecycle(
+        "read_exif_data",
+        Some((7, 2)),
+        Some((8, 0)),
+        "exif_read_data()",
+    ),
+    lifecycle(
+        "fgetss",
+        Some((7, 3)),
+        Some((8, 0)),
+        "fgets() with strip_tags()",
+    ),
+    lifecycle(
+        "gzgetss",
+        Some((7, 3)),
+        Some((8, 0)),
+        "gzgets() with strip_tags()",
+    ),
+    lifecycle("image2wbmp", Some((7, 3)), Some((8, 0)), "imagewbmp()"),
+    lifecycle(
+        "convert_cyr_string",
+        Some((7, 4)),
+        Some((8, 0)),
+        "mb_convert_encoding()",
+    ),
+    lifecycle("ezmlm_hash", Some((7, 4)), Some((8, 0)), ""),
+    lifecycle("get_magic_quotes_gpc", Some((7, 4)), Some((8, 0)), ""),
+    lifecycle("get_magic_quotes_runtime", Some((7, 4)), Some((8, 0)), ""),
+    lifecycle("hebrevc", Some((7, 4)), Some((8, 0)), "nl2br(hebrev())"),
+    lifecycle(
+        "money_format",
+        Some((7, 4)),
+        Some((8, 0)),
+        "NumberFormatter",
+    ),
+    lifecycle(
+        "restore_include_path",
+        Some((7, 4)),
+        Some((8, 0)),
+        "ini_restore('include_path')",
+    ),
+    lifecycle("libxml_disable_entity_loader", Some((8, 0)), None, ""),
+    lifecycle("date_sunrise", Some((8, 1)), None, "date_sun_info()"),
+    lifecycle("date_sunset", Some((8, 1)), None, "date_sun_info()"),
+    lifecycle(
+        "gmstrftime",
+        Some((8, 1)),
+        None,
+        "IntlDateFormatter or date()",
+    ),
+    lifecycle("mhash", Some((8, 1)), None, "hash()"),
+    lifecycle("odbc_result_all", Some((8, 1)), None, ""),
+    lifecycle(
+        "strftime",
+        Some((8, 1)),
+        None,
+        "IntlDateFormatter or date()",
+    ),
+    lifecycle("strptime", Some((8, 1)), None, "date_parse_from_format()"),
+    lifecycle("utf8_decode", Some((8, 2)), None, "mb_convert_encoding()"),
+    lifecycle("utf8_encode", Some((8, 2)), None, "mb_convert_encoding()"),
+    lifecycle("assert_options", Some((8, 3)), None, ""),
+    lifecycle(
+        "lcg_value",
+        Some((8, 4)),
+        None,
+        "random_int() or Random\\Randomizer",
+    ),
+];
+
+fn php_function_lifecycle(name: &str) -> Option<&'static PhpFunctionLifecycle> {
+    PHP_FUNCTION_LIFECYCLE
+        .iter()
+        .find(|lifecycle| lifecycle.name.eq_ignore_ascii_case(name))
+}
+
+/// Reports calls to built-in functions that are deprecated or removed in the
+/// target PHP version. Removed functions are reported here instead of as
+/// unknown functions; their name ranges are added to `removed_ranges`.
+pub(in crate::server) fn php_version_function_diagnostics(
+    tree: &tree_sitter::Tree,
+    source: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    php_version: PhpVersion,
+    line_index: &LspLineIndex,
+    removed_ranges: &mut HashSet<(u32, u32, u32, u32)>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+        if node.kind() != "function_call_expression" {
+            continue;
+        }
+        let Some(name_node) = node.child_by_field_name("function") else {
+            continue;
+        };
+        if !matches!(name_node.kind(), "name" | "qualified_name") {
+            continue;
+        }
+        let text = &source[name_node.byte_range()];
+        let fully_qualified = text.starts_with('\\');
+        let name = text.trim_start_matches('\\');
+        if name.contains('\\') {
+            continue;
+        }
+        let Some(lifecycle) = php_function_lifecycle(name) else {
+            continue;
+        };
+        if !fully_qualified {
+            if let Some(namespace) = file_symbols.namespace.as_deref() {
+                if index.resolve_fqn(&format!("{namespace}\\{name}")).is_some() {
+                    continue;
+                }
+            }
+        }
+        // A workspace polyfill keeps the function callable.
+        if index
+            .resolve_fqn(name)
+            .is_some_and(|symbol| !symbol.modifiers.is_builtin)
+        {
+            continue;
+        }
+
+        let suggestion = if lifecycle.replacement.is_empty() {
+            String::new()
+        } else {
+            format!("; use {} instead", lifecycle.replacement)
+        };
+        let range = node_range_node(name_node);
+        if let Some((major, minor)) = lifecycle
+            .removed
+            .filter(|(major, minor)| php_version.at_least(*major, *minor))
+        {
+            removed_ranges.insert(range);
+            diagnostics.push(diagnostic_at_byte_range(
+                range,
+                line_index,
+                format!(
+                    "Function {name}() was removed in PHP {major}.{minor} (target is PHP {}){suggestion}",
+                    php_version_label(php_version)
+                ),
+            ));
+        } else if let Some((major, minor)) = lifecycle
+            .deprecated
+            .filter(|(major, minor)| php_version.at_least(*major, *minor))
+        {
+            let mut diagnostic = diagnostic_at_byte_range(
+                range,
+                line_index,
+                format!("Function {name}() is deprecated since PHP {major}.{minor}{suggestion}"),
+            );
+            diagnostic.code = Some(NumberOrString::String(
+                DiagnosticRule::DeprecatedFunction.code().to_string(),
+            ));
+            diagnostic.tags = Some(vec![DiagnosticTag::DEPRECATED]);
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| {
+        (
+            diagnostic.range.start.line,
+            diagnostic.range.start.character,
+        )
+    });
+    diagnostics
+}
+
 pub(in crate::server) fn workspace_duplicate_symbol_diagnostics(
     uri_str: &str,
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
@@ -4058,39 +5828,171 @@ pub(in crate::server) fn workspace_duplicate_symbol_diagnostics(
             continue;
         }
 
-        let has_duplicate = index.file_symbols.iter().any(|entry| {
+        let mut other_declarations: Vec<DiagnosticRelatedInformation> = Vec::new();
+        for entry in index.file_symbols.iter() {
             if entry.key().as_str() == uri_str {
-                return false;
+                continue;
             }
+            other_declarations.extend(
+                entry
+                    .value()
+                    .symbols
+                    .iter()
+                    .filter(|other| {
+                        other.kind == sym.kind
+                            && other.fqn == sym.fqn
+                            && !other.modifiers.is_builtin
+                    })
+                    .filter_map(|other| {
+                        symbol_related_information(
+                            other,
+                            uri_str,
+                            line_index,
+                            format!("{} is also declared here", other.fqn),
+                        )
+                    }),
+            );
+        }
+        if other_declarations.is_empty() {
+            continue;
+        }
+        let location_key = |info: &DiagnosticRelatedInformation| {
+            let start = info.location.range.start;
+            (info.location.uri.to_string(), start.line, start.character)
+        };
+        other_declarations.sort_by_key(location_key);
+        other_declarations.truncate(MAX_DUPLICATE_RELATED_DECLARATIONS);
+
+        diagnostics.push(Diagnostic {
+            range: Range {
+                start: Position::new(
+                    sym.selection_range.0,
+                    line_index.byte_col_to_lsp(sym.selection_range.0, sym.selection_range.1),
+                ),
+                end: Position::new(
+                    sym.selection_range.2,
+                    line_index.byte_col_to_lsp(sym.selection_range.2, sym.selection_range.3),
+                ),
+            },
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(
+                semantic_diagnostic_code(&SemanticDiagnosticKind::DuplicateSymbol).to_string(),
+            )),
+            source: Some("php-lsp".to_string()),
+            message: format!("Duplicate symbol: {}", sym.fqn),
+            related_information: Some(other_declarations),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Methods that two used traits both provide without an `insteadof` rule
+/// choosing one, which PHP rejects unless the class declares the method itself.
+pub(in crate::server) fn trait_method_collision_diagnostics(
+    file_symbols: &php_lsp_types::FileSymbols,
+    index: &WorkspaceIndex,
+    line_index: &LspLineIndex,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
 
-            entry.value().symbols.iter().any(|other| {
-                other.kind == sym.kind && other.fqn == sym.fqn && !other.modifiers.is_builtin
+    for class_sym in file_symbols
+        .symbols
+        .iter()
+        .filter(|sym| is_class_like_kind(sym.kind) && sym.traits.len() > 1)
+    {
+        let own_methods: HashSet<String> = file_symbols
+            .symbols
+            .iter()
+            .filter(|sym| {
+                sym.kind == php_lsp_types::PhpSymbolKind::Method
+                    && sym.parent_fqn.as_deref() == Some(class_sym.fqn.as_str())
             })
-        });
+            .map(|sym| sym.name.to_ascii_lowercase())
+            .collect();
 
-        if has_duplicate {
-            diagnostics.push(Diagnostic {
-                range: Range {
-                    start: Position::new(
-                        sym.selection_range.0,
-                        utf16_index.byte_col_to_utf16(sym.selection_range.0, sym.selection_range.1),
-                    ),
-                    end: Position::new(
-                        sym.selection_range.2,
-                        utf16_index.byte_col_to_utf16(sym.selection_range.2, sym.selection_range.3),
-                    ),
-                },
-                severity: Some(DiagnosticSeverity::WARNING),
-                source: Some("php-lsp".to_string()),
-                message: format!("Duplicate symbol: {}", sym.fqn),
-                ..Default::default()
-            });
+        let mut providers: Vec<TraitMethodProviders> = Vec::new();
+        for trait_fqn in &class_sym.traits {
+            for method in index.get_members(trait_fqn).into_iter().filter(|member| {
+                member.kind == php_lsp_types::PhpSymbolKind::Method
+                    && !member.modifiers.is_abstract
+                    && !member
+                        .parent_fqn
+                        .as_deref()
+                        .and_then(|owner| index.types.get(owner))
+                        .is_some_and(|owner| is_phpdoc_virtual_method_symbol(member, &owner))
+            }) {
+                let key = method.name.to_ascii_lowercase();
+                if own_methods.contains(&key)
+                    || trait_method_excluded_by_insteadof(class_sym, trait_fqn, &method.name)
+                {
+                    continue;
+                }
+                let provider = (trait_fqn.clone(), method.fqn.to_string());
+                match providers.iter_mut().find(|existing| existing.key == key) {
+                    Some(existing) => {
+                        if !existing.traits.iter().any(|(fqn, _)| fqn == trait_fqn) {
+                            existing.traits.push(provider);
+                        }
+                    }
+                    None => providers.push(TraitMethodProviders {
+                        key,
+                        name: method.name.clone(),
+                        traits: vec![provider],
+                    }),
+                }
+            }
+        }
+
+        for TraitMethodProviders { name, traits, .. } in providers {
+            let first_declaration = &traits[0].1;
+            if traits
+                .iter()
+                .all(|(_, declaration)| declaration == first_declaration)
+            {
+                continue;
+            }
+            let trait_names: Vec<&str> = traits.iter().map(|(fqn, _)| fqn.as_str()).collect();
+            diagnostics.push(diagnostic_at_byte_range(
+                class_sym.selection_range,
+                line_index,
+                format!(
+                    "Trait method {name}() collides in {}: it is defined in {}; choose one with insteadof",
+                    class_sym.fqn,
+                    trait_names.join(" and ")
+                ),
+            ));
         }
     }
 
     diagnostics
 }
 
+/// Used traits that provide one method name.
+struct TraitMethodProviders {
+    /// Lowercase method name.
+    key: String,
+    name: String,
+    /// `(trait FQN, declaring method FQN)` pairs.
+    traits: Vec<(String, String)>,
+}
+
+fn trait_method_excluded_by_insteadof(
+    class_sym: &php_lsp_types::SymbolInfo,
+    trait_fqn: &str,
+    method_name: &str,
+) -> bool {
+    class_sym.trait_adaptations.iter().any(|adaptation| {
+        matches!(
+            &adaptation.kind,
+            php_lsp_types::TraitAdaptationKind::InsteadOf { excluded }
+                if adaptation.method.eq_ignore_ascii_case(method_name)
+                    && excluded.iter().any(|excluded| fqn_matches(excluded, trait_fqn))
+        )
+    })
+}
+
 pub(in crate::server) fn is_duplicate_checked_symbol_kind(
     kind: php_lsp_types::PhpSymbolKind,
 ) -> bool {
@@ -4116,11 +6018,40 @@ pub(in crate::server) fn current_class_fqn(
                 | php_lsp_types::PhpSymbolKind::Trait
                 | php_lsp_types::PhpSymbolKind::Enum
         )
-        .then(|| sym.fqn.clone())
+        .then(|| sym.fqn.to_string())
     })
 }
 
 impl PhpLspBackend {
+    /// Whether any of PHPStan, Psalm, or PHPCS may run on save/open.
+    async fn external_analyzers_enabled(&self) -> bool {
+        self.phpstan_config.lock().await.enabled
+            || self.psalm_config.lock().await.enabled != AnalyzerActivation::Off
+            || self.phpcs_config.lock().await.enabled
+    }
+
+    /// Run the configured external analyzers in turn; `None` when the run was
+    /// cancelled by a newer edit.
+    async fn external_analyzer_diagnostics(
+        &self,
+        uri: &Uri,
+        token: &OperationCancellationToken,
+    ) -> Option<Vec<Diagnostic>> {
+        let mut external = self.phpstan_diagnostics_for_uri(uri, token.clone()).await;
+        if token.is_cancelled() {
+            return None;
+        }
+        external.extend(self.psalm_diagnostics_for_uri(uri, token.clone()).await);
+        if token.is_cancelled() {
+            return None;
+        }
+        external.extend(self.phpcs_diagnostics_for_uri(uri, token.clone()).await);
+        if token.is_cancelled() {
+            return None;
+        }
+        Some(external)
+    }
+
     pub(in crate::server) async fn phpstan_diagnostics_for_uri(
         &self,
         uri: &Uri,
@@ -4131,7 +6062,7 @@ impl PhpLspBackend {
             return vec![];
         }
 
-        if !uri_is_php_file(uri) {
+        if !self.uri_is_php_source(uri).await {
             return vec![];
         }
 
@@ -4176,11 +6107,11 @@ impl PhpLspBackend {
         cancellation: OperationCancellationToken,
     ) -> Vec<Diagnostic> {
         let config = self.psalm_config.lock().await.clone();
-        if !config.enabled {
+        if config.enabled == AnalyzerActivation::Off {
             return vec![];
         }
 
-        if !uri_is_php_file(uri) {
+        if !self.uri_is_php_source(uri).await {
             return vec![];
         }
 
@@ -4192,6 +6123,11 @@ impl PhpLspBackend {
         }
 
         let workspace_root = self.workspace_root_for_uri(uri.as_str()).await;
+        if config.enabled == AnalyzerActivation::Auto
+            && !workspace_root.as_deref().is_some_and(psalm_config_exists)
+        {
+            return vec![];
+        }
         match run_psalm_for_file(config, file_path, workspace_root, Some(cancellation)).await {
             Ok(diagnostics) => diagnostics,
             Err(message) => {
@@ -4215,6 +6151,51 @@ impl PhpLspBackend {
         }
     }
 
+    pub(in crate::server) async fn phpcs_diagnostics_for_uri(
+        &self,
+        uri: &Uri,
+        cancellation: OperationCancellationToken,
+    ) -> Vec<Diagnostic> {
+        let config = self.phpcs_config.lock().await.clone();
+        if !config.enabled {
+            return vec![];
+        }
+
+        if !self.uri_is_php_source(uri).await {
+            return vec![];
+        }
+
+        let Some(file_path) = uri_to_path(uri.as_str()) else {
+            return vec![];
+        };
+        if !file_path.exists() {
+            return vec![];
+        }
+
+        let workspace_root = self.workspace_root_for_uri(uri.as_str()).await;
+        match run_phpcs_for_file(config, file_path, workspace_root, Some(cancellation)).await {
+            Ok(diagnostics) => diagnostics,
+            Err(message) => {
+                if message.contains("command cancelled") {
+                    tracing::debug!(
+                        "phpcs diagnostics cancelled for {}: {}",
+                        uri.as_str(),
+                        message
+                    );
+                    return vec![];
+                }
+                tracing::warn!("phpcs diagnostics failed for {}: {}", uri.as_str(), message);
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("php-lsp phpcs diagnostics failed: {}", message),
+                    )
+                    .await;
+                vec![]
+            }
+        }
+    }
+
     pub(in crate::server) fn references_for_file(
         &self,
         file_uri: &str,
@@ -4222,30 +6203,21 @@ impl PhpLspBackend {
         target_kind: php_lsp_types::PhpSymbolKind,
         include_declaration: bool,
     ) -> Vec<php_lsp_types::SymbolReference> {
-        let mut refs = if let Some(parser) = self.open_files.get(file_uri) {
-            current_parser_symbol_references(file_uri, &parser)
-        } else {
-            self.index
-                .file_references
-                .get(file_uri)
-                .map(|entry| entry.value().clone())
-                .unwrap_or_default()
-        };
-        refs.retain(|reference| {
-            symbol_reference_matches(
-                &self.index,
-                reference,
-                target_fqn,
-                target_kind,
-                include_declaration,
-            )
-        });
-        refs
+        matching_references_for_file(
+            &self.index,
+            &self.open_files,
+            file_uri,
+            target_fqn,
+            target_kind,
+            include_declaration,
+            self.position_encoding(),
+        )
     }
 
     /// Publish diagnostics for a file.
     pub(in crate::server) async fn publish_diagnostics(&self, uri: &Uri) {
         let uri_str = uri.as_str().to_string();
+        let encoding = self.position_encoding();
         let template_document = self.template_document(&uri_str);
         let version = self.current_document_version(&uri_str);
         let diagnostics_mode = *self.diagnostics_mode.lock().await;
@@ -4255,7 +6227,7 @@ impl PhpLspBackend {
         let should_preresolve_dependencies = template_document.is_none()
             && diagnostics_mode == DiagnosticsMode::BasicSemantic
             && !indexing_active
-            && *self.index_vendor.lock().await;
+            && self.vendor_indexing.lock().await.is_enabled();
 
         // Pre-resolve use statements via lazy indexing so that vendor classes
         // are available for the synchronous `compute_diagnostics` resolver.
@@ -4278,6 +6250,7 @@ impl PhpLspBackend {
             severity: diagnostic_severity,
             budget: diagnostic_budget,
             php_version,
+            encoding,
         };
         let mut diagnostics = compute_open_file_diagnostics(
             &uri_str,
@@ -4291,6 +6264,7 @@ impl PhpLspBackend {
             diagnostics = template.map_diagnostics_to_original(
                 diagnostics,
                 effective_diagnostics_mode == DiagnosticsMode::Off,
+                encoding,
             );
         } else if should_preresolve_dependencies {
             diagnostics = self
@@ -4314,36 +6288,44 @@ impl PhpLspBackend {
                 diagnostics = template.map_diagnostics_to_original(
                     diagnostics,
                     effective_diagnostics_mode == DiagnosticsMode::Off,
+                    encoding,
                 );
             }
         }
 
-        let has_syntax_errors = diagnostics.iter().any(|diagnostic| {
-            diagnostic.source.as_deref() == Some("php-lsp")
-                && diagnostic.severity == Some(DiagnosticSeverity::ERROR)
-        });
         if template_document.is_none()
             && effective_diagnostics_mode == DiagnosticsMode::BasicSemantic
-            && !has_syntax_errors
+            && !has_syntax_error_diagnostics(&diagnostics)
         {
             let analyzer_token = self.start_analyzer_run(&uri_str).await;
-            diagnostics.extend(
-                self.phpstan_diagnostics_for_uri(uri, analyzer_token.clone())
-                    .await,
-            );
-            if analyzer_token.is_cancelled() {
-                self.finish_analyzer_run(&uri_str, &analyzer_token).await;
-                return;
+            let report_analyzing = self.external_analyzers_enabled().await;
+            if report_analyzing {
+                send_server_status(
+                    &self.client,
+                    ServerStatusState::Analyzing,
+                    "Running external analyzers",
+                )
+                .await;
             }
-            diagnostics.extend(
-                self.psalm_diagnostics_for_uri(uri, analyzer_token.clone())
-                    .await,
-            );
-            if analyzer_token.is_cancelled() {
-                self.finish_analyzer_run(&uri_str, &analyzer_token).await;
+            let external = self
+                .external_analyzer_diagnostics(uri, &analyzer_token)
+                .await;
+            self.finish_analyzer_run(&uri_str, &analyzer_token).await;
+            if report_analyzing && !indexing_run_is_active(&self.indexing_run).await {
+                send_server_status(&self.client, ServerStatusState::Idle, "Ready").await;
+            }
+            let Some(external) = external else {
                 return;
+            };
+            {
+                let mut analyzer_diagnostics = self.analyzer_diagnostics.lock().await;
+                if external.is_empty() {
+                    analyzer_diagnostics.remove(&uri_str);
+                } else {
+                    analyzer_diagnostics.insert(uri_str.clone(), external.clone());
+                }
             }
-            self.finish_analyzer_run(&uri_str, &analyzer_token).await;
+            diagnostics.extend(external);
         }
 
         if self.current_document_version(&uri_str) != version {
@@ -4372,6 +6354,7 @@ impl PhpLspBackend {
                 diagnostics = template.map_diagnostics_to_original(
                     diagnostics,
                     effective_diagnostics_mode == DiagnosticsMode::Off,
+                    encoding,
                 );
             }
             if self.current_document_version(&uri_str) != version {
@@ -4401,6 +6384,9 @@ impl PhpLspBackend {
             {
                 return;
             }
+            let diagnostic_baseline = self.diagnostic_baseline.lock().await.clone();
+            let diagnostics =
+                filter_baselined_diagnostics(diagnostic_baseline.as_deref(), &uri_str, diagnostics);
             self.client
                 .publish_diagnostics(uri.clone(), diagnostics, version)
                 .await;
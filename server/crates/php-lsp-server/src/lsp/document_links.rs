@@ -212,6 +212,7 @@ fn collect_document_links(
     file_path: &Path,
     file_dir: &Path,
     links: &mut Vec<DocumentLink>,
+    encoding: PositionEncoding,
 ) {
     if is_document_link_include_expression(node.kind()) {
         if let Some(expression) = node.named_child(0) {
@@ -223,7 +224,7 @@ fn collect_document_links(
                     .and_then(|uri| uri.parse::<Uri>().ok())
                 {
                     links.push(DocumentLink {
-                        range: range_from_byte_range(source, node_byte_range(expression)),
+                        range: range_from_byte_range(source, node_byte_range(expression), encoding),
                         target: Some(target),
                         tooltip: Some(target_path.display().to_string()),
                         data: None,
@@ -235,7 +236,7 @@ fn collect_document_links(
 
     let mut cursor = node.walk();
     for child in node.named_children(&mut cursor) {
-        collect_document_links(child, source, file_path, file_dir, links);
+        collect_document_links(child, source, file_path, file_dir, links, encoding);
     }
 }
 
@@ -285,13 +286,21 @@ fn document_links_for_source(
     source: &str,
     tree: &tree_sitter::Tree,
     file_path: &Path,
+    encoding: PositionEncoding,
 ) -> Vec<DocumentLink> {
     let Some(file_dir) = file_path.parent() else {
         return Vec::new();
     };
 
     let mut links = Vec::new();
-    collect_document_links(tree.root_node(), source, file_path, file_dir, &mut links);
+    collect_document_links(
+        tree.root_node(),
+        source,
+        file_path,
+        file_dir,
+        &mut links,
+        encoding,
+    );
     links
 }
 
@@ -300,6 +309,7 @@ impl PhpLspBackend {
         &self,
         params: DocumentLinkParams,
     ) -> Result<Option<Vec<DocumentLink>>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         let Some(file_path) = uri_to_path(&uri_str) else {
             return Ok(None);
@@ -309,19 +319,17 @@ impl PhpLspBackend {
             let Some(tree) = parser.tree() else {
                 return Ok(None);
             };
-            document_links_for_source(&parser.source(), tree, &file_path)
+            document_links_for_source(&parser.source(), tree, &file_path, encoding)
         } else {
             let Ok(source) =
                 read_file_to_string_blocking(file_path.clone(), "documentLink source read").await
             else {
                 return Ok(None);
             };
-            let mut parser = FileParser::new();
-            parser.parse_full(&source);
-            let Some(tree) = parser.tree() else {
+            let Some(parsed) = self.closed_file_tree(&uri_str, source).await else {
                 return Ok(None);
             };
-            document_links_for_source(&source, tree, &file_path)
+            document_links_for_source(&parsed.source, &parsed.tree, &file_path, encoding)
         };
 
         if links.is_empty() {
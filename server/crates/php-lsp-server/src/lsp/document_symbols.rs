@@ -1,6 +1,7 @@
 //! Document Symbols LSP handlers extracted from `server.rs`.
 
 use super::super::*;
+use super::progress::{PartialResultSender, PARTIAL_RESULT_CHUNK_SIZE};
 
 #[derive(Debug, Clone)]
 pub(crate) struct WorkspaceSymbolCandidate {
@@ -8,36 +9,19 @@ pub(crate) struct WorkspaceSymbolCandidate {
     pub(crate) symbol: php_lsp_types::SymbolInfo,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum WorkspaceSymbolKindFilter {
-    Type,
-    Class,
-    Interface,
-    Trait,
-    Enum,
-    Function,
-    Method,
-    Property,
-    Constant,
-}
-
 pub(crate) fn workspace_symbol_candidates(
     index: &WorkspaceIndex,
     raw_query: &str,
 ) -> Vec<WorkspaceSymbolCandidate> {
-    let (kind_filter, query) = parse_workspace_symbol_query(raw_query);
-    if query.is_empty() && kind_filter.is_none() {
+    let query = SymbolQuery::parse(raw_query);
+    if query.is_empty() {
         return Vec::new();
     }
 
     let mut candidates = Vec::new();
     for file_symbols in index.file_symbols.iter() {
         for symbol in &file_symbols.symbols {
-            if symbol.modifiers.is_builtin {
-                continue;
-            }
-            if !kind_filter.is_none_or(|filter| workspace_symbol_kind_matches(symbol.kind, filter))
-            {
+            if symbol.modifiers.is_builtin || !query.matches_scope(symbol) {
                 continue;
             }
             let Some(score) = workspace_symbol_score(symbol, &query) else {
@@ -63,138 +47,17 @@ pub(crate) fn workspace_symbol_candidates(
     candidates
 }
 
-fn parse_workspace_symbol_query(raw_query: &str) -> (Option<WorkspaceSymbolKindFilter>, String) {
-    let query = raw_query.trim();
-    if let Some((prefix, rest)) = query.split_once(':') {
-        if let Some(filter) = parse_workspace_symbol_kind_filter(prefix) {
-            return (Some(filter), rest.trim().to_string());
-        }
-    }
-
-    if let Some((prefix, rest)) = query.split_once(char::is_whitespace) {
-        if let Some(filter) = parse_workspace_symbol_kind_filter(prefix) {
-            return (Some(filter), rest.trim().to_string());
-        }
-    }
-
-    (None, query.to_string())
-}
-
-fn parse_workspace_symbol_kind_filter(raw: &str) -> Option<WorkspaceSymbolKindFilter> {
-    match raw.trim().to_ascii_lowercase().as_str() {
-        "type" | "types" => Some(WorkspaceSymbolKindFilter::Type),
-        "class" | "classes" => Some(WorkspaceSymbolKindFilter::Class),
-        "interface" | "interfaces" => Some(WorkspaceSymbolKindFilter::Interface),
-        "trait" | "traits" => Some(WorkspaceSymbolKindFilter::Trait),
-        "enum" | "enums" => Some(WorkspaceSymbolKindFilter::Enum),
-        "function" | "functions" | "fn" => Some(WorkspaceSymbolKindFilter::Function),
-        "method" | "methods" => Some(WorkspaceSymbolKindFilter::Method),
-        "property" | "properties" | "prop" | "props" => Some(WorkspaceSymbolKindFilter::Property),
-        "const" | "constant" | "constants" => Some(WorkspaceSymbolKindFilter::Constant),
-        _ => None,
-    }
-}
-
-fn workspace_symbol_kind_matches(
-    kind: php_lsp_types::PhpSymbolKind,
-    filter: WorkspaceSymbolKindFilter,
-) -> bool {
-    match filter {
-        WorkspaceSymbolKindFilter::Type => matches!(
-            kind,
-            php_lsp_types::PhpSymbolKind::Class
-                | php_lsp_types::PhpSymbolKind::Interface
-                | php_lsp_types::PhpSymbolKind::Trait
-                | php_lsp_types::PhpSymbolKind::Enum
-        ),
-        WorkspaceSymbolKindFilter::Class => kind == php_lsp_types::PhpSymbolKind::Class,
-        WorkspaceSymbolKindFilter::Interface => kind == php_lsp_types::PhpSymbolKind::Interface,
-        WorkspaceSymbolKindFilter::Trait => kind == php_lsp_types::PhpSymbolKind::Trait,
-        WorkspaceSymbolKindFilter::Enum => kind == php_lsp_types::PhpSymbolKind::Enum,
-        WorkspaceSymbolKindFilter::Function => kind == php_lsp_types::PhpSymbolKind::Function,
-        WorkspaceSymbolKindFilter::Method => kind == php_lsp_types::PhpSymbolKind::Method,
-        WorkspaceSymbolKindFilter::Property => kind == php_lsp_types::PhpSymbolKind::Property,
-        WorkspaceSymbolKindFilter::Constant => matches!(
-            kind,
-            php_lsp_types::PhpSymbolKind::ClassConstant
-                | php_lsp_types::PhpSymbolKind::GlobalConstant
-                | php_lsp_types::PhpSymbolKind::EnumCase
-        ),
-    }
-}
-
-fn workspace_symbol_score(symbol: &php_lsp_types::SymbolInfo, query: &str) -> Option<i64> {
-    if query.is_empty() {
-        return Some(1_000 + workspace_symbol_kind_bonus(symbol.kind));
-    }
-
-    let mut best_score = fuzzy_text_score(&symbol.name, query);
-    if let Some(fqn_score) = fuzzy_text_score(&symbol.fqn, query) {
-        let qualified_bonus = if query.contains('\\') { 700 } else { 100 };
-        best_score = Some(
-            best_score
-                .unwrap_or(i64::MIN)
-                .max(fqn_score + qualified_bonus),
-        );
-    }
-    if let Some(container) = workspace_symbol_container_name(symbol) {
-        if container
-            .to_ascii_lowercase()
-            .contains(&query.to_ascii_lowercase())
-        {
-            best_score = Some(best_score.unwrap_or(i64::MIN).max(5_500));
-        }
-    }
-
-    Some(best_score? + workspace_symbol_kind_bonus(symbol.kind))
-}
-
-fn fuzzy_text_score(text: &str, query: &str) -> Option<i64> {
-    let text_lower = text.to_ascii_lowercase();
-    let query_lower = query.to_ascii_lowercase();
-    if query_lower.is_empty() {
-        return Some(1_000);
-    }
-    if text_lower == query_lower {
-        return Some(10_000);
-    }
-    if text_lower.starts_with(&query_lower) {
-        return Some(9_000 - text_lower.len().saturating_sub(query_lower.len()) as i64);
-    }
-    if let Some(index) = text_lower.find(&query_lower) {
-        return Some(7_000 - (index as i64 * 10));
-    }
-
-    fuzzy_abbreviation_score(&text_lower, &query_lower)
-}
-
-fn fuzzy_abbreviation_score(text: &str, query: &str) -> Option<i64> {
-    let mut score = 4_000i64;
-    let mut last_match_index: Option<usize> = None;
-    let mut search_from = 0usize;
-
-    for query_char in query.chars() {
-        let relative_index = text[search_from..].find(query_char)?;
-        let absolute_index = search_from + relative_index;
-        if let Some(last_match_index) = last_match_index {
-            let gap = absolute_index.saturating_sub(last_match_index + 1);
-            score -= gap as i64 * 8;
-        } else {
-            score -= absolute_index as i64 * 4;
-        }
-        if absolute_index == 0
-            || text[..absolute_index]
-                .chars()
-                .last()
-                .is_some_and(|ch| ch == '\\' || ch == '_' || ch == '-' || ch.is_whitespace())
-        {
-            score += 80;
-        }
-        last_match_index = Some(absolute_index);
-        search_from = absolute_index + query_char.len_utf8();
-    }
-
-    Some(score - text.len() as i64)
+fn workspace_symbol_score(symbol: &php_lsp_types::SymbolInfo, query: &SymbolQuery) -> Option<i64> {
+    let score = query.score(symbol).or_else(|| {
+        // Members of a type whose name matches rank after every direct match.
+        let text = query.text.to_ascii_lowercase();
+        (!text.is_empty()
+            && workspace_symbol_container_name(symbol)
+                .is_some_and(|container| container.to_ascii_lowercase().contains(&text)))
+        .then_some(0)
+    })?;
+
+    Some(score + workspace_symbol_kind_bonus(symbol.kind))
 }
 
 fn workspace_symbol_kind_bonus(kind: php_lsp_types::PhpSymbolKind) -> i64 {
@@ -230,10 +93,14 @@ fn workspace_symbol_kind_rank(kind: php_lsp_types::PhpSymbolKind) -> u8 {
 }
 
 fn workspace_symbol_container_name(symbol: &php_lsp_types::SymbolInfo) -> Option<String> {
-    symbol.parent_fqn.clone().or_else(|| {
-        let fqn = &symbol.fqn;
-        fqn.rfind('\\').map(|index| fqn[..index].to_string())
-    })
+    symbol
+        .parent_fqn
+        .as_deref()
+        .map(str::to_string)
+        .or_else(|| {
+            let fqn = &symbol.fqn;
+            fqn.rfind('\\').map(|index| fqn[..index].to_string())
+        })
 }
 
 async fn workspace_symbol_source_for_uri(
@@ -264,10 +131,11 @@ async fn workspace_symbol_information(
     symbol: &php_lsp_types::SymbolInfo,
     open_files: &DashMap<String, FileParser>,
     source_cache: &mut HashMap<String, Option<String>>,
+    encoding: PositionEncoding,
 ) -> Option<SymbolInformation> {
     let uri: Uri = symbol.uri.parse().ok()?;
     let source = workspace_symbol_source_for_uri(&symbol.uri, open_files, source_cache).await?;
-    let range = workspace_symbol_lsp_range(&source, symbol.range);
+    let range = workspace_symbol_lsp_range(&source, symbol.range, encoding);
 
     #[allow(deprecated)]
     Some(SymbolInformation {
@@ -284,8 +152,12 @@ async fn workspace_symbol_information(
     })
 }
 
-pub(crate) fn workspace_symbol_lsp_range(source: &str, range: (u32, u32, u32, u32)) -> Range {
-    range_from_byte_range(source, range)
+pub(crate) fn workspace_symbol_lsp_range(
+    source: &str,
+    range: (u32, u32, u32, u32),
+    encoding: PositionEncoding,
+) -> Range {
+    range_from_byte_range(source, range, encoding)
 }
 
 impl PhpLspBackend {
@@ -293,6 +165,7 @@ impl PhpLspBackend {
         &self,
         params: SelectionRangeParams,
     ) -> Result<Option<Vec<SelectionRange>>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         let parser = match self.open_files.get(&uri_str) {
             Some(parser) => parser,
@@ -307,7 +180,7 @@ impl PhpLspBackend {
 
         let mut results = Vec::with_capacity(params.positions.len());
         for position in params.positions {
-            let byte_col = utf16_col_to_byte(&source, position.line, position.character);
+            let byte_col = lsp_col_to_byte(&source, position.line, position.character, encoding);
             let point = tree_sitter::Point::new(position.line as usize, byte_col as usize);
             let mut node = match root.descendant_for_point_range(point, point) {
                 Some(node) => node,
@@ -340,7 +213,9 @@ impl PhpLspBackend {
                 current = node.parent();
             }
 
-            if let Some(selection_range) = selection_range_from_byte_ranges(&source, byte_ranges) {
+            if let Some(selection_range) =
+                selection_range_from_byte_ranges(&source, byte_ranges, encoding)
+            {
                 results.push(selection_range);
             }
         }
@@ -356,6 +231,7 @@ impl PhpLspBackend {
         &self,
         params: LinkedEditingRangeParams,
     ) -> Result<Option<LinkedEditingRanges>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position_params
             .text_document
@@ -373,7 +249,7 @@ impl PhpLspBackend {
             None => return Ok(None),
         };
         let source = parser.source();
-        let byte_col = utf16_col_to_byte(&source, position.line, position.character);
+        let byte_col = lsp_col_to_byte(&source, position.line, position.character, encoding);
         let point = tree_sitter::Point::new(position.line as usize, byte_col as usize);
         let root = tree.root_node();
         let mut node = match root.descendant_for_point_range(point, point) {
@@ -394,7 +270,7 @@ impl PhpLspBackend {
         let ranges = byte_ranges
             .into_iter()
             .map(|range| {
-                let range = range_byte_to_utf16(&source, range);
+                let range = range_byte_to_lsp(&source, range, encoding);
                 Range {
                     start: Position::new(range.0, range.1),
                     end: Position::new(range.2, range.3),
@@ -412,15 +288,18 @@ impl PhpLspBackend {
         &self,
         params: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
 
         // Try open files first, then fall back to index
-        let (file_symbols, source) = if let Some(parser) = self.open_files.get(&uri_str) {
-            if let Some(tree) = parser.tree() {
-                let source = parser.source();
-                (extract_file_symbols(tree, &source, &uri_str), source)
-            } else {
-                return Ok(None);
+        let (file_symbols, source, tree) = if let Some(parser) = self.open_files.get(&uri_str) {
+            match parser.file_symbols(&uri_str) {
+                Some(file_symbols) => (
+                    file_symbols.into_owned(),
+                    parser.source(),
+                    parser.tree().cloned(),
+                ),
+                None => return Ok(None),
             }
         } else if let Some(file_symbols) = self
             .index
@@ -434,125 +313,19 @@ impl PhpLspBackend {
             else {
                 return Ok(None);
             };
-            (file_symbols, source)
+            let mut parser = FileParser::new();
+            parser.parse_full(&source);
+            let tree = parser.tree().cloned();
+            (file_symbols, source, tree)
         } else {
             return Ok(None);
         };
 
-        // Build hierarchical DocumentSymbol tree
-        let mut top_level: Vec<DocumentSymbol> = Vec::new();
-
-        // Collect type-level symbols (classes, interfaces, traits, enums, functions, constants)
-        // and member symbols (methods, properties, class constants, enum cases)
-        let mut type_symbols: Vec<&php_lsp_types::SymbolInfo> = Vec::new();
-        let mut member_symbols: Vec<&php_lsp_types::SymbolInfo> = Vec::new();
-        let mut namespace_sym: Option<&php_lsp_types::SymbolInfo> = None;
-
-        for sym in &file_symbols.symbols {
-            match sym.kind {
-                php_lsp_types::PhpSymbolKind::Class
-                | php_lsp_types::PhpSymbolKind::Interface
-                | php_lsp_types::PhpSymbolKind::Trait
-                | php_lsp_types::PhpSymbolKind::Enum
-                | php_lsp_types::PhpSymbolKind::Function
-                | php_lsp_types::PhpSymbolKind::GlobalConstant => {
-                    type_symbols.push(sym);
-                }
-                php_lsp_types::PhpSymbolKind::Method
-                | php_lsp_types::PhpSymbolKind::Property
-                | php_lsp_types::PhpSymbolKind::ClassConstant
-                | php_lsp_types::PhpSymbolKind::EnumCase => {
-                    member_symbols.push(sym);
-                }
-                php_lsp_types::PhpSymbolKind::Namespace => {
-                    namespace_sym = Some(sym);
-                }
-            }
-        }
-
-        // Helper to convert SymbolInfo range to LSP Range
-        let to_range =
-            |range: (u32, u32, u32, u32)| -> Range { range_from_byte_range(&source, range) };
-
-        // Build DocumentSymbol for a symbol with its children
-        #[allow(deprecated)] // DocumentSymbol.deprecated field
-        let make_doc_symbol =
-            |sym: &php_lsp_types::SymbolInfo, children: Vec<DocumentSymbol>| -> DocumentSymbol {
-                DocumentSymbol {
-                    name: sym.name.clone(),
-                    detail: sym.signature.as_ref().map(|sig| {
-                        let params_str: Vec<String> = sig
-                            .params
-                            .iter()
-                            .map(|p| {
-                                let mut s = String::new();
-                                if let Some(ref t) = p.type_info {
-                                    s.push_str(&t.to_string());
-                                    s.push(' ');
-                                }
-                                s.push('$');
-                                s.push_str(&p.name);
-                                s
-                            })
-                            .collect();
-                        let mut detail = format!("({})", params_str.join(", "));
-                        if let Some(ref ret) = sig.return_type {
-                            detail.push_str(&format!(": {}", ret));
-                        }
-                        detail
-                    }),
-                    kind: php_kind_to_lsp(sym.kind),
-                    tags: if sym.modifiers.is_deprecated {
-                        Some(vec![SymbolTag::DEPRECATED])
-                    } else {
-                        None
-                    },
-                    deprecated: None,
-                    range: to_range(sym.range),
-                    selection_range: to_range(sym.selection_range),
-                    children: if children.is_empty() {
-                        None
-                    } else {
-                        Some(children)
-                    },
-                }
-            };
-
-        // Build type symbols with their children
-        for type_sym in &type_symbols {
-            let children: Vec<DocumentSymbol> = member_symbols
-                .iter()
-                .filter(|m| m.parent_fqn.as_deref() == Some(&type_sym.fqn))
-                .map(|m| make_doc_symbol(m, vec![]))
-                .collect();
-
-            top_level.push(make_doc_symbol(type_sym, children));
-        }
-
-        // Wrap in namespace if present
-        if let Some(ns) = namespace_sym {
-            #[allow(deprecated)]
-            let ns_symbol = DocumentSymbol {
-                name: ns.name.clone(),
-                detail: None,
-                kind: SymbolKind::NAMESPACE,
-                tags: None,
-                deprecated: None,
-                range: to_range(ns.range),
-                selection_range: to_range(ns.selection_range),
-                children: if top_level.is_empty() {
-                    None
-                } else {
-                    Some(top_level)
-                },
-            };
-            return Ok(Some(DocumentSymbolResponse::Nested(vec![ns_symbol])));
-        }
-
-        if top_level.is_empty() {
+        let symbols = document_symbol_tree(&file_symbols, &source, tree.as_ref(), encoding);
+        if symbols.is_empty() {
             Ok(None)
         } else {
-            Ok(Some(DocumentSymbolResponse::Nested(top_level)))
+            Ok(Some(DocumentSymbolResponse::Nested(symbols)))
         }
     }
 
@@ -560,6 +333,7 @@ impl PhpLspBackend {
         &self,
         params: WorkspaceSymbolParams,
     ) -> Result<Option<WorkspaceSymbolResponse>> {
+        let encoding = self.position_encoding();
         let query = &params.query;
 
         // Empty query returns nothing (avoid overwhelming results)
@@ -568,17 +342,33 @@ impl PhpLspBackend {
         }
 
         let candidates = workspace_symbol_candidates(&self.index, query);
+        let partial = PartialResultSender::new(&self.client, &params.partial_result_params);
 
-        // Limit results to avoid overwhelming the client.
+        // Limit results to avoid overwhelming the client. With a partial
+        // result token, ranked symbols stream in chunks as their ranges resolve.
         let mut source_cache = HashMap::new();
         let mut symbols = Vec::new();
         for candidate in candidates.into_iter().take(200) {
-            if let Some(symbol) =
-                workspace_symbol_information(&candidate.symbol, &self.open_files, &mut source_cache)
-                    .await
+            if let Some(symbol) = workspace_symbol_information(
+                &candidate.symbol,
+                &self.open_files,
+                &mut source_cache,
+                encoding,
+            )
+            .await
             {
                 symbols.push(symbol);
             }
+            if let Some(sender) = &partial {
+                if symbols.len() >= PARTIAL_RESULT_CHUNK_SIZE {
+                    sender.send(&symbols).await;
+                    symbols.clear();
+                }
+            }
+        }
+        if let Some(sender) = &partial {
+            sender.send(&symbols).await;
+            symbols.clear();
         }
 
         Ok(Some(WorkspaceSymbolResponse::Flat(symbols)))
@@ -588,11 +378,12 @@ impl PhpLspBackend {
 pub(in crate::server) fn selection_range_from_byte_ranges(
     source: &str,
     byte_ranges: Vec<(u32, u32, u32, u32)>,
+    encoding: PositionEncoding,
 ) -> Option<SelectionRange> {
     let mut parent = None;
 
     for byte_range in byte_ranges.into_iter().rev() {
-        let range = range_byte_to_utf16(source, byte_range);
+        let range = range_byte_to_lsp(source, byte_range, encoding);
         parent = Some(Box::new(SelectionRange {
             range: Range {
                 start: Position::new(range.0, range.1),
@@ -674,3 +465,365 @@ pub(in crate::server) fn linked_editing_ranges_for_namespace_or_use(
 
     (ranges.len() >= 2).then_some(ranges)
 }
+
+/// Name of the pseudo-container holding top-level executable statements.
+pub(in crate::server) const GLOBAL_CODE_SYMBOL_NAME: &str = "(global code)";
+
+/// Hierarchical outline of one file: namespace → types → members, with class
+/// constants and enum cases nested in "Constants" / "Cases" groups, top-level
+/// statements under a [`GLOBAL_CODE_SYMBOL_NAME`] container and closures or
+/// arrow functions assigned to variables nested in their enclosing symbol.
+pub(in crate::server) fn document_symbol_tree(
+    file_symbols: &php_lsp_types::FileSymbols,
+    source: &str,
+    tree: Option<&tree_sitter::Tree>,
+    encoding: PositionEncoding,
+) -> Vec<DocumentSymbol> {
+    let mut type_symbols: Vec<&php_lsp_types::SymbolInfo> = Vec::new();
+    let mut member_symbols: Vec<&php_lsp_types::SymbolInfo> = Vec::new();
+    let mut namespace_sym: Option<&php_lsp_types::SymbolInfo> = None;
+
+    for sym in &file_symbols.symbols {
+        match sym.kind {
+            php_lsp_types::PhpSymbolKind::Class
+            | php_lsp_types::PhpSymbolKind::Interface
+            | php_lsp_types::PhpSymbolKind::Trait
+            | php_lsp_types::PhpSymbolKind::Enum
+            | php_lsp_types::PhpSymbolKind::Function
+            | php_lsp_types::PhpSymbolKind::GlobalConstant => {
+                type_symbols.push(sym);
+            }
+            php_lsp_types::PhpSymbolKind::Method
+            | php_lsp_types::PhpSymbolKind::Property
+            | php_lsp_types::PhpSymbolKind::ClassConstant
+            | php_lsp_types::PhpSymbolKind::EnumCase => {
+                member_symbols.push(sym);
+            }
+            php_lsp_types::PhpSymbolKind::Namespace => {
+                namespace_sym = Some(sym);
+            }
+        }
+    }
+
+    let mut top_level: Vec<DocumentSymbol> = type_symbols
+        .iter()
+        .map(|type_sym| {
+            let members: Vec<&php_lsp_types::SymbolInfo> = member_symbols
+                .iter()
+                .copied()
+                .filter(|m| m.parent_fqn.as_deref() == Some(&type_sym.fqn))
+                .collect();
+            info_document_symbol(
+                source,
+                type_sym,
+                grouped_member_symbols(source, &members, encoding),
+                encoding,
+            )
+        })
+        .collect();
+
+    if let Some(tree) = tree {
+        let statements = top_level_statements(tree.root_node());
+        let mut global_children = Vec::new();
+        for statement in &statements {
+            for closure in assigned_closures(*statement) {
+                let symbol = closure_document_symbol(source, closure, encoding);
+                if is_global_code_statement(*statement) {
+                    global_children.push(symbol);
+                } else {
+                    insert_nested_document_symbol(&mut top_level, symbol);
+                }
+            }
+        }
+        let global_statements: Vec<tree_sitter::Node> = statements
+            .into_iter()
+            .filter(|statement| is_global_code_statement(*statement))
+            .collect();
+        if let (Some(first), Some(last)) = (global_statements.first(), global_statements.last()) {
+            let range = range_from_byte_range(
+                source,
+                (
+                    first.start_position().row as u32,
+                    first.start_position().column as u32,
+                    last.end_position().row as u32,
+                    last.end_position().column as u32,
+                ),
+                encoding,
+            );
+            top_level.push(group_document_symbol(
+                GLOBAL_CODE_SYMBOL_NAME,
+                SymbolKind::MODULE,
+                range,
+                range_from_byte_range(source, node_byte_range(*first), encoding),
+                global_children,
+            ));
+            top_level.sort_by_key(|symbol| (symbol.range.start.line, symbol.range.start.character));
+        }
+    }
+
+    // Wrap in namespace if present
+    if let Some(ns) = namespace_sym {
+        #[allow(deprecated)]
+        let ns_symbol = DocumentSymbol {
+            name: ns.name.clone(),
+            detail: None,
+            kind: SymbolKind::NAMESPACE,
+            tags: None,
+            deprecated: None,
+            range: range_from_byte_range(source, ns.range, encoding),
+            selection_range: range_from_byte_range(source, ns.selection_range, encoding),
+            children: if top_level.is_empty() {
+                None
+            } else {
+                Some(top_level)
+            },
+        };
+        return vec![ns_symbol];
+    }
+
+    top_level
+}
+
+/// Members of one type: constants and enum cases each collected in a group
+/// node ahead of the properties and methods, which keep source order.
+fn grouped_member_symbols(
+    source: &str,
+    members: &[&php_lsp_types::SymbolInfo],
+    encoding: PositionEncoding,
+) -> Vec<DocumentSymbol> {
+    let mut children = Vec::new();
+    for (name, kind, group_kind) in [
+        (
+            "Cases",
+            php_lsp_types::PhpSymbolKind::EnumCase,
+            SymbolKind::ENUM_MEMBER,
+        ),
+        (
+            "Constants",
+            php_lsp_types::PhpSymbolKind::ClassConstant,
+            SymbolKind::CONSTANT,
+        ),
+    ] {
+        let grouped: Vec<DocumentSymbol> = members
+            .iter()
+            .filter(|member| member.kind == kind)
+            .map(|member| info_document_symbol(source, member, vec![], encoding))
+            .collect();
+        let (Some(first), Some(last)) = (grouped.first(), grouped.last()) else {
+            continue;
+        };
+        let range = Range::new(first.range.start, last.range.end);
+        let selection_range = first.selection_range;
+        children.push(group_document_symbol(
+            name,
+            group_kind,
+            range,
+            selection_range,
+            grouped,
+        ));
+    }
+    children.extend(
+        members
+            .iter()
+            .filter(|member| {
+                !matches!(
+                    member.kind,
+                    php_lsp_types::PhpSymbolKind::EnumCase
+                        | php_lsp_types::PhpSymbolKind::ClassConstant
+                )
+            })
+            .map(|member| info_document_symbol(source, member, vec![], encoding)),
+    );
+    children
+}
+
+#[allow(deprecated)] // DocumentSymbol.deprecated field
+fn info_document_symbol(
+    source: &str,
+    sym: &php_lsp_types::SymbolInfo,
+    children: Vec<DocumentSymbol>,
+    encoding: PositionEncoding,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name: sym.name.clone(),
+        detail: sym.signature.as_ref().map(|sig| {
+            let params_str: Vec<String> = sig
+                .params
+                .iter()
+                .map(|p| {
+                    let mut s = String::new();
+                    if let Some(ref t) = p.type_info {
+                        s.push_str(&t.to_string());
+                        s.push(' ');
+                    }
+                    s.push('$');
+                    s.push_str(&p.name);
+                    s
+                })
+                .collect();
+            let mut detail = format!("({})", params_str.join(", "));
+            if let Some(ref ret) = sig.return_type {
+                detail.push_str(&format!(": {}", ret));
+            }
+            detail
+        }),
+        kind: php_kind_to_lsp(sym.kind),
+        tags: if sym.modifiers.is_deprecated {
+            Some(vec![SymbolTag::DEPRECATED])
+        } else {
+            None
+        },
+        deprecated: None,
+        range: range_from_byte_range(source, sym.range, encoding),
+        selection_range: range_from_byte_range(source, sym.selection_range, encoding),
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    }
+}
+
+#[allow(deprecated)] // DocumentSymbol.deprecated field
+fn group_document_symbol(
+    name: &str,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name: name.to_string(),
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    }
+}
+
+/// Statements directly under the program or a namespace block.
+fn top_level_statements(root: tree_sitter::Node) -> Vec<tree_sitter::Node> {
+    let mut statements = Vec::new();
+    let mut cursor = root.walk();
+    for child in root.named_children(&mut cursor) {
+        let body = (child.kind() == "namespace_definition")
+            .then(|| child.child_by_field_name("body"))
+            .flatten();
+        match body {
+            Some(body) => {
+                let mut body_cursor = body.walk();
+                statements.extend(body.named_children(&mut body_cursor));
+            }
+            None => statements.push(child),
+        }
+    }
+    statements
+}
+
+/// Whether a top-level statement runs when the file is included, as opposed
+/// to declarations that already have their own outline entry.
+fn is_global_code_statement(node: tree_sitter::Node) -> bool {
+    !matches!(
+        node.kind(),
+        "php_tag"
+            | "text"
+            | "text_interpolation"
+            | "comment"
+            | "empty_statement"
+            | "namespace_definition"
+            | "namespace_use_declaration"
+            | "declare_statement"
+            | "const_declaration"
+            | "function_definition"
+            | "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration"
+    )
+}
+
+/// `$name = function () {}` / `$name = fn () => ...` assignments inside `node`.
+fn assigned_closures(node: tree_sitter::Node) -> Vec<tree_sitter::Node> {
+    let mut closures = Vec::new();
+    let mut stack = vec![node];
+    while let Some(current) = stack.pop() {
+        if current.kind() == "assignment_expression"
+            && current
+                .child_by_field_name("left")
+                .is_some_and(|left| left.kind() == "variable_name")
+            && current.child_by_field_name("right").is_some_and(|right| {
+                matches!(right.kind(), "anonymous_function" | "arrow_function")
+            })
+        {
+            closures.push(current);
+        }
+        let mut cursor = current.walk();
+        stack.extend(current.named_children(&mut cursor));
+    }
+    closures.sort_by_key(|closure| closure.start_byte());
+    closures
+}
+
+#[allow(deprecated)] // DocumentSymbol.deprecated field
+fn closure_document_symbol(
+    source: &str,
+    assignment: tree_sitter::Node,
+    encoding: PositionEncoding,
+) -> DocumentSymbol {
+    let variable = assignment.child_by_field_name("left").unwrap_or(assignment);
+    let detail = assignment.child_by_field_name("right").map(|closure| {
+        let keyword = if closure.kind() == "arrow_function" {
+            "fn"
+        } else {
+            "function"
+        };
+        let params = closure
+            .child_by_field_name("parameters")
+            .map(|params| {
+                node_text(source, params)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_else(|| "()".to_string());
+        let mut detail = format!("{keyword}{params}");
+        if let Some(ret) = closure.child_by_field_name("return_type") {
+            detail.push_str(&format!(": {}", node_text(source, ret)));
+        }
+        detail
+    });
+    DocumentSymbol {
+        name: node_text(source, variable).to_string(),
+        detail,
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        deprecated: None,
+        range: range_from_byte_range(source, node_byte_range(assignment), encoding),
+        selection_range: range_from_byte_range(source, node_byte_range(variable), encoding),
+        children: None,
+    }
+}
+
+/// Places `symbol` under the deepest symbol whose range contains it, or at
+/// this level when none does.
+fn insert_nested_document_symbol(symbols: &mut Vec<DocumentSymbol>, symbol: DocumentSymbol) {
+    let contains = |outer: &Range, inner: &Range| {
+        (outer.start.line, outer.start.character) <= (inner.start.line, inner.start.character)
+            && (inner.end.line, inner.end.character) <= (outer.end.line, outer.end.character)
+    };
+    if let Some(parent) = symbols
+        .iter_mut()
+        .find(|candidate| contains(&candidate.range, &symbol.range))
+    {
+        insert_nested_document_symbol(parent.children.get_or_insert_with(Vec::new), symbol);
+    } else {
+        symbols.push(symbol);
+    }
+}
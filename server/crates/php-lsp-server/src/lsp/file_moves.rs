@@ -0,0 +1,439 @@
+//! `workspace/willRenameFiles` edits that keep PSR-4 namespaces in step with
+//! moved PHP files and folders.
+//!
+//! A moved file whose new location maps to a different PSR-4 namespace gets
+//! its `namespace` declaration rewritten. Every class-like declaration in it
+//! moves with it, so `use` clauses importing the old name, fully qualified
+//! references and string literals naming it are rewritten across the
+//! workspace. Unqualified names that would resolve differently afterwards
+//! (same-namespace siblings on either side of the move) gain a `use` import.
+
+use super::super::*;
+use std::collections::BTreeSet;
+
+/// Indexed PHP file that changes namespace because of a file operation.
+struct MovedPhpFile {
+    uri: String,
+    new_namespace: String,
+    namespace_edit: TextEdit,
+}
+
+impl PhpLspBackend {
+    /// Edits for `workspace/willRenameFiles`, or `None` when no moved file
+    /// changes namespace.
+    pub(crate) async fn file_move_namespace_edits(
+        &self,
+        renames: &[FileRename],
+    ) -> Option<WorkspaceEdit> {
+        let moved = self.moved_php_files(renames).await;
+        if moved.is_empty() {
+            return None;
+        }
+
+        // Lowercase old FQN → new FQN of every class-like declaration that moves.
+        let mut moved_types: HashMap<String, String> = HashMap::new();
+        for file in &moved {
+            let Some(file_symbols) = self.index.file_symbols.get(&file.uri) else {
+                continue;
+            };
+            for symbol in &file_symbols.symbols {
+                if is_class_like_kind(symbol.kind) && symbol.parent_fqn.is_none() {
+                    moved_types.insert(
+                        symbol.fqn.trim_start_matches('\\').to_ascii_lowercase(),
+                        format!("{}\\{}", file.new_namespace, short_name(&symbol.fqn)),
+                    );
+                }
+            }
+        }
+
+        let mut affected: BTreeSet<String> = moved.iter().map(|file| file.uri.clone()).collect();
+        for old_fqn in moved_types.keys() {
+            affected.extend(self.index.files_referencing_name(old_fqn));
+        }
+        for entry in self.index.file_symbols.iter() {
+            if entry.value().use_statements.iter().any(|stmt| {
+                stmt.kind == php_lsp_types::UseKind::Class
+                    && moved_types
+                        .contains_key(&stmt.fqn.trim_start_matches('\\').to_ascii_lowercase())
+            }) {
+                affected.insert(entry.key().clone());
+            }
+        }
+
+        let mut changes: std::collections::HashMap<Uri, Vec<TextEdit>> =
+            std::collections::HashMap::new();
+        for file_uri in affected {
+            let moved_file = moved.iter().find(|file| file.uri == file_uri);
+            let mut edits = self
+                .file_move_edits_for_file(
+                    &file_uri,
+                    moved_file.map(|file| file.new_namespace.as_str()),
+                    &moved_types,
+                )
+                .await;
+            if let Some(file) = moved_file {
+                edits.push(file.namespace_edit.clone());
+            }
+            edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+            edits.dedup_by_key(|edit| edit.range);
+            if edits.is_empty() {
+                continue;
+            }
+            if let Ok(uri) = file_uri.parse::<Uri>() {
+                changes.entry(uri).or_default().extend(edits);
+            }
+        }
+
+        if changes.is_empty() {
+            return None;
+        }
+        Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        })
+    }
+
+    /// Indexed files affected by `renames` whose PSR-4 namespace changes.
+    /// Folder renames expand to every indexed file below the folder. Files
+    /// without exactly one `namespace` declaration are left alone.
+    async fn moved_php_files(&self, renames: &[FileRename]) -> Vec<MovedPhpFile> {
+        let encoding = self.position_encoding();
+        let configs = self.workspace_configs.lock().await.clone();
+        let mut moved = Vec::new();
+        for rename in renames {
+            let files: Vec<(String, String)> =
+                if self.index.file_symbols.contains_key(&rename.old_uri) {
+                    vec![(rename.old_uri.clone(), rename.new_uri.clone())]
+                } else {
+                    let old_prefix = format!("{}/", rename.old_uri.trim_end_matches('/'));
+                    let new_prefix = format!("{}/", rename.new_uri.trim_end_matches('/'));
+                    self.index
+                        .file_symbols
+                        .iter()
+                        .filter_map(|entry| {
+                            let rest = entry.key().strip_prefix(&old_prefix)?;
+                            Some((entry.key().clone(), format!("{new_prefix}{rest}")))
+                        })
+                        .collect()
+                };
+
+            for (old_uri, new_uri) in files {
+                let Some(old_namespace) = self
+                    .index
+                    .file_symbols
+                    .get(&old_uri)
+                    .and_then(|file_symbols| file_symbols.namespace.clone())
+                else {
+                    continue;
+                };
+                let Some(new_namespace) =
+                    uri_to_path(&new_uri).and_then(|path| psr4_namespace_for_path(&configs, &path))
+                else {
+                    continue;
+                };
+                if new_namespace == old_namespace {
+                    continue;
+                }
+                let Some(source) = self.source_for_uri(&old_uri, "willRenameFiles").await else {
+                    continue;
+                };
+                let Some(namespace_edit) =
+                    namespace_declaration_edit(&source, &old_namespace, &new_namespace, encoding)
+                else {
+                    continue;
+                };
+                moved.push(MovedPhpFile {
+                    uri: old_uri,
+                    new_namespace,
+                    namespace_edit,
+                });
+            }
+        }
+        moved
+    }
+
+    /// `use` clause, class reference and import edits for one file. Only
+    /// references whose resolution changes after the move are touched.
+    async fn file_move_edits_for_file(
+        &self,
+        uri_str: &str,
+        new_namespace: Option<&str>,
+        moved_types: &HashMap<String, String>,
+    ) -> Vec<TextEdit> {
+        let encoding = self.position_encoding();
+        let Some(file_symbols) = self
+            .index
+            .file_symbols
+            .get(uri_str)
+            .map(|entry| entry.value().clone())
+        else {
+            return Vec::new();
+        };
+        let Some(source) = self.source_for_uri(uri_str, "willRenameFiles").await else {
+            return Vec::new();
+        };
+        let references = self
+            .index
+            .file_references
+            .get(uri_str)
+            .map(|entry| entry.value().clone())
+            .unwrap_or_default();
+        let moved_fqn = |fqn: &str| -> Option<String> {
+            moved_types
+                .get(&fqn.trim_start_matches('\\').to_ascii_lowercase())
+                .cloned()
+        };
+
+        let old_namespace = file_symbols.namespace.clone().unwrap_or_default();
+        let final_namespace = new_namespace.unwrap_or(&old_namespace).to_string();
+        let mut imports_before = HashMap::new();
+        let mut imports_after = HashMap::new();
+        let mut edits = Vec::new();
+        for stmt in &file_symbols.use_statements {
+            if stmt.kind != php_lsp_types::UseKind::Class {
+                continue;
+            }
+            let fqn = stmt.fqn.trim_start_matches('\\');
+            let key = stmt
+                .alias
+                .as_deref()
+                .unwrap_or_else(|| short_name(fqn))
+                .to_ascii_lowercase();
+            let new_fqn = moved_fqn(fqn);
+            if let Some(new_fqn) = &new_fqn {
+                edits.extend(use_clause_fqn_edit(
+                    &source, stmt.range, fqn, new_fqn, encoding,
+                ));
+            }
+            imports_before.insert(key.clone(), fqn.to_string());
+            imports_after.insert(key, new_fqn.unwrap_or_else(|| fqn.to_string()));
+        }
+
+        let mut taken_names: HashSet<String> = imports_after.keys().cloned().collect();
+        taken_names.extend(
+            file_symbols
+                .symbols
+                .iter()
+                .filter(|symbol| is_class_like_kind(symbol.kind) && symbol.parent_fqn.is_none())
+                .map(|symbol| short_name(&symbol.fqn).to_ascii_lowercase()),
+        );
+        let mut new_imports: Vec<String> = Vec::new();
+        for reference in &references {
+            if reference.is_declaration || !is_class_like_kind(reference.target_kind) {
+                continue;
+            }
+            let target = reference.target_fqn.trim_start_matches('\\');
+            let target_moved = moved_fqn(target);
+            if target_moved.is_none() && new_namespace.is_none() {
+                continue;
+            }
+            let target_final = target_moved.as_deref().unwrap_or(target);
+            let range = range_from_lsp_tuple(reference.range);
+            let Some(text) = text_at_lsp_range(&source, range, encoding) else {
+                continue;
+            };
+            if reference.is_string_literal {
+                if target_moved.is_some() {
+                    edits.extend(string_class_reference_edit(
+                        text,
+                        range,
+                        target,
+                        target_final,
+                    ));
+                }
+                continue;
+            }
+            if is_special_class_name(text)
+                || !resolve_class_name_text(text, &imports_before, &old_namespace)
+                    .eq_ignore_ascii_case(target)
+                || resolve_class_name_text(text, &imports_after, &final_namespace)
+                    .eq_ignore_ascii_case(target_final)
+            {
+                continue;
+            }
+
+            let target_short = short_name(target_final);
+            let imported = new_imports
+                .iter()
+                .any(|fqn| fqn.eq_ignore_ascii_case(target_final));
+            if text.eq_ignore_ascii_case(target_short)
+                && (imported || taken_names.insert(target_short.to_ascii_lowercase()))
+            {
+                if !imported {
+                    new_imports.push(target_final.to_string());
+                }
+                continue;
+            }
+            edits.push(TextEdit {
+                range,
+                new_text: format!("\\{target_final}"),
+            });
+        }
+
+        if !new_imports.is_empty() {
+            new_imports.sort();
+            let insert_line = find_use_insert_line(&source, &file_symbols);
+            let mut import_text = String::new();
+            for fqn in &new_imports {
+                import_text.push_str(&build_use_statement(fqn, ImportKind::Class, None));
+                import_text.push('\n');
+            }
+            // A first import block is set apart by blank lines on both sides.
+            if file_symbols.use_statements.is_empty() {
+                if line_is_blank(&source, insert_line) {
+                    import_text.insert(0, '\n');
+                } else {
+                    import_text.push('\n');
+                }
+            }
+            edits.push(TextEdit {
+                range: Range {
+                    start: Position::new(insert_line, 0),
+                    end: Position::new(insert_line, 0),
+                },
+                new_text: import_text,
+            });
+        }
+        edits
+    }
+}
+
+/// Class name the PSR-4 mapping of the innermost workspace root containing
+/// `path` assigns to that file.
+pub(in crate::server) fn psr4_class_name_for_path(
+    configs: &[WorkspaceRootConfig],
+    path: &Path,
+) -> Option<String> {
+    configs
+        .iter()
+        .filter(|config| path.starts_with(&config.root))
+        .max_by_key(|config| config.root.components().count())
+        .and_then(|config| config.namespace_map.as_ref())
+        .and_then(|map| map.class_name_for_path(path))
+}
+
+/// Namespace a PSR-4 mapping assigns to a class file at `path`. Files mapped
+/// to the root namespace have none.
+fn psr4_namespace_for_path(configs: &[WorkspaceRootConfig], path: &Path) -> Option<String> {
+    psr4_class_name_for_path(configs, path).and_then(|fqn| {
+        fqn.rsplit_once('\\')
+            .map(|(namespace, _)| namespace.to_string())
+    })
+}
+
+/// Edit replacing the name of the only `namespace` declaration in `source`.
+fn namespace_declaration_edit(
+    source: &str,
+    old_namespace: &str,
+    new_namespace: &str,
+    encoding: PositionEncoding,
+) -> Option<TextEdit> {
+    let mut declarations = source.split('\n').enumerate().filter_map(|(line, text)| {
+        let indent = text.len() - text.trim_start().len();
+        let rest = text[indent..].strip_prefix("namespace")?;
+        let name_start = indent + "namespace".len() + (rest.len() - rest.trim_start().len());
+        let name_len = text[name_start..]
+            .find(|ch: char| ch != '\\' && !ch.is_alphanumeric() && ch != '_')
+            .unwrap_or(text.len() - name_start);
+        (name_start > indent + "namespace".len() && name_len > 0).then_some((
+            line as u32,
+            name_start as u32,
+            (name_start + name_len) as u32,
+        ))
+    });
+    let (line, start, end) = declarations.next()?;
+    if declarations.next().is_some() {
+        return None;
+    }
+    let line_text = source.split('\n').nth(line as usize)?;
+    if line_text[start as usize..end as usize] != *old_namespace {
+        return None;
+    }
+    Some(TextEdit {
+        range: range_from_byte_range(source, (line, start, line, end), encoding),
+        new_text: new_namespace.to_string(),
+    })
+}
+
+/// Replace the whole name imported by a `use` clause when it is `old_fqn`.
+/// Group clauses only spell part of the name and are left alone.
+fn use_clause_fqn_edit(
+    source: &str,
+    clause_range: (u32, u32, u32, u32),
+    old_fqn: &str,
+    new_fqn: &str,
+    encoding: PositionEncoding,
+) -> Option<TextEdit> {
+    let (line, start_col, _, _) = clause_range;
+    let line_text = source.split('\n').nth(line as usize)?;
+    let clause_text = line_text.get(start_col as usize..)?;
+    let name_len = clause_text
+        .find(|ch: char| ch != '\\' && !ch.is_alphanumeric() && ch != '_')
+        .unwrap_or(clause_text.len());
+    let name = &clause_text[..name_len];
+    if !name.trim_start_matches('\\').eq_ignore_ascii_case(old_fqn) {
+        return None;
+    }
+    let leading = if name.starts_with('\\') { "\\" } else { "" };
+    Some(TextEdit {
+        range: range_from_byte_range(
+            source,
+            (line, start_col, line, start_col + name_len as u32),
+            encoding,
+        ),
+        new_text: format!("{leading}{new_fqn}"),
+    })
+}
+
+/// Rewrite a class name spelled as a string literal, keeping its leading
+/// backslash and `\\` escaping.
+fn string_class_reference_edit(
+    text: &str,
+    range: Range,
+    old_fqn: &str,
+    new_fqn: &str,
+) -> Option<TextEdit> {
+    let escaped = text.contains("\\\\");
+    let name = text.replace("\\\\", "\\");
+    if !name.trim_start_matches('\\').eq_ignore_ascii_case(old_fqn) {
+        return None;
+    }
+    let mut new_text = if name.starts_with('\\') {
+        format!("\\{new_fqn}")
+    } else {
+        new_fqn.to_string()
+    };
+    if escaped {
+        new_text = new_text.replace('\\', "\\\\");
+    }
+    Some(TextEdit { range, new_text })
+}
+
+/// Resolve a class name as written in a file with the given class imports
+/// (lowercase alias → FQN) and namespace.
+fn resolve_class_name_text(
+    text: &str,
+    imports: &HashMap<String, String>,
+    namespace: &str,
+) -> String {
+    if let Some(fully_qualified) = text.strip_prefix('\\') {
+        return fully_qualified.to_string();
+    }
+    let (first, rest) = match text.split_once('\\') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (text, None),
+    };
+    match (imports.get(&first.to_ascii_lowercase()), rest) {
+        (Some(imported), Some(rest)) => format!("{imported}\\{rest}"),
+        (Some(imported), None) => imported.clone(),
+        (None, _) if namespace.is_empty() => text.to_string(),
+        (None, _) => format!("{namespace}\\{text}"),
+    }
+}
+
+fn is_special_class_name(text: &str) -> bool {
+    ["self", "static", "parent"]
+        .iter()
+        .any(|name| text.eq_ignore_ascii_case(name))
+}
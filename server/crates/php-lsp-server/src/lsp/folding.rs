@@ -120,12 +120,10 @@ impl PhpLspBackend {
             else {
                 return Ok(None);
             };
-            let mut parser = FileParser::new();
-            parser.parse_full(&source);
-            let Some(tree) = parser.tree() else {
+            let Some(parsed) = self.closed_file_tree(&uri_str, source).await else {
                 return Ok(None);
             };
-            folding_ranges(tree, &source)
+            folding_ranges(&parsed.tree, &parsed.source)
         };
 
         if ranges.is_empty() {
@@ -4,7 +4,7 @@ use super::super::*;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-fn full_document_range(source: &str) -> Range {
+pub(in crate::server) fn full_document_range(source: &str, encoding: PositionEncoding) -> Range {
     let mut line = 0u32;
     let mut character = 0u32;
 
@@ -13,7 +13,7 @@ fn full_document_range(source: &str) -> Range {
             line += 1;
             character = 0;
         } else {
-            character += ch.len_utf16() as u32;
+            character += encoding.char_len(ch) as u32;
         }
     }
 
@@ -221,6 +221,17 @@ fn strip_range_formatter_wrapper(formatted: String, was_wrapped: bool) -> String
         .to_string()
 }
 
+/// Whether `line` belongs to a range selection. A selection ending at column 0
+/// does not include that last line.
+fn range_covers_line(range: Range, line: u32) -> bool {
+    let last_line = if range.end.character == 0 && range.end.line > range.start.line {
+        range.end.line - 1
+    } else {
+        range.end.line
+    };
+    (range.start.line..=last_line).contains(&line)
+}
+
 fn formatting_source_line(source: &str, line: u32) -> Option<&str> {
     source.split('\n').nth(line as usize)
 }
@@ -234,10 +245,6 @@ fn leading_indent(line: &str) -> &str {
     &line[..indent_end]
 }
 
-fn utf16_len(text: &str) -> u32 {
-    text.chars().map(|ch| ch.len_utf16() as u32).sum()
-}
-
 fn formatting_indent_unit(options: &FormattingOptions) -> String {
     if options.insert_spaces {
         " ".repeat(options.tab_size.max(1) as usize)
@@ -266,16 +273,44 @@ fn brace_depth_before_line(source: &str, line: u32) -> usize {
     depth as usize
 }
 
-fn on_type_indent_edit(source: &str, line: u32, options: &FormattingOptions) -> Option<TextEdit> {
-    let current_line = formatting_source_line(source, line)?;
+/// Indentation of `line` from the CST, or from brace counting while the file
+/// has syntax errors. Blank lines keep an indent so the cursor lands at the
+/// right column after Enter.
+fn on_type_indent_edit(
+    tree: Option<&tree_sitter::Tree>,
+    source: &str,
+    line: u32,
+    options: &FormattingOptions,
+    encoding: PositionEncoding,
+) -> Option<TextEdit> {
+    use php_lsp_parser::indent::LineIndent;
+
+    let current_line = formatting_source_line(source, line)?.trim_end_matches('\r');
     let current_indent = leading_indent(current_line);
-    let trimmed = current_line.trim_start_matches([' ', '\t']);
-    let mut depth = brace_depth_before_line(source, line);
-    if trimmed.starts_with('}') {
-        depth = depth.saturating_sub(1);
-    }
-
-    let desired_indent = formatting_indent_unit(options).repeat(depth);
+    let unit = formatting_indent_unit(options);
+    let cst_indent = tree
+        .and_then(|tree| php_lsp_parser::indent::line_indents(tree, source))
+        .and_then(|indents| indents.get(line as usize).copied());
+
+    let desired_indent = match cst_indent {
+        Some(LineIndent::Preserve) => return None,
+        Some(LineIndent::Blank { level }) => unit.repeat(level as usize),
+        Some(LineIndent::Indent { level, align }) => {
+            format!(
+                "{}{}",
+                unit.repeat(level as usize),
+                " ".repeat(align as usize)
+            )
+        }
+        None => {
+            let trimmed = current_line.trim_start_matches([' ', '\t']);
+            let mut depth = brace_depth_before_line(source, line);
+            if trimmed.starts_with('}') {
+                depth = depth.saturating_sub(1);
+            }
+            unit.repeat(depth)
+        }
+    };
     if desired_indent == current_indent {
         return None;
     }
@@ -283,17 +318,200 @@ fn on_type_indent_edit(source: &str, line: u32, options: &FormattingOptions) ->
     Some(TextEdit {
         range: Range {
             start: Position::new(line, 0),
-            end: Position::new(line, utf16_len(current_indent)),
+            end: Position::new(line, encoding.text_len(current_indent)),
         },
         new_text: desired_indent,
     })
 }
 
+/// Largest old×new line product diffed with LCS before falling back to one
+/// edit spanning the changed middle section.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Position just past `lines[..index]`, where each line keeps its `\n`.
+fn line_boundary_position(lines: &[&str], index: usize, encoding: PositionEncoding) -> Position {
+    match index.checked_sub(1).and_then(|last| lines.get(last)) {
+        Some(last) if !last.ends_with('\n') => {
+            Position::new((index - 1) as u32, encoding.text_len(last))
+        }
+        _ => Position::new(index as u32, 0),
+    }
+}
+
+/// Turn formatter output into line-level edits against `old`, so unchanged
+/// lines keep their cursor positions, folds and diagnostics.
+pub(crate) fn minimal_text_edits(
+    old: &str,
+    new: &str,
+    encoding: PositionEncoding,
+) -> Vec<TextEdit> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return Vec::new();
+    }
+
+    let hunk = |old_start: usize, old_end: usize, new_start: usize, new_end: usize| TextEdit {
+        range: Range {
+            start: line_boundary_position(&old_lines, prefix + old_start, encoding),
+            end: line_boundary_position(&old_lines, prefix + old_end, encoding),
+        },
+        new_text: new_mid[new_start..new_end].concat(),
+    };
+
+    let (n, m) = (old_mid.len(), new_mid.len());
+    if n == 0 || m == 0 || n.saturating_mul(m) > MAX_DIFF_CELLS {
+        return vec![hunk(0, n, 0, m)];
+    }
+
+    // lcs[i][j] = length of the LCS of old_mid[i..] and new_mid[j..].
+    let width = m + 1;
+    let mut lcs = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0usize, 0usize);
+    let (mut hunk_i, mut hunk_j) = (0usize, 0usize);
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            if hunk_i < i || hunk_j < j {
+                edits.push(hunk(hunk_i, i, hunk_j, j));
+            }
+            i += 1;
+            j += 1;
+            hunk_i = i;
+            hunk_j = j;
+        } else if j < m && (i == n || lcs[i * width + j + 1] >= lcs[(i + 1) * width + j]) {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    if hunk_i < n || hunk_j < m {
+        edits.push(hunk(hunk_i, n, hunk_j, m));
+    }
+    edits
+}
+
+/// Reindent `source` from its CST, touching only leading whitespace.
+/// Returns `None` when the file has syntax errors.
+pub(crate) fn builtin_indent_edits(
+    tree: &tree_sitter::Tree,
+    source: &str,
+    options: &FormattingOptions,
+    encoding: PositionEncoding,
+) -> Option<Vec<TextEdit>> {
+    let indents = php_lsp_parser::indent::line_indents(tree, source)?;
+    let unit = formatting_indent_unit(options);
+    let edits = source
+        .split('\n')
+        .zip(indents)
+        .enumerate()
+        .filter_map(|(line, (text, indent))| {
+            indent_line_edit(
+                line as u32,
+                text.trim_end_matches('\r'),
+                indent,
+                &unit,
+                encoding,
+            )
+        })
+        .collect();
+    Some(edits)
+}
+
+fn indent_line_edit(
+    line: u32,
+    text: &str,
+    indent: php_lsp_parser::indent::LineIndent,
+    unit: &str,
+    encoding: PositionEncoding,
+) -> Option<TextEdit> {
+    use php_lsp_parser::indent::LineIndent;
+
+    let current_indent = leading_indent(text);
+    let (desired, replaced) = match indent {
+        LineIndent::Preserve => return None,
+        LineIndent::Blank { .. } => (String::new(), text),
+        LineIndent::Indent { level, align } => (
+            format!(
+                "{}{}",
+                unit.repeat(level as usize),
+                " ".repeat(align as usize)
+            ),
+            current_indent,
+        ),
+    };
+    if desired == replaced {
+        return None;
+    }
+    let replace_len = encoding.text_len(replaced);
+
+    Some(TextEdit {
+        range: Range {
+            start: Position::new(line, 0),
+            end: Position::new(line, replace_len),
+        },
+        new_text: desired,
+    })
+}
+
 impl PhpLspBackend {
+    /// Run the configured phpcbf command on a temporary copy of `source` and
+    /// return a whole-document edit, or `None` when phpcbf changed nothing.
+    pub(crate) async fn phpcbf_fix_edit(
+        &self,
+        uri_str: &str,
+        source: String,
+    ) -> std::result::Result<Option<TextEdit>, String> {
+        let encoding = self.position_encoding();
+        let phpcs_config = self.phpcs_config.lock().await.clone();
+        let config = FormattingConfig {
+            provider: "custom".to_string(),
+            command: Some(phpcs_config.fix_command),
+            timeout_ms: phpcs_config.timeout_ms,
+        };
+        let workspace_root = self.workspace_root_for_uri(uri_str).await;
+
+        let token = self.start_formatter_run(uri_str).await;
+        let fixed =
+            run_external_formatter(source.clone(), config, workspace_root, Some(token.clone()))
+                .await;
+        self.finish_formatter_run(uri_str, &token).await;
+
+        Ok(fixed?.map(|fixed| TextEdit {
+            range: full_document_range(&source, encoding),
+            new_text: fixed,
+        }))
+    }
+
     pub(crate) async fn lsp_formatting(
         &self,
         params: DocumentFormattingParams,
     ) -> Result<Option<Vec<TextEdit>>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         tracing::debug!("formatting: {}", uri_str);
 
@@ -311,7 +529,16 @@ impl PhpLspBackend {
             .resolve_for_workspace_blocking(workspace_root.as_deref())
             .await;
         if config.command_template().is_none() {
-            return Ok(None);
+            if !config.uses_builtin_formatter() || self.template_documents.contains_key(&uri_str) {
+                return Ok(None);
+            }
+            let Some(parser) = self.open_files.get(&uri_str) else {
+                return Ok(None);
+            };
+            let edits = parser
+                .tree()
+                .and_then(|tree| builtin_indent_edits(tree, &source, &params.options, encoding));
+            return Ok(Some(edits.unwrap_or_default()));
         }
 
         let token = self.start_formatter_run(&uri_str).await;
@@ -338,16 +565,14 @@ impl PhpLspBackend {
             }
         };
 
-        Ok(Some(vec![TextEdit {
-            range: full_document_range(&source),
-            new_text: formatted,
-        }]))
+        Ok(Some(minimal_text_edits(&source, &formatted, encoding)))
     }
 
     pub(crate) async fn lsp_range_formatting(
         &self,
         params: DocumentRangeFormattingParams,
     ) -> Result<Option<Vec<TextEdit>>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         tracing::debug!("rangeFormatting: {}", uri_str);
 
@@ -359,7 +584,7 @@ impl PhpLspBackend {
             parser.source()
         };
 
-        let Some(fragment) = text_at_lsp_range(&source, params.range) else {
+        let Some(fragment) = text_at_lsp_range(&source, params.range, encoding) else {
             return Ok(Some(vec![]));
         };
         if fragment.is_empty() {
@@ -372,7 +597,22 @@ impl PhpLspBackend {
             .resolve_for_workspace_blocking(workspace_root.as_deref())
             .await;
         if config.command_template().is_none() {
-            return Ok(None);
+            if !config.uses_builtin_formatter() || self.template_documents.contains_key(&uri_str) {
+                return Ok(None);
+            }
+            let Some(parser) = self.open_files.get(&uri_str) else {
+                return Ok(None);
+            };
+            let edits = parser
+                .tree()
+                .and_then(|tree| builtin_indent_edits(tree, &source, &params.options, encoding))
+                .unwrap_or_default();
+            return Ok(Some(
+                edits
+                    .into_iter()
+                    .filter(|edit| range_covers_line(params.range, edit.range.start.line))
+                    .collect(),
+            ));
         }
 
         let (formatter_input, was_wrapped) = range_formatter_input(fragment);
@@ -414,6 +654,7 @@ impl PhpLspBackend {
         &self,
         params: DocumentOnTypeFormattingParams,
     ) -> Result<Option<Vec<TextEdit>>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position
             .text_document
@@ -433,13 +674,13 @@ impl PhpLspBackend {
             return Ok(Some(vec![]));
         }
 
-        let source = {
-            let parser = match self.open_files.get(&uri_str) {
-                Some(parser) => parser,
-                None => return Ok(None),
-            };
-            parser.source()
+        if self.template_documents.contains_key(&uri_str) {
+            return Ok(Some(vec![]));
+        }
+        let Some(parser) = self.open_files.get(&uri_str) else {
+            return Ok(None);
         };
+        let source = parser.source();
 
         let Some(current_line) = formatting_source_line(&source, position.line) else {
             return Ok(Some(vec![]));
@@ -453,9 +694,15 @@ impl PhpLspBackend {
         }
 
         Ok(Some(
-            on_type_indent_edit(&source, position.line, &params.options)
-                .into_iter()
-                .collect(),
+            on_type_indent_edit(
+                parser.tree(),
+                &source,
+                position.line,
+                &params.options,
+                encoding,
+            )
+            .into_iter()
+            .collect(),
         ))
     }
 }
@@ -3,6 +3,7 @@
 use crate::util::lsp_text::range_from_byte_range;
 
 use super::super::*;
+use php_lsp_parser::references::ReferenceKind;
 
 fn is_call_hierarchy_symbol_kind(kind: php_lsp_types::PhpSymbolKind) -> bool {
     matches!(
@@ -21,6 +22,7 @@ fn is_call_hierarchy_ref_kind(ref_kind: RefKind) -> bool {
 fn call_hierarchy_item_from_symbol(
     sym: &php_lsp_types::SymbolInfo,
     source: &str,
+    encoding: PositionEncoding,
 ) -> Option<CallHierarchyItem> {
     let uri = sym.uri.parse::<Uri>().ok()?;
     Some(CallHierarchyItem {
@@ -32,8 +34,8 @@ fn call_hierarchy_item_from_symbol(
             .then_some(vec![SymbolTag::DEPRECATED]),
         detail: Some(call_hierarchy_detail(sym)),
         uri,
-        range: range_from_byte_range(source, sym.range),
-        selection_range: range_from_byte_range(source, sym.selection_range),
+        range: range_from_byte_range(source, sym.range, encoding),
+        selection_range: range_from_byte_range(source, sym.selection_range, encoding),
         data: Some(serde_json::json!({
             "fqn": sym.fqn,
             "kind": call_hierarchy_kind_key(sym.kind),
@@ -55,7 +57,7 @@ fn call_hierarchy_detail(sym: &php_lsp_types::SymbolInfo) -> String {
         }
         detail
     } else {
-        sym.fqn.clone()
+        sym.fqn.to_string()
     }
 }
 
@@ -105,6 +107,7 @@ fn is_type_hierarchy_symbol_kind(kind: php_lsp_types::PhpSymbolKind) -> bool {
 fn type_hierarchy_item_from_symbol(
     sym: &php_lsp_types::SymbolInfo,
     source: &str,
+    encoding: PositionEncoding,
 ) -> Option<TypeHierarchyItem> {
     if !is_type_hierarchy_symbol_kind(sym.kind) {
         return None;
@@ -114,10 +117,10 @@ fn type_hierarchy_item_from_symbol(
         name: sym.name.clone(),
         kind: php_kind_to_lsp(sym.kind),
         tags: sym.modifiers.is_deprecated.then_some(SymbolTag::DEPRECATED),
-        detail: Some(sym.fqn.clone()),
+        detail: Some(sym.fqn.to_string()),
         uri,
-        range: range_from_byte_range(source, sym.range),
-        selection_range: range_from_byte_range(source, sym.selection_range),
+        range: range_from_byte_range(source, sym.range, encoding),
+        selection_range: range_from_byte_range(source, sym.selection_range, encoding),
         data: Some(serde_json::json!({
             "fqn": sym.fqn,
             "kind": call_hierarchy_kind_key(sym.kind),
@@ -128,6 +131,7 @@ fn type_hierarchy_item_from_symbol(
 async fn type_hierarchy_symbol_from_item(
     backend: &PhpLspBackend,
     item: &TypeHierarchyItem,
+    encoding: PositionEncoding,
 ) -> Option<Arc<php_lsp_types::SymbolInfo>> {
     if let Some(data) = item.data.as_ref() {
         if let Some(fqn) = data.get("fqn").and_then(|value| value.as_str()) {
@@ -145,16 +149,18 @@ async fn type_hierarchy_symbol_from_item(
         .await?;
     let selection = (
         item.selection_range.start.line,
-        utf16_col_to_byte(
+        lsp_col_to_byte(
             &source,
             item.selection_range.start.line,
             item.selection_range.start.character,
+            encoding,
         ),
         item.selection_range.end.line,
-        utf16_col_to_byte(
+        lsp_col_to_byte(
             &source,
             item.selection_range.end.line,
             item.selection_range.end.character,
+            encoding,
         ),
     );
     backend
@@ -301,6 +307,7 @@ pub(super) fn implementation_symbols_for_method(
 async fn call_hierarchy_symbol_from_item(
     backend: &PhpLspBackend,
     item: &CallHierarchyItem,
+    encoding: PositionEncoding,
 ) -> Option<Arc<php_lsp_types::SymbolInfo>> {
     if let Some(data) = item.data.as_ref() {
         if let Some(fqn) = data.get("fqn").and_then(|value| value.as_str()) {
@@ -316,16 +323,18 @@ async fn call_hierarchy_symbol_from_item(
         .await?;
     let selection = (
         item.selection_range.start.line,
-        utf16_col_to_byte(
+        lsp_col_to_byte(
             &source,
             item.selection_range.start.line,
             item.selection_range.start.character,
+            encoding,
         ),
         item.selection_range.end.line,
-        utf16_col_to_byte(
+        lsp_col_to_byte(
             &source,
             item.selection_range.end.line,
             item.selection_range.end.character,
+            encoding,
         ),
     );
     backend
@@ -349,8 +358,9 @@ async fn call_hierarchy_symbol_from_item(
 async fn call_hierarchy_target_from_item(
     backend: &PhpLspBackend,
     item: &CallHierarchyItem,
+    encoding: PositionEncoding,
 ) -> Option<(Arc<php_lsp_types::SymbolInfo>, php_lsp_types::PhpSymbolKind)> {
-    let sym = call_hierarchy_symbol_from_item(backend, item).await?;
+    let sym = call_hierarchy_symbol_from_item(backend, item, encoding).await?;
     let kind = item
         .data
         .as_ref()
@@ -368,10 +378,18 @@ fn incoming_call_hierarchy_for_file(
     target_fqn: &str,
     target_kind: php_lsp_types::PhpSymbolKind,
     calls_by_caller: &mut HashMap<String, (php_lsp_types::SymbolInfo, Vec<Range>)>,
+    encoding: PositionEncoding,
 ) {
     let refs = find_references_in_file(tree, source, file_symbols, target_fqn, target_kind, false);
 
     for reference in refs {
+        // Trait adaptations and other non-call mentions are not incoming calls.
+        if !matches!(
+            reference.kind,
+            ReferenceKind::Call | ReferenceKind::Instantiate
+        ) {
+            continue;
+        }
         let Some(caller) = containing_callable_symbol(file_symbols, reference.range) else {
             continue;
         };
@@ -380,10 +398,10 @@ fn incoming_call_hierarchy_for_file(
         }
 
         calls_by_caller
-            .entry(caller.fqn.clone())
+            .entry(caller.fqn.to_string())
             .or_insert_with(|| (caller.clone(), Vec::new()))
             .1
-            .push(range_from_byte_range(source, reference.range));
+            .push(range_from_byte_range(source, reference.range, encoding));
     }
 }
 
@@ -401,6 +419,7 @@ fn outgoing_call_hierarchy_for_tree(
     file_symbols: &php_lsp_types::FileSymbols,
     index: &WorkspaceIndex,
     caller: &php_lsp_types::SymbolInfo,
+    encoding: PositionEncoding,
 ) -> Vec<(Arc<php_lsp_types::SymbolInfo>, Vec<Range>)> {
     let ctx = OutgoingCallHierarchyContext {
         tree,
@@ -411,7 +430,7 @@ fn outgoing_call_hierarchy_for_tree(
     };
     let mut calls_by_target: HashMap<String, (Arc<php_lsp_types::SymbolInfo>, Vec<Range>)> =
         HashMap::new();
-    collect_outgoing_call_hierarchy(tree.root_node(), &ctx, &mut calls_by_target);
+    collect_outgoing_call_hierarchy(tree.root_node(), &ctx, &mut calls_by_target, encoding);
 
     let mut calls: Vec<_> = calls_by_target.into_values().collect();
     calls.sort_by(|left, right| left.0.name.cmp(&right.0.name));
@@ -422,6 +441,7 @@ fn collect_outgoing_call_hierarchy(
     node: tree_sitter::Node,
     ctx: &OutgoingCallHierarchyContext<'_>,
     calls_by_target: &mut HashMap<String, (Arc<php_lsp_types::SymbolInfo>, Vec<Range>)>,
+    encoding: PositionEncoding,
 ) {
     let node_range = node_range_node(node);
     if !byte_ranges_overlap(node_range, ctx.caller_range) {
@@ -452,12 +472,13 @@ fn collect_outgoing_call_hierarchy(
             ) {
                 if is_call_hierarchy_symbol_kind(target.kind) {
                     calls_by_target
-                        .entry(target.fqn.clone())
+                        .entry(target.fqn.to_string())
                         .or_insert_with(|| (target.clone(), Vec::new()))
                         .1
                         .push(range_from_byte_range(
                             ctx.source,
                             node_range_node(name_node),
+                            encoding,
                         ));
                 }
             }
@@ -466,7 +487,7 @@ fn collect_outgoing_call_hierarchy(
 
     let mut cursor = node.walk();
     for child in node.named_children(&mut cursor) {
-        collect_outgoing_call_hierarchy(child, ctx, calls_by_target);
+        collect_outgoing_call_hierarchy(child, ctx, calls_by_target, encoding);
     }
 }
 
@@ -476,8 +497,9 @@ impl PhpLspBackend {
         symbol: &php_lsp_types::SymbolInfo,
         label: &'static str,
     ) -> Option<CallHierarchyItem> {
+        let encoding = self.position_encoding();
         let source = self.source_for_uri(&symbol.uri, label).await?;
-        call_hierarchy_item_from_symbol(symbol, &source)
+        call_hierarchy_item_from_symbol(symbol, &source, encoding)
     }
 
     pub(in crate::server) async fn type_hierarchy_item_for_symbol(
@@ -485,14 +507,16 @@ impl PhpLspBackend {
         symbol: &php_lsp_types::SymbolInfo,
         label: &'static str,
     ) -> Option<TypeHierarchyItem> {
+        let encoding = self.position_encoding();
         let source = self.source_for_uri(&symbol.uri, label).await?;
-        type_hierarchy_item_from_symbol(symbol, &source)
+        type_hierarchy_item_from_symbol(symbol, &source, encoding)
     }
 
     pub(crate) async fn lsp_prepare_call_hierarchy(
         &self,
         params: CallHierarchyPrepareParams,
     ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position_params
             .text_document
@@ -511,7 +535,7 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
             let file_symbols = self
                 .index
                 .file_symbols
@@ -594,7 +618,9 @@ impl PhpLspBackend {
         &self,
         params: CallHierarchyIncomingCallsParams,
     ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
-        let Some((target, target_kind)) = call_hierarchy_target_from_item(self, &params.item).await
+        let encoding = self.position_encoding();
+        let Some((target, target_kind)) =
+            call_hierarchy_target_from_item(self, &params.item, encoding).await
         else {
             return Ok(None);
         };
@@ -615,6 +641,7 @@ impl PhpLspBackend {
                         &target.fqn,
                         target_kind,
                         &mut calls_by_caller,
+                        encoding,
                     );
                 }
                 continue;
@@ -628,16 +655,15 @@ impl PhpLspBackend {
             else {
                 continue;
             };
-            let mut parser = FileParser::new();
-            parser.parse_full(&source);
-            if let Some(tree) = parser.tree() {
+            if let Some(parsed) = self.closed_file_tree(&file_uri, source).await {
                 incoming_call_hierarchy_for_file(
-                    tree,
-                    &source,
+                    &parsed.tree,
+                    &parsed.source,
                     &file_symbols,
                     &target.fqn,
                     target_kind,
                     &mut calls_by_caller,
+                    encoding,
                 );
             }
         }
@@ -670,7 +696,9 @@ impl PhpLspBackend {
         &self,
         params: CallHierarchyOutgoingCallsParams,
     ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
-        let Some((caller, _)) = call_hierarchy_target_from_item(self, &params.item).await else {
+        let encoding = self.position_encoding();
+        let Some((caller, _)) = call_hierarchy_target_from_item(self, &params.item, encoding).await
+        else {
             return Ok(None);
         };
         if !is_call_hierarchy_symbol_kind(caller.kind) {
@@ -681,16 +709,23 @@ impl PhpLspBackend {
         let file_symbols = self
             .index
             .file_symbols
-            .get(&file_uri)
+            .get(file_uri.as_str())
             .map(|entry| entry.value().clone())
             .unwrap_or_default();
 
-        let call_targets = if let Some(parser) = self.open_files.get(&file_uri) {
+        let call_targets = if let Some(parser) = self.open_files.get(file_uri.as_str()) {
             let Some(tree) = parser.tree() else {
                 return Ok(None);
             };
             let source = parser.source();
-            outgoing_call_hierarchy_for_tree(tree, &source, &file_symbols, &self.index, &caller)
+            outgoing_call_hierarchy_for_tree(
+                tree,
+                &source,
+                &file_symbols,
+                &self.index,
+                &caller,
+                encoding,
+            )
         } else {
             let Some(path) = uri_to_path(&file_uri) else {
                 return Ok(None);
@@ -700,12 +735,17 @@ impl PhpLspBackend {
             else {
                 return Ok(None);
             };
-            let mut parser = FileParser::new();
-            parser.parse_full(&source);
-            let Some(tree) = parser.tree() else {
+            let Some(parsed) = self.closed_file_tree(&file_uri, source).await else {
                 return Ok(None);
             };
-            outgoing_call_hierarchy_for_tree(tree, &source, &file_symbols, &self.index, &caller)
+            outgoing_call_hierarchy_for_tree(
+                &parsed.tree,
+                &parsed.source,
+                &file_symbols,
+                &self.index,
+                &caller,
+                encoding,
+            )
         };
 
         let mut calls = Vec::new();
@@ -736,6 +776,7 @@ impl PhpLspBackend {
         &self,
         params: TypeHierarchyPrepareParams,
     ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position_params
             .text_document
@@ -754,7 +795,7 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
             let file_symbols = self
                 .index
                 .file_symbols
@@ -829,7 +870,9 @@ impl PhpLspBackend {
         &self,
         params: TypeHierarchySupertypesParams,
     ) -> Result<Option<Vec<TypeHierarchyItem>>> {
-        let Some(symbol) = type_hierarchy_symbol_from_item(self, &params.item).await else {
+        let encoding = self.position_encoding();
+        let Some(symbol) = type_hierarchy_symbol_from_item(self, &params.item, encoding).await
+        else {
             return Ok(None);
         };
 
@@ -862,7 +905,9 @@ impl PhpLspBackend {
         &self,
         params: TypeHierarchySubtypesParams,
     ) -> Result<Option<Vec<TypeHierarchyItem>>> {
-        let Some(symbol) = type_hierarchy_symbol_from_item(self, &params.item).await else {
+        let encoding = self.position_encoding();
+        let Some(symbol) = type_hierarchy_symbol_from_item(self, &params.item, encoding).await
+        else {
             return Ok(None);
         };
 
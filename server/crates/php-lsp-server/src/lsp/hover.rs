@@ -4,12 +4,13 @@ use super::super::*;
 
 impl PhpLspBackend {
     pub(crate) async fn lsp_hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let encoding = self.position_encoding();
         let uri = params.text_document_position_params.text_document.uri;
         let uri_str = uri.as_str().to_string();
         let original_pos = params.text_document_position_params.position;
         let template_document = self.template_document(&uri_str);
         let pos = if let Some(template) = &template_document {
-            match template.map_original_position_to_virtual(original_pos) {
+            match template.map_original_position_to_virtual(original_pos, encoding) {
                 Some(pos) => pos,
                 None => return Ok(None),
             }
@@ -22,6 +23,7 @@ impl PhpLspBackend {
         let (
             sym_at_pos,
             local_var_hover,
+            local_var_definition,
             shape_member_hover,
             call_site_return_type,
             file_symbols,
@@ -38,8 +40,8 @@ impl PhpLspBackend {
             };
 
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-            let utf16_index = Utf16LineIndex::new(&source);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+            let line_index = LspLineIndex::new(&source, encoding);
 
             // Get file symbols for name resolution
             let file_symbols = self
@@ -70,7 +72,7 @@ impl PhpLspBackend {
                 file_symbols: &file_symbols,
                 index: &self.index,
                 type_cache: &type_cache,
-                utf16_index: &utf16_index,
+                line_index: &line_index,
                 requested_range: (0, 0, u32::MAX, u32::MAX),
                 allow_twig_property_accessors: template_document
                     .as_ref()
@@ -82,8 +84,42 @@ impl PhpLspBackend {
                 &source,
                 (pos.line, byte_col, pos.line, byte_col),
             );
-            let local_var_hover = variable_node_at_position
-                .and_then(|variable_node| local_variable_hover_data(&ctx, variable_node));
+            let local_var_hover = variable_node_at_position.and_then(|variable_node| {
+                // Unknown template variables are not declared anywhere, so only
+                // PHP files answer with a bare `variable $name` hover.
+                let mut hover_data =
+                    local_variable_hover_data(&ctx, variable_node).or_else(|| {
+                        if template_document.is_some() {
+                            return None;
+                        }
+                        Some(LocalVariableHoverData {
+                            variable_name: variable_text_for_node(&source, variable_node)?,
+                            type_hint: None,
+                            phpdoc_comment: None,
+                        })
+                    })?;
+                if hover_data.type_hint.is_none() {
+                    hover_data.type_hint = infer_variable_type_at_position_with_resolvers(
+                        tree,
+                        &source,
+                        &file_symbols,
+                        pos.line,
+                        byte_col,
+                        &hover_data.variable_name,
+                        Some(&resolver),
+                        Some(&callable_param_resolver),
+                    )
+                    .map(|type_fqn| local_variable_hover_type_from_fqn(&type_fqn, &file_symbols))
+                    .or_else(|| local_variable_hover_literal_type(&source, variable_node));
+                }
+                Some(hover_data)
+            });
+            // Template variables map into generated virtual PHP, so their
+            // definition location would not point at the template source.
+            let local_var_definition = local_var_hover
+                .as_ref()
+                .filter(|_| template_document.is_none())
+                .and_then(|_| variable_definition_at_position(tree, &source, pos.line, byte_col));
 
             let inferred_member_symbol = server_member_symbol_at_position(&ctx, pos.line, byte_col);
             let shape_member_hover = shape_member_access_info_at_position(&ctx, pos.line, byte_col);
@@ -114,6 +150,15 @@ impl PhpLspBackend {
                 None => {
                     if let Some(sym) = inferred_member_symbol {
                         sym
+                    } else if let Some(shape_member) = &shape_member_hover {
+                        // Quoted `array{...}` keys have no symbol of their own.
+                        SymbolAtPosition {
+                            fqn: shape_member.member_name.clone(),
+                            name: shape_member.member_name.clone(),
+                            ref_kind: RefKind::Variable,
+                            object_expr: None,
+                            range: shape_member.range,
+                        }
                     } else {
                         let Some(variable_node) = variable_node_at_position else {
                             return Ok(None);
@@ -136,6 +181,7 @@ impl PhpLspBackend {
             (
                 sym_at_pos,
                 local_var_hover,
+                local_var_definition,
                 shape_member_hover,
                 call_site_return_type,
                 file_symbols,
@@ -172,9 +218,16 @@ impl PhpLspBackend {
         let twig_accessor_alias = (symbol_info.is_none() && twig_accessor_symbol.is_some())
             .then(|| sym_at_pos.name.trim_start_matches('$').to_string());
         let symbol_info = symbol_info.or(twig_accessor_symbol);
+        // Members synthesized from `@property`/`@method` tags hover like the
+        // tag itself rather than as a declaration carrying the class docblock.
+        let indexed_virtual_member = symbol_info
+            .as_deref()
+            .and_then(|sym| phpdoc_virtual_member_for_indexed_symbol(&self.index, sym));
+        let symbol_info = symbol_info.filter(|_| indexed_virtual_member.is_none());
 
         let virtual_member = if symbol_info.is_none() {
-            phpdoc_virtual_member_for_symbol(&self.index, &sym_at_pos)
+            indexed_virtual_member
+                .or_else(|| phpdoc_virtual_member_for_symbol(&self.index, &sym_at_pos))
         } else {
             None
         };
@@ -198,7 +251,8 @@ impl PhpLspBackend {
             None
         };
 
-        let hover_range = range_from_byte_range(&source, sym_at_pos.range);
+        let hover_range = range_from_byte_range(&source, sym_at_pos.range, encoding);
+        let workspace_roots = self.current_workspace_roots().await;
         let result = if let Some(sym) = symbol_info {
             // Build hover content
             let mut content = String::new();
@@ -227,7 +281,26 @@ impl PhpLspBackend {
             append_hover_symbol_declaration(&mut content, &sym, kind_label);
             content.push_str("\n```\n");
 
-            let parsed_phpdoc = sym.doc_comment.as_deref().map(parse_phpdoc);
+            // Undocumented and `@inheritdoc` members show the documentation of
+            // the nearest ancestor declaration that has some.
+            let inherited_doc_symbol = hover_inherited_doc_symbol(&self.index, &sym);
+            let doc_symbol = inherited_doc_symbol.as_ref().unwrap_or(&sym);
+            let inherited_file_symbols = inherited_doc_symbol.as_ref().map(|ancestor| {
+                hover_file_symbols_for_uri(&self.index, &file_symbols, &ancestor.uri)
+            });
+            let doc_file_symbols = inherited_file_symbols
+                .as_ref()
+                .unwrap_or(&hover_file_symbols);
+            let doc_owner_fqn = hover_symbol_type_owner_fqn(doc_symbol);
+            let mut parsed_phpdoc = doc_symbol.doc_comment.as_deref().map(parse_phpdoc);
+            // `@throws` plus uncaught `throw new` statements, already resolved.
+            if let Some(sig) = sym.signature.as_ref().filter(|sig| !sig.throws.is_empty()) {
+                parsed_phpdoc.get_or_insert_with(Default::default).throws = sig
+                    .throws
+                    .iter()
+                    .map(|fqn| php_lsp_types::TypeInfo::Simple(format!("\\{fqn}")))
+                    .collect();
+            }
 
             append_hover_symbol_identity_line(&mut content, &sym);
             if let Some(alias) = twig_accessor_alias.as_deref() {
@@ -244,7 +317,14 @@ impl PhpLspBackend {
                     parent_fqn,
                 );
             }
-            append_hover_symbol_source_line(&mut content, &sym);
+            append_hover_symbol_source_line(&mut content, &sym, &workspace_roots);
+            let manual_url = php_manual_url(&sym);
+            if let Some(url) = manual_url.as_deref() {
+                content.push_str(&format!(
+                    "\n**PHP manual:** [{}](<{url}>)\n",
+                    markdown_code_span(url.trim_start_matches("https://www."))
+                ));
+            }
             append_hover_framework_metadata_lines(
                 &mut content,
                 &self.index,
@@ -301,7 +381,19 @@ impl PhpLspBackend {
             if let Some(phpdoc) = parsed_phpdoc.as_ref() {
                 if let Some(ref summary) = phpdoc.summary {
                     content.push_str("\n---\n\n");
-                    content.push_str(summary);
+                    content.push_str(&phpdoc_text_markdown(summary));
+                    content.push('\n');
+                }
+                if let Some(ref description) = phpdoc.description {
+                    for paragraph in description.split("\n\n") {
+                        content.push('\n');
+                        content.push_str(&phpdoc_text_markdown(paragraph));
+                        content.push('\n');
+                    }
+                }
+                if let Some(ancestor) = inherited_doc_symbol.as_ref() {
+                    content.push_str("\n*Documentation inherited from* ");
+                    content.push_str(&hover_method_relation_entry_markdown(ancestor));
                     content.push('\n');
                 }
 
@@ -310,19 +402,23 @@ impl PhpLspBackend {
                     content.push_str("\n**Returns:** ");
                     content.push_str(&type_info_raw_with_links(
                         &self.index,
-                        &hover_file_symbols,
-                        type_owner_fqn,
-                        &sym.uri,
+                        doc_file_symbols,
+                        doc_owner_fqn,
+                        &doc_symbol.uri,
                         ret,
                     ));
+                    if let Some(ref description) = phpdoc.return_description {
+                        content.push_str(" — ");
+                        content.push_str(&phpdoc_text_markdown(description));
+                    }
                     content.push('\n');
                 }
 
                 for section in phpdoc_extra_markdown_sections_with_links(
                     &self.index,
-                    &hover_file_symbols,
-                    type_owner_fqn,
-                    &sym.uri,
+                    doc_file_symbols,
+                    doc_owner_fqn,
+                    &doc_symbol.uri,
                     phpdoc,
                 ) {
                     content.push('\n');
@@ -330,6 +426,8 @@ impl PhpLspBackend {
                     content.push('\n');
                 }
 
+                append_hover_phpdoc_reference_lines(&mut content, phpdoc, manual_url.is_some());
+
                 // @deprecated
                 if let Some(ref dep) = phpdoc.deprecated {
                     content.push_str("\n⚠️ **Deprecated**");
@@ -419,7 +517,11 @@ impl PhpLspBackend {
                     kind: MarkupKind::Markdown,
                     value: content,
                 }),
-                range: Some(range_from_byte_range(&source, shape_member_hover.range)),
+                range: Some(range_from_byte_range(
+                    &source,
+                    shape_member_hover.range,
+                    encoding,
+                )),
             })
         } else if let Some(var_info) = local_var_hover {
             let mut content = String::new();
@@ -440,6 +542,15 @@ impl PhpLspBackend {
                 content.push('\n');
             }
 
+            if let Some(definition) = local_var_definition {
+                append_hover_variable_definition_line(
+                    &mut content,
+                    &uri_str,
+                    definition,
+                    &workspace_roots,
+                );
+            }
+
             if let Some(ref doc) = var_info.phpdoc_comment {
                 let phpdoc = parse_phpdoc(doc);
                 let local_type_owner_fqn =
@@ -475,7 +586,7 @@ impl PhpLspBackend {
 
         Ok(result.map(|mut hover| {
             if let (Some(template), Some(range)) = (&template_document, hover.range) {
-                hover.range = template.map_virtual_range_to_original(range);
+                hover.range = template.map_virtual_range_to_original(range, encoding);
             }
             hover
         }))
@@ -552,6 +663,7 @@ fn append_hover_symbol_declaration(
         php_lsp_types::PhpSymbolKind::EnumCase => {
             content.push_str("case ");
             content.push_str(&symbol.name);
+            append_hover_initializer_value(content, symbol);
         }
         _ => {
             content.push_str(&hover_symbol_prefix(symbol, kind_label));
@@ -626,6 +738,7 @@ fn append_hover_class_constant_declaration(
         content.push(' ');
     }
     content.push_str(&symbol.name);
+    append_hover_initializer_value(content, symbol);
 }
 
 fn append_hover_global_constant_declaration(
@@ -642,6 +755,24 @@ fn append_hover_global_constant_declaration(
         content.push(' ');
     }
     content.push_str(&hover_source_like_symbol_name(symbol));
+    append_hover_initializer_value(content, symbol);
+}
+
+/// Longest initializer shown in the declaration code block; longer values
+/// (typically large array literals) are cut with an ellipsis.
+const HOVER_INITIALIZER_MAX_CHARS: usize = 120;
+
+fn append_hover_initializer_value(content: &mut String, symbol: &php_lsp_types::SymbolInfo) {
+    let Some(value) = symbol.value.as_deref() else {
+        return;
+    };
+    content.push_str(" = ");
+    if value.chars().count() > HOVER_INITIALIZER_MAX_CHARS {
+        content.extend(value.chars().take(HOVER_INITIALIZER_MAX_CHARS));
+        content.push_str("...");
+    } else {
+        content.push_str(value);
+    }
 }
 
 fn hover_source_like_symbol_name(symbol: &php_lsp_types::SymbolInfo) -> String {
@@ -656,7 +787,7 @@ fn hover_source_like_symbol_name(symbol: &php_lsp_types::SymbolInfo) -> String {
         | php_lsp_types::PhpSymbolKind::Enum
         | php_lsp_types::PhpSymbolKind::Function
         | php_lsp_types::PhpSymbolKind::GlobalConstant => symbol.name.clone(),
-        php_lsp_types::PhpSymbolKind::Namespace => symbol.fqn.clone(),
+        php_lsp_types::PhpSymbolKind::Namespace => symbol.fqn.to_string(),
     }
 }
 
@@ -678,9 +809,104 @@ fn append_hover_symbol_identity_line(content: &mut String, symbol: &php_lsp_type
     content.push('\n');
 }
 
-fn append_hover_symbol_source_line(content: &mut String, symbol: &php_lsp_types::SymbolInfo) {
+/// `@since`, `@see` and `@link` tags. Manual links of built-ins are
+/// skipped when the hover already links the php.net page.
+fn append_hover_phpdoc_reference_lines(
+    content: &mut String,
+    phpdoc: &php_lsp_types::PhpDoc,
+    has_manual_link: bool,
+) {
+    if let Some(since) = phpdoc.since.as_deref() {
+        content.push_str("\n**Since:** ");
+        content.push_str(since);
+        content.push('\n');
+    }
+    if !phpdoc.see.is_empty() {
+        let references = phpdoc
+            .see
+            .iter()
+            .map(|reference| phpdoc_reference_markdown(reference))
+            .collect::<Vec<_>>();
+        content.push_str("\n**See:** ");
+        content.push_str(&references.join(", "));
+        content.push('\n');
+    }
+    for link in &phpdoc.links {
+        let url = link.split_whitespace().next().unwrap_or_default();
+        if has_manual_link && url.contains("php.net/") {
+            continue;
+        }
+        content.push_str("\n**Link:** ");
+        content.push_str(&phpdoc_reference_markdown(link));
+        content.push('\n');
+    }
+}
+
+/// A `@see`/`@link` target: URLs become links (using any trailing text as
+/// the label), anything else is shown as code.
+fn phpdoc_reference_markdown(reference: &str) -> String {
+    let (target, text) = reference
+        .split_once(char::is_whitespace)
+        .map_or((reference, ""), |(target, text)| (target, text.trim()));
+    if target.starts_with("http://") || target.starts_with("https://") {
+        let label = if text.is_empty() { target } else { text };
+        format!("[{label}](<{target}>)")
+    } else {
+        markdown_code_span(reference)
+    }
+}
+
+/// php.net manual page for a built-in function, class-like or method, e.g.
+/// `https://www.php.net/function.array-map` or
+/// `https://www.php.net/arrayobject.construct`.
+fn php_manual_url(symbol: &php_lsp_types::SymbolInfo) -> Option<String> {
+    if !symbol.modifiers.is_builtin && !symbol.uri.starts_with("phpstub://") {
+        return None;
+    }
+    let slug = |name: &str| {
+        name.trim_start_matches('\\')
+            .to_ascii_lowercase()
+            .replace(['\\', '_'], "-")
+    };
+    let page = match symbol.kind {
+        php_lsp_types::PhpSymbolKind::Function => format!("function.{}", slug(&symbol.fqn)),
+        php_lsp_types::PhpSymbolKind::Class
+        | php_lsp_types::PhpSymbolKind::Interface
+        | php_lsp_types::PhpSymbolKind::Trait
+        | php_lsp_types::PhpSymbolKind::Enum => format!("class.{}", slug(&symbol.fqn)),
+        // Synthetic enum members live in the declaring file, not the stubs.
+        php_lsp_types::PhpSymbolKind::Method if !symbol.uri.starts_with("phpstub://") => format!(
+            "{}.{}",
+            if symbol.name == "cases" {
+                "unitenum"
+            } else {
+                "backedenum"
+            },
+            slug(&symbol.name)
+        ),
+        php_lsp_types::PhpSymbolKind::Method => format!(
+            "{}.{}",
+            slug(symbol.parent_fqn.as_deref()?),
+            slug(symbol.name.trim_start_matches("__"))
+        ),
+        _ => return None,
+    };
+    Some(format!("https://www.php.net/{page}"))
+}
+
+/// Source file link, followed by the containing namespace for top-level
+/// symbols. Members name their class on the `Declared in` line instead.
+fn append_hover_symbol_source_line(
+    content: &mut String,
+    symbol: &php_lsp_types::SymbolInfo,
+    workspace_roots: &[PathBuf],
+) {
     let destination = markdown_file_location_destination(symbol);
-    let source_label = hover_symbol_source_label(symbol);
+    let source_label = hover_source_location_label(
+        &symbol.uri,
+        symbol.selection_range.0.saturating_add(1),
+        workspace_roots,
+    );
     content.push('\n');
     content.push_str("**Source:** ");
     content.push_str(&format!(
@@ -688,15 +914,101 @@ fn append_hover_symbol_source_line(content: &mut String, symbol: &php_lsp_types:
         markdown_code_span(&source_label),
         destination
     ));
+    if let Some(namespace) = hover_symbol_container_namespace(symbol) {
+        content.push_str(&format!(" ({})", markdown_code_span(namespace)));
+    }
     content.push('\n');
 }
 
-fn hover_symbol_source_label(symbol: &php_lsp_types::SymbolInfo) -> String {
-    let line = symbol.selection_range.0.saturating_add(1);
-    if let Some(path) = uri_to_path(&symbol.uri) {
+fn append_hover_variable_definition_line(
+    content: &mut String,
+    uri: &str,
+    definition: (u32, u32, u32, u32),
+    workspace_roots: &[PathBuf],
+) {
+    let line = definition.0.saturating_add(1);
+    let label = hover_source_location_label(uri, line, workspace_roots);
+    content.push('\n');
+    content.push_str("**Defined:** ");
+    content.push_str(&format!(
+        "[{}](<{uri}#L{line}>)",
+        markdown_code_span(&label)
+    ));
+    content.push('\n');
+}
+
+/// Hover type for a variable whose type is only known from the parser's
+/// position-based inference (a bare FQN or scalar type name).
+fn local_variable_hover_type_from_fqn(
+    type_fqn: &str,
+    file_symbols: &php_lsp_types::FileSymbols,
+) -> LocalVariableInlayType {
+    let target_fqn = type_display_has_single_object_target(type_fqn).then(|| {
+        type_fqn
+            .trim_start_matches('?')
+            .trim_start_matches('\\')
+            .to_string()
+    });
+    LocalVariableInlayType {
+        display: shorten_inlay_type_display(type_fqn, file_symbols),
+        target_fqn,
+    }
+}
+
+/// Scalar type of a literal assigned to the variable. Inlay hints skip these
+/// as noise, but hover should still answer `$count = 3` with `int`.
+fn local_variable_hover_literal_type(
+    source: &str,
+    variable_node: tree_sitter::Node,
+) -> Option<LocalVariableInlayType> {
+    let variable_name = variable_text_for_node(source, variable_node)?;
+    let rhs = current_assignment_rhs_for_variable(variable_node, source).or_else(|| {
+        latest_assignment_rhs_before_usage(
+            local_variable_scope_node(variable_node),
+            &variable_name,
+            variable_node.start_byte(),
+            source,
+        )
+        .map(|(_, rhs)| rhs)
+    })?;
+    let type_info = scalar_literal_type_info_from_text(node_text(source, rhs))?;
+    Some(LocalVariableInlayType {
+        display: type_info.to_string(),
+        target_fqn: None,
+    })
+}
+
+/// `path:line` for a file, relative to the innermost workspace root
+/// containing it so vendor and project files read like `vendor/...`.
+fn hover_source_location_label(uri: &str, line: u32, workspace_roots: &[PathBuf]) -> String {
+    if let Some(path) = uri_to_path(uri) {
+        let relative = workspace_roots
+            .iter()
+            .filter_map(|root| path.strip_prefix(root).ok())
+            .min_by_key(|relative| relative.components().count());
+        if let Some(relative) = relative {
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            return format!("{relative}:{line}");
+        }
         return format!("{}:{line}", path.display());
     }
-    format!("{}:{line}", symbol.uri)
+    format!("{uri}:{line}")
+}
+
+fn hover_symbol_container_namespace(symbol: &php_lsp_types::SymbolInfo) -> Option<&str> {
+    if symbol.parent_fqn.is_some() {
+        return None;
+    }
+    symbol
+        .fqn
+        .trim_start_matches('\\')
+        .rsplit_once('\\')
+        .map(|(namespace, _)| namespace)
+        .filter(|namespace| !namespace.is_empty())
 }
 
 fn hover_call_site_return_type_at_position(
@@ -1336,6 +1648,97 @@ fn hover_method_relations(
     relations
 }
 
+/// Nearest ancestor declaration of an overriding or implementing method or
+/// property without its own documentation (no docblock, or only
+/// `@inheritdoc`) that documents the member. Parent classes are searched
+/// before interfaces.
+fn hover_inherited_doc_symbol(
+    index: &WorkspaceIndex,
+    symbol: &php_lsp_types::SymbolInfo,
+) -> Option<php_lsp_types::SymbolInfo> {
+    if !matches!(
+        symbol.kind,
+        php_lsp_types::PhpSymbolKind::Method | php_lsp_types::PhpSymbolKind::Property
+    ) || !phpdoc_inherits_documentation(symbol.doc_comment.as_deref())
+    {
+        return None;
+    }
+
+    let mut visited = std::collections::HashSet::new();
+    let mut pending = std::collections::VecDeque::from([symbol.clone()]);
+    while let Some(current) = pending.pop_front() {
+        if !visited.insert(format!("{}|{}", current.fqn, current.uri)) {
+            continue;
+        }
+        let ancestors = if current.kind == php_lsp_types::PhpSymbolKind::Method {
+            let relations = hover_method_relations(index, &current);
+            relations
+                .overrides
+                .into_iter()
+                .chain(relations.implements)
+                .collect()
+        } else {
+            hover_parent_property_declarations(index, &current)
+        };
+        for ancestor in ancestors {
+            if !phpdoc_inherits_documentation(ancestor.doc_comment.as_deref()) {
+                return Some(ancestor);
+            }
+            pending.push_back(ancestor);
+        }
+    }
+    None
+}
+
+/// Whether a docblock is missing or holds nothing but an `@inheritdoc` tag.
+fn phpdoc_inherits_documentation(doc_comment: Option<&str>) -> bool {
+    let Some(doc_comment) = doc_comment else {
+        return true;
+    };
+    let text = doc_comment
+        .trim()
+        .trim_start_matches("/**")
+        .trim_end_matches("*/")
+        .lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_lowercase();
+    text.replace("{@inheritdoc}", "")
+        .replace("@inheritdoc", "")
+        .trim()
+        .is_empty()
+}
+
+/// Declaration of the same property in the nearest parent class that
+/// declares it.
+fn hover_parent_property_declarations(
+    index: &WorkspaceIndex,
+    property: &php_lsp_types::SymbolInfo,
+) -> Vec<php_lsp_types::SymbolInfo> {
+    let mut owner_fqn = property.parent_fqn.as_deref().map(str::to_string);
+    let mut visited = std::collections::HashSet::new();
+    while let Some(parent_fqn) = owner_fqn
+        .as_deref()
+        .and_then(|fqn| hover_index_type_symbol(index, fqn))
+        .and_then(|owner| owner.extends.first().cloned())
+    {
+        if !visited.insert(normalized_hover_relation_target(&parent_fqn)) {
+            break;
+        }
+        if let Some(declaration) = hover_direct_member_for_type(
+            index,
+            &parent_fqn,
+            php_lsp_types::PhpSymbolKind::Property,
+            &property.name,
+        ) {
+            return vec![declaration];
+        }
+        owner_fqn = Some(parent_fqn);
+    }
+    Vec::new()
+}
+
 fn collect_hover_method_interface_targets(
     index: &WorkspaceIndex,
     owner: &php_lsp_types::SymbolInfo,
@@ -1499,15 +1902,29 @@ fn hover_direct_method_for_type(
     index: &WorkspaceIndex,
     type_fqn: &str,
     method_name: &str,
+) -> Option<php_lsp_types::SymbolInfo> {
+    hover_direct_member_for_type(
+        index,
+        type_fqn,
+        php_lsp_types::PhpSymbolKind::Method,
+        method_name,
+    )
+}
+
+fn hover_direct_member_for_type(
+    index: &WorkspaceIndex,
+    type_fqn: &str,
+    kind: php_lsp_types::PhpSymbolKind,
+    member_name: &str,
 ) -> Option<php_lsp_types::SymbolInfo> {
     let type_symbol = hover_index_type_symbol(index, type_fqn)?;
-    let file_symbols = index.file_symbols.get(&type_symbol.uri)?;
+    let file_symbols = index.file_symbols.get(type_symbol.uri.as_str())?;
     file_symbols
         .symbols
         .iter()
         .find(|candidate| {
-            candidate.kind == php_lsp_types::PhpSymbolKind::Method
-                && candidate.name == method_name
+            candidate.kind == kind
+                && candidate.name == member_name
                 && candidate.parent_fqn.as_deref().is_some_and(|parent_fqn| {
                     normalized_hover_relation_target(parent_fqn)
                         == normalized_hover_relation_target(type_fqn)
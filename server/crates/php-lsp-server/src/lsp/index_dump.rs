@@ -0,0 +1,201 @@
+//! Symbol index export for `php-lsp.dumpIndex`.
+//!
+//! `json` writes one document with every indexed file, its symbols, and
+//! optionally its precomputed references. `sql` writes a SQLite-compatible
+//! script (`sqlite3 index.db < dump.sql`) with `symbols` and `symbol_references`
+//! tables, which keeps the server free of a database dependency. There is no
+//! `sqlite` format: asking for one is an error rather than a script in disguise.
+
+use super::super::*;
+use php_lsp_types::{SymbolInfo, SymbolReference};
+use std::io::{BufWriter, Write};
+
+/// Bumped when exported fields are removed or change meaning.
+const INDEX_DUMP_SCHEMA_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(in crate::server) enum IndexDumpFormat {
+    Json,
+    Sql,
+}
+
+impl IndexDumpFormat {
+    pub(in crate::server) fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "sql" => Some(Self::Sql),
+            _ => None,
+        }
+    }
+
+    pub(in crate::server) fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Sql => "sql",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(in crate::server) struct IndexDumpSummary {
+    pub files: usize,
+    pub symbols: usize,
+    pub references: usize,
+}
+
+/// Writes the index to `path`, replacing any existing file.
+pub(in crate::server) fn write_index_dump(
+    index: &WorkspaceIndex,
+    path: &Path,
+    format: IndexDumpFormat,
+    include_references: bool,
+) -> std::io::Result<IndexDumpSummary> {
+    let mut uris: Vec<String> = index
+        .file_symbols
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect();
+    uris.sort();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = BufWriter::new(std::fs::File::create(path)?);
+    let summary = match format {
+        IndexDumpFormat::Json => write_json_dump(index, &uris, include_references, &mut out)?,
+        IndexDumpFormat::Sql => write_sql_dump(index, &uris, include_references, &mut out)?,
+    };
+    out.flush()?;
+    Ok(summary)
+}
+
+fn write_json_dump(
+    index: &WorkspaceIndex,
+    uris: &[String],
+    include_references: bool,
+    out: &mut impl Write,
+) -> std::io::Result<IndexDumpSummary> {
+    let mut summary = IndexDumpSummary::default();
+    let mut files = Vec::with_capacity(uris.len());
+    for uri in uris {
+        let Some(file_symbols) = index.file_symbols.get(uri) else {
+            continue;
+        };
+        summary.files += 1;
+        summary.symbols += file_symbols.symbols.len();
+        let mut file = serde_json::json!({
+            "uri": uri,
+            "namespace": file_symbols.namespace,
+            "symbols": file_symbols.symbols,
+        });
+        if include_references {
+            let references = index
+                .file_references
+                .get(uri)
+                .map(|references| references.value().clone())
+                .unwrap_or_default();
+            summary.references += references.len();
+            file["references"] = serde_json::json!(references);
+        }
+        files.push(file);
+    }
+
+    let dump = serde_json::json!({
+        "schemaVersion": INDEX_DUMP_SCHEMA_VERSION,
+        "server": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "files": files,
+    });
+    serde_json::to_writer(&mut *out, &dump)?;
+    Ok(summary)
+}
+
+fn write_sql_dump(
+    index: &WorkspaceIndex,
+    uris: &[String],
+    include_references: bool,
+    out: &mut impl Write,
+) -> std::io::Result<IndexDumpSummary> {
+    writeln!(out, "-- php-lsp {} index dump", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "PRAGMA user_version = {INDEX_DUMP_SCHEMA_VERSION};")?;
+    writeln!(out, "BEGIN TRANSACTION;")?;
+    writeln!(out, "DROP TABLE IF EXISTS symbols;")?;
+    writeln!(out, "DROP TABLE IF EXISTS symbol_references;")?;
+    writeln!(
+        out,
+        "CREATE TABLE symbols (uri TEXT NOT NULL, fqn TEXT NOT NULL, name TEXT NOT NULL, \
+         kind TEXT NOT NULL, parent_fqn TEXT, start_line INTEGER, start_col INTEGER, \
+         end_line INTEGER, end_col INTEGER, data TEXT NOT NULL);"
+    )?;
+    writeln!(
+        out,
+        "CREATE TABLE symbol_references (uri TEXT NOT NULL, target_fqn TEXT NOT NULL, \
+         target_kind TEXT NOT NULL, start_line INTEGER, start_character INTEGER, \
+         end_line INTEGER, end_character INTEGER, is_declaration INTEGER NOT NULL);"
+    )?;
+
+    let mut summary = IndexDumpSummary::default();
+    for uri in uris {
+        let Some(file_symbols) = index.file_symbols.get(uri) else {
+            continue;
+        };
+        summary.files += 1;
+        for symbol in &file_symbols.symbols {
+            summary.symbols += 1;
+            write_symbol_row(out, symbol)?;
+        }
+        if include_references {
+            if let Some(references) = index.file_references.get(uri) {
+                for reference in references.iter() {
+                    summary.references += 1;
+                    write_reference_row(out, uri, reference)?;
+                }
+            }
+        }
+    }
+
+    writeln!(out, "CREATE INDEX symbols_fqn ON symbols (fqn);")?;
+    writeln!(
+        out,
+        "CREATE INDEX symbol_references_target ON symbol_references (target_fqn);"
+    )?;
+    writeln!(out, "COMMIT;")?;
+    Ok(summary)
+}
+
+fn write_symbol_row(out: &mut impl Write, symbol: &SymbolInfo) -> std::io::Result<()> {
+    let (start_line, start_col, end_line, end_col) = symbol.range;
+    let data = serde_json::to_string(symbol).map_err(std::io::Error::other)?;
+    writeln!(
+        out,
+        "INSERT INTO symbols VALUES ({}, {}, {}, {}, {}, {start_line}, {start_col}, {end_line}, {end_col}, {});",
+        sql_text(&symbol.uri),
+        sql_text(&symbol.fqn),
+        sql_text(&symbol.name),
+        sql_text(&format!("{:?}", symbol.kind)),
+        symbol.parent_fqn.as_deref().map_or_else(|| "NULL".to_string(), sql_text),
+        sql_text(&data),
+    )
+}
+
+fn write_reference_row(
+    out: &mut impl Write,
+    uri: &str,
+    reference: &SymbolReference,
+) -> std::io::Result<()> {
+    let (start_line, start_character, end_line, end_character) = reference.range;
+    writeln!(
+        out,
+        "INSERT INTO symbol_references VALUES ({}, {}, {}, {start_line}, {start_character}, {end_line}, {end_character}, {});",
+        sql_text(uri),
+        sql_text(&reference.target_fqn),
+        sql_text(&format!("{:?}", reference.target_kind)),
+        u8::from(reference.is_declaration),
+    )
+}
+
+fn sql_text(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -0,0 +1,156 @@
+//! `php-lsp/indexStats` custom request: file and symbol counts, rough memory
+//! estimates, stub counts, and indexing durations for performance reports.
+
+use super::super::*;
+use php_lsp_types::{FileSymbols, ParamInfo, SymbolInfo, SymbolReference};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::mem::size_of;
+
+/// Custom request method name registered on the LSP service.
+pub const INDEX_STATS_METHOD: &str = "php-lsp/indexStats";
+
+impl PhpLspBackend {
+    /// Handle `php-lsp/indexStats`.
+    pub async fn index_stats_report(&self) -> Result<Value> {
+        let timings = *self.indexing_timings.lock().await;
+        let indexing = indexing_run_is_active(&self.indexing_run).await;
+        let index = self.index.clone();
+        let mut stats = tokio::task::spawn_blocking(move || collect_index_stats(&index))
+            .await
+            .unwrap_or_default();
+        stats["indexing"] = json!({
+            "inProgress": indexing,
+            "stubsMs": timings.stubs_ms,
+            "workspaceMs": timings.workspace_ms,
+        });
+        stats["stubs"]["lastLoadFiles"] = json!(timings.stub_files);
+        Ok(stats)
+    }
+
+    /// Remember how many stub files the latest (re)load produced and how long it took.
+    pub(in crate::server) async fn record_stub_load(&self, files: usize, started_at: Instant) {
+        let mut timings = self.indexing_timings.lock().await;
+        timings.stub_files = files;
+        timings.stubs_ms = Some(elapsed_ms(started_at));
+    }
+}
+
+/// Counts files by origin and symbols by kind, and estimates index memory.
+pub(in crate::server) fn collect_index_stats(index: &WorkspaceIndex) -> Value {
+    let mut workspace_files = 0usize;
+    let mut vendor_files = 0usize;
+    let mut stub_files = 0usize;
+    let mut stub_symbols = 0usize;
+    let mut symbols_by_kind: BTreeMap<String, usize> = BTreeMap::new();
+    let mut symbol_bytes = 0usize;
+    for entry in index.file_symbols.iter() {
+        let uri = entry.key();
+        if uri.starts_with("phpstub://") {
+            stub_files += 1;
+            stub_symbols += entry.symbols.len();
+        } else if uri.contains("/vendor/") {
+            vendor_files += 1;
+        } else {
+            workspace_files += 1;
+        }
+        for symbol in &entry.symbols {
+            *symbols_by_kind
+                .entry(symbol_kind_label(symbol.kind).to_string())
+                .or_default() += 1;
+        }
+        symbol_bytes += uri.len() + file_symbols_heap_bytes(entry.value());
+    }
+
+    let mut reference_count = 0usize;
+    let mut reference_bytes = 0usize;
+    for entry in index.file_references.iter() {
+        reference_count += entry.len();
+        reference_bytes += entry.key().len()
+            + entry
+                .iter()
+                .map(|reference| size_of::<SymbolReference>() + reference.target_fqn.len())
+                .sum::<usize>();
+    }
+
+    let interned_bytes = index.interned_string_bytes();
+    let symbol_count: usize = symbols_by_kind.values().sum();
+    json!({
+        "files": {
+            "total": workspace_files + vendor_files + stub_files,
+            "workspace": workspace_files,
+            "vendor": vendor_files,
+            "stubs": stub_files,
+        },
+        "symbols": {
+            "total": symbol_count,
+            "byKind": symbols_by_kind,
+            "types": index.types.len(),
+            "functions": index.functions.len(),
+            "constants": index.constants.len(),
+        },
+        "references": reference_count,
+        "stubs": {
+            "files": stub_files,
+            "symbols": stub_symbols,
+        },
+        "memory": {
+            "symbolsBytes": symbol_bytes,
+            "referencesBytes": reference_bytes,
+            "internedStrings": index.interned_string_count(),
+            "internedBytes": interned_bytes,
+            "estimatedTotalBytes": symbol_bytes + reference_bytes + interned_bytes,
+        },
+    })
+}
+
+fn symbol_kind_label(kind: php_lsp_types::PhpSymbolKind) -> &'static str {
+    match kind {
+        php_lsp_types::PhpSymbolKind::Class => "class",
+        php_lsp_types::PhpSymbolKind::Interface => "interface",
+        php_lsp_types::PhpSymbolKind::Trait => "trait",
+        php_lsp_types::PhpSymbolKind::Enum => "enum",
+        php_lsp_types::PhpSymbolKind::Function => "function",
+        php_lsp_types::PhpSymbolKind::Method => "method",
+        php_lsp_types::PhpSymbolKind::Property => "property",
+        php_lsp_types::PhpSymbolKind::ClassConstant => "classConstant",
+        php_lsp_types::PhpSymbolKind::GlobalConstant => "constant",
+        php_lsp_types::PhpSymbolKind::EnumCase => "enumCase",
+        php_lsp_types::PhpSymbolKind::Namespace => "namespace",
+    }
+}
+
+/// Approximate heap usage of a file's symbols. Interned URIs and FQNs are
+/// counted once through the index string pool instead.
+fn file_symbols_heap_bytes(file_symbols: &FileSymbols) -> usize {
+    size_of::<FileSymbols>()
+        + file_symbols.namespace.as_ref().map_or(0, String::len)
+        + file_symbols
+            .symbols
+            .iter()
+            .map(symbol_heap_bytes)
+            .sum::<usize>()
+}
+
+fn symbol_heap_bytes(symbol: &SymbolInfo) -> usize {
+    let strings = |values: &[String]| {
+        values
+            .iter()
+            .map(|value| size_of::<String>() + value.len())
+            .sum::<usize>()
+    };
+    size_of::<SymbolInfo>()
+        + symbol.name.len()
+        + symbol.doc_comment.as_ref().map_or(0, String::len)
+        + symbol.value.as_ref().map_or(0, String::len)
+        + strings(&symbol.extends)
+        + strings(&symbol.implements)
+        + strings(&symbol.traits)
+        + symbol.signature.as_ref().map_or(0, |signature| {
+            signature
+                .params
+                .iter()
+                .map(|param| size_of::<ParamInfo>() + param.name.len())
+                .sum()
+        })
+}
@@ -13,6 +13,7 @@ impl PhpLspBackend {
         &self,
         params: InlayHintParams,
     ) -> Result<Option<Vec<InlayHint>>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         let php_version = *self.php_version.lock().await;
         let template_document = self.template_document(&uri_str);
@@ -45,7 +46,7 @@ impl PhpLspBackend {
         let index = self.index.clone();
         let original_requested_range = params.range;
         let requested_range = if template_document.is_some() {
-            full_document_range(&source)
+            full_document_range(&source, encoding)
         } else {
             original_requested_range
         };
@@ -61,6 +62,7 @@ impl PhpLspBackend {
                     &index,
                     requested_range,
                     php_version,
+                    encoding,
                 )
             })
             .await
@@ -73,7 +75,12 @@ impl PhpLspBackend {
             };
 
         if let Some(template) = &template_document {
-            hints = map_inlay_hints_to_template_original(template, original_requested_range, hints);
+            hints = map_inlay_hints_to_template_original(
+                template,
+                original_requested_range,
+                hints,
+                encoding,
+            );
         }
 
         self.hydrate_inlay_hint_label_locations(&mut hints).await;
@@ -109,11 +116,13 @@ fn map_inlay_hints_to_template_original(
     template: &TemplateDocument,
     requested_range: Range,
     hints: Vec<InlayHint>,
+    encoding: PositionEncoding,
 ) -> Vec<InlayHint> {
     hints
         .into_iter()
         .filter_map(|mut hint| {
-            let original_position = template.map_virtual_position_to_original(hint.position)?;
+            let original_position =
+                template.map_virtual_position_to_original(hint.position, encoding)?;
             if !position_in_range(original_position, requested_range) {
                 return None;
             }
@@ -122,7 +131,7 @@ fn map_inlay_hints_to_template_original(
             if let Some(text_edits) = hint.text_edits.take() {
                 let mut mapped_edits = Vec::with_capacity(text_edits.len());
                 for mut edit in text_edits {
-                    edit.range = template.map_virtual_range_to_original(edit.range)?;
+                    edit.range = template.map_virtual_range_to_original(edit.range, encoding)?;
                     mapped_edits.push(edit);
                 }
                 hint.text_edits = Some(mapped_edits);
@@ -133,12 +142,12 @@ fn map_inlay_hints_to_template_original(
         .collect()
 }
 
-fn full_document_range(source: &str) -> Range {
+fn full_document_range(source: &str, encoding: PositionEncoding) -> Range {
     let line = source.bytes().filter(|byte| *byte == b'\n').count() as u32;
     let line_start = source.rfind('\n').map_or(0, |idx| idx + 1);
     Range {
         start: Position::new(0, 0),
-        end: Position::new(line, source[line_start..].encode_utf16().count() as u32),
+        end: Position::new(line, encoding.text_len(&source[line_start..])),
     }
 }
 
@@ -164,9 +173,10 @@ pub(in crate::server) fn inlay_hints(
     index: &WorkspaceIndex,
     requested_range: Range,
     php_version: PhpVersion,
+    encoding: PositionEncoding,
 ) -> Vec<InlayHint> {
-    let utf16_index = Utf16LineIndex::new(source);
-    let byte_range = lsp_range_to_byte_range(source, requested_range);
+    let line_index = LspLineIndex::new(source, encoding);
+    let byte_range = lsp_range_to_byte_range(source, requested_range, encoding);
     let mut hints = Vec::new();
     let type_cache = RequestTypeCache::new(uri_str, document_version);
     let allow_twig_property_accessors = crate::template::is_twig_template_uri(uri_str);
@@ -176,7 +186,7 @@ pub(in crate::server) fn inlay_hints(
         file_symbols,
         index,
         type_cache: &type_cache,
-        utf16_index: &utf16_index,
+        line_index: &line_index,
         requested_range: byte_range,
         allow_twig_property_accessors,
         allow_blocking_file_io: true,
@@ -188,14 +198,14 @@ pub(in crate::server) fn inlay_hints(
     collect_phpdoc_parameter_type_inlay_hints(
         tree.root_node(),
         source,
-        &utf16_index,
+        &line_index,
         byte_range,
         &mut hints,
     );
     collect_phpdoc_return_type_inlay_hints(
         tree,
         source,
-        &utf16_index,
+        &line_index,
         byte_range,
         php_version,
         &mut hints,
@@ -222,7 +232,7 @@ pub(in crate::server) struct InlayHintContext<'a> {
     pub(in crate::server) file_symbols: &'a php_lsp_types::FileSymbols,
     pub(in crate::server) index: &'a WorkspaceIndex,
     pub(in crate::server) type_cache: &'a RequestTypeCache,
-    pub(in crate::server) utf16_index: &'a Utf16LineIndex,
+    pub(in crate::server) line_index: &'a LspLineIndex,
     pub(in crate::server) requested_range: (u32, u32, u32, u32),
     pub(in crate::server) allow_twig_property_accessors: bool,
     pub(in crate::server) allow_blocking_file_io: bool,
@@ -261,7 +271,7 @@ fn scope_end_inlay_hint(ctx: &InlayHintContext<'_>, node: tree_sitter::Node) ->
 
     let label = scope_end_hint_label(scope_node, ctx.source)?;
     Some(InlayHint {
-        position: Position::new(line, ctx.utf16_index.byte_col_to_utf16(line, byte_col)),
+        position: Position::new(line, ctx.line_index.byte_col_to_lsp(line, byte_col)),
         label: InlayHintLabel::String(label.clone()),
         kind: None,
         text_edits: None,
@@ -428,7 +438,7 @@ pub(in crate::server) fn collect_call_argument_inlay_hints(
                 node,
                 &callable,
                 ctx.source,
-                ctx.utf16_index,
+                ctx.line_index,
                 ctx.requested_range,
                 hints,
             );
@@ -480,7 +490,7 @@ pub(in crate::server) fn add_call_argument_inlay_hints(
     call_node: tree_sitter::Node,
     callable: &php_lsp_types::SymbolInfo,
     source: &str,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     requested_range: (u32, u32, u32, u32),
     hints: &mut Vec<InlayHint>,
 ) {
@@ -506,12 +516,12 @@ pub(in crate::server) fn add_call_argument_inlay_hints(
         hints.push(InlayHint {
             position: Position::new(
                 start.row as u32,
-                utf16_index.byte_col_to_utf16(start.row as u32, start.column as u32),
+                line_index.byte_col_to_lsp(start.row as u32, start.column as u32),
             ),
             label: InlayHintLabel::String(format!("{}:", param.name)),
             kind: Some(InlayHintKind::PARAMETER),
             text_edits: None,
-            tooltip: Some(InlayHintTooltip::String(callable.fqn.clone())),
+            tooltip: Some(InlayHintTooltip::String(callable.fqn.to_string())),
             padding_left: Some(false),
             padding_right: Some(true),
             data: None,
@@ -648,8 +658,8 @@ pub(in crate::server) fn add_local_variable_type_inlay_hint(
     let end = variable_node.end_position();
     let position = Position::new(
         end.row as u32,
-        ctx.utf16_index
-            .byte_col_to_utf16(end.row as u32, end.column as u32),
+        ctx.line_index
+            .byte_col_to_lsp(end.row as u32, end.column as u32),
     );
     let label_text = format!(": {}", type_hint.display);
     if !seen.insert((position.line, position.character, label_text)) {
@@ -979,8 +989,9 @@ pub(in crate::server) fn server_member_access_expression_type_info(
     let type_info = symbol_effective_return_type(&symbol)?;
     let owner_fqn = symbol
         .parent_fqn
-        .clone()
+        .as_deref()
         .filter(|owner| !owner.is_empty())
+        .map(str::to_string)
         .unwrap_or(receiver_fqn);
     let type_info = match symbol.kind {
         php_lsp_types::PhpSymbolKind::Method => {
@@ -996,7 +1007,7 @@ pub(in crate::server) fn server_member_access_expression_type_info(
     Some(IndexedExpressionTypeInfo {
         type_info,
         owner_fqn,
-        uri: symbol.uri.clone(),
+        uri: symbol.uri.to_string(),
     })
 }
 
@@ -1031,8 +1042,14 @@ pub(in crate::server) fn indexed_call_expression_type_info(
         .fqn
         .rsplit_once("::")
         .map(|(owner, _)| owner.to_string())
-        .or_else(|| symbol.parent_fqn.clone())
+        .or_else(|| symbol.parent_fqn.as_deref().map(str::to_string))
         .unwrap_or_default();
+    let return_type = php_lsp_parser::resolve::bind_forwarded_static_type_info(
+        expression,
+        &return_type,
+        ctx.source,
+        ctx.file_symbols,
+    );
     let type_info = resolve_call_site_return_type(ctx, expression, &symbol, &return_type);
     let type_info =
         doctrine_collection_getter_return_type_info(ctx, &symbol, &owner_fqn, &type_info)
@@ -1041,7 +1058,7 @@ pub(in crate::server) fn indexed_call_expression_type_info(
     Some(IndexedExpressionTypeInfo {
         type_info,
         owner_fqn,
-        uri: symbol.uri.clone(),
+        uri: symbol.uri.to_string(),
     })
 }
 
@@ -1058,7 +1075,7 @@ pub(in crate::server) fn server_member_call_expression_type_info(
     Some(IndexedExpressionTypeInfo {
         type_info,
         owner_fqn: owner_fqn.to_string(),
-        uri: symbol.uri.clone(),
+        uri: symbol.uri.to_string(),
     })
 }
 
@@ -1143,7 +1160,7 @@ pub(in crate::server) fn server_member_symbol_at_position(
                     RefKind::PropertyAccess
                 };
                 return Some(SymbolAtPosition {
-                    fqn: symbol.fqn.clone(),
+                    fqn: symbol.fqn.to_string(),
                     name: member_name.to_string(),
                     ref_kind,
                     object_expr: Some(node_text(ctx.source, object).trim().to_string()),
@@ -1161,7 +1178,7 @@ pub(in crate::server) fn server_member_symbol_at_position(
                 let method_name = node_text(ctx.source, name_node).trim().to_string();
                 let (_, symbol) = server_member_call_symbol(ctx, candidate)?;
                 return Some(SymbolAtPosition {
-                    fqn: symbol.fqn.clone(),
+                    fqn: symbol.fqn.to_string(),
                     name: method_name,
                     ref_kind: RefKind::MethodCall,
                     object_expr: candidate
@@ -1232,12 +1249,70 @@ pub(in crate::server) fn shape_member_access_info_at_position(
                 });
             }
         }
+        if candidate.kind() == "subscript_expression" {
+            if let Some(info) = array_shape_key_access_info(ctx, candidate, point_range) {
+                return Some(info);
+            }
+        }
         current = candidate.parent();
     }
 
     None
 }
 
+/// Resolve a quoted key in `$data['user']` against the receiver's
+/// `array{...}` shape.
+fn array_shape_key_access_info(
+    ctx: &InlayHintContext<'_>,
+    subscript: tree_sitter::Node,
+    point_range: (u32, u32, u32, u32),
+) -> Option<ShapeMemberAccessInfo> {
+    let key_node = subscript.named_child(1)?;
+    if !byte_range_contains(node_range_node(key_node), point_range) {
+        return None;
+    }
+    let key = array_shape_key_text(ctx.source, key_node)?;
+    let receiver_type = array_shape_receiver_type_info(ctx, subscript.named_child(0)?)?;
+    let type_info = shape_attribute_type_info_for_access(&receiver_type.type_info, &key, true)?;
+    Some(ShapeMemberAccessInfo {
+        member_name: node_text(ctx.source, key_node).trim().to_string(),
+        type_info,
+        owner_fqn: receiver_type.owner_fqn,
+        uri: receiver_type.uri,
+        range: node_range_node(key_node),
+        definition_variable_name: None,
+        definition_target: TemplateShapeDefinitionTarget::Direct,
+        definition_path: vec![key],
+    })
+}
+
+fn array_shape_receiver_type_info(
+    ctx: &InlayHintContext<'_>,
+    receiver: tree_sitter::Node,
+) -> Option<IndexedExpressionTypeInfo> {
+    let receiver = normalized_expression_node(receiver);
+    if receiver.kind() == "subscript_expression" {
+        if let Some(key) = receiver
+            .named_child(1)
+            .and_then(|key_node| array_shape_key_text(ctx.source, key_node))
+        {
+            let outer = array_shape_receiver_type_info(ctx, receiver.named_child(0)?)?;
+            let type_info = shape_attribute_type_info_for_access(&outer.type_info, &key, true)?;
+            return Some(IndexedExpressionTypeInfo { type_info, ..outer });
+        }
+    }
+    local_variable_phpdoc_aware_type_info(ctx, receiver)
+        .or_else(|| server_expression_type_info(ctx, receiver))
+}
+
+fn array_shape_key_text(source: &str, key_node: tree_sitter::Node) -> Option<String> {
+    if !matches!(key_node.kind(), "string" | "encapsed_string") {
+        return None;
+    }
+    let key = php_lsp_types::normalize_shape_key_text(node_text(source, key_node));
+    (!key.is_empty()).then_some(key)
+}
+
 fn shape_member_definition_lookup(
     ctx: &InlayHintContext<'_>,
     expression: tree_sitter::Node,
@@ -1422,7 +1497,7 @@ pub(in crate::server) fn server_expression_type_info(
             let uri = ctx
                 .index
                 .resolve_fqn(&fqn)
-                .map(|symbol| symbol.uri.clone())
+                .map(|symbol| symbol.uri.to_string())
                 .unwrap_or_default();
             Some(IndexedExpressionTypeInfo {
                 type_info: php_lsp_types::TypeInfo::Simple(fqn.clone()),
@@ -1431,17 +1506,31 @@ pub(in crate::server) fn server_expression_type_info(
             })
         }
         "variable_name" => server_variable_type_info(ctx, expression),
-        "function_call_expression"
-        | "member_call_expression"
-        | "nullsafe_member_call_expression"
-        | "scoped_call_expression" => indexed_call_expression_type_info(ctx, expression),
+        "function_call_expression" | "scoped_call_expression" => {
+            indexed_call_expression_type_info(ctx, expression)
+        }
+        "member_call_expression" | "nullsafe_member_call_expression" => {
+            indexed_call_expression_type_info(ctx, expression)
+                .map(|info| nullsafe_indexed_type_info(expression, info))
+        }
         "member_access_expression" | "nullsafe_member_access_expression" => {
             server_member_access_expression_type_info(ctx, expression)
+                .map(|info| nullsafe_indexed_type_info(expression, info))
         }
         _ => None,
     }
 }
 
+fn nullsafe_indexed_type_info(
+    expression: tree_sitter::Node,
+    info: IndexedExpressionTypeInfo,
+) -> IndexedExpressionTypeInfo {
+    IndexedExpressionTypeInfo {
+        type_info: nullsafe_expression_type_info(expression, info.type_info),
+        ..info
+    }
+}
+
 pub(in crate::server) fn server_variable_type_info(
     ctx: &InlayHintContext<'_>,
     variable_node: tree_sitter::Node,
@@ -1517,7 +1606,7 @@ pub(in crate::server) fn doctrine_repository_call_type_info(
             return Some(IndexedExpressionTypeInfo {
                 type_info,
                 owner_fqn: owner_fqn.to_string(),
-                uri: symbol.uri.clone(),
+                uri: symbol.uri.to_string(),
             });
         }
     }
@@ -1526,7 +1615,7 @@ pub(in crate::server) fn doctrine_repository_call_type_info(
     let uri = ctx
         .index
         .resolve_fqn(&entity_fqn)
-        .map(|symbol| symbol.uri.clone())
+        .map(|symbol| symbol.uri.to_string())
         .unwrap_or_default();
 
     Some(IndexedExpressionTypeInfo {
@@ -1623,7 +1712,7 @@ pub(in crate::server) fn doctrine_repository_class_from_template_binding(
             }
 
             let bound_entity = binding.args.first().and_then(type_info_simple_fqn)?;
-            fqn_eq(&bound_entity, entity_fqn).then(|| symbol.fqn.clone())
+            fqn_eq(&bound_entity, entity_fqn).then(|| symbol.fqn.to_string())
         })
     })
 }
@@ -1646,7 +1735,7 @@ pub(in crate::server) fn doctrine_repository_class_from_entity_attribute(
         .join("\n");
     let repository_name = doctrine_repository_class_name_from_attribute_text(&attribute_text)?;
 
-    let entity_file_symbols = index.file_symbols.get(&entity.uri);
+    let entity_file_symbols = index.file_symbols.get(entity.uri.as_str());
     let file_symbols = entity_file_symbols
         .as_ref()
         .map(|symbols| symbols.value())
@@ -2022,9 +2111,14 @@ pub(in crate::server) fn local_variable_inlay_type_from_new_expression(
 ) -> Option<LocalVariableInlayType> {
     let class_node = object_creation_class_node(expression)?;
     let class_name = node_text(ctx.source, class_node).trim();
-    let fqn = resolve_class_name_pub(class_name, ctx.file_symbols)
-        .trim_start_matches('\\')
-        .to_string();
+    let fqn = php_lsp_parser::resolve::resolve_scope_class_name_pub(
+        class_name,
+        expression,
+        ctx.source,
+        ctx.file_symbols,
+    )
+    .trim_start_matches('\\')
+    .to_string();
     if fqn.is_empty() {
         return None;
     }
@@ -2039,7 +2133,10 @@ pub(in crate::server) fn local_variable_inlay_type_from_call_expression(
     ctx: &InlayHintContext<'_>,
     expression: tree_sitter::Node,
 ) -> Option<LocalVariableInlayType> {
-    let info = indexed_call_expression_type_info(ctx, expression)?;
+    let info = nullsafe_indexed_type_info(
+        expression,
+        indexed_call_expression_type_info(ctx, expression)?,
+    );
     local_variable_inlay_type_from_type_info(ctx, &info.owner_fqn, &info.uri, &info.type_info, true)
 }
 
@@ -2579,6 +2676,45 @@ pub(in crate::server) fn call_site_variable_phpdoc_type(
         .map(|type_info| resolve_call_site_type_names(&type_info, ctx.file_symbols))
 }
 
+/// Type of a local variable as the parser infers it, which also honours
+/// `@var` and `@param` tags on the enclosing scope.
+fn local_variable_phpdoc_aware_type_info(
+    ctx: &InlayHintContext<'_>,
+    node: tree_sitter::Node,
+) -> Option<IndexedExpressionTypeInfo> {
+    if node.kind() != "variable_name" {
+        return None;
+    }
+    let variable_name = variable_text_for_node(ctx.source, node)?;
+    let resolver = |class_fqn: &str, member_name: &str| -> Option<String> {
+        ctx.type_cache.cached_string(
+            (0, 0, 0, 0),
+            "member-type",
+            format!("{class_fqn}::{member_name}"),
+            || resolve_member_type_from_index(ctx.index, class_fqn, member_name),
+        )
+    };
+    let callable_param_resolver = |callable_ctx: CallableParameterContext<'_>| {
+        resolve_callable_parameter_type_from_index(ctx.index, ctx.file_symbols, callable_ctx)
+    };
+    let start = node.start_position();
+    let type_info = infer_variable_type_info_at_position_with_resolvers(
+        ctx.tree,
+        ctx.source,
+        ctx.file_symbols,
+        start.row as u32,
+        start.column as u32,
+        &variable_name,
+        Some(&resolver),
+        Some(&callable_param_resolver),
+    )?;
+    Some(IndexedExpressionTypeInfo {
+        type_info: resolve_call_site_type_names(&type_info, ctx.file_symbols),
+        owner_fqn: current_class_fqn(ctx.file_symbols).unwrap_or_default(),
+        uri: String::new(),
+    })
+}
+
 pub(in crate::server) fn resolve_callable_parameter_type_from_index(
     index: &WorkspaceIndex,
     file_symbols: &php_lsp_types::FileSymbols,
@@ -4136,12 +4272,12 @@ pub(in crate::server) fn is_scalar_local_variable_type_hint(display: &str) -> bo
 pub(in crate::server) fn collect_phpdoc_parameter_type_inlay_hints(
     node: tree_sitter::Node,
     source: &str,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     requested_range: (u32, u32, u32, u32),
     hints: &mut Vec<InlayHint>,
 ) {
     if matches!(node.kind(), "function_definition" | "method_declaration") {
-        add_phpdoc_parameter_type_inlay_hints(node, source, utf16_index, requested_range, hints);
+        add_phpdoc_parameter_type_inlay_hints(node, source, line_index, requested_range, hints);
     }
 
     let mut cursor = node.walk();
@@ -4149,7 +4285,7 @@ pub(in crate::server) fn collect_phpdoc_parameter_type_inlay_hints(
         collect_phpdoc_parameter_type_inlay_hints(
             child,
             source,
-            utf16_index,
+            line_index,
             requested_range,
             hints,
         );
@@ -4159,7 +4295,7 @@ pub(in crate::server) fn collect_phpdoc_parameter_type_inlay_hints(
 pub(in crate::server) fn add_phpdoc_parameter_type_inlay_hints(
     node: tree_sitter::Node,
     source: &str,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     requested_range: (u32, u32, u32, u32),
     hints: &mut Vec<InlayHint>,
 ) {
@@ -4201,7 +4337,7 @@ pub(in crate::server) fn add_phpdoc_parameter_type_inlay_hints(
         hints.push(InlayHint {
             position: Position::new(
                 end.row as u32,
-                utf16_index.byte_col_to_utf16(end.row as u32, end.column as u32),
+                line_index.byte_col_to_lsp(end.row as u32, end.column as u32),
             ),
             label: InlayHintLabel::String(format!(": {}", type_info)),
             kind: Some(InlayHintKind::TYPE),
@@ -4217,7 +4353,7 @@ pub(in crate::server) fn add_phpdoc_parameter_type_inlay_hints(
 pub(in crate::server) fn collect_phpdoc_return_type_inlay_hints(
     tree: &tree_sitter::Tree,
     source: &str,
-    utf16_index: &Utf16LineIndex,
+    line_index: &LspLineIndex,
     requested_range: (u32, u32, u32, u32),
     php_version: PhpVersion,
     hints: &mut Vec<InlayHint>,
@@ -4228,8 +4364,8 @@ pub(in crate::server) fn collect_phpdoc_return_type_inlay_hints(
         hints.push(InlayHint {
             position: Position::new(
                 candidate.insert_position.0,
-                utf16_index
-                    .byte_col_to_utf16(candidate.insert_position.0, candidate.insert_position.1),
+                line_index
+                    .byte_col_to_lsp(candidate.insert_position.0, candidate.insert_position.1),
             ),
             label: InlayHintLabel::String(format!(": {}", label)),
             kind: Some(InlayHintKind::TYPE),
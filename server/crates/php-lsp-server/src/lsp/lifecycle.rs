@@ -2,12 +2,18 @@
 
 use super::super::*;
 
-fn php_file_operation_registration_options() -> FileOperationRegistrationOptions {
+fn php_file_operation_registration_options(
+    extensions: &[String],
+) -> FileOperationRegistrationOptions {
+    let glob = match extensions {
+        [extension] => format!("**/*.{extension}"),
+        _ => format!("**/*.{{{}}}", extensions.join(",")),
+    };
     FileOperationRegistrationOptions {
         filters: vec![FileOperationFilter {
             scheme: Some("file".to_string()),
             pattern: FileOperationPattern {
-                glob: "**/*.php".to_string(),
+                glob,
                 matches: Some(FileOperationPatternKind::File),
                 options: None,
             },
@@ -15,6 +21,23 @@ fn php_file_operation_registration_options() -> FileOperationRegistrationOptions
     }
 }
 
+/// Folder moves can change the namespace of every file below them, so
+/// `willRenameFiles` also listens for folders.
+fn php_file_and_folder_operation_registration_options(
+    php_files: &FileOperationRegistrationOptions,
+) -> FileOperationRegistrationOptions {
+    let mut options = php_files.clone();
+    options.filters.push(FileOperationFilter {
+        scheme: Some("file".to_string()),
+        pattern: FileOperationPattern {
+            glob: "**/*".to_string(),
+            matches: Some(FileOperationPatternKind::Folder),
+            options: None,
+        },
+    });
+    options
+}
+
 impl PhpLspBackend {
     pub(crate) async fn lsp_initialize(
         &self,
@@ -24,7 +47,8 @@ impl PhpLspBackend {
 
         // Store trace level from client
         if let Some(trace) = params.trace {
-            *self.trace_level.lock().await = trace;
+            self.config_snapshot
+                .update(|config| config.trace_level = trace);
             tracing::info!("Trace level: {:?}", trace);
         }
 
@@ -35,13 +59,67 @@ impl PhpLspBackend {
             .and_then(|window| window.work_done_progress)
             .unwrap_or(false);
 
+        *self.apply_edit_supported.lock().await = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.apply_edit)
+            .unwrap_or(false);
+
+        *self.rename_file_operation_supported.lock().await = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.workspace_edit.as_ref())
+            .is_some_and(|edit| {
+                edit.document_changes == Some(true)
+                    && edit
+                        .resource_operations
+                        .as_ref()
+                        .is_some_and(|ops| ops.contains(&ResourceOperationKind::Rename))
+            });
+
+        *self.change_annotation_supported.lock().await = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.workspace_edit.as_ref())
+            .is_some_and(|edit| {
+                edit.document_changes == Some(true) && edit.change_annotation_support.is_some()
+            });
+
+        *self.prepare_rename_default_behavior_supported.lock().await = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|text_document| text_document.rename.as_ref())
+            .and_then(|rename| rename.prepare_support_default_behavior)
+            == Some(PrepareSupportDefaultBehavior::IDENTIFIER);
+
+        // Pick the first encoding the client offers that we support; clients
+        // that don't advertise any get the spec default of UTF-16.
+        let position_encoding = PositionEncoding::negotiate(
+            params
+                .capabilities
+                .general
+                .as_ref()
+                .and_then(|general| general.position_encodings.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|kind| kind.as_str()),
+        );
+        self.config_snapshot
+            .update(|config| config.position_encoding = position_encoding);
+        tracing::info!("Position encoding: {}", position_encoding.as_lsp_kind());
+
         let workspace_roots = workspace_roots_from_initialize(&params);
 
         if !workspace_roots.is_empty() {
             for root in &workspace_roots {
                 tracing::info!("Workspace root: {}", root.display());
             }
-            *self.workspace_root.lock().await = workspace_roots.first().cloned();
+            self.config_snapshot
+                .update(|config| config.workspace_root = workspace_roots.first().cloned());
             *self.workspace_roots.lock().await = workspace_roots.clone();
         }
 
@@ -51,9 +129,11 @@ impl PhpLspBackend {
         *self.client_settings.lock().await = client_settings.clone();
         self.apply_effective_configuration_settings(&client_settings, &workspace_roots)
             .await;
+        let php_file_extensions = self.php_file_extensions.lock().await.clone();
 
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                position_encoding: Some(PositionEncodingKind::new(position_encoding.as_lsp_kind())),
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
@@ -93,14 +173,17 @@ impl PhpLspBackend {
                         change_notifications: Some(OneOf::Left(true)),
                     }),
                     file_operations: Some({
-                        let php_files = php_file_operation_registration_options();
+                        let php_files =
+                            php_file_operation_registration_options(&php_file_extensions);
                         WorkspaceFileOperationsServerCapabilities {
                             did_create: Some(php_files.clone()),
                             will_create: Some(php_files.clone()),
                             did_rename: Some(php_files.clone()),
-                            will_rename: None,
-                            did_delete: Some(php_files),
-                            will_delete: Some(php_file_operation_registration_options()),
+                            will_rename: Some(php_file_and_folder_operation_registration_options(
+                                &php_files,
+                            )),
+                            did_delete: Some(php_files.clone()),
+                            will_delete: Some(php_files),
                         }
                     }),
                 }),
@@ -117,6 +200,7 @@ impl PhpLspBackend {
                         "[".to_string(),
                         "'".to_string(),
                         "\"".to_string(),
+                        "@".to_string(),
                     ]),
                     resolve_provider: Some(true),
                     ..Default::default()
@@ -131,6 +215,7 @@ impl PhpLspBackend {
                         code_action_kinds: Some(vec![
                             CodeActionKind::QUICKFIX,
                             CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                            CodeActionKind::new(SOURCE_FIX_ALL_PHPCBF),
                             CodeActionKind::REFACTOR_EXTRACT,
                             CodeActionKind::REFACTOR_INLINE,
                             CodeActionKind::REFACTOR_REWRITE,
@@ -155,6 +240,7 @@ impl PhpLspBackend {
                         },
                     ),
                 ),
+                execute_command_provider: Some(super::commands::execute_command_options()),
                 experimental: Some(serde_json::json!({
                     "typeHierarchyProvider": true,
                 })),
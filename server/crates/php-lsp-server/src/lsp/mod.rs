@@ -1,6 +1,8 @@
 //! Focused LSP request handler modules.
 
+pub(super) mod capabilities;
 pub(super) mod code_action;
+pub(super) mod commands;
 pub(super) mod completion;
 pub(super) mod completion_helpers;
 pub(super) mod conversions;
@@ -9,13 +11,19 @@ pub(super) mod diagnostics;
 pub(super) mod document_links;
 pub(super) mod document_symbols;
 pub(super) mod external_command;
+pub(super) mod file_moves;
 pub(super) mod folding;
 pub(super) mod formatting;
 pub(super) mod hierarchy;
 pub(super) mod hover;
+pub(super) mod index_dump;
+pub(super) mod index_stats;
 pub(super) mod inlay_hints;
 pub(super) mod lifecycle;
+pub(super) mod progress;
 pub(super) mod references;
 pub(super) mod rename;
+pub(super) mod scaffold;
 pub(super) mod semantic_tokens;
 pub(super) mod templates;
+pub(super) mod unused_symbols;
@@ -0,0 +1,76 @@
+//! `window/workDoneProgress/cancel` handling and partial result streaming.
+//!
+//! Workspace indexing advertises a cancellable progress token per root. A
+//! cancel stops that root's scan loop; files indexed so far stay in the index.
+//!
+//! Requests that carry a `partialResultToken` report their results through
+//! `$/progress` chunks instead and answer with an empty final result.
+
+use super::super::*;
+use serde_json::{json, Value};
+
+/// Client-to-server notification not covered by the `LanguageServer` trait.
+pub const WORK_DONE_PROGRESS_CANCEL_METHOD: &str = "window/workDoneProgress/cancel";
+
+/// Number of items reported per partial result chunk when a handler does not
+/// already produce natural batches.
+pub(in crate::server) const PARTIAL_RESULT_CHUNK_SIZE: usize = 50;
+
+struct PartialResultNotification;
+
+impl tower_lsp::ls_types::notification::Notification for PartialResultNotification {
+    type Params = Value;
+
+    const METHOD: &'static str = "$/progress";
+}
+
+/// Sends result chunks for a request whose client asked for partial results.
+pub(in crate::server) struct PartialResultSender<'a> {
+    client: &'a Client,
+    token: ProgressToken,
+}
+
+impl<'a> PartialResultSender<'a> {
+    /// `None` when the client did not pass a `partialResultToken`.
+    pub(in crate::server) fn new(client: &'a Client, params: &PartialResultParams) -> Option<Self> {
+        params
+            .partial_result_token
+            .clone()
+            .map(|token| Self { client, token })
+    }
+
+    /// Report `items` as the next chunk of the result. Empty chunks are skipped.
+    pub(in crate::server) async fn send<T: Serialize>(&self, items: &[T]) {
+        if items.is_empty() {
+            return;
+        }
+        self.client
+            .send_notification::<PartialResultNotification>(json!({
+                "token": self.token,
+                "value": items,
+            }))
+            .await;
+    }
+}
+
+impl PhpLspBackend {
+    /// Handle `window/workDoneProgress/cancel`.
+    pub async fn work_done_progress_cancel(&self, params: WorkDoneProgressCancelParams) {
+        let key = match &params.token {
+            ProgressToken::String(token) => token.clone(),
+            ProgressToken::Number(token) => token.to_string(),
+        };
+        match self
+            .indexing_progress_cancellations
+            .lock()
+            .await
+            .remove(&key)
+        {
+            Some(token) => {
+                tracing::info!("Indexing cancelled by client: {}", key);
+                token.cancel();
+            }
+            None => tracing::debug!("Ignoring cancel for unknown progress token {}", key),
+        }
+    }
+}
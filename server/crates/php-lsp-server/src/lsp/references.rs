@@ -1,7 +1,13 @@
 //! References LSP handlers extracted from `server.rs`.
 
 use super::super::*;
-use super::hierarchy::call_hierarchy_kind_key;
+use super::hierarchy::{
+    call_hierarchy_kind_key, implementation_symbols_for_method, implementation_symbols_for_type,
+};
+use super::progress::PartialResultSender;
+use php_lsp_index::workspace::reference_name_key;
+use php_lsp_parser::references::{ReferenceKind, ReferenceLocation};
+use serde_json::{json, Value};
 
 fn is_code_lens_symbol_kind(kind: php_lsp_types::PhpSymbolKind) -> bool {
     matches!(
@@ -14,6 +20,34 @@ fn is_code_lens_symbol_kind(kind: php_lsp_types::PhpSymbolKind) -> bool {
     )
 }
 
+/// Interfaces, abstract classes and the methods they leave to subtypes get an
+/// extra lens listing their implementations.
+fn is_implementation_lens_symbol(
+    symbol: &php_lsp_types::SymbolInfo,
+    file_symbols: &php_lsp_types::FileSymbols,
+) -> bool {
+    match symbol.kind {
+        php_lsp_types::PhpSymbolKind::Interface => true,
+        php_lsp_types::PhpSymbolKind::Class => symbol.modifiers.is_abstract,
+        php_lsp_types::PhpSymbolKind::Method => {
+            symbol.modifiers.is_abstract
+                || file_symbols.symbols.iter().any(|owner| {
+                    owner.kind == php_lsp_types::PhpSymbolKind::Interface
+                        && symbol.parent_fqn.as_deref() == Some(owner.fqn.as_str())
+                })
+        }
+        _ => false,
+    }
+}
+
+fn implementation_count_title(count: usize) -> String {
+    if count == 1 {
+        "1 implementation".to_string()
+    } else {
+        format!("{} implementations", count)
+    }
+}
+
 fn reference_count_title(count: usize) -> String {
     if count == 1 {
         "1 reference".to_string()
@@ -22,11 +56,259 @@ fn reference_count_title(count: usize) -> String {
     }
 }
 
+/// Custom request method name registered on the LSP service.
+pub const REFERENCES_METHOD: &str = "php-lsp/references";
+
+const REFERENCE_KIND_REFERENCE: &str = "reference";
+
+/// `kind` reported for each location by `php-lsp/references`.
+fn reference_kind_label(reference: &php_lsp_types::SymbolReference) -> &'static str {
+    if reference.is_declaration {
+        return "declaration";
+    }
+    if reference.is_string_literal {
+        return "stringLiteral";
+    }
+    match reference.usage {
+        php_lsp_types::SymbolReferenceUsage::Instantiation => "instantiation",
+        php_lsp_types::SymbolReferenceUsage::TypeHint => "typeHint",
+        php_lsp_types::SymbolReferenceUsage::Other => REFERENCE_KIND_REFERENCE,
+    }
+}
+
+/// Drops cancel the in-flight reference scan so blocking workers stop early
+/// when the request future is abandoned (e.g. after `$/cancelRequest`).
+struct CancelScanOnDrop(OperationCancellationToken);
+
+impl Drop for CancelScanOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+fn reference_scan_worker_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .clamp(1, REFERENCE_SCAN_MAX_WORKERS)
+}
+
+/// Symbol that a workspace reference scan is looking for.
+pub(in crate::server) struct ReferenceScanTarget {
+    pub(in crate::server) fqn: Arc<str>,
+    pub(in crate::server) kind: php_lsp_types::PhpSymbolKind,
+    pub(in crate::server) include_declaration: bool,
+}
+
+fn classified_reference_locations(
+    batch_results: Vec<(String, Vec<php_lsp_types::SymbolReference>)>,
+) -> Vec<(Location, &'static str)> {
+    let mut locations = Vec::new();
+    for (file_uri, refs) in batch_results {
+        let Ok(uri) = file_uri.parse::<Uri>() else {
+            continue;
+        };
+        locations.extend(refs.into_iter().map(|r| {
+            let location = Location {
+                uri: uri.clone(),
+                range: range_from_lsp_tuple(r.range),
+            };
+            (location, reference_kind_label(&r))
+        }));
+    }
+    locations
+}
+
+fn classified_reference_json((location, kind): &(Location, &'static str)) -> Value {
+    json!({
+        "uri": location.uri,
+        "range": location.range,
+        "kind": kind,
+    })
+}
+
+pub(in crate::server) fn scan_reference_batch(
+    index: &WorkspaceIndex,
+    open_files: &DashMap<String, FileParser>,
+    batch: &[String],
+    target: &ReferenceScanTarget,
+    cancellation: &OperationCancellationToken,
+    workers: usize,
+    encoding: PositionEncoding,
+) -> Vec<(String, Vec<php_lsp_types::SymbolReference>)> {
+    let scan_chunk = |chunk: &[String]| {
+        let mut matches = Vec::new();
+        for file_uri in chunk {
+            if cancellation.is_cancelled() {
+                break;
+            }
+            let refs = matching_references_for_file(
+                index,
+                open_files,
+                file_uri,
+                &target.fqn,
+                target.kind,
+                target.include_declaration,
+                encoding,
+            );
+            if !refs.is_empty() {
+                matches.push((file_uri.clone(), refs));
+            }
+        }
+        matches
+    };
+
+    if workers <= 1 || batch.len() < workers * 2 {
+        return scan_chunk(batch);
+    }
+
+    let chunk_size = batch.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = batch
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || scan_chunk(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Batched workspace reference scan started by
+/// [`PhpLspBackend::workspace_reference_scan`].
+pub(in crate::server) struct WorkspaceReferenceScan {
+    files: Vec<String>,
+    next: usize,
+    guard: CancelScanOnDrop,
+    workers: usize,
+    target: Arc<ReferenceScanTarget>,
+    index: Arc<WorkspaceIndex>,
+    open_files: Arc<DashMap<String, FileParser>>,
+    encoding: PositionEncoding,
+}
+
+impl WorkspaceReferenceScan {
+    /// Scan the next batch of candidate files on the blocking pool. Returns
+    /// `None` once every candidate file has been scanned, and an error when a
+    /// scan worker failed, so callers never answer with partial results.
+    pub(in crate::server) async fn next_batch(
+        &mut self,
+    ) -> Result<Option<Vec<(String, Vec<php_lsp_types::SymbolReference>)>>> {
+        if self.next >= self.files.len() {
+            return Ok(None);
+        }
+        let end = (self.next + REFERENCE_SCAN_BATCH_SIZE).min(self.files.len());
+        let batch = self.files[self.next..end].to_vec();
+        self.next = end;
+
+        let index = Arc::clone(&self.index);
+        let open_files = Arc::clone(&self.open_files);
+        let target = Arc::clone(&self.target);
+        let cancellation = self.guard.0.clone();
+        let workers = self.workers;
+        let encoding = self.encoding;
+        let scanned = tokio::task::spawn_blocking(move || {
+            scan_reference_batch(
+                &index,
+                &open_files,
+                &batch,
+                &target,
+                &cancellation,
+                workers,
+                encoding,
+            )
+        })
+        .await;
+        match scanned {
+            Ok(batch_results) => {
+                tokio::task::yield_now().await;
+                Ok(Some(batch_results))
+            }
+            Err(err) => {
+                tracing::error!("reference scan batch failed: {}", err);
+                self.next = self.files.len();
+                let mut error = tower_lsp::jsonrpc::Error::internal_error();
+                error.message = "Workspace reference scan failed".into();
+                Err(error)
+            }
+        }
+    }
+}
+
 impl PhpLspBackend {
+    /// Scan the workspace for references to the target symbol.
+    ///
+    /// Only files that can contain the target are scanned: indexed files whose
+    /// references mention its short name, plus open documents whose text does.
+    /// Files are processed in batches on the blocking pool, split across a
+    /// small worker pool, and the request yields between batches. Results are
+    /// ordered by file URI. Dropping the future stops the scan at the next file
+    /// boundary, and a panicking worker fails the whole request.
+    pub(in crate::server) async fn scan_workspace_references(
+        &self,
+        target_fqn: &str,
+        target_kind: php_lsp_types::PhpSymbolKind,
+        include_declaration: bool,
+    ) -> Result<Vec<(String, Vec<php_lsp_types::SymbolReference>)>> {
+        let mut scan = self.workspace_reference_scan(target_fqn, target_kind, include_declaration);
+        let mut results = Vec::new();
+        while let Some(batch_results) = scan.next_batch().await? {
+            results.extend(batch_results);
+        }
+        Ok(results)
+    }
+
+    /// Start a reference scan whose batches the caller consumes one at a time,
+    /// e.g. to stream them as partial results.
+    pub(in crate::server) fn workspace_reference_scan(
+        &self,
+        target_fqn: &str,
+        target_kind: php_lsp_types::PhpSymbolKind,
+        include_declaration: bool,
+    ) -> WorkspaceReferenceScan {
+        WorkspaceReferenceScan {
+            files: self.reference_candidate_files(target_fqn),
+            next: 0,
+            guard: CancelScanOnDrop(OperationCancellationToken::new()),
+            workers: reference_scan_worker_count(),
+            target: Arc::new(ReferenceScanTarget {
+                fqn: Arc::from(target_fqn),
+                kind: target_kind,
+                include_declaration,
+            }),
+            index: Arc::clone(&self.index),
+            open_files: Arc::clone(&self.open_files),
+            encoding: self.position_encoding(),
+        }
+    }
+
+    /// Files that may reference `target_fqn`, in URI order.
+    pub(in crate::server) fn reference_candidate_files(&self, target_fqn: &str) -> Vec<String> {
+        let mut files = self.index.files_referencing_name(target_fqn);
+        let name = reference_name_key(target_fqn);
+        // Open documents can be ahead of the index, so fall back to a text check.
+        for entry in self.open_files.iter() {
+            if self.index.file_references.contains_key(entry.key())
+                && entry.value().source().to_ascii_lowercase().contains(&name)
+            {
+                files.push(entry.key().clone());
+            }
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+
     pub(crate) async fn lsp_document_highlight(
         &self,
         params: DocumentHighlightParams,
     ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position_params
             .text_document
@@ -44,8 +326,8 @@ impl PhpLspBackend {
             None => return Ok(None),
         };
         let source = parser.source();
-        let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-        let file_symbols = extract_file_symbols(tree, &source, &uri_str);
+        let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+        let file_symbols = parser.file_symbols(&uri_str).unwrap_or_default();
         let sym = match symbol_at_position(tree, &source, pos.line, byte_col, &file_symbols) {
             Some(sym) => sym,
             None => return Ok(None),
@@ -55,7 +337,9 @@ impl PhpLspBackend {
             let highlights: Vec<DocumentHighlight> =
                 find_variable_references_at_position(tree, &source, pos.line, byte_col, true)
                     .into_iter()
-                    .map(|reference| document_highlight_from_range(&source, reference.range, true))
+                    .map(|reference| {
+                        document_highlight_from_reference(&source, &reference, encoding)
+                    })
                     .collect();
             return if highlights.is_empty() {
                 Ok(None)
@@ -69,18 +353,15 @@ impl PhpLspBackend {
         };
         let resolved = self.resolve_fqn_with_fallback(&sym.fqn, sym.ref_kind);
         let (target_fqn, target_kind) = if let Some(resolved) = resolved {
-            (resolved.fqn.clone(), resolved.kind)
+            (resolved.fqn.to_string(), resolved.kind)
         } else {
             (sym.fqn.clone(), kind)
         };
-        let read_write_capable = target_kind == php_lsp_types::PhpSymbolKind::Property;
 
         let highlights: Vec<DocumentHighlight> =
             find_references_in_file(tree, &source, &file_symbols, &target_fqn, target_kind, true)
                 .into_iter()
-                .map(|reference| {
-                    document_highlight_from_range(&source, reference.range, read_write_capable)
-                })
+                .map(|reference| document_highlight_from_reference(&source, &reference, encoding))
                 .collect();
 
         if highlights.is_empty() {
@@ -94,6 +375,32 @@ impl PhpLspBackend {
         &self,
         params: ReferenceParams,
     ) -> Result<Option<Vec<Location>>> {
+        Ok(self
+            .classified_references(params, |(location, _)| json!(location))
+            .await?
+            .map(|refs| refs.into_iter().map(|(location, _)| location).collect()))
+    }
+
+    /// Handle `php-lsp/references`: `textDocument/references` with a `kind`
+    /// per location so clients can tell instantiations from type hints.
+    pub async fn references_with_kinds(&self, params: ReferenceParams) -> Result<Option<Value>> {
+        Ok(self
+            .classified_references(params, classified_reference_json)
+            .await?
+            .map(|refs| Value::Array(refs.iter().map(classified_reference_json).collect())))
+    }
+
+    /// Resolve the symbol under the cursor and collect its references.
+    ///
+    /// With a `partialResultToken`, workspace matches are streamed per scan
+    /// batch (serialized with `encode_partial`) and the returned list is empty.
+    async fn classified_references(
+        &self,
+        params: ReferenceParams,
+        encode_partial: fn(&(Location, &'static str)) -> Value,
+    ) -> Result<Option<Vec<(Location, &'static str)>>> {
+        let encoding = self.position_encoding();
+        let partial = PartialResultSender::new(&self.client, &params.partial_result_params);
         let uri_str = params
             .text_document_position
             .text_document
@@ -114,8 +421,8 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let source = parser.source();
-            let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-            let file_symbols = extract_file_symbols(tree, &source, &uri_str);
+            let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+            let file_symbols = parser.file_symbols(&uri_str).unwrap_or_default();
 
             let resolver = |class_fqn: &str, member_name: &str| -> Option<String> {
                 self.resolve_member_type(class_fqn, member_name)
@@ -149,17 +456,18 @@ impl PhpLspBackend {
                             Ok(u) => u,
                             Err(_) => return Ok(None),
                         };
-                        let locations: Vec<Location> = refs
+                        let locations = refs
                             .into_iter()
                             .map(|r| {
-                                let rng = range_byte_to_utf16(&source, r.range);
-                                Location {
+                                let rng = range_byte_to_lsp(&source, r.range, encoding);
+                                let location = Location {
                                     uri: uri.clone(),
                                     range: Range {
                                         start: Position::new(rng.0, rng.1),
                                         end: Position::new(rng.2, rng.3),
                                     },
-                                }
+                                };
+                                (location, REFERENCE_KIND_REFERENCE)
                             })
                             .collect();
                         return Ok(Some(locations));
@@ -183,7 +491,11 @@ impl PhpLspBackend {
                     // Try to canonicalize symbol via index lookup.
                     let resolved = self.resolve_fqn_with_fallback(&sym.fqn, sym.ref_kind);
                     if let Some(resolved) = resolved {
-                        (resolved.fqn.clone(), resolved.kind)
+                        (resolved.fqn.to_string(), resolved.kind)
+                    } else if sym.ref_kind == RefKind::Constructor {
+                        // No constructor anywhere in the hierarchy: list class usages.
+                        let class_fqn = sym.fqn.strip_suffix("::__construct").unwrap_or(&sym.fqn);
+                        (class_fqn.to_string(), kind)
                     } else {
                         (sym.fqn.clone(), kind)
                     }
@@ -194,29 +506,21 @@ impl PhpLspBackend {
 
         // Search all indexed files for references
         let mut locations = Vec::new();
-        let indexed_files: Vec<_> = self
-            .index
-            .file_references
-            .iter()
-            .map(|entry| entry.key().clone())
-            .collect();
-
-        for (scanned_files, file_uri) in indexed_files.into_iter().enumerate() {
-            cooperative_heavy_request_yield(scanned_files).await;
-
-            for r in
-                self.references_for_file(&file_uri, &target_fqn, target_kind, include_declaration)
-            {
-                if let Ok(uri) = file_uri.parse::<Uri>() {
-                    locations.push(Location {
-                        uri,
-                        range: range_from_lsp_tuple(r.range),
-                    });
+        let mut scan = self.workspace_reference_scan(&target_fqn, target_kind, include_declaration);
+        while let Some(batch_results) = scan.next_batch().await? {
+            let batch_locations = classified_reference_locations(batch_results);
+            match &partial {
+                Some(sender) => {
+                    let chunk: Vec<Value> = batch_locations.iter().map(encode_partial).collect();
+                    sender.send(&chunk).await;
                 }
+                None => locations.extend(batch_locations),
             }
         }
 
-        if locations.is_empty() {
+        if partial.is_some() {
+            Ok(Some(Vec::new()))
+        } else if locations.is_empty() {
             Ok(None)
         } else {
             Ok(Some(locations))
@@ -227,6 +531,7 @@ impl PhpLspBackend {
         &self,
         params: CodeLensParams,
     ) -> Result<Option<Vec<CodeLens>>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         let document_uri = match uri_str.parse::<Uri>() {
             Ok(uri) => uri,
@@ -234,11 +539,10 @@ impl PhpLspBackend {
         };
 
         let (file_symbols, source) = if let Some(parser) = self.open_files.get(&uri_str) {
-            let Some(tree) = parser.tree() else {
+            let Some(file_symbols) = parser.file_symbols(&uri_str) else {
                 return Ok(None);
             };
-            let source = parser.source();
-            (extract_file_symbols(tree, &source, &uri_str), source)
+            (file_symbols.into_owned(), parser.source())
         } else if let Some(file_symbols) = self.index.file_symbols.get(&uri_str) {
             let file_symbols = file_symbols.value().clone();
             let Some(path) = uri_to_path(&uri_str) else {
@@ -260,7 +564,7 @@ impl PhpLspBackend {
             .filter(|symbol| is_code_lens_symbol_kind(symbol.kind))
         {
             let locations = self.reference_locations_for_symbol(&symbol.fqn, symbol.kind, false);
-            let range_tuple = range_byte_to_utf16(&source, symbol.selection_range);
+            let range_tuple = range_byte_to_lsp(&source, symbol.selection_range, encoding);
             let start = Position::new(range_tuple.0, range_tuple.1);
             let end = if range_tuple.0 == range_tuple.2 {
                 Position::new(range_tuple.2, range_tuple.3)
@@ -290,6 +594,45 @@ impl PhpLspBackend {
                     "references": locations.len(),
                 })),
             });
+
+            if !is_implementation_lens_symbol(symbol, &file_symbols) {
+                continue;
+            }
+            let implementations = if symbol.kind == php_lsp_types::PhpSymbolKind::Method {
+                implementation_symbols_for_method(&self.index, symbol)
+            } else {
+                implementation_symbols_for_type(&self.index, symbol)
+            };
+            let mut implementation_locations = Vec::new();
+            for implementation in &implementations {
+                if let Some(location) = self
+                    .location_for_symbol_selection(implementation, "codeLens source read")
+                    .await
+                {
+                    implementation_locations.push(location);
+                }
+            }
+            let arguments = match (
+                serde_json::to_value(document_uri.clone()),
+                serde_json::to_value(start),
+                serde_json::to_value(&implementation_locations),
+            ) {
+                (Ok(uri), Ok(position), Ok(locations)) => Some(vec![uri, position, locations]),
+                _ => None,
+            };
+            lenses.push(CodeLens {
+                range: Range { start, end },
+                command: Some(Command {
+                    title: implementation_count_title(implementation_locations.len()),
+                    command: "editor.action.showReferences".to_string(),
+                    arguments,
+                }),
+                data: Some(serde_json::json!({
+                    "fqn": symbol.fqn,
+                    "kind": call_hierarchy_kind_key(symbol.kind),
+                    "implementations": implementation_locations.len(),
+                })),
+            });
         }
 
         if lenses.is_empty() {
@@ -300,118 +643,30 @@ impl PhpLspBackend {
     }
 }
 
-pub(in crate::server) fn line_byte_col_to_byte(
-    source: &str,
-    line: u32,
-    byte_col: u32,
-) -> Option<usize> {
-    let mut offset = 0usize;
-
-    for (current_line, l) in source.split_inclusive('\n').enumerate() {
-        if current_line as u32 == line {
-            let col = byte_col as usize;
-            return (col <= l.len()).then_some(offset + col);
+/// Calls, instantiations and type hints are neither reads nor writes of the
+/// target, so they get the plain text highlight.
+pub(in crate::server) fn document_highlight_kind(kind: ReferenceKind) -> DocumentHighlightKind {
+    match kind {
+        ReferenceKind::Write => DocumentHighlightKind::WRITE,
+        ReferenceKind::Read => DocumentHighlightKind::READ,
+        ReferenceKind::Call | ReferenceKind::Instantiate | ReferenceKind::TypeHint => {
+            DocumentHighlightKind::TEXT
         }
-        offset += l.len();
-    }
-
-    None
-}
-
-pub(in crate::server) fn starts_with_assignment_operator(text: &str) -> bool {
-    matches!(
-        text.as_bytes(),
-        [b'=', rest @ ..] if !matches!(rest.first(), Some(b'=' | b'>'))
-    ) || text.starts_with("+=")
-        || text.starts_with("-=")
-        || text.starts_with("*=")
-        || text.starts_with("/=")
-        || text.starts_with("%=")
-        || text.starts_with(".=")
-        || text.starts_with("&=")
-        || text.starts_with("|=")
-        || text.starts_with("^=")
-        || text.starts_with("??=")
-        || text.starts_with("<<=")
-        || text.starts_with(">>=")
-}
-
-pub(in crate::server) fn is_declaration_like_write(
-    before_trimmed: &str,
-    after_trimmed: &str,
-) -> bool {
-    let segment = before_trimmed
-        .rsplit([';', '{', '}'])
-        .next()
-        .unwrap_or(before_trimmed)
-        .trim_start();
-    let declaration_tail = after_trimmed.starts_with([',', ')', ';', '=']);
-
-    declaration_tail
-        && (segment.contains("function ")
-            || segment.starts_with("public ")
-            || segment.starts_with("protected ")
-            || segment.starts_with("private ")
-            || segment.starts_with("readonly ")
-            || segment.starts_with("static ")
-            || segment.starts_with("var "))
-}
-
-pub(in crate::server) fn is_write_reference(source: &str, range: (u32, u32, u32, u32)) -> bool {
-    let Some(start) = line_byte_col_to_byte(source, range.0, range.1) else {
-        return false;
-    };
-    let Some(end) = line_byte_col_to_byte(source, range.2, range.3) else {
-        return false;
-    };
-    if start > end || end > source.len() {
-        return false;
-    }
-
-    let line_start = source[..start].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
-    let line_end = source[end..]
-        .find('\n')
-        .map(|idx| end + idx)
-        .unwrap_or(source.len());
-    let before_trimmed = source[line_start..start].trim_end();
-    let after_trimmed = source[end..line_end].trim_start();
-
-    starts_with_assignment_operator(after_trimmed)
-        || after_trimmed.starts_with("++")
-        || after_trimmed.starts_with("--")
-        || before_trimmed.ends_with("++")
-        || before_trimmed.ends_with("--")
-        || is_declaration_like_write(before_trimmed, after_trimmed)
-}
-
-pub(in crate::server) fn document_highlight_kind(
-    source: &str,
-    range: (u32, u32, u32, u32),
-    read_write_capable: bool,
-) -> DocumentHighlightKind {
-    if !read_write_capable {
-        return DocumentHighlightKind::TEXT;
-    }
-
-    if is_write_reference(source, range) {
-        DocumentHighlightKind::WRITE
-    } else {
-        DocumentHighlightKind::READ
     }
 }
 
-pub(in crate::server) fn document_highlight_from_range(
+pub(in crate::server) fn document_highlight_from_reference(
     source: &str,
-    range: (u32, u32, u32, u32),
-    read_write_capable: bool,
+    reference: &ReferenceLocation,
+    encoding: PositionEncoding,
 ) -> DocumentHighlight {
-    let rng = range_byte_to_utf16(source, range);
+    let rng = range_byte_to_lsp(source, reference.range, encoding);
     DocumentHighlight {
         range: Range {
             start: Position::new(rng.0, rng.1),
             end: Position::new(rng.2, rng.3),
         },
-        kind: Some(document_highlight_kind(source, range, read_write_capable)),
+        kind: Some(document_highlight_kind(reference.kind)),
     }
 }
 
@@ -4,6 +4,7 @@ use super::super::*;
 
 impl PhpLspBackend {
     pub(crate) async fn lsp_rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let encoding = self.position_encoding();
         let uri_str = params
             .text_document_position
             .text_document
@@ -22,8 +23,8 @@ impl PhpLspBackend {
             None => return Ok(None),
         };
         let source = parser.source();
-        let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-        let file_symbols = extract_file_symbols(tree, &source, &uri_str);
+        let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+        let file_symbols = parser.file_symbols(&uri_str).unwrap_or_default();
 
         let resolver = |class_fqn: &str, member_name: &str| -> Option<String> {
             self.resolve_member_type(class_fqn, member_name)
@@ -40,7 +41,7 @@ impl PhpLspBackend {
             Some(&resolver),
             Some(&callable_param_resolver),
         ) {
-            Some(s) => s,
+            Some(s) => constructor_site_as_class(s),
             None => return Ok(None),
         };
 
@@ -67,7 +68,7 @@ impl PhpLspBackend {
             let edits: Vec<TextEdit> = refs
                 .into_iter()
                 .map(|r| {
-                    let rng = range_byte_to_utf16(&source, r.range);
+                    let rng = range_byte_to_lsp(&source, r.range, encoding);
                     TextEdit {
                         range: Range {
                             start: Position::new(rng.0, rng.1),
@@ -118,7 +119,7 @@ impl PhpLspBackend {
         // Resolve symbol under cursor
         let (target_fqn, target_kind, _old_name) = {
             if let Some(resolved) = resolved_for_rename {
-                (resolved.fqn.clone(), resolved.kind, sym.name.clone())
+                (resolved.fqn.to_string(), resolved.kind, sym.name.clone())
             } else {
                 let kind = match rename_target_kind_from_ref_kind(sym.ref_kind) {
                     Some(kind) => kind,
@@ -132,6 +133,12 @@ impl PhpLspBackend {
             return Ok(None);
         }
 
+        if is_constructor_fqn(target_kind, &target_fqn) {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                "Cannot rename a constructor",
+            ));
+        }
+
         if is_member_rename_kind(target_kind) && !target_fqn.contains("::") {
             return Err(tower_lsp::jsonrpc::Error::invalid_params(
                 "Cannot safely rename member without a resolved receiver type",
@@ -159,57 +166,554 @@ impl PhpLspBackend {
             }
         }
 
+        // Overrides and overridden declarations must be renamed together.
+        let scan_targets = if target_kind == php_lsp_types::PhpSymbolKind::Method {
+            self.method_override_root_targets(&target_fqn)?
+        } else {
+            vec![target_fqn.clone()]
+        };
+
         // Find all references (including declaration)
+        let class_short_name = is_class_like_rename_kind(target_kind)
+            .then(|| short_class_name(&target_fqn).to_string());
+        let mut scanned = Vec::new();
+        for scan_target in &scan_targets {
+            scanned.extend(
+                self.scan_workspace_references(scan_target, target_kind, true)
+                    .await?,
+            );
+        }
         let mut changes: std::collections::HashMap<Uri, Vec<TextEdit>> =
             std::collections::HashMap::new();
-        let indexed_files: Vec<_> = self
+        for (file_uri, refs) in scanned {
+            let Ok(uri) = file_uri.parse::<Uri>() else {
+                continue;
+            };
+            let edits: Vec<TextEdit> = if let Some(old_short) = class_short_name.as_deref() {
+                // Qualified names only get their last segment replaced, and
+                // aliased usages keep their alias.
+                let Some(file_source) = self.source_for_uri(&file_uri, "rename").await else {
+                    continue;
+                };
+                refs.into_iter()
+                    .filter_map(|r| {
+                        class_reference_rename_edit(
+                            &file_source,
+                            range_from_lsp_tuple(r.range),
+                            old_short,
+                            &normalized_new_name,
+                            encoding,
+                        )
+                    })
+                    .collect()
+            } else {
+                refs.into_iter()
+                    .map(|r| TextEdit {
+                        range: range_from_lsp_tuple(r.range),
+                        new_text: if target_kind == php_lsp_types::PhpSymbolKind::Property
+                            && r.starts_with_dollar
+                        {
+                            format!(
+                                "${}",
+                                property_new_name.unwrap_or(new_name.trim_start_matches('$'))
+                            )
+                        } else {
+                            property_new_name
+                                .unwrap_or(normalized_new_name.as_str())
+                                .to_string()
+                        },
+                    })
+                    .collect()
+            };
+            changes.entry(uri).or_default().extend(edits);
+        }
+
+        if let Some(old_short) = class_short_name.as_deref() {
+            for (uri, edit) in self
+                .class_use_statement_rename_edits(&target_fqn, old_short, &normalized_new_name)
+                .await
+            {
+                changes.entry(uri).or_default().push(edit);
+            }
+        }
+        if let Some(property_name) = property_new_name {
+            if let Some((uri, edits)) = self
+                .promoted_parameter_rename_edits(&target_fqn, property_name)
+                .await
+            {
+                changes.entry(uri).or_default().extend(edits);
+            }
+        }
+        for edits in changes.values_mut() {
+            edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+            edits.dedup_by_key(|edit| edit.range);
+        }
+        changes.retain(|_, edits| !edits.is_empty());
+        let dynamic_access_edits = if matches!(
+            target_kind,
+            php_lsp_types::PhpSymbolKind::Method | php_lsp_types::PhpSymbolKind::Property
+        ) && *self.change_annotation_supported.lock().await
+        {
+            self.dynamic_member_access_rename_edits(
+                &target_fqn,
+                target_kind,
+                &normalized_new_name,
+                &changes,
+            )
+            .await
+        } else {
+            std::collections::HashMap::new()
+        };
+
+        if changes.is_empty() && is_member_rename_kind(target_kind) {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                "Cannot safely rename member because no exact references were found",
+            ));
+        }
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some(old_short) = class_short_name.as_deref() {
+            if *self.rename_file_operation_supported.lock().await {
+                if let Some(rename_file) =
+                    self.class_file_rename(&target_fqn, old_short, &normalized_new_name)
+                {
+                    return Ok(Some(
+                        self.workspace_edit_with_file_rename(changes, rename_file),
+                    ));
+                }
+            }
+        }
+
+        if !dynamic_access_edits.is_empty() {
+            return Ok(Some(self.workspace_edit_with_dynamic_access_edits(
+                changes,
+                dynamic_access_edits,
+            )));
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }))
+    }
+
+    /// Range and placeholder for the rename box. Qualified class names are
+    /// narrowed to their last segment and property placeholders drop the `$`.
+    /// Plain identifiers defer to the client when it can select them itself.
+    async fn prepare_rename_response(
+        &self,
+        source: &str,
+        range: Range,
+        target_kind: Option<php_lsp_types::PhpSymbolKind>,
+    ) -> Option<PrepareRenameResponse> {
+        let encoding = self.position_encoding();
+        let text = text_at_lsp_range(source, range, encoding)?;
+        let (range, placeholder) = match target_kind {
+            Some(kind) if is_class_like_rename_kind(kind) => {
+                let short_name = short_class_name(text);
+                let short_len = encoding.text_len(short_name);
+                let range = Range {
+                    start: Position::new(range.end.line, range.end.character - short_len),
+                    end: range.end,
+                };
+                (range, short_name)
+            }
+            Some(php_lsp_types::PhpSymbolKind::Property) => (range, text.trim_start_matches('$')),
+            _ => (range, text),
+        };
+
+        if placeholder == text_at_lsp_range(source, range, encoding)?
+            && is_php_identifier(placeholder)
+            && *self.prepare_rename_default_behavior_supported.lock().await
+        {
+            return Some(PrepareRenameResponse::DefaultBehavior {
+                default_behavior: true,
+            });
+        }
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range,
+            placeholder: placeholder.to_string(),
+        })
+    }
+
+    /// Collect the override set of a method: every same-named declaration in
+    /// a parent, interface, trait or subclass connected to `target_fqn`. Only
+    /// the topmost owners are returned, because reference scans already match
+    /// declarations and receivers in their descendants.
+    fn method_override_root_targets(&self, target_fqn: &str) -> Result<Vec<String>> {
+        let Some((target_owner, method_name)) = target_fqn.rsplit_once("::") else {
+            return Ok(vec![target_fqn.to_string()]);
+        };
+        if self
+            .index
+            .resolve_fqn(target_fqn)
+            .is_some_and(|method| method.visibility == php_lsp_types::Visibility::Private)
+        {
+            return Ok(vec![target_fqn.to_string()]);
+        }
+
+        let declaring_owners: Vec<String> = self
             .index
-            .file_references
+            .file_symbols
             .iter()
-            .map(|entry| entry.key().clone())
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .symbols
+                    .iter()
+                    .filter(|sym| {
+                        sym.kind == php_lsp_types::PhpSymbolKind::Method
+                            && sym.visibility != php_lsp_types::Visibility::Private
+                            && sym.name.eq_ignore_ascii_case(method_name)
+                    })
+                    .filter_map(|sym| sym.parent_fqn.as_deref().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
             .collect();
 
-        for (scanned_files, file_uri) in indexed_files.into_iter().enumerate() {
-            cooperative_heavy_request_yield(scanned_files).await;
-            let refs = self.references_for_file(&file_uri, &target_fqn, target_kind, true);
+        let mut owners = vec![target_owner.to_string()];
+        loop {
+            let related: Vec<String> = declaring_owners
+                .iter()
+                .filter(|candidate| !owners.iter().any(|owner| fqn_matches(owner, candidate)))
+                .filter(|candidate| {
+                    owners.iter().any(|owner| {
+                        owner_inherits_from(&self.index, candidate, owner)
+                            || owner_inherits_from(&self.index, owner, candidate)
+                    })
+                })
+                .cloned()
+                .collect();
+            if related.is_empty() {
+                break;
+            }
+            owners.extend(related);
+            owners.sort();
+            owners.dedup();
+        }
 
-            if !refs.is_empty() {
-                if let Ok(uri) = file_uri.parse::<Uri>() {
-                    let edits: Vec<TextEdit> = refs
-                        .into_iter()
-                        .map(|r| TextEdit {
-                            range: range_from_lsp_tuple(r.range),
-                            new_text: if target_kind == php_lsp_types::PhpSymbolKind::Property
-                                && r.starts_with_dollar
-                            {
-                                format!(
-                                    "${}",
-                                    property_new_name.unwrap_or(new_name.trim_start_matches('$'))
-                                )
-                            } else {
-                                property_new_name
-                                    .unwrap_or(normalized_new_name.as_str())
-                                    .to_string()
-                            },
+        for owner in &owners {
+            let overrides_builtin = self
+                .index
+                .get_type_hierarchy_symbols(owner)
+                .iter()
+                .filter(|ancestor| ancestor.modifiers.is_builtin)
+                .any(|ancestor| {
+                    self.index
+                        .resolve_member(&format!("{}::{}", ancestor.fqn, method_name))
+                        .is_some_and(|method| {
+                            method.parent_fqn.as_deref() == Some(ancestor.fqn.as_str())
                         })
-                        .collect();
-                    changes.entry(uri).or_default().extend(edits);
+                });
+            if overrides_builtin {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                    "Cannot rename a method that overrides a built-in method",
+                ));
+            }
+        }
+
+        Ok(owners
+            .iter()
+            .filter(|owner| {
+                !owners.iter().any(|other| {
+                    !fqn_matches(other, owner) && owner_inherits_from(&self.index, owner, other)
+                })
+            })
+            .map(|owner| format!("{owner}::{method_name}"))
+            .collect())
+    }
+
+    /// Edits that rewrite the imported name of every `use` clause importing
+    /// `target_fqn`. Aliases are kept as they are.
+    async fn class_use_statement_rename_edits(
+        &self,
+        target_fqn: &str,
+        old_short: &str,
+        new_name: &str,
+    ) -> Vec<(Uri, TextEdit)> {
+        let encoding = self.position_encoding();
+        let target = target_fqn.trim_start_matches('\\');
+        let clauses: Vec<(String, (u32, u32, u32, u32))> = self
+            .index
+            .file_symbols
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .use_statements
+                    .iter()
+                    .filter(|stmt| {
+                        stmt.kind == php_lsp_types::UseKind::Class
+                            && stmt
+                                .fqn
+                                .trim_start_matches('\\')
+                                .eq_ignore_ascii_case(target)
+                    })
+                    .map(|stmt| (entry.key().clone(), stmt.range))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut edits = Vec::new();
+        let mut sources: HashMap<String, Option<String>> = HashMap::new();
+        for (file_uri, clause_range) in clauses {
+            let Ok(uri) = file_uri.parse::<Uri>() else {
+                continue;
+            };
+            if !sources.contains_key(&file_uri) {
+                let source = self.source_for_uri(&file_uri, "rename").await;
+                sources.insert(file_uri.clone(), source);
+            }
+            let Some(source) = sources.get(&file_uri).and_then(Option::as_deref) else {
+                continue;
+            };
+            if let Some(edit) =
+                use_clause_rename_edit(source, clause_range, old_short, new_name, encoding)
+            {
+                edits.push((uri, edit));
+            }
+        }
+        edits
+    }
+
+    /// A promoted property is also a constructor parameter, so the `$name`
+    /// variable used inside the constructor body is renamed with it.
+    async fn promoted_parameter_rename_edits(
+        &self,
+        property_fqn: &str,
+        new_name: &str,
+    ) -> Option<(Uri, Vec<TextEdit>)> {
+        let encoding = self.position_encoding();
+        let property = self.index.resolve_fqn(property_fqn)?;
+        let owner = property.parent_fqn.as_deref()?;
+        let constructor = self.index.resolve_fqn(&format!("{}::__construct", owner))?;
+        if constructor.uri != property.uri
+            || !constructor.signature.as_ref().is_some_and(|signature| {
+                signature
+                    .params
+                    .iter()
+                    .any(|param| param.is_promoted && param.name == property.name)
+            })
+        {
+            return None;
+        }
+
+        let uri = property.uri.parse::<Uri>().ok()?;
+        let source = self.source_for_uri(&property.uri, "rename").await?;
+        let mut parser = FileParser::new();
+        parser.parse_full(&source);
+        let tree = parser.tree()?;
+        let (line, byte_col, _, _) = property.selection_range;
+        let edits = find_variable_references_at_position(tree, &source, line, byte_col, true)
+            .into_iter()
+            .map(|r| {
+                let rng = range_byte_to_lsp(&source, r.range, encoding);
+                TextEdit {
+                    range: Range {
+                        start: Position::new(rng.0, rng.1),
+                        end: Position::new(rng.2, rng.3),
+                    },
+                    new_text: format!("${}", new_name),
+                }
+            })
+            .collect();
+        Some((uri, edits))
+    }
+
+    /// String literals spelling the member name in files that reference the
+    /// member or declare it. They may feed dynamic accesses (`$obj->$name`,
+    /// `[$obj, 'name']`, `compact('name')`), so they are only offered as edits
+    /// that need confirmation. Ranges already covered by `changes` are skipped.
+    async fn dynamic_member_access_rename_edits(
+        &self,
+        target_fqn: &str,
+        target_kind: php_lsp_types::PhpSymbolKind,
+        new_name: &str,
+        changes: &std::collections::HashMap<Uri, Vec<TextEdit>>,
+    ) -> std::collections::HashMap<Uri, Vec<TextEdit>> {
+        let encoding = self.position_encoding();
+        let Some((_, member_name)) = target_fqn.rsplit_once("::") else {
+            return std::collections::HashMap::new();
+        };
+        let member_name = member_name.trim_start_matches('$');
+        let mut files: Vec<String> = changes.keys().map(|uri| uri.as_str().to_string()).collect();
+        files.extend(self.reference_candidate_files(target_fqn));
+        if let Some(declaration) = self.index.resolve_fqn(target_fqn) {
+            files.push(declaration.uri.to_string());
+        }
+        files.sort();
+        files.dedup();
+
+        let case_insensitive = target_kind == php_lsp_types::PhpSymbolKind::Method;
+        let literal_edits = |tree: &tree_sitter::Tree, source: &str| -> Vec<TextEdit> {
+            find_member_name_string_literals(tree, source, member_name, case_insensitive)
+                .into_iter()
+                .map(|literal| TextEdit {
+                    range: range_from_byte_range(source, literal.range, encoding),
+                    new_text: new_name.to_string(),
+                })
+                .collect()
+        };
+
+        let mut edits_by_uri = std::collections::HashMap::new();
+        for file_uri in files {
+            let Ok(uri) = file_uri.parse::<Uri>() else {
+                continue;
+            };
+            let open_edits = self.open_files.get(&file_uri).map(|parser| {
+                let source = parser.source();
+                match parser.tree() {
+                    Some(tree) if contains_quoted_member_name(&source, member_name) => {
+                        literal_edits(tree, &source)
+                    }
+                    _ => Vec::new(),
                 }
+            });
+            let literals = match open_edits {
+                Some(literals) => literals,
+                None => {
+                    let Some(source) = self.source_for_uri(&file_uri, "rename").await else {
+                        continue;
+                    };
+                    if !contains_quoted_member_name(&source, member_name) {
+                        continue;
+                    }
+                    let Some(parsed) = self.closed_file_tree(&file_uri, source).await else {
+                        continue;
+                    };
+                    literal_edits(&parsed.tree, &parsed.source)
+                }
+            };
+            let existing = changes.get(&uri);
+            let edits: Vec<TextEdit> = literals
+                .into_iter()
+                .filter(|edit| {
+                    !existing.is_some_and(|edits| {
+                        edits.iter().any(|existing| {
+                            existing.range.start < edit.range.end
+                                && edit.range.start < existing.range.end
+                        })
+                    })
+                })
+                .collect();
+            if !edits.is_empty() {
+                edits_by_uri.insert(uri, edits);
             }
         }
+        edits_by_uri
+    }
 
-        if changes.is_empty() && is_member_rename_kind(target_kind) {
-            Err(tower_lsp::jsonrpc::Error::invalid_params(
-                "Cannot safely rename member because no exact references were found",
-            ))
-        } else if changes.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(WorkspaceEdit {
-                changes: Some(changes),
-                document_changes: None,
-                change_annotations: None,
-            }))
+    /// Exact edits stay plain; dynamic access candidates carry the
+    /// [`DYNAMIC_ACCESS_ANNOTATION_ID`] annotation so the client asks first.
+    fn workspace_edit_with_dynamic_access_edits(
+        &self,
+        mut changes: std::collections::HashMap<Uri, Vec<TextEdit>>,
+        mut dynamic_access_edits: std::collections::HashMap<Uri, Vec<TextEdit>>,
+    ) -> WorkspaceEdit {
+        let mut uris: Vec<Uri> = changes
+            .keys()
+            .chain(dynamic_access_edits.keys())
+            .cloned()
+            .collect();
+        uris.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        uris.dedup();
+        let edits = uris
+            .into_iter()
+            .map(|uri| {
+                let mut edits: Vec<OneOf<TextEdit, AnnotatedTextEdit>> = changes
+                    .remove(&uri)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(OneOf::Left)
+                    .collect();
+                edits.extend(
+                    dynamic_access_edits
+                        .remove(&uri)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|text_edit| {
+                            OneOf::Right(AnnotatedTextEdit {
+                                text_edit,
+                                annotation_id: DYNAMIC_ACCESS_ANNOTATION_ID.to_string(),
+                            })
+                        }),
+                );
+                let version = self.current_document_version(uri.as_str());
+                TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+                    edits,
+                }
+            })
+            .collect();
+        WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Edits(edits)),
+            change_annotations: Some(std::collections::HashMap::from([(
+                DYNAMIC_ACCESS_ANNOTATION_ID.to_string(),
+                ChangeAnnotation {
+                    label: "Possible dynamic access".to_string(),
+                    needs_confirmation: Some(true),
+                    description: Some(
+                        "String literal matching the renamed member; it may be used through dynamic access."
+                            .to_string(),
+                    ),
+                },
+            )])),
+        }
+    }
+
+    /// PSR-4 keeps one class per file named after the class, so renaming the
+    /// class also renames `Old.php` to `New.php` when the declaration follows
+    /// that layout.
+    fn class_file_rename(
+        &self,
+        target_fqn: &str,
+        old_short: &str,
+        new_name: &str,
+    ) -> Option<RenameFile> {
+        let declaration = self.index.resolve_fqn(target_fqn)?;
+        let (dir, file_name) = declaration.uri.rsplit_once('/')?;
+        if file_name != format!("{old_short}.php") || old_short == new_name {
+            return None;
+        }
+        let new_uri_str = format!("{dir}/{new_name}.php");
+        if uri_to_path(&new_uri_str).is_some_and(|path| path.exists()) {
+            return None;
+        }
+        Some(RenameFile {
+            old_uri: declaration.uri.parse().ok()?,
+            new_uri: new_uri_str.parse().ok()?,
+            options: None,
+            annotation_id: None,
+        })
+    }
+
+    fn workspace_edit_with_file_rename(
+        &self,
+        changes: std::collections::HashMap<Uri, Vec<TextEdit>>,
+        rename_file: RenameFile,
+    ) -> WorkspaceEdit {
+        let mut changes: Vec<(Uri, Vec<TextEdit>)> = changes.into_iter().collect();
+        changes.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        // Text edits address the old URI, so the file rename goes last.
+        let mut operations: Vec<DocumentChangeOperation> = changes
+            .into_iter()
+            .map(|(uri, edits)| {
+                let version = self.current_document_version(uri.as_str());
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+            })
+            .collect();
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(rename_file)));
+        WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            change_annotations: None,
         }
     }
 
@@ -217,6 +721,7 @@ impl PhpLspBackend {
         &self,
         params: TextDocumentPositionParams,
     ) -> Result<Option<PrepareRenameResponse>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         let pos = params.position;
 
@@ -229,8 +734,8 @@ impl PhpLspBackend {
             None => return Ok(None),
         };
         let source = parser.source();
-        let byte_col = utf16_col_to_byte(&source, pos.line, pos.character);
-        let file_symbols = extract_file_symbols(tree, &source, &uri_str);
+        let byte_col = lsp_col_to_byte(&source, pos.line, pos.character, encoding);
+        let file_symbols = parser.file_symbols(&uri_str).unwrap_or_default();
 
         let resolver = |class_fqn: &str, member_name: &str| -> Option<String> {
             self.resolve_member_type(class_fqn, member_name)
@@ -249,17 +754,14 @@ impl PhpLspBackend {
             Some(&callable_param_resolver),
         ) {
             Some(sym) => {
+                let sym = constructor_site_as_class(sym);
                 // Variable rename support is local-scope only.
                 if sym.ref_kind == RefKind::Variable {
                     if !is_renameable_variable(&sym.name) {
                         return Ok(None);
                     }
-                    let rng = range_byte_to_utf16(&source, sym.range);
-                    let range = Range {
-                        start: Position::new(rng.0, rng.1),
-                        end: Position::new(rng.2, rng.3),
-                    };
-                    return Ok(Some(PrepareRenameResponse::Range(range)));
+                    let range = range_from_byte_range(&source, sym.range, encoding);
+                    return Ok(self.prepare_rename_response(&source, range, None).await);
                 }
                 if sym.ref_kind == RefKind::Unknown || sym.ref_kind == RefKind::NamespaceName {
                     return Ok(None);
@@ -289,7 +791,9 @@ impl PhpLspBackend {
                     return Ok(None);
                 }
                 let target_kind = if let Some(resolved) = resolved {
-                    if resolved.modifiers.is_builtin {
+                    if resolved.modifiers.is_builtin
+                        || is_constructor_fqn(resolved.kind, &resolved.fqn)
+                    {
                         return Ok(None);
                     }
                     resolved.kind
@@ -304,13 +808,10 @@ impl PhpLspBackend {
                     return Ok(None);
                 }
 
-                let rng2 = range_byte_to_utf16(&source, sym.range);
-                let range = Range {
-                    start: Position::new(rng2.0, rng2.1),
-                    end: Position::new(rng2.2, rng2.3),
-                };
-
-                Ok(Some(PrepareRenameResponse::Range(range)))
+                let range = range_from_byte_range(&source, sym.range, encoding);
+                Ok(self
+                    .prepare_rename_response(&source, range, Some(target_kind))
+                    .await)
             }
             None => Ok(None),
         }
@@ -323,6 +824,9 @@ enum RenameNameKind {
     Variable,
 }
 
+/// Change annotation marking rename edits found through string literals.
+pub(in crate::server) const DYNAMIC_ACCESS_ANNOTATION_ID: &str = "php-lsp.dynamicAccess";
+
 pub(in crate::server) fn normalize_variable_new_name(new_name: &str) -> Option<String> {
     let raw_name = exact_trimmed_new_name(new_name)?;
     let raw = raw_name.strip_prefix('$').unwrap_or(raw_name);
@@ -363,7 +867,7 @@ fn exact_trimmed_new_name(new_name: &str) -> Option<&str> {
     Some(trimmed)
 }
 
-fn is_php_identifier(name: &str) -> bool {
+pub(in crate::server) fn is_php_identifier(name: &str) -> bool {
     let mut chars = name.chars();
     let Some(first) = chars.next() else {
         return false;
@@ -493,6 +997,24 @@ pub(in crate::server) fn is_renameable_variable(var_name: &str) -> bool {
     )
 }
 
+/// `new ClassName()` renames the class rather than its constructor.
+fn constructor_site_as_class(mut sym: SymbolAtPosition) -> SymbolAtPosition {
+    if sym.ref_kind == RefKind::Constructor {
+        if let Some(class_fqn) = sym.fqn.strip_suffix("::__construct") {
+            sym.fqn = class_fqn.to_string();
+        }
+        sym.ref_kind = RefKind::ClassName;
+    }
+    sym
+}
+
+fn is_constructor_fqn(kind: php_lsp_types::PhpSymbolKind, fqn: &str) -> bool {
+    kind == php_lsp_types::PhpSymbolKind::Method
+        && fqn
+            .rsplit_once("::")
+            .is_some_and(|(_, name)| name.eq_ignore_ascii_case("__construct"))
+}
+
 fn rename_target_kind_from_ref_kind(kind: RefKind) -> Option<php_lsp_types::PhpSymbolKind> {
     match kind {
         RefKind::ClassName | RefKind::Constructor => Some(php_lsp_types::PhpSymbolKind::Class),
@@ -523,6 +1045,90 @@ fn is_supported_rename_kind(kind: php_lsp_types::PhpSymbolKind) -> bool {
     )
 }
 
+fn is_class_like_rename_kind(kind: php_lsp_types::PhpSymbolKind) -> bool {
+    matches!(
+        kind,
+        php_lsp_types::PhpSymbolKind::Class
+            | php_lsp_types::PhpSymbolKind::Interface
+            | php_lsp_types::PhpSymbolKind::Trait
+            | php_lsp_types::PhpSymbolKind::Enum
+    )
+}
+
+/// Whether `owner` extends, implements or uses `ancestor` anywhere in its
+/// hierarchy.
+fn owner_inherits_from(index: &WorkspaceIndex, owner: &str, ancestor: &str) -> bool {
+    class_extends_or_implements(index, owner, ancestor, &mut Vec::new())
+        || class_or_ancestor_uses_trait(index, owner, ancestor, &mut Vec::new())
+}
+
+/// Cheap pre-parse filter: whether `source` holds `member_name` as a quoted
+/// string literal, ignoring ASCII case.
+fn contains_quoted_member_name(source: &str, member_name: &str) -> bool {
+    let source = source.to_ascii_lowercase();
+    let member_name = member_name.to_ascii_lowercase();
+    source.contains(&format!("'{member_name}'")) || source.contains(&format!("\"{member_name}\""))
+}
+
+fn short_class_name(fqn: &str) -> &str {
+    fqn.rsplit('\\').next().unwrap_or(fqn)
+}
+
+/// Narrow a class reference to its last name segment, so `\App\Old` becomes
+/// `\App\New`. Returns `None` when the reference uses an alias.
+fn class_reference_rename_edit(
+    source: &str,
+    range: Range,
+    old_short: &str,
+    new_name: &str,
+    encoding: PositionEncoding,
+) -> Option<TextEdit> {
+    if range.start.line != range.end.line {
+        return None;
+    }
+    let text = text_at_lsp_range(source, range, encoding)?;
+    let last_segment = short_class_name(text);
+    if !last_segment.eq_ignore_ascii_case(old_short) {
+        return None;
+    }
+    let segment_len = encoding.text_len(last_segment);
+    Some(TextEdit {
+        range: Range {
+            start: Position::new(range.end.line, range.end.character - segment_len),
+            end: range.end,
+        },
+        new_text: new_name.to_string(),
+    })
+}
+
+/// Replace the last segment of the name imported by a `use` clause whose
+/// byte-column range is `clause_range`.
+fn use_clause_rename_edit(
+    source: &str,
+    clause_range: (u32, u32, u32, u32),
+    old_short: &str,
+    new_name: &str,
+    encoding: PositionEncoding,
+) -> Option<TextEdit> {
+    let (line, start_col, _, _) = clause_range;
+    let line_text = source.split('\n').nth(line as usize)?;
+    let clause_text = line_text.get(start_col as usize..)?;
+    let name_len = clause_text
+        .find(|ch: char| ch != '\\' && !is_php_identifier_continue(ch))
+        .unwrap_or(clause_text.len());
+    let name = &clause_text[..name_len];
+    let last_segment = short_class_name(name);
+    if !last_segment.eq_ignore_ascii_case(old_short) {
+        return None;
+    }
+    let end_col = start_col + name_len as u32;
+    let segment_start_col = end_col - last_segment.len() as u32;
+    Some(TextEdit {
+        range: range_from_byte_range(source, (line, segment_start_col, line, end_col), encoding),
+        new_text: new_name.to_string(),
+    })
+}
+
 fn invalid_rename_name_message(kind: RenameNameKind) -> &'static str {
     match kind {
         RenameNameKind::Variable => "Invalid variable name",
@@ -595,6 +1201,26 @@ fn resolved_symbol_is_phpdoc_virtual_member(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_contains_quoted_member_name_requires_a_quoted_literal() {
+        assert!(contains_quoted_member_name(
+            "<?php $m = 'Process';",
+            "process"
+        ));
+        assert!(contains_quoted_member_name(
+            "<?php call([$o, \"process\"]);",
+            "process"
+        ));
+        assert!(!contains_quoted_member_name(
+            "<?php $o->process(); // process",
+            "process"
+        ));
+        assert!(!contains_quoted_member_name(
+            "<?php $m = 'processAll';",
+            "process"
+        ));
+    }
+
     #[test]
     fn test_symbol_rename_name_validation_by_kind() {
         let cases = [
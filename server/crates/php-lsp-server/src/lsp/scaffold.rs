@@ -0,0 +1,93 @@
+//! Scaffolding for new PHP files.
+//!
+//! `workspace/didCreateFiles` and the `php-lsp.newClass` command fill an empty
+//! PHP file with the opening tag, `declare(strict_types=1);`, the namespace
+//! PSR-4 assigns to its path and an empty class named after the file. The
+//! content goes through `workspace/applyEdit`, so it lands in the editor buffer
+//! and its undo history. Files outside PSR-4 directories only get the header.
+
+use super::super::*;
+use super::file_moves::psr4_class_name_for_path;
+use super::formatting::full_document_range;
+
+const APPLY_EDIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Content for a new PHP file declaring `class_fqn`, if any.
+pub(in crate::server) fn new_file_scaffold(class_fqn: Option<&str>, strict_types: bool) -> String {
+    let mut text = String::from("<?php\n\n");
+    if strict_types {
+        text.push_str("declare(strict_types=1);\n\n");
+    }
+    if let Some(class_fqn) = class_fqn {
+        let class_name = match class_fqn.rsplit_once('\\') {
+            Some((namespace, class_name)) => {
+                text.push_str(&format!("namespace {namespace};\n\n"));
+                class_name
+            }
+            None => class_fqn,
+        };
+        text.push_str(&format!("class {class_name}\n{{\n}}\n"));
+    }
+    text
+}
+
+impl PhpLspBackend {
+    /// Scaffold content for `uri`, or `None` unless it is an empty PHP file.
+    pub(in crate::server) async fn new_file_scaffold_for_uri(
+        &self,
+        uri: &Uri,
+        strict_types: bool,
+    ) -> Option<(Range, String)> {
+        let encoding = self.position_encoding();
+        if !self.uri_is_php_source(uri).await {
+            return None;
+        }
+        let source = self
+            .source_for_uri(uri.as_str(), "new file scaffold")
+            .await?;
+        if !source.trim().is_empty() {
+            return None;
+        }
+        let path = uri_to_path(uri.as_str())?;
+        let configs = self.workspace_configs.lock().await.clone();
+        let class_fqn = psr4_class_name_for_path(&configs, &path)
+            .filter(|class_fqn| is_php_identifier(short_name(class_fqn)));
+        Some((
+            full_document_range(&source, encoding),
+            new_file_scaffold(class_fqn.as_deref(), strict_types),
+        ))
+    }
+
+    /// Ask the client to fill `uri` with scaffold content. Returns whether the
+    /// client applied the edit; clients without `workspace.applyEdit` support
+    /// are never asked.
+    pub(in crate::server) async fn apply_new_file_scaffold(&self, uri: &Uri) -> bool {
+        if !*self.apply_edit_supported.lock().await {
+            return false;
+        }
+        let strict_types = self.new_file_scaffold.lock().await.strict_types;
+        let Some((range, new_text)) = self.new_file_scaffold_for_uri(uri, strict_types).await
+        else {
+            return false;
+        };
+
+        let mut changes = std::collections::HashMap::new();
+        changes.insert(uri.clone(), vec![TextEdit { range, new_text }]);
+        let edit = WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        };
+        match tokio::time::timeout(APPLY_EDIT_TIMEOUT, self.client.apply_edit(edit)).await {
+            Ok(Ok(response)) => response.applied,
+            Ok(Err(err)) => {
+                tracing::debug!("Scaffold edit for {} failed: {}", uri.as_str(), err);
+                false
+            }
+            Err(_) => {
+                tracing::debug!("Timed out applying scaffold edit for {}", uri.as_str());
+                false
+            }
+        }
+    }
+}
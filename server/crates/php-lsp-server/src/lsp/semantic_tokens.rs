@@ -19,11 +19,14 @@ pub(super) fn semantic_tokens_legend() -> SemanticTokensLegend {
     }
 }
 
-fn semantic_tokens_for_parser(parser: &FileParser) -> Option<Vec<SemanticToken>> {
+fn semantic_tokens_for_parser(
+    parser: &FileParser,
+    encoding: PositionEncoding,
+) -> Option<Vec<SemanticToken>> {
     let tree = parser.tree()?;
     let source = parser.source();
     Some(
-        extract_semantic_tokens(tree, &source)
+        extract_semantic_tokens(tree, &source, encoding)
             .into_iter()
             .map(|token| SemanticToken {
                 delta_line: token.delta_line,
@@ -48,8 +51,9 @@ struct AbsoluteSemanticToken {
 fn semantic_tokens_for_parser_range(
     parser: &FileParser,
     range: Range,
+    encoding: PositionEncoding,
 ) -> Option<Vec<SemanticToken>> {
-    let tokens = semantic_tokens_for_parser(parser)?;
+    let tokens = semantic_tokens_for_parser(parser, encoding)?;
     let absolute_tokens = decode_semantic_tokens(&tokens);
     let range_tokens: Vec<_> = absolute_tokens
         .into_iter()
@@ -167,6 +171,7 @@ impl PhpLspBackend {
         &self,
         params: SemanticTokensParams,
     ) -> Result<Option<SemanticTokensResult>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         tracing::debug!("semanticTokens/full: {}", uri_str);
 
@@ -176,11 +181,11 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let template_document = self.template_document(&uri_str);
-            match semantic_tokens_for_parser(&parser) {
+            match semantic_tokens_for_parser(&parser, encoding) {
                 Some(data) if template_document.is_some() => template_document
                     .as_ref()
                     .expect("checked above")
-                    .map_semantic_tokens_to_original(data),
+                    .map_semantic_tokens_to_original(data, encoding),
                 Some(data) => data,
                 None => return Ok(None),
             }
@@ -201,6 +206,7 @@ impl PhpLspBackend {
         &self,
         params: SemanticTokensDeltaParams,
     ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         tracing::debug!(
             "semanticTokens/full/delta: {} previous={}",
@@ -214,11 +220,11 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let template_document = self.template_document(&uri_str);
-            match semantic_tokens_for_parser(&parser) {
+            match semantic_tokens_for_parser(&parser, encoding) {
                 Some(data) if template_document.is_some() => template_document
                     .as_ref()
                     .expect("checked above")
-                    .map_semantic_tokens_to_original(data),
+                    .map_semantic_tokens_to_original(data, encoding),
                 Some(data) => data,
                 None => return Ok(None),
             }
@@ -249,6 +255,7 @@ impl PhpLspBackend {
         &self,
         params: SemanticTokensRangeParams,
     ) -> Result<Option<SemanticTokensRangeResult>> {
+        let encoding = self.position_encoding();
         let uri_str = params.text_document.uri.as_str().to_string();
         tracing::debug!("semanticTokens/range: {}", uri_str);
 
@@ -258,15 +265,17 @@ impl PhpLspBackend {
                 None => return Ok(None),
             };
             let template_document = self.template_document(&uri_str);
-            match semantic_tokens_for_parser(&parser) {
+            match semantic_tokens_for_parser(&parser, encoding) {
                 Some(data) if template_document.is_some() => template_document
                     .as_ref()
                     .expect("checked above")
-                    .map_semantic_tokens_range_to_original(data, params.range),
-                Some(_) => match semantic_tokens_for_parser_range(&parser, params.range) {
-                    Some(data) => data,
-                    None => return Ok(None),
-                },
+                    .map_semantic_tokens_range_to_original(data, params.range, encoding),
+                Some(_) => {
+                    match semantic_tokens_for_parser_range(&parser, params.range, encoding) {
+                        Some(data) => data,
+                        None => return Ok(None),
+                    }
+                }
                 None => return Ok(None),
             }
         };
@@ -38,7 +38,7 @@ impl TwigContextPhpSourceResolver<'_> {
         &self,
         symbol: &php_lsp_types::SymbolInfo,
     ) -> Option<TwigContextResolvedPhpSource> {
-        if let Some(open_source) = self.sources.get(&symbol.uri) {
+        if let Some(open_source) = self.sources.get(symbol.uri.as_str()) {
             return Some(TwigContextResolvedPhpSource {
                 uri: open_source.uri.clone(),
                 source: open_source.source.clone(),
@@ -53,7 +53,7 @@ impl TwigContextPhpSourceResolver<'_> {
         let path = uri_to_path(&symbol.uri)?;
         let source = std::fs::read_to_string(path).ok()?;
         Some(TwigContextResolvedPhpSource {
-            uri: symbol.uri.clone(),
+            uri: symbol.uri.to_string(),
             source,
             file_symbols: None,
         })
@@ -234,6 +234,7 @@ fn collect_twig_render_context_types(
     ctx: &TwigShapeDefinitionContext<'_>,
     variables: &mut HashMap<String, String>,
     shape_definitions: &mut HashMap<String, Vec<TemplateShapeKeyDefinition>>,
+    encoding: PositionEncoding,
 ) {
     let mut offset = 0usize;
     while let Some((name_start, name_end, open_paren)) = next_twig_context_call(ctx.source, offset)
@@ -260,9 +261,16 @@ fn collect_twig_render_context_types(
                     context_arg,
                     variables,
                     shape_definitions,
+                    encoding,
                 )
             {
-                collect_twig_context_array_types(ctx, context_arg, variables, shape_definitions);
+                collect_twig_context_array_types(
+                    ctx,
+                    context_arg,
+                    variables,
+                    shape_definitions,
+                    encoding,
+                );
             }
         }
         offset = close_paren + 1;
@@ -281,6 +289,7 @@ fn collect_twig_context_compact_types(
     range: (usize, usize),
     variables: &mut HashMap<String, String>,
     shape_definitions: &mut HashMap<String, Vec<TemplateShapeKeyDefinition>>,
+    encoding: PositionEncoding,
 ) -> bool {
     let (start, end) = trim_source_range(ctx.source, range.0, range.1);
     let Some(value) = ctx.source.get(start..end).map(str::trim) else {
@@ -330,6 +339,7 @@ fn collect_twig_context_compact_types(
             &name,
             TemplateShapeDefinitionTarget::Direct,
             &mut visited_variables,
+            encoding,
         );
         merge_twig_context_variable_shape_definitions(shape_definitions, name, definitions);
     }
@@ -364,6 +374,7 @@ fn collect_twig_context_array_types(
     range: (usize, usize),
     variables: &mut HashMap<String, String>,
     shape_definitions: &mut HashMap<String, Vec<TemplateShapeKeyDefinition>>,
+    encoding: PositionEncoding,
 ) {
     let (start, end) = range;
     let Some((inner_start, inner_end)) = php_array_inner_range(ctx.source, start, end) else {
@@ -401,6 +412,7 @@ fn collect_twig_context_array_types(
             value_range,
             TemplateShapeDefinitionTarget::Direct,
             &mut visited_variables,
+            encoding,
         );
         merge_twig_context_variable_shape_definitions(shape_definitions, name, definitions);
     }
@@ -497,6 +509,7 @@ fn collect_twig_context_value_shape_definitions(
     range: (usize, usize),
     default_target: TemplateShapeDefinitionTarget,
     visited_variables: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Vec<TemplateShapeKeyDefinition> {
     let (start, end) = trim_source_range(ctx.source, range.0, range.1);
     let value = ctx.source.get(start..end).unwrap_or("").trim();
@@ -508,6 +521,7 @@ fn collect_twig_context_value_shape_definitions(
         default_target,
         &[],
         &mut definitions,
+        encoding,
     );
 
     if let Some(found) = collect_twig_context_symfony_form_shape_definitions(
@@ -515,6 +529,7 @@ fn collect_twig_context_value_shape_definitions(
         (start, end),
         default_target,
         visited_variables,
+        encoding,
     ) {
         definitions.extend(found);
         return definitions;
@@ -527,6 +542,7 @@ fn collect_twig_context_value_shape_definitions(
             variable_name,
             default_target,
             visited_variables,
+            encoding,
         ));
         return definitions;
     }
@@ -536,6 +552,7 @@ fn collect_twig_context_value_shape_definitions(
         (start, end),
         default_target,
         visited_variables,
+        encoding,
     ) {
         definitions.extend(found);
         return definitions;
@@ -546,6 +563,7 @@ fn collect_twig_context_value_shape_definitions(
         (start, end),
         default_target,
         visited_variables,
+        encoding,
     ) {
         definitions.extend(found);
     }
@@ -559,6 +577,7 @@ fn collect_twig_context_assignment_shape_definitions(
     variable_name: &str,
     default_target: TemplateShapeDefinitionTarget,
     visited_variables: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Vec<TemplateShapeKeyDefinition> {
     if !visited_variables.insert(variable_name.to_string()) {
         return Vec::new();
@@ -577,6 +596,7 @@ fn collect_twig_context_assignment_shape_definitions(
                 assignment,
                 default_target,
                 visited_variables,
+                encoding,
             ));
         }
         if definitions.is_empty() {
@@ -592,6 +612,7 @@ fn collect_twig_context_assignment_shape_definitions(
                         append_value,
                         TemplateShapeDefinitionTarget::IterableValue,
                         visited_variables,
+                        encoding,
                     ));
                 }
             }
@@ -608,6 +629,7 @@ fn collect_literal_array_shape_definitions(
     target: TemplateShapeDefinitionTarget,
     prefix: &[String],
     definitions: &mut Vec<TemplateShapeKeyDefinition>,
+    encoding: PositionEncoding,
 ) {
     let Some((inner_start, inner_end)) = php_array_inner_range(ctx.source, range.0, range.1) else {
         return;
@@ -640,10 +662,18 @@ fn collect_literal_array_shape_definitions(
                 target,
                 path.clone(),
                 (key_start, key_end),
+                encoding,
             ) {
                 definitions.push(definition);
             }
-            collect_literal_array_shape_definitions(ctx, value_range, target, &path, definitions);
+            collect_literal_array_shape_definitions(
+                ctx,
+                value_range,
+                target,
+                &path,
+                definitions,
+                encoding,
+            );
             continue;
         }
 
@@ -653,6 +683,7 @@ fn collect_literal_array_shape_definitions(
             TemplateShapeDefinitionTarget::IterableValue,
             prefix,
             definitions,
+            encoding,
         );
     }
 }
@@ -662,6 +693,7 @@ fn collect_twig_context_member_call_shape_definitions(
     range: (usize, usize),
     default_target: TemplateShapeDefinitionTarget,
     visited_variables: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Option<Vec<TemplateShapeKeyDefinition>> {
     let index = ctx.index?;
     let (start, end) = trim_source_range(ctx.source, range.0, range.1);
@@ -689,6 +721,7 @@ fn collect_twig_context_member_call_shape_definitions(
                 &receiver_fqn,
                 &return_type,
                 default_target,
+                encoding,
             ));
         }
     }
@@ -700,6 +733,7 @@ fn collect_twig_context_member_access_shape_definitions(
     range: (usize, usize),
     default_target: TemplateShapeDefinitionTarget,
     visited_variables: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Option<Vec<TemplateShapeKeyDefinition>> {
     let index = ctx.index?;
     let (start, end) = trim_source_range(ctx.source, range.0, range.1);
@@ -728,6 +762,7 @@ fn collect_twig_context_member_access_shape_definitions(
                 &receiver_fqn,
                 &property_type,
                 default_target,
+                encoding,
             ));
         }
     }
@@ -739,6 +774,7 @@ fn collect_twig_context_symfony_form_shape_definitions(
     range: (usize, usize),
     default_target: TemplateShapeDefinitionTarget,
     visited_variables: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Option<Vec<TemplateShapeKeyDefinition>> {
     let fields = twig_context_symfony_form_fields_for_value(
         ctx.source,
@@ -752,6 +788,7 @@ fn collect_twig_context_symfony_form_shape_definitions(
         &fields,
         default_target,
         &[],
+        encoding,
     ))
 }
 
@@ -759,6 +796,7 @@ fn symfony_form_shape_definitions_from_fields(
     fields: &SymfonyFormTypeFields,
     target: TemplateShapeDefinitionTarget,
     path_prefix: &[String],
+    encoding: PositionEncoding,
 ) -> Vec<TemplateShapeKeyDefinition> {
     let mut definitions = Vec::new();
     for field in &fields.fields {
@@ -772,6 +810,7 @@ fn symfony_form_shape_definitions_from_fields(
                 target,
                 path,
                 field.key_range,
+                encoding,
             ) {
                 if !definitions.iter().any(|existing| existing == &definition) {
                     definitions.push(definition);
@@ -812,10 +851,11 @@ fn collect_symbol_type_shape_definitions(
     fallback_owner_fqn: &str,
     type_info: &php_lsp_types::TypeInfo,
     direct_target: TemplateShapeDefinitionTarget,
+    encoding: PositionEncoding,
 ) -> Vec<TemplateShapeKeyDefinition> {
     let owner_fqn = symbol.parent_fqn.as_deref().unwrap_or(fallback_owner_fqn);
     let normalized_type_info =
-        if let Some(symbol_file_symbols) = index.file_symbols.get(&symbol.uri) {
+        if let Some(symbol_file_symbols) = index.file_symbols.get(symbol.uri.as_str()) {
             resolve_type_info_with_context(type_info, &symbol_file_symbols, owner_fqn)
         } else {
             resolve_type_info_with_context(type_info, fallback_file_symbols, owner_fqn)
@@ -837,12 +877,13 @@ fn collect_symbol_type_shape_definitions(
     let mut definitions = Vec::new();
     for (target, path, segments) in requests {
         if let Some((start, end)) = find_shape_path_range_in_text(comment, 0, &segments) {
-            if let Some(range) = doc_comment_relative_range_to_lsp(comment, doc_start, (start, end))
+            if let Some(range) =
+                doc_comment_relative_range_to_lsp(comment, doc_start, (start, end), encoding)
             {
                 let definition = TemplateShapeKeyDefinition {
                     target,
                     path,
-                    uri: symbol.uri.clone(),
+                    uri: symbol.uri.to_string(),
                     range,
                 };
                 if !definitions.iter().any(|existing| existing == &definition) {
@@ -1069,8 +1110,9 @@ fn doc_comment_relative_range_to_lsp(
     comment: &str,
     doc_start: (u32, u32),
     byte_range: (usize, usize),
+    encoding: PositionEncoding,
 ) -> Option<(u32, u32, u32, u32)> {
-    let range = php_lsp_parser::utf16::range_byte_to_utf16(
+    let range = php_lsp_parser::position::range_byte_to_lsp(
         comment,
         (
             byte_line_col_at_offset(comment, byte_range.0).0,
@@ -1078,6 +1120,7 @@ fn doc_comment_relative_range_to_lsp(
             byte_line_col_at_offset(comment, byte_range.1).0,
             byte_line_col_at_offset(comment, byte_range.1).1,
         ),
+        encoding,
     );
     let start_col = if range.0 == 0 {
         doc_start.1 + range.1
@@ -1103,11 +1146,15 @@ fn template_shape_key_definition_from_byte_range(
     target: TemplateShapeDefinitionTarget,
     path: Vec<String>,
     byte_range: (usize, usize),
+    encoding: PositionEncoding,
 ) -> Option<TemplateShapeKeyDefinition> {
     let start = byte_line_col_at_offset(source, byte_range.0);
     let end = byte_line_col_at_offset(source, byte_range.1);
-    let range =
-        php_lsp_parser::utf16::range_byte_to_utf16(source, (start.0, start.1, end.0, end.1));
+    let range = php_lsp_parser::position::range_byte_to_lsp(
+        source,
+        (start.0, start.1, end.0, end.1),
+        encoding,
+    );
     Some(TemplateShapeKeyDefinition {
         target,
         path,
@@ -2490,7 +2537,7 @@ fn resolve_twig_context_symbol_source(
     let path = uri_to_path(&symbol.uri)?;
     let source = std::fs::read_to_string(path).ok()?;
     Some(TwigContextResolvedPhpSource {
-        uri: symbol.uri.clone(),
+        uri: symbol.uri.to_string(),
         source,
         file_symbols: None,
     })
@@ -2581,7 +2628,7 @@ fn symfony_symbol_is_descendant_of(
     target_fqn: &str,
     visited: &mut HashSet<String>,
 ) -> bool {
-    if !visited.insert(symbol.fqn.clone()) {
+    if !visited.insert(symbol.fqn.to_string()) {
         return false;
     }
 
@@ -2872,7 +2919,7 @@ fn containing_class_fqn_at_offset(
             ) && byte_position_in_range(position, symbol.range)
         })
         .min_by_key(|symbol| symbol_range_len(symbol.range))
-        .map(|symbol| symbol.fqn.clone())
+        .map(|symbol| symbol.fqn.to_string())
 }
 
 fn twig_context_repository_member_call_type_text(
@@ -2961,7 +3008,7 @@ fn doctrine_repository_entity_from_repository_class_binding(
             if binding.kind != php_lsp_types::TemplateBindingKind::RepositoryClass {
                 return None;
             }
-            fqn_eq(&binding.target, repository_fqn).then(|| symbol.fqn.clone())
+            fqn_eq(&binding.target, repository_fqn).then(|| symbol.fqn.to_string())
         })
     })
 }
@@ -2979,7 +3026,7 @@ fn conventional_entity_fqn_for_repository(
     if let Some(candidate) = direct_candidate {
         if let Some(symbol) = index.resolve_fqn(&candidate) {
             if matches!(symbol.kind, php_lsp_types::PhpSymbolKind::Class) {
-                return Some(symbol.fqn.clone());
+                return Some(symbol.fqn.to_string());
             }
         }
     }
@@ -2989,7 +3036,7 @@ fn conventional_entity_fqn_for_repository(
         (matches!(symbol.kind, php_lsp_types::PhpSymbolKind::Class)
             && symbol.name == entity_short
             && symbol.fqn.contains("\\Entity\\"))
-        .then(|| symbol.fqn.clone())
+        .then(|| symbol.fqn.to_string())
     });
     let first = candidates.next()?;
     candidates.next().is_none().then_some(first)
@@ -3040,7 +3087,7 @@ fn twig_context_type_info_text_for_symbol(
     type_info: &php_lsp_types::TypeInfo,
 ) -> Option<String> {
     let owner_fqn = symbol.parent_fqn.as_deref().unwrap_or(fallback_owner_fqn);
-    if let Some(symbol_file_symbols) = index.file_symbols.get(&symbol.uri) {
+    if let Some(symbol_file_symbols) = index.file_symbols.get(symbol.uri.as_str()) {
         return twig_context_type_info_text(&symbol_file_symbols, owner_fqn, type_info);
     }
 
@@ -3472,15 +3519,18 @@ pub(in crate::server) fn map_goto_definition_response_for_template(
     current_uri: &str,
     template: &TemplateDocument,
     response: GotoDefinitionResponse,
+    encoding: PositionEncoding,
 ) -> GotoDefinitionResponse {
     match response {
         GotoDefinitionResponse::Scalar(location) => GotoDefinitionResponse::Scalar(
-            map_location_for_template(current_uri, template, location),
+            map_location_for_template(current_uri, template, location, encoding),
         ),
         GotoDefinitionResponse::Array(locations) => GotoDefinitionResponse::Array(
             locations
                 .into_iter()
-                .map(|location| map_location_for_template(current_uri, template, location))
+                .map(|location| {
+                    map_location_for_template(current_uri, template, location, encoding)
+                })
                 .collect(),
         ),
         GotoDefinitionResponse::Link(links) => GotoDefinitionResponse::Link(
@@ -3489,12 +3539,12 @@ pub(in crate::server) fn map_goto_definition_response_for_template(
                 .map(|mut link| {
                     if link.target_uri.as_str() == current_uri {
                         if let Some(range) =
-                            template.map_virtual_range_to_original(link.target_range)
+                            template.map_virtual_range_to_original(link.target_range, encoding)
                         {
                             link.target_range = range;
                         }
-                        if let Some(range) =
-                            template.map_virtual_range_to_original(link.target_selection_range)
+                        if let Some(range) = template
+                            .map_virtual_range_to_original(link.target_selection_range, encoding)
                         {
                             link.target_selection_range = range;
                         }
@@ -3510,9 +3560,10 @@ pub(in crate::server) fn map_location_for_template(
     current_uri: &str,
     template: &TemplateDocument,
     mut location: Location,
+    encoding: PositionEncoding,
 ) -> Location {
     if location.uri.as_str() == current_uri {
-        if let Some(range) = template.map_virtual_range_to_original(location.range) {
+        if let Some(range) = template.map_virtual_range_to_original(location.range, encoding) {
             location.range = range;
         }
     }
@@ -3525,6 +3576,7 @@ async fn cached_twig_context_file_variables_for_state(
     index: Arc<WorkspaceIndex>,
     twig_context_disk_cache: &Arc<Mutex<TwigContextDiskCache>>,
     open_php_sources: HashMap<String, TwigContextPhpSource>,
+    encoding: PositionEncoding,
 ) -> Vec<TwigContextFileVariables> {
     let key = TwigContextDiskCacheKey {
         root: root.to_path_buf(),
@@ -3546,6 +3598,7 @@ async fn cached_twig_context_file_variables_for_state(
             &template_name,
             index.as_ref(),
             &open_php_sources,
+            encoding,
         )
     })
     .await
@@ -3571,6 +3624,7 @@ fn collect_cached_twig_context_file_variables(
     template_name: &str,
     index: &WorkspaceIndex,
     open_php_sources: &HashMap<String, TwigContextPhpSource>,
+    encoding: PositionEncoding,
 ) -> Vec<TwigContextFileVariables> {
     let mut result = Vec::new();
     let php_sources = TwigContextPhpSourceResolver {
@@ -3604,6 +3658,7 @@ fn collect_cached_twig_context_file_variables(
             &ctx,
             &mut variables,
             &mut shape_definitions,
+            encoding,
         );
         if variables.is_empty() {
             continue;
@@ -3624,6 +3679,7 @@ async fn direct_twig_variable_types_for_template_state(
     open_files: &Arc<DashMap<String, FileParser>>,
     index: &Arc<WorkspaceIndex>,
     twig_context_disk_cache: &Arc<Mutex<TwigContextDiskCache>>,
+    encoding: PositionEncoding,
 ) -> Vec<TemplateVariableType> {
     let mut variables = HashMap::<String, String>::new();
     let mut shape_definitions = HashMap::<String, Vec<TemplateShapeKeyDefinition>>::new();
@@ -3686,6 +3742,7 @@ async fn direct_twig_variable_types_for_template_state(
                     &ctx,
                     &mut variables,
                     &mut shape_definitions,
+                    encoding,
                 );
             }
             (variables, shape_definitions)
@@ -3716,6 +3773,7 @@ async fn direct_twig_variable_types_for_template_state(
         index.clone(),
         twig_context_disk_cache,
         open_php_sources_for_disk_scan,
+        encoding,
     )
     .await
     {
@@ -3755,6 +3813,7 @@ struct SymfonyFormField {
     key_range: (usize, usize),
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn twig_include_variable_types_for_template_state(
     root: &Path,
     target_template_name: &str,
@@ -3763,6 +3822,7 @@ async fn twig_include_variable_types_for_template_state(
     template_documents: &Arc<DashMap<String, TemplateDocument>>,
     index: &Arc<WorkspaceIndex>,
     twig_context_disk_cache: &Arc<Mutex<TwigContextDiskCache>>,
+    encoding: PositionEncoding,
 ) -> Vec<TemplateVariableType> {
     let mut variables = HashMap::<String, String>::new();
     let mut shape_definitions = HashMap::<String, Vec<TemplateShapeKeyDefinition>>::new();
@@ -3801,6 +3861,7 @@ async fn twig_include_variable_types_for_template_state(
             open_files,
             index,
             twig_context_disk_cache,
+            encoding,
         )
         .await;
         collect_twig_include_context_types_from_ranges(
@@ -3868,6 +3929,7 @@ async fn twig_include_variable_types_for_template_state(
             open_files,
             index,
             twig_context_disk_cache,
+            encoding,
         )
         .await;
         collect_twig_include_context_types_from_ranges(
@@ -4332,6 +4394,7 @@ async fn twig_variable_types_for_template_state(
     index: &Arc<WorkspaceIndex>,
     workspace_roots: &[PathBuf],
     twig_context_disk_cache: &Arc<Mutex<TwigContextDiskCache>>,
+    encoding: PositionEncoding,
 ) -> Vec<TemplateVariableType> {
     let Some(root) = workspace_root_for_template_context_uri(uri_str, workspace_roots) else {
         return Vec::new();
@@ -4350,6 +4413,7 @@ async fn twig_variable_types_for_template_state(
         open_files,
         index,
         twig_context_disk_cache,
+        encoding,
     )
     .await
     {
@@ -4364,6 +4428,7 @@ async fn twig_variable_types_for_template_state(
         template_documents,
         index,
         twig_context_disk_cache,
+        encoding,
     )
     .await
     {
@@ -4426,7 +4491,7 @@ fn symfony_user_class_fqn(index: &WorkspaceIndex) -> Option<String> {
                 &mut HashSet::new(),
             )
         {
-            candidates.push(symbol.fqn.clone());
+            candidates.push(symbol.fqn.to_string());
         }
     }
     candidates.sort_by(|left, right| {
@@ -4458,6 +4523,7 @@ pub(in crate::server) async fn refresh_open_twig_contexts_for_state(
     workspace_roots: &[PathBuf],
     twig_context_disk_cache: &Arc<Mutex<TwigContextDiskCache>>,
     semantic_tokens_cache: &Arc<Mutex<SemanticTokensCache>>,
+    encoding: PositionEncoding,
 ) -> Vec<String> {
     let mut candidates: Vec<_> = template_documents
         .iter()
@@ -4495,6 +4561,7 @@ pub(in crate::server) async fn refresh_open_twig_contexts_for_state(
             index,
             workspace_roots,
             twig_context_disk_cache,
+            encoding,
         )
         .await;
         let refreshed_template = template.with_twig_variable_types(&variable_types);
@@ -4539,6 +4606,7 @@ impl PhpLspBackend {
         &self,
         uri_str: &str,
     ) -> Vec<TemplateVariableType> {
+        let encoding = self.position_encoding();
         let roots = self.current_workspace_roots().await;
         twig_variable_types_for_template_state(
             uri_str,
@@ -4547,11 +4615,13 @@ impl PhpLspBackend {
             &self.index,
             &roots,
             &self.twig_context_disk_cache,
+            encoding,
         )
         .await
     }
 
     pub(in crate::server) async fn refresh_open_twig_contexts(&self) -> Vec<String> {
+        let encoding = self.position_encoding();
         let roots = self.current_workspace_roots().await;
         refresh_open_twig_contexts_for_state(
             &self.open_files,
@@ -4560,6 +4630,7 @@ impl PhpLspBackend {
             &roots,
             &self.twig_context_disk_cache,
             &self.semantic_tokens_cache,
+            encoding,
         )
         .await
     }
@@ -4720,9 +4791,9 @@ mod tests {
         let comment = "/**\n * @return list<array{🇺🇸 中国 བོད note: string, npId: string}>\n */";
         let symbol = php_lsp_types::SymbolInfo {
             name: "fetchRows".to_string(),
-            fqn: "App\\Repository\\MessageLogRepository::fetchRows".to_string(),
+            fqn: "App\\Repository\\MessageLogRepository::fetchRows".into(),
             kind: php_lsp_types::PhpSymbolKind::Method,
-            uri: "file:///workspace/src/Repository/MessageLogRepository.php".to_string(),
+            uri: "file:///workspace/src/Repository/MessageLogRepository.php".into(),
             range: (12, 4, 15, 5),
             selection_range: (12, 20, 12, 29),
             visibility: php_lsp_types::Visibility::Public,
@@ -4730,12 +4801,14 @@ mod tests {
             attributes: Vec::new(),
             doc_comment: Some(comment.to_string()),
             signature: None,
-            parent_fqn: Some("App\\Repository\\MessageLogRepository".to_string()),
+            value: None,
+            parent_fqn: Some("App\\Repository\\MessageLogRepository".into()),
             extends: Vec::new(),
             implements: Vec::new(),
             traits: Vec::new(),
             templates: Vec::new(),
             template_bindings: Vec::new(),
+            trait_adaptations: Vec::new(),
         };
         let type_info = php_lsp_types::TypeInfo::Generic {
             base: "list".to_string(),
@@ -4759,6 +4832,7 @@ mod tests {
             "App\\Repository\\MessageLogRepository",
             &type_info,
             TemplateShapeDefinitionTarget::Direct,
+            PositionEncoding::Utf16,
         );
         let definition = definitions
             .iter()
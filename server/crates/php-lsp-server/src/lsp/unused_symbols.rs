@@ -0,0 +1,198 @@
+//! Workspace report of public symbols nothing references, for
+//! `php-lsp.findUnusedSymbols`.
+//!
+//! Candidates are classes, interfaces, traits, enums, functions and public
+//! methods declared in workspace files; vendor and stub files are never
+//! reported. A candidate is unused when no precomputed reference outside its
+//! own declaration matches it. References from excluded files (entry points,
+//! tests) still count as usages: the exclude patterns only keep their own
+//! declarations out of the report.
+//!
+//! Methods stay conservative because dynamic calls are invisible to the index:
+//! magic methods, methods that override or implement an inherited one and
+//! methods whose name appears on an unresolved receiver are never reported.
+
+use super::super::*;
+use super::hierarchy::call_hierarchy_kind_key;
+use php_lsp_index::workspace::reference_name_key;
+use php_lsp_types::{PhpSymbolKind, SymbolInfo, SymbolReference, Visibility};
+
+/// Paths skipped when the command gets no `exclude` argument.
+pub(in crate::server) const DEFAULT_UNUSED_SYMBOL_EXCLUDES: &[&str] =
+    &["**/tests", "**/test", "**/*Test.php", "**/public", "**/bin"];
+
+/// Unused symbols in `index`, grouped by file URI in URI order.
+///
+/// Files outside `roots` are skipped unless `roots` is empty; `exclude`
+/// entries are matched like `indexing.exclude`, relative to the file's root.
+pub(in crate::server) fn find_unused_symbols(
+    index: &WorkspaceIndex,
+    open_files: &DashMap<String, FileParser>,
+    roots: &[PathBuf],
+    exclude: &[PathBuf],
+    encoding: PositionEncoding,
+) -> Vec<(String, Vec<Diagnostic>)> {
+    let references = references_by_name(index, open_files, encoding);
+    let mut uris: Vec<String> = index
+        .file_symbols
+        .iter()
+        .map(|entry| entry.key().clone())
+        .filter(|uri| is_reportable_file(uri, roots, exclude))
+        .collect();
+    uris.sort();
+
+    let mut report = Vec::new();
+    for uri in uris {
+        let Some(file_symbols) = index.file_symbols.get(&uri).map(|entry| entry.clone()) else {
+            continue;
+        };
+        let unused: Vec<&SymbolInfo> = file_symbols
+            .symbols
+            .iter()
+            .filter(|symbol| is_unused_symbol_candidate(index, symbol))
+            .filter(|symbol| !is_referenced(index, &references, symbol))
+            .collect();
+        if unused.is_empty() {
+            continue;
+        }
+
+        let source = match open_files.get(&uri) {
+            Some(parser) => Some(parser.source()),
+            None => uri_to_path(&uri).and_then(|path| std::fs::read_to_string(path).ok()),
+        };
+        let diagnostics = unused
+            .into_iter()
+            .map(|symbol| unused_symbol_diagnostic(symbol, source.as_deref(), encoding))
+            .collect();
+        report.push((uri, diagnostics));
+    }
+    report
+}
+
+fn is_reportable_file(uri: &str, roots: &[PathBuf], exclude: &[PathBuf]) -> bool {
+    if uri.starts_with("phpstub://") || uri.contains("/vendor/") {
+        return false;
+    }
+    let Some(path) = uri_to_path(uri) else {
+        return false;
+    };
+    let root = if roots.is_empty() {
+        Some(Path::new("/"))
+    } else {
+        roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.as_os_str().len())
+            .map(PathBuf::as_path)
+    };
+    root.is_some_and(|root| !path_is_excluded(&path, root, exclude))
+}
+
+/// Non-declaration references from every indexed file, bucketed by
+/// [`reference_name_key`]. Open documents use their live references.
+fn references_by_name(
+    index: &WorkspaceIndex,
+    open_files: &DashMap<String, FileParser>,
+    encoding: PositionEncoding,
+) -> HashMap<String, Vec<SymbolReference>> {
+    let mut buckets: HashMap<String, Vec<SymbolReference>> = HashMap::new();
+    for entry in index.file_references.iter() {
+        let references = match open_files.get(entry.key()) {
+            Some(parser) => current_parser_symbol_references(entry.key(), &parser, encoding),
+            None => entry.value().clone(),
+        };
+        for reference in references {
+            if !reference.is_declaration {
+                buckets
+                    .entry(reference_name_key(&reference.target_fqn))
+                    .or_default()
+                    .push(reference);
+            }
+        }
+    }
+    buckets
+}
+
+fn is_unused_symbol_candidate(index: &WorkspaceIndex, symbol: &SymbolInfo) -> bool {
+    match symbol.kind {
+        PhpSymbolKind::Function => true,
+        kind if is_class_like_kind(kind) => true,
+        PhpSymbolKind::Method => {
+            symbol.visibility == Visibility::Public
+                && !symbol.name.starts_with("__")
+                && !overrides_inherited_method(index, symbol)
+        }
+        _ => false,
+    }
+}
+
+/// Whether a parent class, interface or used trait of the method's owner
+/// declares a method with the same name.
+fn overrides_inherited_method(index: &WorkspaceIndex, method: &SymbolInfo) -> bool {
+    let Some(owner) = method.parent_fqn.as_deref() else {
+        return false;
+    };
+    index
+        .get_type_hierarchy_symbols(owner)
+        .iter()
+        .filter(|ancestor| !fqn_matches(&ancestor.fqn, owner))
+        .any(|ancestor| {
+            index.file_symbols.get(&*ancestor.uri).is_some_and(|file| {
+                file.symbols.iter().any(|member| {
+                    member.kind == PhpSymbolKind::Method
+                        && member.name.eq_ignore_ascii_case(&method.name)
+                        && member
+                            .parent_fqn
+                            .as_deref()
+                            .is_some_and(|parent| fqn_matches(parent, &ancestor.fqn))
+                })
+            })
+        })
+}
+
+fn is_referenced(
+    index: &WorkspaceIndex,
+    references: &HashMap<String, Vec<SymbolReference>>,
+    symbol: &SymbolInfo,
+) -> bool {
+    let Some(candidates) = references.get(&reference_name_key(&symbol.fqn)) else {
+        return false;
+    };
+    candidates.iter().any(|reference| {
+        symbol_reference_matches(index, reference, &symbol.fqn, symbol.kind, false)
+            || (symbol.kind == PhpSymbolKind::Method
+                && reference.target_kind == PhpSymbolKind::Method
+                && reference.receiver == php_lsp_types::SymbolReferenceReceiver::Unresolved)
+    })
+}
+
+fn unused_symbol_diagnostic(
+    symbol: &SymbolInfo,
+    source: Option<&str>,
+    encoding: PositionEncoding,
+) -> Diagnostic {
+    let message = match symbol.kind {
+        PhpSymbolKind::Method => format!("Public method {}() is never referenced", symbol.fqn),
+        PhpSymbolKind::Function => format!("Function {}() is never referenced", symbol.fqn),
+        PhpSymbolKind::Interface => format!("Interface {} is never referenced", symbol.fqn),
+        PhpSymbolKind::Trait => format!("Trait {} is never referenced", symbol.fqn),
+        PhpSymbolKind::Enum => format!("Enum {} is never referenced", symbol.fqn),
+        _ => format!("Class {} is never referenced", symbol.fqn),
+    };
+    Diagnostic {
+        range: match source {
+            Some(source) => range_from_byte_range(source, symbol.selection_range, encoding),
+            None => range_from_lsp_tuple(symbol.selection_range),
+        },
+        severity: Some(DiagnosticSeverity::HINT),
+        code: Some(NumberOrString::String("php-lsp.unused".to_string())),
+        source: Some("php-lsp".to_string()),
+        message,
+        tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+        data: Some(serde_json::json!({
+            "fqn": symbol.fqn,
+            "kind": call_hierarchy_kind_key(symbol.kind),
+        })),
+        ..Default::default()
+    }
+}
@@ -5,9 +5,10 @@
 use php_lsp_server::config::{
     write_default_project_config, InitConfigResult, PROJECT_CONFIG_FILE_NAME,
 };
-use php_lsp_server::PhpLspBackend;
+use php_lsp_server::{normalize_request_uris, PhpLspBackend};
 use std::path::PathBuf;
-use tower_lsp::{LspService, Server};
+use tower::ServiceBuilder;
+use tower_lsp::Server;
 use tracing_subscriber::EnvFilter;
 
 const DEFAULT_WORKER_THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
@@ -43,7 +44,10 @@ async fn async_main() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(PhpLspBackend::new);
+    let (service, socket) = PhpLspBackend::service();
+    let service = ServiceBuilder::new()
+        .map_request(normalize_request_uris)
+        .service(service);
 
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -77,6 +81,16 @@ async fn handle_cli_command() -> bool {
             }
             std::process::exit(result.exit_code);
         }
+        "check" => {
+            let result = php_lsp_server::analyze::run_check_cli(args.collect());
+            if !result.stdout.is_empty() {
+                print!("{}", result.stdout);
+            }
+            if !result.stderr.is_empty() {
+                eprint!("{}", result.stderr);
+            }
+            std::process::exit(result.exit_code);
+        }
         "fix" => {
             let result = php_lsp_server::fix::run_fix_cli(args.collect());
             if !result.stdout.is_empty() {
@@ -87,6 +101,26 @@ async fn handle_cli_command() -> bool {
             }
             std::process::exit(result.exit_code);
         }
+        "export" => {
+            let result = php_lsp_server::export::run_export_cli(args.collect());
+            if !result.stdout.is_empty() {
+                print!("{}", result.stdout);
+            }
+            if !result.stderr.is_empty() {
+                eprint!("{}", result.stderr);
+            }
+            std::process::exit(result.exit_code);
+        }
+        "tags" => {
+            let result = php_lsp_server::tags::run_tags_cli(args.collect());
+            if !result.stdout.is_empty() {
+                print!("{}", result.stdout);
+            }
+            if !result.stderr.is_empty() {
+                eprint!("{}", result.stderr);
+            }
+            std::process::exit(result.exit_code);
+        }
         "init-config" => {
             let path = parse_init_config_path(args.collect());
             match write_default_project_config(&path) {
@@ -138,7 +172,7 @@ fn parse_init_config_path(args: Vec<String>) -> PathBuf {
 
 fn print_help() {
     println!(
-        "php-lsp {}\n\nUsage:\n  php-lsp                 Start the LSP server on stdio\n  php-lsp analyze [PATH]  Analyze PHP files and print diagnostics\n  php-lsp analyze [PATH] --project-root <DIR> --severity <all|hint|info|warning|error> --format <table|json|github>\n  php-lsp fix [PATH] --dry-run\n  php-lsp fix [PATH] --dry-run --project-root <DIR> --rule <unused-imports|organize-imports|add-return-type> --format <table|json>\n  php-lsp init-config     Create .php-lsp.toml in the current directory\n  php-lsp init-config --path <path>\n  php-lsp --version",
+        "php-lsp {}\n\nUsage:\n  php-lsp                 Start the LSP server on stdio\n  php-lsp analyze [PATH]  Analyze PHP files and print diagnostics\n  php-lsp analyze [PATH] --project-root <DIR> --severity <all|hint|info|warning|error> --format <table|json|github|checkstyle>\n  php-lsp analyze [PATH] --generate-baseline <FILE>\n  php-lsp analyze [PATH] --baseline <FILE>\n  php-lsp check [PATH...] --format <human|json|github|checkstyle> --fail-on <all|hint|info|warning|error>\n  php-lsp fix [PATH] --dry-run\n  php-lsp fix [PATH] --dry-run --project-root <DIR> --rule <unused-imports|organize-imports|add-return-type> --format <table|json>\n  php-lsp export [PROJECT_ROOT] --format <scip|lsif> --output <FILE>\n  php-lsp tags [PROJECT_ROOT] --format <ctags|etags> --output <FILE>\n  php-lsp init-config     Create .php-lsp.toml in the current directory\n  php-lsp init-config --path <path>\n  php-lsp --version",
         env!("CARGO_PKG_VERSION")
     );
 }
@@ -10,6 +10,7 @@
 //! - convert incoming positions to byte offsets before parser queries;
 //! - convert byte-backed ranges before returning them through LSP.
 
+use crate::baseline::DiagnosticBaseline;
 use crate::config::{
     global_config_candidates, load_toml_settings, merge_json_objects, normalize_client_settings,
     PROJECT_CONFIG_FILE_NAME,
@@ -25,23 +26,27 @@ use crate::util::lsp_text::{
 use crate::util::uri::uri_to_path;
 use dashmap::DashMap;
 use php_lsp_completion::context::detect_context_at_byte_col;
-use php_lsp_completion::provider::provide_completions_at_range;
+use php_lsp_completion::provider::{provide_completions_at_range, SnippetOptions};
 use php_lsp_index::cache::{self, CacheNamespace, CacheSourceFile, IndexCacheConfig};
 use php_lsp_index::composer::{parse_composer_json, NamespaceMap};
 use php_lsp_index::stubs;
+use php_lsp_index::symbol_query::SymbolQuery;
 use php_lsp_index::workspace::WorkspaceIndex;
 use php_lsp_parser::diagnostics::extract_syntax_errors;
 use php_lsp_parser::parser::FileParser;
 use php_lsp_parser::phpdoc::parse_phpdoc;
+use php_lsp_parser::position::{
+    byte_col_to_lsp, lsp_col_to_byte, range_byte_to_lsp, LspLineIndex, PositionEncoding,
+};
 use php_lsp_parser::references::{
-    collect_symbol_references_in_file, find_references_in_file,
+    collect_symbol_references_in_file, find_member_name_string_literals, find_references_in_file,
     find_variable_references_at_position,
 };
 use php_lsp_parser::resolve::{
     infer_property_type_from_assignments, infer_variable_hover_info_at_node_with_resolvers,
     infer_variable_type_at_position_with_resolvers,
     infer_variable_type_info_at_position_with_resolvers, iterable_value_type_info,
-    local_variable_names_at_position, resolve_class_name_pub, symbol_at_position,
+    nullsafe_expression_type_info, resolve_class_name_pub, symbol_at_position,
     symbol_at_position_with_resolvers, variable_definition_at_position, CallableParamTypeResolver,
     CallableParameterContext, MemberTypeResolver, RefKind, SymbolAtPosition,
 };
@@ -53,14 +58,14 @@ use php_lsp_parser::semantic::{
     SemanticDiagnosticKind,
 };
 use php_lsp_parser::signature_help::signature_help_context_at_position;
+use php_lsp_parser::source::{read_php_source, SourceEncoding};
 use php_lsp_parser::symbols::extract_file_symbols;
-use php_lsp_parser::utf16::{range_byte_to_utf16, utf16_col_to_byte, Utf16LineIndex};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Component, Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
 use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, Notify};
 use tokio::task::{JoinHandle, JoinSet};
@@ -75,6 +80,8 @@ mod indexing;
 #[path = "lsp/mod.rs"]
 mod lsp;
 use indexing::cache::*;
+use indexing::phar::*;
+use indexing::runtime_stubs::*;
 pub(crate) use indexing::stubs::load_configured_stubs;
 use indexing::stubs::*;
 use indexing::vendor::*;
@@ -85,7 +92,7 @@ pub(crate) use indexing::vendor::{
 use indexing::workspace::*;
 pub(crate) use indexing::workspace::{
     collect_php_files, discover_workspace_root_config, load_effective_configuration_settings,
-    path_is_excluded, workspace_index_directories,
+    path_has_php_extension, path_is_excluded, workspace_exclude_paths, workspace_index_directories,
 };
 pub(crate) use lsp::code_action::*;
 use lsp::completion_helpers::*;
@@ -105,10 +112,15 @@ use lsp::rename::*;
 use lsp::templates::*;
 
 struct PhpLspIndexingStatusNotification;
+struct PhpLspServerStatusNotification;
 
 const DID_CHANGE_DIAGNOSTICS_DEBOUNCE_MS: u64 = 180;
-const HEAVY_REQUEST_YIELD_INTERVAL: usize = 32;
+const REFERENCE_SCAN_BATCH_SIZE: usize = 256;
+const REFERENCE_SCAN_MAX_WORKERS: usize = 4;
 const FILE_IO_SLOW_WARNING_MS: u64 = 100;
+const DEFAULT_COMPLETION_MAX_ITEMS: usize = 100;
+const MAX_DUPLICATE_RELATED_DECLARATIONS: usize = 20;
+const DEFAULT_PHP_FILE_EXTENSIONS: &[&str] = &["php"];
 const FILE_IO_TIMEOUT_MS: u64 = 15_000;
 const DIAGNOSTIC_PHASE_SLOW_WARNING_MS: u64 = 500;
 
@@ -270,12 +282,6 @@ fn document_version_is_newer(current: Option<i32>, incoming: i32) -> bool {
     current.is_none_or(|current| incoming > current)
 }
 
-async fn cooperative_heavy_request_yield(index: usize) {
-    if index % HEAVY_REQUEST_YIELD_INTERVAL == 0 {
-        tokio::task::yield_now().await;
-    }
-}
-
 async fn run_file_io_blocking<T, F>(
     label: &'static str,
     path_label: String,
@@ -323,7 +329,7 @@ async fn read_file_to_string_blocking(
 ) -> std::io::Result<String> {
     let path_label = path.display().to_string();
     match run_file_io_blocking(label, path_label.clone(), move || {
-        std::fs::read_to_string(&path)
+        read_php_source(&path).map(|decoded| decoded.text)
     })
     .await
     {
@@ -419,10 +425,76 @@ impl tower_lsp::ls_types::notification::Notification for PhpLspIndexingStatusNot
     const METHOD: &'static str = "phpLsp/indexingStatus";
 }
 
+impl tower_lsp::ls_types::notification::Notification for PhpLspServerStatusNotification {
+    type Params = serde_json::Value;
+
+    const METHOD: &'static str = "$/php-lsp/status";
+}
+
+/// Coarse server state reported through `$/php-lsp/status` for status bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ServerStatusState {
+    Idle,
+    Indexing,
+    Analyzing,
+    Error,
+}
+
+impl ServerStatusState {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Indexing => "indexing",
+            Self::Analyzing => "analyzing",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Sends the detailed `phpLsp/indexingStatus` notification followed by the
+/// matching coarse `$/php-lsp/status` state.
 async fn send_indexing_status(client: &Client, params: serde_json::Value) {
+    let status = server_status_for_indexing_phase(&params);
     client
         .send_notification::<PhpLspIndexingStatusNotification>(params)
         .await;
+    if let Some(status) = status {
+        client
+            .send_notification::<PhpLspServerStatusNotification>(status)
+            .await;
+    }
+}
+
+async fn send_server_status(client: &Client, state: ServerStatusState, message: &str) {
+    client
+        .send_notification::<PhpLspServerStatusNotification>(serde_json::json!({
+            "state": state.as_str(),
+            "message": message,
+        }))
+        .await;
+}
+
+/// Map an indexing phase to the coarse server status, carrying over the
+/// message and, while indexing, the percentage when one is known.
+pub(crate) fn server_status_for_indexing_phase(
+    params: &serde_json::Value,
+) -> Option<serde_json::Value> {
+    let state = match params.get("phase")?.as_str()? {
+        "discovering" | "loadingStubs" | "stubsLoaded" | "indexing" => ServerStatusState::Indexing,
+        "ready" => ServerStatusState::Idle,
+        "error" => ServerStatusState::Error,
+        _ => return None,
+    };
+    let mut status = serde_json::json!({ "state": state.as_str() });
+    if let Some(message) = params.get("message") {
+        status["message"] = message.clone();
+    }
+    if state == ServerStatusState::Indexing {
+        if let Some(percentage) = params.get("percentage") {
+            status["percentage"] = percentage.clone();
+        }
+    }
+    Some(status)
 }
 
 async fn clear_request_fs_caches(
@@ -548,9 +620,15 @@ impl FormattingConfig {
         }
     }
 
+    /// Whether the built-in indentation formatter handles this config: the
+    /// explicit `built-in` provider, or `auto` when no tool was detected.
+    fn uses_builtin_formatter(&self) -> bool {
+        matches!(self.provider.as_str(), "auto" | "built-in")
+    }
+
     fn command_template(&self) -> Option<String> {
         match self.provider.as_str() {
-            "auto" | "none" => None,
+            "auto" | "none" | "built-in" => None,
             "custom" => self.command.clone(),
             "pint" => self
                 .command
@@ -589,9 +667,44 @@ impl Default for PhpStanConfig {
     }
 }
 
+/// Whether an external analyzer runs: always, never, or only when its project
+/// config file exists in the workspace root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum AnalyzerActivation {
+    #[default]
+    Off,
+    On,
+    Auto,
+}
+
+impl AnalyzerActivation {
+    fn parse(value: &serde_json::Value) -> Option<Self> {
+        match value {
+            serde_json::Value::Bool(true) => Some(Self::On),
+            serde_json::Value::Bool(false) => Some(Self::Off),
+            serde_json::Value::String(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                "auto" => Some(Self::Auto),
+                "on" | "true" => Some(Self::On),
+                "off" | "false" => Some(Self::Off),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Psalm config files that enable `psalm.enabled = "auto"`.
+const PSALM_CONFIG_FILE_NAMES: &[&str] = &["psalm.xml", "psalm.xml.dist"];
+
+fn psalm_config_exists(root: &Path) -> bool {
+    PSALM_CONFIG_FILE_NAMES
+        .iter()
+        .any(|name| root.join(name).is_file())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct PsalmConfig {
-    enabled: bool,
+    enabled: AnalyzerActivation,
     command: String,
     timeout_ms: u64,
 }
@@ -599,18 +712,82 @@ struct PsalmConfig {
 impl Default for PsalmConfig {
     fn default() -> Self {
         Self {
-            enabled: false,
+            enabled: AnalyzerActivation::Off,
             command: "vendor/bin/psalm --output-format=json --no-progress {file}".to_string(),
             timeout_ms: 30_000,
         }
     }
 }
 
+/// PHP_CodeSniffer integration: `phpcs` style diagnostics and `phpcbf` fixes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PhpcsConfig {
+    enabled: bool,
+    command: String,
+    fix_command: String,
+    timeout_ms: u64,
+}
+
+impl Default for PhpcsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: "vendor/bin/phpcs --report=json -q {file}".to_string(),
+            fix_command: "vendor/bin/phpcbf -q {file}".to_string(),
+            timeout_ms: 30_000,
+        }
+    }
+}
+
+/// Stub generation for loaded extensions that phpstorm-stubs does not cover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RuntimeStubsConfig {
+    enabled: bool,
+    php_executable: String,
+    timeout_ms: u64,
+}
+
+impl Default for RuntimeStubsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            php_executable: "php".to_string(),
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+/// Durations recorded by the latest stub load and full workspace indexing run.
+#[derive(Debug, Clone, Copy, Default)]
+struct IndexingTimings {
+    stub_files: usize,
+    stubs_ms: Option<u64>,
+    workspace_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 struct AnalyzerCodeActionConfig {
     enabled: bool,
 }
 
+/// Boilerplate inserted into newly created, empty PHP files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NewFileScaffoldConfig {
+    /// Scaffold files reported by `workspace/didCreateFiles`.
+    pub(crate) enabled: bool,
+    /// Start the file with `declare(strict_types=1);`.
+    pub(crate) strict_types: bool,
+}
+
+impl Default for NewFileScaffoldConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strict_types: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub(crate) enum DiagnosticsMode {
     Off,
@@ -630,6 +807,43 @@ impl DiagnosticsMode {
     }
 }
 
+/// How `vendor/` sources reach the index (`vendorIndexing` setting).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum VendorIndexing {
+    /// Never index vendor sources.
+    Off,
+    /// Index vendor classes on first use and preload `autoload.files`.
+    #[default]
+    Lazy,
+    /// Also index every autoloaded vendor source in the background, so
+    /// workspace symbols and references cover dependencies.
+    Full,
+}
+
+impl VendorIndexing {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "lazy" => Some(Self::Lazy),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    /// The legacy `indexVendor` boolean maps to lazy or off.
+    pub(crate) fn from_index_vendor(enabled: bool) -> Self {
+        if enabled {
+            Self::Lazy
+        } else {
+            Self::Off
+        }
+    }
+
+    pub(crate) fn is_enabled(self) -> bool {
+        self != Self::Off
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum DiagnosticCategory {
     UnknownSymbols,
@@ -639,9 +853,23 @@ enum DiagnosticCategory {
     TypeCompatibility,
     OverrideSignatures,
     PhpVersion,
+    /// Opt-in: exceptions a call may throw that the caller neither catches
+    /// nor declares with `@throws`.
+    UnhandledExceptions,
 }
 
 impl DiagnosticCategory {
+    const ALL: [Self; 8] = [
+        Self::UnknownSymbols,
+        Self::Unused,
+        Self::DuplicateSymbols,
+        Self::Members,
+        Self::TypeCompatibility,
+        Self::OverrideSignatures,
+        Self::PhpVersion,
+        Self::UnhandledExceptions,
+    ];
+
     fn code(self) -> &'static str {
         match self {
             Self::UnknownSymbols => "php-lsp.unknownSymbols",
@@ -651,6 +879,7 @@ impl DiagnosticCategory {
             Self::TypeCompatibility => "php-lsp.typeCompatibility",
             Self::OverrideSignatures => "php-lsp.overrideSignatures",
             Self::PhpVersion => "php-lsp.phpVersion",
+            Self::UnhandledExceptions => "php-lsp.unhandledExceptions",
         }
     }
 
@@ -669,11 +898,178 @@ impl DiagnosticCategory {
             "typecompatibility" | "types" => Some(Self::TypeCompatibility),
             "overridesignatures" | "overrides" => Some(Self::OverrideSignatures),
             "phpversion" | "version" => Some(Self::PhpVersion),
+            "unhandledexceptions" | "exceptions" => Some(Self::UnhandledExceptions),
             _ => None,
         }
     }
 }
 
+/// Individual diagnostic rules that can override their category severity.
+/// Each rule publishes its own diagnostic code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiagnosticRule {
+    UnknownClass,
+    UnknownFunction,
+    UnresolvedUse,
+    ArgumentCountMismatch,
+    UndefinedVariable,
+    UnusedImport,
+    UnusedVariable,
+    UnusedParameter,
+    DuplicateSymbol,
+    /// A member missing from every type of a union receiver.
+    UnknownUnionMember,
+    /// Member access through a variable declared nullable.
+    PossibleNullDereference,
+    /// An enum `match` without a `default` arm that misses some cases.
+    NonExhaustiveMatch,
+    UnknownMember,
+    /// Static/instance misuse or a visibility violation on a resolved member.
+    InvalidMemberAccess,
+    TypeMismatch,
+    IncompatibleOverride,
+    /// A native type or syntax newer than the target PHP version.
+    UnsupportedPhpFeature,
+    RemovedFunction,
+    DeprecatedFunction,
+    TraitMethodCollision,
+    UnhandledException,
+}
+
+impl DiagnosticRule {
+    const COUNT: usize = 21;
+    const ALL: [Self; Self::COUNT] = [
+        Self::UnknownClass,
+        Self::UnknownFunction,
+        Self::UnresolvedUse,
+        Self::ArgumentCountMismatch,
+        Self::UndefinedVariable,
+        Self::UnusedImport,
+        Self::UnusedVariable,
+        Self::UnusedParameter,
+        Self::DuplicateSymbol,
+        Self::UnknownUnionMember,
+        Self::PossibleNullDereference,
+        Self::NonExhaustiveMatch,
+        Self::UnknownMember,
+        Self::InvalidMemberAccess,
+        Self::TypeMismatch,
+        Self::IncompatibleOverride,
+        Self::UnsupportedPhpFeature,
+        Self::RemovedFunction,
+        Self::DeprecatedFunction,
+        Self::TraitMethodCollision,
+        Self::UnhandledException,
+    ];
+
+    fn code(self) -> &'static str {
+        match self {
+            Self::UnknownClass => "php-lsp.unknownClass",
+            Self::UnknownFunction => "php-lsp.unknownFunction",
+            Self::UnresolvedUse => "php-lsp.unresolvedUse",
+            Self::ArgumentCountMismatch => "php-lsp.argumentCountMismatch",
+            Self::UndefinedVariable => "php-lsp.undefinedVariable",
+            Self::UnusedImport => "php-lsp.unusedImport",
+            Self::UnusedVariable => "php-lsp.unusedVariable",
+            Self::UnusedParameter => "php-lsp.unusedParameter",
+            Self::DuplicateSymbol => "php-lsp.duplicateSymbol",
+            Self::UnknownUnionMember => "php-lsp.unknownUnionMember",
+            Self::PossibleNullDereference => "php-lsp.possibleNullDereference",
+            Self::NonExhaustiveMatch => "php-lsp.nonExhaustiveMatch",
+            Self::UnknownMember => "php-lsp.unknownMember",
+            Self::InvalidMemberAccess => "php-lsp.invalidMemberAccess",
+            Self::TypeMismatch => "php-lsp.typeMismatch",
+            Self::IncompatibleOverride => "php-lsp.incompatibleOverride",
+            Self::UnsupportedPhpFeature => "php-lsp.unsupportedPhpFeature",
+            Self::RemovedFunction => "php-lsp.removedFunction",
+            Self::DeprecatedFunction => "php-lsp.deprecatedFunction",
+            Self::TraitMethodCollision => "php-lsp.traitMethodCollision",
+            Self::UnhandledException => "php-lsp.unhandledException",
+        }
+    }
+
+    fn category(self) -> DiagnosticCategory {
+        match self {
+            Self::UnknownClass
+            | Self::UnknownFunction
+            | Self::UnresolvedUse
+            | Self::UndefinedVariable => DiagnosticCategory::UnknownSymbols,
+            Self::ArgumentCountMismatch => DiagnosticCategory::TypeCompatibility,
+            Self::UnusedImport | Self::UnusedVariable | Self::UnusedParameter => {
+                DiagnosticCategory::Unused
+            }
+            Self::DuplicateSymbol | Self::TraitMethodCollision => {
+                DiagnosticCategory::DuplicateSymbols
+            }
+            Self::UnknownUnionMember
+            | Self::PossibleNullDereference
+            | Self::NonExhaustiveMatch
+            | Self::UnknownMember
+            | Self::InvalidMemberAccess => DiagnosticCategory::Members,
+            Self::TypeMismatch => DiagnosticCategory::TypeCompatibility,
+            Self::IncompatibleOverride => DiagnosticCategory::OverrideSignatures,
+            Self::UnsupportedPhpFeature | Self::RemovedFunction | Self::DeprecatedFunction => {
+                DiagnosticCategory::PhpVersion
+            }
+            Self::UnhandledException => DiagnosticCategory::UnhandledExceptions,
+        }
+    }
+
+    fn from_kind(kind: &SemanticDiagnosticKind) -> Self {
+        match kind {
+            SemanticDiagnosticKind::UnknownClass => Self::UnknownClass,
+            SemanticDiagnosticKind::UnknownFunction => Self::UnknownFunction,
+            SemanticDiagnosticKind::UnresolvedUse => Self::UnresolvedUse,
+            SemanticDiagnosticKind::ArgumentCountMismatch => Self::ArgumentCountMismatch,
+            SemanticDiagnosticKind::UndefinedVariable => Self::UndefinedVariable,
+            SemanticDiagnosticKind::UnusedImport => Self::UnusedImport,
+            SemanticDiagnosticKind::UnusedVariable => Self::UnusedVariable,
+            SemanticDiagnosticKind::UnusedParameter => Self::UnusedParameter,
+            SemanticDiagnosticKind::DuplicateSymbol => Self::DuplicateSymbol,
+        }
+    }
+
+    /// Parse a rule key such as `unresolvedUse`, `unresolved_use`, or the
+    /// published diagnostic code `php-lsp.unresolvedUse`.
+    fn parse(key: &str) -> Option<Self> {
+        let key = key.strip_prefix("php-lsp.").unwrap_or(key);
+        match key
+            .chars()
+            .filter(|ch| *ch != '-' && *ch != '_')
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+            .as_str()
+        {
+            "unknownclass" => Some(Self::UnknownClass),
+            "unknownfunction" => Some(Self::UnknownFunction),
+            "unresolveduse" | "unresolvedimport" => Some(Self::UnresolvedUse),
+            "argumentcountmismatch" | "argumentcount" => Some(Self::ArgumentCountMismatch),
+            "undefinedvariable" => Some(Self::UndefinedVariable),
+            "unusedimport" => Some(Self::UnusedImport),
+            "unusedvariable" => Some(Self::UnusedVariable),
+            "unusedparameter" => Some(Self::UnusedParameter),
+            "duplicatesymbol" => Some(Self::DuplicateSymbol),
+            "unknownunionmember" => Some(Self::UnknownUnionMember),
+            "possiblenulldereference" | "nulldereference" => Some(Self::PossibleNullDereference),
+            "nonexhaustivematch" => Some(Self::NonExhaustiveMatch),
+            "unknownmember" => Some(Self::UnknownMember),
+            "invalidmemberaccess" => Some(Self::InvalidMemberAccess),
+            "typemismatch" => Some(Self::TypeMismatch),
+            "incompatibleoverride" => Some(Self::IncompatibleOverride),
+            "unsupportedphpfeature" => Some(Self::UnsupportedPhpFeature),
+            "removedfunction" => Some(Self::RemovedFunction),
+            "deprecatedfunction" => Some(Self::DeprecatedFunction),
+            "traitmethodcollision" => Some(Self::TraitMethodCollision),
+            "unhandledexception" => Some(Self::UnhandledException),
+            _ => None,
+        }
+    }
+
+    fn slot(self) -> usize {
+        self as usize
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct DiagnosticLevel(Option<DiagnosticSeverity>);
 
@@ -689,6 +1085,26 @@ impl DiagnosticLevel {
             _ => None,
         }
     }
+
+    fn label(self) -> &'static str {
+        match self.0 {
+            None => "off",
+            Some(DiagnosticSeverity::ERROR) => "error",
+            Some(DiagnosticSeverity::INFORMATION) => "information",
+            Some(DiagnosticSeverity::HINT) => "hint",
+            Some(_) => "warning",
+        }
+    }
+
+    /// Parse a per-rule toggle. `true` keeps the category severity and `false`
+    /// disables the rule; strings use the regular severity names.
+    fn parse_rule(value: &serde_json::Value) -> Option<Option<Self>> {
+        match value.as_bool() {
+            Some(true) => Some(None),
+            Some(false) => Some(Some(Self(None))),
+            None => Self::parse(value).map(Some),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -700,6 +1116,8 @@ pub(crate) struct DiagnosticSeverityConfig {
     type_compatibility: DiagnosticLevel,
     override_signatures: DiagnosticLevel,
     php_version: DiagnosticLevel,
+    unhandled_exceptions: DiagnosticLevel,
+    rules: [Option<DiagnosticLevel>; DiagnosticRule::COUNT],
 }
 
 impl Default for DiagnosticSeverityConfig {
@@ -713,6 +1131,8 @@ impl Default for DiagnosticSeverityConfig {
             type_compatibility: warning,
             override_signatures: warning,
             php_version: warning,
+            unhandled_exceptions: DiagnosticLevel(None),
+            rules: [None; DiagnosticRule::COUNT],
         }
     }
 }
@@ -726,17 +1146,20 @@ impl DiagnosticSeverityConfig {
         let object = value.as_object()?;
         let mut config = Self::default();
         for (key, value) in object {
-            let Some(category) = DiagnosticCategory::parse(key) else {
-                continue;
-            };
-            let Some(level) = DiagnosticLevel::parse(value) else {
-                continue;
-            };
-            config.set(category, level);
+            if let Some(category) = DiagnosticCategory::parse(key) {
+                if let Some(level) = DiagnosticLevel::parse(value) {
+                    config.set(category, level);
+                }
+            } else if let Some(rule) = DiagnosticRule::parse(key) {
+                if let Some(level) = DiagnosticLevel::parse_rule(value) {
+                    config.rules[rule.slot()] = level;
+                }
+            }
         }
         Some(config)
     }
 
+    /// Every default-on category at `level`; opt-in categories stay off.
     fn all(level: DiagnosticLevel) -> Self {
         Self {
             unknown_symbols: level,
@@ -746,6 +1169,8 @@ impl DiagnosticSeverityConfig {
             type_compatibility: level,
             override_signatures: level,
             php_version: level,
+            unhandled_exceptions: DiagnosticLevel(None),
+            rules: [None; DiagnosticRule::COUNT],
         }
     }
 
@@ -758,6 +1183,7 @@ impl DiagnosticSeverityConfig {
             DiagnosticCategory::TypeCompatibility => self.type_compatibility = level,
             DiagnosticCategory::OverrideSignatures => self.override_signatures = level,
             DiagnosticCategory::PhpVersion => self.php_version = level,
+            DiagnosticCategory::UnhandledExceptions => self.unhandled_exceptions = level,
         }
     }
 
@@ -770,11 +1196,33 @@ impl DiagnosticSeverityConfig {
             DiagnosticCategory::TypeCompatibility => self.type_compatibility,
             DiagnosticCategory::OverrideSignatures => self.override_signatures,
             DiagnosticCategory::PhpVersion => self.php_version,
+            DiagnosticCategory::UnhandledExceptions => self.unhandled_exceptions,
         }
     }
 
-    fn severity(self, category: DiagnosticCategory) -> Option<DiagnosticSeverity> {
-        self.level(category).0
+    /// Severity for a rule's diagnostics, preferring a per-rule override over
+    /// the rule's category.
+    fn rule_severity(self, rule: DiagnosticRule) -> Option<DiagnosticSeverity> {
+        self.rule_level(rule).0
+    }
+
+    fn rule_level(self, rule: DiagnosticRule) -> DiagnosticLevel {
+        self.rules[rule.slot()].unwrap_or_else(|| self.level(rule.category()))
+    }
+
+    /// Whether any rule in `category` reports at all, so a category that is
+    /// off still runs when one of its rules is switched back on.
+    fn category_enabled(self, category: DiagnosticCategory) -> bool {
+        DiagnosticRule::ALL
+            .iter()
+            .filter(|rule| rule.category() == category)
+            .any(|rule| self.rule_level(*rule).0.is_some())
+    }
+
+    /// Severity for a semantic diagnostic, preferring a per-rule override over
+    /// the rule's category.
+    fn semantic_severity(self, kind: &SemanticDiagnosticKind) -> Option<DiagnosticSeverity> {
+        self.rule_severity(DiagnosticRule::from_kind(kind))
     }
 }
 
@@ -793,12 +1241,43 @@ impl Default for DiagnosticBudgetConfig {
     }
 }
 
+/// When php-lsp recomputes diagnostics after `didChange`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DiagnosticScheduleConfig {
+    /// Quiet period after the last change before diagnostics run.
+    pub(crate) debounce_ms: u64,
+    /// Limit `didChange` runs to syntax diagnostics; semantic diagnostics are
+    /// published on open and save only.
+    pub(crate) semantic_on_save: bool,
+}
+
+impl Default for DiagnosticScheduleConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: DID_CHANGE_DIAGNOSTICS_DEBOUNCE_MS,
+            semantic_on_save: false,
+        }
+    }
+}
+
+impl DiagnosticScheduleConfig {
+    /// Diagnostics mode for a debounced `didChange` run.
+    fn change_mode(self, mode: DiagnosticsMode) -> DiagnosticsMode {
+        if self.semantic_on_save && mode == DiagnosticsMode::BasicSemantic {
+            DiagnosticsMode::SyntaxOnly
+        } else {
+            mode
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct DiagnosticsRuntimeConfig {
     pub(crate) mode: DiagnosticsMode,
     pub(crate) severity: DiagnosticSeverityConfig,
     pub(crate) budget: DiagnosticBudgetConfig,
     pub(crate) php_version: PhpVersion,
+    pub(crate) encoding: PositionEncoding,
 }
 
 impl Default for DiagnosticsRuntimeConfig {
@@ -808,6 +1287,7 @@ impl Default for DiagnosticsRuntimeConfig {
             severity: DiagnosticSeverityConfig::default(),
             budget: DiagnosticBudgetConfig::default(),
             php_version: PhpVersion::DEFAULT,
+            encoding: PositionEncoding::default(),
         }
     }
 }
@@ -823,8 +1303,12 @@ struct AppliedConfiguration {
 struct WorkspaceIndexingOptions {
     include_paths: Vec<PathBuf>,
     exclude_paths: Vec<PathBuf>,
+    respect_gitignore: bool,
+    file_extensions: Vec<String>,
     cache_config: IndexCacheConfig,
     work_done_progress_supported: bool,
+    /// Registry of user-cancellable indexing progress tokens, keyed by token.
+    progress_cancellations: Arc<Mutex<HashMap<String, OperationCancellationToken>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -993,6 +1477,135 @@ impl TwigContextDiskCache {
     }
 }
 
+/// Parsed tree for a file that is not open in the editor, together with the
+/// source it was parsed from.
+#[derive(Debug)]
+pub(crate) struct ClosedFileTree {
+    pub(crate) source: String,
+    pub(crate) tree: tree_sitter::Tree,
+}
+
+/// Small LRU of parsed trees for recently touched closed files, so repeated
+/// folding, document-link and call-hierarchy requests do not re-parse them.
+/// Entries are only returned when the on-disk source still matches.
+#[derive(Debug)]
+struct ClosedFileTreeCache {
+    capacity: usize,
+    entries: HashMap<String, Arc<ClosedFileTree>>,
+    order: VecDeque<String>,
+}
+
+impl Default for ClosedFileTreeCache {
+    fn default() -> Self {
+        Self {
+            capacity: CLOSED_FILE_TREE_CACHE_CAPACITY,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+}
+
+impl ClosedFileTreeCache {
+    fn get(&mut self, uri: &str, source: &str) -> Option<Arc<ClosedFileTree>> {
+        let entry = self.entries.get(uri).cloned()?;
+        if entry.source != source {
+            self.remove(uri);
+            return None;
+        }
+        self.touch(uri.to_string());
+        Some(entry)
+    }
+
+    fn insert(&mut self, uri: String, value: Arc<ClosedFileTree>) {
+        self.entries.insert(uri.clone(), value);
+        self.touch(uri);
+        self.evict_over_capacity();
+    }
+
+    fn remove(&mut self, uri: &str) {
+        self.entries.remove(uri);
+        self.order.retain(|existing| existing != uri);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn touch(&mut self, uri: String) {
+        if let Some(position) = self.order.iter().position(|existing| existing == &uri) {
+            self.order.remove(position);
+        }
+        self.order.push_back(uri);
+    }
+
+    fn evict_over_capacity(&mut self) {
+        while self.order.len() > self.capacity {
+            if let Some(uri) = self.order.pop_front() {
+                self.entries.remove(&uri);
+            }
+        }
+    }
+}
+
+/// Immutable view of configuration that request handlers read on every lazy
+/// resolve. Writers publish a new snapshot instead of mutating it in place.
+#[derive(Debug, Clone)]
+pub(crate) struct ConfigSnapshot {
+    /// Workspace root path (set during initialize).
+    pub(crate) workspace_root: Option<PathBuf>,
+    /// Namespace map from composer.json.
+    pub(crate) namespace_map: Option<NamespaceMap>,
+    /// Path to bundled phpstorm-stubs (from client initializationOptions).
+    pub(crate) stubs_path: Option<PathBuf>,
+    /// Trace level from InitializeParams (off/messages/verbose).
+    pub(crate) trace_level: TraceValue,
+    /// Column unit negotiated with the client during initialize.
+    pub(crate) position_encoding: PositionEncoding,
+}
+
+impl Default for ConfigSnapshot {
+    fn default() -> Self {
+        Self {
+            workspace_root: None,
+            namespace_map: None,
+            stubs_path: None,
+            trace_level: TraceValue::Off,
+            position_encoding: PositionEncoding::default(),
+        }
+    }
+}
+
+/// Holder for the current [`ConfigSnapshot`]. The lock is only held to clone
+/// or replace the `Arc`, so concurrent requests never wait on each other.
+#[derive(Debug, Default)]
+pub(crate) struct ConfigSnapshotCell {
+    current: RwLock<Arc<ConfigSnapshot>>,
+}
+
+impl ConfigSnapshotCell {
+    pub(crate) fn load(&self) -> Arc<ConfigSnapshot> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Publish a copy of the current snapshot with `update` applied.
+    pub(crate) fn update<R>(&self, update: impl FnOnce(&mut ConfigSnapshot) -> R) -> R {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let mut next = ConfigSnapshot::clone(&current);
+        let result = update(&mut next);
+        *current = Arc::new(next);
+        result
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct WorkspaceRootConfig {
     pub(crate) root: PathBuf,
@@ -1002,6 +1615,7 @@ pub(crate) struct WorkspaceRootConfig {
 const VENDOR_FILE_LRU_CAPACITY: usize = 512;
 const FRAMEWORK_STRING_KEY_CACHE_CAPACITY: usize = 32;
 const TWIG_CONTEXT_DISK_CACHE_CAPACITY: usize = 64;
+const CLOSED_FILE_TREE_CACHE_CAPACITY: usize = 16;
 const MAX_INDEXING_PARSE_CONCURRENCY: usize = 8;
 
 #[derive(Debug, Clone)]
@@ -1010,11 +1624,21 @@ pub(crate) struct VendorPsr4Mapping {
     directories: Vec<PathBuf>,
 }
 
+/// One installed.json package entry, used to tell which packages changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VendorPackage {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) reference: String,
+    pub(crate) dir: PathBuf,
+}
+
 #[derive(Debug, Clone, Default)]
 pub(crate) struct VendorAutoloadMap {
     psr4: Vec<VendorPsr4Mapping>,
     pub(crate) files: Vec<PathBuf>,
     pub(crate) classmap: Vec<PathBuf>,
+    pub(crate) packages: Vec<VendorPackage>,
 }
 
 #[derive(Debug)]
@@ -1113,6 +1737,59 @@ fn normalize_path(path: &Path) -> PathBuf {
     normalized
 }
 
+pub(crate) fn default_php_file_extensions() -> Vec<String> {
+    DEFAULT_PHP_FILE_EXTENSIONS
+        .iter()
+        .map(|extension| extension.to_string())
+        .collect()
+}
+
+/// Lowercase extensions without their leading dot; an empty list falls back
+/// to the defaults.
+pub(crate) fn normalize_php_file_extensions(extensions: Vec<String>) -> Vec<String> {
+    let mut normalized = Vec::new();
+    for extension in extensions {
+        let extension = extension
+            .trim()
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        if !extension.is_empty() && !normalized.contains(&extension) {
+            normalized.push(extension);
+        }
+    }
+    if normalized.is_empty() {
+        return default_php_file_extensions();
+    }
+    normalized
+}
+
+/// Combines `stubs.extensions` with the `stubs.enable` and `stubs.disable`
+/// sets. Without an explicit extension list the sets are kept as `+name` and
+/// `-name` entries and applied to the discovered extensions when stubs load.
+pub(crate) fn configured_stub_extensions(
+    extensions: Option<Vec<String>>,
+    enable: Vec<String>,
+    disable: Vec<String>,
+) -> Option<Vec<String>> {
+    let overrides: Vec<String> = enable
+        .iter()
+        .map(|extension| (extension, '+'))
+        .chain(disable.iter().map(|extension| (extension, '-')))
+        .filter_map(|(extension, prefix)| {
+            let extension = extension.trim();
+            (!extension.is_empty()).then(|| format!("{prefix}{extension}"))
+        })
+        .collect();
+    if overrides.is_empty() {
+        return extensions;
+    }
+
+    match extensions {
+        Some(extensions) => Some(apply_stub_extension_overrides(extensions, &overrides)),
+        None => Some(overrides),
+    }
+}
+
 pub(crate) fn normalize_config_paths(paths: Vec<String>) -> Vec<PathBuf> {
     paths
         .into_iter()
@@ -1240,26 +1917,29 @@ pub struct PhpLspBackend {
     diagnostic_debounce_tasks: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
     /// Per-document external analyzer runs that can be cancelled by newer document events.
     analyzer_runs: Arc<Mutex<HashMap<String, OperationCancellationToken>>>,
+    /// Last external analyzer diagnostics per document, merged into debounced
+    /// didChange publishes until the next open or save reruns the analyzers.
+    analyzer_diagnostics: Arc<Mutex<HashMap<String, Vec<Diagnostic>>>>,
     /// Per-document external formatter runs that can be cancelled by newer document events.
     formatter_runs: Arc<Mutex<HashMap<String, OperationCancellationToken>>>,
     /// Current background workspace indexing run.
     indexing_run: Arc<Mutex<Option<OperationCancellationToken>>>,
+    /// Per-root indexing progress tokens the client can cancel through
+    /// `window/workDoneProgress/cancel`.
+    indexing_progress_cancellations: Arc<Mutex<HashMap<String, OperationCancellationToken>>>,
+    /// Stub load and workspace indexing durations for `php-lsp/indexStats`.
+    indexing_timings: Arc<Mutex<IndexingTimings>>,
     /// Global workspace symbol index.
     index: Arc<WorkspaceIndex>,
-    /// Workspace root path (set during initialize).
-    workspace_root: Mutex<Option<PathBuf>>,
+    /// Workspace root, namespace map, stubs path and trace level, read on
+    /// hot request paths without holding a lock across awaits.
+    config_snapshot: ConfigSnapshotCell,
     /// Workspace roots from initialize/workspaceFolders after composer discovery.
     workspace_roots: Mutex<Vec<PathBuf>>,
-    /// Namespace map from composer.json.
-    namespace_map: Mutex<Option<NamespaceMap>>,
     /// Per-workspace composer namespace maps and effective roots.
     workspace_configs: Mutex<Vec<WorkspaceRootConfig>>,
-    /// Trace level from InitializeParams (off/messages/verbose).
-    trace_level: Mutex<TraceValue>,
     /// Last explicit client initialization/configuration settings.
     client_settings: Mutex<serde_json::Value>,
-    /// Path to bundled phpstorm-stubs (from client initializationOptions).
-    stubs_path: Mutex<Option<PathBuf>>,
     /// Target PHP version from client initializationOptions.
     php_version: Mutex<PhpVersion>,
     /// Diagnostics level from phpLsp.diagnostics.mode.
@@ -1268,20 +1948,41 @@ pub struct PhpLspBackend {
     diagnostic_severity: Mutex<DiagnosticSeverityConfig>,
     /// Latency budget controls for expensive in-process diagnostics.
     diagnostic_budget: Mutex<DiagnosticBudgetConfig>,
+    /// Debounce and on-save scheduling for `didChange` diagnostics.
+    diagnostic_schedule: Mutex<DiagnosticScheduleConfig>,
+    /// Baselined diagnostics hidden from published results.
+    diagnostic_baseline: Mutex<Option<Arc<DiagnosticBaseline>>>,
     /// PHPStan subprocess diagnostics configuration.
     phpstan_config: Mutex<PhpStanConfig>,
     /// Psalm subprocess diagnostics configuration.
     psalm_config: Mutex<PsalmConfig>,
+    /// PHP_CodeSniffer diagnostics and phpcbf fix configuration.
+    phpcs_config: Mutex<PhpcsConfig>,
+    runtime_stubs_config: Mutex<RuntimeStubsConfig>,
     /// Opt-in code actions for external analyzer diagnostics.
     analyzer_code_actions: Mutex<AnalyzerCodeActionConfig>,
+    /// Offer snippet completions (`phpLsp.completion.snippets`).
+    completion_snippets: Mutex<bool>,
+    /// Maximum completion items per response (`phpLsp.completion.maxItems`);
+    /// longer lists are cut and marked incomplete.
+    completion_max_items: Mutex<usize>,
+    /// Insert call parentheses for functions and methods
+    /// (`phpLsp.completion.callSnippets`).
+    completion_call_snippets: Mutex<bool>,
+    /// Scaffolding for new PHP files (`phpLsp.newFiles.*`).
+    new_file_scaffold: Mutex<NewFileScaffoldConfig>,
     /// Whether composer.json autoload discovery is enabled.
     composer_enabled: Mutex<bool>,
-    /// Whether lazy vendor indexing is enabled.
-    index_vendor: Mutex<bool>,
+    /// How vendor sources are indexed.
+    vendor_indexing: Mutex<VendorIndexing>,
     /// Additional files/directories included in workspace indexing.
     include_paths: Mutex<Vec<PathBuf>>,
-    /// Files/directories excluded from workspace indexing.
+    /// Files/directories or glob patterns excluded from workspace indexing.
     exclude_paths: Mutex<Vec<PathBuf>>,
+    /// Whether root `.gitignore` patterns also exclude workspace files.
+    respect_gitignore: Mutex<bool>,
+    /// File extensions treated as PHP sources (`phpLsp.fileExtensions`).
+    php_file_extensions: Mutex<Vec<String>>,
     /// Configured phpstorm-stubs extension directory names.
     ///
     /// `None` means use defaults. `Some([])` means stubs were explicitly disabled
@@ -1291,6 +1992,14 @@ pub struct PhpLspBackend {
     log_level: Mutex<String>,
     /// Whether the client advertised window/workDoneProgress support.
     work_done_progress_supported: Mutex<bool>,
+    /// Whether the client accepts `RenameFile` operations in workspace edits.
+    rename_file_operation_supported: Mutex<bool>,
+    /// Whether the client accepts annotated text edits in workspace edits.
+    change_annotation_supported: Mutex<bool>,
+    /// Whether the client accepts `workspace/applyEdit` requests.
+    apply_edit_supported: Mutex<bool>,
+    /// Whether the client can select the identifier itself for prepareRename.
+    prepare_rename_default_behavior_supported: Mutex<bool>,
     /// External formatter configuration.
     formatting_config: Mutex<FormattingConfig>,
     /// Last semantic token snapshots used for full/delta requests.
@@ -1299,10 +2008,14 @@ pub struct PhpLspBackend {
     framework_string_key_cache: Arc<Mutex<FrameworkStringKeyCache>>,
     /// Bounded cache for disk-backed Twig render-context scans.
     twig_context_disk_cache: Arc<Mutex<TwigContextDiskCache>>,
+    /// Bounded cache of parsed trees for recently touched closed files.
+    closed_file_trees: Arc<Mutex<ClosedFileTreeCache>>,
     /// Parsed Composer vendor metadata keyed by vendor directory.
     vendor_autoload_cache: Arc<Mutex<VendorAutoloadCache>>,
     /// Bounded set of lazy-indexed vendor files currently kept in the symbol index.
     vendor_file_lru: Arc<Mutex<VendorFileLru>>,
+    /// Parsed PHAR manifests keyed by archive path.
+    phar_manifest_cache: Arc<Mutex<PharManifestCache>>,
 }
 
 impl PhpLspBackend {
@@ -1314,48 +2027,92 @@ impl PhpLspBackend {
             document_versions: Arc::new(DashMap::new()),
             diagnostic_debounce_tasks: Arc::new(Mutex::new(HashMap::new())),
             analyzer_runs: Arc::new(Mutex::new(HashMap::new())),
+            analyzer_diagnostics: Arc::new(Mutex::new(HashMap::new())),
             formatter_runs: Arc::new(Mutex::new(HashMap::new())),
             indexing_run: Arc::new(Mutex::new(None)),
+            indexing_progress_cancellations: Arc::new(Mutex::new(HashMap::new())),
+            indexing_timings: Arc::new(Mutex::new(IndexingTimings::default())),
             index: Arc::new(WorkspaceIndex::new()),
-            workspace_root: Mutex::new(None),
+            config_snapshot: ConfigSnapshotCell::default(),
             workspace_roots: Mutex::new(Vec::new()),
-            namespace_map: Mutex::new(None),
             workspace_configs: Mutex::new(Vec::new()),
-            trace_level: Mutex::new(TraceValue::Off),
             client_settings: Mutex::new(serde_json::json!({})),
-            stubs_path: Mutex::new(None),
             php_version: Mutex::new(PhpVersion::DEFAULT),
             diagnostics_mode: Mutex::new(DiagnosticsMode::default()),
             diagnostic_severity: Mutex::new(DiagnosticSeverityConfig::default()),
             diagnostic_budget: Mutex::new(DiagnosticBudgetConfig::default()),
+            diagnostic_schedule: Mutex::new(DiagnosticScheduleConfig::default()),
+            diagnostic_baseline: Mutex::new(None),
             phpstan_config: Mutex::new(PhpStanConfig::default()),
             psalm_config: Mutex::new(PsalmConfig::default()),
+            phpcs_config: Mutex::new(PhpcsConfig::default()),
+            runtime_stubs_config: Mutex::new(RuntimeStubsConfig::default()),
             analyzer_code_actions: Mutex::new(AnalyzerCodeActionConfig::default()),
+            completion_snippets: Mutex::new(true),
+            completion_max_items: Mutex::new(DEFAULT_COMPLETION_MAX_ITEMS),
+            completion_call_snippets: Mutex::new(false),
+            new_file_scaffold: Mutex::new(NewFileScaffoldConfig::default()),
             composer_enabled: Mutex::new(true),
-            index_vendor: Mutex::new(true),
+            vendor_indexing: Mutex::new(VendorIndexing::default()),
             include_paths: Mutex::new(Vec::new()),
             exclude_paths: Mutex::new(Vec::new()),
+            respect_gitignore: Mutex::new(false),
+            php_file_extensions: Mutex::new(default_php_file_extensions()),
             stub_extensions: Mutex::new(None),
             log_level: Mutex::new("info".to_string()),
             work_done_progress_supported: Mutex::new(false),
+            rename_file_operation_supported: Mutex::new(false),
+            change_annotation_supported: Mutex::new(false),
+            apply_edit_supported: Mutex::new(false),
+            prepare_rename_default_behavior_supported: Mutex::new(false),
             formatting_config: Mutex::new(FormattingConfig::default()),
             semantic_tokens_cache: Arc::new(Mutex::new(SemanticTokensCache::default())),
             framework_string_key_cache: Arc::new(Mutex::new(FrameworkStringKeyCache::default())),
             twig_context_disk_cache: Arc::new(Mutex::new(TwigContextDiskCache::default())),
+            closed_file_trees: Arc::new(Mutex::new(ClosedFileTreeCache::default())),
             vendor_autoload_cache: Arc::new(Mutex::new(VendorAutoloadCache::default())),
             vendor_file_lru: Arc::new(Mutex::new(VendorFileLru::default())),
+            phar_manifest_cache: Arc::new(Mutex::new(PharManifestCache::default())),
         }
     }
 
+    /// Build the LSP service with php-lsp custom requests registered.
+    pub fn service() -> (tower_lsp::LspService<Self>, tower_lsp::ClientSocket) {
+        tower_lsp::LspService::build(Self::new)
+            .custom_method(
+                lsp::capabilities::CAPABILITIES_METHOD,
+                Self::capabilities_report,
+            )
+            .custom_method(
+                lsp::references::REFERENCES_METHOD,
+                Self::references_with_kinds,
+            )
+            .custom_method(
+                lsp::index_stats::INDEX_STATS_METHOD,
+                Self::index_stats_report,
+            )
+            .custom_method(
+                lsp::progress::WORK_DONE_PROGRESS_CANCEL_METHOD,
+                Self::work_done_progress_cancel,
+            )
+            .custom_method(indexing::phar::PHAR_CONTENT_METHOD, Self::phar_content)
+            .finish()
+    }
+
     /// Log a message to the client if trace level is verbose.
     async fn log_trace(&self, message: &str) {
-        let level = *self.trace_level.lock().await;
+        let level = self.config_snapshot.load().trace_level;
         if level == TraceValue::Verbose {
             tracing::trace!("{}", message);
             self.client.log_message(MessageType::LOG, message).await;
         }
     }
 
+    /// Column unit of LSP positions exchanged with this client.
+    fn position_encoding(&self) -> PositionEncoding {
+        self.config_snapshot.load().position_encoding
+    }
+
     fn current_document_version(&self, uri_str: &str) -> Option<i32> {
         self.document_versions.get(uri_str).map(|version| *version)
     }
@@ -1382,6 +2139,29 @@ impl PhpLspBackend {
             &self.twig_context_disk_cache,
         )
         .await;
+        self.closed_file_trees.lock().await.clear();
+    }
+
+    /// Parse `source` for a file that is not open, reusing the cached tree
+    /// when the same source was parsed recently.
+    pub(crate) async fn closed_file_tree(
+        &self,
+        uri: &str,
+        source: String,
+    ) -> Option<Arc<ClosedFileTree>> {
+        if let Some(cached) = self.closed_file_trees.lock().await.get(uri, &source) {
+            return Some(cached);
+        }
+
+        let mut parser = FileParser::new();
+        parser.parse_full(&source);
+        let tree = parser.tree()?.clone();
+        let entry = Arc::new(ClosedFileTree { source, tree });
+        self.closed_file_trees
+            .lock()
+            .await
+            .insert(uri.to_string(), entry.clone());
+        Some(entry)
     }
 
     async fn invalidate_twig_context_disk_cache_for_source_uri(&self, source_uri: &str) {
@@ -1434,6 +2214,11 @@ impl PhpLspBackend {
         }
     }
 
+    async fn clear_analyzer_diagnostics(&self, uri_str: &str) {
+        self.cancel_analyzer_run(uri_str).await;
+        self.analyzer_diagnostics.lock().await.remove(uri_str);
+    }
+
     async fn start_formatter_run(&self, uri_str: &str) -> OperationCancellationToken {
         let token = OperationCancellationToken::new();
         if let Some(previous) = self
@@ -1473,17 +2258,21 @@ impl PhpLspBackend {
 
     async fn schedule_fast_diagnostics(&self, uri: Uri, version: i32) {
         let uri_str = uri.as_str().to_string();
+        let encoding = self.position_encoding();
         let client = self.client.clone();
         let open_files = self.open_files.clone();
         let template_documents = self.template_documents.clone();
         let document_versions = self.document_versions.clone();
         let index = self.index.clone();
         let indexing_run = self.indexing_run.clone();
-        let diagnostics_mode = *self.diagnostics_mode.lock().await;
+        let analyzer_diagnostics = self.analyzer_diagnostics.clone();
+        let schedule = *self.diagnostic_schedule.lock().await;
+        let diagnostics_mode = schedule.change_mode(*self.diagnostics_mode.lock().await);
         let diagnostic_severity = *self.diagnostic_severity.lock().await;
         let diagnostic_budget = *self.diagnostic_budget.lock().await;
+        let diagnostic_baseline = self.diagnostic_baseline.lock().await.clone();
         let php_version = *self.php_version.lock().await;
-        let debounce = Duration::from_millis(DID_CHANGE_DIAGNOSTICS_DEBOUNCE_MS);
+        let debounce = Duration::from_millis(schedule.debounce_ms);
         let task_uri_str = uri_str.clone();
 
         let handle = tokio::spawn(async move {
@@ -1501,6 +2290,7 @@ impl PhpLspBackend {
                 severity: diagnostic_severity,
                 budget: diagnostic_budget,
                 php_version,
+                encoding,
             };
             let template_document = template_documents
                 .get(&task_uri_str)
@@ -1517,6 +2307,7 @@ impl PhpLspBackend {
                 diagnostics = template.map_diagnostics_to_original(
                     diagnostics,
                     diagnostics_config.mode == DiagnosticsMode::Off,
+                    encoding,
                 );
             }
 
@@ -1537,6 +2328,7 @@ impl PhpLspBackend {
                         diagnostics = template.map_diagnostics_to_original(
                             diagnostics,
                             diagnostics_config.mode == DiagnosticsMode::Off,
+                            encoding,
                         );
                     }
                 }
@@ -1545,6 +2337,15 @@ impl PhpLspBackend {
                     return;
                 }
 
+                if template_document.is_none()
+                    && diagnostics_config.mode == DiagnosticsMode::BasicSemantic
+                    && !has_syntax_error_diagnostics(&diagnostics)
+                {
+                    if let Some(external) = analyzer_diagnostics.lock().await.get(&task_uri_str) {
+                        diagnostics.extend(external.iter().cloned());
+                    }
+                }
+
                 let publish_started = Instant::now();
                 let publish_span = tracing::debug_span!(
                     "diagnostics.publish",
@@ -1562,6 +2363,11 @@ impl PhpLspBackend {
                     {
                         return;
                     }
+                    let diagnostics = filter_baselined_diagnostics(
+                        diagnostic_baseline.as_deref(),
+                        &task_uri_str,
+                        diagnostics,
+                    );
                     client
                         .publish_diagnostics(uri, diagnostics, Some(version))
                         .await;
@@ -1632,6 +2438,19 @@ impl PhpLspBackend {
             }
         }
 
+        if let Some(raw_baseline) = settings_string(
+            settings,
+            "diagnosticsBaseline",
+            &["diagnostics", "baseline"],
+        ) {
+            let next = self.load_diagnostic_baseline(raw_baseline).await;
+            let mut diagnostic_baseline = self.diagnostic_baseline.lock().await;
+            if *diagnostic_baseline != next {
+                *diagnostic_baseline = next;
+                applied.diagnostics_changed = true;
+            }
+        }
+
         let raw_member_type_node_budget = diagnostic_member_type_node_budget_setting(settings);
         let partial_analysis_diagnostic = settings_bool(
             settings,
@@ -1662,6 +2481,26 @@ impl PhpLspBackend {
             }
         }
 
+        let debounce_ms = settings_u64(
+            settings,
+            "diagnosticsDebounceMs",
+            &["diagnostics", "debounceMs"],
+        );
+        let semantic_on_save = settings_bool(
+            settings,
+            "diagnosticsSemanticOnSave",
+            &["diagnostics", "semanticOnSave"],
+        );
+        if debounce_ms.is_some() || semantic_on_save.is_some() {
+            let mut schedule = self.diagnostic_schedule.lock().await;
+            if let Some(debounce_ms) = debounce_ms {
+                schedule.debounce_ms = debounce_ms;
+            }
+            if let Some(enabled) = semantic_on_save {
+                schedule.semantic_on_save = enabled;
+            }
+        }
+
         if let Some(enabled) = settings_bool(settings, "composerEnabled", &["composer", "enabled"])
         {
             let mut composer_enabled = self.composer_enabled.lock().await;
@@ -1671,20 +2510,29 @@ impl PhpLspBackend {
             }
         }
 
-        if let Some(enabled) = settings_bool(settings, "indexVendor", &["indexVendor"]) {
-            let changed = {
-                let mut index_vendor = self.index_vendor.lock().await;
-                if *index_vendor != enabled {
-                    *index_vendor = enabled;
-                    true
-                } else {
-                    false
-                }
+        let mut next_vendor_indexing = settings_bool(settings, "indexVendor", &["indexVendor"])
+            .map(VendorIndexing::from_index_vendor);
+        if let Some(raw_vendor_indexing) =
+            settings_string(settings, "vendorIndexing", &["vendorIndexing"])
+        {
+            if let Some(parsed) = VendorIndexing::parse(raw_vendor_indexing) {
+                next_vendor_indexing = Some(parsed);
+            } else {
+                tracing::warn!("Ignoring invalid vendorIndexing: {}", raw_vendor_indexing);
+            }
+        }
+        if let Some(next) = next_vendor_indexing {
+            let previous = {
+                let mut vendor_indexing = self.vendor_indexing.lock().await;
+                std::mem::replace(&mut *vendor_indexing, next)
             };
-            if changed {
+            if previous != next {
                 applied.indexing_changed = true;
-                if !enabled {
+                // Turning vendor indexing off, or leaving full mode, drops the
+                // indexed vendor files; the reindex preloads what lazy mode needs.
+                if next == VendorIndexing::Off || previous == VendorIndexing::Full {
                     self.vendor_autoload_cache.lock().await.clear();
+                    self.phar_manifest_cache.lock().await.clear();
                     let evicted = self.vendor_file_lru.lock().await.clear();
                     for uri in evicted {
                         self.index.remove_file(&uri);
@@ -1713,8 +2561,32 @@ impl PhpLspBackend {
             }
         }
 
-        let next_stub_extensions =
-            settings_string_array(settings, "stubExtensions", &["stubs", "extensions"]);
+        if let Some(extensions) =
+            settings_string_array(settings, "fileExtensions", &["fileExtensions"])
+        {
+            let extensions = normalize_php_file_extensions(extensions);
+            let mut php_file_extensions = self.php_file_extensions.lock().await;
+            if *php_file_extensions != extensions {
+                *php_file_extensions = extensions;
+                applied.indexing_changed = true;
+            }
+        }
+
+        if let Some(enabled) = settings_bool(settings, "respectGitignore", &["respectGitignore"]) {
+            let mut respect_gitignore = self.respect_gitignore.lock().await;
+            if *respect_gitignore != enabled {
+                *respect_gitignore = enabled;
+                applied.indexing_changed = true;
+            }
+        }
+
+        let next_stub_extensions = configured_stub_extensions(
+            settings_string_array(settings, "stubExtensions", &["stubs", "extensions"]),
+            settings_string_array(settings, "stubExtensionsEnable", &["stubs", "enable"])
+                .unwrap_or_default(),
+            settings_string_array(settings, "stubExtensionsDisable", &["stubs", "disable"])
+                .unwrap_or_default(),
+        );
         {
             let mut stub_extensions = self.stub_extensions.lock().await;
             if *stub_extensions != next_stub_extensions {
@@ -1723,6 +2595,48 @@ impl PhpLspBackend {
             }
         }
 
+        let runtime_stubs_enabled = settings_bool(
+            settings,
+            "runtimeStubsEnabled",
+            &["runtimeStubs", "enabled"],
+        );
+        let runtime_stubs_php_executable = settings_string(
+            settings,
+            "runtimeStubsPhpExecutable",
+            &["runtimeStubs", "phpExecutable"],
+        );
+        let runtime_stubs_timeout_ms = settings_u64(
+            settings,
+            "runtimeStubsTimeoutMs",
+            &["runtimeStubs", "timeoutMs"],
+        );
+        if runtime_stubs_enabled.is_some()
+            || runtime_stubs_php_executable.is_some()
+            || runtime_stubs_timeout_ms.is_some()
+        {
+            let current = self.runtime_stubs_config.lock().await.clone();
+            let mut next_config = current.clone();
+            if let Some(enabled) = runtime_stubs_enabled {
+                next_config.enabled = enabled;
+            }
+            if let Some(php_executable) = runtime_stubs_php_executable {
+                let php_executable = php_executable.trim();
+                if php_executable.is_empty() {
+                    next_config.php_executable = RuntimeStubsConfig::default().php_executable;
+                } else {
+                    next_config.php_executable = php_executable.to_string();
+                }
+            }
+            if let Some(timeout_ms) = runtime_stubs_timeout_ms {
+                next_config.timeout_ms = timeout_ms.max(1_000);
+            }
+
+            if next_config != current {
+                *self.runtime_stubs_config.lock().await = next_config;
+                applied.stubs_changed = true;
+            }
+        }
+
         if let Some(log_level) = settings_string(settings, "logLevel", &["logLevel"]) {
             *self.log_level.lock().await = log_level.trim().to_ascii_lowercase();
         }
@@ -1737,9 +2651,12 @@ impl PhpLspBackend {
             } else {
                 Some(PathBuf::from(stubs_path))
             };
-            let mut current_path = self.stubs_path.lock().await;
-            if *current_path != next_path {
-                *current_path = next_path;
+            let changed = self.config_snapshot.update(|config| {
+                let changed = config.stubs_path != next_path;
+                config.stubs_path = next_path;
+                changed
+            });
+            if changed {
                 applied.stubs_changed = true;
             }
         }
@@ -1826,7 +2743,11 @@ impl PhpLspBackend {
             }
         }
 
-        let psalm_enabled = settings_bool(settings, "psalmEnabled", &["psalm", "enabled"]);
+        let raw_psalm_enabled = settings_value(settings, "psalmEnabled", &["psalm", "enabled"]);
+        let psalm_enabled = raw_psalm_enabled.and_then(AnalyzerActivation::parse);
+        if let Some(raw) = raw_psalm_enabled.filter(|_| psalm_enabled.is_none()) {
+            tracing::warn!("Ignoring invalid psalm.enabled setting: {raw}");
+        }
         let psalm_command = settings_string(settings, "psalmCommand", &["psalm", "command"]);
         let psalm_timeout_ms = settings_u64(settings, "psalmTimeoutMs", &["psalm", "timeoutMs"]);
 
@@ -1854,6 +2775,48 @@ impl PhpLspBackend {
             }
         }
 
+        let phpcs_enabled = settings_bool(settings, "phpcsEnabled", &["phpcs", "enabled"]);
+        let phpcs_command = settings_string(settings, "phpcsCommand", &["phpcs", "command"]);
+        let phpcs_fix_command =
+            settings_string(settings, "phpcsFixCommand", &["phpcs", "fixCommand"]);
+        let phpcs_timeout_ms = settings_u64(settings, "phpcsTimeoutMs", &["phpcs", "timeoutMs"]);
+
+        if phpcs_enabled.is_some()
+            || phpcs_command.is_some()
+            || phpcs_fix_command.is_some()
+            || phpcs_timeout_ms.is_some()
+        {
+            let current = self.phpcs_config.lock().await.clone();
+            let mut next_config = current.clone();
+            if let Some(enabled) = phpcs_enabled {
+                next_config.enabled = enabled;
+            }
+            if let Some(command) = phpcs_command {
+                let command = command.trim();
+                if command.is_empty() {
+                    next_config.command = PhpcsConfig::default().command;
+                } else {
+                    next_config.command = command.to_string();
+                }
+            }
+            if let Some(command) = phpcs_fix_command {
+                let command = command.trim();
+                if command.is_empty() {
+                    next_config.fix_command = PhpcsConfig::default().fix_command;
+                } else {
+                    next_config.fix_command = command.to_string();
+                }
+            }
+            if let Some(timeout_ms) = phpcs_timeout_ms {
+                next_config.timeout_ms = timeout_ms.max(1_000);
+            }
+
+            if next_config != current {
+                *self.phpcs_config.lock().await = next_config;
+                applied.diagnostics_changed = true;
+            }
+        }
+
         if let Some(enabled) = settings_bool(
             settings,
             "analyzerCodeActionsEnabled",
@@ -1866,9 +2829,81 @@ impl PhpLspBackend {
             }
         }
 
+        if let Some(enabled) =
+            settings_bool(settings, "completionSnippets", &["completion", "snippets"])
+        {
+            *self.completion_snippets.lock().await = enabled;
+        }
+
+        if let Some(enabled) = settings_bool(
+            settings,
+            "completionCallSnippets",
+            &["completion", "callSnippets"],
+        ) {
+            *self.completion_call_snippets.lock().await = enabled;
+        }
+
+        if let Some(max_items) =
+            settings_u64(settings, "completionMaxItems", &["completion", "maxItems"])
+        {
+            *self.completion_max_items.lock().await = (max_items as usize).max(1);
+        }
+
+        {
+            let mut new_file_scaffold = self.new_file_scaffold.lock().await;
+            if let Some(enabled) =
+                settings_bool(settings, "newFilesScaffold", &["newFiles", "scaffold"])
+            {
+                new_file_scaffold.enabled = enabled;
+            }
+            if let Some(strict_types) = settings_bool(
+                settings,
+                "newFilesStrictTypes",
+                &["newFiles", "strictTypes"],
+            ) {
+                new_file_scaffold.strict_types = strict_types;
+            }
+        }
+
         applied
     }
 
+    /// Load the configured diagnostics baseline, resolving relative paths
+    /// against the first workspace root. An empty path disables the baseline.
+    async fn load_diagnostic_baseline(&self, raw_path: &str) -> Option<Arc<DiagnosticBaseline>> {
+        let raw_path = raw_path.trim();
+        if raw_path.is_empty() {
+            return None;
+        }
+        let path = PathBuf::from(raw_path);
+        let path = if path.is_absolute() {
+            path
+        } else {
+            match self.config_snapshot.load().workspace_root.clone() {
+                Some(root) => root.join(path),
+                None => path,
+            }
+        };
+        let label = path.display().to_string();
+        match run_file_io_blocking("diagnostics baseline load", label, move || {
+            DiagnosticBaseline::load(&path)
+        })
+        .await
+        {
+            Ok(Ok(baseline)) => {
+                tracing::info!("Loaded {} baselined diagnostics", baseline.len());
+                Some(Arc::new(baseline))
+            }
+            Ok(Err(message)) | Err(message) => {
+                tracing::warn!("Ignoring diagnostics baseline: {}", message);
+                self.client
+                    .log_message(MessageType::WARNING, format!("php-lsp: {message}"))
+                    .await;
+                None
+            }
+        }
+    }
+
     async fn apply_effective_configuration_settings(
         &self,
         client_settings: &serde_json::Value,
@@ -1896,7 +2931,8 @@ impl PhpLspBackend {
             self.reload_configured_stubs().await;
         }
         if applied.indexing_changed {
-            self.reindex_workspaces().await;
+            self.reindex_workspaces("after indexing configuration change")
+                .await;
         }
         if applied.diagnostics_changed || applied.stubs_changed {
             self.republish_open_diagnostics().await;
@@ -1913,12 +2949,12 @@ impl PhpLspBackend {
     }
 
     async fn reload_configured_stubs(&self) {
-        let Some(root) = self.workspace_root.lock().await.clone() else {
+        let Some(root) = self.config_snapshot.load().workspace_root.clone() else {
             return;
         };
         let root_label = root.display().to_string();
         let index = self.index.clone();
-        let client_stubs_path = self.stubs_path.lock().await.clone();
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
         let stub_extensions = self.stub_extensions.lock().await.clone();
         let php_version = *self.php_version.lock().await;
 
@@ -1932,10 +2968,12 @@ impl PhpLspBackend {
         )
         .await;
 
+        let load_root = root.clone();
+        let stubs_started_at = Instant::now();
         let loaded = tokio::task::spawn_blocking(move || {
             load_configured_stubs(
                 &index,
-                &root,
+                &load_root,
                 client_stubs_path,
                 stub_extensions,
                 php_version,
@@ -1943,7 +2981,9 @@ impl PhpLspBackend {
             )
         })
         .await
-        .unwrap_or(0);
+        .unwrap_or(0)
+            + self.load_runtime_stubs(root, php_version).await;
+        self.record_stub_load(loaded, stubs_started_at).await;
 
         send_indexing_status(
             &self.client,
@@ -1964,7 +3004,10 @@ impl PhpLspBackend {
             .await;
     }
 
-    async fn reindex_workspaces(&self) {
+    /// Drop indexed workspace files and rescan every root in the background.
+    /// `reason` completes the "reindexing workspace ..." log line.
+    async fn reindex_workspaces(&self, reason: &str) {
+        let encoding = self.position_encoding();
         let roots = self.workspace_roots.lock().await.clone();
         if roots.is_empty() {
             return;
@@ -1980,22 +3023,24 @@ impl PhpLspBackend {
         let effective_roots: Vec<PathBuf> =
             configs.iter().map(|config| config.root.clone()).collect();
 
-        if let Some(first_root) = effective_roots.first() {
-            *self.workspace_root.lock().await = Some(first_root.clone());
-        }
+        self.config_snapshot.update(|config| {
+            if let Some(first_root) = effective_roots.first() {
+                config.workspace_root = Some(first_root.clone());
+            }
+            config.namespace_map = configs
+                .iter()
+                .find_map(|config| config.namespace_map.clone());
+        });
         *self.workspace_roots.lock().await = effective_roots.clone();
         *self.workspace_configs.lock().await = configs.clone();
-        *self.namespace_map.lock().await = configs
-            .iter()
-            .find_map(|config| config.namespace_map.clone());
 
         let removed = remove_indexed_file_symbols(&self.index, &effective_roots);
         self.client
             .log_message(
                 MessageType::INFO,
                 format!(
-                    "php-lsp: reindexing workspace after indexing configuration change (removed {} indexed files)",
-                    removed
+                    "php-lsp: reindexing workspace {} (removed {} indexed files)",
+                    reason, removed
                 ),
             )
             .await;
@@ -2012,21 +3057,27 @@ impl PhpLspBackend {
         let diagnostics_mode = *self.diagnostics_mode.lock().await;
         let diagnostic_severity = *self.diagnostic_severity.lock().await;
         let diagnostic_budget = *self.diagnostic_budget.lock().await;
+        let diagnostic_baseline = self.diagnostic_baseline.lock().await.clone();
         let php_version = *self.php_version.lock().await;
         let diagnostics_config = DiagnosticsRuntimeConfig {
             mode: diagnostics_mode,
             severity: diagnostic_severity,
             budget: diagnostic_budget,
             php_version,
+            encoding,
         };
-        let index_vendor = *self.index_vendor.lock().await;
+        let vendor_indexing = *self.vendor_indexing.lock().await;
+        let index_vendor = vendor_indexing.is_enabled();
         let vendor_autoload_cache = self.vendor_autoload_cache.clone();
         let vendor_file_lru = self.vendor_file_lru.clone();
+        let phar_manifest_cache = self.phar_manifest_cache.clone();
         let work_done_progress_supported = *self.work_done_progress_supported.lock().await;
         let include_paths = self.include_paths.lock().await.clone();
         let exclude_paths = self.exclude_paths.lock().await.clone();
+        let respect_gitignore = *self.respect_gitignore.lock().await;
+        let file_extensions = self.php_file_extensions.lock().await.clone();
         let stub_extensions = self.stub_extensions.lock().await.clone();
-        let client_stubs_path = self.stubs_path.lock().await.clone();
+        let client_stubs_path = self.config_snapshot.load().stubs_path.clone();
         let cache_config = workspace_index_cache_config(
             configs.first().map(|config| config.root.as_path()),
             php_version,
@@ -2038,20 +3089,28 @@ impl PhpLspBackend {
         let indexing_options = WorkspaceIndexingOptions {
             include_paths,
             exclude_paths,
+            respect_gitignore,
+            file_extensions,
             cache_config,
             work_done_progress_supported,
+            progress_cancellations: self.indexing_progress_cancellations.clone(),
         };
         let vendor_lazy_context = VendorLazyIndexContext {
             index: index.clone(),
             workspace_configs: configs.clone(),
             exclude_paths: indexing_options.exclude_paths.clone(),
+            file_extensions: indexing_options.file_extensions.clone(),
             php_version,
             index_vendor,
             vendor_autoload_cache: vendor_autoload_cache.clone(),
             vendor_file_lru: vendor_file_lru.clone(),
+            phar_manifest_cache: phar_manifest_cache.clone(),
+            encoding,
         };
         let indexing_run_state = self.indexing_run.clone();
         let indexing_token = self.start_indexing_run().await;
+        let indexing_timings = self.indexing_timings.clone();
+        let indexing_started_at = Instant::now();
         tokio::spawn(async move {
             for config in &configs {
                 if finish_indexing_run_if_cancelled(&indexing_run_state, &indexing_token).await {
@@ -2064,6 +3123,7 @@ impl PhpLspBackend {
                     config.namespace_map.as_ref(),
                     &indexing_options,
                     &indexing_token,
+                    encoding,
                 )
                 .await
                 {
@@ -2095,9 +3155,24 @@ impl PhpLspBackend {
                         index.clone(),
                         &config.root,
                         &indexing_options.exclude_paths,
+                        &indexing_options.file_extensions,
                         php_version,
                         &vendor_autoload_cache,
                         &vendor_file_lru,
+                        encoding,
+                    )
+                    .await;
+                }
+                if vendor_indexing == VendorIndexing::Full {
+                    index_vendor_sources(
+                        index.clone(),
+                        &config.root,
+                        &indexing_options.exclude_paths,
+                        &indexing_options.file_extensions,
+                        php_version,
+                        &vendor_autoload_cache,
+                        &indexing_token,
+                        encoding,
                     )
                     .await;
                 }
@@ -2106,6 +3181,7 @@ impl PhpLspBackend {
             if finish_indexing_run_if_cancelled(&indexing_run_state, &indexing_token).await {
                 return;
             }
+            indexing_timings.lock().await.workspace_ms = Some(elapsed_ms(indexing_started_at));
             finish_indexing_run_state(&indexing_run_state, &indexing_token).await;
 
             let workspace_roots: Vec<PathBuf> =
@@ -2118,6 +3194,7 @@ impl PhpLspBackend {
                 &workspace_roots,
                 &twig_context_disk_cache,
                 &semantic_tokens_cache,
+                encoding,
             )
             .await;
             if finish_indexing_run_if_cancelled(&indexing_run_state, &indexing_token).await {
@@ -2157,6 +3234,7 @@ impl PhpLspBackend {
                         diags = template.map_diagnostics_to_original(
                             diags,
                             diagnostics_config.mode == DiagnosticsMode::Off,
+                            encoding,
                         );
                     } else if diagnostics_config.mode == DiagnosticsMode::BasicSemantic
                         && index_vendor
@@ -2180,6 +3258,11 @@ impl PhpLspBackend {
                             version = ?version,
                             duration_ms = tracing::field::Empty,
                         );
+                        let diags = filter_baselined_diagnostics(
+                            diagnostic_baseline.as_deref(),
+                            &uri_str,
+                            diags,
+                        );
                         async {
                             reindex_client
                                 .publish_diagnostics(uri, diags, version)
@@ -2210,7 +3293,7 @@ impl PhpLspBackend {
     async fn current_workspace_roots(&self) -> Vec<PathBuf> {
         let mut roots = self.workspace_roots.lock().await.clone();
         if roots.is_empty() {
-            if let Some(root) = self.workspace_root.lock().await.clone() {
+            if let Some(root) = self.config_snapshot.load().workspace_root.clone() {
                 roots.push(root);
             }
         }
@@ -2228,7 +3311,31 @@ impl PhpLspBackend {
 
     async fn invalidate_composer_metadata(&self, path: &Path, reindex_workspace: bool) {
         self.invalidate_request_fs_caches().await;
+
+        // A vendor-only change such as `composer update foo/bar` keeps the
+        // lazily indexed files of packages whose installed.json entry did not
+        // change.
+        if !reindex_workspace && *self.vendor_indexing.lock().await != VendorIndexing::Full {
+            if let Some(removed) = self.evict_changed_vendor_packages().await {
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!(
+                            "php-lsp: Composer metadata changed at {}; evicted {} indexed file(s) from changed vendor packages",
+                            path.display(),
+                            removed
+                        ),
+                    )
+                    .await;
+                self.reload_vendor_entrypoints().await;
+                self.refresh_open_twig_contexts().await;
+                self.republish_open_diagnostics().await;
+                return;
+            }
+        }
+
         self.vendor_autoload_cache.lock().await.clear();
+        self.phar_manifest_cache.lock().await.clear();
         let evicted = self.vendor_file_lru.lock().await.clear();
         for uri in evicted {
             self.index.remove_file(&uri);
@@ -2247,14 +3354,44 @@ impl PhpLspBackend {
             )
             .await;
 
-        if reindex_workspace {
-            self.reindex_workspaces().await;
+        // Full vendor indexing re-reads every dependency, which belongs in the
+        // cancellable background reindex rather than this notification.
+        if reindex_workspace || *self.vendor_indexing.lock().await == VendorIndexing::Full {
+            self.reindex_workspaces("after Composer metadata change")
+                .await;
         } else {
+            self.reload_vendor_entrypoints().await;
             self.refresh_open_twig_contexts().await;
             self.republish_open_diagnostics().await;
         }
     }
 
+    /// Re-run the vendor `autoload.files` preload for every workspace root so
+    /// helpers from newly required packages resolve without a restart.
+    async fn reload_vendor_entrypoints(&self) {
+        let encoding = self.position_encoding();
+        if !self.vendor_indexing.lock().await.is_enabled() {
+            return;
+        }
+        let configs = self.workspace_configs.lock().await.clone();
+        let exclude_paths = self.exclude_paths.lock().await.clone();
+        let file_extensions = self.php_file_extensions.lock().await.clone();
+        let php_version = *self.php_version.lock().await;
+        for config in &configs {
+            preload_vendor_entrypoints(
+                self.index.clone(),
+                &config.root,
+                &exclude_paths,
+                &file_extensions,
+                php_version,
+                &self.vendor_autoload_cache,
+                &self.vendor_file_lru,
+                encoding,
+            )
+            .await;
+        }
+    }
+
     async fn workspace_root_for_uri(&self, uri_str: &str) -> Option<PathBuf> {
         let roots = self.workspace_roots.lock().await.clone();
         if let Some(path) = uri_to_path(uri_str) {
@@ -2271,7 +3408,7 @@ impl PhpLspBackend {
             return Some(root);
         }
 
-        self.workspace_root.lock().await.clone()
+        self.config_snapshot.load().workspace_root.clone()
     }
 }
 
@@ -2324,8 +3461,8 @@ impl LanguageServer for PhpLspBackend {
         self.lsp_did_create_files(params).await
     }
 
-    async fn will_rename_files(&self, _params: RenameFilesParams) -> Result<Option<WorkspaceEdit>> {
-        self.lsp_will_rename_files(_params).await
+    async fn will_rename_files(&self, params: RenameFilesParams) -> Result<Option<WorkspaceEdit>> {
+        self.lsp_will_rename_files(params).await
     }
 
     async fn did_rename_files(&self, params: RenameFilesParams) {
@@ -2519,6 +3656,13 @@ impl LanguageServer for PhpLspBackend {
         self.lsp_symbol(params).await
     }
 
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        self.lsp_execute_command(params).await
+    }
+
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
         self.lsp_code_action(params).await
     }
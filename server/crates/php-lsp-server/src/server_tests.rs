@@ -2,7 +2,11 @@ use super::lsp::diagnostics::{
     current_class_fqn_at_range, parse_phpstan_json_diagnostics, parse_psalm_json_diagnostics,
     run_diagnostics_blocking, type_info_accepts_inferred_type, InferredExprType,
 };
-use super::lsp::document_symbols::{workspace_symbol_candidates, workspace_symbol_lsp_range};
+use super::lsp::document_symbols::{
+    document_symbol_tree, workspace_symbol_candidates, workspace_symbol_lsp_range,
+    GLOBAL_CODE_SYMBOL_NAME,
+};
+use super::lsp::references::{scan_reference_batch, ReferenceScanTarget};
 use super::*;
 use php_lsp_types::*;
 use std::cell::Cell;
@@ -18,9 +22,9 @@ fn make_symbol(
 ) -> SymbolInfo {
     SymbolInfo {
         name: name.to_string(),
-        fqn: fqn.to_string(),
+        fqn: fqn.into(),
         kind,
-        uri: "file:///test.php".to_string(),
+        uri: "file:///test.php".into(),
         range,
         selection_range: range,
         visibility: Visibility::Public,
@@ -28,12 +32,14 @@ fn make_symbol(
         attributes: vec![],
         doc_comment: None,
         signature: None,
-        parent_fqn: parent_fqn.map(|s| s.to_string()),
+        value: None,
+        parent_fqn: parent_fqn.map(Into::into),
         extends: vec![],
         implements: vec![],
         traits: vec![],
         templates: vec![],
         template_bindings: vec![],
+        trait_adaptations: vec![],
     }
 }
 
@@ -46,7 +52,7 @@ fn make_symbol_for_uri(
     parent_fqn: Option<&str>,
 ) -> SymbolInfo {
     let mut symbol = make_symbol(name, fqn, kind, range, parent_fqn);
-    symbol.uri = uri.to_string();
+    symbol.uri = uri.into();
     symbol
 }
 
@@ -88,6 +94,42 @@ fn unique_server_temp_dir(name: &str) -> PathBuf {
     dir
 }
 
+#[test]
+fn test_server_status_for_indexing_phase_maps_phases_to_states() {
+    let indexing = server_status_for_indexing_phase(&serde_json::json!({
+        "phase": "indexing",
+        "message": "Indexed 5/10 files",
+        "percentage": 50
+    }))
+    .unwrap();
+    assert_eq!(
+        indexing,
+        serde_json::json!({
+            "state": "indexing",
+            "message": "Indexed 5/10 files",
+            "percentage": 50
+        })
+    );
+
+    let ready = server_status_for_indexing_phase(&serde_json::json!({
+        "phase": "ready",
+        "message": "Indexed 10 PHP files",
+        "percentage": 100
+    }))
+    .unwrap();
+    assert_eq!(
+        ready,
+        serde_json::json!({ "state": "idle", "message": "Indexed 10 PHP files" })
+    );
+
+    let error = server_status_for_indexing_phase(&serde_json::json!({ "phase": "error" }));
+    assert_eq!(error, Some(serde_json::json!({ "state": "error" })));
+    assert_eq!(
+        server_status_for_indexing_phase(&serde_json::json!({ "phase": "starting" })),
+        None
+    );
+}
+
 #[test]
 fn test_current_class_fqn_at_range_uses_innermost_class_like() {
     let file_symbols = FileSymbols {
@@ -423,14 +465,35 @@ fn test_infer_new_expression_type_from_parenthesized_expression() {
         ..Default::default()
     };
 
+    let source = "<?php\n(new TypeGuess(Foo::class));\n";
+    let mut parser = FileParser::new();
+    parser.parse_full(source);
+    let tree = parser.tree().unwrap();
+    let context_node = tree.root_node();
+
     assert_eq!(
-        infer_new_expression_type("(new \\ReflectionClass($v))", &file_symbols).as_deref(),
+        infer_new_expression_type(
+            "(new \\ReflectionClass($v))",
+            &file_symbols,
+            source,
+            context_node
+        )
+        .as_deref(),
         Some("ReflectionClass")
     );
     assert_eq!(
-        infer_new_expression_type("((new TypeGuess(Foo::class)))", &file_symbols).as_deref(),
+        infer_new_expression_type(
+            "((new TypeGuess(Foo::class)))",
+            &file_symbols,
+            source,
+            context_node
+        )
+        .as_deref(),
         Some("Symfony\\Component\\Form\\Guess\\TypeGuess")
     );
+    assert!(
+        infer_new_expression_type("new static()", &file_symbols, source, context_node).is_none()
+    );
 }
 
 #[test]
@@ -651,6 +714,7 @@ fn test_phpdoc_virtual_member_range_points_to_tag_name() {
         params: Vec::new(),
         description: Some("Service slug".to_string()),
         is_static: false,
+        resolved_type: None,
     };
 
     let range = phpdoc_virtual_member_range(source, doc_comment, doc_start, &member)
@@ -748,6 +812,82 @@ fn test_document_symbol_hierarchy() {
     assert_eq!(members.len(), 2); // getUser + $name
 }
 
+#[test]
+fn test_document_symbol_tree_groups_members_closures_and_global_code() {
+    let code = r#"<?php
+namespace App;
+
+enum Status: string {
+    case Active = 'active';
+    case Archived = 'archived';
+}
+
+class Formatter {
+    const PREFIX = '[';
+    const SUFFIX = ']';
+
+    public function label(Status $status): string {
+        $format = fn(string $value): string => ucfirst($value);
+        return $format($status->value);
+    }
+}
+
+$handler = function (int $code) {
+    return $code;
+};
+echo $handler(1);
+"#;
+    let uri = "file:///test/outline.php";
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let file_symbols = parser.file_symbols(uri).unwrap().into_owned();
+
+    let outline = document_symbol_tree(&file_symbols, code, parser.tree(), PositionEncoding::Utf16);
+    let names: Vec<&str> = outline.iter().map(|symbol| symbol.name.as_str()).collect();
+    assert_eq!(names, ["Status", "Formatter", GLOBAL_CODE_SYMBOL_NAME]);
+
+    let enum_members = outline[0].children.as_ref().unwrap();
+    assert_eq!(
+        enum_members.first().map(|symbol| symbol.name.as_str()),
+        Some("Cases")
+    );
+    assert_eq!(enum_members[0].kind, SymbolKind::ENUM_MEMBER);
+    let cases: Vec<&str> = enum_members[0]
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect();
+    assert_eq!(cases, ["Active", "Archived"]);
+
+    let class_members = outline[1].children.as_ref().unwrap();
+    let member_names: Vec<&str> = class_members
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect();
+    assert_eq!(member_names, ["Constants", "label"]);
+    assert_eq!(class_members[0].children.as_ref().unwrap().len(), 2);
+    assert_eq!(class_members[0].range.start.line, 9);
+    assert_eq!(class_members[0].range.end.line, 10);
+
+    let method_closure = &class_members[1].children.as_ref().unwrap()[0];
+    assert_eq!(method_closure.name, "$format");
+    assert_eq!(method_closure.kind, SymbolKind::FUNCTION);
+    assert_eq!(
+        method_closure.detail.as_deref(),
+        Some("fn(string $value): string")
+    );
+
+    let global_code = &outline[2];
+    assert_eq!(global_code.kind, SymbolKind::MODULE);
+    assert_eq!(global_code.range.start.line, 18);
+    assert_eq!(global_code.range.end.line, 21);
+    let handler = &global_code.children.as_ref().unwrap()[0];
+    assert_eq!(handler.name, "$handler");
+    assert_eq!(handler.detail.as_deref(), Some("function(int $code)"));
+}
+
 #[test]
 fn test_workspace_symbol_search() {
     let index = WorkspaceIndex::new();
@@ -793,6 +933,13 @@ fn test_workspace_symbol_search() {
     // Search for "xyz" should find nothing
     let results = index.search("xyz");
     assert!(results.is_empty());
+
+    // Kind filters and namespace prefixes narrow the same search
+    let results = index.search("#function foo");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "helper_foo");
+    let results = index.search("App\\");
+    assert_eq!(results.len(), 3);
 }
 
 #[test]
@@ -849,12 +996,28 @@ fn test_workspace_symbol_candidates_rank_filters_and_members() {
         class_candidates.is_empty(),
         "kind filter should exclude method-only matches"
     );
+
+    let hashed_candidates = workspace_symbol_candidates(&index, "#method build");
+    assert_eq!(hashed_candidates.len(), 1);
+    assert_eq!(hashed_candidates[0].symbol.name, "buildUser");
+
+    let member_candidates = workspace_symbol_candidates(&index, "UserService::bu");
+    assert_eq!(member_candidates.len(), 1);
+    assert_eq!(member_candidates[0].symbol.name, "buildUser");
+    assert!(workspace_symbol_candidates(&index, "UserServiceFactory::bu").is_empty());
+
+    let namespace_candidates = workspace_symbol_candidates(&index, "App\\Service\\");
+    let names: Vec<_> = namespace_candidates
+        .iter()
+        .map(|candidate| candidate.symbol.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["UserService"]);
 }
 
 #[test]
 fn test_workspace_symbol_lsp_range_converts_byte_columns_to_utf16() {
     let source = "<?php\n$привет = 1; class Demo {}\n";
-    let range = workspace_symbol_lsp_range(source, (1, 19, 1, 24));
+    let range = workspace_symbol_lsp_range(source, (1, 19, 1, 24), PositionEncoding::Utf16);
 
     assert_eq!(range.start, Position::new(1, 13));
     assert_eq!(range.end, Position::new(1, 18));
@@ -935,7 +1098,7 @@ fn test_workspace_index_reads_non_utf8_php_lossily() {
     )
     .unwrap();
 
-    let parsed = parse_workspace_file_for_index(file);
+    let parsed = parse_workspace_file_for_index(file, PositionEncoding::Utf16);
 
     assert!(
         parsed.error.is_none(),
@@ -950,6 +1113,70 @@ fn test_workspace_index_reads_non_utf8_php_lossily() {
     std::fs::remove_dir_all(tmp).unwrap();
 }
 
+#[test]
+fn test_workspace_index_strips_bom_and_keeps_latin1_identifiers() {
+    let tmp = std::env::temp_dir().join(format!("php-lsp-bom-index-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let bom_file = tmp.join("Bom.php");
+    std::fs::write(&bom_file, b"\xEF\xBB\xBF<?php class Bom {}\n").unwrap();
+    let latin1_file = tmp.join("Latin1.php");
+    std::fs::write(&latin1_file, b"<?php\nclass Caf\xe9 {}\n").unwrap();
+
+    let bom = parse_workspace_file_for_index(bom_file, PositionEncoding::Utf16);
+    let bom_class = bom
+        .file_symbols
+        .as_ref()
+        .and_then(|symbols| symbols.symbols.iter().find(|sym| sym.fqn == "Bom"))
+        .expect("Bom class indexed");
+    assert_eq!(
+        (bom_class.selection_range.0, bom_class.selection_range.1),
+        (0, "<?php class ".len() as u32),
+        "BOM must not shift line 0 columns"
+    );
+
+    let latin1 = parse_workspace_file_for_index(latin1_file, PositionEncoding::Utf16);
+    assert!(latin1
+        .file_symbols
+        .as_ref()
+        .is_some_and(|symbols| symbols.symbols.iter().any(|sym| sym.fqn == "Café")));
+
+    std::fs::remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn test_workspace_index_reports_binary_php_file_as_error() {
+    let tmp = std::env::temp_dir().join(format!("php-lsp-binary-index-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let file = tmp.join("Archive.php");
+    std::fs::write(&file, b"\x1f\x8b\x08\x00\x00\x00\x00\x00").unwrap();
+
+    let parsed = parse_workspace_file_for_index(file, PositionEncoding::Utf16);
+
+    assert!(parsed.file_symbols.is_none());
+    assert!(
+        parsed
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("binary")),
+        "got: {:?}",
+        parsed.error
+    );
+
+    std::fs::remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn test_find_use_insert_line_skips_shebang() {
+    let code = "#!/usr/bin/env php\n<?php\n\necho new Foo();\n";
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, "file:///bin/tool");
+
+    assert_eq!(find_use_insert_line(code, &symbols), 2);
+}
+
 #[test]
 fn test_workspace_index_parallel_updates_are_safe() {
     let index = Arc::new(WorkspaceIndex::new());
@@ -1060,6 +1287,89 @@ fn test_effective_stub_extensions_distinguishes_defaults_from_explicit_empty() {
     );
 }
 
+#[test]
+fn test_configured_stub_extensions_enable_and_disable_extension_sets() {
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-stub-extension-overrides-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&tmp_root);
+    for extension in ["Core", "oci8", "standard"] {
+        std::fs::create_dir_all(tmp_root.join(extension)).unwrap();
+        std::fs::write(
+            tmp_root.join(extension).join(format!("{extension}.php")),
+            "<?php\n",
+        )
+        .unwrap();
+    }
+    let strings =
+        |values: &[&str]| -> Vec<String> { values.iter().map(|value| value.to_string()).collect() };
+
+    let configured =
+        configured_stub_extensions(None, strings(&["redis"]), strings(&["oci8"])).unwrap();
+    assert_eq!(configured, strings(&["+redis", "-oci8"]));
+    let mut extensions = effective_stub_extensions_for_path(&tmp_root, Some(&configured));
+    extensions.sort();
+    assert_eq!(extensions, strings(&["Core", "redis", "standard"]));
+
+    let explicit = configured_stub_extensions(
+        Some(strings(&["Core", "oci8"])),
+        strings(&["imagick"]),
+        strings(&["OCI8"]),
+    )
+    .unwrap();
+    assert_eq!(explicit, strings(&["Core", "imagick"]));
+    assert_eq!(
+        effective_stub_extensions_for_path(&tmp_root, Some(&explicit)),
+        explicit
+    );
+
+    assert_eq!(
+        configured_stub_extensions(None, Vec::new(), Vec::new()),
+        None
+    );
+    let _ = std::fs::remove_dir_all(&tmp_root);
+}
+
+#[test]
+fn test_load_runtime_stub_sources_indexes_generated_builtins() {
+    let output = br#"{
+        "acme": "<?php\n\nnamespace {\n\nconst ACME_VERSION = '1.2';\n\nfunction acme_ping(string $host, int $port = 6379): bool {}\n\n}\n\nnamespace Acme {\n\nfinal class Client\n{\n    public function send(string $payload): ?int {}\n}\n\n}\n",
+        "Zend OPcache": "<?php\n\nnamespace {\n\nfunction opcache_probe(): bool {}\n\n}\n"
+    }"#;
+    let generated = parse_runtime_stubs_output(output).unwrap();
+    assert_eq!(parse_runtime_stubs_output(b"[]").unwrap(), Vec::new());
+    assert!(parse_runtime_stubs_output(b"PHP Warning: boom").is_err());
+
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-runtime-stubs-{}", std::process::id()));
+    let index = WorkspaceIndex::new();
+    let loaded = load_runtime_stub_sources(
+        &index,
+        &tmp_root,
+        &generated,
+        PhpVersion { major: 8, minor: 2 },
+    );
+
+    assert_eq!(loaded, 2);
+    let function = index
+        .resolve_fqn("acme_ping")
+        .expect("runtime stub function");
+    assert!(function.modifiers.is_builtin);
+    assert_eq!(function.uri, "phpstub://acme/acme.php");
+    assert!(index.resolve_fqn("Acme\\Client").is_some());
+    assert!(index.resolve_fqn("ACME_VERSION").is_some());
+    assert_eq!(
+        index
+            .resolve_fqn("opcache_probe")
+            .map(|symbol| symbol.uri.to_string()),
+        Some("phpstub://Zend_OPcache/Zend_OPcache.php".to_string())
+    );
+    assert!(tmp_root.join("acme/acme.php").is_file());
+
+    let _ = std::fs::remove_dir_all(&tmp_root);
+}
+
 #[test]
 fn test_effective_stub_extensions_for_path_discovers_available_stub_dirs() {
     let stubs_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../data/stubs");
@@ -1134,6 +1444,78 @@ fn test_vendor_file_lru_evicts_old_index_entries() {
     assert!(index.resolve_fqn("Vendor\\Pkg\\Two").is_some());
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_installed_json_change_evicts_only_changed_vendor_packages() {
+    fn write_installed_json(composer_dir: &Path, two_version: &str) {
+        let installed_json = serde_json::json!({
+            "packages": [
+                {
+                    "name": "acme/one",
+                    "version": "1.0.0",
+                    "install-path": "../acme/one",
+                    "autoload": { "psr-4": { "Acme\\One\\": "src/" } }
+                },
+                {
+                    "name": "acme/two",
+                    "version": two_version,
+                    "install-path": "../acme/two",
+                    "autoload": { "psr-4": { "Acme\\Two\\": "src/" } }
+                }
+            ]
+        });
+        std::fs::write(
+            composer_dir.join("installed.json"),
+            serde_json::to_string(&installed_json).unwrap(),
+        )
+        .unwrap();
+    }
+
+    let root = unique_server_temp_dir("vendor-package-eviction");
+    let vendor_dir = root.join("vendor");
+    let composer_dir = vendor_dir.join("composer");
+    let one = vendor_dir.join("acme/one/src/One.php");
+    let two = vendor_dir.join("acme/two/src/Two.php");
+    std::fs::create_dir_all(&composer_dir).unwrap();
+    std::fs::create_dir_all(one.parent().unwrap()).unwrap();
+    std::fs::create_dir_all(two.parent().unwrap()).unwrap();
+    std::fs::write(&one, "<?php\nnamespace Acme\\One;\nclass One {}\n").unwrap();
+    std::fs::write(&two, "<?php\nnamespace Acme\\Two;\nclass Two {}\n").unwrap();
+    write_installed_json(&composer_dir, "2.0.0");
+
+    let (service, _socket) = tower_lsp::LspService::new(PhpLspBackend::new);
+    let backend = service.inner();
+    backend
+        .config_snapshot
+        .update(|config| config.workspace_root = Some(root.clone()));
+    assert!(
+        cached_vendor_autoload_map(&backend.vendor_autoload_cache, &vendor_dir)
+            .await
+            .is_some()
+    );
+    for path in [&one, &two] {
+        let uri = crate::util::uri::path_to_uri(path).unwrap();
+        let code = std::fs::read_to_string(path).unwrap();
+        parse_and_index_php_file(&backend.index, &uri, &code);
+        touch_vendor_file_lru(&backend.index, &backend.vendor_file_lru, path).await;
+    }
+
+    assert_eq!(backend.evict_changed_vendor_packages().await, Some(0));
+    assert!(backend.index.resolve_fqn("Acme\\Two\\Two").is_some());
+
+    write_installed_json(&composer_dir, "2.1.0");
+    assert_eq!(backend.evict_changed_vendor_packages().await, Some(1));
+    assert!(backend.index.resolve_fqn("Acme\\One\\One").is_some());
+    assert!(backend.index.resolve_fqn("Acme\\Two\\Two").is_none());
+    let cached = backend.vendor_autoload_cache.lock().await;
+    let packages = &cached.by_vendor_dir[&vendor_dir].map.packages;
+    assert!(packages
+        .iter()
+        .any(|package| package.name == "acme/two" && package.version == "2.1.0"));
+    drop(cached);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
 #[test]
 fn test_vendor_autoload_map_parses_psr4_and_files() {
     let tmp = std::env::temp_dir().join(format!(
@@ -1184,11 +1566,14 @@ fn test_vendor_autoload_map_parses_psr4_and_files() {
     .unwrap();
 
     let map = parse_vendor_autoload_map(&vendor_dir).unwrap();
-    let paths = resolve_vendor_paths_from_map("Acme\\Library\\Http\\Client", &map).unwrap();
-    let classmap_paths = resolve_vendor_paths_from_map("Acme\\Library\\Metadata\\Test", &map)
-        .expect("classmap paths");
+    let extensions = default_php_file_extensions();
+    let paths =
+        resolve_vendor_paths_from_map("Acme\\Library\\Http\\Client", &map, &extensions).unwrap();
+    let classmap_paths =
+        resolve_vendor_paths_from_map("Acme\\Library\\Metadata\\Test", &map, &extensions)
+            .expect("classmap paths");
     let mixed_psr4_classmap_paths =
-        resolve_vendor_paths_from_map("Acme\\Library\\Legacy\\OddClass", &map)
+        resolve_vendor_paths_from_map("Acme\\Library\\Legacy\\OddClass", &map, &extensions)
             .expect("mixed PSR-4 and classmap paths");
 
     assert!(
@@ -1206,7 +1591,7 @@ fn test_vendor_autoload_map_parses_psr4_and_files() {
         map.files
     );
     assert!(
-        vendor_autoload_file_paths_from_map(&map, &tmp, &[])
+        vendor_autoload_file_paths_from_map(&map, &tmp, &[], &extensions)
             .iter()
             .any(|path| path.to_string_lossy().ends_with("dev-bootstrap.php")),
         "Expected autoload-dev file path, got: {:?}",
@@ -1262,6 +1647,96 @@ async fn test_lazy_index_class_returns_false_when_psr4_file_contains_different_c
     std::fs::remove_dir_all(root).unwrap();
 }
 
+fn apply_lsp_text_edits(source: &str, edits: &[TextEdit]) -> String {
+    let mut edits = edits.to_vec();
+    edits.sort_by_key(|edit| std::cmp::Reverse((edit.range.start, edit.range.end)));
+    let mut result = source.to_string();
+    for edit in edits {
+        let start = crate::util::lsp_text::lsp_position_to_byte(
+            &result,
+            edit.range.start,
+            PositionEncoding::Utf16,
+        )
+        .unwrap();
+        let end = crate::util::lsp_text::lsp_position_to_byte(
+            &result,
+            edit.range.end,
+            PositionEncoding::Utf16,
+        )
+        .unwrap();
+        result.replace_range(start..end, &edit.new_text);
+    }
+    result
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_will_rename_files_moves_namespace_and_rewrites_imports() {
+    let root = unique_server_temp_dir("will-rename-files");
+    let src = root.join("src");
+    std::fs::create_dir_all(src.join("Models")).unwrap();
+    let files = [
+        (
+            "Models/User.php",
+            "<?php\nnamespace App\\Models;\n\nclass User\n{\n    public function latest(): Post\n    {\n        return new Post();\n    }\n}\n",
+        ),
+        (
+            "Models/Post.php",
+            "<?php\nnamespace App\\Models;\n\nclass Post\n{\n    public function author(): User\n    {\n        return new User();\n    }\n}\n",
+        ),
+        (
+            "Service.php",
+            "<?php\nnamespace App;\n\nuse App\\Models\\User;\n\nfunction load(): ?\\App\\Models\\User\n{\n    return User::class === 'App\\Models\\User' ? new User() : null;\n}\n",
+        ),
+    ];
+    let (service, _socket) = tower_lsp::LspService::new(PhpLspBackend::new);
+    let backend = service.inner();
+    *backend.workspace_configs.lock().await = vec![WorkspaceRootConfig {
+        root: root.clone(),
+        namespace_map: Some(NamespaceMap {
+            psr4: vec![("App\\".to_string(), vec![src.clone()])],
+            ..Default::default()
+        }),
+    }];
+    for (relative, code) in files {
+        let path = src.join(relative);
+        std::fs::write(&path, code).unwrap();
+        let uri: Uri = crate::util::uri::path_to_uri(&path)
+            .unwrap()
+            .parse()
+            .unwrap();
+        backend.reindex_php_file(&uri).await;
+    }
+
+    let uri_for = |relative: &str| crate::util::uri::path_to_uri(&src.join(relative)).unwrap();
+    let edit = backend
+        .file_move_namespace_edits(&[FileRename {
+            old_uri: uri_for("Models/User.php"),
+            new_uri: uri_for("Domain/User.php"),
+        }])
+        .await
+        .expect("moving a PSR-4 class should produce edits");
+    let changes = edit.changes.expect("text edits");
+    let edited = |relative: &str, code: &str| {
+        let uri: Uri = uri_for(relative).parse().unwrap();
+        apply_lsp_text_edits(code, changes.get(&uri).map_or(&[][..], Vec::as_slice))
+    };
+
+    assert_eq!(
+        edited("Models/User.php", files[0].1),
+        "<?php\nnamespace App\\Domain;\n\nuse App\\Models\\Post;\n\nclass User\n{\n    public function latest(): Post\n    {\n        return new Post();\n    }\n}\n"
+    );
+    assert_eq!(
+        edited("Models/Post.php", files[1].1),
+        "<?php\nnamespace App\\Models;\n\nuse App\\Domain\\User;\n\nclass Post\n{\n    public function author(): User\n    {\n        return new User();\n    }\n}\n"
+    );
+    assert_eq!(
+        edited("Service.php", files[2].1),
+        "<?php\nnamespace App;\n\nuse App\\Domain\\User;\n\nfunction load(): ?\\App\\Domain\\User\n{\n    return User::class === 'App\\Domain\\User' ? new User() : null;\n}\n"
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_lazy_indexed_vendor_symbol_survives_restart_cache_load() {
     let root = unique_server_temp_dir("lazy-vendor-cache");
@@ -1393,6 +1868,11 @@ fn test_compute_diagnostics_reports_duplicate_workspace_symbols() {
     let symbols2 = extract_file_symbols(parser2.tree().unwrap(), code2, uri2);
     index.update_file(uri1, symbols1);
     index.update_file(uri2, symbols2);
+    parse_and_index_php_file(
+        &index,
+        "file:///three.php",
+        "<?php\nnamespace App;\n\nfinal class Duplicate {}\n",
+    );
 
     let diagnostics = compute_diagnostics(
         uri1,
@@ -1402,12 +1882,45 @@ fn test_compute_diagnostics_reports_duplicate_workspace_symbols() {
         PhpVersion::DEFAULT,
     );
 
-    assert!(
-        diagnostics
-            .iter()
-            .any(|diag| diag.message == "Duplicate symbol: App\\Duplicate"),
-        "Expected duplicate workspace symbol diagnostic, got: {:?}",
-        diagnostics
+    let duplicate = diagnostics
+        .iter()
+        .find(|diag| diag.message == "Duplicate symbol: App\\Duplicate")
+        .unwrap_or_else(|| {
+            panic!("Expected duplicate workspace symbol diagnostic, got: {diagnostics:?}")
+        });
+    assert_eq!(
+        duplicate.code,
+        Some(NumberOrString::String(
+            "php-lsp.duplicateSymbol".to_string()
+        ))
+    );
+    let related: Vec<(&str, Position, &str)> = duplicate
+        .related_information
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|info| {
+            (
+                info.location.uri.as_str(),
+                info.location.range.start,
+                info.message.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        related,
+        vec![
+            (
+                "file:///three.php",
+                Position::new(3, 12),
+                "App\\Duplicate is also declared here"
+            ),
+            (
+                uri2,
+                Position::new(2, 6),
+                "App\\Duplicate is also declared here"
+            ),
+        ]
     );
 }
 
@@ -1437,6 +1950,77 @@ fn test_compute_diagnostics_deduplicates_same_file_duplicate_symbols() {
         "Expected one diagnostic per duplicate declaration, got: {:?}",
         diagnostics
     );
+    let related_lines: Vec<u32> = duplicates
+        .iter()
+        .map(|diag| {
+            let related = diag.related_information.as_ref().unwrap();
+            assert_eq!(related.len(), 1);
+            related[0].location.range.start.line
+        })
+        .collect();
+    assert_eq!(related_lines, vec![3, 2]);
+}
+
+#[test]
+fn test_unknown_class_diagnostic_lists_candidate_imports() {
+    let uri = "file:///test/controller.php";
+    let code = "<?php\nnamespace App\\Http;\n\nnew Mailer();\n";
+    let index = WorkspaceIndex::new();
+    parse_and_index_php_file(
+        &index,
+        "file:///test/Mail/Mailer.php",
+        "<?php\nnamespace App\\Mail;\nclass Mailer {}\n",
+    );
+    parse_and_index_php_file(
+        &index,
+        "file:///test/Legacy/Mailer.php",
+        "<?php\nnamespace Legacy;\nclass Mailer {}\n",
+    );
+    let parser = parse_and_index_php_file(&index, uri, code);
+
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let unknown = diagnostics
+        .iter()
+        .find(|diag| diag.message == "Unknown class: App\\Http\\Mailer")
+        .unwrap_or_else(|| panic!("missing unknown class diagnostic: {diagnostics:?}"));
+    assert_eq!(
+        unknown.code,
+        Some(NumberOrString::String("php-lsp.unknownClass".to_string()))
+    );
+    let related: Vec<(&str, &str, Position)> = unknown
+        .related_information
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|info| {
+            (
+                info.message.as_str(),
+                info.location.uri.as_str(),
+                info.location.range.start,
+            )
+        })
+        .collect();
+    assert_eq!(
+        related,
+        vec![
+            (
+                "Candidate import: App\\Mail\\Mailer",
+                "file:///test/Mail/Mailer.php",
+                Position::new(2, 6)
+            ),
+            (
+                "Candidate import: Legacy\\Mailer",
+                "file:///test/Legacy/Mailer.php",
+                Position::new(2, 6)
+            ),
+        ]
+    );
 }
 
 #[test]
@@ -2098,24 +2682,78 @@ function run(
 }
 
 #[test]
-fn test_compute_diagnostics_skips_members_on_unindexed_imported_types() {
-    let uri = "file:///external-client.php";
+fn test_compute_diagnostics_narrows_variables_through_early_return_guards() {
+    let uri = "file:///early-return-guards.php";
     let code = r#"<?php
 namespace App;
 
-use Vendor\Package\Client;
+class User {
+    public function name(): string { return ''; }
+}
+class Post {
+    public function title(): string { return ''; }
+}
 
-class Demo {
-    public function run(Client $client): void {
-        $client->send();
+function byInstanceof(Post|User $subject): void {
+    if ($subject instanceof Post) {
+        return;
     }
+    $subject->name();
 }
-"#;
 
-    let mut parser = FileParser::new();
-    parser.parse_full(code);
+function byTypeCheck(string|Post $subject): void {
+    if (is_string($subject)) {
+        return;
+    }
+    $subject->title();
+    $subject->missing();
+}
 
-    let index = WorkspaceIndex::new();
+function byNegatedDisjunction(mixed $subject, bool $force): void {
+    if (!$subject instanceof User || $force) {
+        throw new \RuntimeException();
+    }
+    $subject->name();
+    $subject->title();
+}
+"#;
+
+    let index = WorkspaceIndex::new();
+    let parser = parse_and_index_php_file(&index, uri, code);
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let messages = diagnostic_messages(&diagnostics);
+
+    assert_no_diagnostic_containing(&messages, "Unknown method: App\\Post::name");
+    assert_no_diagnostic_containing(&messages, "Unknown method: App\\User::name");
+    assert_diagnostic_containing(&messages, "Unknown method: App\\Post::missing");
+    assert_diagnostic_containing(&messages, "Unknown method: App\\User::title");
+}
+
+#[test]
+fn test_compute_diagnostics_skips_members_on_unindexed_imported_types() {
+    let uri = "file:///external-client.php";
+    let code = r#"<?php
+namespace App;
+
+use Vendor\Package\Client;
+
+class Demo {
+    public function run(Client $client): void {
+        $client->send();
+    }
+}
+"#;
+
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+
+    let index = WorkspaceIndex::new();
     let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
     index.update_file(uri, symbols);
 
@@ -2340,6 +2978,78 @@ function run(Service $service): void
     );
 }
 
+#[test]
+fn test_compute_diagnostics_applies_rule_severity_overrides() {
+    let uri = "file:///rule-severity.php";
+    let code = r#"<?php
+namespace App;
+
+use Legacy\Missing\Helper;
+
+function run(): void
+{
+    $unused = 1;
+    new MissingClass();
+}
+"#;
+
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let severity = DiagnosticSeverityConfig::parse(&serde_json::json!({
+        "unknownSymbols": "information",
+        "unresolvedUse": "off",
+        "php-lsp.unknownClass": "error",
+        "unusedVariable": false,
+        "unusedImport": true
+    }))
+    .expect("severity config should parse");
+
+    let diagnostics = compute_diagnostics_with_config(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        severity,
+        PhpVersion::DEFAULT,
+    );
+    let code_of = |diagnostic: &Diagnostic| match &diagnostic.code {
+        Some(NumberOrString::String(code)) => code.clone(),
+        _ => String::new(),
+    };
+
+    assert!(
+        !diagnostics
+            .iter()
+            .any(|diagnostic| code_of(diagnostic) == "php-lsp.unresolvedUse"),
+        "unresolvedUse rule is off, got diagnostics: {:?}",
+        diagnostics
+    );
+    assert!(
+        !diagnostics
+            .iter()
+            .any(|diagnostic| code_of(diagnostic) == "php-lsp.unusedVariable"),
+        "unusedVariable rule is disabled, got diagnostics: {:?}",
+        diagnostics
+    );
+
+    let unknown_class = diagnostics
+        .iter()
+        .find(|diagnostic| code_of(diagnostic) == "php-lsp.unknownClass")
+        .expect("Expected unknown class diagnostic");
+    assert_eq!(unknown_class.severity, Some(DiagnosticSeverity::ERROR));
+
+    let unused_import = diagnostics
+        .iter()
+        .find(|diagnostic| code_of(diagnostic) == "php-lsp.unusedImport")
+        .expect("Expected unused import diagnostic to inherit the category severity");
+    assert_eq!(unused_import.severity, Some(DiagnosticSeverity::WARNING));
+}
+
 #[test]
 fn test_compute_diagnostics_allows_magic_class_and_late_bound_self_calls() {
     let uri = "file:///phpunit-patterns.php";
@@ -2397,6 +3107,43 @@ class Demo extends Base {
     }
 }
 
+#[test]
+fn test_compute_diagnostics_resolves_parent_scope_case_insensitively() {
+    let uri = "file:///parent-scope-case.php";
+    let code = r#"<?php
+namespace App;
+
+class Base {
+    public function __construct() {}
+    protected function boot(): void {}
+}
+
+class Child extends Base {
+    public function __construct() {
+        Parent::__construct();
+        PARENT::boot();
+        parent::missing();
+    }
+}
+"#;
+
+    let index = WorkspaceIndex::new();
+    let parser = parse_and_index_php_file(&index, uri, code);
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let messages = diagnostic_messages(&diagnostics);
+
+    assert_no_diagnostic_containing(&messages, "Instance method called statically");
+    assert_no_diagnostic_containing(&messages, "Unknown method: App\\Base::__construct");
+    assert_no_diagnostic_containing(&messages, "Unknown method: App\\Base::boot");
+    assert_diagnostic_containing(&messages, "Unknown method: App\\Base::missing");
+}
+
 #[test]
 fn test_compute_diagnostics_allows_phpunit_stub_api_on_typed_properties() {
     let uri = "file:///phpunit-stub-api.php";
@@ -3976,6 +4723,157 @@ function nullableUnion(): string|null {
     }
 }
 
+#[test]
+fn test_compute_diagnostics_gates_newer_syntax_on_php_version() {
+    let uri = "file:///syntax-version.php";
+    let code = r#"<?php
+enum Status {
+    case Active;
+}
+
+final class User {
+    public function __construct(public readonly int $id) {}
+}
+
+function describe(?User $user): string {
+    $label = match ($user?->id) {
+        1 => 'first',
+        default => 'other',
+    };
+    $format = strlen(...);
+    return str_pad(string: $label, length: 10);
+}
+"#;
+
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let messages = |php_version: PhpVersion| -> Vec<String> {
+        compute_diagnostics(
+            uri,
+            &parser,
+            &index,
+            DiagnosticsMode::BasicSemantic,
+            php_version,
+        )
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .filter(|message| message.contains("requires PHP"))
+        .collect()
+    };
+
+    let php80 = messages(PhpVersion { major: 8, minor: 0 });
+    for expected in [
+        "Enums requires PHP 8.1 (target is PHP 8.0)",
+        "Readonly properties requires PHP 8.1 (target is PHP 8.0)",
+        "First-class callable syntax requires PHP 8.1 (target is PHP 8.0)",
+    ] {
+        assert!(
+            php80.iter().any(|message| message == expected),
+            "Expected `{}` in diagnostics, got: {:?}",
+            expected,
+            php80
+        );
+    }
+    assert!(
+        !php80
+            .iter()
+            .any(|message| message.contains("Match expressions")
+                || message.contains("Named arguments")
+                || message.contains("nullsafe")
+                || message.contains("promotion")),
+        "PHP 8.0 syntax should be accepted on PHP 8.0, got: {:?}",
+        php80
+    );
+
+    let php74 = messages(PhpVersion { major: 7, minor: 4 });
+    for expected in [
+        "Match expressions requires PHP 8.0 (target is PHP 7.4)",
+        "The nullsafe operator requires PHP 8.0 (target is PHP 7.4)",
+        "Constructor property promotion requires PHP 8.0 (target is PHP 7.4)",
+        "Named arguments requires PHP 8.0 (target is PHP 7.4)",
+    ] {
+        assert!(
+            php74.iter().any(|message| message == expected),
+            "Expected `{}` in diagnostics, got: {:?}",
+            expected,
+            php74
+        );
+    }
+
+    assert!(messages(PhpVersion { major: 8, minor: 2 }).is_empty());
+}
+
+#[test]
+fn test_compute_diagnostics_reports_removed_and_deprecated_functions_for_php_version() {
+    let uri = "file:///function-version.php";
+    let code = r#"<?php
+function legacy(array $items): string {
+    each($items);
+    return utf8_encode('x');
+}
+"#;
+
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let php82 = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion { major: 8, minor: 2 },
+    );
+    let removed = php82
+        .iter()
+        .find(|diagnostic| diagnostic.message.contains("each()"))
+        .expect("removed function diagnostic");
+    assert_eq!(
+        removed.message,
+        "Function each() was removed in PHP 8.0 (target is PHP 8.2); use foreach instead"
+    );
+    assert!(
+        !php82
+            .iter()
+            .any(|diagnostic| diagnostic.message == "Unknown function: each"),
+        "Removed functions should not also be reported as unknown: {:?}",
+        php82
+    );
+    let deprecated = php82
+        .iter()
+        .find(|diagnostic| diagnostic.message.contains("utf8_encode()"))
+        .expect("deprecated function diagnostic");
+    assert_eq!(
+        deprecated.message,
+        "Function utf8_encode() is deprecated since PHP 8.2; use mb_convert_encoding() instead"
+    );
+    assert_eq!(deprecated.tags, Some(vec![DiagnosticTag::DEPRECATED]));
+
+    let php71 = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion { major: 7, minor: 1 },
+    );
+    assert!(
+        !php71
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("each()")
+                || diagnostic.message.contains("utf8_encode()")),
+        "Functions should not be flagged before their deprecation, got: {:?}",
+        php71
+    );
+}
+
 #[test]
 fn test_compute_diagnostics_applies_class_variance_to_override_signatures() {
     let uri = "file:///override-variance.php";
@@ -4049,6 +4947,91 @@ class BadReturnChild extends ReturnBase {
     );
 }
 
+#[test]
+fn test_compute_diagnostics_checks_static_return_override_covariance() {
+    let uri = "file:///override-static.php";
+    let code = r#"<?php
+namespace App;
+
+class Builder {
+    public function withSelf(): self {
+        return $this;
+    }
+
+    public function withStatic(): static {
+        return $this;
+    }
+
+    public function maybe(): ?static {
+        return null;
+    }
+}
+
+class GoodBuilder extends Builder {
+    public function withSelf(): static {
+        return $this;
+    }
+
+    public function withStatic(): static {
+        return $this;
+    }
+
+    public function maybe(): static {
+        return $this;
+    }
+}
+
+class SelfBuilder extends Builder {
+    public function withStatic(): self {
+        return $this;
+    }
+}
+
+final class ConcreteBuilder extends Builder {
+    public function withStatic(): ConcreteBuilder {
+        return $this;
+    }
+}
+
+class NullableBuilder extends GoodBuilder {
+    public function withStatic(): ?static {
+        return null;
+    }
+}
+"#;
+
+    let index = WorkspaceIndex::new();
+    let parser = parse_and_index_php_file(&index, uri, code);
+
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let messages = diagnostic_messages(&diagnostics);
+
+    assert_no_diagnostic_containing(
+        &messages,
+        "Incompatible override signature: App\\GoodBuilder::",
+    );
+    for child in [
+        "App\\SelfBuilder::withStatic differs from App\\Builder::withStatic",
+        "App\\ConcreteBuilder::withStatic differs from App\\Builder::withStatic",
+        "App\\NullableBuilder::withStatic differs from App\\GoodBuilder::withStatic",
+    ] {
+        assert!(
+            messages
+                .iter()
+                .any(|message| message == &format!("Incompatible override signature: {}", child)),
+            "Expected static return override diagnostic for {}, got: {:?}",
+            child,
+            messages
+        );
+    }
+}
+
 #[test]
 fn test_compute_diagnostics_allows_named_arguments() {
     let uri = "file:///named-args.php";
@@ -4665,15 +5648,51 @@ fn test_formatting_provider_none_disables_stale_command() {
         custom.command_template().as_deref(),
         Some("vendor/bin/fmt {file}")
     );
+
+    let builtin = FormattingConfig::from_options(Some("built-in"), None, None);
+    assert!(builtin.command_template().is_none());
+    assert!(builtin.uses_builtin_formatter());
+    assert!(!config.uses_builtin_formatter());
 }
 
 #[test]
-fn test_framework_string_key_cache_evicts_lru_entries() {
-    fn key(root: &str, domain: &str) -> FrameworkStringKeyCacheKey {
-        FrameworkStringKeyCacheKey {
-            root: PathBuf::from(root),
-            domain: domain.to_string(),
-        }
+fn test_minimal_text_edits_replace_only_changed_lines() {
+    use lsp::formatting::minimal_text_edits;
+
+    let old = "<?php\nfoo( );\nbar();\nbaz( );\nqux();";
+    let new = "<?php\nfoo();\nbar();\nbaz();\nqux();\n";
+    let edits = minimal_text_edits(old, new, PositionEncoding::Utf16);
+    let summary: Vec<_> = edits
+        .iter()
+        .map(|edit| {
+            (
+                edit.range.start.line,
+                edit.range.start.character,
+                edit.range.end.line,
+                edit.range.end.character,
+                edit.new_text.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(1, 0, 2, 0, "foo();\n"), (3, 0, 4, 6, "baz();\nqux();\n"),]
+    );
+
+    assert!(minimal_text_edits(old, old, PositionEncoding::Utf16).is_empty());
+    assert_eq!(
+        minimal_text_edits("", "<?php\n", PositionEncoding::Utf16).len(),
+        1
+    );
+}
+
+#[test]
+fn test_framework_string_key_cache_evicts_lru_entries() {
+    fn key(root: &str, domain: &str) -> FrameworkStringKeyCacheKey {
+        FrameworkStringKeyCacheKey {
+            root: PathBuf::from(root),
+            domain: domain.to_string(),
+        }
     }
 
     fn value(name: &str) -> crate::framework::FrameworkStringKey {
@@ -4811,6 +5830,55 @@ fn test_twig_context_disk_cache_evicts_entries_for_source_uri() {
     assert_eq!(cache.evict_entries_for_source_uri(controller_uri), 0);
 }
 
+#[test]
+fn test_config_snapshot_update_keeps_loaded_snapshots_unchanged() {
+    let cell = ConfigSnapshotCell::default();
+    let before = cell.load();
+
+    let changed = cell.update(|config| {
+        config.workspace_root = Some(PathBuf::from("/workspace"));
+        config.trace_level = TraceValue::Verbose;
+        true
+    });
+
+    assert!(changed);
+    assert!(before.workspace_root.is_none());
+    assert_eq!(before.trace_level, TraceValue::Off);
+    let after = cell.load();
+    assert_eq!(after.workspace_root, Some(PathBuf::from("/workspace")));
+    assert_eq!(after.trace_level, TraceValue::Verbose);
+}
+
+#[test]
+fn test_closed_file_tree_cache_evicts_lru_and_stale_entries() {
+    fn parsed(source: &str) -> Arc<ClosedFileTree> {
+        let mut parser = FileParser::new();
+        parser.parse_full(source);
+        Arc::new(ClosedFileTree {
+            source: source.to_string(),
+            tree: parser.tree().unwrap().clone(),
+        })
+    }
+
+    let mut cache = ClosedFileTreeCache {
+        capacity: 2,
+        ..Default::default()
+    };
+    let first = "file:///workspace/src/A.php";
+    let second = "file:///workspace/src/B.php";
+    let third = "file:///workspace/src/C.php";
+
+    cache.insert(first.to_string(), parsed("<?php class A {}"));
+    cache.insert(second.to_string(), parsed("<?php class B {}"));
+    assert!(cache.get(first, "<?php class A {}").is_some());
+    cache.insert(third.to_string(), parsed("<?php class C {}"));
+
+    assert!(cache.get(second, "<?php class B {}").is_none());
+    assert!(cache.get(third, "<?php class C {}").is_some());
+    assert!(cache.get(first, "<?php class A { public $x; }").is_none());
+    assert_eq!(cache.len(), 1);
+}
+
 #[tokio::test]
 async fn test_request_fs_cache_invalidation_clears_framework_and_twig_caches() {
     let framework_cache = Arc::new(Mutex::new(FrameworkStringKeyCache {
@@ -5090,7 +6158,7 @@ async fn test_external_analyzers_timeout_without_hanging() {
         Duration::from_secs(1),
         run_psalm_for_file(
             PsalmConfig {
-                enabled: true,
+                enabled: AnalyzerActivation::On,
                 command,
                 timeout_ms: 50,
             },
@@ -5163,7 +6231,7 @@ async fn test_external_analyzers_malformed_json_without_hanging() {
         Duration::from_secs(1),
         run_psalm_for_file(
             PsalmConfig {
-                enabled: true,
+                enabled: AnalyzerActivation::On,
                 command,
                 timeout_ms: 5_000,
             },
@@ -5258,7 +6326,7 @@ async fn test_run_psalm_for_file_accepts_nonzero_json_output() {
     .unwrap();
 
     let config = PsalmConfig {
-        enabled: true,
+        enabled: AnalyzerActivation::On,
         command: format!(
             "sh {} {{file}}",
             shell_escape(&script_path.to_string_lossy())
@@ -5280,6 +6348,51 @@ async fn test_run_psalm_for_file_accepts_nonzero_json_output() {
     let _ = std::fs::remove_dir_all(&tmp);
 }
 
+#[test]
+fn test_psalm_auto_activation_requires_workspace_psalm_config() {
+    assert_eq!(
+        AnalyzerActivation::parse(&serde_json::json!("auto")),
+        Some(AnalyzerActivation::Auto)
+    );
+    assert_eq!(
+        AnalyzerActivation::parse(&serde_json::json!(true)),
+        Some(AnalyzerActivation::On)
+    );
+    assert_eq!(
+        AnalyzerActivation::parse(&serde_json::json!(false)),
+        Some(AnalyzerActivation::Off)
+    );
+    assert_eq!(
+        AnalyzerActivation::parse(&serde_json::json!("sometimes")),
+        None
+    );
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp = std::env::temp_dir().join(format!(
+        "php-lsp-psalm-auto-test-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    std::fs::create_dir_all(&tmp).unwrap();
+    assert!(!psalm_config_exists(&tmp));
+    std::fs::write(tmp.join("psalm.xml.dist"), "<psalm/>\n").unwrap();
+    assert!(psalm_config_exists(&tmp));
+    let _ = std::fs::remove_dir_all(&tmp);
+
+    let mut settings = serde_json::json!({ "psalm": { "enabled": "auto" } });
+    let message = sanitize_project_settings_for_command_trust(
+        &mut settings,
+        Path::new("/workspace/.php-lsp.toml"),
+        false,
+    )
+    .expect("expected untrusted psalm auto-detection to be ignored");
+    assert!(settings["psalm"].get("enabled").is_none());
+    assert!(message.contains("psalm.enabled"));
+}
+
 #[test]
 fn test_untrusted_project_config_strips_executable_settings() {
     let mut settings = serde_json::json!({
@@ -5302,6 +6415,17 @@ fn test_untrusted_project_config_strips_executable_settings() {
             "enabled": true,
             "command": "sh -c 'touch /tmp/php-lsp-owned' {file}",
             "timeoutMs": 1000
+        },
+        "phpcs": {
+            "enabled": true,
+            "command": "sh -c 'touch /tmp/php-lsp-owned' {file}",
+            "fixCommand": "sh -c 'touch /tmp/php-lsp-owned' {file}",
+            "timeoutMs": 1000
+        },
+        "runtimeStubs": {
+            "enabled": true,
+            "phpExecutable": "/tmp/php-lsp-owned",
+            "timeoutMs": 1000
         }
     });
 
@@ -5326,9 +6450,18 @@ fn test_untrusted_project_config_strips_executable_settings() {
     assert!(settings["psalm"].get("enabled").is_none());
     assert!(settings["psalm"].get("command").is_none());
     assert_eq!(settings["psalm"]["timeoutMs"], 1000);
+    assert!(settings["phpcs"].get("enabled").is_none());
+    assert!(settings["phpcs"].get("command").is_none());
+    assert!(settings["phpcs"].get("fixCommand").is_none());
+    assert_eq!(settings["phpcs"]["timeoutMs"], 1000);
+    assert!(settings["runtimeStubs"].get("enabled").is_none());
+    assert!(settings["runtimeStubs"].get("phpExecutable").is_none());
+    assert_eq!(settings["runtimeStubs"]["timeoutMs"], 1000);
     assert!(message.contains("formatting.command"));
     assert!(message.contains("phpstan.enabled"));
     assert!(message.contains("psalm.command"));
+    assert!(message.contains("phpcs.fixCommand"));
+    assert!(message.contains("runtimeStubs.phpExecutable"));
 }
 
 #[test]
@@ -5419,7 +6552,12 @@ fn test_collect_php_files_uses_include_paths_and_excludes() {
 
     let include_paths = vec![PathBuf::from("src"), PathBuf::from("extra")];
     let exclude_paths = normalize_config_paths(vec!["extra/generated".to_string()]);
-    let mut files = collect_php_files(&include_paths, &tmp, &exclude_paths);
+    let mut files = collect_php_files(
+        &include_paths,
+        &tmp,
+        &exclude_paths,
+        &default_php_file_extensions(),
+    );
     files.sort();
 
     assert_eq!(files, vec![extra.join("Helper.php"), src.join("App.php")]);
@@ -5427,6 +6565,138 @@ fn test_collect_php_files_uses_include_paths_and_excludes() {
     let _ = std::fs::remove_dir_all(&tmp);
 }
 
+#[test]
+fn test_path_is_excluded_matches_glob_patterns() {
+    let root = PathBuf::from("/project");
+    let exclude_paths = normalize_config_paths(vec![
+        "**/cache/**".to_string(),
+        "**/*.blade.php".to_string(),
+        "tests/fixture[0-9]".to_string(),
+    ]);
+
+    for excluded in [
+        "/project/var/cache",
+        "/project/var/cache/Generated.php",
+        "/project/resources/views/home.blade.php",
+        "/project/tests/fixture1/Sample.php",
+    ] {
+        assert!(
+            path_is_excluded(Path::new(excluded), &root, &exclude_paths),
+            "{excluded} should be excluded"
+        );
+    }
+    for included in [
+        "/project/src/CacheManager.php",
+        "/project/resources/views/Home.php",
+        "/project/tests/fixtures/Sample.php",
+    ] {
+        assert!(
+            !path_is_excluded(Path::new(included), &root, &exclude_paths),
+            "{included} should not be excluded"
+        );
+    }
+}
+
+#[test]
+fn test_collect_php_files_expands_include_globs_and_gitignore() {
+    let tmp = std::env::temp_dir().join(format!(
+        "php-lsp-include-glob-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    let tools = tmp.join("tools");
+    let generated = tmp.join("src/Generated");
+    std::fs::create_dir_all(tools.join("bin")).unwrap();
+    std::fs::create_dir_all(&generated).unwrap();
+    std::fs::write(tools.join("bin/Tool.php"), "<?php class Tool {}").unwrap();
+    std::fs::write(tools.join("notes.txt"), "not php").unwrap();
+    std::fs::write(tmp.join("src/App.php"), "<?php class App {}").unwrap();
+    std::fs::write(generated.join("Proxy.php"), "<?php class Proxy {}").unwrap();
+    std::fs::write(
+        tmp.join(".gitignore"),
+        "# build output\nGenerated/\n!keep.php\n",
+    )
+    .unwrap();
+
+    let include_paths = normalize_config_paths(vec!["tools/**/*.php".to_string()]);
+    let extensions = default_php_file_extensions();
+    let files = collect_php_files(&include_paths, &tmp, &[], &extensions);
+    assert_eq!(files, vec![tools.join("bin/Tool.php")]);
+
+    assert_eq!(
+        workspace_exclude_paths(&tmp, &[], true),
+        vec![PathBuf::from("**/Generated")]
+    );
+    let mut files = collect_php_files(
+        &[PathBuf::from("src")],
+        &tmp,
+        &workspace_exclude_paths(&tmp, &[], true),
+        &extensions,
+    );
+    files.sort();
+    assert_eq!(files, vec![tmp.join("src/App.php")]);
+    assert_eq!(
+        collect_php_files(
+            &[PathBuf::from("src")],
+            &tmp,
+            &workspace_exclude_paths(&tmp, &[], false),
+            &extensions,
+        )
+        .len(),
+        2
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_collect_php_files_uses_configured_extensions() {
+    let tmp = std::env::temp_dir().join(format!(
+        "php-lsp-file-extensions-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    std::fs::create_dir_all(tmp.join("modules")).unwrap();
+    std::fs::write(tmp.join("index.php"), "<?php").unwrap();
+    std::fs::write(tmp.join("modules/legacy.inc"), "<?php").unwrap();
+    std::fs::write(tmp.join("modules/custom.MODULE"), "<?php").unwrap();
+    std::fs::write(tmp.join("modules/readme.txt"), "docs").unwrap();
+
+    let extensions =
+        normalize_php_file_extensions(vec!["php".into(), ".inc".into(), "Module".into()]);
+    assert_eq!(extensions, vec!["php", "inc", "module"]);
+    assert_eq!(
+        normalize_php_file_extensions(vec![" ".into()]),
+        default_php_file_extensions()
+    );
+
+    let mut files = collect_php_files(std::slice::from_ref(&tmp), &tmp, &[], &extensions);
+    files.sort();
+    assert_eq!(
+        files,
+        vec![
+            tmp.join("index.php"),
+            tmp.join("modules/custom.MODULE"),
+            tmp.join("modules/legacy.inc"),
+        ]
+    );
+    assert_eq!(
+        collect_php_files(
+            std::slice::from_ref(&tmp),
+            &tmp,
+            &[],
+            &default_php_file_extensions()
+        ),
+        vec![tmp.join("index.php")]
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
 #[test]
 fn test_resolve_vendor_paths() {
     // Create temp dir with fake vendor/composer/installed.json
@@ -5477,3 +6747,638 @@ fn test_resolve_vendor_paths() {
     // Cleanup
     let _ = std::fs::remove_dir_all(&tmp);
 }
+
+#[test]
+fn test_reference_scan_batch_parallel_matches_serial_order_and_honors_cancellation() {
+    let index = WorkspaceIndex::new();
+    let open_files = DashMap::new();
+    let mut uris = Vec::new();
+    for file in 0..24 {
+        let uri = format!("file:///scan/File{file}.php");
+        let code = if file % 3 == 0 {
+            format!("<?php\nnamespace App;\nfunction f{file}(): void {{ new Target(); }}\n")
+        } else {
+            format!("<?php\nnamespace App;\nfunction f{file}(): void {{}}\n")
+        };
+        let mut parser = FileParser::new();
+        parser.parse_full(&code);
+        let tree = parser.tree().unwrap();
+        let symbols = extract_file_symbols(tree, &code, &uri);
+        let references =
+            collect_symbol_references_in_file(tree, &code, &symbols, PositionEncoding::Utf16);
+        index.update_file_with_references(&uri, symbols, references);
+        uris.push(uri);
+    }
+    let target = ReferenceScanTarget {
+        fqn: Arc::from("App\\Target"),
+        kind: php_lsp_types::PhpSymbolKind::Class,
+        include_declaration: false,
+    };
+
+    let token = OperationCancellationToken::new();
+    let serial = scan_reference_batch(
+        &index,
+        &open_files,
+        &uris,
+        &target,
+        &token,
+        1,
+        PositionEncoding::Utf16,
+    );
+    let parallel = scan_reference_batch(
+        &index,
+        &open_files,
+        &uris,
+        &target,
+        &token,
+        4,
+        PositionEncoding::Utf16,
+    );
+    let serial_files: Vec<_> = serial.iter().map(|(uri, _)| uri.clone()).collect();
+    let parallel_files: Vec<_> = parallel.iter().map(|(uri, _)| uri.clone()).collect();
+    assert_eq!(serial_files.len(), 8);
+    assert_eq!(serial_files, parallel_files);
+
+    token.cancel();
+    assert!(scan_reference_batch(
+        &index,
+        &open_files,
+        &uris,
+        &target,
+        &token,
+        4,
+        PositionEncoding::Utf16
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_union_receiver_member_missing_on_a_branch_is_reported() {
+    let uri = "file:///test/union_members.php";
+    let code = r#"<?php
+namespace App;
+
+class A { public function foo(): void {} public function both(): void {} public int $p = 0; }
+class B { public function both(): void {} }
+
+function f(A|B $x, ?A $n): void {
+    $x->foo();
+    $x->both();
+    echo $x->p;
+    if ($x instanceof A) { $x->foo(); }
+    $n->foo();
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let messages = diagnostic_messages(&diagnostics);
+    assert_eq!(
+        messages
+            .iter()
+            .filter(|message| message.contains("does not exist on"))
+            .count(),
+        2,
+        "{messages:?}"
+    );
+    let method = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.message == "Method foo() does not exist on A|B")
+        .unwrap_or_else(|| panic!("missing union method diagnostic: {messages:?}"));
+    assert_eq!(method.range.start, Position::new(7, 8));
+    assert_eq!(
+        method.code,
+        Some(NumberOrString::String(
+            "php-lsp.unknownUnionMember".to_string()
+        ))
+    );
+    let related = method.related_information.as_ref().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].message, "foo() is not declared on App\\B");
+    assert_eq!(related[0].location.range.start, Position::new(4, 6));
+    assert_diagnostic_containing(&messages, "Property $p does not exist on A|B");
+    assert_no_diagnostic_containing(&messages, "both()");
+}
+
+#[test]
+fn test_member_and_type_diagnostics_carry_family_rule_codes() {
+    let uri = "file:///test/family_codes.php";
+    let code = r#"<?php
+namespace App;
+
+class Box {
+    public function set(string $name): void {}
+    private function hidden(): void {}
+}
+
+function run(Box $box): void {
+    $box->missing();
+    $box->hidden();
+    $box->set(123);
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let severity = DiagnosticSeverityConfig::parse(&serde_json::json!({
+        "members": "off",
+        "unknownMember": "error",
+        "typeMismatch": "hint"
+    }))
+    .expect("severity config should parse");
+    let diagnostics = compute_diagnostics_with_config(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        severity,
+        PhpVersion::DEFAULT,
+    );
+    let code_and_severity = |prefix: &str| {
+        diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.message.starts_with(prefix))
+            .map(|diagnostic| (diagnostic.code.clone(), diagnostic.severity))
+    };
+
+    assert_eq!(
+        code_and_severity("Unknown method: "),
+        Some((
+            Some(NumberOrString::String("php-lsp.unknownMember".to_string())),
+            Some(DiagnosticSeverity::ERROR)
+        ))
+    );
+    assert_eq!(
+        code_and_severity("Type mismatch for "),
+        Some((
+            Some(NumberOrString::String("php-lsp.typeMismatch".to_string())),
+            Some(DiagnosticSeverity::HINT)
+        ))
+    );
+    assert!(
+        !diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("hidden")),
+        "invalid member access follows the members category: {diagnostics:?}"
+    );
+}
+
+#[test]
+fn test_union_member_rule_overrides_members_severity() {
+    let uri = "file:///test/union_member_rule.php";
+    let code = r#"<?php
+namespace App;
+
+class A { public function foo(): void {} }
+class B {}
+
+function f(A|B $x, A $a): void {
+    $x->foo();
+    $a->missing();
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+    let diagnostics_with = |config: serde_json::Value| {
+        compute_diagnostics_with_config(
+            uri,
+            &parser,
+            &index,
+            DiagnosticsMode::BasicSemantic,
+            DiagnosticSeverityConfig::parse(&config).expect("severity config should parse"),
+            PhpVersion::DEFAULT,
+        )
+    };
+    let severity_of = |diagnostics: &[Diagnostic], needle: &str| {
+        diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.message.contains(needle))
+            .map(|diagnostic| diagnostic.severity)
+    };
+
+    let diagnostics = diagnostics_with(serde_json::json!({
+        "members": "information",
+        "unknownUnionMember": "error"
+    }));
+    assert_eq!(
+        severity_of(&diagnostics, "Method foo() does not exist on A|B"),
+        Some(Some(DiagnosticSeverity::ERROR))
+    );
+    assert_eq!(
+        severity_of(&diagnostics, "missing"),
+        Some(Some(DiagnosticSeverity::INFORMATION))
+    );
+
+    let diagnostics = diagnostics_with(serde_json::json!({ "php-lsp.unknownUnionMember": false }));
+    assert_eq!(severity_of(&diagnostics, "does not exist on A|B"), None);
+    assert_eq!(
+        severity_of(&diagnostics, "missing"),
+        Some(Some(DiagnosticSeverity::WARNING))
+    );
+}
+
+#[test]
+fn test_possible_null_dereference_is_reported_with_nullsafe_fix() {
+    let uri = "file:///test/null_deref.php";
+    let code = r#"<?php
+namespace App;
+
+class A {
+    public function foo(): void {}
+}
+
+function f(?A $n, A|null $u, A $a, ?A $s, ?A $g): void {
+    $n->foo();
+    $u->foo();
+    $a->foo();
+    $s?->foo();
+    if ($g !== null) { $g->foo(); }
+}
+
+function g(?A $t, ?A $v, ?A $w): void {
+    if (!$t) { throw new \Exception(); }
+    $t->foo();
+    $v && $v->foo();
+    $w ??= new A();
+    $w->foo();
+}
+
+function h(?A $x, ?A $y, ?A $e): void {
+    if ($x === null) { $x->foo(); }
+    if ($y !== null) { echo 'set'; }
+    $y->foo();
+    if ($e === null) { echo 'unset'; } else { $e->foo(); }
+}
+
+function k(int $k, ?A $x, ?A $y): void {
+    if ($k === 1) { return; } elseif ($x === null) { return; }
+    $x->foo();
+    if ($y !== null) { $y = null; $y->foo(); }
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let null_derefs = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.message.starts_with("Possible null dereference"))
+        .collect::<Vec<_>>();
+    let messages = null_derefs
+        .iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            (8, "Possible null dereference: $n may be null"),
+            (9, "Possible null dereference: $u may be null"),
+            (24, "Possible null dereference: $x may be null"),
+            (26, "Possible null dereference: $y may be null"),
+            (33, "Possible null dereference: $y may be null"),
+        ]
+    );
+    assert_eq!(
+        null_derefs[0].code,
+        Some(NumberOrString::String(
+            "php-lsp.possibleNullDereference".to_string()
+        ))
+    );
+
+    let Some(CodeActionOrCommand::CodeAction(action)) = build_diagnostic_replacement_action(
+        uri.parse().unwrap(),
+        code,
+        null_derefs[0],
+        &serde_json::from_value::<DiagnosticReplacement>(
+            null_derefs[0].data.as_ref().unwrap()["replacement"].clone(),
+        )
+        .unwrap(),
+        true,
+        PositionEncoding::Utf16,
+    ) else {
+        panic!("expected nullsafe quick fix");
+    };
+    assert_eq!(action.title, "Use nullsafe operator `?->`");
+    let edit = &action.edit.unwrap().changes.unwrap()[&uri.parse::<Uri>().unwrap()][0];
+    assert_eq!(edit.new_text, "?->");
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(8, 6), Position::new(8, 8))
+    );
+
+    let severity = DiagnosticSeverityConfig::parse(&serde_json::json!({
+        "possibleNullDereference": "hint"
+    }))
+    .expect("severity config should parse");
+    let diagnostics = compute_diagnostics_with_config(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        severity,
+        PhpVersion::DEFAULT,
+    );
+    assert!(diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.message.starts_with("Possible null dereference"))
+        .all(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::HINT)));
+}
+
+#[test]
+fn test_non_exhaustive_enum_match_is_reported_with_missing_arms_fix() {
+    let uri = "file:///test/enum_match.php";
+    let code = r#"<?php
+namespace App;
+
+enum Status {
+    case Active;
+    case Pending;
+    case Archived;
+
+    public function label(): string {
+        return match ($this) {
+            self::Active, self::Pending => 'open',
+            self::Archived => 'closed',
+        };
+    }
+}
+
+class Order {
+    public Status $status = Status::Active;
+}
+
+function f(Status $s, Order $order): string {
+    $a = match ($s) {
+        Status::Active => 'a'
+    };
+    $b = match ($order->status) {
+        Status::Pending => 'b',
+        default => 'x',
+    };
+    return match ($order->status) {
+        Status::Archived => 'c',
+    };
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let non_exhaustive = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.message.starts_with("Match on"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        non_exhaustive
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                21,
+                "Match on Status is not exhaustive; missing Status::Pending, Status::Archived"
+            ),
+            (
+                28,
+                "Match on Status is not exhaustive; missing Status::Active, Status::Pending"
+            ),
+        ]
+    );
+    assert_eq!(
+        non_exhaustive[0].code,
+        Some(NumberOrString::String(
+            "php-lsp.nonExhaustiveMatch".to_string()
+        ))
+    );
+    let severity = DiagnosticSeverityConfig::parse(&serde_json::json!({
+        "members": "error",
+        "nonExhaustiveMatch": "off"
+    }))
+    .expect("severity config should parse");
+    assert!(!compute_diagnostics_with_config(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        severity,
+        PhpVersion::DEFAULT,
+    )
+    .iter()
+    .any(|diagnostic| diagnostic.message.starts_with("Match on")));
+
+    let Some(CodeActionOrCommand::CodeAction(action)) = build_diagnostic_replacement_action(
+        uri.parse().unwrap(),
+        code,
+        non_exhaustive[0],
+        &serde_json::from_value::<DiagnosticReplacement>(
+            non_exhaustive[0].data.as_ref().unwrap()["replacement"].clone(),
+        )
+        .unwrap(),
+        true,
+        PositionEncoding::Utf16,
+    ) else {
+        panic!("expected missing arms quick fix");
+    };
+    assert_eq!(action.title, "Add missing match arms");
+    let edit = &action.edit.unwrap().changes.unwrap()[&uri.parse::<Uri>().unwrap()][0];
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(22, 29), Position::new(23, 4))
+    );
+    assert_eq!(
+        edit.new_text,
+        ",\n        Status::Pending => throw new \\LogicException('Not implemented yet.'),\n        Status::Archived => throw new \\LogicException('Not implemented yet.'),\n    "
+    );
+}
+
+#[test]
+fn test_unhandled_exception_diagnostics_are_opt_in_and_respect_catch_and_throws() {
+    let uri = "file:///test/unhandled.php";
+    let code = r#"<?php
+namespace App;
+
+class NotFound extends \RuntimeException {}
+class Missing extends NotFound {}
+
+class Repo {
+    /** @throws Missing */
+    public function find(): void {}
+
+    public function __construct() {
+        throw new \LogicException();
+    }
+}
+
+/** @throws NotFound */
+function declared(Repo $repo): void { $repo->find(); }
+
+function caught(Repo $repo): void {
+    try {
+        $repo->find();
+    } catch (NotFound $e) {
+    }
+}
+
+function unhandled(Repo $repo): void {
+    $repo->find();
+    new Repo();
+    $retry = function () use ($repo) { $repo->find(); };
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let default_diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    assert_no_diagnostic_containing(
+        &diagnostic_messages(&default_diagnostics),
+        "Unhandled exception",
+    );
+
+    let mut severity = DiagnosticSeverityConfig::default();
+    severity.set(
+        DiagnosticCategory::UnhandledExceptions,
+        DiagnosticLevel(Some(DiagnosticSeverity::WARNING)),
+    );
+    let diagnostics = compute_diagnostics_with_config(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        severity,
+        PhpVersion::DEFAULT,
+    );
+    let unhandled = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.message.starts_with("Unhandled exception"))
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unhandled,
+        vec![
+            (
+                26,
+                "Unhandled exception: App\\Missing thrown by find() is neither caught nor declared with @throws"
+            ),
+            (
+                27,
+                "Unhandled exception: LogicException thrown by __construct() is neither caught nor declared with @throws"
+            ),
+        ]
+    );
+    assert!(diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.message.starts_with("Unhandled exception"))
+        .all(|diagnostic| diagnostic.code
+            == Some(NumberOrString::String(
+                "php-lsp.unhandledException".to_string()
+            ))));
+}
+
+#[test]
+fn test_trait_method_collisions_respect_insteadof_and_aliases() {
+    let uri = "file:///test/traits.php";
+    let code = r#"<?php
+namespace App;
+
+trait A { public function hello(): string { return 'A'; } }
+trait B {
+    public function hello(): string { return 'B'; }
+    public function world(): string { return 'B'; }
+}
+
+class Conflicted {
+    use A, B;
+}
+
+class Resolved {
+    use A, B {
+        A::hello insteadof B;
+        B::hello as protected helloB;
+        world as private;
+    }
+
+    public function run(): string { return $this->helloB(); }
+}
+
+function greet(Resolved $resolved): void {
+    $resolved->hello();
+    $resolved->helloB();
+    $resolved->world();
+}
+"#;
+    let mut parser = FileParser::new();
+    parser.parse_full(code);
+    let index = WorkspaceIndex::new();
+    let symbols = extract_file_symbols(parser.tree().unwrap(), code, uri);
+    index.update_file(uri, symbols);
+
+    let diagnostics = compute_diagnostics(
+        uri,
+        &parser,
+        &index,
+        DiagnosticsMode::BasicSemantic,
+        PhpVersion::DEFAULT,
+    );
+    let messages = diagnostic_messages(&diagnostics);
+    assert_diagnostic_containing(
+        &messages,
+        "Trait method hello() collides in App\\Conflicted: it is defined in App\\A and App\\B; choose one with insteadof",
+    );
+    assert_no_diagnostic_containing(&messages, "collides in App\\Resolved");
+    assert_no_diagnostic_containing(&messages, "does not exist");
+    assert_no_diagnostic_containing(&messages, "Unknown class constant");
+    // Only the calls from outside the class see the narrowed visibility.
+    assert_eq!(
+        messages
+            .iter()
+            .filter(|message| message.contains("member is not accessible here"))
+            .collect::<Vec<_>>(),
+        vec![
+            "Protected member is not accessible here: App\\B::hello",
+            "Private member is not accessible here: App\\B::world",
+        ]
+    );
+}
@@ -0,0 +1,507 @@
+//! `php-lsp tags`: ctags / etags files for editors without LSP support.
+//!
+//! Files are indexed into a `WorkspaceIndex` exactly like the language server
+//! does on startup, then every declaration is written as a tag. Tag paths are
+//! relative to the directory of the tags file when the source lives below it,
+//! which is what Vim and Emacs expect for a tags file in the project root.
+
+use crate::export::workspace_php_files;
+use crate::util::uri::path_to_uri;
+use php_lsp_index::workspace::WorkspaceIndex;
+use php_lsp_parser::parser::FileParser;
+use php_lsp_parser::source::read_php_source;
+use php_lsp_parser::symbols::extract_file_symbols;
+use php_lsp_types::{PhpSymbolKind, SymbolInfo};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagsFormat {
+    Ctags,
+    Etags,
+}
+
+impl TagsFormat {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "ctags" => Some(Self::Ctags),
+            "etags" => Some(Self::Etags),
+            _ => None,
+        }
+    }
+
+    fn default_output(self) -> &'static str {
+        match self {
+            Self::Ctags => "tags",
+            Self::Etags => "TAGS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagsArgs {
+    pub project_root: Option<PathBuf>,
+    pub format: TagsFormat,
+    /// Defaults to `tags` / `TAGS` in the current directory.
+    pub output: Option<PathBuf>,
+}
+
+impl Default for TagsArgs {
+    fn default() -> Self {
+        Self {
+            project_root: None,
+            format: TagsFormat::Ctags,
+            output: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TagsCliResult {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug)]
+pub struct TagsError {
+    message: String,
+}
+
+impl TagsError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for TagsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TagsError {}
+
+/// A declaration ready to be written as a tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tag {
+    name: String,
+    /// Path as written to the tags file.
+    file: String,
+    /// Zero-based line of the declared name.
+    line: u32,
+    /// Source line containing the name, without the line terminator.
+    line_text: String,
+    /// Byte offset of `line_text` in the file.
+    line_offset: usize,
+    /// Byte column where the name ends on `line_text`.
+    name_end: usize,
+    kind: PhpSymbolKind,
+    /// `(scope kind, scope name)`, e.g. `("class", "App\\Foo")`.
+    scope: Option<(&'static str, String)>,
+}
+
+pub fn parse_tags_args(raw_args: Vec<String>) -> Result<TagsArgs, TagsError> {
+    let mut parsed = TagsArgs::default();
+    let mut iter = raw_args.into_iter();
+
+    while let Some(arg) = iter.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .map(str::to_string)
+                .or_else(|| iter.next())
+                .ok_or_else(|| TagsError::new(format!("Missing value for {name}")))
+        };
+        match flag.as_str() {
+            "--project-root" => {
+                parsed.project_root = Some(PathBuf::from(value("--project-root")?));
+            }
+            "--format" => {
+                let raw = value("--format")?;
+                parsed.format = TagsFormat::parse(&raw).ok_or_else(|| {
+                    TagsError::new(format!("Invalid --format `{raw}`; expected ctags or etags"))
+                })?;
+            }
+            "-e" | "--etags" => parsed.format = TagsFormat::Etags,
+            "--output" | "-o" | "-f" => {
+                parsed.output = Some(PathBuf::from(value("--output")?));
+            }
+            value if value.starts_with('-') => {
+                return Err(TagsError::new(format!("Unknown tags option `{value}`")));
+            }
+            value => {
+                if parsed.project_root.is_some() {
+                    return Err(TagsError::new(format!(
+                        "Unexpected extra tags path `{value}`"
+                    )));
+                }
+                parsed.project_root = Some(PathBuf::from(value));
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub fn tags_help() -> &'static str {
+    "Usage:\n  php-lsp tags [PROJECT_ROOT] [--format <ctags|etags>] [--output <FILE>]\n  php-lsp tags [PROJECT_ROOT] -e"
+}
+
+pub fn run_tags_cli(raw_args: Vec<String>) -> TagsCliResult {
+    if raw_args
+        .iter()
+        .any(|arg| matches!(arg.as_str(), "--help" | "-h" | "help"))
+    {
+        return TagsCliResult {
+            exit_code: 0,
+            stdout: format!("{}\n", tags_help()),
+            stderr: String::new(),
+        };
+    }
+
+    match parse_tags_args(raw_args).and_then(|args| run_tags(&args)) {
+        Ok((output, count)) => TagsCliResult {
+            exit_code: 0,
+            stdout: format!("Wrote {count} tags to {}\n", output.display()),
+            stderr: String::new(),
+        },
+        Err(err) => TagsCliResult {
+            exit_code: 1,
+            stdout: String::new(),
+            stderr: format!("{err}\n"),
+        },
+    }
+}
+
+fn run_tags(args: &TagsArgs) -> Result<(PathBuf, usize), TagsError> {
+    let cwd = std::env::current_dir()
+        .map_err(|err| TagsError::new(format!("Failed to read current directory: {err}")))?;
+    let requested_root = args.project_root.clone().unwrap_or_else(|| cwd.clone());
+    let requested_root = if requested_root.is_absolute() {
+        requested_root
+    } else {
+        cwd.join(requested_root)
+    };
+    let requested_root = requested_root.canonicalize().map_err(|err| {
+        TagsError::new(format!("Invalid path {}: {err}", requested_root.display()))
+    })?;
+    let (_, files) = workspace_php_files(&requested_root).map_err(TagsError::new)?;
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(args.format.default_output()));
+    let output = if output.is_absolute() {
+        output
+    } else {
+        cwd.join(output)
+    };
+    let output_dir = output
+        .parent()
+        .map(|parent| {
+            parent
+                .canonicalize()
+                .unwrap_or_else(|_| parent.to_path_buf())
+        })
+        .unwrap_or_else(|| cwd.clone());
+
+    let index = WorkspaceIndex::new();
+    let mut sources = Vec::with_capacity(files.len());
+    for path in &files {
+        let source = match read_php_source(path) {
+            Ok(source) => source.text,
+            Err(err) => {
+                tracing::warn!("Skipping {} in tags: {}", path.display(), err);
+                continue;
+            }
+        };
+        let mut parser = FileParser::new();
+        parser.parse_full(&source);
+        let Some(tree) = parser.tree() else {
+            continue;
+        };
+        let uri = path_to_uri(path).map_err(|err| TagsError::new(err.to_string()))?;
+        index.update_file(&uri, extract_file_symbols(tree, &source, &uri));
+        sources.push((tag_path(path, &output_dir), uri, source));
+    }
+
+    let mut tags = Vec::new();
+    for (file, uri, source) in &sources {
+        let Some(file_symbols) = index.file_symbols.get(uri) else {
+            continue;
+        };
+        tags.extend(
+            file_symbols
+                .symbols
+                .iter()
+                .filter_map(|symbol| symbol_tag(&index, symbol, file, source)),
+        );
+    }
+
+    let content = match args.format {
+        TagsFormat::Ctags => render_ctags(&mut tags),
+        TagsFormat::Etags => render_etags(&tags),
+    };
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            TagsError::new(format!("Failed to create {}: {err}", parent.display()))
+        })?;
+    }
+    std::fs::write(&output, content)
+        .map_err(|err| TagsError::new(format!("Failed to write {}: {err}", output.display())))?;
+
+    Ok((output, tags.len()))
+}
+
+/// Path of `path` relative to the tags file directory, or absolute when the
+/// file is outside it. Always uses `/` separators.
+fn tag_path(path: &Path, output_dir: &Path) -> String {
+    let path = path
+        .strip_prefix(output_dir)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf());
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn symbol_tag(
+    index: &WorkspaceIndex,
+    symbol: &SymbolInfo,
+    file: &str,
+    source: &str,
+) -> Option<Tag> {
+    let (line, start, _, end) = symbol.selection_range;
+    let line_offset = source
+        .split_inclusive('\n')
+        .take(line as usize)
+        .map(str::len)
+        .sum::<usize>();
+    let line_text = source[line_offset..]
+        .split('\n')
+        .next()?
+        .trim_end_matches('\r')
+        .to_string();
+    let name_end = (end as usize).max(start as usize).min(line_text.len());
+
+    let name = match symbol.kind {
+        PhpSymbolKind::Namespace => symbol.fqn.to_string(),
+        _ => symbol.name.trim_start_matches('$').to_string(),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    let scope = match symbol.kind {
+        PhpSymbolKind::Method
+        | PhpSymbolKind::Property
+        | PhpSymbolKind::ClassConstant
+        | PhpSymbolKind::EnumCase => symbol.parent_fqn.as_ref().map(|parent| {
+            let kind = index
+                .types
+                .get(parent)
+                .map(|parent| ctags_kind_name(parent.kind))
+                .unwrap_or("class");
+            (kind, parent.to_string())
+        }),
+        PhpSymbolKind::Namespace => None,
+        _ => symbol
+            .fqn
+            .rsplit_once('\\')
+            .map(|(namespace, _)| ("namespace", namespace.to_string()))
+            .filter(|(_, namespace)| !namespace.is_empty()),
+    };
+
+    Some(Tag {
+        name,
+        file: file.to_string(),
+        line,
+        line_text,
+        line_offset,
+        name_end,
+        kind: symbol.kind,
+        scope,
+    })
+}
+
+/// Single-letter kinds as used by Universal Ctags' PHP parser, plus `g` for
+/// enums and `e` for enum cases which it does not define.
+fn ctags_kind_letter(kind: PhpSymbolKind) -> char {
+    match kind {
+        PhpSymbolKind::Class => 'c',
+        PhpSymbolKind::Interface => 'i',
+        PhpSymbolKind::Trait => 't',
+        PhpSymbolKind::Enum => 'g',
+        PhpSymbolKind::Function | PhpSymbolKind::Method => 'f',
+        PhpSymbolKind::Property => 'v',
+        PhpSymbolKind::ClassConstant | PhpSymbolKind::GlobalConstant => 'd',
+        PhpSymbolKind::EnumCase => 'e',
+        PhpSymbolKind::Namespace => 'n',
+    }
+}
+
+fn ctags_kind_name(kind: PhpSymbolKind) -> &'static str {
+    match kind {
+        PhpSymbolKind::Interface => "interface",
+        PhpSymbolKind::Trait => "trait",
+        PhpSymbolKind::Enum => "enum",
+        _ => "class",
+    }
+}
+
+/// Extended-format ctags, sorted bytewise so editors can binary-search it.
+fn render_ctags(tags: &mut [Tag]) -> String {
+    let mut lines = tags
+        .iter()
+        .map(|tag| {
+            let pattern = tag.line_text.replace('\\', "\\\\").replace('/', "\\/");
+            let mut line = format!(
+                "{}\t{}\t/^{}$/;\"\t{}\tline:{}",
+                tag.name,
+                tag.file,
+                pattern,
+                ctags_kind_letter(tag.kind),
+                tag.line + 1
+            );
+            if let Some((kind, scope)) = &tag.scope {
+                line.push_str(&format!("\t{kind}:{scope}"));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.dedup();
+
+    let mut out = format!(
+        "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+         !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+         !_TAG_PROGRAM_NAME\tphp-lsp\t//\n\
+         !_TAG_PROGRAM_VERSION\t{}\t//\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    for line in lines {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Emacs `TAGS`: one section per file with explicit tag names, so members
+/// resolve by their short name regardless of the line prefix.
+fn render_etags(tags: &[Tag]) -> String {
+    let mut out = String::new();
+    let mut start = 0;
+    while start < tags.len() {
+        let file = &tags[start].file;
+        let end = tags[start..]
+            .iter()
+            .position(|tag| &tag.file != file)
+            .map_or(tags.len(), |offset| start + offset);
+        let mut section = String::new();
+        for tag in &tags[start..end] {
+            section.push_str(&tag.line_text[..tag.name_end]);
+            section.push('\u{7f}');
+            section.push_str(&tag.name);
+            section.push('\u{1}');
+            section.push_str(&format!("{},{}\n", tag.line + 1, tag.line_offset));
+        }
+        out.push_str(&format!("\u{c}\n{},{}\n{}", file, section.len(), section));
+        start = end;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::temp_project::TempProject;
+
+    const CART: &str = "<?php\nnamespace Shop;\n\ninterface Priced {}\n\nclass Cart implements Priced {\n    public const LIMIT = 10;\n    private array $items = [];\n    public function add(string $sku): void {}\n}\n\nfunction cart_total(Cart $cart): int { return 0; }\n";
+
+    #[test]
+    fn parse_tags_args_accepts_format_output_and_etags_flag() {
+        let args = parse_tags_args(vec![
+            "--format=etags".to_string(),
+            "-f".to_string(),
+            "TAGS".to_string(),
+            "project".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(args.format, TagsFormat::Etags);
+        assert_eq!(args.output, Some(PathBuf::from("TAGS")));
+        assert_eq!(args.project_root, Some(PathBuf::from("project")));
+
+        assert_eq!(
+            parse_tags_args(vec!["-e".to_string()]).unwrap().format,
+            TagsFormat::Etags
+        );
+        assert!(parse_tags_args(vec!["--format".to_string(), "gtags".to_string()]).is_err());
+    }
+
+    #[test]
+    fn ctags_output_is_sorted_and_scoped() {
+        let project = TempProject::new("tags", &[("src/Cart.php", CART)]);
+        let root = project.root().to_path_buf();
+        let output = root.join("tags");
+
+        let (_, count) = run_tags(&TagsArgs {
+            project_root: Some(root.clone()),
+            format: TagsFormat::Ctags,
+            output: Some(output.clone()),
+        })
+        .unwrap();
+        let content = std::fs::read_to_string(&output).unwrap();
+        let tags = content
+            .lines()
+            .filter(|line| !line.starts_with("!_TAG_"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(tags.len(), count);
+        let mut sorted = tags.clone();
+        sorted.sort();
+        assert_eq!(tags, sorted);
+        assert!(tags.contains(
+            &"Cart\tsrc/Cart.php\t/^class Cart implements Priced {$/;\"\tc\tline:6\tnamespace:Shop"
+        ));
+        assert!(tags.contains(
+            &"add\tsrc/Cart.php\t/^    public function add(string $sku): void {}$/;\"\tf\tline:9\tclass:Shop\\Cart"
+        ));
+        assert!(tags.iter().any(
+            |tag| tag.starts_with("items\t") && tag.ends_with("\tv\tline:8\tclass:Shop\\Cart")
+        ));
+        assert!(tags
+            .iter()
+            .any(|tag| tag.starts_with("Priced\t") && tag.contains("\ti\tline:4\t")));
+    }
+
+    #[test]
+    fn etags_sections_record_line_and_byte_offsets() {
+        let project = TempProject::new("tags", &[("src/Cart.php", CART)]);
+        let root = project.root().to_path_buf();
+        let output = root.join("TAGS");
+
+        run_tags(&TagsArgs {
+            project_root: Some(root.clone()),
+            format: TagsFormat::Etags,
+            output: Some(output.clone()),
+        })
+        .unwrap();
+        let content = std::fs::read_to_string(&output).unwrap();
+
+        let (header, section) = content
+            .strip_prefix("\u{c}\n")
+            .unwrap()
+            .split_once('\n')
+            .unwrap();
+        assert_eq!(header, format!("src/Cart.php,{}", section.len()));
+        let add_offset = CART.find("    public function add").unwrap();
+        assert!(
+            section.contains(&format!(
+                "    public function add\u{7f}add\u{1}9,{add_offset}\n"
+            )),
+            "{section:?}"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use php_lsp_parser::utf16::{utf16_col_to_byte, Utf16LineIndex};
+use php_lsp_parser::position::{lsp_col_to_byte, LspLineIndex, PositionEncoding};
 use std::collections::HashSet;
 use tower_lsp::ls_types::{
     Diagnostic, DiagnosticSeverity, Location, NumberOrString, Position, Range, SemanticToken, Uri,
@@ -91,18 +91,27 @@ impl TemplateDocument {
         })
     }
 
-    pub(crate) fn map_original_position_to_virtual(&self, position: Position) -> Option<Position> {
-        let original_offset = byte_offset_for_position(&self.original_source, position)?;
+    pub(crate) fn map_original_position_to_virtual(
+        &self,
+        position: Position,
+        encoding: PositionEncoding,
+    ) -> Option<Position> {
+        let original_offset = byte_offset_for_position(&self.original_source, position, encoding)?;
         let virtual_offset = self.source_map.original_to_virtual(original_offset)?;
         Some(position_for_byte_offset(
             &self.virtual_source,
             virtual_offset,
+            encoding,
         ))
     }
 
-    pub(crate) fn map_virtual_range_to_original(&self, range: Range) -> Option<Range> {
-        let virtual_start = byte_offset_for_position(&self.virtual_source, range.start)?;
-        let virtual_end = byte_offset_for_position(&self.virtual_source, range.end)?;
+    pub(crate) fn map_virtual_range_to_original(
+        &self,
+        range: Range,
+        encoding: PositionEncoding,
+    ) -> Option<Range> {
+        let virtual_start = byte_offset_for_position(&self.virtual_source, range.start, encoding)?;
+        let virtual_end = byte_offset_for_position(&self.virtual_source, range.end, encoding)?;
         let (original_start, original_end) = self
             .source_map
             .virtual_range_to_original(virtual_start, virtual_end)?;
@@ -110,24 +119,30 @@ impl TemplateDocument {
             &self.original_source,
             original_start,
             original_end,
+            encoding,
         ))
     }
 
-    pub(crate) fn map_virtual_position_to_original(&self, position: Position) -> Option<Position> {
-        let virtual_offset = byte_offset_for_position(&self.virtual_source, position)?;
+    pub(crate) fn map_virtual_position_to_original(
+        &self,
+        position: Position,
+        encoding: PositionEncoding,
+    ) -> Option<Position> {
+        let virtual_offset = byte_offset_for_position(&self.virtual_source, position, encoding)?;
         let (original_offset, _) = self
             .source_map
             .virtual_range_to_original(virtual_offset, virtual_offset)?;
         Some(position_for_byte_offset(
             &self.original_source,
             original_offset,
+            encoding,
         ))
     }
 
-    pub(crate) fn syntax_diagnostics(&self) -> Vec<Diagnostic> {
+    pub(crate) fn syntax_diagnostics(&self, encoding: PositionEncoding) -> Vec<Diagnostic> {
         match self.kind {
             TemplateKind::Blade => Vec::new(),
-            TemplateKind::Twig => twig_syntax_diagnostics(&self.original_source),
+            TemplateKind::Twig => twig_syntax_diagnostics(&self.original_source, encoding),
         }
     }
 
@@ -135,10 +150,11 @@ impl TemplateDocument {
         &self,
         diagnostics: Vec<Diagnostic>,
         diagnostics_mode_is_off: bool,
+        encoding: PositionEncoding,
     ) -> Vec<Diagnostic> {
-        let mut diagnostics = self.map_safe_diagnostics_to_original(diagnostics);
+        let mut diagnostics = self.map_safe_diagnostics_to_original(diagnostics, encoding);
         if !diagnostics_mode_is_off {
-            diagnostics.extend(self.syntax_diagnostics());
+            diagnostics.extend(self.syntax_diagnostics(encoding));
         }
         diagnostics
     }
@@ -146,6 +162,7 @@ impl TemplateDocument {
     pub(crate) fn map_safe_diagnostics_to_original(
         &self,
         diagnostics: Vec<Diagnostic>,
+        encoding: PositionEncoding,
     ) -> Vec<Diagnostic> {
         diagnostics
             .into_iter()
@@ -153,15 +170,20 @@ impl TemplateDocument {
                 if !self.is_safe_template_diagnostic(&diagnostic) {
                     return None;
                 }
-                diagnostic.range = self.map_virtual_range_to_original_exact(diagnostic.range)?;
+                diagnostic.range =
+                    self.map_virtual_range_to_original_exact(diagnostic.range, encoding)?;
                 Some(diagnostic)
             })
             .collect()
     }
 
-    fn map_virtual_range_to_original_exact(&self, range: Range) -> Option<Range> {
-        let virtual_start = byte_offset_for_position(&self.virtual_source, range.start)?;
-        let virtual_end = byte_offset_for_position(&self.virtual_source, range.end)?;
+    fn map_virtual_range_to_original_exact(
+        &self,
+        range: Range,
+        encoding: PositionEncoding,
+    ) -> Option<Range> {
+        let virtual_start = byte_offset_for_position(&self.virtual_source, range.start, encoding)?;
+        let virtual_end = byte_offset_for_position(&self.virtual_source, range.end, encoding)?;
         let (original_start, original_end) = self
             .source_map
             .virtual_range_to_original_exact(virtual_start, virtual_end)?;
@@ -169,6 +191,7 @@ impl TemplateDocument {
             &self.original_source,
             original_start,
             original_end,
+            encoding,
         ))
     }
 
@@ -182,8 +205,8 @@ impl TemplateDocument {
             Some("php-lsp.undefinedVariable") => false,
             Some("php-lsp.unknownClass")
             | Some("php-lsp.argumentCountMismatch")
-            | Some("php-lsp.typeCompatibility") => true,
-            Some("php-lsp.members") => is_unknown_member_diagnostic_message(message),
+            | Some("php-lsp.typeMismatch") => true,
+            Some("php-lsp.unknownMember") => is_unknown_member_diagnostic_message(message),
             Some("php-lsp.unknownSymbols") => is_unknown_symbol_diagnostic_message(message),
             _ => {
                 is_unknown_member_diagnostic_message(message)
@@ -196,15 +219,19 @@ impl TemplateDocument {
     pub(crate) fn map_semantic_tokens_to_original(
         &self,
         tokens: Vec<SemanticToken>,
+        encoding: PositionEncoding,
     ) -> Vec<SemanticToken> {
         let mut absolute = Vec::new();
         for token in decode_semantic_tokens(&tokens) {
             let start = Position::new(token.line, token.start);
             let end = Position::new(token.line, token.start.saturating_add(token.length));
-            let Some(virtual_start) = byte_offset_for_position(&self.virtual_source, start) else {
+            let Some(virtual_start) =
+                byte_offset_for_position(&self.virtual_source, start, encoding)
+            else {
                 continue;
             };
-            let Some(virtual_end) = byte_offset_for_position(&self.virtual_source, end) else {
+            let Some(virtual_end) = byte_offset_for_position(&self.virtual_source, end, encoding)
+            else {
                 continue;
             };
             let Some((original_start, original_end)) = self
@@ -220,6 +247,7 @@ impl TemplateDocument {
                 token.token_type,
                 token.token_modifiers_bitset,
                 &mut absolute,
+                encoding,
             );
         }
 
@@ -231,6 +259,7 @@ impl TemplateDocument {
                 token.token_type,
                 token.token_modifiers_bitset,
                 &mut absolute,
+                encoding,
             );
         }
 
@@ -241,18 +270,24 @@ impl TemplateDocument {
         &self,
         tokens: Vec<SemanticToken>,
         requested_range: Range,
+        encoding: PositionEncoding,
     ) -> Vec<SemanticToken> {
         let absolute: Vec<_> =
-            decode_semantic_tokens(&self.map_semantic_tokens_to_original(tokens))
+            decode_semantic_tokens(&self.map_semantic_tokens_to_original(tokens, encoding))
                 .into_iter()
                 .filter(|token| semantic_token_overlaps_range(*token, requested_range))
                 .collect();
         encode_semantic_tokens(&absolute)
     }
 
-    pub(crate) fn apply_change(&self, range: Option<Range>, text: &str) -> Self {
+    pub(crate) fn apply_change(
+        &self,
+        range: Option<Range>,
+        text: &str,
+        encoding: PositionEncoding,
+    ) -> Self {
         let mut source = self.original_source.clone();
-        apply_text_change(&mut source, range, text);
+        apply_text_change(&mut source, range, text, encoding);
         match self.kind {
             TemplateKind::Blade => preprocess_blade_template(&source),
             TemplateKind::Twig => preprocess_twig_template(&source, &self.twig_variable_types),
@@ -262,11 +297,12 @@ impl TemplateDocument {
     pub(crate) fn twig_template_path_context_at_position(
         &self,
         position: Position,
+        encoding: PositionEncoding,
     ) -> Option<TwigTemplatePathContext> {
         if self.kind != TemplateKind::Twig {
             return None;
         }
-        twig_template_path_context_at_position(&self.original_source, position)
+        twig_template_path_context_at_position(&self.original_source, position, encoding)
     }
 }
 
@@ -723,7 +759,7 @@ pub(crate) fn preprocess_twig_template(
     }
 }
 
-fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
+fn twig_syntax_diagnostics(source: &str, encoding: PositionEncoding) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
     let mut block_stack: Vec<TwigBlock> = Vec::new();
     let mut offset = 0usize;
@@ -740,6 +776,7 @@ fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
                         offset,
                         (offset + 2).min(source.len()),
                         "Unclosed Twig comment",
+                        encoding,
                     ));
                     break;
                 }
@@ -758,6 +795,7 @@ fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
                         offset,
                         (offset + 2).min(source.len()),
                         "Unclosed Twig expression",
+                        encoding,
                     ));
                     break;
                 }
@@ -772,6 +810,7 @@ fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
                     offset,
                     (offset + 2).min(source.len()),
                     "Unclosed Twig tag",
+                    encoding,
                 ));
                 break;
             };
@@ -790,6 +829,7 @@ fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
                         content.content_start,
                         content.content_start + "verbatim".len(),
                         "Unclosed Twig `verbatim` block, expected `endverbatim`",
+                        encoding,
                     ));
                     break;
                 }
@@ -802,6 +842,7 @@ fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
                 content.content_end,
                 &mut block_stack,
                 &mut diagnostics,
+                encoding,
             );
             offset = content.close_end;
             continue;
@@ -823,6 +864,7 @@ fn twig_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
                 "Unclosed Twig `{}` block, expected `{}`",
                 block.name, block.end_tag
             ),
+            encoding,
         ));
     }
 
@@ -844,6 +886,7 @@ fn check_twig_block_tag(
     content_end: usize,
     block_stack: &mut Vec<TwigBlock>,
     diagnostics: &mut Vec<Diagnostic>,
+    encoding: PositionEncoding,
 ) {
     let Some((name_start, name_end)) = twig_tag_name_range(source, content_start, content_end)
     else {
@@ -852,6 +895,7 @@ fn check_twig_block_tag(
             tag_start,
             (tag_start + 2).min(source.len()),
             "Missing Twig tag name",
+            encoding,
         ));
         return;
     };
@@ -861,6 +905,7 @@ fn check_twig_block_tag(
             tag_start,
             (tag_start + 2).min(source.len()),
             "Missing Twig tag name",
+            encoding,
         ));
         return;
     }
@@ -893,6 +938,7 @@ fn check_twig_block_tag(
             name_start,
             tag_range_end,
             &format!("Unexpected Twig `{name}` tag"),
+            encoding,
         ));
         return;
     }
@@ -904,6 +950,7 @@ fn check_twig_block_tag(
                 name_start,
                 tag_range_end,
                 &format!("Unexpected Twig `{name}` tag"),
+                encoding,
             ));
             return;
         };
@@ -916,6 +963,7 @@ fn check_twig_block_tag(
                     "Mismatched Twig `{name}` tag, expected `{}` for `{}` block",
                     block.end_tag, block.name
                 ),
+                encoding,
             ));
         }
     }
@@ -1113,9 +1161,15 @@ fn find_twig_delimiter_close(source: &str, mut offset: usize, delimiter: &str) -
     None
 }
 
-fn twig_syntax_diagnostic(source: &str, start: usize, end: usize, message: &str) -> Diagnostic {
+fn twig_syntax_diagnostic(
+    source: &str,
+    start: usize,
+    end: usize,
+    message: &str,
+    encoding: PositionEncoding,
+) -> Diagnostic {
     Diagnostic {
-        range: range_for_byte_offsets(source, start, end.max(start)),
+        range: range_for_byte_offsets(source, start, end.max(start), encoding),
         severity: Some(DiagnosticSeverity::ERROR),
         code: Some(NumberOrString::String("php-lsp.twigSyntax".to_string())),
         source: Some("php-lsp".to_string()),
@@ -2250,8 +2304,9 @@ fn find_quoted_string_end(source: &str, start: usize, end: usize, quote: char) -
 fn twig_template_path_context_at_position(
     source: &str,
     position: Position,
+    encoding: PositionEncoding,
 ) -> Option<TwigTemplatePathContext> {
-    let offset = byte_offset_for_position(source, position)?;
+    let offset = byte_offset_for_position(source, position, encoding)?;
     let bounds = template_string_literal_bounds_at_offset(source, offset)?;
     let (tag_start, tag_end) = twig_tag_bounds_containing(source, bounds.quote_start)?;
     let content = twig_delimiter_content(source, tag_start, "{%", "%}")?;
@@ -2525,45 +2580,59 @@ fn find_matching_paren(source: &str, open: usize) -> Option<usize> {
     None
 }
 
-pub(crate) fn apply_text_change(source: &mut String, range: Option<Range>, text: &str) {
+pub(crate) fn apply_text_change(
+    source: &mut String,
+    range: Option<Range>,
+    text: &str,
+    encoding: PositionEncoding,
+) {
     let Some(range) = range else {
         source.clear();
         source.push_str(text);
         return;
     };
-    let Some(start) = byte_offset_for_position(source, range.start) else {
+    let Some(start) = byte_offset_for_position(source, range.start, encoding) else {
         return;
     };
-    let Some(end) = byte_offset_for_position(source, range.end) else {
+    let Some(end) = byte_offset_for_position(source, range.end, encoding) else {
         return;
     };
     source.replace_range(start.min(end)..end.max(start), text);
 }
 
-fn byte_offset_for_position(source: &str, position: Position) -> Option<usize> {
+fn byte_offset_for_position(
+    source: &str,
+    position: Position,
+    encoding: PositionEncoding,
+) -> Option<usize> {
     let line_start = *line_start_offsets(source).get(position.line as usize)?;
-    let byte_col = utf16_col_to_byte(source, position.line, position.character) as usize;
+    let byte_col = lsp_col_to_byte(source, position.line, position.character, encoding) as usize;
     Some((line_start + byte_col).min(source.len()))
 }
 
-fn position_for_byte_offset(source: &str, offset: usize) -> Position {
+fn position_for_byte_offset(source: &str, offset: usize, encoding: PositionEncoding) -> Position {
     let offsets = line_start_offsets(source);
     let line_idx = offsets
         .partition_point(|line_start| *line_start <= offset)
         .saturating_sub(1);
     let line_start = offsets.get(line_idx).copied().unwrap_or(0);
     let byte_col = offset.saturating_sub(line_start) as u32;
-    let utf16_index = Utf16LineIndex::new(source);
+    let line_index = LspLineIndex::new(source, encoding);
     Position::new(
         line_idx as u32,
-        utf16_index.byte_col_to_utf16(line_idx as u32, byte_col),
+        line_index.byte_col_to_lsp(line_idx as u32, byte_col),
     )
 }
 
-fn range_for_byte_offsets(source: &str, start: usize, end: usize) -> Range {
+fn range_for_byte_offsets(
+    source: &str,
+    start: usize,
+    end: usize,
+    encoding: PositionEncoding,
+) -> Range {
     Range {
-        start: position_for_byte_offset(source, start),
-        end: position_for_byte_offset(source, end),
+        start: position_for_byte_offset(source, start, encoding),
+        end: position_for_byte_offset(source, end, encoding),
     }
 }
 
@@ -2655,12 +2724,13 @@ fn push_original_semantic_token(
     token_type: u32,
     token_modifiers_bitset: u32,
     tokens: &mut Vec<AbsoluteSemanticToken>,
+    encoding: PositionEncoding,
 ) {
     if original_end <= original_start {
         return;
     }
-    let start = position_for_byte_offset(source, original_start);
-    let end = position_for_byte_offset(source, original_end);
+    let start = position_for_byte_offset(source, original_start, encoding);
+    let end = position_for_byte_offset(source, original_end, encoding);
     if start.line != end.line {
         let line_offsets = line_start_offsets(source);
         for line in start.line..=end.line {
@@ -2687,6 +2757,7 @@ fn push_original_semantic_token(
                 token_type,
                 token_modifiers_bitset,
                 tokens,
+                encoding,
             );
         }
         return;
@@ -2724,10 +2795,14 @@ mod tests {
 
         let original_position = Position::new(0, 8);
         let virtual_position = doc
-            .map_original_position_to_virtual(original_position)
+            .map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
             .expect("template expression position should map");
-        let virtual_offset = byte_offset_for_position(doc.virtual_source(), virtual_position)
-            .expect("virtual position offset");
+        let virtual_offset = byte_offset_for_position(
+            doc.virtual_source(),
+            virtual_position,
+            PositionEncoding::Utf16,
+        )
+        .expect("virtual position offset");
         assert_eq!(
             doc.virtual_source()
                 .get(virtual_offset..virtual_offset + "$user".len()),
@@ -2747,7 +2822,7 @@ mod tests {
         assert!(doc.virtual_source().contains("<?php endforeach; ?>"));
         assert!(doc.virtual_source().contains("<?php endif; ?>"));
 
-        let tokens = doc.map_semantic_tokens_to_original(Vec::new());
+        let tokens = doc.map_semantic_tokens_to_original(Vec::new(), PositionEncoding::Utf16);
         assert!(
             tokens.iter().any(|token| token.token_type == TOKEN_KEYWORD),
             "expected directive keyword semantic tokens"
@@ -2763,18 +2838,18 @@ mod tests {
         let doc = preprocess_blade_template("<div>{{ $user }}</div>");
         let generated_prefix = Range::new(Position::new(0, 0), Position::new(0, 5));
         assert!(doc
-            .map_virtual_range_to_original(generated_prefix)
+            .map_virtual_range_to_original(generated_prefix, PositionEncoding::Utf16)
             .is_none());
 
         let user_virtual = doc
-            .map_original_position_to_virtual(Position::new(0, 8))
+            .map_original_position_to_virtual(Position::new(0, 8), PositionEncoding::Utf16)
             .expect("mapped user position");
         let user_range = Range::new(
             user_virtual,
             Position::new(user_virtual.line, user_virtual.character + 5),
         );
         let original = doc
-            .map_virtual_range_to_original(user_range)
+            .map_virtual_range_to_original(user_range, PositionEncoding::Utf16)
             .expect("mapped variable range");
         assert_eq!(original.start, Position::new(0, 8));
         assert_eq!(original.end, Position::new(0, 13));
@@ -2794,10 +2869,14 @@ mod tests {
 
         let original_position = Position::new(0, 7);
         let virtual_position = doc
-            .map_original_position_to_virtual(original_position)
+            .map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
             .expect("Twig variable should map to virtual PHP variable");
-        let virtual_offset = byte_offset_for_position(doc.virtual_source(), virtual_position)
-            .expect("virtual position offset");
+        let virtual_offset = byte_offset_for_position(
+            doc.virtual_source(),
+            virtual_position,
+            PositionEncoding::Utf16,
+        )
+        .expect("virtual position offset");
         assert_eq!(
             doc.virtual_source()
                 .get(virtual_offset..virtual_offset + "$user".len()),
@@ -2818,14 +2897,18 @@ mod tests {
         assert!(doc.virtual_source().contains("$user->name"));
         assert!(doc.virtual_source().contains("foreach ($users as $item)"));
         assert!(doc.virtual_source().contains("$item->name"));
-        assert!(doc.syntax_diagnostics().is_empty());
+        assert!(doc.syntax_diagnostics(PositionEncoding::Utf16).is_empty());
 
         let original_position = Position::new(0, 8);
         let virtual_position = doc
-            .map_original_position_to_virtual(original_position)
+            .map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
             .expect("Twig whitespace-control variable should map");
-        let virtual_offset = byte_offset_for_position(doc.virtual_source(), virtual_position)
-            .expect("virtual position offset");
+        let virtual_offset = byte_offset_for_position(
+            doc.virtual_source(),
+            virtual_position,
+            PositionEncoding::Utf16,
+        )
+        .expect("virtual position offset");
         assert_eq!(
             doc.virtual_source()
                 .get(virtual_offset..virtual_offset + "$user".len()),
@@ -2843,10 +2926,10 @@ mod tests {
                 shape_definitions: Vec::new(),
             }],
         );
-        assert!(doc.syntax_diagnostics().is_empty());
+        assert!(doc.syntax_diagnostics(PositionEncoding::Utf16).is_empty());
         assert_eq!(doc.virtual_source().matches("$user->name").count(), 1);
 
-        let tokens = doc.map_semantic_tokens_to_original(Vec::new());
+        let tokens = doc.map_semantic_tokens_to_original(Vec::new(), PositionEncoding::Utf16);
         assert!(
             tokens.iter().any(|token| token.token_type == TOKEN_KEYWORD),
             "expected verbatim keyword semantic tokens"
@@ -2863,7 +2946,7 @@ mod tests {
                 shape_definitions: Vec::new(),
             }],
         );
-        assert!(doc.syntax_diagnostics().is_empty());
+        assert!(doc.syntax_diagnostics(PositionEncoding::Utf16).is_empty());
         assert_eq!(doc.virtual_source().matches("$user->name").count(), 1);
     }
 
@@ -2877,10 +2960,10 @@ mod tests {
                 shape_definitions: Vec::new(),
             }],
         );
-        assert!(doc.syntax_diagnostics().is_empty());
+        assert!(doc.syntax_diagnostics(PositionEncoding::Utf16).is_empty());
         assert_eq!(doc.virtual_source().matches("$user->name").count(), 1);
 
-        let tokens = doc.map_semantic_tokens_to_original(Vec::new());
+        let tokens = doc.map_semantic_tokens_to_original(Vec::new(), PositionEncoding::Utf16);
         assert!(
             tokens.iter().any(|token| token.token_type == TOKEN_COMMENT),
             "expected quoted Twig comment semantic token"
@@ -2897,7 +2980,7 @@ mod tests {
                 shape_definitions: Vec::new(),
             }],
         );
-        assert!(doc.syntax_diagnostics().is_empty());
+        assert!(doc.syntax_diagnostics(PositionEncoding::Utf16).is_empty());
         assert!(!doc.virtual_source().contains("function input"));
         assert!(!doc.virtual_source().contains("$name"));
         assert!(doc.virtual_source().contains("$user->name"));
@@ -2907,7 +2990,7 @@ mod tests {
     fn twig_macro_body_is_still_checked_for_syntax_errors() {
         let doc = preprocess_twig_template("{% macro input(name) %}{{ name }{% endmacro %}\n", &[]);
         let messages: Vec<_> = doc
-            .syntax_diagnostics()
+            .syntax_diagnostics(PositionEncoding::Utf16)
             .into_iter()
             .map(|diagnostic| diagnostic.message)
             .collect();
@@ -2929,7 +3012,7 @@ mod tests {
                 shape_definitions: Vec::new(),
             }],
         );
-        assert!(doc.syntax_diagnostics().is_empty());
+        assert!(doc.syntax_diagnostics(PositionEncoding::Utf16).is_empty());
         assert!(doc.virtual_source().contains("$user->name"));
     }
 
@@ -2943,12 +3026,12 @@ mod tests {
         assert!(doc.virtual_source().contains("$item->name"));
 
         let context = doc
-            .twig_template_path_context_at_position(Position::new(3, 23))
+            .twig_template_path_context_at_position(Position::new(3, 23), PositionEncoding::Utf16)
             .expect("include path context");
         assert_eq!(context.key, "shared/card.html.twig");
         assert_eq!(context.prefix, "shared/card");
 
-        let tokens = doc.map_semantic_tokens_to_original(Vec::new());
+        let tokens = doc.map_semantic_tokens_to_original(Vec::new(), PositionEncoding::Utf16);
         assert!(
             tokens.iter().any(|token| token.token_type == TOKEN_KEYWORD),
             "expected Twig keyword semantic tokens"
@@ -3048,9 +3131,10 @@ mod tests {
 
         for needle in ["user is defined", "users|filter", "user['name']"] {
             let original_offset = source.find(needle).expect("fixture needle");
-            let original_position = position_for_byte_offset(source, original_offset);
+            let original_position =
+                position_for_byte_offset(source, original_offset, PositionEncoding::Utf16);
             assert!(
-                doc.map_original_position_to_virtual(original_position)
+                doc.map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
                     .is_some(),
                 "unsupported Twig expression root variable `{needle}` should map to no-op virtual PHP"
             );
@@ -3058,9 +3142,10 @@ mod tests {
 
         for needle in ["upper", "attribute", "forms.input", "_self.card", "??"] {
             let original_offset = source.find(needle).expect("fixture needle");
-            let original_position = position_for_byte_offset(source, original_offset);
+            let original_position =
+                position_for_byte_offset(source, original_offset, PositionEncoding::Utf16);
             assert!(
-                doc.map_original_position_to_virtual(original_position)
+                doc.map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
                     .is_none(),
                 "unsupported Twig expression `{needle}` should not map to virtual PHP"
             );
@@ -3114,12 +3199,17 @@ mod tests {
             "user.createdAt",
         ] {
             let original_offset = source.find(needle).expect("fixture member chain");
-            let original_position = position_for_byte_offset(source, original_offset);
+            let original_position =
+                position_for_byte_offset(source, original_offset, PositionEncoding::Utf16);
             let virtual_position = doc
-                .map_original_position_to_virtual(original_position)
+                .map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
                 .unwrap_or_else(|| panic!("member chain `{needle}` should map"));
-            let virtual_offset = byte_offset_for_position(doc.virtual_source(), virtual_position)
-                .expect("virtual position offset");
+            let virtual_offset = byte_offset_for_position(
+                doc.virtual_source(),
+                virtual_position,
+                PositionEncoding::Utf16,
+            )
+            .expect("virtual position offset");
             assert_eq!(
                 doc.virtual_source()
                     .get(virtual_offset..virtual_offset + "$user".len()),
@@ -3131,13 +3221,17 @@ mod tests {
             .find("user.})")
             .map(|offset| offset + "user.".len())
             .expect("fixture trailing member access");
-        let trailing_dot_position = position_for_byte_offset(source, trailing_dot_offset);
+        let trailing_dot_position =
+            position_for_byte_offset(source, trailing_dot_offset, PositionEncoding::Utf16);
         let trailing_virtual_position = doc
-            .map_original_position_to_virtual(trailing_dot_position)
+            .map_original_position_to_virtual(trailing_dot_position, PositionEncoding::Utf16)
             .expect("trailing member access cursor should map");
-        let trailing_virtual_offset =
-            byte_offset_for_position(doc.virtual_source(), trailing_virtual_position)
-                .expect("trailing virtual offset");
+        let trailing_virtual_offset = byte_offset_for_position(
+            doc.virtual_source(),
+            trailing_virtual_position,
+            PositionEncoding::Utf16,
+        )
+        .expect("trailing virtual offset");
         assert_eq!(
             doc.virtual_source()
                 .get(trailing_virtual_offset.saturating_sub("->".len())..trailing_virtual_offset),
@@ -3147,9 +3241,10 @@ mod tests {
 
         for needle in ["upper", "path", "date"] {
             let original_offset = source.find(needle).expect("fixture unsupported token");
-            let original_position = position_for_byte_offset(source, original_offset);
+            let original_position =
+                position_for_byte_offset(source, original_offset, PositionEncoding::Utf16);
             assert!(
-                doc.map_original_position_to_virtual(original_position)
+                doc.map_original_position_to_virtual(original_position, PositionEncoding::Utf16)
                     .is_none(),
                 "unsupported Twig token `{needle}` should stay unmapped"
             );
@@ -3168,7 +3263,7 @@ mod tests {
         );
         let generated_prelude = Range::new(Position::new(1, 0), Position::new(1, 5));
         assert!(doc
-            .map_virtual_range_to_original(generated_prelude)
+            .map_virtual_range_to_original(generated_prelude, PositionEncoding::Utf16)
             .is_none());
     }
 
@@ -3186,21 +3281,31 @@ mod tests {
             .source_map
             .original_to_virtual(original_end)
             .expect("member end should map");
-        let virtual_range =
-            range_for_byte_offsets(doc.virtual_source(), virtual_start, virtual_end);
+        let virtual_range = range_for_byte_offsets(
+            doc.virtual_source(),
+            virtual_start,
+            virtual_end,
+            PositionEncoding::Utf16,
+        );
 
         let unknown_member = Diagnostic {
             range: virtual_range,
             source: Some("php-lsp".to_string()),
-            code: Some(NumberOrString::String("php-lsp.members".to_string())),
+            code: Some(NumberOrString::String("php-lsp.unknownMember".to_string())),
             message: "Unknown method: User::missing".to_string(),
             ..Default::default()
         };
-        let mapped = doc.map_safe_diagnostics_to_original(vec![unknown_member]);
+        let mapped =
+            doc.map_safe_diagnostics_to_original(vec![unknown_member], PositionEncoding::Utf16);
         assert_eq!(mapped.len(), 1);
         assert_eq!(
             mapped[0].range,
-            range_for_byte_offsets(source, original_start, original_end)
+            range_for_byte_offsets(
+                source,
+                original_start,
+                original_end,
+                PositionEncoding::Utf16
+            )
         );
 
         let syntax = Diagnostic {
@@ -3210,7 +3315,7 @@ mod tests {
             ..Default::default()
         };
         assert!(doc
-            .map_safe_diagnostics_to_original(vec![syntax])
+            .map_safe_diagnostics_to_original(vec![syntax], PositionEncoding::Utf16)
             .is_empty());
     }
 
@@ -3221,13 +3326,13 @@ mod tests {
         let diagnostic = Diagnostic {
             range: generated_prefix,
             source: Some("php-lsp".to_string()),
-            code: Some(NumberOrString::String("php-lsp.members".to_string())),
+            code: Some(NumberOrString::String("php-lsp.unknownMember".to_string())),
             message: "Unknown property: User::$name".to_string(),
             ..Default::default()
         };
 
         assert!(doc
-            .map_safe_diagnostics_to_original(vec![diagnostic])
+            .map_safe_diagnostics_to_original(vec![diagnostic], PositionEncoding::Utf16)
             .is_empty());
     }
 
@@ -3246,15 +3351,20 @@ mod tests {
             .original_to_virtual(original_end)
             .expect("property end should map");
         let diagnostic = Diagnostic {
-            range: range_for_byte_offsets(doc.virtual_source(), virtual_start, virtual_end),
+            range: range_for_byte_offsets(
+                doc.virtual_source(),
+                virtual_start,
+                virtual_end,
+                PositionEncoding::Utf16,
+            ),
             source: Some("php-lsp".to_string()),
-            code: Some(NumberOrString::String("php-lsp.members".to_string())),
+            code: Some(NumberOrString::String("php-lsp.unknownMember".to_string())),
             message: "Unknown property: User::$missing".to_string(),
             ..Default::default()
         };
 
         assert!(doc
-            .map_safe_diagnostics_to_original(vec![diagnostic])
+            .map_safe_diagnostics_to_original(vec![diagnostic], PositionEncoding::Utf16)
             .is_empty());
     }
 
@@ -3275,7 +3385,12 @@ mod tests {
             .original_to_virtual(original_end)
             .expect("variable end should map");
         let diagnostic = Diagnostic {
-            range: range_for_byte_offsets(doc.virtual_source(), virtual_start, virtual_end),
+            range: range_for_byte_offsets(
+                doc.virtual_source(),
+                virtual_start,
+                virtual_end,
+                PositionEncoding::Utf16,
+            ),
             source: Some("php-lsp".to_string()),
             code: Some(NumberOrString::String(
                 "php-lsp.undefinedVariable".to_string(),
@@ -3285,7 +3400,7 @@ mod tests {
         };
 
         assert!(doc
-            .map_safe_diagnostics_to_original(vec![diagnostic])
+            .map_safe_diagnostics_to_original(vec![diagnostic], PositionEncoding::Utf16)
             .is_empty());
     }
 
@@ -3311,21 +3426,25 @@ mod tests {
             .original_to_virtual(original_end)
             .expect("literal end should map");
         let diagnostic = Diagnostic {
-            range: range_for_byte_offsets(doc.virtual_source(), virtual_start, virtual_end),
+            range: range_for_byte_offsets(doc.virtual_source(), virtual_start, virtual_end, PositionEncoding::Utf16),
             source: Some("php-lsp".to_string()),
-            code: Some(NumberOrString::String(
-                "php-lsp.typeCompatibility".to_string(),
-            )),
+            code: Some(NumberOrString::String("php-lsp.typeMismatch".to_string())),
             message: "Type mismatch for App\\Entity\\User::setAge argument $age: expected string, got int"
                 .to_string(),
             ..Default::default()
         };
 
-        let mapped = doc.map_safe_diagnostics_to_original(vec![diagnostic]);
+        let mapped =
+            doc.map_safe_diagnostics_to_original(vec![diagnostic], PositionEncoding::Utf16);
         assert_eq!(mapped.len(), 1);
         assert_eq!(
             mapped[0].range,
-            range_for_byte_offsets(source, original_start, original_end)
+            range_for_byte_offsets(
+                source,
+                original_start,
+                original_end,
+                PositionEncoding::Utf16
+            )
         );
     }
 }
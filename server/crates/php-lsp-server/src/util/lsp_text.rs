@@ -1,4 +1,4 @@
-use php_lsp_parser::utf16::{range_byte_to_utf16, utf16_col_to_byte};
+use php_lsp_parser::position::{lsp_col_to_byte, range_byte_to_lsp, PositionEncoding};
 use tower_lsp::ls_types::{Position, Range};
 
 pub(crate) fn range_from_lsp_tuple(range: (u32, u32, u32, u32)) -> Range {
@@ -8,13 +8,21 @@ pub(crate) fn range_from_lsp_tuple(range: (u32, u32, u32, u32)) -> Range {
     }
 }
 
-pub(crate) fn range_from_byte_range(source: &str, range: (u32, u32, u32, u32)) -> Range {
-    range_from_lsp_tuple(range_byte_to_utf16(source, range))
+pub(crate) fn range_from_byte_range(
+    source: &str,
+    range: (u32, u32, u32, u32),
+    encoding: PositionEncoding,
+) -> Range {
+    range_from_lsp_tuple(range_byte_to_lsp(source, range, encoding))
 }
 
-/// Convert an LSP UTF-16 position to a byte offset in `source`.
-pub(crate) fn lsp_position_to_byte(source: &str, position: Position) -> Option<usize> {
-    let byte_col = utf16_col_to_byte(source, position.line, position.character) as usize;
+/// Convert an LSP position in `encoding` to a byte offset in `source`.
+pub(crate) fn lsp_position_to_byte(
+    source: &str,
+    position: Position,
+    encoding: PositionEncoding,
+) -> Option<usize> {
+    let byte_col = lsp_col_to_byte(source, position.line, position.character, encoding) as usize;
     let mut offset = 0usize;
 
     for (current_line, row) in source.split_inclusive('\n').enumerate() {
@@ -31,10 +39,14 @@ pub(crate) fn lsp_position_to_byte(source: &str, position: Position) -> Option<u
     }
 }
 
-/// Return the text covered by an LSP UTF-16 range.
-pub(crate) fn text_at_lsp_range(source: &str, range: Range) -> Option<&str> {
-    let start = lsp_position_to_byte(source, range.start)?;
-    let end = lsp_position_to_byte(source, range.end)?;
+/// Return the text covered by an LSP range in `encoding`.
+pub(crate) fn text_at_lsp_range(
+    source: &str,
+    range: Range,
+    encoding: PositionEncoding,
+) -> Option<&str> {
+    let start = lsp_position_to_byte(source, range.start, encoding)?;
+    let end = lsp_position_to_byte(source, range.end, encoding)?;
     source.get(start..end)
 }
 
@@ -42,6 +54,8 @@ pub(crate) fn text_at_lsp_range(source: &str, range: Range) -> Option<&str> {
 mod tests {
     use super::*;
 
+    const UTF16: PositionEncoding = PositionEncoding::Utf16;
+
     #[test]
     fn lsp_position_to_byte_handles_utf16_columns() {
         let source = "<?php\n$привет = 1;\n";
@@ -50,7 +64,7 @@ mod tests {
         let utf16_after_variable = "$привет".encode_utf16().count() as u32;
 
         assert_eq!(
-            lsp_position_to_byte(source, Position::new(line, utf16_after_variable)),
+            lsp_position_to_byte(source, Position::new(line, utf16_after_variable), UTF16),
             Some("<?php\n".len() + byte_after_variable as usize)
         );
     }
@@ -62,7 +76,7 @@ mod tests {
         let end = Position::new(1, "$привет".encode_utf16().count() as u32);
 
         assert_eq!(
-            text_at_lsp_range(source, Range::new(start, end)),
+            text_at_lsp_range(source, Range::new(start, end), UTF16),
             Some("$привет")
         );
     }
@@ -74,7 +88,8 @@ mod tests {
         assert_eq!(
             text_at_lsp_range(
                 source,
-                Range::new(Position::new(1, 15), Position::new(1, 22))
+                Range::new(Position::new(1, 15), Position::new(1, 22)),
+                UTF16
             ),
             Some("$target")
         );
@@ -87,7 +102,7 @@ mod tests {
         let end = start + "$target".len() as u32;
 
         assert_eq!(
-            range_from_byte_range(source, (1, start, 1, end)),
+            range_from_byte_range(source, (1, start, 1, end), UTF16),
             Range::new(Position::new(1, 15), Position::new(1, 22))
         );
     }
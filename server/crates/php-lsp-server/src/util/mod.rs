@@ -1,2 +1,4 @@
 pub(crate) mod lsp_text;
+#[cfg(test)]
+pub(crate) mod temp_project;
 pub(crate) mod uri;
@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A throwaway project directory under the system temp dir, removed on drop so
+/// failing tests do not leave it behind.
+pub(crate) struct TempProject {
+    root: PathBuf,
+}
+
+impl TempProject {
+    /// Create `php-lsp-<label>-...` holding `files`, given as relative paths
+    /// and their contents.
+    pub(crate) fn new(label: &str, files: &[(&str, &str)]) -> Self {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root =
+            std::env::temp_dir().join(format!("php-lsp-{label}-{}-{suffix}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        for (path, content) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        Self {
+            root: root.canonicalize().unwrap(),
+        }
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Drop for TempProject {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
@@ -1 +1,79 @@
-pub(crate) use php_lsp_types::uri::{path_to_uri, uri_to_path};
+pub(crate) use php_lsp_types::uri::{normalize_file_uri, path_to_uri, uri_to_path};
+use tower_lsp::jsonrpc::Request;
+
+/// Rewrite every `file://` URI in an incoming message to the spelling
+/// [`path_to_uri`] produces.
+///
+/// Handlers key open documents and index entries by URI string, so a client
+/// spelling such as `file:///c%3A/...` would otherwise miss the indexed copy
+/// of the same file. Normalizing once at the transport keeps every handler on
+/// the canonical form.
+pub fn normalize_request_uris(request: Request) -> Request {
+    let (method, id, params) = request.into_parts();
+    let mut builder = Request::build(method);
+    if let Some(mut params) = params {
+        normalize_uri_fields(&mut params);
+        builder = builder.params(params);
+    }
+    if let Some(id) = id {
+        builder = builder.id(id);
+    }
+    builder.finish()
+}
+
+fn normalize_uri_fields(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if key == "uri" || key.ends_with("Uri") {
+                    if let serde_json::Value::String(uri) = field {
+                        if let std::borrow::Cow::Owned(normalized) = normalize_file_uri(uri) {
+                            *uri = normalized;
+                        }
+                        continue;
+                    }
+                }
+                normalize_uri_fields(field);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(normalize_uri_fields),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_request_uris_rewrites_nested_uri_fields() {
+        let request = Request::build("workspace/didRenameFiles")
+            .params(json!({
+                "files": [{
+                    "oldUri": "file:///c%3A/src/Old%20File.php",
+                    "newUri": "file:///c%3A/src/New%2BFile.php"
+                }],
+                "textDocument": { "uri": "untitled:Untitled-1" },
+                "text": "file:///c%3A/left/alone.php"
+            }))
+            .id(7)
+            .finish();
+
+        let normalized = normalize_request_uris(request);
+
+        assert_eq!(normalized.method(), "workspace/didRenameFiles");
+        assert_eq!(normalized.id(), Some(&7.into()));
+        assert_eq!(
+            normalized.params(),
+            Some(&json!({
+                "files": [{
+                    "oldUri": "file:///C:/src/Old%20File.php",
+                    "newUri": "file:///C:/src/New+File.php"
+                }],
+                "textDocument": { "uri": "untitled:Untitled-1" },
+                "text": "file:///c%3A/left/alone.php"
+            }))
+        );
+    }
+}
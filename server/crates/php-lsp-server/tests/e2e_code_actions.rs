@@ -249,6 +249,10 @@ interface Factory
 interface CountableThing extends Logger
 {
     public function count(): int;
+
+    public function merge(self $other): self;
+
+    public function fresh(): ?static;
 }
 
 interface RepositoryContract
@@ -298,6 +302,16 @@ class Complete extends Base implements CountableThing, Factory, RepositoryContra
         return 0;
     }
 
+    public function merge(CountableThing $other): static
+    {
+        return $this;
+    }
+
+    public function fresh(): ?static
+    {
+        return null;
+    }
+
     public static function make(?string &$name, int ...$ids): array
     {
         return [];
@@ -322,7 +336,7 @@ class Complete extends Base implements CountableThing, Factory, RepositoryContra
         .ready()
         .await
         .unwrap()
-        .call(code_action_request(2, uri, 49, 0, 49, 0, json!([])))
+        .call(code_action_request(2, uri, 53, 0, 53, 0, json!([])))
         .await
         .unwrap();
     let result = extract_result(resp);
@@ -331,7 +345,7 @@ class Complete extends Base implements CountableThing, Factory, RepositoryContra
         .iter()
         .find(|action| {
             action.get("title").and_then(|value| value.as_str())
-                == Some("Implement 4 missing methods")
+                == Some("Implement 6 missing methods")
         })
         .cloned()
         .unwrap_or_else(|| panic!("expected implement missing methods action, got: {}", result));
@@ -375,6 +389,12 @@ class Complete extends Base implements CountableThing, Factory, RepositoryContra
         "expected interface method stub, got: {}",
         new_text
     );
+    assert!(
+        new_text.contains("public function merge(\\App\\CountableThing $other): static")
+            && new_text.contains("public function fresh(): ?static"),
+        "expected self parameters to name the contract and self returns to become static, got: {}",
+        new_text
+    );
     assert!(
         new_text.contains("Find entity by id.")
             && new_text.contains("@template T of object")
@@ -407,7 +427,7 @@ class Complete extends Base implements CountableThing, Factory, RepositoryContra
         .ready()
         .await
         .unwrap()
-        .call(code_action_request(4, uri, 53, 0, 53, 0, json!([])))
+        .call(code_action_request(4, uri, 57, 0, 57, 0, json!([])))
         .await
         .unwrap();
     let complete_result = extract_result(complete_resp);
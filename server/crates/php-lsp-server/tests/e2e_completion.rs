@@ -67,6 +67,107 @@ echo $
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_in_mixed_php_html_template_blocks() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<!DOCTYPE html>
+<?php
+class Post { public string $title = ''; public function permalink(): string { return ''; } }
+$post = new Post();
+?>
+<a href="http://example.com/#top" title="It's <?= $post->ti ?>">
+<?php if ($post): ?>
+  <p><?= theme_ ?></p>
+<?php endif; ?>
+<p>$post-> is plain text here</p>
+<?php function theme_footer(): void {} ?>
+"#;
+    let uri = "file:///test/theme/single.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let member = utf16_position_after(code, "$post->ti");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(2, uri, member.0, member.1))
+        .await
+        .unwrap();
+    let labels: Vec<String> = completion_items_from_result(&extract_result(resp))
+        .iter()
+        .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+        .map(str::to_string)
+        .collect();
+    assert!(
+        labels.iter().any(|label| label == "title"),
+        "member completion inside an attribute value block, got: {labels:?}"
+    );
+
+    let function = utf16_position_after(code, "theme_");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(3, uri, function.0, function.1))
+        .await
+        .unwrap();
+    let labels: Vec<String> = completion_items_from_result(&extract_result(resp))
+        .iter()
+        .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+        .map(str::to_string)
+        .collect();
+    assert!(
+        labels.iter().any(|label| label == "theme_footer"),
+        "function declared in a later block should complete, got: {labels:?}"
+    );
+
+    let html = utf16_position_after(code, "<p>$post->");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(4, uri, html.0, html.1))
+        .await
+        .unwrap();
+    assert!(
+        extract_result(resp).is_null(),
+        "inline HTML between PHP blocks is not code"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_completion_context_uses_utf16_lsp_position_after_non_ascii_text() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -1298,6 +1399,102 @@ class Controller {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_and_definition_follow_trait_insteadof_and_aliases() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+trait A { public function hello(): string { return 'A'; } }
+trait B { public function hello(): string { return 'B'; } }
+
+class Greeter {
+    use A, B {
+        A::hello insteadof B;
+        B::hello as helloB;
+    }
+
+    public function run(): void {
+        $this->helloB();
+        $this->
+    }
+}
+"#;
+    let uri = "file:///test/trait-adaptation-completion.php";
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(2, uri, 14, 15))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let items = completion_items_from_result(&result);
+    let labels: Vec<_> = items
+        .iter()
+        .filter_map(|item| item.get("label").and_then(|value| value.as_str()))
+        .collect();
+    assert_eq!(
+        labels.iter().filter(|label| **label == "hello").count(),
+        1,
+        "insteadof should leave one hello(), got: {labels:?}"
+    );
+    assert!(
+        labels.contains(&"helloB"),
+        "expected the trait alias in completion, got: {labels:?}"
+    );
+
+    let definition = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(3, uri, 13, 17))
+        .await
+        .unwrap();
+    let definition_result = extract_result(definition);
+    assert_eq!(
+        definition_result["range"]["start"]["line"].as_u64(),
+        Some(4),
+        "alias definition should point to B::hello, got: {definition_result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_completion_member_access_from_scoped_static_call_chain() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -2035,8 +2232,8 @@ class Demo {
         class_item
             .get("insertText")
             .and_then(|value| value.as_str())
-            .is_some_and(|text| text.contains("${1:Name}")),
-        "class snippet should include a name placeholder"
+            .is_some_and(|text| text.contains("${1:CompletionSnippet}")),
+        "class snippet should default its name placeholder to the file name"
     );
 
     service
@@ -2122,7 +2319,7 @@ function run(): void {
         function_result["signatures"][0]["label"]
             .as_str()
             .unwrap_or("")
-            .contains("App\\greet(string $name, int $count = 1): string"),
+            .contains("App\\greet(string $name, [int $count = 1]): string"),
         "expected function signature, got: {}",
         function_result
     );
@@ -2188,6 +2385,142 @@ function run(): void {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_signature_help_marks_by_ref_variadic_params_and_picks_overload() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+function array_push_all(array &$array, mixed ...$values): int { return 0; }
+function bump(int &...$counters): void {}
+
+/**
+ * @method bool setOption(array $options)
+ * @method bool setOption(int $option, mixed $value)
+ */
+class Client {}
+
+function run(Client $client): void {
+    $items = [];
+    array_push_all($items, 1, 2, 3);
+    $client->setOption(1, 2);
+    $client->setOption([1]);
+    bump($a, $b);
+}
+"#;
+    let uri = "file:///test/signature-help-variants.php";
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let (line, character) = utf16_position_at(code, "3);");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(signature_help_request(2, uri, line, character))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result["signatures"][0]["label"],
+        "array_push_all(array &$array, [mixed ...$values]): int"
+    );
+    assert_eq!(
+        result["activeParameter"].as_u64(),
+        Some(1),
+        "extra variadic arguments should keep the variadic parameter active"
+    );
+
+    let labels = |result: &serde_json::Value| -> Vec<String> {
+        result["signatures"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|signature| signature["label"].as_str().map(str::to_string))
+            .collect()
+    };
+
+    let (line, character) = utf16_position_at(code, "2);");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(signature_help_request(3, uri, line, character))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        labels(&result),
+        vec![
+            "Client::setOption(array $options): bool",
+            "Client::setOption(int $option, mixed $value): bool",
+        ]
+    );
+    assert_eq!(
+        result["activeSignature"].as_u64(),
+        Some(1),
+        "two arguments should select the two-parameter @method overload"
+    );
+    assert_eq!(result["activeParameter"].as_u64(), Some(1));
+
+    let (line, character) = utf16_position_at(code, "[1]);");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(signature_help_request(4, uri, line, character))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(labels(&result).len(), 2);
+    assert_eq!(result["activeSignature"].as_u64(), Some(0));
+
+    let (line, character) = utf16_position_at(code, "bump($a");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(5, uri, line, character))
+        .await
+        .unwrap();
+    let hover = hover_markdown_value(&extract_result(resp));
+    assert!(
+        hover.contains("int &...$counters"),
+        "by-ref variadic parameters should render as `&...`, got: {hover}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_hover_and_completion_respond_while_workspace_indexing_runs() {
     let nanos = std::time::SystemTime::now()
@@ -2708,3 +3041,1778 @@ async fn test_phpdoc_fixture_hover_completion_definition_and_diagnostics() {
         .await
         .unwrap();
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_class_snippet_uses_psr4_namespace_and_respects_snippet_setting() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-psr4-class-snippet-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&tmp_root);
+    fs::create_dir_all(tmp_root.join("src/Service")).unwrap();
+    fs::write(
+        tmp_root.join("composer.json"),
+        r#"{"autoload":{"psr-4":{"App\\":"src/"}}}"#,
+    )
+    .unwrap();
+
+    let file_uri = |path: &std::path::Path| php_lsp_types::uri::path_to_uri(path).unwrap();
+    let root_uri = file_uri(&tmp_root);
+    let service_path = tmp_root.join("src/Service/UserService.php");
+    let service_uri = file_uri(&service_path);
+    let code = "<?php\n\ncla";
+    fs::write(&service_path, code).unwrap();
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(1, Some(&root_uri), None))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(5)).await;
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&service_uri, code))
+        .await
+        .unwrap();
+
+    let response = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(2, &service_uri, 2, 3))
+        .await
+        .unwrap();
+    let result = extract_result(response);
+    let items = completion_items_from_result(&result);
+    let class_item = items
+        .iter()
+        .find(|item| item["label"] == "class")
+        .unwrap_or_else(|| panic!("class snippet expected, got: {}", result));
+    assert_eq!(class_item["insertTextFormat"], json!(2));
+    assert_eq!(
+        class_item["insertText"].as_str(),
+        Some("namespace App\\\\Service;\n\nclass ${1:UserService}\n{\n    $0\n}")
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_configuration_notification(json!({
+            "phpLsp": { "completion": { "snippets": false } }
+        })))
+        .await
+        .unwrap();
+    let response = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(3, &service_uri, 2, 3))
+        .await
+        .unwrap();
+    let result = extract_result(response);
+    let items = completion_items_from_result(&result);
+    assert!(
+        items
+            .iter()
+            .all(|item| item.get("insertTextFormat") != Some(&json!(2))),
+        "snippets should be disabled, got: {}",
+        result
+    );
+    assert!(items.iter().any(|item| item["label"] == "class"));
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_attribute_completion_definition_and_hover() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let attribute_uri = "file:///test/Routing/Route.php";
+    let attribute_code = r#"<?php
+namespace App\Routing;
+
+#[\Attribute(\Attribute::TARGET_METHOD)]
+final class Route
+{
+    public function __construct(public string $path) {}
+}
+
+final class RouteCollection {}
+"#;
+    let controller_uri = "file:///test/Controller.php";
+    let controller_code = r#"<?php
+namespace App;
+
+use App\Routing\Route;
+
+final class Controller
+{
+    #[Route('/users')]
+    public function users(): void {}
+
+    #[Rou]
+    public function pending(): void {}
+}
+"#;
+    for (uri, code) in [
+        (attribute_uri, attribute_code),
+        (controller_uri, controller_code),
+    ] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(2, controller_uri, 10, 9))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let items = completion_items_from_result(&result);
+    let labels: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+        .collect();
+    assert!(labels.contains(&"Route"), "labels: {labels:?}");
+    assert!(
+        !labels.contains(&"RouteCollection"),
+        "non-attribute classes must not be offered: {labels:?}"
+    );
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(3, controller_uri, 7, 7))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result.get("uri").and_then(|uri| uri.as_str()),
+        Some(attribute_uri),
+        "definition: {result}"
+    );
+    assert_eq!(result["range"]["start"]["line"], 4);
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(4, controller_uri, 7, 7))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let hover = result["contents"]["value"].as_str().unwrap_or_default();
+    assert!(hover.contains("App\\Routing\\Route"), "hover: {hover}");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_inside_string_interpolation() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let model_uri = "file:///test/User.php";
+    let model_code = r#"<?php
+namespace App;
+
+class User
+{
+    public string $name = '';
+
+    public function greet(): string { return ''; }
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(model_uri, model_code))
+        .await
+        .unwrap();
+
+    // (body line, marker after which the cursor sits, expects User members)
+    let cases = [
+        ("echo \"Hello {$user->", "{$user->", true),
+        ("echo \"Hello $user->na today\";", "$user->na", true),
+        ("echo <<<EOT\nHello {$user->\nEOT;", "{$user->", true),
+        ("echo 'Hello $user->na today';", "$user->na", false),
+        ("echo \"Hello Us today\";", "Hello Us", false),
+    ];
+    for (idx, (body, marker, expects_members)) in cases.into_iter().enumerate() {
+        let uri = format!("file:///test/run{idx}.php");
+        let code =
+            format!("<?php\nnamespace App;\n\nfunction run(User $user): void {{\n    {body}\n}}\n");
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let offset = code.find(marker).unwrap() + marker.len();
+        let before = &code[..offset];
+        let line = before.matches('\n').count() as u32;
+        let col = (before.len() - before.rfind('\n').map_or(0, |nl| nl + 1)) as u32;
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, col))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        let labels: Vec<&str> = items
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .collect();
+        if expects_members {
+            assert!(
+                labels.contains(&"name") && labels.contains(&"greet"),
+                "{body:?}: {labels:?}"
+            );
+        } else {
+            assert!(labels.is_empty(), "{body:?}: {labels:?}");
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_phpdoc_tag_type_and_param_completion() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let model_uri = "file:///test/Model/User.php";
+    let model_code = "<?php\nnamespace App\\Model;\n\nclass User {}\n";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(model_uri, model_code))
+        .await
+        .unwrap();
+
+    // (doc line, expected label, expects a `use` import edit)
+    let cases = [
+        (" * @thr", "@throws", false),
+        (" * @param Us", "User", true),
+        (" * @param User $", "$user", false),
+    ];
+    for (idx, (doc_line, expected, expects_import)) in cases.into_iter().enumerate() {
+        let uri = format!("file:///test/Service{idx}.php");
+        let code = format!(
+            "<?php\nnamespace App;\n\nfinal class Service\n{{\n    /**\n{doc_line}\n     */\n    public function save(User $user, bool $flush): void {{}}\n}}\n"
+        );
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(
+                2 + idx as i64,
+                &uri,
+                6,
+                doc_line.len() as u32,
+            ))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        let item = items
+            .iter()
+            .find(|item| item.get("label").and_then(|label| label.as_str()) == Some(expected))
+            .unwrap_or_else(|| panic!("{doc_line:?}: missing {expected} in {result}"));
+        let import = item["additionalTextEdits"][0]["newText"]
+            .as_str()
+            .unwrap_or_default();
+        assert_eq!(
+            import.contains("use App\\Model\\User;"),
+            expects_import,
+            "{doc_line:?}: {item}"
+        );
+        if expected == "$user" {
+            let labels: Vec<&str> = items
+                .iter()
+                .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+                .collect();
+            assert_eq!(labels, vec!["$flush", "$user"], "only parameters apply");
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_variable_completion_is_scoped_to_the_current_function() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let uri = "file:///test/Scopes.php";
+    let code = r#"<?php
+function unrelated(int $stray): void { $leak = 1; }
+
+final class Exporter
+{
+    public function export(array $rows): array
+    {
+        $header = [];
+        return array_map(function (array $row) use ($header) {
+            $line = $row;
+            return $;
+        }, $rows);
+    }
+
+    public function count(array $items): int
+    {
+        return $;
+    }
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    for (id, (line, expected)) in [
+        (10u32, vec!["$header", "$line", "$row", "$this"]),
+        (16u32, vec!["$items", "$this"]),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let col = code.lines().nth(line as usize).unwrap().find('$').unwrap() as u32 + 1;
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + id as i64, uri, line, col))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        let mut labels: Vec<&str> = items
+            .iter()
+            .filter(|item| item["detail"].as_str() != Some("superglobal"))
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .collect();
+        labels.sort_unstable();
+        assert_eq!(labels, expected, "line {line}");
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_superglobal_and_literal_array_key_completion() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    // (function body with `|` at the cursor, labels that must be offered)
+    let cases = [
+        ("$_SE|", vec!["$_SERVER", "$_SESSION"]),
+        ("$method = $_SERVER['REQUEST_M|", vec!["REQUEST_METHOD"]),
+        (
+            "$opts = ['cache' => false];\n    $opts['debug'] = true;\n    $opts[\"level\"] = 1;\n    $opts['|",
+            vec!["cache", "debug", "level"],
+        ),
+    ];
+    for (idx, (body, expected)) in cases.into_iter().enumerate() {
+        let code = format!("<?php\nfunction run(): void {{\n    {body}\n}}\n");
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let before = &code[..offset];
+        let line = before.matches('\n').count() as u32;
+        let col = (before.len() - before.rfind('\n').map_or(0, |nl| nl + 1)) as u32;
+        let uri = format!("file:///test/keys{idx}.php");
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, col))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        let labels: Vec<&str> = items
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .collect();
+        assert_eq!(labels, expected, "{body:?}");
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_completion_preselects_best_ranked_symbol() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let uri = "file:///test/ranking.php";
+    let code = "<?php\nnamespace App;\n\nclass OrderRepository {}\nclass ColorReader {}\n\n$repo = orre;\n";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let (line, col) = utf16_position_at(code, "orre;");
+    let col = col + "orre".len() as u32;
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(completion_request(2, uri, line, col))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let items = completion_items_from_result(&result);
+    let labels: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+        .collect();
+    assert_eq!(labels, vec!["OrderRepository", "ColorReader"]);
+    assert_eq!(
+        items[0].get("preselect").and_then(|value| value.as_bool()),
+        Some(true)
+    );
+    assert!(items[1].get("preselect").is_none());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_completion_list_is_capped_and_marked_incomplete() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_configuration_notification(json!({
+            "phpLsp": { "completion": { "maxItems": 3 } }
+        })))
+        .await
+        .unwrap();
+
+    let classes: String = (1..=6).map(|n| format!("class Widget{n} {{}}\n")).collect();
+    let cases = [("Widg", 3, true), ("Widget4", 1, false)];
+    for (idx, (prefix, expected_len, expected_incomplete)) in cases.into_iter().enumerate() {
+        let code = format!("<?php\nnamespace App;\n\n{classes}\n$widget = new \\App\\{prefix}");
+        let uri = format!("file:///test/capped{idx}.php");
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let line = code.matches('\n').count() as u32;
+        let col = code.len() - code.rfind('\n').unwrap() - 1;
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, col as u32))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        assert_eq!(items.len(), expected_len, "{prefix}: {result}");
+        assert_eq!(
+            result["isIncomplete"],
+            json!(expected_incomplete),
+            "{prefix}: {result}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_completion_text_edits_replace_the_typed_prefix() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    // (function body with `|` at the cursor, label, replaced text, new text)
+    let cases = [
+        ("$user = new Widget();\n    $us|", "$user", "$us", "$user"),
+        (
+            "$user = new Widget();\n    $user->na|",
+            "name",
+            "na",
+            "name",
+        ),
+        (
+            "$w = new \\App\\Ui\\Wid|",
+            "Widget",
+            "\\App\\Ui\\Wid",
+            "\\App\\Ui\\Widget",
+        ),
+        (
+            "$user = 'x';\n    echo \"Hi ${us|}\";",
+            "$user",
+            "us",
+            "user",
+        ),
+    ];
+    for (idx, (body, label, replaced, new_text)) in cases.into_iter().enumerate() {
+        let code = format!(
+            "<?php\nnamespace App\\Ui;\n\nclass Widget {{ public string $name = ''; }}\n\nfunction run(): void {{\n    {body}\n}}\n"
+        );
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let before = &code[..offset];
+        let line = before.matches('\n').count() as u32;
+        let col = (before.len() - before.rfind('\n').map_or(0, |nl| nl + 1)) as u32;
+        let uri = format!("file:///test/edits{idx}.php");
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, col))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        let item = items
+            .iter()
+            .find(|item| item["label"] == label)
+            .unwrap_or_else(|| panic!("{body:?}: expected {label}, got {result}"));
+        let edit = &item["textEdit"];
+        assert_eq!(edit["newText"], json!(new_text), "{body:?}");
+        assert_eq!(edit["range"]["start"]["line"], json!(line), "{body:?}");
+        assert_eq!(
+            edit["range"]["start"]["character"],
+            json!(col - replaced.len() as u32),
+            "{body:?}"
+        );
+        assert_eq!(edit["range"]["end"]["character"], json!(col), "{body:?}");
+        assert!(item.get("additionalTextEdits").is_none(), "{body:?}");
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_function_and_method_completion_inserts_call_snippets() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_configuration_notification(json!({
+            "phpLsp": { "completion": { "callSnippets": true } }
+        })))
+        .await
+        .unwrap();
+
+    // (function body with `|` at the cursor, label, expected insert text)
+    let cases = [
+        ("format_total|", "format_total", Some("format_total($1)$0")),
+        (
+            "$cart = new Cart();\n    $cart->cle|",
+            "clear",
+            Some("clear()"),
+        ),
+        (
+            "$cart = new Cart();\n    $cart->ad|",
+            "add",
+            Some("add($1)$0"),
+        ),
+        ("Cart::ma|", "make", Some("make()")),
+        ("format_tot|($x)", "format_total", None),
+    ];
+    for (idx, (body, label, expected)) in cases.into_iter().enumerate() {
+        let code = format!(
+            "<?php\nfunction format_total(int $cents): string {{ return ''; }}\n\nclass Cart {{\n    public function add(string $sku): void {{}}\n    public function clear(): void {{}}\n    public static function make(): self {{ return new self(); }}\n}}\n\nfunction run(): void {{\n    {body}\n}}\n"
+        );
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let before = &code[..offset];
+        let line = before.matches('\n').count() as u32;
+        let col = (before.len() - before.rfind('\n').map_or(0, |nl| nl + 1)) as u32;
+        let uri = format!("file:///test/calls{idx}.php");
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, col))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let items = completion_items_from_result(&result);
+        let item = items
+            .iter()
+            .find(|item| item["label"] == label)
+            .unwrap_or_else(|| panic!("{body:?}: expected {label}, got {result}"));
+        let new_text = item["textEdit"]["newText"].as_str();
+        match expected {
+            Some(snippet) => {
+                assert_eq!(new_text, Some(snippet), "{body:?}");
+                let expects_parameters = snippet.contains("$1");
+                assert_eq!(
+                    item["insertTextFormat"] == json!(2),
+                    expects_parameters,
+                    "{body:?}"
+                );
+                assert_eq!(
+                    item["command"]["command"].as_str(),
+                    expects_parameters.then_some("editor.action.triggerParameterHints"),
+                    "{body:?}"
+                );
+            }
+            None => {
+                assert_eq!(new_text, Some(label), "{body:?}");
+                assert!(item.get("command").is_none(), "{body:?}");
+            }
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_generic_element_types_from_phpdoc_and_templates() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let library = r#"<?php
+namespace App;
+
+class User
+{
+    public function getName(): string { return ''; }
+}
+
+/**
+ * @template TKey
+ * @template TValue
+ */
+class Collection implements \IteratorAggregate
+{
+    /** @return TValue|null */
+    public function first() { return null; }
+    /** @return array<TKey, TValue> */
+    public function all(): array { return []; }
+    public function getIterator(): \Iterator { return new \ArrayIterator([]); }
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(
+            "file:///test/generic-element-lib.php",
+            library,
+        ))
+        .await
+        .unwrap();
+
+    let bodies = [
+        (
+            "list-return-index",
+            "/** @return list<User> */ public function users(): array { return []; }\n    public function run(): void { $users = $this->users(); $users[0]->/*caret*/ }",
+        ),
+        (
+            "list-return-foreach",
+            "/** @return list<User> */ public function users(): array { return []; }\n    public function run(): void { foreach ($this->users() as $user) { $user->/*caret*/ } }",
+        ),
+        (
+            "param-array-index",
+            "/** @param array<int, User> $users */\n    public function run(array $users): void { $users[0]->/*caret*/ }",
+        ),
+        (
+            "param-list-foreach",
+            "/** @param list<User> $users */\n    public function run(array $users): void { foreach ($users as $user) { $user->/*caret*/ } }",
+        ),
+        (
+            "template-first",
+            "/** @param Collection<int, User> $users */\n    public function run(Collection $users): void { $users->first()->/*caret*/ }",
+        ),
+        (
+            "template-all-foreach",
+            "/** @param Collection<int, User> $users */\n    public function run(Collection $users): void { foreach ($users->all() as $user) { $user->/*caret*/ } }",
+        ),
+        (
+            "template-return-first",
+            "/** @return Collection<int, User> */ public function users(): Collection { return new Collection(); }\n    public function run(): void { $users = $this->users(); $users->first()->/*caret*/ }",
+        ),
+    ];
+
+    for (idx, (name, body)) in bodies.into_iter().enumerate() {
+        let code_with_marker =
+            format!("<?php\nnamespace App;\n\nclass Service\n{{\n    {body}\n}}\n");
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/generic-element-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        assert!(
+            labels.contains(&"getName".to_string()),
+            "{name}: generic element type should resolve to App\\User, got: {labels:?}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_array_shape_value_members_from_param_and_return() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let bodies = [
+        (
+            "param",
+            "/** @param array{id: int, user: User} $data */\n    public function run(array $data): void { $data['user']->/*caret*/ }",
+        ),
+        (
+            "return",
+            "/** @return array{id: int, user: User} */ public function load(): array { return []; }\n    public function run(): void { $data = $this->load(); $data['user']->/*caret*/ }",
+        ),
+    ];
+
+    for (idx, (name, body)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            "<?php\nnamespace App;\n\nclass User\n{{\n    public function getName(): string {{ return ''; }}\n}}\n\nclass Service\n{{\n    {body}\n}}\n"
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/array-shape-value-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        assert!(
+            labels.contains(&"getName".to_string()),
+            "{name}: array shape value should complete App\\User members, got: {labels:?}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_union_members_from_match_and_ternary_assignments() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let library = r#"<?php
+namespace App;
+
+class Admin
+{
+    public function permissions(): array { return []; }
+}
+
+class Guest
+{
+    public function visit(): void {}
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(
+            "file:///test/union-assignment-lib.php",
+            library,
+        ))
+        .await
+        .unwrap();
+
+    let bodies = [
+        (
+            "match",
+            "$subject = match ($kind) { 1 => new Admin(), default => new Guest() };\n    $subject->/*caret*/",
+        ),
+        (
+            "ternary",
+            "$subject = $kind > 1 ? new Admin() : new Guest();\n    $subject->/*caret*/",
+        ),
+        (
+            "short-ternary",
+            "$subject = $admin ?: new Guest();\n    $subject->/*caret*/",
+        ),
+        (
+            "phpdoc-var",
+            "/** @var Admin|Guest $subject */\n    $subject = load();\n    $subject->/*caret*/",
+        ),
+    ];
+
+    for (idx, (name, body)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            "<?php\nnamespace App;\n\nfunction run(int $kind, ?Admin $admin): void\n{{\n    {body}\n}}\n"
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/union-assignment-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        for expected in ["permissions", "visit"] {
+            assert!(
+                labels.contains(&expected.to_string()),
+                "{name}: union members should include {expected}, got: {labels:?}"
+            );
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_closure_params_of_builtin_array_callbacks() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let library = r#"<?php
+namespace App;
+
+class User
+{
+    public function getName(): string { return ''; }
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(
+            "file:///test/array-callback-lib.php",
+            library,
+        ))
+        .await
+        .unwrap();
+
+    let bodies = [
+        (
+            "array-map",
+            "array_map(function ($user) { return $user->/*caret*/ }, $users);",
+        ),
+        (
+            "array-filter",
+            "array_filter($users, fn ($user) => $user->/*caret*/);",
+        ),
+        (
+            "usort",
+            "usort($users, function ($left, $right) { return $right->/*caret*/ });",
+        ),
+    ];
+
+    for (idx, (name, body)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            "<?php\nnamespace App;\n\n/** @param list<User> $users */\nfunction run(array $users): void\n{{\n    {body}\n}}\n"
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/array-callback-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        assert!(
+            labels.contains(&"getName".to_string()),
+            "{name}: callback parameter should resolve to App\\User, got: {labels:?}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_untyped_property_from_constructor_assignment() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let bodies = [
+        ("new", "$this->logger->/*caret*/", "log"),
+        ("typed-param", "$this->mailer->/*caret*/", "send"),
+    ];
+
+    for (idx, (name, body, expected)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            r#"<?php
+namespace App;
+
+class NullLogger {{ public function log(): void {{}} }}
+class Mailer {{ public function send(): void {{}} }}
+
+class Service
+{{
+    private $logger;
+    protected $mailer;
+
+    public function __construct(Mailer $mailer)
+    {{
+        $this->logger = new NullLogger();
+        $this->mailer = $mailer;
+    }}
+
+    public function run(): void
+    {{
+        {body}
+    }}
+}}
+"#
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/constructor-property-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        assert!(
+            labels.contains(&expected.to_string()),
+            "{name}: constructor-assigned property should complete {expected}, got: {labels:?}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_docblock_typed_properties() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let bodies = [
+        ("union-collection", "$this->items->/*caret*/", "first"),
+        (
+            "union-element",
+            "foreach ($this->items as $item) { $item->/*caret*/ }",
+            "getName",
+        ),
+        ("list-subscript", "$this->list[0]->/*caret*/", "getName"),
+        ("map-subscript", "$this->map['a']->/*caret*/", "getName"),
+        ("variable-first", "$this->legacy->/*caret*/", "getName"),
+        ("psalm-var", "$this->prefixed->/*caret*/", "getName"),
+    ];
+
+    for (idx, (name, body, expected)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            r#"<?php
+namespace App;
+
+interface Collection extends \IteratorAggregate {{ public function first(): mixed; }}
+class Item {{ public function getName(): string {{ return ''; }} }}
+
+class Holder
+{{
+    /** @var Collection|Item[] */
+    private $items;
+
+    /** @var Item[] */
+    private $list;
+
+    /** @var array<string, Item> */
+    private $map;
+
+    /** @var $legacy Item */
+    var $legacy;
+
+    /** @psalm-var Item */
+    protected $prefixed;
+
+    public function run(): void
+    {{
+        {body}
+    }}
+}}
+"#
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/docblock-property-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        assert!(
+            labels.contains(&expected.to_string()),
+            "{name}: docblock-typed property should complete {expected}, got: {labels:?}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_fluent_chains_resolve_to_receiver_class() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let bodies = [
+        ("doc-this", "$query->where('a')->/*caret*/", "active"),
+        ("doc-static", "$query->orderBy('id')->/*caret*/", "active"),
+        ("native-static", "$query->limit(1)->/*caret*/", "active"),
+        ("native-self", "$query->offset(1)->/*caret*/", "active"),
+        (
+            "static-factory",
+            "UserQuery::query()->where('a')->/*caret*/",
+            "active",
+        ),
+        (
+            "multiline",
+            "$query\n            ->where('a')\n            ->orderBy('id')\n            ->/*caret*/",
+            "active",
+        ),
+        (
+            "multiline-assignment",
+            "$users = $query->where('a')\n            ->limit(10)\n            ->act/*caret*/",
+            "active",
+        ),
+    ];
+
+    for (idx, (name, body, expected)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            r#"<?php
+namespace App;
+
+class Builder
+{{
+    /** @return $this */
+    public function where(string $condition) {{ return $this; }}
+    /** @return static */
+    public function orderBy(string $field) {{ return $this; }}
+    public function limit(int $limit): static {{ return $this; }}
+    public function offset(int $offset): self {{ return $this; }}
+    public static function query(): static {{ return new static(); }}
+}}
+
+class UserQuery extends Builder
+{{
+    public function active(): static {{ return $this; }}
+}}
+
+function users(UserQuery $query): void
+{{
+    {body}
+}}
+"#
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/fluent-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        assert!(
+            labels.contains(&expected.to_string()),
+            "{name}: fluent chain should stay on UserQuery and complete {expected}, got: {labels:?}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_completion_late_static_binding_resolves_calling_class() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let library = r#"<?php
+namespace App;
+
+class Model
+{
+    public static function create(): static { return new static(); }
+    public function save(): void {}
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(
+            "file:///test/late-static-model.php",
+            library,
+        ))
+        .await
+        .unwrap();
+
+    let bodies = [
+        ("new-static", "(new static())->/*caret*/"),
+        (
+            "new-static-var",
+            "$user = new static();\n        $user->/*caret*/",
+        ),
+        ("parent-call", "parent::create()->/*caret*/"),
+        (
+            "parent-call-var",
+            "$user = parent::create();\n        $user->/*caret*/",
+        ),
+        ("static-call", "static::create()->/*caret*/"),
+    ];
+
+    for (idx, (name, body)) in bodies.into_iter().enumerate() {
+        let code_with_marker = format!(
+            "<?php\nnamespace App;\n\nclass User extends Model\n{{\n    public function posts(): array {{ return []; }}\n\n    public static function make(): void\n    {{\n        {body}\n    }}\n}}\n"
+        );
+        let marker = "/*caret*/";
+        let marker_offset = code_with_marker
+            .find(marker)
+            .expect("test code should contain marker");
+        let code = code_with_marker.replace(marker, "");
+        let (line, character) = utf16_position_for_offset(&code, marker_offset);
+        let uri = format!("file:///test/late-static-{name}.php");
+
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(&uri, &code))
+            .await
+            .unwrap();
+
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(completion_request(2 + idx as i64, &uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        let labels: Vec<String> = completion_items_from_result(&result)
+            .iter()
+            .filter_map(|item| item.get("label").and_then(|label| label.as_str()))
+            .map(str::to_string)
+            .collect();
+        for expected in ["posts", "save"] {
+            assert!(
+                labels.contains(&expected.to_string()),
+                "{name}: members of the calling class should include {expected}, got: {labels:?}"
+            );
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
@@ -1010,6 +1010,329 @@ class Demo {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_define_constants_support_definition_and_hover() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let bootstrap_code = r#"<?php
+namespace Legacy;
+
+/** Current application version. */
+define('APP_VERSION', '1.0');
+if (!defined('Legacy\DEBUG_MODE')) {
+    define(__NAMESPACE__ . '\DEBUG_MODE', true);
+}
+"#;
+    let usage_code = r#"<?php
+namespace App;
+
+echo APP_VERSION;
+echo \Legacy\DEBUG_MODE;
+"#;
+    let bootstrap_uri = "file:///test/bootstrap.php";
+    let usage_uri = "file:///test/usage.php";
+    for (uri, code) in [(bootstrap_uri, bootstrap_code), (usage_uri, usage_code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, usage_uri, 3, 8))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(result["uri"], bootstrap_uri);
+    assert_eq!(result["range"]["start"]["line"], 4);
+    assert_eq!(result["range"]["start"]["character"], 8);
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(3, usage_uri, 3, 8))
+        .await
+        .unwrap();
+    let hover = extract_result(resp)["contents"]["value"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        hover.contains("const APP_VERSION = '1.0'")
+            && hover.contains("Current application version."),
+        "hover should show the define() value and doc comment: {hover}"
+    );
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(4, usage_uri, 4, 16))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(result["uri"], bootstrap_uri);
+    assert_eq!(result["range"]["start"]["line"], 6);
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_enum_constants_builtin_members_and_interface_implementations() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+interface HasLabel {
+    public function label(): string;
+}
+
+enum Suit: string implements HasLabel {
+    case Hearts = 'H';
+    const Wild = self::Hearts;
+
+    public function label(): string { return ucfirst($this->name); }
+}
+
+function useIt(HasLabel $item): void {
+    $item->label();
+    echo Suit::Wild->value;
+    Suit::tryFrom('H')?->label();
+}
+"#;
+    let uri = "file:///test/enum.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(implementation_request(2, uri, 3, 12))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(
+        result
+            .as_array()
+            .is_some_and(|impls| impls.iter().any(|loc| loc["range"]["start"]["line"] == 7)),
+        "enum should implement HasLabel: {result}"
+    );
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(implementation_request(3, uri, 4, 22))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(
+        result
+            .as_array()
+            .is_some_and(|impls| impls.iter().any(|loc| loc["range"]["start"]["line"] == 11)),
+        "Suit::label should implement HasLabel::label: {result}"
+    );
+
+    let (line, col) = utf16_position_at(code, "Wild->value");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(4, uri, line, col + 1))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result["range"]["start"]["line"], 9,
+        "enum constant: {result}"
+    );
+
+    let (line, col) = utf16_position_after(code, "tryFrom('H')?->");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(5, uri, line, col + 1))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result["range"]["start"]["line"], 11,
+        "method on tryFrom() result: {result}"
+    );
+
+    let (line, col) = utf16_position_at(code, "tryFrom");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(6, uri, line, col + 1))
+        .await
+        .unwrap();
+    let hover = extract_result(resp)["contents"]["value"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        hover.contains("public static function tryFrom(")
+            && hover.contains("https://www.php.net/backedenum.tryfrom"),
+        "hover should describe the BackedEnum method: {hover}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_goto_definition_for_callable_strings_and_arrays() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App\Service;
+
+function format_subject(string $subject): string { return $subject; }
+
+class Mailer {
+    public static function send(): void {}
+}
+
+class Listener {
+    public function handle(): void {}
+
+    public function register(Dispatcher $dispatcher): void {
+        call_user_func('App\Service\Mailer::send');
+        $dispatcher->addListener('mail', [$this, 'handle']);
+        $dispatcher->addListener('mail', ['App\\Service\\Mailer', 'send']);
+        array_map('App\Service\format_subject', []);
+        in_array('App\Service\format_subject', []);
+    }
+}
+"#;
+    let uri = "file:///test/callables.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let cases = [
+        ("Mailer::send')", 2, Some(5)),
+        ("send');", 1, Some(6)),
+        ("handle']", 1, Some(10)),
+        ("Mailer', 'send']", 1, Some(5)),
+        ("send']", 1, Some(6)),
+        ("format_subject', []);\n        in", 1, Some(3)),
+        ("format_subject', []);\n    }", 1, None),
+    ];
+    for (id, (needle, offset, expected_line)) in cases.into_iter().enumerate() {
+        let (line, col) = utf16_position_at(code, needle);
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(definition_request(id as i64 + 2, uri, line, col + offset))
+            .await
+            .unwrap();
+        let result = extract_result(resp);
+        match expected_line {
+            Some(expected_line) => assert_eq!(
+                result["range"]["start"]["line"], expected_line,
+                "definition for {needle:?}: {result}"
+            ),
+            None => assert!(result.is_null(), "no definition for {needle:?}: {result}"),
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_goto_definition_vendor_inherited_method() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -1326,3 +1649,204 @@ async fn test_goto_definition_cross_file_method_via_same_file_property() {
         .await
         .unwrap();
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_goto_definition_opens_class_inside_phar_archive() {
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-phar-definition-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&tmp_root);
+    fs::create_dir_all(tmp_root.join("tools")).unwrap();
+    let runner_source = r#"<?php
+namespace Tool\Console;
+
+class Runner
+{
+    public function run(): int { return 0; }
+}
+"#;
+    fs::write(
+        tmp_root.join("tools/tool.phar"),
+        uncompressed_phar(&[
+            (
+                "src/Console/Helper.php",
+                "<?php\nnamespace Tool\\Console;\nclass Helper {}\n",
+            ),
+            ("src/Console/Runner.php", runner_source),
+            // Ranked first by path, but declares another class.
+            (
+                "Tool/Console/Runner.php",
+                "<?php\nnamespace Stale;\nclass Runner {}\n",
+            ),
+        ]),
+    )
+    .unwrap();
+
+    let (mut service, socket) = PhpLspBackend::service();
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+    let root_uri = format!("file://{}", tmp_root.display());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(1, Some(&root_uri), None))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+use Tool\Console\Runner;
+
+(new Runner())->run();
+"#;
+    let uri = format!("file://{}/bin/console.php", tmp_root.display());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&uri, code))
+        .await
+        .unwrap();
+
+    // Cursor on "Runner" in `(new Runner())->run();`
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, &uri, 3, 6))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let expected_uri = format!(
+        "phar://{}/tools/tool.phar/src/Console/Runner.php",
+        tmp_root.display()
+    );
+    assert_eq!(result["uri"], expected_uri, "definition: {result}");
+    assert_eq!(result["range"]["start"]["line"], 3);
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(workspace_symbol_request(5, "Runner"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(
+        !result.to_string().contains("Stale"),
+        "non-declaring PHAR entry was indexed: {result}"
+    );
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(phar_content_request(3, &expected_uri))
+        .await
+        .unwrap();
+    assert_eq!(extract_result(resp)["text"], runner_source);
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(phar_content_request(
+            4,
+            &format!(
+                "phar://{}/tools/tool.phar/../secret.php",
+                tmp_root.display()
+            ),
+        ))
+        .await
+        .unwrap();
+    assert!(extract_result(resp).is_null());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_goto_definition_skips_phar_archives_when_vendor_indexing_is_off() {
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-phar-definition-off-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&tmp_root);
+    fs::create_dir_all(&tmp_root).unwrap();
+    fs::write(
+        tmp_root.join("tool.phar"),
+        uncompressed_phar(&[(
+            "src/Runner.php",
+            "<?php\nnamespace Tool;\nclass Runner {}\n",
+        )]),
+    )
+    .unwrap();
+
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+    let root_uri = format!("file://{}", tmp_root.display());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({ "vendorIndexing": "off" })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = "<?php\nnew \\Tool\\Runner();\n";
+    let uri = format!("file://{}/index.php", tmp_root.display());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&uri, code))
+        .await
+        .unwrap();
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, &uri, 1, 12))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(
+        result.is_null(),
+        "PHAR lookup ran with vendor indexing off: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
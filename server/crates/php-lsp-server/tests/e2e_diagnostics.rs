@@ -579,6 +579,115 @@ async fn test_did_change_debounces_diagnostics_and_ignores_stale_versions() {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_semantic_on_save_limits_did_change_to_syntax_diagnostics() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            None,
+            Some(json!({
+                "diagnostics": { "debounceMs": 0, "semanticOnSave": true }
+            })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let uri = "file:///test/SemanticOnSave.php";
+    let code = "<?php\nnamespace App;\nfunction demo(): void { new MissingClass(); }\n";
+    let messages = |params: &serde_json::Value| {
+        params["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+    let opened = next_publish_diagnostics(&mut notifications, uri, Duration::from_secs(1)).await;
+    assert!(
+        messages(&opened)
+            .iter()
+            .any(|message| message.contains("Unknown class")),
+        "didOpen should publish semantic diagnostics, got: {opened}"
+    );
+
+    let broken = "<?php\nnamespace App;\nfunction demo(): void { new MissingClass(); \n";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_full_notification(uri, 2, broken))
+        .await
+        .unwrap();
+    let changed = next_publish_diagnostics(&mut notifications, uri, Duration::from_secs(1)).await;
+    assert_eq!(changed["version"], 2);
+    let changed_messages = messages(&changed);
+    assert!(
+        !changed_messages.is_empty()
+            && changed_messages
+                .iter()
+                .all(|message| !message.contains("Unknown class")),
+        "didChange should publish syntax diagnostics only, got: {changed}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_full_notification(uri, 3, code))
+        .await
+        .unwrap();
+    let fixed = next_publish_diagnostics(&mut notifications, uri, Duration::from_secs(1)).await;
+    assert_eq!(messages(&fixed), Vec::<String>::new());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_save_notification(uri))
+        .await
+        .unwrap();
+    let saved = next_publish_diagnostics(&mut notifications, uri, Duration::from_secs(1)).await;
+    assert!(
+        messages(&saved)
+            .iter()
+            .any(|message| message.contains("Unknown class")),
+        "didSave should publish semantic diagnostics, got: {saved}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_stress_100_did_change_non_ascii_publishes_latest_version() {
     let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
@@ -807,3 +916,426 @@ sodium_crypto_stream_xchacha20_xor_ic('a', 'b', 0, 'c');
         .unwrap();
     let _ = fs::remove_dir_all(&tmp_root82);
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_diagnostics_baseline_hides_baselined_issues_in_published_diagnostics() {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-diagnostics-baseline-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    let _ = fs::remove_dir_all(&tmp_root);
+    let src_dir = tmp_root.join("src");
+    fs::create_dir_all(&src_dir).unwrap();
+    fs::write(
+        tmp_root.join("php-lsp-baseline.json"),
+        r#"{
+  "schemaVersion": 1,
+  "entries": [
+    {
+      "path": "src/Legacy.php",
+      "source": "php-lsp",
+      "code": "php-lsp.unknownClass",
+      "message": "Unknown class: App\\MissingClass",
+      "count": 1
+    }
+  ]
+}"#,
+    )
+    .unwrap();
+
+    let app_path = src_dir.join("Legacy.php");
+    let app_uri = path_to_uri(&app_path).unwrap();
+    let app_code = r#"<?php
+namespace App;
+
+function legacy(): void
+{
+    new MissingClass();
+    new OtherMissing();
+}
+"#;
+    fs::write(&app_path, app_code).unwrap();
+
+    let root_uri = path_to_uri(&tmp_root).unwrap();
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({ "diagnostics": { "baseline": "php-lsp-baseline.json" } })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(10)).await;
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&app_uri, app_code))
+        .await
+        .unwrap();
+    let diagnostics =
+        next_publish_diagnostics(&mut notifications, &app_uri, Duration::from_secs(3)).await;
+    let messages = published_diagnostic_messages(&diagnostics);
+    assert!(
+        messages
+            .iter()
+            .any(|message| message == "Unknown class: App\\OtherMissing"),
+        "new diagnostics should still be published, got: {:?}",
+        messages
+    );
+    assert!(
+        !messages
+            .iter()
+            .any(|message| message == "Unknown class: App\\MissingClass"),
+        "baselined diagnostics should be hidden, got: {:?}",
+        messages
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test]
+async fn test_phpstan_diagnostics_survive_debounced_did_change_publishes() {
+    if cfg!(windows) {
+        return;
+    }
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-phpstan-merge-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    let _ = fs::remove_dir_all(&tmp_root);
+    let src_dir = tmp_root.join("src");
+    fs::create_dir_all(&src_dir).unwrap();
+
+    let app_path = src_dir.join("Subject.php");
+    let app_uri = path_to_uri(&app_path).unwrap();
+    let app_code = "<?php\nnamespace App;\n\nclass Subject {}\n";
+    fs::write(&app_path, app_code).unwrap();
+
+    let output = json!({
+        "totals": { "errors": 0, "file_errors": 1 },
+        "files": {
+            (app_path.to_string_lossy().to_string()): {
+                "errors": 1,
+                "messages": [
+                    { "message": "PHPStan reported a test error.", "line": 4 }
+                ]
+            }
+        },
+        "errors": []
+    });
+    let script_path = tmp_root.join("phpstan-fake.sh");
+    fs::write(
+        &script_path,
+        format!("#!/bin/sh\ncat <<'JSON'\n{}\nJSON\nexit 1\n", output),
+    )
+    .unwrap();
+
+    let root_uri = path_to_uri(&tmp_root).unwrap();
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({
+                "phpstan": {
+                    "enabled": true,
+                    "command": format!("sh '{}' {{file}}", script_path.display()),
+                }
+            })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(10)).await;
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&app_uri, app_code))
+        .await
+        .unwrap();
+    let diagnostics =
+        next_publish_diagnostics(&mut notifications, &app_uri, Duration::from_secs(5)).await;
+    let messages = published_diagnostic_messages(&diagnostics);
+    assert!(
+        messages
+            .iter()
+            .any(|message| message == "PHPStan reported a test error."),
+        "PHPStan diagnostics should be published on open, got: {:?}",
+        messages
+    );
+
+    let changed_code = "<?php\nnamespace App;\n\nclass Subject\n{\n}\n";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_full_notification(&app_uri, 2, changed_code))
+        .await
+        .unwrap();
+    let diagnostics =
+        next_publish_diagnostics(&mut notifications, &app_uri, Duration::from_secs(5)).await;
+    let messages = published_diagnostic_messages(&diagnostics);
+    assert!(
+        messages
+            .iter()
+            .any(|message| message == "PHPStan reported a test error."),
+        "debounced didChange diagnostics should keep the last PHPStan results, got: {:?}",
+        messages
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_phpcs_diagnostics_offer_phpcbf_fix_edit() {
+    if cfg!(windows) {
+        return;
+    }
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-phpcs-{}-{}", std::process::id(), nanos));
+    let _ = fs::remove_dir_all(&tmp_root);
+    let src_dir = tmp_root.join("src");
+    fs::create_dir_all(&src_dir).unwrap();
+
+    let app_path = src_dir.join("Styled.php");
+    let app_uri = path_to_uri(&app_path).unwrap();
+    let app_code = "<?php\nnamespace App;\n\nclass Styled {}\n";
+    fs::write(&app_path, app_code).unwrap();
+
+    let output = json!({
+        "totals": { "errors": 1, "warnings": 0, "fixable": 1 },
+        "files": {
+            (app_path.to_string_lossy().to_string()): {
+                "errors": 1,
+                "warnings": 0,
+                "messages": [{
+                    "message": "Opening brace should be on a new line",
+                    "source": "PSR2.Classes.ClassDeclaration.OpenBraceNewLine",
+                    "severity": 5,
+                    "fixable": true,
+                    "type": "ERROR",
+                    "line": 4,
+                    "column": 14
+                }]
+            }
+        }
+    });
+    let phpcs_path = tmp_root.join("phpcs-fake.sh");
+    fs::write(
+        &phpcs_path,
+        format!("#!/bin/sh\ncat <<'JSON'\n{}\nJSON\nexit 2\n", output),
+    )
+    .unwrap();
+    let fixed_code = "<?php\nnamespace App;\n\nclass Styled\n{\n}\n";
+    let phpcbf_path = tmp_root.join("phpcbf-fake.sh");
+    fs::write(
+        &phpcbf_path,
+        format!(
+            "#!/bin/sh\ncat > \"$1\" <<'PHP'\n{}PHP\nexit 1\n",
+            fixed_code
+        ),
+    )
+    .unwrap();
+
+    let root_uri = path_to_uri(&tmp_root).unwrap();
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({
+                "phpcs": {
+                    "enabled": true,
+                    "command": format!("sh '{}' {{file}}", phpcs_path.display()),
+                    "fixCommand": format!("sh '{}' {{file}}", phpcbf_path.display()),
+                }
+            })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(10)).await;
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&app_uri, app_code))
+        .await
+        .unwrap();
+    let published =
+        next_publish_diagnostics(&mut notifications, &app_uri, Duration::from_secs(5)).await;
+    let phpcs_diagnostics: Vec<serde_json::Value> = published["diagnostics"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|diagnostic| diagnostic["source"] == json!("phpcs"))
+        .collect();
+    assert_eq!(
+        phpcs_diagnostics.len(),
+        1,
+        "expected one phpcs diagnostic, got: {}",
+        published
+    );
+    assert_eq!(phpcs_diagnostics[0]["severity"], json!(3));
+    assert_eq!(
+        phpcs_diagnostics[0]["code"],
+        json!("PSR2.Classes.ClassDeclaration.OpenBraceNewLine")
+    );
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(code_action_request_with_only(
+            2,
+            &app_uri,
+            ((3, 0), (3, 16)),
+            json!(phpcs_diagnostics),
+            vec!["quickfix"],
+        ))
+        .await
+        .unwrap();
+    let actions = extract_result(resp);
+    let action = actions
+        .as_array()
+        .and_then(|actions| {
+            actions
+                .iter()
+                .find(|action| action["title"] == json!("Fix with phpcbf"))
+        })
+        .cloned()
+        .unwrap_or_else(|| panic!("expected Fix with phpcbf action, got: {}", actions));
+    assert!(action.get("edit").is_none_or(|edit| edit.is_null()));
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(code_action_resolve_request(3, action))
+        .await
+        .unwrap();
+    let resolved = extract_result(resp);
+    let edits = resolved["edit"]["changes"][app_uri.as_str()]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    assert_eq!(edits.len(), 1, "expected one whole-file edit: {}", resolved);
+    assert_eq!(edits[0]["newText"], json!(fixed_code));
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(code_action_request_with_only(
+            4,
+            &app_uri,
+            ((0, 0), (0, 0)),
+            json!([]),
+            vec!["source.fixAll.phpcbf"],
+        ))
+        .await
+        .unwrap();
+    let actions = extract_result(resp);
+    assert!(
+        actions.as_array().is_some_and(|actions| actions
+            .iter()
+            .any(|action| action["kind"] == json!("source.fixAll.phpcbf"))),
+        "expected source.fixAll.phpcbf action, got: {}",
+        actions
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
@@ -53,15 +53,17 @@ async fn test_document_formatting_uses_custom_external_command() {
         .unwrap();
     let result = extract_result(resp);
     let edits = result.as_array().expect("formatting edits array");
-    assert_eq!(edits.len(), 1, "expected one full-document edit");
+    assert_eq!(edits.len(), 1, "expected one edit for the changed lines");
     assert_eq!(
         edits[0]["newText"].as_str(),
-        Some(formatted),
-        "formatter edit should contain formatted source, got: {}",
+        formatted.strip_prefix("<?php\n"),
+        "formatter edit should only replace changed lines, got: {}",
         result
     );
-    assert_eq!(edits[0]["range"]["start"]["line"].as_u64(), Some(0));
+    assert_eq!(edits[0]["range"]["start"]["line"].as_u64(), Some(1));
     assert_eq!(edits[0]["range"]["start"]["character"].as_u64(), Some(0));
+    assert_eq!(edits[0]["range"]["end"]["line"].as_u64(), Some(2));
+    assert_eq!(edits[0]["range"]["end"]["character"].as_u64(), Some(0));
 
     service
         .ready()
@@ -154,7 +156,7 @@ async fn test_document_formatting_auto_detects_php_cs_fixer_from_composer_metada
     assert_eq!(edits.len(), 1, "expected one auto-detected edit");
     assert_eq!(
         edits[0]["newText"].as_str(),
-        Some(formatted),
+        formatted.strip_prefix("<?php\n"),
         "auto-detected formatter edit should contain formatted source, got: {}",
         result
     );
@@ -169,6 +171,78 @@ async fn test_document_formatting_auto_detects_php_cs_fixer_from_composer_metada
     let _ = fs::remove_dir_all(tmp);
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_document_formatting_falls_back_to_builtin_indentation() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(1, None, None))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = "<?php\nclass Demo\n{\n  public function run(): void\n  {\n  $text = <<<EOT\n  keep\n  EOT;\n      return;\n  }\n}\n";
+    let uri = "file:///test/BuiltinFormat.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(formatting_request(2, uri))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let edits = result.as_array().expect("formatting edits array");
+    let edited_lines: Vec<(u64, &str)> = edits
+        .iter()
+        .map(|edit| {
+            (
+                edit["range"]["start"]["line"].as_u64().unwrap(),
+                edit["newText"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        edited_lines,
+        vec![
+            (3, "    "),
+            (4, "    "),
+            (5, "        "),
+            (8, "        "),
+            (9, "    ")
+        ],
+        "built-in formatter should only fix leading indentation outside heredocs, got: {}",
+        result
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_document_range_formatting_uses_custom_external_command() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -410,3 +484,144 @@ async fn test_on_type_formatting_returns_local_indentation_edits() {
         .await
         .unwrap();
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_range_formatting_reindents_pasted_block_with_builtin_indenter() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = "<?php\nfunction outer(): void\n{\n  $keep = 1;\nforeach ($items as $item) {\nif ($item) {\nreturn;\n}\n}\n}\n";
+    let uri = "file:///test/RangeBuiltin.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(range_formatting_request(2, uri, 4, 0, 9, 0))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let edits = result.as_array().expect("range formatting edits array");
+    let edited_lines: Vec<(u64, &str)> = edits
+        .iter()
+        .map(|edit| {
+            (
+                edit["range"]["start"]["line"].as_u64().unwrap(),
+                edit["newText"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        edited_lines,
+        vec![
+            (4, "    "),
+            (5, "        "),
+            (6, "            "),
+            (7, "        "),
+            (8, "    ")
+        ],
+        "only the selected lines should be reindented, got: {}",
+        result
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_on_type_formatting_uses_syntax_tree_for_case_bodies_and_chains() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = "<?php\nswitch ($x) {\n    case 1:\n\n}\n$query = $builder\n->where('a', 1);\n";
+    let uri = "file:///test/OnTypeCst.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let newline_resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(on_type_formatting_request(2, uri, 3, 0, "\n"))
+        .await
+        .unwrap();
+    let newline_result = extract_result(newline_resp);
+    let newline_edits = newline_result.as_array().expect("newline edits array");
+    assert_eq!(newline_edits.len(), 1, "expected case body indent");
+    assert_eq!(newline_edits[0]["newText"].as_str(), Some("        "));
+
+    let semicolon_resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(on_type_formatting_request(3, uri, 6, 16, ";"))
+        .await
+        .unwrap();
+    let semicolon_result = extract_result(semicolon_resp);
+    let semicolon_edits = semicolon_result.as_array().expect("semicolon edits array");
+    assert_eq!(semicolon_edits.len(), 1, "expected continuation indent");
+    assert_eq!(
+        semicolon_edits[0]["range"]["start"]["line"].as_u64(),
+        Some(6)
+    );
+    assert_eq!(semicolon_edits[0]["newText"].as_str(), Some("    "));
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
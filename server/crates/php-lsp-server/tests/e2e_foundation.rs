@@ -1,11 +1,13 @@
 mod support;
 
+use php_lsp_server::normalize_request_uris;
 use php_lsp_types::uri::path_to_uri;
 use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use support::*;
+use tower::ServiceBuilder;
 
 fn unique_temp_dir(prefix: &str) -> PathBuf {
     let suffix = SystemTime::now()
@@ -217,6 +219,71 @@ function make(): EncodedTarget {
     let _ = fs::remove_dir_all(&tmp_root);
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_foundation_client_uri_spelling_is_normalized_to_indexed_form() {
+    let (service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+    let mut service = ServiceBuilder::new()
+        .map_request(normalize_request_uris)
+        .service(service);
+
+    let tmp_root = unique_temp_dir("php-lsp-foundation-client-uri");
+    let file_path = tmp_root.join("src").join("Lib+Target@1.php");
+    let code = "<?php\nclass LibTarget {}\nnew LibTarget();\n";
+    let canonical_uri = path_to_uri(&file_path).unwrap();
+    // VS Code escapes `+` and `@`, which `url` leaves alone.
+    let client_uri = canonical_uri.replace('+', "%2b").replace('@', "%40");
+    assert_ne!(client_uri, canonical_uri);
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&client_uri, code))
+        .await
+        .unwrap();
+
+    let usage = utf16_position_after(code, "new ");
+    let definition = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, &canonical_uri, usage.0, usage.1))
+        .await
+        .unwrap();
+    let result = extract_result(definition);
+    let location = first_location(&result);
+    assert_eq!(location_uri(location), Some(canonical_uri.as_str()));
+    assert_eq!(
+        location_start(location),
+        Some(utf16_position_after(code, "class "))
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_foundation_current_class_completion_uses_cursor_scope() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -352,12 +352,19 @@ class Settings
     {
         self::MODE;
         GLOBAL_LIMIT;
+        Status::Active;
     }
 }
+
+enum Status: string
+{
+    case Active = 'active';
+}
 "#;
     let uri = "file:///test/hover-constants.php";
     let mode_position = utf16_position_at(code, "MODE;");
     let global_position = utf16_position_at(code, "GLOBAL_LIMIT;");
+    let case_position = utf16_position_at(code, "Active;");
 
     service
         .ready()
@@ -376,7 +383,7 @@ class Settings
         .unwrap();
     let mode_hover = hover_markdown_value(&extract_result(mode_hover));
     assert!(
-        mode_hover.contains("```php\npublic const MODE\n```"),
+        mode_hover.contains("```php\npublic const MODE = 'safe'\n```"),
         "class constant hover should use source-like declaration, got: {}",
         mode_hover
     );
@@ -403,7 +410,7 @@ class Settings
         .unwrap();
     let global_hover = hover_markdown_value(&extract_result(global_hover));
     assert!(
-        global_hover.contains("```php\nconst GLOBAL_LIMIT\n```"),
+        global_hover.contains("```php\nconst GLOBAL_LIMIT = 10\n```"),
         "global constant hover should use source-like declaration, got: {}",
         global_hover
     );
@@ -421,6 +428,20 @@ class Settings
         global_hover
     );
 
+    let case_hover = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(4, uri, case_position.0, case_position.1))
+        .await
+        .unwrap();
+    let case_hover = hover_markdown_value(&extract_result(case_hover));
+    assert!(
+        case_hover.contains("```php\ncase Active = 'active'\n```"),
+        "enum case hover should show the backing value, got: {}",
+        case_hover
+    );
+
     service
         .ready()
         .await
@@ -958,6 +979,123 @@ class DeleteMultipleVCard
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_and_definition_on_use_statements() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let service_uri = "file:///test/Service/UserService.php";
+    let service_php = r#"<?php
+namespace App\Service;
+
+/** Loads and persists application users. */
+class UserService {}
+
+function format_user(string $name): string { return $name; }
+"#;
+    let consumer_uri = "file:///test/Controller.php";
+    let consumer_php = r#"<?php
+namespace App;
+
+use App\{Service\UserService, Service\Mailer};
+use function App\Service\format_user;
+
+class Controller {}
+"#;
+    for (uri, code) in [(service_uri, service_php), (consumer_uri, consumer_php)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let class_position = utf16_position_at(consumer_php, "UserService,");
+    let hover = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(
+            2,
+            consumer_uri,
+            class_position.0,
+            class_position.1,
+        ))
+        .await
+        .unwrap();
+    let hover = hover_markdown_value(&extract_result(hover));
+    assert!(
+        hover.contains("class UserService")
+            && hover.contains("Loads and persists application users."),
+        "hover on a group use clause should show the imported class docblock, got: {}",
+        hover
+    );
+
+    let namespace_position = utf16_position_at(consumer_php, "Service\\UserService,");
+    let function_position = utf16_position_at(consumer_php, "format_user;");
+    for (id, (line, character)) in [(3, class_position), (4, namespace_position)] {
+        let definition = service
+            .ready()
+            .await
+            .unwrap()
+            .call(definition_request(id, consumer_uri, line, character))
+            .await
+            .unwrap();
+        let definition = extract_result(definition);
+        assert_eq!(
+            definition["uri"], service_uri,
+            "definition on any segment of a use clause should open the imported class"
+        );
+        assert_eq!(definition["range"]["start"]["line"], 4);
+    }
+
+    let definition = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(
+            5,
+            consumer_uri,
+            function_position.0,
+            function_position.1,
+        ))
+        .await
+        .unwrap();
+    let definition = extract_result(definition);
+    assert_eq!(definition["uri"], service_uri);
+    assert_eq!(
+        definition["range"]["start"]["line"], 6,
+        "definition on a `use function` clause should open the function"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_hover_callsite_generic_resolved_returns() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -2657,6 +2795,198 @@ function run(): void {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_local_variables_assigned_from_nullsafe_chains_are_nullable() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Profile
+{
+    public string $name = '';
+    public function getBio(): string { return ''; }
+}
+
+class User
+{
+    public Profile $profile;
+    public function getProfile(): Profile { return $this->profile; }
+}
+
+function run(?User $user, User $known): void
+{
+    $name = $user?->profile->name;
+    $bio = $user?->getProfile()->getBio();
+    $profile = $user?->getProfile();
+    $plain = $known->profile->name;
+    echo $name, $bio, $profile, $plain;
+}
+"#;
+    let uri = "file:///test/nullsafe-chain-hover.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let cases = [
+        ("$name,", "?string $name"),
+        ("$bio,", "?string $bio"),
+        ("$profile,", "?Profile $profile"),
+        ("$plain;", "string $plain"),
+    ];
+    for (idx, (needle, expected)) in cases.into_iter().enumerate() {
+        let (line, character) = utf16_position_at(code, needle);
+        let hover = service
+            .ready()
+            .await
+            .unwrap()
+            .call(hover_request(2 + idx as i64, uri, line, character + 1))
+            .await
+            .unwrap();
+        let hover_result = extract_result(hover);
+        let hover_text = hover_markdown_value(&hover_result);
+        assert!(
+            hover_text.contains(&format!("```php\n{expected}\n```")),
+            "hovering {needle} should show `{expected}`, got: {hover_text}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_array_shape_keys_show_value_types() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class User
+{
+    public function getName(): string { return ''; }
+}
+
+class Service
+{
+    /** @return array{id: int, user: User} */
+    public function load(): array { return []; }
+
+    /** @param array{id: int, user: User, meta: array{city: string}} $data */
+    public function fromParam(array $data): void
+    {
+        $data['user']->getName();
+        $data['id'];
+        $data['meta']['city'];
+    }
+
+    public function fromReturn(): void
+    {
+        $loaded = $this->load();
+        $loaded['user']->getName();
+    }
+
+    public function fromVar(): void
+    {
+        /** @var array{id: int, user: User} $row */
+        $row = [];
+        $row["user"]->getName();
+    }
+}
+"#;
+    let uri = "file:///test/array-shape-key-hover.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let cases = [
+        ("$data['user'", "User 'user'", Some("App\\User")),
+        ("$data['id'", "int 'id'", None),
+        ("$data['meta']['city'", "string 'city'", None),
+        ("$loaded['user'", "User 'user'", Some("App\\User")),
+        ("$row[\"user\"", "User \"user\"", Some("App\\User")),
+    ];
+    for (idx, (needle, expected, linked_fqn)) in cases.into_iter().enumerate() {
+        let (line, character) = utf16_position_after(code, needle);
+        let hover = service
+            .ready()
+            .await
+            .unwrap()
+            .call(hover_request(2 + idx as i64, uri, line, character - 2))
+            .await
+            .unwrap();
+        let hover_result = extract_result(hover);
+        let hover_text = hover_markdown_value(&hover_result);
+        assert!(
+            hover_text.contains(expected),
+            "hovering {needle} should show `{expected}`, got: {hover_text}"
+        );
+        if let Some(fqn) = linked_fqn {
+            assert!(
+                hover_text.contains(&format!("[`{}`]", fqn.rsplit('\\').next().unwrap())),
+                "hovering {needle} should link the {fqn} value type, got: {hover_text}"
+            );
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_hover_local_variable_method_return_types_and_links() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -3715,14 +4045,36 @@ function run(Library $library): void
     );
     assert!(
         hover.contains(&format!(
-            "**Source:** [`{}:6`](<{}#L6>)",
-            vendor_path.display(),
+            "**Source:** [`vendor/acme/package/src/Library.php:6`](<{}#L6>)\n",
             vendor_uri
         )),
-        "vendor-path method hover should expose clickable vendor source metadata, got: {}",
+        "vendor-path method hover should expose workspace-relative vendor source metadata, got: {}",
         hover
     );
 
+    let library_position = utf16_position_at(app_php, "Library $library");
+    let class_hover_response = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(
+            3,
+            &app_uri,
+            library_position.0,
+            library_position.1,
+        ))
+        .await
+        .unwrap();
+    let class_hover = hover_markdown_value(&extract_result(class_hover_response));
+    assert!(
+        class_hover.contains(&format!(
+            "**Source:** [`vendor/acme/package/src/Library.php:4`](<{}#L4>) (`VendorPkg`)",
+            vendor_uri
+        )),
+        "top-level symbol hover should name its declaring file and namespace, got: {}",
+        class_hover
+    );
+
     let _ = fs::remove_dir_all(&tmp_root);
     service
         .ready()
@@ -4240,3 +4592,619 @@ function run(): void {
         .await
         .unwrap();
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_indexed_phpdoc_virtual_members_show_only_their_tag() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Builder {}
+
+/**
+ * Eloquent-style model.
+ *
+ * @property string $name Display name
+ * @property-write bool $secret
+ * @method static Builder where(string $column, mixed $value)
+ */
+class User {}
+
+function demo(User $user): void {
+    echo $user->name;
+    User::where('name', 'x');
+}
+"#;
+    let uri = "file:///test/hover-indexed-virtual-members.php";
+    let name_position = utf16_position_at(code, "name;");
+    let where_position = utf16_position_at(code, "where('name'");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let name_hover = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(2, uri, name_position.0, name_position.1))
+        .await
+        .unwrap();
+    let name_hover = hover_markdown_value(&extract_result(name_hover));
+    assert!(
+        name_hover.starts_with("```php\n@property string $name\n```")
+            && name_hover.contains("Display name")
+            && !name_hover.contains("$secret")
+            && !name_hover.contains("Eloquent-style model"),
+        "expected only the @property tag, got: {}",
+        name_hover
+    );
+
+    let where_hover = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(3, uri, where_position.0, where_position.1))
+        .await
+        .unwrap();
+    let where_hover = hover_markdown_value(&extract_result(where_hover));
+    assert!(
+        where_hover.starts_with("```php\n@method static Builder where(string $column, mixed $value)\n```")
+            && where_hover.contains(
+                "**Returns:** [`App\\Builder`](<file:///test/hover-indexed-virtual-members.php#L4>)"
+            )
+            && !where_hover.contains("$name"),
+        "expected only the @method tag, got: {}",
+        where_hover
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_hover_shows_documentation_inherited_from_ancestors() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+interface Renderer {
+    /**
+     * Render the view to HTML.
+     *
+     * @return string Rendered markup
+     */
+    public function render(): string;
+}
+
+abstract class BaseView implements Renderer {
+    /** Title shown in the browser tab. */
+    protected string $title = '';
+
+    /**
+     * Escape a value for HTML output.
+     */
+    public function escape(string $value): string { return $value; }
+}
+
+class PageView extends BaseView {
+    protected string $title = 'Home';
+
+    /** {@inheritdoc} */
+    public function escape(string $value): string { return $value; }
+
+    public function render(): string { return $this->escape($this->title); }
+}
+
+class DocumentedView extends BaseView {
+    /** Own documentation wins. */
+    public function escape(string $value): string { return $value; }
+
+    public function render(): string { return ''; }
+}
+"#;
+    let uri = "file:///test/hover-inherited-docs.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let hover_at = |needle: &str| utf16_position_at(code, needle);
+    let cases = [
+        (
+            hover_at("render(): string { return $this"),
+            vec![
+                "Render the view to HTML.",
+                "*Documentation inherited from* [`App\\Renderer::render`]",
+            ],
+        ),
+        (
+            hover_at("escape(string $value): string { return $value; }\n\n    public function render(): string { return $this"),
+            vec![
+                "Escape a value for HTML output.",
+                "*Documentation inherited from* [`App\\BaseView::escape`]",
+            ],
+        ),
+        (
+            hover_at("title); }"),
+            vec![
+                "Title shown in the browser tab.",
+                "*Documentation inherited from* [`App\\BaseView::$title`]",
+            ],
+        ),
+        (
+            hover_at("escape(string $value): string { return $value; }\n\n    public function render(): string { return ''"),
+            vec!["Own documentation wins."],
+        ),
+    ];
+    for (idx, ((line, character), expected)) in cases.into_iter().enumerate() {
+        let hover = service
+            .ready()
+            .await
+            .unwrap()
+            .call(hover_request(2 + idx as i64, uri, line, character))
+            .await
+            .unwrap();
+        let markdown = hover_markdown_value(&extract_result(hover));
+        for fragment in &expected {
+            assert!(
+                markdown.contains(fragment),
+                "case {idx}: expected {fragment:?} in: {markdown}"
+            );
+        }
+        if idx == 3 {
+            assert!(
+                !markdown.contains("Documentation inherited")
+                    && !markdown.contains("Escape a value"),
+                "documented override should keep its own docs: {markdown}"
+            );
+        }
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_variables_show_inferred_type_and_definition() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Order
+{
+    public int $total = 0;
+}
+
+class OrderService
+{
+    public function find(int $id): ?Order { return null; }
+
+    public function handle(Order $order, $untyped, int|string $key): void
+    {
+        $found = $this->find(1);
+        $count = 3;
+        $mystery = unknown_call();
+        $order->total;
+        $untyped;
+        $key;
+        $found;
+        $count;
+        $mystery;
+    }
+}
+"#;
+    let uri = "file:///test/hover-variables.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let cases = [
+        ("$order->total", "Order $order", "hover-variables.php:13"),
+        ("$untyped;", "variable $untyped", "hover-variables.php:13"),
+        ("$key;", "int|string $key", "hover-variables.php:13"),
+        ("$found;", "?Order $found", "hover-variables.php:15"),
+        ("$count;", "int $count", "hover-variables.php:16"),
+        ("$mystery;", "variable $mystery", "hover-variables.php:17"),
+    ];
+    for (idx, (needle, declaration, definition)) in cases.into_iter().enumerate() {
+        let (line, character) = utf16_position_at(code, needle);
+        let hover = service
+            .ready()
+            .await
+            .unwrap()
+            .call(hover_request(2 + idx as i64, uri, line, character + 1))
+            .await
+            .unwrap();
+        let markdown = hover_markdown_value(&extract_result(hover));
+        assert!(
+            markdown.contains(&format!("```php\n{declaration}\n```")),
+            "{needle}: expected {declaration:?} in: {markdown}"
+        );
+        assert!(
+            markdown.contains(&format!("**Defined:** [`/test/{definition}`]")),
+            "{needle}: expected definition {definition:?} in: {markdown}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_builtin_stub_renders_full_docblock_and_manual_link() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-hover-builtin-docs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&tmp_root);
+    let stubs_dir = tmp_root.join("stubs/standard");
+    let workspace_dir = tmp_root.join("workspace");
+    fs::create_dir_all(&workspace_dir).unwrap();
+    // The stub loader only accepts a phpstorm-stubs checkout with its core files.
+    for required in [
+        "PhpStormStubsMap.php",
+        "Core/Core.php",
+        "SPL/SPL.php",
+        "standard/basic.php",
+        "standard/standard_0.php",
+        "date/date.php",
+        "json/json.php",
+        "pcre/pcre.php",
+        "Reflection/Reflection.php",
+        "SimpleXML/SimpleXML.php",
+        "soap/soap.php",
+    ] {
+        let path = tmp_root.join("stubs").join(required);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "<?php\n").unwrap();
+    }
+    fs::write(
+        stubs_dir.join("standard_9.php"),
+        r#"<?php
+/**
+ * Applies the callback to the elements of the given arrays
+ *
+ * Returns an array containing the results of applying the <b>callback</b>
+ * function to the corresponding index of <i>array</i>.
+ * @link https://php.net/manual/en/function.array-map.php
+ * @param callable|null $callback <p>
+ * Callback function to run for each element in each array.
+ * </p>
+ * @param array $array <p>
+ * An array to run through the <code>callback</code> function.
+ * </p>
+ * @param array ...$arrays
+ * @return array an array containing all the elements of <i>array</i>
+ * after applying the callback function to each one.
+ * @see array_walk()
+ * @since 4.0.6
+ */
+function array_map(?callable $callback, array $array, array ...$arrays): array {}
+
+class ArrayObject
+{
+    /**
+     * Construct a new array object
+     * @link https://php.net/manual/en/arrayobject.construct.php
+     */
+    public function __construct(object|array $array = []) {}
+}
+"#,
+    )
+    .unwrap();
+    let root_uri = php_lsp_types::uri::path_to_uri(&workspace_dir).unwrap();
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({
+                "stubsPath": tmp_root.join("stubs").to_string_lossy().to_string(),
+                "stubExtensions": ["standard"]
+            })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "stubsLoaded", Duration::from_secs(5)).await;
+
+    let code = r#"<?php
+$doubled = array_map(fn ($n) => $n * 2, [1, 2]);
+$items = new ArrayObject([]);
+"#;
+    let uri = "file:///test/hover-builtins.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let map_position = utf16_position_at(code, "array_map");
+    let map_hover = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(2, uri, map_position.0, map_position.1))
+        .await
+        .unwrap();
+    let map_hover = hover_markdown_value(&extract_result(map_hover));
+    for expected in [
+        "**PHP manual:** [`php.net/function.array-map`](<https://www.php.net/function.array-map>)",
+        "Applies the callback to the elements of the given arrays",
+        "Returns an array containing the results of applying the **callback** function to the corresponding index of *array*.",
+        "An array to run through the `callback` function.",
+        "— an array containing all the elements of *array* after applying the callback function to each one.",
+        "**See:** `array_walk()`",
+        "**Since:** 4.0.6",
+    ] {
+        assert!(
+            map_hover.contains(expected),
+            "expected {expected:?} in built-in function hover: {map_hover}"
+        );
+    }
+    assert!(
+        !map_hover.contains("<p>") && !map_hover.contains("**Link:**"),
+        "stub HTML and duplicate php.net links should not be rendered: {map_hover}"
+    );
+
+    let constructor_position = utf16_position_at(code, "ArrayObject([");
+    let constructor_hover = service
+        .ready()
+        .await
+        .unwrap()
+        .call(hover_request(
+            3,
+            uri,
+            constructor_position.0,
+            constructor_position.1,
+        ))
+        .await
+        .unwrap();
+    let constructor_hover = hover_markdown_value(&extract_result(constructor_hover));
+    assert!(
+        constructor_hover.contains(
+            "**PHP manual:** [`php.net/arrayobject.construct`](<https://www.php.net/arrayobject.construct>)"
+        ),
+        "built-in constructor hover should link the php.net method page: {constructor_hover}"
+    );
+
+    let _ = fs::remove_dir_all(&tmp_root);
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_hover_resolves_chained_method_call_return_types() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let library = r#"<?php
+namespace Lib;
+
+class User
+{
+    public function getName(): string { return ''; }
+}
+
+class UserRepository
+{
+    /** @return User|null */
+    public function findUser(int $id) { return null; }
+}
+
+class QueryBuilder
+{
+    public function where(string $condition): static { return $this; }
+    public function orderBy(string $field): self { return $this; }
+    /** @return $this */
+    public function limit(int $limit) { return $this; }
+    public function getQuery(): Query { return new Query(); }
+}
+
+class ChildBuilder extends QueryBuilder
+{
+    public function childOnly(): static { return $this; }
+}
+
+class Query
+{
+    public function execute(): array { return []; }
+}
+
+function make_builder(): ChildBuilder { return new ChildBuilder(); }
+"#;
+    let code = r#"<?php
+namespace App;
+
+use Lib\UserRepository;
+use Lib\QueryBuilder;
+use Lib\ChildBuilder;
+
+class Controller
+{
+    public function __construct(private UserRepository $repo) {}
+
+    public function show(QueryBuilder $qb): void
+    {
+        $this->repo->findUser(1)->getName();
+        $qb->where('a')->orderBy('b')->limit(3)->getQuery()->execute();
+        (new ChildBuilder())->where('a')->limit(1)->childOnly();
+        \Lib\make_builder()->orderBy('x')->where('y')->childOnly();
+        $built = $qb->where('a')->getQuery();
+        $built->execute();
+    }
+}
+"#;
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification("file:///test/chain-lib.php", library))
+        .await
+        .unwrap();
+    let uri = "file:///test/chain-app.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let cases = [
+        ("getName();", "Lib\\User::getName"),
+        ("orderBy('b')", "Lib\\QueryBuilder::orderBy"),
+        ("limit(3)", "Lib\\QueryBuilder::limit"),
+        ("getQuery()->execute", "Lib\\QueryBuilder::getQuery"),
+        ("execute();\n        (new", "Lib\\Query::execute"),
+        (
+            "childOnly();\n        \\Lib",
+            "Lib\\ChildBuilder::childOnly",
+        ),
+        (
+            "childOnly();\n        $built",
+            "Lib\\ChildBuilder::childOnly",
+        ),
+        ("execute();\n    }", "Lib\\Query::execute"),
+    ];
+    for (idx, (needle, fqn)) in cases.into_iter().enumerate() {
+        let (line, character) = utf16_position_at(code, needle);
+        let hover = service
+            .ready()
+            .await
+            .unwrap()
+            .call(hover_request(2 + idx as i64, uri, line, character))
+            .await
+            .unwrap();
+        let result = extract_result(hover);
+        assert!(!result.is_null(), "expected hover for {needle:?}");
+        let markdown = hover_markdown_value(&result);
+        assert!(
+            markdown.contains(&format!("`{fqn}`")),
+            "expected {fqn} for {needle:?}, got: {markdown}"
+        );
+    }
+}
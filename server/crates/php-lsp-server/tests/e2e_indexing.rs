@@ -429,6 +429,939 @@ async fn test_watched_files_incrementally_reindex_created_changed_deleted_php_fi
     let _ = fs::remove_dir_all(&tmp_root);
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_reindex_command_rescans_files_and_workspace() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-reindex-{}-{}", std::process::id(), nanos));
+    fs::create_dir_all(&tmp_root).unwrap();
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    let init = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(initialize_request_with_options(1, Some(&root_uri), None))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(
+        init["capabilities"]["executeCommandProvider"]["commands"],
+        json!([
+            "php-lsp.reindex",
+            "php-lsp.dumpIndex",
+            "php-lsp.newClass",
+            "php-lsp.findUnusedSymbols"
+        ])
+    );
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(2)).await;
+
+    // Files written behind the server's back, e.g. by a git checkout.
+    let single_path = tmp_root.join("Single.php");
+    fs::write(&single_path, "<?php\nnamespace App;\nclass Single {}\n").unwrap();
+    fs::write(
+        tmp_root.join("Bulk.php"),
+        "<?php\nnamespace App;\nclass Bulk {}\n",
+    )
+    .unwrap();
+
+    let single_uri = format!("file://{}", single_path.to_string_lossy());
+    let file_result = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                2,
+                "php-lsp.reindex",
+                vec![json!({ "uri": single_uri })],
+            ))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(file_result, json!({ "scope": "files", "files": 1 }));
+    let names = workspace_symbol_names(&extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(workspace_symbol_request(3, "App"))
+            .await
+            .unwrap(),
+    ));
+    assert!(names.iter().any(|name| name == "Single"), "got: {names:?}");
+    assert!(!names.iter().any(|name| name == "Bulk"), "got: {names:?}");
+
+    let workspace_result = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(4, "php-lsp.reindex", Vec::new()))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(workspace_result, json!({ "scope": "workspace" }));
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(2)).await;
+    let names = workspace_symbol_names(&extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(workspace_symbol_request(5, "App"))
+            .await
+            .unwrap(),
+    ));
+    assert!(names.iter().any(|name| name == "Single"), "got: {names:?}");
+    assert!(names.iter().any(|name| name == "Bulk"), "got: {names:?}");
+
+    let unknown = service
+        .ready()
+        .await
+        .unwrap()
+        .call(execute_command_request(6, "php-lsp.unknown", Vec::new()))
+        .await
+        .unwrap()
+        .expect("response");
+    assert!(unknown.is_error(), "unknown commands should be rejected");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_server_status_notifications_follow_indexing() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-status-{}-{}", std::process::id(), nanos));
+    fs::create_dir_all(&tmp_root).unwrap();
+    fs::write(
+        tmp_root.join("Status.php"),
+        "<?php\nnamespace App;\nclass Status {}\n",
+    )
+    .unwrap();
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(1, Some(&root_uri), None))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let mut states = Vec::new();
+    let deadline = std::time::Instant::now() + Duration::from_secs(2);
+    while states.last().map(String::as_str) != Some("idle") {
+        let remaining = deadline
+            .checked_duration_since(std::time::Instant::now())
+            .unwrap_or_else(|| panic!("timed out waiting for idle status, got: {states:?}"));
+        let notification = tokio::time::timeout(remaining, notifications.recv())
+            .await
+            .unwrap_or_else(|_| panic!("timed out waiting for idle status, got: {states:?}"))
+            .expect("notification channel closed");
+        if notification.method() != "$/php-lsp/status" {
+            continue;
+        }
+        let params = notification.params().cloned().expect("status params");
+        if params["state"] == "indexing" {
+            if let Some(percentage) = params.get("percentage") {
+                assert!(percentage.as_u64().is_some_and(|value| value <= 100));
+            }
+        }
+        states.push(params["state"].as_str().unwrap_or_default().to_string());
+    }
+    assert!(
+        states.iter().any(|state| state == "indexing"),
+        "got: {states:?}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_work_done_progress_cancel_stops_indexing_and_keeps_partial_index() {
+    use futures::SinkExt;
+
+    let (mut service, socket) = PhpLspBackend::service();
+    let (mut requests, mut responses) = socket.split();
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(request) = requests.next().await {
+            // Accept `window/workDoneProgress/create` so the server reports progress.
+            if let Some(id) = request.id().cloned() {
+                let _ = responses
+                    .send(tower_lsp::jsonrpc::Response::from_ok(
+                        id,
+                        serde_json::Value::Null,
+                    ))
+                    .await;
+            }
+            let _ = notification_tx.send(request);
+        }
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-progress-cancel-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    let src = tmp_root.join("src");
+    fs::create_dir_all(&src).unwrap();
+    for i in 0..1000 {
+        fs::write(
+            src.join(format!("Cancel{i}.php")),
+            format!(
+                "<?php\nnamespace App;\nclass Cancel{i} {{ public function run(): void {{}} }}\n"
+            ),
+        )
+        .unwrap();
+    }
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(
+            Request::build("initialize")
+                .params(json!({
+                    "capabilities": { "window": { "workDoneProgress": true } },
+                    "rootUri": root_uri
+                }))
+                .id(1)
+                .finish(),
+        )
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let mut token = None;
+    let mut end_message = None;
+    let mut cancelled_status = None;
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while end_message.is_none() || cancelled_status.is_none() {
+        let remaining = deadline
+            .checked_duration_since(std::time::Instant::now())
+            .expect("timed out waiting for cancelled indexing");
+        let notification = tokio::time::timeout(remaining, notifications.recv())
+            .await
+            .expect("timed out waiting for cancelled indexing")
+            .expect("notification channel closed");
+        let params = notification.params().cloned().unwrap_or_default();
+        match notification.method() {
+            "$/progress" if params["value"]["kind"] == "begin" => {
+                assert_eq!(params["value"]["cancellable"], true);
+                token = Some(params["token"].clone());
+                service
+                    .ready()
+                    .await
+                    .unwrap()
+                    .call(
+                        Request::build("window/workDoneProgress/cancel")
+                            .params(json!({ "token": params["token"] }))
+                            .finish(),
+                    )
+                    .await
+                    .unwrap();
+            }
+            "$/progress" if params["value"]["kind"] == "end" => {
+                assert_eq!(Some(&params["token"]), token.as_ref());
+                end_message = params["value"]["message"].as_str().map(str::to_string);
+            }
+            "phpLsp/indexingStatus" if params["phase"] == "ready" => {
+                assert_eq!(params["cancelled"], true, "indexing finished before cancel");
+                cancelled_status = Some(params);
+            }
+            _ => {}
+        }
+    }
+    let end_message = end_message.unwrap();
+    assert!(
+        end_message.starts_with("Indexing cancelled after"),
+        "got: {end_message}"
+    );
+    let cancelled_status = cancelled_status.unwrap();
+    assert!(cancelled_status["indexedFiles"].as_u64().unwrap() < 1000);
+
+    // The partial index keeps answering requests.
+    let result = service
+        .ready()
+        .await
+        .unwrap()
+        .call(workspace_symbol_request(2, "Cancel"))
+        .await
+        .unwrap()
+        .expect("response");
+    assert!(!result.is_error());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_dump_index_command_writes_json_and_sql() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-dump-{}-{}", std::process::id(), nanos));
+    fs::create_dir_all(&tmp_root).unwrap();
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let uri = "file:///test/Dumped.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(
+            uri,
+            "<?php\nnamespace App;\nclass Dumped {\n    public function run(): void {}\n}\nfunction helper(): Dumped { return new Dumped(); }\n",
+        ))
+        .await
+        .unwrap();
+
+    let json_path = tmp_root.join("index.json");
+    let json_result = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                2,
+                "php-lsp.dumpIndex",
+                vec![json!({
+                    "path": json_path.to_string_lossy(),
+                    "references": true,
+                })],
+            ))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(json_result["format"], "json");
+    assert!(json_result["files"].as_u64().unwrap() >= 1);
+    assert!(json_result["references"].as_u64().unwrap() >= 1);
+
+    let dump: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(dump["schemaVersion"], 1);
+    let file = dump["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|file| file["uri"] == uri)
+        .expect("opened file in dump");
+    assert_eq!(file["namespace"], "App");
+    let fqns: Vec<&str> = file["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|symbol| symbol["fqn"].as_str())
+        .collect();
+    assert!(fqns.contains(&"App\\Dumped"), "got: {fqns:?}");
+    assert!(fqns.contains(&"App\\helper"), "got: {fqns:?}");
+    assert!(file["references"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|reference| reference["target_fqn"] == "App\\Dumped"));
+
+    let sql_path = tmp_root.join("index.sql");
+    let sql_result = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                3,
+                "php-lsp.dumpIndex",
+                vec![json!({ "path": sql_path.to_string_lossy() })],
+            ))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(sql_result["format"], "sql");
+    assert!(sql_result["references"].is_null());
+    let sql = fs::read_to_string(&sql_path).unwrap();
+    assert!(sql.contains("CREATE TABLE symbols"));
+    assert!(sql.contains("INSERT INTO symbols VALUES ('file:///test/Dumped.php', 'App\\Dumped'"));
+    assert!(!sql.contains("INSERT INTO symbol_references"));
+    assert!(sql.trim_end().ends_with("COMMIT;"));
+
+    for (id, format) in [(4, "xml"), (5, "sqlite")] {
+        let invalid = service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                id,
+                "php-lsp.dumpIndex",
+                vec![json!({ "path": sql_path.to_string_lossy(), "format": format })],
+            ))
+            .await
+            .unwrap()
+            .expect("response");
+        assert!(
+            invalid.is_error(),
+            "unsupported dump format {format} should be rejected"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_find_unused_symbols_command_reports_unreferenced_public_symbols() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    for (uri, text) in [
+        (
+            "file:///project/src/Service.php",
+            "<?php\nnamespace App;\ninterface Runner { public function run(): void; }\nclass Service implements Runner {\n    public function run(): void {}\n    public function used(): void {}\n    public function unused(): void {}\n    public function __toString(): string { return ''; }\n    private function hidden(): void {}\n}\nclass Orphan {}\nfunction start(Runner $runner): void { $runner->run(); }\nfunction orphan(): void {}\nstart(new Service());\n",
+        ),
+        (
+            "file:///project/tests/ServiceTest.php",
+            "<?php\nnamespace Tests;\nuse App\\Service;\nclass ServiceTest {\n    public function testUsed(): void { (new Service())->used(); }\n}\n",
+        ),
+    ] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, text))
+            .await
+            .unwrap();
+    }
+
+    let unused_messages = |report: &serde_json::Value| -> Vec<String> {
+        report["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|file| file["diagnostics"].as_array().unwrap().iter())
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let report = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                2,
+                "php-lsp.findUnusedSymbols",
+                Vec::new(),
+            ))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(
+        unused_messages(&report),
+        vec![
+            "Public method App\\Service::unused() is never referenced",
+            "Class App\\Orphan is never referenced",
+            "Function App\\orphan() is never referenced",
+        ]
+    );
+    assert_eq!(report["symbols"], 3);
+    let file = &report["files"][0];
+    assert_eq!(file["uri"], "file:///project/src/Service.php");
+    let diagnostic = &file["diagnostics"][0];
+    assert_eq!(diagnostic["code"], "php-lsp.unused");
+    assert_eq!(
+        diagnostic["range"]["start"],
+        json!({ "line": 6, "character": 20 })
+    );
+    assert_eq!(diagnostic["data"]["fqn"], "App\\Service::unused");
+
+    let unfiltered = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                3,
+                "php-lsp.findUnusedSymbols",
+                vec![json!({ "exclude": [] })],
+            ))
+            .await
+            .unwrap(),
+    );
+    let messages = unused_messages(&unfiltered);
+    assert!(
+        messages.contains(&"Class Tests\\ServiceTest is never referenced".to_string()),
+        "got: {messages:?}"
+    );
+    assert!(
+        messages.contains(
+            &"Public method Tests\\ServiceTest::testUsed() is never referenced".to_string()
+        ),
+        "got: {messages:?}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_composer_install_metadata_change_preloads_new_vendor_helpers() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-composer-require-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    let composer_dir = tmp_root.join("vendor/composer");
+    fs::create_dir_all(tmp_root.join("src")).unwrap();
+    fs::create_dir_all(&composer_dir).unwrap();
+    fs::write(
+        tmp_root.join("composer.json"),
+        r#"{"autoload":{"psr-4":{"App\\":"src/"}}}"#,
+    )
+    .unwrap();
+    let installed_json = composer_dir.join("installed.json");
+    fs::write(&installed_json, r#"{"packages":[]}"#).unwrap();
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(1, Some(&root_uri), None))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(2)).await;
+
+    let app_uri = format!("file://{}", tmp_root.join("src/app.php").to_string_lossy());
+    let app_code = "<?php\nnamespace App;\n\n\\Acme\\Pkg\\acme_helper();\n";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(&app_uri, app_code))
+        .await
+        .unwrap();
+    let (line, col) = utf16_position_at(app_code, "acme_helper");
+
+    let before = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(definition_request(2, &app_uri, line, col + 1))
+            .await
+            .unwrap(),
+    );
+    assert!(
+        before.is_null(),
+        "helper should not resolve before the package is installed: {before}"
+    );
+
+    // `composer require acme/pkg`: the package lands in vendor/ and
+    // installed.json gains its `files` autoload entry.
+    let helpers_path = tmp_root.join("vendor/acme/pkg/src/helpers.php");
+    fs::create_dir_all(helpers_path.parent().unwrap()).unwrap();
+    fs::write(
+        &helpers_path,
+        "<?php\nnamespace Acme\\Pkg;\n\nfunction acme_helper(): void {}\n",
+    )
+    .unwrap();
+    fs::write(
+        &installed_json,
+        r#"{"packages":[{"name":"acme/pkg","install-path":"../acme/pkg","autoload":{"files":["src/helpers.php"]}}]}"#,
+    )
+    .unwrap();
+    let installed_uri = format!("file://{}", installed_json.to_string_lossy());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_watched_files_notification(vec![(
+            &installed_uri,
+            2,
+        )]))
+        .await
+        .unwrap();
+
+    let after = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(definition_request(3, &app_uri, line, col + 1))
+            .await
+            .unwrap(),
+    );
+    assert!(
+        after["uri"]
+            .as_str()
+            .is_some_and(|uri| uri.ends_with("acme/pkg/src/helpers.php")),
+        "helper should resolve after installed.json changes: {after}"
+    );
+    assert_eq!(after["range"]["start"]["line"], 3);
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_configured_file_extensions_index_and_watch_non_php_sources() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-file-extensions-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    fs::create_dir_all(tmp_root.join("includes")).unwrap();
+    fs::write(
+        tmp_root.join("includes/legacy.inc"),
+        "<?php\nclass LegacyIncludeWidget {}\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp_root.join("includes/notes.txt"),
+        "<?php\nclass IgnoredTextWidget {}\n",
+    )
+    .unwrap();
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({ "fileExtensions": ["php", "inc", "module"] })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(2)).await;
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(workspace_symbol_request(2, "Widget"))
+        .await
+        .unwrap();
+    assert_eq!(
+        workspace_symbol_names(&extract_result(resp)),
+        vec!["LegacyIncludeWidget"]
+    );
+
+    let module_path = tmp_root.join("includes/custom.module");
+    fs::write(&module_path, "<?php\nclass CustomModuleWidget {}\n").unwrap();
+    let module_uri = format!("file://{}", module_path.to_string_lossy());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_watched_files_notification(vec![(
+            &module_uri,
+            1,
+        )]))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(workspace_symbol_request(3, "CustomModuleWidget"))
+        .await
+        .unwrap();
+    assert_eq!(
+        workspace_symbol_uris(&extract_result(resp)),
+        vec![module_uri.clone()],
+        "watched .module files should be indexed when the extension is configured"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_full_vendor_indexing_exposes_unreferenced_vendor_symbols() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-vendor-full-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    let package_src = tmp_root.join("vendor/acme/pkg/src");
+    fs::create_dir_all(tmp_root.join("src")).unwrap();
+    fs::create_dir_all(tmp_root.join("vendor/composer")).unwrap();
+    fs::create_dir_all(&package_src).unwrap();
+    fs::write(
+        tmp_root.join("composer.json"),
+        r#"{"autoload":{"psr-4":{"App\\":"src/"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        tmp_root.join("vendor/composer/installed.json"),
+        r#"{"packages":[{"name":"acme/pkg","install-path":"acme/pkg","autoload":{"psr-4":{"Acme\\Pkg\\":"src/"}}}]}"#,
+    )
+    .unwrap();
+    fs::write(
+        package_src.join("UnusedVendorWidget.php"),
+        "<?php\nnamespace Acme\\Pkg;\n\nfinal class UnusedVendorWidget {}\n",
+    )
+    .unwrap();
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({ "vendorIndexing": "full" })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let mut names = Vec::new();
+    for attempt in 0..50 {
+        let resp = service
+            .ready()
+            .await
+            .unwrap()
+            .call(workspace_symbol_request(10 + attempt, "UnusedVendorWidget"))
+            .await
+            .unwrap();
+        names = workspace_symbol_names(&extract_result(resp));
+        if !names.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(
+        names,
+        vec!["UnusedVendorWidget"],
+        "full vendor indexing should index vendor classes nothing references"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_configuration_notification(
+            json!({ "phpLsp": { "vendorIndexing": "lazy" } }),
+        ))
+        .await
+        .unwrap();
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(workspace_symbol_request(99, "UnusedVendorWidget"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(
+        workspace_symbol_names(&result).is_empty(),
+        "switching back to lazy indexing should drop eagerly indexed vendor files, got: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(100))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_workspace_file_operations_update_index_uris() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -660,3 +1593,353 @@ async fn test_workspace_folders_index_multiple_roots() {
 
     let _ = fs::remove_dir_all(&tmp_root);
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_stub_extension_enable_and_disable_settings_reload_stubs() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-stub-extension-sets-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&tmp_root);
+    let stubs_root = tmp_root.join("stubs");
+    let workspace_dir = tmp_root.join("workspace");
+    fs::create_dir_all(&workspace_dir).unwrap();
+    // The stub loader only accepts a phpstorm-stubs checkout with its core files.
+    for required in [
+        "PhpStormStubsMap.php",
+        "Core/Core.php",
+        "SPL/SPL.php",
+        "standard/basic.php",
+        "standard/standard_0.php",
+        "date/date.php",
+        "json/json.php",
+        "pcre/pcre.php",
+        "Reflection/Reflection.php",
+        "SimpleXML/SimpleXML.php",
+        "soap/soap.php",
+    ] {
+        let path = stubs_root.join(required);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "<?php\n").unwrap();
+    }
+    fs::create_dir_all(stubs_root.join("redis")).unwrap();
+    fs::write(
+        stubs_root.join("redis/Redis.php"),
+        "<?php\nclass Redis\n{\n    public function ping() {}\n}\n",
+    )
+    .unwrap();
+    let stubs_path = stubs_root.to_string_lossy().to_string();
+    let root_uri = php_lsp_types::uri::path_to_uri(&workspace_dir).unwrap();
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({
+                "stubsPath": stubs_path,
+                "stubExtensionsDisable": ["redis"]
+            })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "stubsLoaded", Duration::from_secs(5)).await;
+
+    let code = "<?php\n$client = new Redis();\n";
+    let uri = "file:///test/stub-extension-sets.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+    let position = utf16_position_at(code, "Redis()");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, uri, position.0, position.1))
+        .await
+        .unwrap();
+    assert!(
+        extract_result(resp).is_null(),
+        "disabled stub extensions should not be loaded"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_change_configuration_notification(json!({
+            "phpLsp": { "stubsPath": stubs_path, "stubExtensionsEnable": ["redis"] }
+        })))
+        .await
+        .unwrap();
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(3, uri, position.0, position.1))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(
+        result
+            .get("uri")
+            .and_then(|value| value.as_str())
+            .is_some_and(|uri| uri.starts_with("phpstub://redis/")),
+        "re-enabling a stub extension should reload stubs without a restart, got: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "current_thread")]
+async fn test_runtime_stubs_index_uncovered_extensions_from_php_binary() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    let tmp_root =
+        std::env::temp_dir().join(format!("php-lsp-runtime-stubs-e2e-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&tmp_root);
+    let workspace_dir = tmp_root.join("workspace");
+    fs::create_dir_all(&workspace_dir).unwrap();
+    // Stands in for `php -r <reflection script>` with one custom extension loaded.
+    let fake_php = tmp_root.join("fake-php");
+    fs::write(
+        &fake_php,
+        r#"#!/bin/sh
+[ "$3" = "-r" ] || exit 2
+printf '%s' '{"acme":"<?php\nnamespace {\nfunction acme_ping(string $host): bool {}\n}\n"}'
+"#,
+    )
+    .unwrap();
+    fs::set_permissions(&fake_php, fs::Permissions::from_mode(0o755)).unwrap();
+    let root_uri = php_lsp_types::uri::path_to_uri(&workspace_dir).unwrap();
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            Some(&root_uri),
+            Some(json!({
+                "stubExtensions": [],
+                "runtimeStubs": {
+                    "enabled": true,
+                    "phpExecutable": fake_php.to_string_lossy().to_string()
+                }
+            })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "stubsLoaded", Duration::from_secs(5)).await;
+
+    let code = "<?php\nacme_ping('localhost');\n";
+    let uri = "file:///test/runtime-stubs.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+    let position = utf16_position_at(code, "acme_ping");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, uri, position.0, position.1))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result.get("uri").and_then(|value| value.as_str()),
+        Some("phpstub://acme/acme.php"),
+        "runtime stubs should resolve functions from uncovered extensions, got: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&tmp_root);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_created_php_files_get_psr4_scaffold() {
+    use futures::SinkExt;
+
+    let (mut service, socket) = PhpLspBackend::service();
+    let (mut requests, mut responses) = socket.split();
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(request) = requests.next().await {
+            if let Some(id) = request.id().cloned() {
+                let result = if request.method() == "workspace/applyEdit" {
+                    json!({ "applied": true })
+                } else {
+                    serde_json::Value::Null
+                };
+                let _ = responses
+                    .send(tower_lsp::jsonrpc::Response::from_ok(id, result))
+                    .await;
+            }
+            let _ = notification_tx.send(request);
+        }
+    });
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let tmp_root = std::env::temp_dir().join(format!(
+        "php-lsp-new-file-scaffold-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    fs::create_dir_all(tmp_root.join("src/Models")).unwrap();
+    fs::create_dir_all(tmp_root.join("bin")).unwrap();
+    fs::write(
+        tmp_root.join("composer.json"),
+        r#"{"autoload":{"psr-4":{"App\\":"src/"}}}"#,
+    )
+    .unwrap();
+    let root_uri = format!("file://{}", tmp_root.to_string_lossy());
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(
+            Request::build("initialize")
+                .params(json!({
+                    "capabilities": { "workspace": { "applyEdit": true } },
+                    "rootUri": root_uri
+                }))
+                .id(1)
+                .finish(),
+        )
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+    wait_for_indexing_phase(&mut notifications, "ready", Duration::from_secs(2)).await;
+
+    let next_scaffold = |notifications: &mut UnboundedReceiver<Request>| {
+        let mut received = Vec::new();
+        while let Ok(request) = notifications.try_recv() {
+            if request.method() == "workspace/applyEdit" {
+                received.push(request.params().cloned().unwrap_or_default());
+            }
+        }
+        received
+    };
+
+    let class_path = tmp_root.join("src/Models/User.php");
+    fs::write(&class_path, "").unwrap();
+    let class_uri = format!("file://{}", class_path.to_string_lossy());
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_create_files_notification(vec![&class_uri]))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let edits = next_scaffold(&mut notifications);
+    assert_eq!(edits.len(), 1, "expected one scaffold edit, got: {edits:?}");
+    assert_eq!(
+        edits[0]["edit"]["changes"][&class_uri][0]["newText"],
+        "<?php\n\ndeclare(strict_types=1);\n\nnamespace App\\Models;\n\nclass User\n{\n}\n"
+    );
+
+    let script_path = tmp_root.join("bin/console.php");
+    fs::write(&script_path, "\n").unwrap();
+    let script_uri = format!("file://{}", script_path.to_string_lossy());
+    let result = extract_result(
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(execute_command_request(
+                2,
+                "php-lsp.newClass",
+                vec![json!(script_uri)],
+            ))
+            .await
+            .unwrap(),
+    );
+    assert_eq!(result["applied"], true);
+    let edits = next_scaffold(&mut notifications);
+    assert_eq!(
+        edits[0]["edit"]["changes"][&script_uri][0],
+        json!({
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 1, "character": 0 }
+            },
+            "newText": "<?php\n\ndeclare(strict_types=1);\n\n"
+        }),
+        "files outside PSR-4 directories only get the header"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_root);
+}
@@ -147,9 +147,18 @@ async fn test_initialize_and_shutdown() {
         "expected implemented will/did file operation capabilities, got: {}",
         file_operations
     );
-    assert!(
-        file_operations.get("willRename").is_none(),
-        "willRename should not be advertised until it returns meaningful edits, got: {}",
+    let will_rename_matches: Vec<_> = file_operations
+        .get("willRename")
+        .and_then(|options| options.get("filters"))
+        .and_then(|filters| filters.as_array())
+        .expect("expected willRename filters")
+        .iter()
+        .filter_map(|filter| filter["pattern"]["matches"].as_str())
+        .collect();
+    assert_eq!(
+        will_rename_matches,
+        vec!["file", "folder"],
+        "willRename should watch PHP files and folders, got: {}",
         file_operations
     );
     assert!(
@@ -734,3 +743,122 @@ class Demo {
         .await
         .unwrap();
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_capabilities_request_reports_effective_configuration() {
+    let (mut service, socket) = PhpLspBackend::service();
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            None,
+            Some(json!({
+                "diagnosticsMode": "syntax-only",
+                "diagnosticsSeverity": {
+                    "unused": "hint",
+                    "unresolvedUse": false
+                },
+                "psalm": { "enabled": "auto" },
+                "formatting": { "provider": "none" },
+                "stubExtensions": []
+            })),
+        ))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(capabilities_request(2))
+        .await
+        .unwrap();
+    let report = extract_result(resp);
+
+    assert_eq!(report["schemaVersion"], json!(1));
+    assert_eq!(
+        report["server"]["version"],
+        json!(env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(report["features"]["diagnostics"], json!("syntax-only"));
+    assert_eq!(report["features"]["formatting"], json!("none"));
+    assert_eq!(report["features"]["psalm"]["enabled"], json!("auto"));
+    assert_eq!(report["features"]["psalm"]["active"], json!(false));
+    assert!(
+        report["frameworkPlugins"]
+            .as_array()
+            .is_some_and(|plugins| plugins.contains(&json!("laravel.eloquent"))),
+        "expected framework providers in report, got: {}",
+        report
+    );
+    assert_eq!(
+        report["rules"]["categories"]["php-lsp.unused"],
+        json!("hint")
+    );
+    assert_eq!(
+        report["rules"]["rules"]["php-lsp.unusedImport"],
+        json!("hint")
+    );
+    assert_eq!(
+        report["rules"]["rules"]["php-lsp.unresolvedUse"],
+        json!("off")
+    );
+    assert_eq!(report["index"]["backend"], json!("in-memory"));
+    assert!(report["stubs"]["loadedFiles"].is_u64());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_index_stats_request_reports_counts_memory_and_timings() {
+    let (mut service, socket) = PhpLspBackend::service();
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_options(
+            1,
+            None,
+            Some(json!({ "stubExtensions": [] })),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(
+            "file:///project/src/Service.php",
+            "<?php\nnamespace App;\n\nclass Service\n{\n    public function run(): void {}\n}\n\nfunction helper(): void {}\n",
+        ))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(index_stats_request(2))
+        .await
+        .unwrap();
+    let stats = extract_result(resp);
+
+    assert_eq!(stats["files"]["workspace"], json!(1));
+    assert_eq!(stats["files"]["stubs"], json!(0));
+    assert_eq!(stats["symbols"]["byKind"]["class"], json!(1));
+    assert_eq!(stats["symbols"]["byKind"]["method"], json!(1));
+    assert_eq!(stats["symbols"]["byKind"]["function"], json!(1));
+    assert_eq!(stats["symbols"]["types"], json!(1));
+    assert!(stats["memory"]["internedStrings"].as_u64().unwrap() > 0);
+    assert!(stats["memory"]["estimatedTotalBytes"].as_u64().unwrap() > 0);
+    assert_eq!(stats["indexing"]["inProgress"], json!(false));
+    assert!(stats["indexing"]["workspaceMs"].is_null());
+}
@@ -0,0 +1,179 @@
+//! Position encoding negotiation.
+//!
+//! Each connection keeps the encoding it negotiated, so sessions with
+//! different column units can run side by side.
+
+mod support;
+
+use support::*;
+
+const CODE: &str = "<?php\n/* 🐘 é */ class Greeter {}\n/* 🐘 é */ $g = new Greeter();\n";
+const URI: &str = "file:///test/Encoding.php";
+
+async fn initialize_with(
+    service: &mut LspService<PhpLspBackend>,
+    capabilities: serde_json::Value,
+) -> serde_json::Value {
+    let init = service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_capabilities(1, capabilities))
+        .await
+        .unwrap();
+    let init = extract_result(init);
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(URI, CODE))
+        .await
+        .unwrap();
+
+    init["capabilities"]["positionEncoding"].clone()
+}
+
+async fn highlight_starts(
+    service: &mut LspService<PhpLspBackend>,
+    line: u32,
+    character: u32,
+) -> Vec<(u64, u64)> {
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(document_highlight_request(2, URI, line, character))
+        .await
+        .unwrap();
+    let highlights = extract_result(resp);
+    let mut starts = highlights
+        .as_array()
+        .expect("document highlights")
+        .iter()
+        .map(|highlight| {
+            let start = &highlight["range"]["start"];
+            (
+                start["line"].as_u64().unwrap(),
+                start["character"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+    starts
+}
+
+async fn shutdown(service: &mut LspService<PhpLspBackend>) {
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+fn new_service() -> LspService<PhpLspBackend> {
+    let (service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+    service
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_position_encoding_negotiation_drives_column_units() {
+    let declaration_line = CODE.lines().nth(1).unwrap();
+    let usage_line = CODE.lines().nth(2).unwrap();
+    let declaration_bytes = declaration_line.find("Greeter").unwrap();
+    let usage_bytes = usage_line.find("Greeter").unwrap();
+    let declaration_utf16 = declaration_line[..declaration_bytes].encode_utf16().count();
+    let usage_utf16 = usage_line[..usage_bytes].encode_utf16().count();
+    let declaration_chars = declaration_line[..declaration_bytes].chars().count();
+
+    let mut service = new_service();
+    let encoding = initialize_with(
+        &mut service,
+        json!({ "general": { "positionEncodings": ["utf-8", "utf-16"] } }),
+    )
+    .await;
+    let starts = highlight_starts(&mut service, 1, declaration_bytes as u32 + 1).await;
+    shutdown(&mut service).await;
+    assert_eq!(encoding, json!("utf-8"));
+    assert_eq!(
+        starts,
+        vec![(1, declaration_bytes as u64), (2, usage_bytes as u64)],
+        "utf-8 sessions should report byte columns"
+    );
+
+    let mut service = new_service();
+    let encoding = initialize_with(
+        &mut service,
+        json!({ "general": { "positionEncodings": ["utf-32"] } }),
+    )
+    .await;
+    let starts = highlight_starts(&mut service, 1, declaration_chars as u32 + 1).await;
+    shutdown(&mut service).await;
+    assert_eq!(encoding, json!("utf-32"));
+    assert_eq!(
+        starts,
+        vec![
+            (1, declaration_chars as u64),
+            (2, usage_line[..usage_bytes].chars().count() as u64)
+        ],
+        "utf-32 sessions should report code point columns"
+    );
+
+    let mut service = new_service();
+    let encoding = initialize_with(&mut service, json!({})).await;
+    let starts = highlight_starts(&mut service, 1, declaration_utf16 as u32 + 1).await;
+    shutdown(&mut service).await;
+    assert_eq!(
+        encoding,
+        json!("utf-16"),
+        "clients offering nothing get UTF-16"
+    );
+    assert_eq!(
+        starts,
+        vec![(1, declaration_utf16 as u64), (2, usage_utf16 as u64)]
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_position_encoding_is_kept_per_connection() {
+    let declaration_line = CODE.lines().nth(1).unwrap();
+    let usage_line = CODE.lines().nth(2).unwrap();
+    let declaration_bytes = declaration_line.find("Greeter").unwrap();
+    let usage_bytes = usage_line.find("Greeter").unwrap();
+    let declaration_utf16 = declaration_line[..declaration_bytes].encode_utf16().count();
+    let usage_utf16 = usage_line[..usage_bytes].encode_utf16().count();
+
+    let mut utf8 = new_service();
+    let mut utf16 = new_service();
+    initialize_with(
+        &mut utf8,
+        json!({ "general": { "positionEncodings": ["utf-8"] } }),
+    )
+    .await;
+    initialize_with(&mut utf16, json!({})).await;
+
+    assert_eq!(
+        highlight_starts(&mut utf8, 1, declaration_bytes as u32 + 1).await,
+        vec![(1, declaration_bytes as u64), (2, usage_bytes as u64)],
+        "a later UTF-16 session must not change an earlier UTF-8 one"
+    );
+    assert_eq!(
+        highlight_starts(&mut utf16, 1, declaration_utf16 as u32 + 1).await,
+        vec![(1, declaration_utf16 as u64), (2, usage_utf16 as u64)]
+    );
+
+    shutdown(&mut utf8).await;
+    shutdown(&mut utf16).await;
+}
@@ -236,6 +236,124 @@ function boot(): void {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_code_lens_and_outline_positions_list_implementations() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+interface Shape {
+    public function area(): float;
+}
+
+abstract class Polygon implements Shape {
+    abstract public function sides(): int;
+}
+
+class Square extends Polygon {
+    public function area(): float { return 1.0; }
+    public function sides(): int { return 4; }
+}
+
+class Circle implements Shape {
+    public function area(): float { return 3.14; }
+}
+"#;
+    let uri = "file:///test/Shapes.php";
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(code_lens_request(2, uri))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let lenses = result.as_array().expect("code lens array");
+    let implementation_title = |fqn: &str| {
+        lenses
+            .iter()
+            .find(|lens| {
+                lens["data"]["fqn"].as_str() == Some(fqn)
+                    && lens["data"].get("implementations").is_some()
+            })
+            .and_then(|lens| lens["command"]["title"].as_str())
+    };
+    assert_eq!(
+        implementation_title("App\\Shape"),
+        Some("2 implementations")
+    );
+    assert_eq!(
+        implementation_title("App\\Shape::area"),
+        Some("2 implementations")
+    );
+    assert_eq!(
+        implementation_title("App\\Polygon"),
+        Some("1 implementation")
+    );
+    assert_eq!(
+        implementation_title("App\\Polygon::sides"),
+        Some("1 implementation")
+    );
+    assert_eq!(
+        implementation_title("App\\Square"),
+        None,
+        "concrete classes only get a reference lens: {result}"
+    );
+
+    // Outline selections land on the declaration name.
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(implementation_request(3, uri, 8, 30))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let lines: Vec<u64> = result
+        .as_array()
+        .expect("implementations of Polygon::sides")
+        .iter()
+        .filter_map(|location| location["range"]["start"]["line"].as_u64())
+        .collect();
+    assert_eq!(lines, [13], "got: {result}");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_workspace_references_use_indexed_closed_files() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -456,6 +574,92 @@ function run(): void {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_references_stream_partial_results() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Target {}
+
+function run(): void {
+    new Target();
+    new Target();
+}
+"#;
+    let uri = "file:///test/PartialReferences.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let (line, col) = line_col(code, "class Target");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(
+            Request::build("textDocument/references")
+                .params(json!({
+                    "textDocument": { "uri": uri },
+                    "position": { "line": line, "character": col + "class ".len() as u32 + 1 },
+                    "context": { "includeDeclaration": true },
+                    "partialResultToken": "refs-1"
+                }))
+                .id(2)
+                .finish(),
+        )
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result,
+        json!([]),
+        "streamed references should answer with an empty final result"
+    );
+
+    let streamed =
+        serde_json::Value::Array(collect_partial_results(&mut notifications, "refs-1").await);
+    assert_eq!(
+        location_start_lines(&streamed),
+        BTreeSet::from([3, 6, 7]),
+        "partial results should carry every reference: {streamed}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_rename() {
     let (mut service, socket) = LspService::new(PhpLspBackend::new);
@@ -1041,7 +1245,7 @@ async fn test_cancel_request_cancels_references_request() {
         .ready()
         .await
         .unwrap()
-        .call(references_request(2, target_uri, 3, 29, true));
+        .call(references_request(2, target_uri, 2, 8, true));
     let cancel = service.ready().await.unwrap().call(cancel_request(2));
     let (references_response, cancel_response) = futures::join!(references, cancel);
 
@@ -1605,3 +1809,1315 @@ class Repo {
         .await
         .unwrap();
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_class_rename_updates_use_statements_and_qualified_names() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_capabilities(
+            1,
+            json!({
+                "workspace": {
+                    "workspaceEdit": {
+                        "documentChanges": true,
+                        "resourceOperations": ["create", "rename", "delete"]
+                    }
+                }
+            }),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let model_code = r#"<?php
+namespace App\Model;
+
+class OldName {}
+"#;
+    let consumer_code = r#"<?php
+namespace App\Service;
+
+use App\Model\OldName;
+use App\Model\OldName as Aliased;
+use App\Model\{OldName as Grouped, Other};
+
+class Consumer {
+    public function make(OldName $a, Aliased $b, Grouped $c): \App\Model\OldName {
+        return new \App\Model\OldName();
+    }
+}
+"#;
+    let model_uri = "file:///test/src/Model/OldName.php";
+    let consumer_uri = "file:///test/src/Service/Consumer.php";
+    for (uri, code) in [(model_uri, model_code), (consumer_uri, consumer_code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let (line, col) = line_col(model_code, "OldName {}");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(2, model_uri, line, col + 1, "NewName"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let operations = result["documentChanges"]
+        .as_array()
+        .unwrap_or_else(|| panic!("class rename should return document changes: {result}"));
+
+    let mut edited_texts = std::collections::HashMap::new();
+    for (uri, code) in [(model_uri, model_code), (consumer_uri, consumer_code)] {
+        let edits = operations
+            .iter()
+            .find(|op| op["textDocument"]["uri"] == uri)
+            .and_then(|op| op["edits"].as_array())
+            .unwrap_or_else(|| panic!("rename should edit {uri}: {result}"));
+        let mut lines: Vec<String> = code.lines().map(str::to_string).collect();
+        let mut edits = edits.clone();
+        edits.sort_by_key(|edit| {
+            std::cmp::Reverse((
+                edit["range"]["start"]["line"].as_u64().unwrap(),
+                edit["range"]["start"]["character"].as_u64().unwrap(),
+            ))
+        });
+        for edit in edits {
+            let line = edit["range"]["start"]["line"].as_u64().unwrap() as usize;
+            let start = edit["range"]["start"]["character"].as_u64().unwrap() as usize;
+            let end = edit["range"]["end"]["character"].as_u64().unwrap() as usize;
+            lines[line].replace_range(start..end, edit["newText"].as_str().unwrap());
+        }
+        edited_texts.insert(uri, lines.join("\n"));
+    }
+
+    assert!(edited_texts[model_uri].contains("class NewName {}"));
+    let consumer = &edited_texts[consumer_uri];
+    for expected in [
+        "use App\\Model\\NewName;",
+        "use App\\Model\\NewName as Aliased;",
+        "use App\\Model\\{NewName as Grouped, Other};",
+        "make(NewName $a, Aliased $b, Grouped $c): \\App\\Model\\NewName {",
+        "return new \\App\\Model\\NewName();",
+    ] {
+        assert!(
+            consumer.contains(expected),
+            "expected {expected:?} in renamed consumer:\n{consumer}"
+        );
+    }
+
+    let rename_op = operations
+        .last()
+        .unwrap_or_else(|| panic!("document changes should not be empty: {result}"));
+    assert_eq!(rename_op["kind"], "rename");
+    assert_eq!(rename_op["oldUri"], model_uri);
+    assert_eq!(rename_op["newUri"], "file:///test/src/Model/NewName.php");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_member_rename_annotates_string_literals_used_for_dynamic_access() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_capabilities(
+            1,
+            json!({
+                "workspace": {
+                    "workspaceEdit": {
+                        "documentChanges": true,
+                        "changeAnnotationSupport": { "groupsOnLabel": false }
+                    }
+                }
+            }),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let model_code = r#"<?php
+namespace App;
+
+class Team {
+    public array $users = [];
+
+    public function save(): void {}
+}
+"#;
+    let consumer_code = r#"<?php
+namespace App;
+
+function export(Team $team, string $field): array {
+    $team->save();
+    $field = 'users';
+    $callback = [$team, 'save'];
+    return [$team->users, $team->$field];
+}
+"#;
+    let model_uri = "file:///test/src/Team.php";
+    let consumer_uri = "file:///test/src/export.php";
+    for (uri, code) in [(model_uri, model_code), (consumer_uri, consumer_code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let annotated_edits = |result: &serde_json::Value, uri: &str| -> Vec<(u64, String)> {
+        result["documentChanges"]
+            .as_array()
+            .unwrap_or_else(|| panic!("rename should return document changes: {result}"))
+            .iter()
+            .filter(|change| change["textDocument"]["uri"] == uri)
+            .flat_map(|change| change["edits"].as_array().cloned().unwrap_or_default())
+            .filter(|edit| edit["annotationId"] == "php-lsp.dynamicAccess")
+            .map(|edit| {
+                (
+                    edit["range"]["start"]["line"].as_u64().unwrap(),
+                    edit["newText"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+
+    let (line, col) = line_col(model_code, "users = []");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(2, model_uri, line, col + 1, "members"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        annotated_edits(&result, consumer_uri),
+        [(5, "members".to_string())],
+        "property rename: {result}"
+    );
+    let annotation = &result["changeAnnotations"]["php-lsp.dynamicAccess"];
+    assert_eq!(annotation["needsConfirmation"], true, "{result}");
+    let plain_consumer_edits = result["documentChanges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|change| change["textDocument"]["uri"] == consumer_uri)
+        .flat_map(|change| change["edits"].as_array().cloned().unwrap_or_default())
+        .filter(|edit| edit.get("annotationId").is_none())
+        .count();
+    assert_eq!(plain_consumer_edits, 1, "exact property access: {result}");
+
+    let (line, col) = line_col(model_code, "save()");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(3, model_uri, line, col + 1, "persist"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        annotated_edits(&result, consumer_uri),
+        [(6, "persist".to_string())],
+        "method rename: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_class_references_include_attributes_and_string_class_names() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let mailer_code = r#"<?php
+namespace App\Service;
+
+#[\Attribute]
+class Mailer {}
+"#;
+    let config_code = r#"<?php
+namespace App;
+
+use App\Service\Mailer;
+
+#[Mailer]
+final class Config {
+    public function definitions(): array {
+        return [
+            Mailer::class => fn () => new Mailer(),
+            'mailer' => 'App\Service\Mailer',
+            'escaped' => "\\App\\Service\\Mailer",
+            'factory' => 'App\Service\MailerFactory',
+        ];
+    }
+}
+"#;
+    let mailer_uri = "file:///test/src/Service/Mailer.php";
+    let config_uri = "file:///test/src/Config.php";
+    for (uri, code) in [(mailer_uri, mailer_code), (config_uri, config_code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let (line, col) = line_col(mailer_code, "Mailer {}");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(references_request(2, mailer_uri, line, col + 1, false))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let config_lines: BTreeSet<u64> = result
+        .as_array()
+        .unwrap_or_else(|| panic!("references result should be an array: {result}"))
+        .iter()
+        .filter(|location| location["uri"] == config_uri)
+        .map(|location| location["range"]["start"]["line"].as_u64().unwrap())
+        .collect();
+    assert_eq!(config_lines, BTreeSet::from([5, 9, 10, 11]));
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(3, mailer_uri, line, col + 1, "Postman"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let edits = result["changes"][config_uri]
+        .as_array()
+        .unwrap_or_else(|| panic!("rename should edit {config_uri}: {result}"));
+    let mut lines: Vec<String> = config_code.lines().map(str::to_string).collect();
+    let mut edits = edits.clone();
+    edits.sort_by_key(|edit| {
+        std::cmp::Reverse((
+            edit["range"]["start"]["line"].as_u64().unwrap(),
+            edit["range"]["start"]["character"].as_u64().unwrap(),
+        ))
+    });
+    for edit in edits {
+        let line = edit["range"]["start"]["line"].as_u64().unwrap() as usize;
+        let start = edit["range"]["start"]["character"].as_u64().unwrap() as usize;
+        let end = edit["range"]["end"]["character"].as_u64().unwrap() as usize;
+        lines[line].replace_range(start..end, edit["newText"].as_str().unwrap());
+    }
+    let config = lines.join("\n");
+    for expected in [
+        "use App\\Service\\Postman;",
+        "#[Postman]",
+        "Postman::class => fn () => new Postman(),",
+        "'mailer' => 'App\\Service\\Postman',",
+        "'escaped' => \"\\\\App\\\\Service\\\\Postman\",",
+        "'factory' => 'App\\Service\\MailerFactory',",
+    ] {
+        assert!(
+            config.contains(expected),
+            "expected {expected:?} in renamed config:\n{config}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_attribute_class_rename_updates_all_attribute_positions() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let route_code = r#"<?php
+namespace App\Routing;
+
+#[\Attribute]
+class Route {
+    public function __construct(string $path) {}
+}
+"#;
+    let controller_code = r#"<?php
+namespace App\Controller;
+
+use App\Routing\Route;
+use App\Routing as Routing;
+
+#[Route('/users')]
+final class UserController {
+    public function show(#[Route('/param')] int $id): void {}
+
+    #[Routing\Route('/list'), Route('/other')]
+    public function list(): void {}
+
+    #[\App\Routing\Route('/fq')]
+    public const X = 1;
+}
+"#;
+    let route_uri = "file:///test/src/Routing/Route.php";
+    let controller_uri = "file:///test/src/Controller/UserController.php";
+    for (uri, code) in [(route_uri, route_code), (controller_uri, controller_code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let (line, col) = line_col(controller_code, "Route('/param')");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(2, controller_uri, line, col + 1))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        result["uri"], route_uri,
+        "parameter attribute definition: {result}"
+    );
+    assert_eq!(result["range"]["start"]["line"], 4);
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(3, controller_uri, line, col + 1, "Path"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let edits = result["changes"][controller_uri]
+        .as_array()
+        .unwrap_or_else(|| panic!("rename should edit {controller_uri}: {result}"));
+    let mut lines: Vec<String> = controller_code.lines().map(str::to_string).collect();
+    let mut edits = edits.clone();
+    edits.sort_by_key(|edit| {
+        std::cmp::Reverse((
+            edit["range"]["start"]["line"].as_u64().unwrap(),
+            edit["range"]["start"]["character"].as_u64().unwrap(),
+        ))
+    });
+    for edit in edits {
+        let line = edit["range"]["start"]["line"].as_u64().unwrap() as usize;
+        let start = edit["range"]["start"]["character"].as_u64().unwrap() as usize;
+        let end = edit["range"]["end"]["character"].as_u64().unwrap() as usize;
+        lines[line].replace_range(start..end, edit["newText"].as_str().unwrap());
+    }
+    let controller = lines.join("\n");
+    for expected in [
+        "use App\\Routing\\Path;",
+        "#[Path('/users')]",
+        "public function show(#[Path('/param')] int $id)",
+        "#[Routing\\Path('/list'), Path('/other')]",
+        "#[\\App\\Routing\\Path('/fq')]",
+    ] {
+        assert!(
+            controller.contains(expected),
+            "expected {expected:?} in renamed controller:\n{controller}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_constructor_references_include_new_expressions() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Base {
+    public function __construct(int $x) {}
+}
+class Child extends Base {}
+class Plain {}
+
+function make(Base $base): Plain {
+    $a = new Base(1);
+    $b = new Child(2);
+    return new Plain();
+}
+"#;
+    let uri = "file:///test/constructors.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let (line, col) = line_col(code, "__construct");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(references_request(2, uri, line, col + 2, true))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        location_start_lines(&result),
+        BTreeSet::from([4, 10, 11]),
+        "constructor references should list its declaration and new sites: {result}"
+    );
+
+    let (line, col) = line_col(code, "Plain()");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(references_request(3, uri, line, col + 1, true))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        location_start_lines(&result),
+        BTreeSet::from([7, 9, 12]),
+        "new on a class without a constructor should list class usages: {result}"
+    );
+
+    let (line, col) = line_col(code, "__construct");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(4, uri, line, col + 2, "build"))
+        .await
+        .unwrap();
+    assert!(
+        resp.as_ref().and_then(|resp| resp.error()).is_some(),
+        "constructor rename should be rejected: {resp:?}"
+    );
+
+    let (line, col) = line_col(code, "Base(1)");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(5, uri, line, col + 1, "Root"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        workspace_edit_start_lines(&result, uri),
+        BTreeSet::from([3, 6, 9, 10]),
+        "rename on new should rename the class: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_references_with_kinds_distinguish_instantiations_from_type_hints() {
+    let (mut service, socket) = PhpLspBackend::service();
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Mailer {}
+
+final class Factory {
+    private ?Mailer $mailer = null;
+
+    public function make(): Mailer {
+        $class = Mailer::class;
+        return new Mailer();
+    }
+}
+"#;
+    let uri = "file:///test/kinds.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let (line, col) = line_col(code, "Mailer {}");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(references_with_kinds_request(2, uri, line, col + 1, true))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let kinds: Vec<(u64, &str)> = result
+        .as_array()
+        .unwrap_or_else(|| panic!("php-lsp/references should return an array: {result}"))
+        .iter()
+        .map(|location| {
+            (
+                location["range"]["start"]["line"].as_u64().unwrap(),
+                location["kind"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (3, "declaration"),
+            (6, "typeHint"),
+            (8, "typeHint"),
+            (9, "reference"),
+            (10, "instantiation"),
+        ]
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_method_rename_covers_inheritance_hierarchy() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let contracts_code = r#"<?php
+namespace App;
+
+interface Handler {
+    public function handle(): void;
+}
+
+abstract class Base implements Handler {
+    public function handle(): void {}
+}
+"#;
+    let code = r#"<?php
+namespace App;
+
+class Child extends Base {
+    public function handle(): void {
+        parent::handle();
+    }
+}
+
+class Unrelated {
+    public function handle(): void {}
+}
+
+function run(Handler $handler, Child $child, Unrelated $unrelated): void {
+    $handler->handle();
+    $child->handle();
+    $unrelated->handle();
+}
+"#;
+    let contracts_uri = "file:///test/Contracts.php";
+    let uri = "file:///test/HierarchyRename.php";
+    for (file_uri, file_code) in [(contracts_uri, contracts_code), (uri, code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(file_uri, file_code))
+            .await
+            .unwrap();
+    }
+
+    // Renaming the override must reach the parent and interface declarations,
+    // and renaming the interface must reach the overrides.
+    let (child_line, child_col) = line_col(code, "handle(): void {");
+    let (iface_line, iface_col) = line_col(contracts_code, "handle(): void;");
+    for (id, (rename_uri, line, col)) in [
+        (uri, child_line, child_col),
+        (contracts_uri, iface_line, iface_col),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let rename = service
+            .ready()
+            .await
+            .unwrap()
+            .call(rename_request(
+                id as i64 + 2,
+                rename_uri,
+                line,
+                col + 1,
+                "process",
+            ))
+            .await
+            .unwrap();
+        let result = extract_result(rename);
+
+        assert_eq!(
+            workspace_edit_start_lines(&result, contracts_uri),
+            BTreeSet::from([
+                line_col(contracts_code, "handle(): void;").0 as u64,
+                line_col(contracts_code, "handle(): void {}").0 as u64,
+            ]),
+            "rename should update interface and parent declarations: {result}"
+        );
+        assert_eq!(
+            workspace_edit_start_lines(&result, uri),
+            BTreeSet::from([
+                line_col(code, "handle(): void {").0 as u64,
+                line_col(code, "parent::handle();").0 as u64,
+                line_col(code, "$handler->handle();").0 as u64,
+                line_col(code, "$child->handle();").0 as u64,
+            ]),
+            "rename should not touch Unrelated::handle: {result}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_prepare_rename_returns_identifier_placeholder() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App\Model;
+
+class Basket {
+    public array $items = [];
+    public function count(): int {
+        return \count($this->items);
+    }
+}
+
+function fill(\App\Model\Basket $basket): void {}
+"#;
+    let uri = "file:///test/PrepareRenamePlaceholder.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let cases = [
+        ("$items = []", 1, "items"),
+        ("items);", 1, "items"),
+        ("Basket {", 1, "Basket"),
+        ("Basket $basket", 1, "Basket"),
+        ("$basket", 1, "$basket"),
+    ];
+    for (id, (needle, offset, expected)) in cases.into_iter().enumerate() {
+        let (line, col) = line_col(code, needle);
+        let response = service
+            .ready()
+            .await
+            .unwrap()
+            .call(prepare_rename_request(
+                id as i64 + 2,
+                uri,
+                line,
+                col + offset,
+            ))
+            .await
+            .unwrap();
+        let result = extract_result(response);
+        assert_eq!(
+            result["placeholder"], expected,
+            "prepareRename placeholder at {needle:?}: {result}"
+        );
+        let start = result["range"]["start"]["character"].as_u64().unwrap() as usize;
+        let end = result["range"]["end"]["character"].as_u64().unwrap() as usize;
+        let line_text = code.lines().nth(line as usize).unwrap();
+        assert!(
+            line_text[start..end].ends_with(expected.trim_start_matches('$')),
+            "prepareRename range at {needle:?} should cover the identifier: {result}"
+        );
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_prepare_rename_uses_default_behavior_for_plain_identifiers() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request_with_capabilities(
+            1,
+            json!({
+                "textDocument": {
+                    "rename": {
+                        "prepareSupport": true,
+                        "prepareSupportDefaultBehavior": 1
+                    }
+                }
+            }),
+        ))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+class Basket {
+    public array $items = [];
+}
+
+function fill(\App\Basket $basket): void {}
+"#;
+    let uri = "file:///test/PrepareRenameDefaultBehavior.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    // Plain identifiers, including the last segment of a qualified name, are
+    // left to the client; a property declaration keeps its placeholder.
+    let cases = [
+        ("Basket {", json!({ "defaultBehavior": true })),
+        ("Basket $basket", json!({ "defaultBehavior": true })),
+        ("$items", json!("items")),
+    ];
+    for (id, (needle, expected)) in cases.into_iter().enumerate() {
+        let (line, col) = line_col(code, needle);
+        let response = service
+            .ready()
+            .await
+            .unwrap()
+            .call(prepare_rename_request(id as i64 + 2, uri, line, col + 1))
+            .await
+            .unwrap();
+        let result = extract_result(response);
+        let actual = if expected.is_string() {
+            result["placeholder"].clone()
+        } else {
+            result.clone()
+        };
+        assert_eq!(actual, expected, "prepareRename at {needle:?}: {result}");
+    }
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_interface_method_references_include_implementations_and_interface_calls() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let contract_code = r#"<?php
+namespace App\Repository;
+
+interface ReadRepository {
+    public function find(int $id): ?object;
+}
+
+interface UserRepositoryInterface extends ReadRepository {}
+"#;
+    let code = r#"<?php
+namespace App\Repository;
+
+trait FindsRecords {
+    public function find(int $id): ?object { return null; }
+}
+
+final class TraitUserRepository implements UserRepositoryInterface {
+    use FindsRecords;
+}
+
+final class DbUserRepository implements UserRepositoryInterface {
+    public function find(int $id): ?object { return null; }
+}
+
+final class AuditLog {
+    public function find(int $id): ?object { return null; }
+}
+
+final class UserService {
+    public function __construct(
+        private UserRepositoryInterface $users,
+        private AuditLog $log,
+    ) {}
+
+    public function show(DbUserRepository $db): void {
+        $this->users->find(1);
+        $db->find(2);
+        $this->log->find(3);
+    }
+}
+"#;
+    let contract_uri = "file:///test/Repository/ReadRepository.php";
+    let uri = "file:///test/Repository/UserService.php";
+    for (file_uri, file_code) in [(contract_uri, contract_code), (uri, code)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(file_uri, file_code))
+            .await
+            .unwrap();
+    }
+
+    let (line, col) = line_col(contract_code, "find(int $id)");
+    let refs = service
+        .ready()
+        .await
+        .unwrap()
+        .call(references_request(2, contract_uri, line, col + 1, true))
+        .await
+        .unwrap();
+    let result = extract_result(refs);
+    let locations = result
+        .as_array()
+        .unwrap_or_else(|| panic!("interface method should have references: {result}"));
+    let lines_in = |file_uri: &str| -> BTreeSet<u64> {
+        locations
+            .iter()
+            .filter(|location| location["uri"] == file_uri)
+            .map(|location| location["range"]["start"]["line"].as_u64().unwrap())
+            .collect()
+    };
+
+    assert_eq!(lines_in(contract_uri), BTreeSet::from([line as u64]));
+    let trait_line = code
+        .lines()
+        .position(|text| text.contains("find(int $id)"))
+        .unwrap() as u64;
+    let db_line = code
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| text.contains("find(int $id)"))
+        .nth(1)
+        .unwrap()
+        .0 as u64;
+    assert_eq!(
+        lines_in(uri),
+        BTreeSet::from([
+            trait_line,
+            db_line,
+            line_col(code, "$this->users->find(1);").0 as u64,
+            line_col(code, "$db->find(2);").0 as u64,
+        ]),
+        "interface method references should include implementations and interface calls only: {result}"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_promoted_property_rename_updates_constructor_parameter_usages() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let code = r#"<?php
+namespace App;
+
+final class Service {
+    public function __construct(
+        private readonly Logger $logger,
+        public int $retries = 3,
+    ) {
+        $logger->boot();
+    }
+
+    public function run(): void {
+        $this->logger->info('run');
+    }
+}
+"#;
+    let uri = "file:///test/src/Service.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(document_symbol_request(2, uri))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let symbols = result.to_string();
+    for name in ["\"logger\"", "\"retries\""] {
+        assert!(
+            symbols.contains(name),
+            "promoted property {name} should be a document symbol: {result}"
+        );
+    }
+
+    let (line, col) = line_col(code, "logger->info");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(3, uri, line, col + 1, "log"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        workspace_edit_start_lines(&result, uri),
+        BTreeSet::from([5, 8, 12]),
+        "promoted property rename: {result}"
+    );
+    let edits = result["changes"][uri].as_array().unwrap();
+    let body_edit = edits
+        .iter()
+        .find(|edit| edit["range"]["start"]["line"] == 8)
+        .unwrap();
+    assert_eq!(body_edit["newText"], "$log");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_references_and_rename_work_across_untitled_and_remote_documents() {
+    let (mut service, socket) = LspService::new(PhpLspBackend::new);
+    tokio::spawn(async move {
+        socket.collect::<Vec<_>>().await;
+    });
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let scratch_uri = "untitled:Untitled-1";
+    let scratch = "<?php\nclass ScratchMailer { public function send(): void {} }\n";
+    let remote_uri = "vscode-vfs://github/acme/app/src/Notify.php";
+    let remote = "<?php\n(new ScratchMailer())->send();\n";
+    for (uri, code) in [(scratch_uri, scratch), (remote_uri, remote)] {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(did_open_notification(uri, code))
+            .await
+            .unwrap();
+    }
+
+    let (line, col) = line_col(scratch, "ScratchMailer");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(references_request(2, scratch_uri, line, col + 1, true))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    let uris: BTreeSet<&str> = result
+        .as_array()
+        .unwrap_or_else(|| panic!("references should be an array: {result}"))
+        .iter()
+        .filter_map(|location| location["uri"].as_str())
+        .collect();
+    assert_eq!(uris, BTreeSet::from([scratch_uri, remote_uri]));
+
+    let (line, col) = line_col(scratch, "send");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(rename_request(3, scratch_uri, line, col + 1, "deliver"))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert_eq!(
+        workspace_edit_start_lines(&result, scratch_uri),
+        BTreeSet::from([1])
+    );
+    assert_eq!(
+        workspace_edit_start_lines(&result, remote_uri),
+        BTreeSet::from([1])
+    );
+
+    // A closed untitled buffer has no file behind it, so its class goes away.
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_close_notification(scratch_uri))
+        .await
+        .unwrap();
+    let (line, col) = line_col(remote, "ScratchMailer");
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(definition_request(4, remote_uri, line, col + 1))
+        .await
+        .unwrap();
+    let result = extract_result(resp);
+    assert!(result.is_null(), "closed buffer still resolves: {result}");
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
@@ -514,6 +514,83 @@ class UserService {
         .unwrap();
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn test_workspace_symbols_stream_partial_results() {
+    let (mut service, mut socket) = LspService::new(PhpLspBackend::new);
+    let (notification_tx, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(notification) = socket.next().await {
+            let _ = notification_tx.send(notification);
+        }
+    });
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialize_request(1))
+        .await
+        .unwrap();
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(initialized_notification())
+        .await
+        .unwrap();
+
+    let mut code = String::from("<?php\nnamespace App;\n\n");
+    for index in 0..60 {
+        code.push_str(&format!("class StreamedSymbol{index} {{}}\n"));
+    }
+    let uri = "file:///test/StreamedSymbols.php";
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(did_open_notification(uri, &code))
+        .await
+        .unwrap();
+
+    let resp = service
+        .ready()
+        .await
+        .unwrap()
+        .call(
+            Request::build("workspace/symbol")
+                .params(json!({ "query": "StreamedSymbol", "partialResultToken": 7 }))
+                .id(2)
+                .finish(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(extract_result(resp), json!([]));
+
+    let mut chunks = Vec::new();
+    while let Ok(Some(notification)) =
+        tokio::time::timeout(Duration::from_millis(200), notifications.recv()).await
+    {
+        if notification.method() == "$/progress" {
+            let params = notification.params().cloned().unwrap_or_default();
+            assert_eq!(params["token"], json!(7));
+            chunks.push(params["value"].as_array().cloned().unwrap_or_default());
+        }
+    }
+    assert_eq!(
+        chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![50, 10],
+        "workspace symbols should stream in chunks"
+    );
+
+    service
+        .ready()
+        .await
+        .unwrap()
+        .call(shutdown_request(99))
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn test_semantic_tokens_full_returns_php_token_types() {
     const TOKEN_CLASS: u64 = 2;
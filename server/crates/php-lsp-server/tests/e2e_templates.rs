@@ -507,8 +507,7 @@ final class DashboardController
     );
     assert!(
         accessor_hover_text.contains(&format!(
-            "**Source:** [`{}:8`](<{}#L8>)",
-            user_path.display(),
+            "**Source:** [`src/Entity/User.php:8`](<{}#L8>)",
             user_uri
         )),
         "expected Twig property accessor hover to expose clickable source metadata, got: {}",
@@ -23,6 +23,16 @@ pub fn initialize_request(id: i64) -> Request {
         .finish()
 }
 
+pub fn initialize_request_with_capabilities(id: i64, capabilities: serde_json::Value) -> Request {
+    Request::build("initialize")
+        .params(json!({
+            "capabilities": capabilities,
+            "rootUri": null
+        }))
+        .id(id)
+        .finish()
+}
+
 pub fn initialize_request_with_options(
     id: i64,
     root_uri: Option<&str>,
@@ -168,6 +178,30 @@ pub async fn wait_for_indexing_phase(
     }
 }
 
+/// Items reported through `$/progress` for `token`, in arrival order. Stops
+/// once no notification arrives for a short while.
+pub async fn collect_partial_results(
+    notifications: &mut UnboundedReceiver<Request>,
+    token: &str,
+) -> Vec<serde_json::Value> {
+    let mut items = Vec::new();
+    while let Ok(Some(notification)) =
+        tokio::time::timeout(Duration::from_millis(200), notifications.recv()).await
+    {
+        if notification.method() != "$/progress" {
+            continue;
+        }
+        let Some(params) = notification.params() else {
+            continue;
+        };
+        if params["token"].as_str() != Some(token) {
+            continue;
+        }
+        items.extend(params["value"].as_array().cloned().unwrap_or_default());
+    }
+    items
+}
+
 pub fn shutdown_request(id: i64) -> Request {
     Request::build("shutdown").id(id).finish()
 }
@@ -199,6 +233,16 @@ pub fn did_close_notification(uri: &str) -> Request {
         .finish()
 }
 
+pub fn did_save_notification(uri: &str) -> Request {
+    Request::build("textDocument/didSave")
+        .params(json!({
+            "textDocument": {
+                "uri": uri
+            }
+        }))
+        .finish()
+}
+
 pub fn did_change_full_notification(uri: &str, version: i32, text: &str) -> Request {
     Request::build("textDocument/didChange")
         .params(json!({
@@ -287,6 +331,28 @@ pub fn utf16_position_for_offset(source: &str, offset: usize) -> (u32, u32) {
     (line, character)
 }
 
+pub fn capabilities_request(id: i64) -> Request {
+    Request::build("php-lsp/capabilities").id(id).finish()
+}
+
+pub fn execute_command_request(
+    id: i64,
+    command: &str,
+    arguments: Vec<serde_json::Value>,
+) -> Request {
+    Request::build("workspace/executeCommand")
+        .id(id)
+        .params(json!({
+            "command": command,
+            "arguments": arguments
+        }))
+        .finish()
+}
+
+pub fn index_stats_request(id: i64) -> Request {
+    Request::build("php-lsp/indexStats").id(id).finish()
+}
+
 pub fn hover_request(id: i64, uri: &str, line: u32, character: u32) -> Request {
     Request::build("textDocument/hover")
         .params(json!({
@@ -706,6 +772,23 @@ pub fn references_request(
         .finish()
 }
 
+pub fn references_with_kinds_request(
+    id: i64,
+    uri: &str,
+    line: u32,
+    character: u32,
+    include_declaration: bool,
+) -> Request {
+    Request::build("php-lsp/references")
+        .params(json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+            "context": { "includeDeclaration": include_declaration }
+        }))
+        .id(id)
+        .finish()
+}
+
 pub fn lsp_cases_fixture_root() -> std::path::PathBuf {
     std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("../../../test-fixtures/lsp-cases")
@@ -914,3 +997,38 @@ pub fn apply_semantic_token_delta(
 
     data
 }
+
+pub fn phar_content_request(id: i64, uri: &str) -> Request {
+    Request::build("php-lsp/pharContent")
+        .params(json!({ "uri": uri }))
+        .id(id)
+        .finish()
+}
+
+/// Builds an uncompressed PHAR archive holding `entries` as (name, source).
+pub fn uncompressed_phar(entries: &[(&str, &str)]) -> Vec<u8> {
+    let mut manifest = Vec::new();
+    manifest.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    manifest.extend_from_slice(&0x1100u16.to_be_bytes());
+    manifest.extend_from_slice(&0x0001_0000u32.to_le_bytes());
+    manifest.extend_from_slice(&0u32.to_le_bytes()); // alias
+    manifest.extend_from_slice(&0u32.to_le_bytes()); // metadata
+    for (name, source) in entries {
+        manifest.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        manifest.extend_from_slice(name.as_bytes());
+        manifest.extend_from_slice(&(source.len() as u32).to_le_bytes());
+        manifest.extend_from_slice(&0u32.to_le_bytes()); // timestamp
+        manifest.extend_from_slice(&(source.len() as u32).to_le_bytes());
+        manifest.extend_from_slice(&0u32.to_le_bytes()); // crc32
+        manifest.extend_from_slice(&0o644u32.to_le_bytes());
+        manifest.extend_from_slice(&0u32.to_le_bytes()); // metadata
+    }
+
+    let mut archive = b"<?php __HALT_COMPILER(); ?>\r\n".to_vec();
+    archive.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&manifest);
+    for (_, source) in entries {
+        archive.extend_from_slice(source.as_bytes());
+    }
+    archive
+}
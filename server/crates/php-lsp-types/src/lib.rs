@@ -5,8 +5,11 @@
 
 use serde::{Deserialize, Serialize};
 
+pub mod shared_str;
 pub mod uri;
 
+pub use shared_str::SharedStr;
+
 /// Kind of a PHP symbol.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PhpSymbolKind {
@@ -234,6 +237,9 @@ pub struct ParamInfo {
     pub is_variadic: bool,
     pub is_by_ref: bool,
     pub is_promoted: bool,
+    /// Attribute groups on the parameter (`#[SensitiveParameter]`).
+    #[serde(default)]
+    pub attributes: Vec<SymbolAttribute>,
 }
 
 /// Function/method signature.
@@ -241,6 +247,10 @@ pub struct ParamInfo {
 pub struct Signature {
     pub params: Vec<ParamInfo>,
     pub return_type: Option<TypeInfo>,
+    /// Exception class FQNs from `@throws` tags and `throw new` statements in
+    /// the body that no surrounding `catch` handles.
+    #[serde(default)]
+    pub throws: Vec<String>,
 }
 
 /// Variance declared for a PHPDoc template parameter.
@@ -278,6 +288,27 @@ pub struct TemplateBinding {
     pub args: Vec<TypeInfo>,
 }
 
+/// A conflict-resolution rule from a trait `use A, B { ... }` block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TraitAdaptation {
+    /// Trait named by the rule (`A` in `A::hello`); `None` for a bare `hello`.
+    pub trait_fqn: Option<String>,
+    /// Method the rule applies to, as written in the trait.
+    pub method: String,
+    pub kind: TraitAdaptationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TraitAdaptationKind {
+    /// `A::hello insteadof B, C`: the method is not taken from these traits.
+    InsteadOf { excluded: Vec<String> },
+    /// `hello as [visibility] [alias]`: an alias, a visibility change, or both.
+    Alias {
+        alias: Option<String>,
+        visibility: Option<Visibility>,
+    },
+}
+
 /// A PHPStan/Psalm local type alias declared by `@phpstan-type` or `@psalm-type`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PhpDocTypeAlias {
@@ -301,11 +332,26 @@ pub struct PhpDocTypeAliasImport {
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PhpDoc {
     pub summary: Option<String>,
+    /// Free-text paragraphs after the summary, separated by blank lines.
+    #[serde(default)]
+    pub description: Option<String>,
     pub params: Vec<PhpDocParam>,
     pub return_type: Option<TypeInfo>,
+    /// Text following the `@return` type.
+    #[serde(default)]
+    pub return_description: Option<String>,
     pub var_type: Option<TypeInfo>,
     pub throws: Vec<TypeInfo>,
     pub deprecated: Option<String>,
+    /// `@since` version text.
+    #[serde(default)]
+    pub since: Option<String>,
+    /// `@see` references (FQSENs or URLs), as written.
+    #[serde(default)]
+    pub see: Vec<String>,
+    /// `@link` URLs, as written (optionally followed by link text).
+    #[serde(default)]
+    pub links: Vec<String>,
     pub properties: Vec<PhpDocProperty>,
     pub methods: Vec<PhpDocMethod>,
     pub templates: Vec<TemplateParam>,
@@ -372,6 +418,18 @@ pub struct SymbolAttribute {
     pub text: String,
     /// Source byte-column range (start line, start col, end line, end col).
     pub range: (u32, u32, u32, u32),
+    /// Resolved FQNs of the attribute classes in the group, without a
+    /// leading backslash, for example `Doctrine\ORM\Mapping\Entity`.
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+impl SymbolAttribute {
+    /// Whether one of the attributes in this group resolves to `fqn`.
+    pub fn has_name(&self, fqn: &str) -> bool {
+        let fqn = fqn.trim_start_matches('\\');
+        self.names.iter().any(|name| name.eq_ignore_ascii_case(fqn))
+    }
 }
 
 /// Full information about a symbol in the index.
@@ -383,11 +441,11 @@ pub struct SymbolInfo {
     /// Short name (e.g. "Foo", "bar", "BAZ")
     pub name: String,
     /// Fully Qualified Name (e.g. "App\\Service\\Foo")
-    pub fqn: String,
+    pub fqn: SharedStr,
     /// Kind of symbol
     pub kind: PhpSymbolKind,
     /// File URI
-    pub uri: String,
+    pub uri: SharedStr,
     /// Byte-column range in the file (start line, start col, end line, end col).
     pub range: (u32, u32, u32, u32),
     /// Byte-column selection range for the name part.
@@ -403,8 +461,12 @@ pub struct SymbolInfo {
     pub doc_comment: Option<String>,
     /// Parsed signature (for functions/methods)
     pub signature: Option<Signature>,
+    /// Initializer source text for constants and backed enum cases
+    /// (e.g. `100`, `'red'`).
+    #[serde(default)]
+    pub value: Option<String>,
     /// Parent FQN (for methods/properties → class FQN)
-    pub parent_fqn: Option<String>,
+    pub parent_fqn: Option<SharedStr>,
     /// Extended class/interface FQNs (for class-like symbols)
     #[serde(default)]
     pub extends: Vec<String>,
@@ -420,6 +482,9 @@ pub struct SymbolInfo {
     /// PHPDoc generic bindings declared on this class-like symbol.
     #[serde(default)]
     pub template_bindings: Vec<TemplateBinding>,
+    /// `insteadof`/`as` rules from the trait `use` blocks of a class-like body.
+    #[serde(default)]
+    pub trait_adaptations: Vec<TraitAdaptation>,
 }
 
 impl SymbolInfo {
@@ -497,6 +562,17 @@ impl SymbolReferenceReceiver {
     }
 }
 
+/// How a class or constructor occurrence is used at its site.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SymbolReferenceUsage {
+    #[default]
+    Other,
+    /// `new ClassName(...)`.
+    Instantiation,
+    /// Parameter, return, or property type declaration.
+    TypeHint,
+}
+
 /// A precomputed symbol occurrence used by references/rename/code lens.
 ///
 /// Unlike `SymbolInfo` ranges, `range` is already an LSP UTF-16 range because
@@ -513,6 +589,11 @@ pub struct SymbolReference {
     /// Receiver resolution state for member references.
     #[serde(default)]
     pub receiver: SymbolReferenceReceiver,
+    /// True for class names spelled as string literals (`'App\Foo'`).
+    #[serde(default)]
+    pub is_string_literal: bool,
+    #[serde(default)]
+    pub usage: SymbolReferenceUsage,
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 use url::Url;
 
@@ -48,13 +50,58 @@ pub fn path_to_uri(path: &Path) -> Result<String, FileUriError> {
 }
 
 pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
-    let url = Url::parse(uri).ok()?;
+    let url = Url::parse(&normalize_file_uri(uri)).ok()?;
     if url.scheme() != "file" {
         return None;
     }
     url.to_file_path().ok()
 }
 
+/// Rewrite a client-supplied `file://` URI into the spelling [`path_to_uri`]
+/// produces, so open documents and indexed files share one map key.
+///
+/// Clients differ from the `url` crate in what they percent-encode: VS Code
+/// sends `file:///c%3A/Users/...` for `C:\Users\...` and escapes characters
+/// such as `+` or `@` that [`path_to_uri`] leaves alone. The path is decoded
+/// and re-encoded, Windows drive letters are uppercased with a literal `:`,
+/// and a `localhost` host is dropped. Non-file URIs are returned unchanged.
+pub fn normalize_file_uri(uri: &str) -> Cow<'_, str> {
+    let Ok(mut url) = Url::parse(uri) else {
+        return Cow::Borrowed(uri);
+    };
+    if url.scheme() != "file" {
+        return Cow::Borrowed(uri);
+    }
+
+    if url.host_str() == Some("localhost") && url.set_host(None).is_err() {
+        return Cow::Borrowed(uri);
+    }
+    let mut path = percent_decode_str(url.path())
+        .decode_utf8_lossy()
+        .replace('%', "%25");
+    if is_windows_drive_path(&path) {
+        path[1..2].make_ascii_uppercase();
+    }
+    url.set_path(&path);
+
+    let normalized = url.to_string();
+    if normalized == uri {
+        Cow::Borrowed(uri)
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+/// Whether a URI path starts with a drive letter, e.g. `/c:` or `/C:/...`.
+fn is_windows_drive_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0] == b'/'
+        && bytes[1].is_ascii_alphabetic()
+        && bytes[2] == b':'
+        && bytes.get(3).is_none_or(|byte| *byte == b'/')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,6 +132,45 @@ mod tests {
         assert_eq!(decoded.file_name(), path.file_name());
     }
 
+    #[test]
+    fn normalize_matches_path_to_uri_for_client_encodings() {
+        let path = PathBuf::from("/tmp/php lsp/Foo #1%/a+b@c/Привет.php");
+        let canonical = path_to_uri(&path).unwrap();
+
+        assert_eq!(normalize_file_uri(&canonical), canonical);
+        assert!(matches!(normalize_file_uri(&canonical), Cow::Borrowed(_)));
+        assert_eq!(
+            normalize_file_uri(
+                "file:///tmp/php%20lsp/Foo%20%231%25/a%2Bb%40c/%d0%9f%d1%80%d0%b8%d0%b2%d0%b5%d1%82.php"
+            ),
+            canonical
+        );
+        assert_eq!(
+            normalize_file_uri("file://localhost/tmp/php%20lsp/Foo%20%231%25/a+b@c/Привет.php"),
+            canonical
+        );
+    }
+
+    #[test]
+    fn normalize_spells_windows_drives_like_url_from_file_path() {
+        assert_eq!(
+            normalize_file_uri("file:///c%3A/Users/php%20lsp/Foo.php"),
+            "file:///C:/Users/php%20lsp/Foo.php"
+        );
+        assert_eq!(
+            normalize_file_uri("file:///d:/src/Bar.php"),
+            "file:///D:/src/Bar.php"
+        );
+        assert_eq!(
+            normalize_file_uri("file://server/share/php%20lsp/Baz.php"),
+            "file://server/share/php%20lsp/Baz.php"
+        );
+        assert_eq!(
+            normalize_file_uri("untitled:Untitled-1"),
+            "untitled:Untitled-1"
+        );
+    }
+
     #[cfg(windows)]
     #[test]
     fn windows_drive_path_round_trips() {
@@ -93,6 +179,20 @@ mod tests {
 
         assert!(uri.starts_with("file:///C:/Users/php%20lsp/"));
         assert!(uri.ends_with("Foo%20%231%25.php"));
+        assert_eq!(uri_to_path(&uri), Some(path.clone()));
+        assert_eq!(
+            uri_to_path("file:///c%3A/Users/php%20lsp/Foo%20%231%25.php"),
+            Some(path)
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unc_path_round_trips() {
+        let path = PathBuf::from(r"\\server\share\php lsp\Foo.php");
+        let uri = path_to_uri(&path).unwrap();
+
+        assert_eq!(uri, "file://server/share/php%20lsp/Foo.php");
         assert_eq!(uri_to_path(&uri), Some(path));
     }
 }